@@ -11,6 +11,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: HD_WIDTH,
     h: HD_HEIGHT,
     banks: 24,
+    ..SketchConfig::DEFAULT
 };
 
 pub fn init() -> FullscreenShaderSketch {
@@ -13,6 +13,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: HD_WIDTH,
     h: HD_HEIGHT,
     banks: 12,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct BlobSketch {
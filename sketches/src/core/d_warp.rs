@@ -9,6 +9,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: 700,
     h: 700,
     banks: 7,
+    ..SketchConfig::DEFAULT
 };
 
 pub fn init() -> FullscreenShaderSketch {
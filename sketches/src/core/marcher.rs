@@ -9,6 +9,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: 800,
     h: 800,
     banks: 8,
+    ..SketchConfig::DEFAULT
 };
 
 pub fn init() -> FullscreenShaderSketch {
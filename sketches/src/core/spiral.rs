@@ -18,6 +18,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: HD_WIDTH,
     h: HD_HEIGHT,
     banks: 12,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct SpiralSketch {
@@ -15,6 +15,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: HD_WIDTH,
     h: HD_HEIGHT,
     banks: 24,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct WaveFractSketch {
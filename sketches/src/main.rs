@@ -11,19 +11,19 @@ use drafts::*;
 use templates::*;
 
 fn main() {
-    let registry = xtal::register_sketches! {
+    let mut registry = xtal::register_sketches! {
         {
             title: "Main",
             enabled: true,
             sketches: [
                 acc,
-                blob,
+                blob: ["feedback"],
                 cloud,
                 d_warp,
                 dreams,
                 dyn_uni,
                 flow,
-                grid_splash,
+                grid_splash: ["feedback"],
                 gyroid,
                 hatch,
                 interference,
@@ -45,7 +45,7 @@ fn main() {
             sketches: [
                 domain_warps,
                 displ,
-                grid_splash_bw,
+                grid_splash_bw: ["feedback"],
                 layers,
                 phase_matrix,
                 sline,
@@ -64,7 +64,7 @@ fn main() {
             enabled: true,
             sketches: [
                 basic,
-                feedback,
+                feedback: ["feedback"],
                 multipass,
                 compute,
                 image,
@@ -76,7 +76,22 @@ fn main() {
         std::process::exit(1);
     });
 
-    let initial_sketch = std::env::args().nth(1);
+    let mut initial_sketch = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--tag" {
+            initial_sketch = Some(arg);
+            continue;
+        }
+        let Some(tag) = args.next() else {
+            eprintln!("--tag requires a tag name");
+            std::process::exit(1);
+        };
+        if let Err(err) = registry.filter_by_tag(&tag) {
+            eprintln!("xtal sketch registry failed: {}", err);
+            std::process::exit(1);
+        }
+    }
 
     if let Err(err) = run_registry(registry, initial_sketch.as_deref()) {
         eprintln!("xtal runtime failed: {}", err);
@@ -11,6 +11,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: 900,
     h: 600,
     banks: 4,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct ComputeSketch {
@@ -11,6 +11,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: 960,
     h: 540,
     banks: 4,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct FeedbackSketch {
@@ -11,6 +11,7 @@ pub static SKETCH_CONFIG: SketchConfig = SketchConfig {
     w: 700,
     h: 700,
     banks: 4,
+    ..SketchConfig::DEFAULT
 };
 
 pub struct ImageSketch {
@@ -22,7 +23,7 @@ pub struct ImageSketch {
 impl Sketch for ImageSketch {
     fn setup(&self, graph: &mut GraphBuilder) {
         let params = graph.uniforms();
-        let img0 = graph.image(self.image_path.clone());
+        let img0 = graph.dropped_image(self.image_path.clone());
 
         graph
             .render()
@@ -8,7 +8,13 @@ import {
   Exclusions,
   Mappings,
   OsDir,
+  PassTimings,
+  PreRoll,
   RawControl,
+  RecordingOptions,
+  SketchStateTransfer,
+  StillFormat,
+  TimelapseOptions,
   UserDir,
   View,
 } from './types'
@@ -22,6 +28,7 @@ import { isMac, setCssBeat } from './util'
 
 type EventMap = {
   Advance: void
+  AdvanceRandomizeSeed: void
   Alert: string
   AverageFps: number
   Bpm: number
@@ -32,11 +39,14 @@ type EventMap = {
   ChangeMidiControlInputPort: string
   ChangeMidiControlOutputPort: string
   ChangeOscPort: number
+  ChangeOscPrefix: string
+  ChooseSketchStateFile: SketchStateTransfer
   ClearBuffer: void
   CommitMappings: void
   CurrentlyMapping: string
   Encoding: boolean
   Error: string
+  ExportControlScript: void
   Hrcc: boolean
   HubPopulated: [RawControl[], Bypassed]
   SnapshotSequenceEnabled: boolean
@@ -48,14 +58,26 @@ type EventMap = {
     isLightTheme: boolean
     mappingsEnabled: boolean
     midiClockPort: string
+    midiFeedbackEnabled: boolean
     midiInputPort: string
     midiOutputPort: string
     midiInputPorts: [number, string][]
     midiOutputPorts: [number, string][]
     monitorPreviewEnabled: boolean
+    offlineRecordingSeconds: number
     oscPort: number
+    oscPrefix: string
+    oscTransportSmoothing: number
+    perfModeHidesCursor: boolean
+    recordAudio: boolean
+    recordingOptions: RecordingOptions
+    recordingPreRoll: PreRoll
+    sketchCatalog?: SketchCatalogCategory[] | null
     sketchesByCategory: Record<string, string[]>
+    sketchesByTag?: Record<string, string[]>
     sketchName: string
+    stillFormat: StillFormat
+    timelapseOptions: TimelapseOptions
     transitionTime: number
     userDataDir: string
     videosDir: string
@@ -64,6 +86,7 @@ type EventMap = {
     bpm: number
     bypassed: Bypassed
     controls: RawControl[]
+    description?: string | null
     exclusions: Exclusions
     fps: number
     paused: boolean
@@ -75,30 +98,55 @@ type EventMap = {
   }
   Mappings: Mappings
   MappingsEnabled: boolean
+  MidiFeedbackEnabled: boolean
   MonitorPreview: boolean
+  OfflineRecordingSeconds: number
   OpenOsDir: OsDir
+  OscTransportSmoothing: number
+  PassTimings: PassTimings
   Paused: boolean
   PerfMode: boolean
+  PerfModeHidesCursor: boolean
+  PreviewControl: {
+    name: string
+    value: number | null
+  }
   QueueRecord: void
   Quit: void
   Randomize: Exclusions
+  // A u64 as a decimal string; JS numbers can't hold every seed exactly
+  RandomizeSeed: string | null
   Ready: void
   ReceiveDir: [UserDir, string]
+  RecordAudio: boolean
+  RecordingCountdown: number | null
+  RecordingOptions: RecordingOptions
+  RecordingPreRoll: PreRoll
   RemoveMapping: string
   Reset: void
   Save: string[]
   SendMidi: void
+  ShaderError: {
+    path: string
+    message: string
+    line: number | null
+    column: number | null
+  }
   SnapshotEnded: RawControl[]
   SnapshotDelete: string
   SnapshotRecall: string
   SnapshotStore: string
   StartRecording: void
+  StillFormat: StillFormat
   StopRecording: void
   SwitchSketch: string
   Tap: void
   TapTempoEnabled: boolean
+  Timelapse: boolean
+  TimelapseOptions: TimelapseOptions
   ToggleFullScreen: void
   ToggleMainFocus: void
+  ToggleTimelapse: void
   TransitionTime: number
   UpdateControlBool: {
     name: string
@@ -120,6 +168,13 @@ type OptionGroup = {
   options: string[]
 }
 
+type SketchCatalogCategory = {
+  title: string
+  enabled: boolean
+  sketches: string[]
+  descriptions?: Record<string, string>
+}
+
 function subscribe<K extends keyof EventMap>(
   callback: (event: K, data: EventMap[K]) => void,
 ) {
@@ -190,12 +245,14 @@ export default function App() {
   const [alertText, setAlertText] = useState('')
   const [audioDevices, setAudioDevices] = useState<string[]>([])
   const [audioDevice, setAudioDevice] = useState('')
+  const [countdown, setCountdown] = useState<number | null>(null)
   const [bpm, setBpm] = useState(134)
   const [bypassed, setBypassed] = useState<Bypassed>({})
   const [controls, setControls] = useState<Control[]>([])
   const [controlsLastSaved, setControlsLastSaved] = useState<Control[]>([])
   const [exclusions, setExclusions] = useState<string[]>([])
   const [fps, setFps] = useState(60)
+  const [passTimings, setPassTimings] = useState<PassTimings | null>(null)
   const [hrcc, setHrcc] = useState(false)
   const [imagesDir, setImagesDir] = useState('')
   const [isEncoding, setIsEncoding] = useState(false)
@@ -205,25 +262,46 @@ export default function App() {
   const [mappingsEnabled, setMappingsEnabled] = useState(true)
   const [monitorPreviewEnabled, setMonitorPreviewEnabled] = useState(false)
   const [midiClockPort, setMidiClockPort] = useState('')
+  const [midiFeedbackEnabled, setMidiFeedbackEnabled] = useState(false)
   const [midiInputPort, setMidiInputPort] = useState('')
   const [midiInputPorts, setMidiInputPorts] = useState<string[]>([])
   const [midiOutputPort, setMidiOutputPort] = useState('')
   const [midiOutputPorts, setMidiOutputPorts] = useState<string[]>([])
+  const [offlineRecordingSeconds, setOfflineRecordingSeconds] = useState(0)
   const [oscPort, setOscPort] = useState(5000)
+  const [oscPrefix, setOscPrefix] = useState('')
+  const [oscTransportSmoothing, setOscTransportSmoothing] = useState(0)
   const [paused, setPaused] = useState(false)
+  const [recordAudio, setRecordAudio] = useState(false)
+  const [recordingOptions, setRecordingOptions] = useState<RecordingOptions>({
+    codec: 'H264',
+    quality: 60,
+  })
+  const [recordingPreRoll, setRecordingPreRoll] = useState<PreRoll>('Off')
   const [perfMode, setPerfMode] = useState(false)
+  const [perfModeHidesCursor, setPerfModeHidesCursor] = useState(true)
   const [showExclusions, setShowExclusions] = useState(false)
   const [showHelp, setShowHelp] = useState(false)
   const [showSnapshots, setShowSnapshots] = useState(false)
   const [singleTransitionControlName, setSingleTransitionControlName] =
     useState('')
+  const [sketchDescription, setSketchDescription] = useState('')
+  const [sketchDescriptions, setSketchDescriptions] = useState<
+    Record<string, string>
+  >({})
   const [sketchName, setSketchName] = useState('')
   const [sketchOptionGroups, setSketchOptionGroups] = useState<OptionGroup[]>(
     [],
   )
   const [snapshots, setSnapshots] = useState<string[]>([])
   const [snapshotSequenceEnabled, setSnapshotSequenceEnabled] = useState(false)
+  const [stillFormat, setStillFormat] = useState<StillFormat>('Png8')
   const [tapTempoEnabled, setTapTempoEnabled] = useState(false)
+  const [timelapseActive, setTimelapseActive] = useState(false)
+  const [timelapseOptions, setTimelapseOptions] = useState<TimelapseOptions>({
+    interval: { Frames: 60 },
+    pattern: '{sketch}-{session}-{n}',
+  })
   const [transitionTime, setTransitionTime] = useState(4)
   const [transitionInProgress, setTransitionInProgress] = useState(false)
   const [videosDir, setVideosDir] = useState('')
@@ -232,7 +310,7 @@ export default function App() {
 
   useEffect(() => {
     const unsubscribe = subscribe((event: keyof EventMap, data) => {
-      if (event !== 'AverageFps') {
+      if (event !== 'AverageFps' && event !== 'PassTimings') {
         console.debug('[app]', event, data)
       }
 
@@ -245,6 +323,10 @@ export default function App() {
           setFps(data as EventMap['AverageFps'])
           break
         }
+        case 'PassTimings': {
+          setPassTimings(data as EventMap['PassTimings'])
+          break
+        }
         case 'Bpm': {
           const bpm = data as EventMap['Bpm']
           setBpm(bpm)
@@ -278,23 +360,47 @@ export default function App() {
           setImagesDir(d.imagesDir)
           setMappingsEnabled(d.mappingsEnabled)
           setMidiClockPort(d.midiClockPort)
+          setMidiFeedbackEnabled(d.midiFeedbackEnabled)
           setMidiInputPort(d.midiInputPort)
           setMidiOutputPort(d.midiOutputPort)
           const getPort = ([, port]: [number, string]) => port
           setMidiInputPorts(d.midiInputPorts.map(getPort))
           setMidiOutputPorts(d.midiOutputPorts.map(getPort))
           setMonitorPreviewEnabled(d.monitorPreviewEnabled)
+          setOfflineRecordingSeconds(d.offlineRecordingSeconds)
           setOscPort(d.oscPort)
+          setOscPrefix(d.oscPrefix)
+          setOscTransportSmoothing(d.oscTransportSmoothing)
+          setPerfModeHidesCursor(d.perfModeHidesCursor)
+          setRecordAudio(d.recordAudio)
+          setRecordingOptions(d.recordingOptions)
+          setRecordingPreRoll(d.recordingPreRoll)
           setUserDataDir(d.userDataDir)
+          setSketchDescriptions(
+            Object.assign(
+              {},
+              ...(d.sketchCatalog ?? []).map(
+                (category) => category.descriptions ?? {},
+              ),
+            ),
+          )
           setSketchName(d.sketchName)
-          setSketchOptionGroups(
-            Object.entries(d.sketchesByCategory).map(
+          setSketchOptionGroups([
+            ...Object.entries(d.sketchesByCategory).map(
               ([label, options]) => ({
                 label,
                 options,
               }),
             ),
-          )
+            ...Object.entries(d.sketchesByTag ?? {}).map(
+              ([tag, options]) => ({
+                label: `#${tag}`,
+                options,
+              }),
+            ),
+          ])
+          setStillFormat(d.stillFormat)
+          setTimelapseOptions(d.timelapseOptions)
           setTransitionTime(d.transitionTime)
           setVideosDir(d.videosDir)
           break
@@ -306,6 +412,7 @@ export default function App() {
           const controls = fromRawControls(d.controls)
           setControls(controls)
           setControlsLastSaved(controls)
+          setSketchDescription(d.description ?? '')
           setExclusions(d.exclusions)
           setFps(d.fps)
           setMappings(d.mappings)
@@ -336,17 +443,43 @@ export default function App() {
           }
           break
         }
+        case 'ShaderError': {
+          const { path, message, line, column } =
+            data as EventMap['ShaderError']
+          const location = [path, line, column]
+            .filter((part) => part !== null)
+            .join(':')
+          setAlertText(`${location}: ${message}`)
+          break
+        }
         case 'SnapshotEnded': {
           setControls(fromRawControls(data as EventMap['SnapshotEnded']))
           setTransitionInProgress(false)
           setSingleTransitionControlName('')
           break
         }
+        case 'RecordingCountdown': {
+          const count = data as EventMap['RecordingCountdown']
+          setCountdown(count)
+          if (count !== null) {
+            setIsRecording(true)
+            setIsQueued(false)
+          }
+          break
+        }
         case 'StartRecording': {
           setIsRecording(true)
           setIsQueued(false)
           break
         }
+        case 'StopRecording': {
+          setIsRecording(false)
+          break
+        }
+        case 'Timelapse': {
+          setTimelapseActive(data as EventMap['Timelapse'])
+          break
+        }
         case 'UpdatedControls': {
           setControls(fromRawControls(data as EventMap['UpdatedControls']))
           break
@@ -408,7 +541,7 @@ export default function App() {
             break
           }
           case 'KeyI': {
-            post('CaptureFrame')
+            post(e.shiftKey ? 'ToggleTimelapse' : 'CaptureFrame')
             break
           }
           case 'KeyM': {
@@ -542,6 +675,12 @@ export default function App() {
     post('ChangeMidiClockPort', port)
   }
 
+  function onChangeMidiFeedbackEnabled() {
+    const enabled = !midiFeedbackEnabled
+    setMidiFeedbackEnabled(enabled)
+    post('MidiFeedbackEnabled', enabled)
+  }
+
   function onChangeMidiInputPort(port: string) {
     setMidiInputPort(port)
     post('ChangeMidiControlInputPort', port)
@@ -563,12 +702,63 @@ export default function App() {
     post('ChangeOscPort', port)
   }
 
+  function onChangeOfflineRecordingSeconds(seconds: number) {
+    setOfflineRecordingSeconds(seconds)
+    post('OfflineRecordingSeconds', seconds)
+  }
+
+  function onChangeRecordAudio() {
+    const enabled = !recordAudio
+    setRecordAudio(enabled)
+    post('RecordAudio', enabled)
+  }
+
+  function onChangeRecordingOptions(options: RecordingOptions) {
+    setRecordingOptions(options)
+    post('RecordingOptions', options)
+  }
+
+  function onChangeRecordingPreRoll(preRoll: PreRoll) {
+    setRecordingPreRoll(preRoll)
+    post('RecordingPreRoll', preRoll)
+  }
+
+  function onChangeStillFormat(format: StillFormat) {
+    setStillFormat(format)
+    post('StillFormat', format)
+  }
+
+  function onChangeTimelapseOptions(options: TimelapseOptions) {
+    setTimelapseOptions(options)
+    post('TimelapseOptions', options)
+  }
+
+  function onToggleTimelapse() {
+    post('ToggleTimelapse')
+  }
+
+  function onChangeOscPrefix(prefix: string) {
+    setOscPrefix(prefix)
+    post('ChangeOscPrefix', prefix)
+  }
+
+  function onChangeOscTransportSmoothing(smoothing: number) {
+    setOscTransportSmoothing(smoothing)
+    post('OscTransportSmoothing', smoothing)
+  }
+
   function onChangePerfMode() {
     const value = !perfMode
     setPerfMode(value)
     post('PerfMode', value)
   }
 
+  function onChangePerfModeHidesCursor() {
+    const hides = !perfModeHidesCursor
+    setPerfModeHidesCursor(hides)
+    post('PerfModeHidesCursor', hides)
+  }
+
   function onChangeTapTempoEnabled() {
     const enabled = !tapTempoEnabled
     setTapTempoEnabled(enabled)
@@ -640,6 +830,14 @@ export default function App() {
     post('SendMidi')
   }
 
+  function onExportControlScript() {
+    post('ExportControlScript')
+  }
+
+  function onTransferSketchState(transfer: SketchStateTransfer) {
+    post('ChooseSketchStateFile', transfer)
+  }
+
   function onDeleteMappings() {
     Object.keys(mappings).forEach((key) => {
       post('RemoveMapping', key)
@@ -651,6 +849,10 @@ export default function App() {
     post('OpenOsDir', osDir)
   }
 
+  function onPreviewControl(name: string, value: number | null) {
+    post('PreviewControl', { name, value })
+  }
+
   function onQueueRecord() {
     const value = !isQueued
     setIsQueued(value)
@@ -727,7 +929,9 @@ export default function App() {
     <div id="app">
       <Header
         fps={fps}
+        passTimings={passTimings}
         bpm={bpm}
+        countdown={countdown}
         isEncoding={isEncoding}
         isQueued={isQueued}
         isRecording={isRecording}
@@ -736,6 +940,7 @@ export default function App() {
         perfMode={perfMode}
         showExclusions={showExclusions}
         showSnapshots={showSnapshots}
+        sketchDescriptions={sketchDescriptions}
         sketchName={sketchName}
         sketchOptionGroups={sketchOptionGroups}
         tapTempoEnabled={tapTempoEnabled}
@@ -774,12 +979,23 @@ export default function App() {
             mappings={mappings}
             mappingsEnabled={mappingsEnabled}
             midiClockPort={midiClockPort}
+            midiFeedbackEnabled={midiFeedbackEnabled}
             midiInputPort={midiInputPort}
             midiInputPorts={midiInputPorts}
             midiOutputPort={midiOutputPort}
             midiOutputPorts={midiOutputPorts}
+            offlineRecordingSeconds={offlineRecordingSeconds}
             oscPort={oscPort}
+            oscPrefix={oscPrefix}
+            oscTransportSmoothing={oscTransportSmoothing}
+            perfModeHidesCursor={perfModeHidesCursor}
+            recordAudio={recordAudio}
+            recordingOptions={recordingOptions}
+            recordingPreRoll={recordingPreRoll}
             sliderNames={getSliderNames()}
+            stillFormat={stillFormat}
+            timelapseActive={timelapseActive}
+            timelapseOptions={timelapseOptions}
             userDataDir={userDataDir}
             videosDir={videosDir}
             onChangeAudioDevice={onChangeAudioDevice}
@@ -787,36 +1003,57 @@ export default function App() {
             onChangeHrcc={onChangeHrcc}
             onChangeMappingsEnabled={onChangeMappingsEnabled}
             onChangeMidiClockPort={onChangeMidiClockPort}
+            onChangeMidiFeedbackEnabled={onChangeMidiFeedbackEnabled}
             onChangeMidiInputPort={onChangeMidiInputPort}
             onChangeMidiOutputPort={onChangeMidiOutputPort}
+            onChangeOfflineRecordingSeconds={onChangeOfflineRecordingSeconds}
             onChangeOscPort={onChangeOscPort}
+            onChangeOscPrefix={onChangeOscPrefix}
+            onChangeOscTransportSmoothing={onChangeOscTransportSmoothing}
+            onChangePerfModeHidesCursor={onChangePerfModeHidesCursor}
+            onChangeRecordAudio={onChangeRecordAudio}
+            onChangeRecordingOptions={onChangeRecordingOptions}
+            onChangeRecordingPreRoll={onChangeRecordingPreRoll}
+            onChangeStillFormat={onChangeStillFormat}
+            onChangeTimelapseOptions={onChangeTimelapseOptions}
             onClickSend={onClickSendMidi}
             onDeleteMappings={onDeleteMappings}
+            onExportControlScript={onExportControlScript}
             onOpenOsDir={onOpenOsDir}
             onRemoveMapping={onRemoveMapping}
             onSetCurrentlyMapping={onSetCurrentlyMapping}
+            onToggleTimelapse={onToggleTimelapse}
+            onTransferSketchState={onTransferSketchState}
           />
         ) : (
-          <Controls
-            bypassed={bypassed}
-            controls={controls}
-            exclusions={exclusions}
-            mappings={mappings}
-            mappingsEnabled={mappingsEnabled}
-            showExclusions={showExclusions}
-            showSnapshots={showSnapshots}
-            singleTransitionControlName={singleTransitionControlName}
-            transitionInProgress={transitionInProgress}
-            onChange={onChangeControl}
-            onClickRandomize={onClickRandomizeSingleControl}
-            onClickRevert={onClickRevert}
-            onToggleExclusion={onToggleExclusion}
-            snapshots={snapshots}
-            snapshotSequenceEnabled={snapshotSequenceEnabled}
-            onDeleteSnapshot={onDeleteSnapshot}
-            onLoadSnapshot={onLoadSnapshot}
-            onSaveSnapshot={onSaveSnapshot}
-          />
+          <>
+            {sketchDescription && (
+              <section className="sketch-description" data-help-id="About">
+                {sketchDescription}
+              </section>
+            )}
+            <Controls
+              bypassed={bypassed}
+              controls={controls}
+              exclusions={exclusions}
+              mappings={mappings}
+              mappingsEnabled={mappingsEnabled}
+              showExclusions={showExclusions}
+              showSnapshots={showSnapshots}
+              singleTransitionControlName={singleTransitionControlName}
+              transitionInProgress={transitionInProgress}
+              onChange={onChangeControl}
+              onClickRandomize={onClickRandomizeSingleControl}
+              onClickRevert={onClickRevert}
+              onToggleExclusion={onToggleExclusion}
+              snapshots={snapshots}
+              snapshotSequenceEnabled={snapshotSequenceEnabled}
+              onDeleteSnapshot={onDeleteSnapshot}
+              onLoadSnapshot={onLoadSnapshot}
+              onPreview={onPreviewControl}
+              onSaveSnapshot={onSaveSnapshot}
+            />
+          </>
         )}
       </main>
       <footer>
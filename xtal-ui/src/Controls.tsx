@@ -1,4 +1,4 @@
-import { ReactNode, useEffect, useState } from 'react'
+import { PointerEvent, ReactNode, useEffect, useState } from 'react'
 import NumberBox from '@lokua/number-box'
 import ExcludedIcon from '@material-symbols/svg-400/outlined/keep.svg?react'
 import MappedIcon from '@material-symbols/svg-400/outlined/app_badging.svg?react'
@@ -31,6 +31,21 @@ const MappedIndicator = () => (
   </span>
 )
 
+// The value of a range input under the pointer, snapped to its step
+function valueAtPointer(
+  e: PointerEvent<HTMLInputElement>,
+  min: number,
+  max: number,
+  step: number,
+): number {
+  const rect = e.currentTarget.getBoundingClientRect()
+  const t = Math.min(1, Math.max(0, (e.clientX - rect.left) / rect.width))
+  const value = min + t * (max - min)
+  return step > 0
+    ? Math.min(max, min + Math.round((value - min) / step) * step)
+    : value
+}
+
 export type Props = {
   bypassed: Bypassed
   controls: Control[]
@@ -48,6 +63,7 @@ export type Props = {
   onClickRevert: (control: Control) => void
   onDeleteSnapshot: (snapshot: string) => void
   onLoadSnapshot: (snapshot: string) => void
+  onPreview: (name: string, value: number | null) => void
   onToggleExclusion: (name: string) => void
   onSaveSnapshot: (snapshot: string) => void
 }
@@ -69,6 +85,7 @@ export default function Controls({
   onClickRevert,
   onDeleteSnapshot,
   onLoadSnapshot,
+  onPreview,
   onToggleExclusion,
   onSaveSnapshot,
 }: Props) {
@@ -181,6 +198,19 @@ export default function Controls({
                     onChange={(e) => {
                       onChange(c, e.currentTarget.valueAsNumber)
                     }}
+                    onPointerMove={(e) => {
+                      // While dragging, changes are committed instead
+                      if (disabled || e.buttons !== 0) {
+                        return
+                      }
+                      onPreview(c.name, valueAtPointer(e, c.min, c.max, c.step))
+                    }}
+                    onPointerDown={() => {
+                      onPreview(c.name, null)
+                    }}
+                    onPointerLeave={() => {
+                      onPreview(c.name, null)
+                    }}
                   />
                   <NumberBox
                     data-help-id="NumberBox"
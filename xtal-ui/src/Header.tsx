@@ -1,6 +1,6 @@
 import clsx from 'clsx/lite'
 
-import type { noop } from './types'
+import type { noop, PassTimings } from './types'
 import { View } from './types'
 
 import Select from './Select'
@@ -18,15 +18,18 @@ type OptionGroup = {
 
 type HeaderProps = {
   bpm: number
+  countdown: number | null
   fps: number
   isEncoding: boolean
   isQueued: boolean
   isRecording: boolean
   monitorPreviewEnabled: boolean
+  passTimings: PassTimings | null
   paused: boolean
   perfMode: boolean
   showExclusions: boolean
   showSnapshots: boolean
+  sketchDescriptions: Record<string, string>
   sketchName: string
   sketchOptionGroups: OptionGroup[]
   tapTempoEnabled: boolean
@@ -54,15 +57,18 @@ type HeaderProps = {
 
 export default function Header({
   bpm,
+  countdown,
   fps,
   isEncoding,
   isQueued,
   isRecording,
   monitorPreviewEnabled,
+  passTimings,
   paused,
   perfMode,
   showExclusions,
   showSnapshots,
+  sketchDescriptions,
   sketchName,
   sketchOptionGroups,
   tapTempoEnabled,
@@ -133,11 +139,29 @@ export default function Header({
           isToggle
           onClick={onRecord}
         />
+        {countdown !== null && (
+          <div data-help-id="PreRoll" className="meter">
+            <span className="meter-value">{countdown}</span>
+          </div>
+        )}
 
         <VerticalSeparator />
 
-        <div data-help-id="Fps" className="meter">
+        <div
+          data-help-id="Fps"
+          className="meter"
+          title={passTimings ? formatPassTimings(passTimings) : undefined}
+        >
           FPS: <span className="meter-value">{fps.toFixed(1)}</span>
+          {passTimings && (
+            <>
+              {' '}
+              GPU:{' '}
+              <span className="meter-value">
+                {passTimings.totalMilliseconds.toFixed(2)}ms
+              </span>
+            </>
+          )}
         </div>
 
         <VerticalSeparator />
@@ -166,6 +190,7 @@ export default function Header({
           data-help-id="Sketch"
           value={sketchName}
           optionGroups={sketchOptionGroups}
+          optionTitles={sketchDescriptions}
           onChange={onSwitchSketch}
           style={{ maxWidth: '164px' }}
         />
@@ -234,3 +259,9 @@ export default function Header({
     </header>
   )
 }
+
+function formatPassTimings({ passes }: PassTimings) {
+  return passes
+    .map(({ name, milliseconds }) => `${name}: ${milliseconds.toFixed(2)}ms`)
+    .join('\n')
+}
@@ -3,6 +3,10 @@ import { format, isMac } from './util'
 const mod = isMac ? 'Cmd' : 'Ctrl'
 
 export const Help = {
+  About: format(`
+    About this sketch: notes from the sketch's author on what its controls
+    and keys do
+  `),
   Advance: format(
     `When the [Play/Pause] toggle is set to [Pause], allows manually advancing 
     frames (Shortcut: [A])`
@@ -34,6 +38,11 @@ export const Help = {
   `),
   MidiClockPort:
     "The MIDI port used to sync all Xtal's frame counter and animations",
+  MidiFeedback: format(`
+    Send mapped control values to the Output Port whenever they change
+    (including from the UI and snapshot recalls) so motorized faders and
+    LED rings stay in sync
+  `),
   MidiInputPort:
     'The MIDI port Xtal will listen to for incoming MIDI CC messages',
   MidiOutputPort: format(`
@@ -45,7 +54,28 @@ export const Help = {
       enable fine adjustments. Double clicking will enable manual keyboard 
       entry.
   `),
+  OfflineRecording: format(`
+    When above 0, [Record] renders exactly this many seconds of frames at the
+    sketch's fps, stepping time one frame per render instead of in real time,
+    then stops on its own. Use it for clean exports on slow machines
+  `),
   OscPort: 'The OSC port Xtal will use for OSC controls',
+  OscPrefix: format(`
+    Optional address namespace, e.g. /xtal1, for running several instances
+    on one network. When set, only /xtal1/... addresses are handled, apart
+    from the DAW plug-in's /transport
+  `),
+  OscTransportSmoothing: format(`
+    Smooths jittery /transport positions in OSC timing mode (0 - 0.95, higher
+    is smoother but settles more slowly after a tempo change). Jitter never
+    runs time backward; relocations and rewinds are followed immediately.
+    Leave at 0 when tick accuracy matters
+  `),
+  PreRoll: format(`
+    Count down this many beats or seconds before a recording starts, whether
+    triggered by [Record] or a queued MIDI start. Click [Record] again during
+    the countdown to cancel
+  `),
   Play: format(`
     Play/Pause Toggle (Shortcut: [P]). When Pause is engaged, use the [Advance]
     button or [${mod} A] to manually advance frames.
@@ -55,8 +85,13 @@ export const Help = {
     applying a sketch's default width and height and also disables automatic 
     window repositioning. This is necessary in live performance contexts where 
     you likely will fullsize the screen and want to keep it that way when 
-    switching sketches`
+    switching sketches. Also hides the window's title bar and, unless
+    disabled in Settings, the cursor over the sketch window`
   ),
+  PerfModeHidesCursor: format(`
+    Hide the cursor over the sketch window while Performance Mode is on. The
+    cursor still shows whenever the window loses focus
+  `),
   Queue: 'Queue recording to start upon receiving a MIDI Start message',
   Random: `Randomize all UI controls (Shortcut: [${mod} R])`,
   Reload: format(
@@ -64,12 +99,39 @@ export const Help = {
     (Shortcut: [Shift ${mod} R])`
   ),
   Record: 'Start/Stop recording',
+  RecordAudio: format(`
+    Mux the selected audio device into real-time recordings, starting with the
+    first recorded frame. Without a device the video is recorded silently
+  `),
+  RecordingCodec: format(`
+    H264 (.mp4) for quick shares, ProRes (.mov) for editing, or Vp9 (.webm).
+    Falls back to H264 if the installed ffmpeg lacks the encoder
+  `),
+  RecordingQuality: format(`
+    0 (smallest files) to 100 (best quality), mapped onto the codec's own
+    quality scale
+  `),
   Reset: 'Reset the frame counter and all animations (Shortcut: [R])',
+  StillFormat: format(`
+    Format of images captured with [I]: 8-bit PNG, 16-bit PNG, or half-float
+    OpenEXR, which keeps values above 1 from an HDR texture. Timelapse stills
+    are always 8-bit PNG
+  `),
   Tap: `
     Enabled/disable tap tempo. When enabled, use the [Space] key to tap. 
     Note that keeping enabled will preserve the currently tapped-in tempo when 
     switching sketches; disabling will always revert to a sketch's configured BPM.
   `,
+  Timelapse: format(`
+    Save a numbered PNG to the images directory at a fixed interval until
+    toggled off (Shortcut: [Shift I]). Uses the recording readback when a
+    recording is running
+  `),
+  TimelapseInterval: 'How often a timelapse saves a still, in frames or beats',
+  TimelapsePattern: format(`
+    Timelapse file name without extension. {sketch}, {session} and {n} (the
+    zero-padded image number) are filled in; {n} is appended if missing
+  `),
   TransitionTime: 'Snapshot and Randomization transition time (in beats)',
   Save: format(`
     Save UI control states and MIDI mappings for this sketch to disk 
@@ -77,7 +139,9 @@ export const Help = {
   `),
   Send: 'Sends the state of all CCs to the MIDI output port',
   Settings: 'Global settings and MIDI mappings',
-  Sketch: 'Sketch chooser',
+  Sketch: format(`
+    Sketch chooser. Hover an option to preview that sketch's description
+  `),
   Snapshots: format(`
     Snapshot Editor: store and recall up to 10 snapshots (Shortcut: [S]).
     You can also save snapshots via [Shift Digit] and recall them
@@ -11,6 +11,7 @@ type Props = Override<
     value: string
     options?: string[] | number[]
     optionGroups?: OptionGroup[]
+    optionTitles?: Record<string, string>
     onChange: (value: string) => void
   }
 >
@@ -19,6 +20,7 @@ export default function Select({
   value,
   options = [],
   optionGroups,
+  optionTitles = {},
   onChange,
   ...rest
 }: Props) {
@@ -35,7 +37,11 @@ export default function Select({
           ? optionGroups.map((group) => (
               <optgroup key={group.label} label={group.label}>
                 {group.options.map((option) => (
-                  <option key={option} value={option}>
+                  <option
+                    key={option}
+                    value={option}
+                    title={optionTitles[option]}
+                  >
                     {option}
                   </option>
                 ))}
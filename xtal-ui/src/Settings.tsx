@@ -1,4 +1,16 @@
-import { Mappings, noop, OsDir, UserDir } from './types'
+import {
+  Mappings,
+  noop,
+  OsDir,
+  PreRoll,
+  RecordingOptions,
+  SketchStateTransfer,
+  StillFormat,
+  TimelapseInterval,
+  TimelapseOptions,
+  UserDir,
+  VideoCodec,
+} from './types'
 import Checkbox from './Checkbox'
 import MapMode from './MapMode'
 import OscPortInput from './OscPortInput'
@@ -24,6 +36,43 @@ function fromSizePreset(size: SizePreset) {
   }[size] as FontSizeChoice
 }
 
+function preRollUnit(preRoll: PreRoll) {
+  if (preRoll === 'Off') {
+    return 'Off'
+  }
+  return 'Beats' in preRoll ? 'Beats' : 'Seconds'
+}
+
+function preRollLength(preRoll: PreRoll) {
+  if (preRoll === 'Off') {
+    return 0
+  }
+  return 'Beats' in preRoll ? preRoll.Beats : preRoll.Seconds
+}
+
+function toPreRoll(unit: string, length: number): PreRoll {
+  switch (unit) {
+    case 'Beats':
+      return { Beats: length }
+    case 'Seconds':
+      return { Seconds: length }
+    default:
+      return 'Off'
+  }
+}
+
+function timelapseUnit(interval: TimelapseInterval) {
+  return 'Frames' in interval ? 'Frames' : 'Beats'
+}
+
+function timelapseLength(interval: TimelapseInterval) {
+  return 'Frames' in interval ? interval.Frames : interval.Beats
+}
+
+function toTimelapseInterval(unit: string, length: number): TimelapseInterval {
+  return unit === 'Beats' ? { Beats: length } : { Frames: Math.round(length) }
+}
+
 type Props = {
   audioDevice: string
   audioDevices: string[]
@@ -32,12 +81,23 @@ type Props = {
   mappings: Mappings
   mappingsEnabled: boolean
   midiClockPort: string
+  midiFeedbackEnabled: boolean
   midiInputPort: string
   midiInputPorts: string[]
   midiOutputPort: string
   midiOutputPorts: string[]
+  offlineRecordingSeconds: number
   oscPort: number
+  oscPrefix: string
+  oscTransportSmoothing: number
+  perfModeHidesCursor: boolean
+  recordAudio: boolean
+  recordingOptions: RecordingOptions
+  recordingPreRoll: PreRoll
   sliderNames: string[]
+  stillFormat: StillFormat
+  timelapseActive: boolean
+  timelapseOptions: TimelapseOptions
   userDataDir: string
   videosDir: string
   onChangeAudioDevice: (name: string) => void
@@ -45,14 +105,27 @@ type Props = {
   onChangeHrcc: noop
   onChangeMappingsEnabled: () => void
   onChangeMidiClockPort: (port: string) => void
+  onChangeMidiFeedbackEnabled: noop
   onChangeMidiInputPort: (port: string) => void
   onChangeMidiOutputPort: (port: string) => void
+  onChangeOfflineRecordingSeconds: (seconds: number) => void
   onChangeOscPort: (port: number) => void
+  onChangeOscPrefix: (prefix: string) => void
+  onChangeOscTransportSmoothing: (smoothing: number) => void
+  onChangePerfModeHidesCursor: noop
+  onChangeRecordAudio: noop
+  onChangeRecordingOptions: (options: RecordingOptions) => void
+  onChangeRecordingPreRoll: (preRoll: PreRoll) => void
+  onChangeStillFormat: (format: StillFormat) => void
+  onChangeTimelapseOptions: (options: TimelapseOptions) => void
   onClickSend: () => void
   onDeleteMappings: () => void
+  onExportControlScript: () => void
   onOpenOsDir: (osDir: OsDir) => void
   onRemoveMapping: (name: string) => void
   onSetCurrentlyMapping: (name: string) => void
+  onToggleTimelapse: noop
+  onTransferSketchState: (transfer: SketchStateTransfer) => void
 }
 
 export default function Settings({
@@ -63,12 +136,23 @@ export default function Settings({
   mappings,
   mappingsEnabled,
   midiClockPort,
+  midiFeedbackEnabled,
   midiInputPort,
   midiInputPorts,
   midiOutputPort,
   midiOutputPorts,
+  offlineRecordingSeconds,
   oscPort,
+  oscPrefix,
+  oscTransportSmoothing,
+  perfModeHidesCursor,
+  recordAudio,
+  recordingOptions,
+  recordingPreRoll,
   sliderNames,
+  stillFormat,
+  timelapseActive,
+  timelapseOptions,
   userDataDir,
   videosDir,
   onChangeAudioDevice,
@@ -76,14 +160,27 @@ export default function Settings({
   onChangeHrcc,
   onChangeMappingsEnabled,
   onChangeMidiClockPort,
+  onChangeMidiFeedbackEnabled,
   onChangeMidiInputPort,
   onChangeMidiOutputPort,
+  onChangeOfflineRecordingSeconds,
   onChangeOscPort,
+  onChangeOscPrefix,
+  onChangeOscTransportSmoothing,
+  onChangePerfModeHidesCursor,
+  onChangeRecordAudio,
+  onChangeRecordingOptions,
+  onChangeRecordingPreRoll,
+  onChangeStillFormat,
+  onChangeTimelapseOptions,
   onClickSend,
   onDeleteMappings,
+  onExportControlScript,
   onOpenOsDir,
   onRemoveMapping,
   onSetCurrentlyMapping,
+  onToggleTimelapse,
+  onTransferSketchState,
 }: Props) {
   const { localSettings, updateLocalSettings } = useLocalSettings()
 
@@ -104,6 +201,15 @@ export default function Settings({
           />
           <label htmlFor="size">Size</label>
         </fieldset>
+        <fieldset data-help-id="PerfModeHidesCursor">
+          <Checkbox
+            id="perf-mode-hides-cursor"
+            type="checkbox"
+            checked={perfModeHidesCursor}
+            onChange={onChangePerfModeHidesCursor}
+          />
+          <label htmlFor="perf-mode-hides-cursor">Perf Hides Cursor</label>
+        </fieldset>
 
         <h2>Storage</h2>
         <fieldset
@@ -163,8 +269,207 @@ export default function Settings({
           >
             Open config dir
           </button>
+          <button onClick={onExportControlScript}>
+            Export control script
+          </button>
+          <button
+            onClick={() => {
+              onTransferSketchState(SketchStateTransfer.Export)
+            }}
+          >
+            Export sketch state
+          </button>
+          <button
+            onClick={() => {
+              onTransferSketchState(SketchStateTransfer.Import)
+            }}
+          >
+            Import sketch state
+          </button>
         </aside>
 
+        <h2>Recording</h2>
+        <fieldset data-help-id="OfflineRecording">
+          <input
+            key={offlineRecordingSeconds}
+            id="offline-recording"
+            type="number"
+            min={0}
+            step={1}
+            defaultValue={offlineRecordingSeconds}
+            onBlur={(e) => {
+              const seconds = Math.max(0, Number(e.target.value) || 0)
+              if (seconds !== offlineRecordingSeconds) {
+                onChangeOfflineRecordingSeconds(seconds)
+              }
+            }}
+            style={{
+              width: '64px',
+            }}
+          />
+          <label htmlFor="offline-recording">Offline Seconds</label>
+        </fieldset>
+        <fieldset data-help-id="RecordAudio">
+          <Checkbox
+            id="record-audio"
+            type="checkbox"
+            checked={recordAudio}
+            onChange={onChangeRecordAudio}
+          />
+          <label htmlFor="record-audio">Audio</label>
+        </fieldset>
+        <fieldset data-help-id="RecordingCodec">
+          <Select
+            id="recording-codec"
+            value={recordingOptions.codec}
+            options={['H264', 'ProRes', 'Vp9']}
+            onChange={(codec) =>
+              onChangeRecordingOptions({
+                ...recordingOptions,
+                codec: codec as VideoCodec,
+              })
+            }
+          />
+          <label htmlFor="recording-codec">Codec</label>
+        </fieldset>
+        <fieldset data-help-id="RecordingQuality">
+          <input
+            key={recordingOptions.quality}
+            id="recording-quality"
+            type="number"
+            min={0}
+            max={100}
+            step={1}
+            defaultValue={recordingOptions.quality}
+            onBlur={(e) => {
+              const quality = Math.min(
+                100,
+                Math.max(0, Math.round(Number(e.target.value) || 0))
+              )
+              if (quality !== recordingOptions.quality) {
+                onChangeRecordingOptions({ ...recordingOptions, quality })
+              }
+            }}
+            style={{
+              width: '64px',
+            }}
+          />
+          <label htmlFor="recording-quality">Quality</label>
+        </fieldset>
+        <fieldset data-help-id="PreRoll">
+          <Select
+            id="pre-roll-unit"
+            value={preRollUnit(recordingPreRoll)}
+            options={['Off', 'Beats', 'Seconds']}
+            onChange={(unit) =>
+              onChangeRecordingPreRoll(
+                toPreRoll(unit, preRollLength(recordingPreRoll) || 4)
+              )
+            }
+          />
+          <label htmlFor="pre-roll-unit">Pre-roll</label>
+        </fieldset>
+        {recordingPreRoll !== 'Off' && (
+          <fieldset data-help-id="PreRoll">
+            <input
+              key={preRollLength(recordingPreRoll)}
+              id="pre-roll-length"
+              type="number"
+              min={1}
+              step={1}
+              defaultValue={preRollLength(recordingPreRoll)}
+              onBlur={(e) => {
+                const length = Math.max(1, Number(e.target.value) || 1)
+                if (length !== preRollLength(recordingPreRoll)) {
+                  onChangeRecordingPreRoll(
+                    toPreRoll(preRollUnit(recordingPreRoll), length)
+                  )
+                }
+              }}
+              style={{
+                width: '64px',
+              }}
+            />
+            <label htmlFor="pre-roll-length">Length</label>
+          </fieldset>
+        )}
+        <fieldset data-help-id="StillFormat">
+          <Select
+            id="still-format"
+            value={stillFormat}
+            options={['Png8', 'Png16', 'Exr']}
+            onChange={(format) => onChangeStillFormat(format as StillFormat)}
+          />
+          <label htmlFor="still-format">Image Format</label>
+        </fieldset>
+        <fieldset data-help-id="Timelapse">
+          <Checkbox
+            id="timelapse"
+            type="checkbox"
+            checked={timelapseActive}
+            onChange={onToggleTimelapse}
+          />
+          <label htmlFor="timelapse">Timelapse</label>
+        </fieldset>
+        <fieldset data-help-id="TimelapseInterval">
+          <Select
+            id="timelapse-unit"
+            value={timelapseUnit(timelapseOptions.interval)}
+            options={['Frames', 'Beats']}
+            onChange={(unit) =>
+              onChangeTimelapseOptions({
+                ...timelapseOptions,
+                interval: toTimelapseInterval(
+                  unit,
+                  timelapseLength(timelapseOptions.interval)
+                ),
+              })
+            }
+          />
+          <label htmlFor="timelapse-unit">Every</label>
+        </fieldset>
+        <fieldset data-help-id="TimelapseInterval">
+          <input
+            key={timelapseLength(timelapseOptions.interval)}
+            id="timelapse-length"
+            type="number"
+            min={1}
+            step={1}
+            defaultValue={timelapseLength(timelapseOptions.interval)}
+            onBlur={(e) => {
+              const length = Math.max(1, Number(e.target.value) || 1)
+              if (length !== timelapseLength(timelapseOptions.interval)) {
+                onChangeTimelapseOptions({
+                  ...timelapseOptions,
+                  interval: toTimelapseInterval(
+                    timelapseUnit(timelapseOptions.interval),
+                    length
+                  ),
+                })
+              }
+            }}
+            style={{
+              width: '64px',
+            }}
+          />
+          <label htmlFor="timelapse-length">Interval</label>
+        </fieldset>
+        <fieldset data-help-id="TimelapsePattern">
+          <input
+            key={timelapseOptions.pattern}
+            id="timelapse-pattern"
+            type="text"
+            defaultValue={timelapseOptions.pattern}
+            onBlur={(e) => {
+              const pattern = e.target.value.trim()
+              if (pattern && pattern !== timelapseOptions.pattern) {
+                onChangeTimelapseOptions({ ...timelapseOptions, pattern })
+              }
+            }}
+          />
+          <label htmlFor="timelapse-pattern">File Name</label>
+        </fieldset>
+
         <h2>MIDI</h2>
         <button data-help-id="Send" onClick={onClickSend}>
           Send
@@ -205,6 +510,15 @@ export default function Settings({
           />
           <label htmlFor="hrcc">HRCC</label>
         </fieldset>
+        <fieldset data-help-id="MidiFeedback">
+          <Checkbox
+            id="midi-feedback"
+            type="checkbox"
+            checked={midiFeedbackEnabled}
+            onChange={onChangeMidiFeedbackEnabled}
+          />
+          <label htmlFor="midi-feedback">Feedback</label>
+        </fieldset>
 
         <h2>Audio</h2>
         <fieldset data-help-id="Audio">
@@ -223,6 +537,46 @@ export default function Settings({
           port={oscPort}
           onChange={onChangeOscPort}
         />
+        <fieldset data-help-id="OscPrefix">
+          <input
+            key={oscPrefix}
+            id="osc-prefix"
+            type="text"
+            placeholder="/xtal1"
+            defaultValue={oscPrefix}
+            onBlur={(e) => {
+              if (e.target.value !== oscPrefix) {
+                onChangeOscPrefix(e.target.value)
+              }
+            }}
+            style={{
+              width: '64px',
+            }}
+          />
+          <label htmlFor="osc-prefix">Prefix</label>
+        </fieldset>
+        <fieldset data-help-id="OscTransportSmoothing">
+          <input
+            key={oscTransportSmoothing}
+            id="osc-transport-smoothing"
+            type="number"
+            min={0}
+            max={0.95}
+            step={0.05}
+            defaultValue={oscTransportSmoothing}
+            onBlur={(e) => {
+              const value = Number(e.target.value) || 0
+              const smoothing = Math.min(0.95, Math.max(0, value))
+              if (smoothing !== oscTransportSmoothing) {
+                onChangeOscTransportSmoothing(smoothing)
+              }
+            }}
+            style={{
+              width: '64px',
+            }}
+          />
+          <label htmlFor="osc-transport-smoothing">Smoothing</label>
+        </fieldset>
       </section>
 
       <section id="mappings-section" data-help-id="Mappings">
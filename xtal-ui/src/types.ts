@@ -29,6 +29,34 @@ export enum OsDir {
   Config = 'Config',
 }
 
+export enum SketchStateTransfer {
+  Export = 'Export',
+  Import = 'Import',
+}
+
+export type VideoCodec = 'H264' | 'ProRes' | 'Vp9'
+
+export type RecordingOptions = {
+  codec: VideoCodec
+  quality: number
+}
+
+export type PreRoll = 'Off' | { Beats: number } | { Seconds: number }
+
+export type StillFormat = 'Png8' | 'Png16' | 'Exr'
+
+export type TimelapseInterval = { Frames: number } | { Beats: number }
+
+export type TimelapseOptions = {
+  interval: TimelapseInterval
+  pattern: string
+}
+
+export type PassTimings = {
+  passes: { name: string; milliseconds: number }[]
+  totalMilliseconds: number
+}
+
 export type ChannelAndController = [number, number]
 export type Mappings = {
   [key: string]: ChannelAndController
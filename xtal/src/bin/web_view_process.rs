@@ -77,6 +77,33 @@ fn main() -> Result<(), Box<dyn Error>> {
                         }
                     }
                 }
+                wv::Event::ChooseSketchStateFile(transfer) => {
+                    let dialog =
+                        FileDialog::new().add_filter("Sketch state", &["json"]);
+                    let path = match transfer {
+                        wv::SketchStateTransfer::Export => dialog.save_file(),
+                        wv::SketchStateTransfer::Import => dialog.pick_file(),
+                    };
+                    match path {
+                        Some(path) => {
+                            let path = path.to_string_lossy().into_owned();
+                            let _ = ipc_sender.send(match transfer {
+                                wv::SketchStateTransfer::Export => {
+                                    wv::Event::ExportSketchState(path)
+                                }
+                                wv::SketchStateTransfer::Import => {
+                                    wv::Event::ImportSketchState(path)
+                                }
+                            });
+                        }
+                        None => {
+                            log::info!(
+                                "{:?} file selection cancelled",
+                                transfer
+                            );
+                        }
+                    }
+                }
                 _ => {
                     let _ = ipc_sender.send(event);
                 }
@@ -119,25 +146,22 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
 
                 match event {
-                    wv::Event::LoadSketch {
-                        display_name,
-                        controls,
-                        perf_mode: sketch_perf_mode,
-                        sketch_width,
-                        ..
-                    } => {
-                        last_sketch_width = sketch_width;
-
-                        window.set_title(&format!("{} Controls", display_name));
+                    wv::Event::LoadSketch(sketch) => {
+                        last_sketch_width = sketch.sketch_width;
+
+                        window.set_title(&format!(
+                            "{} Controls",
+                            sketch.display_name
+                        ));
                         window.set_inner_size(LogicalSize::new(
                             DEFAULT_WIDTH,
-                            derive_gui_height(&controls)
+                            derive_gui_height(&sketch.controls)
                                 .max(MIN_SETTINGS_HEIGHT),
                         ));
 
-                        if !sketch_perf_mode {
+                        if !sketch.perf_mode {
                             window.set_outer_position(LogicalPosition::new(
-                                sketch_width,
+                                sketch.sketch_width,
                                 0,
                             ));
                         }
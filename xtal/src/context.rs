@@ -1,6 +1,18 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+use winit::keyboard::KeyCode;
+
+use crate::camera::{self, Camera2D};
+use crate::core::util::HashSet;
+
+/// Bit set in [`Context::mouse_buttons`] while the left button is held
+pub const MOUSE_LEFT: u32 = 1;
+/// Bit set in [`Context::mouse_buttons`] while the right button is held
+pub const MOUSE_RIGHT: u32 = 2;
+/// Bit set in [`Context::mouse_buttons`] while the middle button is held
+pub const MOUSE_MIDDLE: u32 = 4;
+
 pub struct Context {
     pub device: Arc<wgpu::Device>,
     pub queue: Arc<wgpu::Queue>,
@@ -8,6 +20,11 @@ pub struct Context {
     scale_factor: f64,
     frame_count: u64,
     start_time: Instant,
+    mouse_position_px: [f32; 2],
+    mouse_buttons: u32,
+    keys_down: HashSet<KeyCode>,
+    // Behind a lock so sketches can move it from `update` and `view`
+    camera: Mutex<Camera2D>,
 }
 
 impl Context {
@@ -24,6 +41,10 @@ impl Context {
             scale_factor,
             frame_count: 0,
             start_time: Instant::now(),
+            mouse_position_px: [0.0, 0.0],
+            mouse_buttons: 0,
+            keys_down: HashSet::default(),
+            camera: Mutex::new(Camera2D::default()),
         }
     }
 
@@ -47,6 +68,108 @@ impl Context {
         self.scale_factor
     }
 
+    /// Convert a point in logical (DPI-independent) pixels to physical
+    /// pixels on this window
+    pub fn logical_to_physical(&self, point: [f32; 2]) -> [f32; 2] {
+        logical_to_physical(point, self.scale_factor)
+    }
+
+    /// Convert a point in physical pixels, e.g. [`Self::mouse_position_px`],
+    /// to logical pixels on this window
+    pub fn physical_to_logical(&self, point: [f32; 2]) -> [f32; 2] {
+        physical_to_logical(point, self.scale_factor)
+    }
+
+    /// The whole window as `[x, y, width, height]` in logical pixels
+    pub fn window_rect(&self) -> [f32; 4] {
+        window_rect(self.window_size, self.scale_factor)
+    }
+
+    /// A rect of `size` logical pixels centered in the window, as
+    /// `[x, y, width, height]` in physical pixels (origin top-left), so it
+    /// covers the same share of the screen on Retina and normal displays
+    pub fn centered_rect(&self, size: [f32; 2]) -> [f32; 4] {
+        centered_rect(size, self.window_size, self.scale_factor)
+    }
+
+    /// Cursor position normalized to `[0, 1]` with the origin at the
+    /// bottom-left, matching the `uv` convention of the shader templates
+    pub fn mouse_position(&self) -> [f32; 2] {
+        normalize_mouse_position(self.mouse_position_px, self.window_size)
+    }
+
+    /// Cursor position in physical pixels with the origin at the top-left,
+    /// matching `@builtin(position)` in fragment shaders
+    pub fn mouse_position_px(&self) -> [f32; 2] {
+        self.mouse_position_px
+    }
+
+    /// Cursor position in the world space of [`Self::camera`]
+    pub fn mouse_world_position(&self) -> [f32; 2] {
+        let clip = camera::uv_to_clip(self.mouse_position());
+        self.camera().clip_to_world(clip, self.aspect())
+    }
+
+    /// Bitmask of held buttons; see [`MOUSE_LEFT`], [`MOUSE_RIGHT`], and
+    /// [`MOUSE_MIDDLE`]
+    pub fn mouse_buttons(&self) -> u32 {
+        self.mouse_buttons
+    }
+
+    pub fn set_mouse_position_px(&mut self, position: [f32; 2]) {
+        self.mouse_position_px = position;
+    }
+
+    pub fn set_mouse_button(&mut self, button: u32, pressed: bool) {
+        if pressed {
+            self.mouse_buttons |= button;
+        } else {
+            self.mouse_buttons &= !button;
+        }
+    }
+
+    /// Physical keys currently held in the main window. Runtime shortcuts
+    /// still fire for their keys unless the sketch lists them in
+    /// [`SketchConfig::captured_keys`](crate::sketch::SketchConfig)
+    pub fn keys_down(&self) -> &HashSet<KeyCode> {
+        &self.keys_down
+    }
+
+    pub fn is_key_down(&self, code: KeyCode) -> bool {
+        self.keys_down.contains(&code)
+    }
+
+    pub fn set_key_pressed(&mut self, code: KeyCode, pressed: bool) {
+        track_key(&mut self.keys_down, code, pressed);
+    }
+
+    /// Forget held keys, e.g. when the window loses focus and will not
+    /// receive the matching release events
+    pub fn clear_keys(&mut self) {
+        self.keys_down.clear();
+    }
+
+    /// The 2D camera published to shaders at `@group(0) @binding(2)`; see
+    /// [`crate::camera`]
+    pub fn camera(&self) -> Camera2D {
+        *self.camera.lock().unwrap()
+    }
+
+    pub fn set_camera(&self, camera: Camera2D) {
+        *self.camera.lock().unwrap() = camera;
+    }
+
+    /// Edit the camera in place, e.g. to pan it from `update`
+    pub fn update_camera(&self, edit: impl FnOnce(&mut Camera2D)) {
+        edit(&mut self.camera.lock().unwrap());
+    }
+
+    /// Window width over height
+    pub fn aspect(&self) -> f32 {
+        let [width, height] = self.resolution();
+        width / height.max(1.0)
+    }
+
     pub fn elapsed_seconds(&self) -> f32 {
         self.start_time.elapsed().as_secs_f32()
     }
@@ -59,3 +182,140 @@ impl Context {
         self.frame_count += 1;
     }
 }
+
+/// Map a physical-pixel cursor position (origin top-left) into `[0, 1]` with
+/// the origin at the bottom-left. Positions outside the window, which winit
+/// reports while dragging, are clamped to the edges.
+pub fn normalize_mouse_position(
+    position_px: [f32; 2],
+    window_size: [u32; 2],
+) -> [f32; 2] {
+    let width = window_size[0].max(1) as f32;
+    let height = window_size[1].max(1) as f32;
+    [
+        (position_px[0] / width).clamp(0.0, 1.0),
+        (1.0 - position_px[1] / height).clamp(0.0, 1.0),
+    ]
+}
+
+pub fn logical_to_physical(point: [f32; 2], scale_factor: f64) -> [f32; 2] {
+    let scale = scale_factor as f32;
+    [point[0] * scale, point[1] * scale]
+}
+
+pub fn physical_to_logical(point: [f32; 2], scale_factor: f64) -> [f32; 2] {
+    let scale = scale_factor.max(f64::EPSILON) as f32;
+    [point[0] / scale, point[1] / scale]
+}
+
+/// A physical window size as a logical `[x, y, width, height]` rect
+pub fn window_rect(window_size: [u32; 2], scale_factor: f64) -> [f32; 4] {
+    let [width, height] = physical_to_logical(
+        [window_size[0] as f32, window_size[1] as f32],
+        scale_factor,
+    );
+    [0.0, 0.0, width, height]
+}
+
+/// Center a rect of `size` logical pixels in a physical window, returning
+/// `[x, y, width, height]` in physical pixels with the origin top-left
+pub fn centered_rect(
+    size: [f32; 2],
+    window_size: [u32; 2],
+    scale_factor: f64,
+) -> [f32; 4] {
+    let [width, height] = logical_to_physical(size, scale_factor);
+    [
+        (window_size[0] as f32 - width) / 2.0,
+        (window_size[1] as f32 - height) / 2.0,
+        width,
+        height,
+    ]
+}
+
+fn track_key(keys_down: &mut HashSet<KeyCode>, code: KeyCode, pressed: bool) {
+    if pressed {
+        keys_down.insert(code);
+    } else {
+        keys_down.remove(&code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::KeyCode;
+
+    use super::{
+        centered_rect, logical_to_physical, normalize_mouse_position,
+        physical_to_logical, track_key, window_rect,
+    };
+    use crate::core::util::HashSet;
+
+    #[test]
+    fn normalizes_and_flips_against_window_size() {
+        let size = [800, 600];
+        assert_eq!(normalize_mouse_position([0.0, 0.0], size), [0.0, 1.0]);
+        assert_eq!(normalize_mouse_position([800.0, 600.0], size), [1.0, 0.0]);
+        assert_eq!(normalize_mouse_position([400.0, 150.0], size), [0.5, 0.75]);
+    }
+
+    #[test]
+    fn clamps_positions_outside_the_window() {
+        let size = [800, 600];
+        assert_eq!(normalize_mouse_position([-20.0, 700.0], size), [0.0, 0.0]);
+        assert_eq!(normalize_mouse_position([900.0, -5.0], size), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn uses_physical_pixels_on_high_dpi_windows() {
+        // A 2x display reports both the cursor and window size in physical
+        // pixels, so the scale factor cancels out.
+        let size = [1600, 1200];
+        assert_eq!(normalize_mouse_position([800.0, 300.0], size), [0.5, 0.75]);
+    }
+
+    #[test]
+    fn converts_between_logical_and_physical_pixels() {
+        assert_eq!(logical_to_physical([100.0, 50.0], 1.0), [100.0, 50.0]);
+        assert_eq!(physical_to_logical([100.0, 50.0], 1.0), [100.0, 50.0]);
+
+        assert_eq!(logical_to_physical([100.0, 50.0], 2.0), [200.0, 100.0]);
+        assert_eq!(physical_to_logical([200.0, 100.0], 2.0), [100.0, 50.0]);
+    }
+
+    #[test]
+    fn window_rect_is_in_logical_pixels() {
+        assert_eq!(window_rect([800, 600], 1.0), [0.0, 0.0, 800.0, 600.0]);
+        assert_eq!(window_rect([1600, 1200], 2.0), [0.0, 0.0, 800.0, 600.0]);
+    }
+
+    #[test]
+    fn centered_rect_covers_the_same_share_at_any_scale_factor() {
+        assert_eq!(
+            centered_rect([200.0, 100.0], [800, 600], 1.0),
+            [300.0, 250.0, 200.0, 100.0]
+        );
+        assert_eq!(
+            centered_rect([200.0, 100.0], [1600, 1200], 2.0),
+            [600.0, 500.0, 400.0, 200.0]
+        );
+    }
+
+    #[test]
+    fn pressing_and_releasing_a_key_updates_keys_down() {
+        let mut keys_down = HashSet::default();
+
+        track_key(&mut keys_down, KeyCode::KeyW, true);
+        track_key(&mut keys_down, KeyCode::ShiftLeft, true);
+        assert!(keys_down.contains(&KeyCode::KeyW));
+        assert!(keys_down.contains(&KeyCode::ShiftLeft));
+
+        // Repeats arrive as further presses and must not duplicate state
+        track_key(&mut keys_down, KeyCode::KeyW, true);
+        assert_eq!(keys_down.len(), 2);
+
+        track_key(&mut keys_down, KeyCode::KeyW, false);
+        assert!(!keys_down.contains(&KeyCode::KeyW));
+        assert!(keys_down.contains(&KeyCode::ShiftLeft));
+    }
+}
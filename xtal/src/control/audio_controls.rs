@@ -1,6 +1,7 @@
 use cpal::{Device, Stream, StreamConfig, traits::*};
 use std::error::Error;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -81,6 +82,7 @@ pub struct AudioControls {
     state: Arc<Mutex<State>>,
     device_name: Option<String>,
     stream: Option<Rc<Stream>>,
+    stream_error: Arc<AtomicBool>,
 }
 
 impl Default for AudioControls {
@@ -102,6 +104,7 @@ impl AudioControls {
             })),
             device_name: None,
             stream: None,
+            stream_error: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -141,6 +144,12 @@ impl AudioControls {
         self.is_active
     }
 
+    /// True once after the audio backend reports a stream error, such as the
+    /// device being unplugged
+    pub fn take_stream_error(&self) -> bool {
+        self.stream_error.swap(false, Ordering::AcqRel)
+    }
+
     pub fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let Some(device_name) = self.device_name.clone() else {
             warn!("Skipping AudioControls listener setup; no audio device.");
@@ -163,6 +172,8 @@ impl AudioControls {
         }
 
         let state = self.state.clone();
+        let stream_error = self.stream_error.clone();
+        stream_error.store(false, Ordering::Release);
         let stream = device.build_input_stream(
             &stream_config,
             move |data: &[f32], _: &_| {
@@ -202,7 +213,10 @@ impl AudioControls {
                     state.values.insert(name, mapped);
                 }
             },
-            move |err| error!("Error in audio stream: {}", err),
+            move |err| {
+                error!("Error in audio stream: {}", err);
+                stream_error.store(true, Ordering::Release);
+            },
             None,
         )?;
 
@@ -7,6 +7,7 @@ use std::fmt;
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
 
+use super::midi_controls::EncoderMode;
 use super::param_mod::ParamValue;
 use crate::core::prelude::*;
 
@@ -18,7 +19,7 @@ use crate::core::prelude::*;
 /// declared in yaml
 pub type ConfigFile = IndexMap<String, MaybeControlConfig>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum MaybeControlConfig {
     Control(ScriptedControlConfig),
@@ -26,7 +27,7 @@ pub enum MaybeControlConfig {
     Other(serde_yml::Value),
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug)]
 pub struct ScriptedControlConfig {
     #[serde(rename = "type")]
     pub control_type: ControlType,
@@ -67,6 +68,14 @@ pub enum ControlType {
     RoundRobin,
     #[serde(rename = "triangle")]
     Triangle,
+    #[serde(rename = "gate")]
+    Gate,
+    #[serde(rename = "euclidean")]
+    Euclidean,
+    #[serde(rename = "noise")]
+    Noise,
+    #[serde(rename = "spring")]
+    Spring,
     #[serde(rename = "snapshot_sequence")]
     SnapshotSequence,
 
@@ -87,6 +96,14 @@ pub struct Shared {
     // TODO: this really shouldn't be on shared because only UI controls use it
     #[serde(default, deserialize_with = "to_disabled_fn")]
     pub disabled: Option<DisabledConfig>,
+    /// Fraction of a slider, MIDI, or OSC control's range `randomize` may
+    /// move it from its current value. Defaults to the full range.
+    #[serde(default)]
+    pub randomize_amount: Option<f32>,
+    /// Beats a snapshot recall or randomize takes to move a slider, MIDI, or
+    /// OSC control, overriding the hub's transition time
+    #[serde(default)]
+    pub transition_time: Option<f32>,
 }
 
 //------------------------------------------------------------------------------
@@ -126,10 +143,34 @@ pub struct CheckboxConfig {
 pub struct SelectConfig {
     #[serde(flatten)]
     pub shared: Shared,
-    pub options: Vec<String>,
+    pub options: Vec<SelectOption>,
     pub default: String,
 }
 
+/// A `select` option, either a plain string or `{ value, weight }` where
+/// `weight` biases randomization toward that option. Plain options weigh 1.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SelectOption {
+    Plain(String),
+    Weighted { value: String, weight: f32 },
+}
+
+impl SelectOption {
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Plain(value) | Self::Weighted { value, .. } => value,
+        }
+    }
+
+    pub fn weight(&self) -> f32 {
+        match self {
+            Self::Plain(_) => 1.0,
+            Self::Weighted { weight, .. } => *weight,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct Separator {}
 
@@ -140,13 +181,13 @@ struct Separator {}
 #[derive(Deserialize, Debug)]
 #[serde(default)]
 pub struct MidiConfig {
-    #[allow(dead_code)]
     #[serde(flatten)]
-    shared: Shared,
+    pub shared: Shared,
     pub channel: u8,
     pub cc: u8,
     pub range: [f32; 2],
     pub default: f32,
+    pub encoder: EncoderMode,
 }
 
 impl Default for MidiConfig {
@@ -157,6 +198,7 @@ impl Default for MidiConfig {
             cc: 0,
             range: [0.0, 1.0],
             default: 0.0,
+            encoder: EncoderMode::Absolute,
         }
     }
 }
@@ -164,9 +206,8 @@ impl Default for MidiConfig {
 #[derive(Deserialize, Debug)]
 #[serde(default)]
 pub struct OscConfig {
-    #[allow(dead_code)]
     #[serde(flatten)]
-    shared: Shared,
+    pub shared: Shared,
     pub range: [f32; 2],
     pub default: f32,
 }
@@ -221,6 +262,23 @@ pub enum AnimationConfig {
     RandomSlewed(RandomSlewedConfig),
     RoundRobin(RoundRobinConfig),
     Triangle(TriangleConfig),
+    Gate(GateConfig),
+    Euclidean(EuclideanConfig),
+    Noise(NoiseConfig),
+    Spring(SpringConfig),
+}
+
+impl AnimationConfig {
+    /// Stem of the internal state (slew history, spring) the animation keeps
+    /// between frames, if it keeps any
+    pub fn state_stem(&self) -> Option<u64> {
+        match self {
+            Self::RandomSlewed(conf) => conf.stem,
+            Self::RoundRobin(conf) => conf.stem,
+            Self::Spring(conf) => conf.stem,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -434,6 +492,112 @@ impl Default for TriangleConfig {
     }
 }
 
+/// Outputs 1.0 during the non-zero `steps` of a `beats` long cycle and 0.0
+/// otherwise. See [`Animation::gate`](crate::motion::Animation::gate).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GateConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub beats: ParamValue,
+    pub steps: Vec<f32>,
+}
+
+impl Default for GateConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            beats: ParamValue::Cold(1.0),
+            steps: vec![1.0],
+        }
+    }
+}
+
+/// A gate that fires `pulses` evenly spread over `steps` per `beats` long
+/// cycle. See [`euclidean_rhythm`](crate::motion::euclidean_rhythm).
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct EuclideanConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub beats: ParamValue,
+    pub pulses: usize,
+    pub steps: usize,
+    pub rotation: usize,
+    /// Generated from the fields above when the script is loaded
+    #[serde(skip)]
+    pub pattern: Vec<bool>,
+}
+
+impl Default for EuclideanConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            beats: ParamValue::Cold(4.0),
+            pulses: 4,
+            steps: 16,
+            rotation: 0,
+            pattern: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct NoiseConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub beats: ParamValue,
+    pub range: [f32; 2],
+    pub octaves: u32,
+    /// Resolved when the control is populated: the script's `seed`, or like
+    /// [`RandomConfig::stem`], a hash of the mapping's name
+    #[serde(skip)]
+    pub seed: u64,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            beats: ParamValue::Cold(1.0),
+            range: [0.0, 1.0],
+            octaves: 1,
+            seed: 0,
+        }
+    }
+}
+
+/// Follows `target`, usually another control (`$name`), with a
+/// [`Spring`](crate::motion::Spring)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct SpringConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub target: ParamValue,
+    pub stiffness: ParamValue,
+    pub damping: ParamValue,
+    /// See [`RandomConfig`] for stem resolution docs.
+    pub stem: Option<u64>,
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            target: ParamValue::Cold(0.0),
+            stiffness: ParamValue::Cold(100.0),
+            damping: ParamValue::Cold(10.0),
+            stem: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SnapshotSequenceConfig {
     pub disabled: Option<DisabledConfig>,
@@ -608,6 +772,17 @@ pub struct EffectConfig {
 #[derive(Clone, Deserialize, Debug)]
 #[serde(rename_all = "snake_case", tag = "kind")]
 pub enum EffectKind {
+    Comparator {
+        #[serde(default = "default_comparison")]
+        op: String,
+        #[serde(default = "default_param_value_0_5")]
+        threshold: ParamValue,
+        #[serde(default = "default_epsilon")]
+        epsilon: f32,
+        #[serde(default = "default_false")]
+        pass_through: bool,
+    },
+
     Constrain {
         #[serde(default = "default_clamp_string")]
         mode: String,
@@ -615,6 +790,13 @@ pub enum EffectKind {
         range: (f32, f32),
     },
 
+    Delay {
+        #[serde(default = "default_param_value_1")]
+        beats: ParamValue,
+        #[serde(default = "default_delay_max_beats")]
+        max_beats: f32,
+    },
+
     Hysteresis {
         #[serde(default = "default_param_value_0_3")]
         lower_threshold: ParamValue,
@@ -645,6 +827,18 @@ pub enum EffectKind {
         range: (f32, f32),
     },
 
+    Min {
+        sources: Vec<String>,
+    },
+
+    Max {
+        sources: Vec<String>,
+    },
+
+    Average {
+        sources: Vec<String>,
+    },
+
     RingModulator {
         #[serde(default = "default_param_value_0")]
         mix: ParamValue,
@@ -941,6 +1135,15 @@ fn default_clamp_string() -> String {
 fn default_false() -> bool {
     false
 }
+fn default_comparison() -> String {
+    "gt".to_string()
+}
+fn default_epsilon() -> f32 {
+    0.000_1
+}
+fn default_delay_max_beats() -> f32 {
+    4.0
+}
 fn default_param_value_0_25() -> ParamValue {
     ParamValue::Cold(0.25)
 }
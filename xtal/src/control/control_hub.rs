@@ -6,8 +6,11 @@
 
 use log::{debug, error, info, trace, warn};
 use notify::{Event, RecursiveMode, Watcher};
-use rand::Rng;
+use rand::distr::weighted::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::collections::hash_map::DefaultHasher;
 use std::error::Error;
 use std::fs;
@@ -22,9 +25,15 @@ use yaml_merge_keys::merge_keys_serde_yml;
 use super::config::*;
 use super::dep_graph::{DepGraph, Node};
 use super::eval_cache::EvalCache;
-use super::param_mod::{FromColdParams, ParamValue, SetFromParam};
+use super::map_mode;
+use super::param_mod::{
+    FromColdParams, ParamValue, SetFromParam, suggested_param_range,
+};
+use super::script_export;
+use super::script_include;
 
 use crate::core::prelude::*;
+use crate::io::osc::SHARED_OSC_SENDER;
 use crate::time::frame_clock;
 use crate::{ternary, warn_once};
 
@@ -37,20 +46,42 @@ const WATCHER_CHANGE_INFO_DEBOUNCE: Duration = Duration::from_millis(150);
 
 #[derive(Debug)]
 struct UpdateState {
-    #[allow(dead_code)]
     watcher: notify::RecommendedWatcher,
     path: PathBuf,
     state: Arc<Mutex<Option<ConfigFile>>>,
 
+    /// Every file the script last resolved through `!include`, shared with
+    /// the watcher so edits to them reload the script too
+    includes: Arc<Mutex<Vec<PathBuf>>>,
+
+    /// Directories the watcher has been registered on
+    watched_dirs: HashSet<PathBuf>,
+
     /// Optimization to speed up checking for changes vs having to acquire a
     /// lock on the above state mutex
     has_changes: Arc<AtomicBool>,
+
+    /// The latest parse or populate failure, see `take_script_error`
+    error: Arc<Mutex<Option<String>>>,
 }
 
 #[derive(Debug)]
 struct SnapshotTransition {
     values: HashMap<String, (f32, f32)>,
     start_beat: f32,
+    /// When the last control lands and the transition ends
+    end_beat: f32,
+    /// Every control's own end beat when any has a `transition_time`
+    /// override; empty when they all land at `end_beat`
+    end_beats: HashMap<String, f32>,
+    easing: Easing,
+}
+
+// A leg of `morph_snapshots` waiting for the one before it to end
+#[derive(Debug)]
+struct MorphSegment {
+    id: String,
+    easing: Easing,
     end_beat: f32,
 }
 
@@ -58,6 +89,7 @@ struct SnapshotSequenceRuntime {
     sequence_length: f32,
     disabled: DisabledFn,
     last_phase: Option<f32>,
+    last_beat: Option<f32>,
 }
 
 impl Default for SnapshotSequenceRuntime {
@@ -66,6 +98,7 @@ impl Default for SnapshotSequenceRuntime {
             sequence_length: 0.0,
             disabled: None,
             last_phase: None,
+            last_beat: None,
         }
     }
 }
@@ -80,6 +113,26 @@ pub type Snapshots = HashMap<String, ControlValues>;
 
 pub type Exclusions = Vec<String>;
 
+/// One side of an A/B comparison, see [`ControlHub::stash`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbSlot {
+    A,
+    B,
+}
+
+impl AbSlot {
+    pub fn other(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 struct Callback(Box<dyn Fn()>);
 
 impl Callback {
@@ -111,6 +164,14 @@ pub struct ControlHub<T: TimingSource> {
     pub audio_controls: AudioControls,
     pub snapshots: Snapshots,
     pub midi_overrides_enabled: bool,
+    // Mappings in `midi_override_configs` that target an effect or animation
+    // param, as (mapping name, param name) keyed by effect or animation, so
+    // updates don't scan every override. See `sync_midi_override_configs`.
+    param_mappings: HashMap<String, Vec<(String, String)>>,
+    // Kept apart from `snapshots` so they never show up in (or clobber) the
+    // numbered slots and aren't saved with the sketch
+    ab_snapshots: [Option<ControlValues>; 2],
+    ab_active: Option<AbSlot>,
     animations: HashMap<String, (AnimationConfig, KeyframeSequence)>,
     modulations: HashMap<String, Vec<String>>,
     effects: RefCell<HashMap<String, (EffectConfig, Effect)>>,
@@ -120,16 +181,44 @@ pub struct ControlHub<T: TimingSource> {
     /// `var`** section for more info.
     vars: HashMap<String, String>,
     bypassed: HashMap<String, Option<f32>>,
+    randomize_amounts: HashMap<String, f32>,
+    // Per-control `transition_time` overrides in beats
+    transition_times: HashMap<String, f32>,
+    freeze_random: bool,
+    // Random and noise animation values pinned by recalling a snapshot taken
+    // while `freeze_random` was on
+    frozen_values: HashMap<String, f32>,
+    select_weights: HashMap<String, Vec<f32>>,
+    randomize_seed: Option<u64>,
+    bypass_all: bool,
+    solo: Option<String>,
+    // Uncommitted values shown while hovering UI controls, see `set_preview`
+    previews: HashMap<String, f32>,
+    // Every disabled-capable UI control's `disabled` state, refreshed once per
+    // update so callers don't re-evaluate the expressions per query
+    disabled_map: HashMap<String, bool>,
     dep_graph: DepGraph,
     eval_cache: EvalCache,
     update_state: Option<UpdateState>,
     active_transition: Option<SnapshotTransition>,
+    morph: VecDeque<MorphSegment>,
     transition_time: f32,
     snapshot_sequence: Option<SnapshotSequenceConfig>,
     snapshot_sequence_runtime: SnapshotSequenceRuntime,
     snapshot_ended_callbacks: Vec<Callback>,
     populated_callbacks: Vec<Callback>,
+    reload_callbacks: Vec<Callback>,
+    // Whether populate_controls has run before, i.e. the next run is a reload
+    populated: bool,
     preserve_values_on_reload: bool,
+    preserve_animation_state_on_reload: bool,
+    // Hash of each control's raw config as of the last population, used to
+    // find animations and effects a reload left unchanged
+    config_hashes: HashMap<String, u64>,
+    // The last populated script before `fragments` are layered over it
+    script: ConfigFile,
+    // Added with `merge`, in order, and reapplied whenever the script reloads
+    fragments: Vec<ConfigFile>,
 }
 
 impl<T: TimingSource> ControlHub<T> {
@@ -139,6 +228,7 @@ impl<T: TimingSource> ControlHub<T> {
             midi_controls: MidiControls::default(),
             midi_overrides: Arc::new(Mutex::new(HashMap::default())),
             midi_override_configs: HashMap::default(),
+            param_mappings: HashMap::default(),
             osc_controls: OscControls::default(),
             audio_controls: AudioControls::default(),
             animation: Animation::new(timing),
@@ -147,18 +237,37 @@ impl<T: TimingSource> ControlHub<T> {
             effects: RefCell::new(HashMap::default()),
             vars: HashMap::default(),
             bypassed: HashMap::default(),
+            randomize_amounts: HashMap::default(),
+            transition_times: HashMap::default(),
+            freeze_random: false,
+            frozen_values: HashMap::default(),
+            select_weights: HashMap::default(),
+            randomize_seed: None,
+            bypass_all: false,
+            solo: None,
+            previews: HashMap::default(),
+            disabled_map: HashMap::default(),
             eval_cache: EvalCache::default(),
             dep_graph: DepGraph::default(),
             update_state: None,
             snapshots: HashMap::default(),
+            ab_snapshots: [None, None],
+            ab_active: None,
             active_transition: None,
+            morph: VecDeque::new(),
             transition_time: 4.0,
             snapshot_sequence: None,
             snapshot_sequence_runtime: SnapshotSequenceRuntime::default(),
             snapshot_ended_callbacks: vec![],
             populated_callbacks: vec![],
+            reload_callbacks: vec![],
+            populated: false,
             midi_overrides_enabled: true,
             preserve_values_on_reload: true,
+            preserve_animation_state_on_reload: true,
+            config_hashes: HashMap::default(),
+            script: ConfigFile::default(),
+            fragments: vec![],
         };
 
         script
@@ -169,11 +278,13 @@ impl<T: TimingSource> ControlHub<T> {
             let config =
                 Self::parse_from_str(yaml).expect("Unable to parse yaml");
 
-            script
-                .populate_controls(&config)
-                .expect("Unable to populate controls");
+            if let Err(e) = script.populate_controls(&config) {
+                error!("Unable to populate controls: {}", e);
+            }
         }
 
+        script.refresh_disabled_map();
+
         script
     }
 
@@ -198,22 +309,75 @@ impl<T: TimingSource> ControlHub<T> {
 
         let file_content =
             fs::read_to_string(&path).expect("Unable to read file");
-        let initial_content_hash = content_hash(&file_content);
 
-        let mut script = Self::new(Some(&file_content), timing);
+        let mut script = Self::new(None, timing);
+        let error = Arc::new(Mutex::new(None));
+        let includes = Arc::new(Mutex::new(Vec::new()));
+        let populated =
+            Self::parse_script_with_includes(&file_content, Some(&path))
+                .and_then(|(config, resolved)| {
+                    if let Ok(mut guard) = includes.lock() {
+                        *guard = resolved;
+                    }
+                    script.populate_controls(&config)
+                });
+        if let Err(e) = populated {
+            error!("Unable to load control script: {}", e);
+            if let Ok(mut guard) = error.lock() {
+                *guard = Some(e.to_string());
+            }
+        }
+        let initial_content_hash = includes
+            .lock()
+            .map(|guard| script_hash(&file_content, &guard))
+            .ok();
         let has_changes = Arc::new(AtomicBool::new(false));
 
+        let watched_dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
         script.update_state = Some(UpdateState {
             watcher: Self::setup_watcher(
                 path.clone(),
                 state_clone,
                 has_changes.clone(),
-                Some(initial_content_hash),
+                error.clone(),
+                includes.clone(),
+                initial_content_hash,
             ),
             path,
             state: state.clone(),
+            includes,
+            watched_dirs: HashSet::from_iter([watched_dir]),
             has_changes,
+            error,
         });
+        script.watch_includes();
+
+        script
+    }
+
+    /// Instantiate a hub from a control script embedded in the binary, e.g.
+    /// with `include_str!`. Nothing is read from or watched on disk, so
+    /// [`Self::request_reload`] is a no-op and
+    /// [`Self::export_control_script`] returns an error.
+    pub fn from_str(yaml: &str, timing: T) -> Self {
+        Self::new(Some(yaml), timing)
+    }
+
+    /// Instantiate a hub from controls declared in code by a
+    /// [`ControlHubBuilder`], going through the same population as a script
+    pub(crate) fn from_config(config: &ConfigFile, timing: T) -> Self {
+        let mut script = Self::new(None, timing);
+
+        if let Err(e) = Self::validate_config_file(config)
+            .and_then(|_| script.populate_controls(config))
+        {
+            error!("Unable to populate controls: {}", e);
+        }
+
+        script.refresh_disabled_map();
 
         script
     }
@@ -227,12 +391,19 @@ impl<T: TimingSource> ControlHub<T> {
             None => name,
         };
 
+        if self.is_debug_bypassed(original_name) {
+            self.run_dependencies(original_name, current_frame);
+            return self.get_raw(original_name, current_frame);
+        }
+
         if let Some(Some(bypass)) = self.bypassed.get(original_name) {
             return *bypass;
         }
 
         self.run_dependencies(original_name, current_frame);
 
+        let preview = self.previews.get(original_name).copied();
+
         let midi_override_value = if self.midi_overrides_enabled {
             self.midi_overrides
                 .lock()
@@ -243,10 +414,13 @@ impl<T: TimingSource> ControlHub<T> {
             None
         };
 
-        let value = if let Some(value) =
+        let value = if let Some(value) = preview {
+            value
+        } else if let Some(value) =
             self.active_transition.as_ref().and_then(|t| {
                 self.get_transition_value(current_beat, original_name, t)
-            }) {
+            })
+        {
             value
         } else if let Some(value) = midi_override_value {
             value
@@ -273,18 +447,21 @@ impl<T: TimingSource> ControlHub<T> {
         transition: &SnapshotTransition,
     ) -> Option<f32> {
         let (from, to) = *transition.values.get(name)?;
+        let end_beat = transition
+            .end_beats
+            .get(name)
+            .copied()
+            .unwrap_or(transition.end_beat);
         if current_beat < transition.start_beat {
             return None;
         }
-        if current_beat >= transition.end_beat
-            || transition.start_beat == transition.end_beat
-        {
+        if current_beat >= end_beat || transition.start_beat == end_beat {
             return Some(to);
         }
-        let duration = transition.end_beat - transition.start_beat;
+        let duration = end_beat - transition.start_beat;
         let progress = current_beat - transition.start_beat;
         let t = (progress / duration).clamp(0.0, 1.0);
-        Some(lerp(from, to, t))
+        Some(lerp(from, to, transition.easing.apply(t)))
     }
 
     fn run_dependencies(&self, target_name: &str, current_frame: u32) {
@@ -331,9 +508,40 @@ impl<T: TimingSource> ControlHub<T> {
                 value,
                 self.get_raw(modulation_source.as_str(), current_frame),
             )
+        } else if let (
+            EffectKind::Min { sources }
+            | EffectKind::Max { sources }
+            | EffectKind::Average { sources },
+            Effect::Reducer(m),
+        ) = (&config.kind, &*effect)
+        {
+            let others: Vec<f32> = sources
+                .iter()
+                .map(|source| self.get_raw(source, current_frame))
+                .collect();
+            m.apply(value, &others)
         } else {
             match effect {
+                Effect::Comparator(m) => {
+                    self.update_effect_params(
+                        &mut *m,
+                        modulator,
+                        current_frame,
+                    );
+                    m.apply(value)
+                }
                 Effect::Constrain(m) => m.apply(value),
+                Effect::Delay(m) => {
+                    self.update_effect_params(
+                        &mut *m,
+                        modulator,
+                        current_frame,
+                    );
+                    // Frames advance at the render rate
+                    let frames_per_beat = self.animation.beats_to_seconds(1.0)
+                        * frame_clock::render_fps();
+                    m.apply(value, current_frame, frames_per_beat)
+                }
                 Effect::Hysteresis(m) => {
                     self.update_effect_params(
                         &mut *m,
@@ -390,6 +598,13 @@ impl<T: TimingSource> ControlHub<T> {
                     );
                     value
                 }
+                Effect::Reducer(_) => {
+                    warn_once!(
+                        "Unexpected Reducer branch for '{}'; bypassing effect",
+                        modulator
+                    );
+                    value
+                }
             }
         }
     }
@@ -412,9 +627,39 @@ impl<T: TimingSource> ControlHub<T> {
                 effect.set_from_param(param_name, value);
             }
         }
+
+        self.apply_param_overrides(node_name, |param_name, value| {
+            effect.set_from_param(param_name, value);
+        });
+    }
+
+    // Passes the values of MIDI mappings that target one of `node_name`'s
+    // params directly to `apply`
+    fn apply_param_overrides(
+        &self,
+        node_name: &str,
+        mut apply: impl FnMut(&str, f32),
+    ) {
+        if !self.midi_overrides_enabled {
+            return;
+        }
+        let Some(mappings) = self.param_mappings.get(node_name) else {
+            return;
+        };
+
+        let overrides = self.midi_overrides.lock().unwrap();
+        for (name, param_name) in mappings {
+            if let Some(value) = overrides.get(name) {
+                apply(param_name, *value);
+            }
+        }
     }
 
     fn get_raw(&self, name: &str, current_frame: u32) -> f32 {
+        if let Some(value) = self.previews.get(name) {
+            return *value;
+        }
+
         let is_dep = self.dep_graph.is_prerequisite(name);
 
         if is_dep {
@@ -440,6 +685,7 @@ impl<T: TimingSource> ControlHub<T> {
             .or_else(|| self.midi_controls.get_optional(name))
             .or_else(|| self.audio_controls.get_optional(name))
             .or_else(|| self.osc_controls.get_optional(name))
+            .or_else(|| self.frozen_values.get(name).copied())
             .or_else(|| {
                 self.animations.get(name).map(|(config, sequence)| {
                     match (config, sequence) {
@@ -538,6 +784,67 @@ impl<T: TimingSource> ControlHub<T> {
                                 conf.phase.as_float(),
                             )
                         }
+                        (
+                            AnimationConfig::Gate(conf),
+                            KeyframeSequence::None,
+                        ) => {
+                            let conf = self.resolve_animation_config_params(
+                                conf,
+                                name,
+                                current_frame,
+                            );
+                            let high = self
+                                .animation
+                                .gate(conf.beats.as_float(), &conf.steps);
+                            ternary!(high, 1.0, 0.0)
+                        }
+                        (
+                            AnimationConfig::Euclidean(conf),
+                            KeyframeSequence::None,
+                        ) => {
+                            let conf = self.resolve_animation_config_params(
+                                conf,
+                                name,
+                                current_frame,
+                            );
+                            let high = self.animation.pattern_gate(
+                                conf.beats.as_float(),
+                                &conf.pattern,
+                            );
+                            ternary!(high, 1.0, 0.0)
+                        }
+                        (
+                            AnimationConfig::Noise(conf),
+                            KeyframeSequence::None,
+                        ) => {
+                            let conf = self.resolve_animation_config_params(
+                                conf,
+                                name,
+                                current_frame,
+                            );
+                            self.animation.noise(
+                                conf.beats.as_float(),
+                                (conf.range[0], conf.range[1]),
+                                conf.octaves,
+                                conf.seed,
+                            )
+                        }
+                        (
+                            AnimationConfig::Spring(conf),
+                            KeyframeSequence::None,
+                        ) => {
+                            let conf = self.resolve_animation_config_params(
+                                conf,
+                                name,
+                                current_frame,
+                            );
+                            self.animation.spring(
+                                conf.target.as_float(),
+                                conf.stiffness.as_float(),
+                                conf.damping.as_float(),
+                                conf.stem.unwrap(),
+                            )
+                        }
                         _ => {
                             warn_once!(
                                 "Unsupported animation sequence for '{}'; defaulting to 0.0",
@@ -622,6 +929,10 @@ impl<T: TimingSource> ControlHub<T> {
             }
         }
 
+        self.apply_param_overrides(node_name, |param_name, value| {
+            config.set_from_param(param_name, value);
+        });
+
         config
     }
 
@@ -637,6 +948,138 @@ impl<T: TimingSource> ControlHub<T> {
             .unwrap_or_else(|| panic!("No breakpoints for name: {}", name))
     }
 
+    /// Range a MIDI mapping to `name` scales to: a slider's own range, or for
+    /// a `control.param` target (see [`map_mode::param_target`]) the range of
+    /// the slider feeding that param via `$`, falling back to the param's
+    /// suggested range. `None` if `name` can't be mapped.
+    pub fn mapping_range(&self, name: &str) -> Option<(f32, f32)> {
+        let Some((control, param)) = map_mode::param_target(name) else {
+            return self.ui_controls.slider_range(name);
+        };
+
+        let has_params = self.effects.borrow().contains_key(control)
+            || matches!(
+                self.animations.get(control),
+                Some((_, KeyframeSequence::None))
+            );
+        if !has_params {
+            return None;
+        }
+
+        let hot_source = self
+            .dep_graph
+            .node(control)
+            .and_then(|node| node.get(param))
+            .and_then(|value| match value {
+                ParamValue::Hot(source) => Some(source.as_str()),
+                ParamValue::Cold(_) => None,
+            });
+
+        hot_source
+            .and_then(|source| self.ui_controls.slider_range(source))
+            .or_else(|| suggested_param_range(param))
+    }
+
+    /// Hand [`Self::midi_override_configs`] to the MIDI controls and index the
+    /// ones targeting effect or animation params. Call after changing them.
+    pub fn sync_midi_override_configs(&mut self) {
+        self.param_mappings.clear();
+        for name in self.midi_override_configs.keys() {
+            if let Some((control, param)) = map_mode::param_target(name) {
+                self.param_mappings
+                    .entry(control.to_string())
+                    .or_default()
+                    .push((name.clone(), param.to_string()));
+            }
+        }
+
+        self.midi_controls
+            .set_override_configs(self.midi_override_configs.clone());
+    }
+
+    /// Add configs for the `control.param` targets in `mappings`, which saved
+    /// state can't resolve on its own since their ranges come from the hub,
+    /// then [sync](Self::sync_midi_override_configs) them. Each starts at its
+    /// restored override value, or the bottom of its range.
+    pub fn restore_param_mappings(&mut self, mappings: &map_mode::Mappings) {
+        for (name, (ch, cc)) in mappings {
+            if map_mode::param_target(name).is_none()
+                || self.midi_override_configs.contains_key(name)
+            {
+                continue;
+            }
+            let Some(range) = self.mapping_range(name) else {
+                continue;
+            };
+            let value = self
+                .midi_overrides
+                .lock()
+                .unwrap()
+                .get(name)
+                .copied()
+                .unwrap_or(range.0);
+            self.midi_override_configs.insert(
+                name.clone(),
+                MidiControlConfig::new((*ch as u8, *cc as u8), range, value),
+            );
+        }
+
+        self.sync_midi_override_configs();
+    }
+
+    /// Current value of every MIDI-mapped control, including in-flight
+    /// snapshot transitions, for echoing back to controllers with motorized
+    /// faders or LED rings. Param targets only report once a value has been
+    /// received or recalled for them.
+    pub fn midi_mapped_values(&self) -> Vec<(String, MidiControlConfig, f32)> {
+        let current_frame = frame_clock::frame_count();
+        let current_beat = self.animation.beats();
+
+        self.midi_override_configs
+            .iter()
+            .filter_map(|(name, config)| {
+                let value = match map_mode::param_target(name) {
+                    Some(_) => self
+                        .active_transition
+                        .as_ref()
+                        .and_then(|transition| {
+                            self.get_transition_value(
+                                current_beat,
+                                name,
+                                transition,
+                            )
+                        })
+                        .or_else(|| {
+                            self.midi_overrides
+                                .lock()
+                                .unwrap()
+                                .get(name)
+                                .copied()
+                        })?,
+                    None => self.current_snapshot_value(
+                        name,
+                        current_frame,
+                        current_beat,
+                    ),
+                };
+                Some((name.clone(), config.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Send an OSC message to external software, e.g. to trigger a sampler
+    /// when a particle hits an edge. Messages go through the runtime's
+    /// [`SHARED_OSC_SENDER`] to
+    /// [`DEFAULT_OSC_SEND_TARGET`](crate::io::osc::DEFAULT_OSC_SEND_TARGET)
+    /// unless its target is changed.
+    pub fn osc_send(
+        &self,
+        address: &str,
+        args: &[OscArg],
+    ) -> Result<(), String> {
+        SHARED_OSC_SENDER.send(address, args)
+    }
+
     pub fn bypassed(&self) -> HashMap<String, f32> {
         self.bypassed
             .iter()
@@ -644,6 +1087,66 @@ impl<T: TimingSource> ControlHub<T> {
             .collect()
     }
 
+    /// Debugging aid: make [`Self::get`] return every control's raw value,
+    /// skipping snapshot transitions, modulations and `bypass` overrides.
+    /// The script's `bypass` entries are untouched and apply again once
+    /// disabled.
+    pub fn set_bypass_all(&mut self, bypass_all: bool) {
+        self.bypass_all = bypass_all;
+    }
+
+    pub fn bypass_all(&self) -> bool {
+        self.bypass_all
+    }
+
+    /// Debugging aid: resolve only `name` (a control name or `var`) with its
+    /// full modulation chain while every other control returns its raw value
+    /// as with [`Self::set_bypass_all`]. `None` ends the solo.
+    pub fn solo(&mut self, name: Option<&str>) {
+        self.solo = name.map(|name| {
+            self.vars.get(name).map_or(name, String::as_str).to_string()
+        });
+    }
+
+    pub fn soloed(&self) -> Option<&str> {
+        self.solo.as_deref()
+    }
+
+    /// Show `value` in place of a control's stored value without committing
+    /// it, e.g. while hovering a slider position in the UI. [`Self::get`] and
+    /// anything modulated by the control see the preview, while snapshots,
+    /// randomization and saved state keep using the committed value. `None`
+    /// ends the preview and restores the committed value.
+    pub fn set_preview(&mut self, name: &str, value: Option<f32>) {
+        let name = self.vars.get(name).map_or(name, String::as_str);
+        match value {
+            Some(value) => {
+                self.previews.insert(name.to_string(), value);
+            }
+            None => {
+                self.previews.remove(name);
+            }
+        }
+        self.eval_cache.clear();
+    }
+
+    pub fn preview(&self, name: &str) -> Option<f32> {
+        let name = self.vars.get(name).map_or(name, String::as_str);
+        self.previews.get(name).copied()
+    }
+
+    pub fn clear_previews(&mut self) {
+        self.previews.clear();
+        self.eval_cache.clear();
+    }
+
+    fn is_debug_bypassed(&self, name: &str) -> bool {
+        match &self.solo {
+            Some(solo) => solo != name,
+            None => self.bypass_all,
+        }
+    }
+
     /// Helper to create snapshot (values only)
     fn create_snapshot(
         &mut self,
@@ -696,26 +1199,161 @@ impl<T: TimingSource> ControlHub<T> {
         snapshot
     }
 
+    // The current value of every random and noise animation when
+    // `freeze_random` is on, for snapshots to pin on recall
+    fn random_animation_values(&self) -> ControlValues {
+        if !self.freeze_random {
+            return ControlValues::default();
+        }
+
+        let current_frame = frame_clock::frame_count();
+        let current_beat = self.animation.beats();
+
+        self.animations
+            .keys()
+            .filter(|name| self.is_random_animation(name))
+            .map(|name| {
+                self.run_dependencies(name, current_frame);
+                let value = self.current_snapshot_value(
+                    name,
+                    current_frame,
+                    current_beat,
+                );
+                (name.clone(), ControlValue::from(value))
+            })
+            .collect()
+    }
+
+    /// When on, snapshots also capture the current value of every `random`,
+    /// `random_slewed` and `noise` animation, and recalling one pins those
+    /// animations to the captured values so a recalled look holds still.
+    /// Turning it off releases them to animate live again. Captured values
+    /// aren't written to disk with the sketch's other snapshot values.
+    pub fn set_freeze_random(&mut self, freeze: bool) {
+        self.freeze_random = freeze;
+        if !freeze {
+            self.frozen_values.clear();
+        }
+    }
+
+    pub fn freeze_random(&self) -> bool {
+        self.freeze_random
+    }
+
+    fn is_random_animation(&self, name: &str) -> bool {
+        matches!(
+            self.animations.get(name),
+            Some((
+                AnimationConfig::Random(_)
+                    | AnimationConfig::RandomSlewed(_)
+                    | AnimationConfig::Noise(_),
+                _
+            ))
+        )
+    }
+
     /// Create and store a snapshot for later recall
     pub fn take_snapshot(&mut self, id: &str) {
-        let snapshot = self.create_snapshot(Vec::new());
+        let mut snapshot = self.create_snapshot(Vec::new());
+        snapshot.extend(self.random_animation_values());
         self.snapshots.insert(id.to_string(), snapshot);
     }
 
     pub fn recall_snapshot(&mut self, id: &str) -> Result<(), String> {
+        let current_beat = self.animation.beats();
+        let transition_beats = self.transition_time.max(0.0);
+
+        self.start_snapshot_transition(
+            id,
+            current_beat,
+            current_beat + transition_beats,
+            Easing::Linear,
+        )?;
+        self.apply_transition_times();
+        self.morph.clear();
+
+        info!("Snapshot \"{}\" recalled", id);
+        Ok(())
+    }
+
+    /// Transition through the snapshots in `path` in order, spending an equal
+    /// share of `beats` on each and arriving at each one's values in turn,
+    /// starting from the current values. Each stop's [`Easing`] shapes the
+    /// leg leading to it. Unlike a `snapshot_sequence`, which jumps at fixed
+    /// positions, this is one continuous morph. Recalling a snapshot,
+    /// randomizing or starting another morph ends it early.
+    ///
+    /// ```rs
+    /// // 1 -> 2 -> 3 over 16 beats, easing into 2
+    /// let path = [
+    ///     ("1", Easing::Linear),
+    ///     ("2", Easing::EaseIn),
+    ///     ("3", Easing::Linear),
+    /// ];
+    /// hub.morph_snapshots(&path, 16.0)?;
+    /// ```
+    pub fn morph_snapshots(
+        &mut self,
+        path: &[(&str, Easing)],
+        beats: f32,
+    ) -> Result<(), String> {
+        let Some(((first, first_easing), rest)) = path.split_first() else {
+            return Err("Snapshot morph needs at least one snapshot".into());
+        };
+        if let Some((id, _)) = path
+            .iter()
+            .find(|(id, _)| !self.snapshots.contains_key(*id))
+        {
+            return Err(format!("No snapshot \"{}\"", id));
+        }
+
+        let start_beat = self.animation.beats();
+        let segment_beats = beats.max(0.0) / path.len() as f32;
+
+        self.start_snapshot_transition(
+            first,
+            start_beat,
+            start_beat + segment_beats,
+            first_easing.clone(),
+        )?;
+        self.morph = rest
+            .iter()
+            .enumerate()
+            .map(|(i, (id, easing))| MorphSegment {
+                id: id.to_string(),
+                easing: easing.clone(),
+                end_beat: start_beat + segment_beats * (i + 2) as f32,
+            })
+            .collect();
+
+        info!("Snapshot morph started over {} beats", beats);
+        Ok(())
+    }
+
+    fn start_snapshot_transition(
+        &mut self,
+        id: &str,
+        start_beat: f32,
+        end_beat: f32,
+        easing: Easing,
+    ) -> Result<(), String> {
         match self.snapshots.get(id) {
             Some(snapshot) => {
                 let current_frame = frame_clock::frame_count();
                 let current_beat = self.animation.beats();
-                let transition_beats = self.transition_time.max(0.0);
 
                 let mut transition = SnapshotTransition {
                     values: HashMap::default(),
-                    start_beat: current_beat,
-                    end_beat: current_beat + transition_beats,
+                    start_beat,
+                    end_beat,
+                    end_beats: HashMap::default(),
+                    easing,
                 };
 
                 for (name, value) in snapshot {
+                    if self.ui_controls.disabled(name) {
+                        continue;
+                    }
                     if self.midi_override_configs.contains_key(name) {
                         let from = self.current_snapshot_value(
                             name,
@@ -741,11 +1379,15 @@ impl<T: TimingSource> ControlHub<T> {
                                     .values
                                     .insert(name.to_string(), (from, *v));
                             }
-                            ControlValue::Bool(_) | ControlValue::String(_) => {
+                            ControlValue::Bool(_)
+                            | ControlValue::String(_)
+                            | ControlValue::Vec2(_)
+                            | ControlValue::Color(_) => {
                                 // Just update immediately since we can't
                                 // interpolate over a bool and interpolating
                                 // over static select options is likely to yield
-                                // undesired results
+                                // undesired results. Transitions only carry
+                                // scalars, so compound values jump as well
                                 self.ui_controls.set(name, value.clone());
                             }
                         }
@@ -754,6 +1396,8 @@ impl<T: TimingSource> ControlHub<T> {
 
                     if self.midi_controls.has(name)
                         || self.osc_controls.has(name)
+                        || (self.freeze_random
+                            && self.is_random_animation(name))
                     {
                         let from = self.current_snapshot_value(
                             name,
@@ -770,13 +1414,42 @@ impl<T: TimingSource> ControlHub<T> {
 
                 self.active_transition = Some(transition);
 
-                info!("Snapshot \"{}\" recalled", id);
                 Ok(())
             }
             None => Err(format!("No snapshot \"{}\"", id)),
         }
     }
 
+    // Lets controls with a `transition_time` override land on their own beat.
+    // Morph segments keep their shared timing so they don't get this.
+    fn apply_transition_times(&mut self) {
+        let Some(transition) = self.active_transition.as_mut() else {
+            return;
+        };
+        if !transition
+            .values
+            .keys()
+            .any(|name| self.transition_times.contains_key(name))
+        {
+            return;
+        }
+
+        let start_beat = transition.start_beat;
+        let default_end_beat = transition.end_beat;
+        let mut last_end_beat = start_beat;
+
+        for name in transition.values.keys() {
+            let end_beat = self
+                .transition_times
+                .get(name)
+                .map_or(default_end_beat, |beats| start_beat + beats);
+            transition.end_beats.insert(name.clone(), end_beat);
+            last_end_beat = last_end_beat.max(end_beat);
+        }
+
+        transition.end_beat = last_end_beat;
+    }
+
     fn current_snapshot_value(
         &self,
         name: &str,
@@ -799,9 +1472,75 @@ impl<T: TimingSource> ControlHub<T> {
         self.snapshots.clear()
     }
 
-    pub fn snapshot_sequence_enabled(&self) -> bool {
-        if self.snapshot_sequence.is_none() {
-            return false;
+    /// Store the current values as one side of an A/B comparison, e.g. stash
+    /// `A`, tweak, stash `B`, then [`Self::toggle_ab`] to flip between them.
+    /// A/B stashes are separate from the numbered snapshots.
+    pub fn stash(&mut self, slot: AbSlot) {
+        let mut snapshot = self.create_snapshot(Vec::new());
+        snapshot.extend(self.random_animation_values());
+        self.ab_snapshots[slot.index()] = Some(snapshot);
+        self.ab_active = Some(slot);
+    }
+
+    /// Instantly restore the values stashed in `slot`, skipping any
+    /// transition. Disabled controls are left alone as with
+    /// [`Self::recall_snapshot`].
+    pub fn recall_ab(&mut self, slot: AbSlot) -> Result<(), String> {
+        let Some(snapshot) = self.ab_snapshots[slot.index()].clone() else {
+            return Err(format!("Nothing stashed in {:?}", slot));
+        };
+
+        self.active_transition = None;
+        self.morph.clear();
+
+        for (name, value) in &snapshot {
+            if !self.ui_controls.disabled(name) {
+                self.apply_snapshot_value(name, value);
+            }
+        }
+
+        self.ab_active = Some(slot);
+        self.eval_cache.clear();
+
+        Ok(())
+    }
+
+    // Set whichever kind of control `name` is to a recalled or
+    // transitioned-to snapshot value
+    fn apply_snapshot_value(&mut self, name: &str, value: &ControlValue) {
+        if self.midi_override_configs.contains_key(name) {
+            self.midi_overrides
+                .lock()
+                .unwrap()
+                .insert(name.to_string(), value.as_float().unwrap_or(0.0));
+        } else if self.ui_controls.has(name) {
+            self.ui_controls.set(name, value.clone());
+        } else if self.midi_controls.has(name) {
+            self.midi_controls
+                .set(name, value.as_float().unwrap_or(0.0));
+        } else if self.osc_controls.has(name) {
+            self.osc_controls.set(name, value.as_float().unwrap_or(0.0));
+        } else if self.freeze_random && self.is_random_animation(name) {
+            self.frozen_values
+                .insert(name.to_string(), value.as_float().unwrap_or(0.0));
+        }
+    }
+
+    /// Recall whichever A/B side isn't active, returning it
+    pub fn toggle_ab(&mut self) -> Result<AbSlot, String> {
+        let slot = self.ab_active.map_or(AbSlot::A, AbSlot::other);
+        self.recall_ab(slot)?;
+        Ok(slot)
+    }
+
+    /// The A/B side last stashed or recalled
+    pub fn ab_slot(&self) -> Option<AbSlot> {
+        self.ab_active
+    }
+
+    pub fn snapshot_sequence_enabled(&self) -> bool {
+        if self.snapshot_sequence.is_none() {
+            return false;
         }
 
         self.snapshot_sequence_runtime
@@ -822,6 +1561,21 @@ impl<T: TimingSource> ControlHub<T> {
         self.transition_time = transition_time;
     }
 
+    /// Make [`Self::randomize`] reproducible: with a seed set, randomizing
+    /// from the same control values always lands on the same targets.
+    pub fn set_randomize_seed(&mut self, seed: u64) {
+        self.randomize_seed = Some(seed);
+    }
+
+    /// Return [`Self::randomize`] to its default nondeterministic behavior
+    pub fn clear_randomize_seed(&mut self) {
+        self.randomize_seed = None;
+    }
+
+    pub fn randomize_seed(&self) -> Option<u64> {
+        self.randomize_seed
+    }
+
     pub fn snapshot_keys_sorted(&self) -> Vec<String> {
         let mut keys: Vec<_> = self.snapshots.keys().cloned().collect();
         keys.sort();
@@ -843,21 +1597,40 @@ impl<T: TimingSource> ControlHub<T> {
             values: HashMap::default(),
             start_beat: current_beat,
             end_beat: current_beat + transition_beats,
+            end_beats: HashMap::default(),
+            easing: Easing::Linear,
+        };
+
+        let mut rng = match self.randomize_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
         };
 
-        for (name, value) in &self.create_snapshot(exclusions) {
+        // Draw in a stable order so a seed always maps to the same values
+        let mut snapshot: Vec<_> =
+            self.create_snapshot(exclusions).into_iter().collect();
+        snapshot.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (name, value) in &snapshot {
+            // Disabled controls are frozen. Checked live since their
+            // condition may have changed since the last update.
+            if self.ui_controls.disabled(name) {
+                continue;
+            }
             if let Some(config) = self.midi_override_configs.get(name) {
-                transition.values.insert(
-                    name.to_string(),
-                    (
-                        self.current_snapshot_value(
-                            name,
-                            current_frame,
-                            current_beat,
-                        ),
-                        rand::rng().random_range(config.min..=config.max),
-                    ),
+                let from = self.current_snapshot_value(
+                    name,
+                    current_frame,
+                    current_beat,
+                );
+                let to = self.random_target(
+                    &mut rng,
+                    name,
+                    from,
+                    (config.min, config.max),
+                    None,
                 );
+                transition.values.insert(name.to_string(), (from, to));
             } else if self.ui_controls.has(name) {
                 match value {
                     ControlValue::Float(_) => {
@@ -866,8 +1639,13 @@ impl<T: TimingSource> ControlHub<T> {
                         } = self.ui_controls.config(name).unwrap()
                         {
                             let from = self.get_raw(name, current_frame);
-                            let to =
-                                random_within_range_stepped(min, max, step);
+                            let to = self.random_target(
+                                &mut rng,
+                                name,
+                                from,
+                                (min, max),
+                                Some(step),
+                            );
                             transition
                                 .values
                                 .insert(name.to_string(), (from, to));
@@ -876,8 +1654,10 @@ impl<T: TimingSource> ControlHub<T> {
                     ControlValue::Bool(_) => {
                         // Just update immediately since we can't interpolate
                         // over a bool
-                        self.ui_controls
-                            .set(name, ControlValue::from(random_bool()));
+                        self.ui_controls.set(
+                            name,
+                            ControlValue::from(rng.random::<bool>()),
+                        );
                     }
                     ControlValue::String(_) => {
                         if let UiControlConfig::Select { options, .. } =
@@ -886,8 +1666,8 @@ impl<T: TimingSource> ControlHub<T> {
                             // Just update immediately since interpolating over
                             // static select options is likely to yield
                             // undesired results
-                            let index =
-                                rand::rng().random_range(0..options.len());
+                            let index = self
+                                .random_option_index(&mut rng, name, &options);
 
                             self.ui_controls.set(
                                 name,
@@ -895,25 +1675,32 @@ impl<T: TimingSource> ControlHub<T> {
                             );
                         }
                     }
+                    // No UI control holds compound values yet, so there's no
+                    // range to randomize within
+                    ControlValue::Vec2(_) | ControlValue::Color(_) => {}
                 }
             } else if self.midi_controls.has(name) {
                 let config = self.midi_controls.config(name).unwrap();
-                transition.values.insert(
-                    name.to_string(),
-                    (
-                        self.get_raw(name, current_frame),
-                        rand::rng().random_range(config.min..=config.max),
-                    ),
+                let from = self.get_raw(name, current_frame);
+                let to = self.random_target(
+                    &mut rng,
+                    name,
+                    from,
+                    (config.min, config.max),
+                    None,
                 );
+                transition.values.insert(name.to_string(), (from, to));
             } else if self.osc_controls.has(name) {
                 let config = self.osc_controls.config(name).unwrap();
-                transition.values.insert(
-                    name.to_string(),
-                    (
-                        self.get_raw(name, current_frame),
-                        rand::rng().random_range(config.min..=config.max),
-                    ),
+                let from = self.get_raw(name, current_frame);
+                let to = self.random_target(
+                    &mut rng,
+                    name,
+                    from,
+                    (config.min, config.max),
+                    None,
                 );
+                transition.values.insert(name.to_string(), (from, to));
             } else {
                 error!("Unsupported snapshot value: {} {:?}", name, value);
             }
@@ -921,6 +1708,77 @@ impl<T: TimingSource> ControlHub<T> {
 
         // Executes the transition immediately
         self.active_transition = Some(transition);
+        self.apply_transition_times();
+        self.morph.clear();
+    }
+
+    // Random value within `range`. A `randomize_amount` below 1 limits it to
+    // that fraction of the range either side of `from`. With a `step` the
+    // value is snapped without leaving that neighborhood.
+    fn random_target(
+        &self,
+        rng: &mut impl Rng,
+        name: &str,
+        from: f32,
+        range: (f32, f32),
+        step: Option<f32>,
+    ) -> f32 {
+        let (min, max) = (range.0.min(range.1), range.0.max(range.1));
+        let amount = self.randomize_amounts.get(name).copied().unwrap_or(1.0);
+        let span = (max - min) * amount;
+        let (low, high) = ((from - span).max(min), (from + span).min(max));
+        if low > high {
+            return from.clamp(min, max);
+        }
+
+        let value = rng.random_range(low..=high);
+        let Some(step) = step.filter(|step| *step > 0.0) else {
+            return value;
+        };
+
+        let stepped = (value / step).round() * step;
+        let stepped = if stepped > high {
+            stepped - step
+        } else if stepped < low {
+            stepped + step
+        } else {
+            stepped
+        };
+        ternary!((low..=high).contains(&stepped), stepped, from)
+    }
+
+    // Index into `options`, favoring heavier options when the select was
+    // configured with weights and uniform otherwise
+    fn random_option_index(
+        &self,
+        rng: &mut impl Rng,
+        name: &str,
+        options: &[String],
+    ) -> usize {
+        let weighted = self
+            .select_weights
+            .get(name)
+            .filter(|weights| weights.len() == options.len())
+            .and_then(|weights| WeightedIndex::new(weights).ok());
+
+        match weighted {
+            Some(weighted) => rng.sample(weighted),
+            None => rng.random_range(0..options.len()),
+        }
+    }
+
+    fn insert_randomize_amount(&mut self, name: &str, shared: &Shared) {
+        if let Some(amount) = shared.randomize_amount {
+            self.randomize_amounts
+                .insert(name.to_string(), amount.clamp(0.0, 1.0));
+        }
+    }
+
+    fn insert_transition_time(&mut self, name: &str, shared: &Shared) {
+        if let Some(beats) = shared.transition_time {
+            self.transition_times
+                .insert(name.to_string(), beats.max(0.0));
+        }
     }
 
     pub fn update(&mut self) {
@@ -936,9 +1794,13 @@ impl<T: TimingSource> ControlHub<T> {
         if let Some(config) = new_config {
             if let Err(e) = self.populate_controls(&config) {
                 error!("Failed to apply new configuration: {:?}", e);
+                self.set_script_error(e.to_string());
             }
+            self.watch_includes();
         }
 
+        self.refresh_disabled_map();
+
         let sequence_disabled = self
             .snapshot_sequence_runtime
             .disabled
@@ -946,40 +1808,37 @@ impl<T: TimingSource> ControlHub<T> {
             .is_some_and(|disabled| disabled(&self.ui_controls));
 
         let current_beat = self.animation.beats();
-        if self
+        self.cancel_transition_before(current_beat);
+
+        // Loops so a frame that skips past several morph segments lands in
+        // the right one
+        while let Some(transition) = self
             .active_transition
-            .as_ref()
-            .is_some_and(|transition| current_beat < transition.start_beat)
+            .take_if(|transition| current_beat >= transition.end_beat)
         {
-            self.active_transition = None;
-            self.snapshot_sequence_runtime.last_phase = None;
-        }
+            for (name, (_from, to)) in &transition.values {
+                self.apply_snapshot_value(name, &ControlValue::Float(*to));
+            }
 
-        if let Some(transition) = &self.active_transition {
-            if current_beat >= transition.end_beat {
-                for (name, (_from, to)) in &transition.values {
-                    if self.midi_override_configs.contains_key(name) {
-                        self.midi_overrides
-                            .lock()
-                            .unwrap()
-                            .insert(name.to_string(), *to);
-                        continue;
-                    } else if self.ui_controls.has(name) {
-                        let value = ControlValue::Float(*to);
-                        self.ui_controls.set(name, value);
-                        continue;
-                    } else if self.midi_controls.has(name) {
-                        self.midi_controls.set(name, *to);
-                        continue;
-                    } else if self.osc_controls.has(name) {
-                        self.osc_controls.set(name, *to);
-                        continue;
+            // The next segment starts where this one was due to end rather
+            // than at the current beat so the morph keeps its timing
+            if let Some(segment) = self.morph.pop_front() {
+                match self.start_snapshot_transition(
+                    &segment.id,
+                    transition.end_beat,
+                    segment.end_beat,
+                    segment.easing,
+                ) {
+                    Ok(()) => continue,
+                    Err(e) => {
+                        error!("Snapshot morph ended early: {}", e);
+                        self.morph.clear();
                     }
                 }
-                self.active_transition = None;
-                for callback in &self.snapshot_ended_callbacks {
-                    callback.call();
-                }
+            }
+
+            for callback in &self.snapshot_ended_callbacks {
+                callback.call();
             }
         }
 
@@ -990,10 +1849,56 @@ impl<T: TimingSource> ControlHub<T> {
         }
     }
 
+    /// Whether the UI control `name` is disabled as of the last
+    /// [`Self::update`]. Unknown and non-UI controls are never disabled.
+    pub fn disabled(&self, name: &str) -> bool {
+        self.disabled_map.get(name).copied().unwrap_or(false)
+    }
+
+    /// The `disabled` state of every slider, checkbox and select as of the
+    /// last [`Self::update`]. Disabled controls keep their value: randomize
+    /// and snapshot recalls leave them alone until they are enabled again.
+    pub fn disabled_map(&self) -> &HashMap<String, bool> {
+        &self.disabled_map
+    }
+
+    fn refresh_disabled_map(&mut self) {
+        self.disabled_map = self
+            .ui_controls
+            .config_refs()
+            .values()
+            .filter(|config| !config.is_separator())
+            .map(|config| {
+                let disabled = config.is_disabled(&self.ui_controls);
+                (config.name().to_string(), disabled)
+            })
+            .collect();
+    }
+
+    // A transition that starts after `beat` is stale once the transport has
+    // moved backwards past it (transport reset or scrub).
+    fn cancel_transition_before(&mut self, beat: f32) {
+        if self
+            .active_transition
+            .as_ref()
+            .is_some_and(|transition| beat < transition.start_beat)
+        {
+            self.active_transition = None;
+            self.morph.clear();
+            self.snapshot_sequence_runtime.last_phase = None;
+        }
+    }
+
     fn update_snapshot_sequences(&mut self) {
         let current_beat = self.animation.beats();
         let beat_epsilon =
             (1.0 / self.animation.beats_to_frames(1.0)).max(0.000_001);
+        let previous_beat = self
+            .snapshot_sequence_runtime
+            .last_beat
+            .replace(current_beat);
+        let loop_wrapped = self.animation.loop_region().is_some()
+            && previous_beat.is_some_and(|beat| current_beat < beat);
 
         let Some(sequence) = self.snapshot_sequence.as_ref() else {
             return;
@@ -1014,6 +1919,26 @@ impl<T: TimingSource> ControlHub<T> {
         let end = sequence.stages.len().saturating_sub(1);
         let stages = &sequence.stages[..end];
 
+        // Jumping from loop out back to loop in isn't a pass through the rest
+        // of the sequence, so restore whichever stage is in effect at the new
+        // phase instead of firing every stage "crossed" on the way around.
+        if loop_wrapped {
+            let stage_id = stages
+                .iter()
+                .rev()
+                .find(|stage| stage.position() <= phase + beat_epsilon)
+                .and_then(|stage| stage.snapshot())
+                .map(|stage_id| stage_id.to_string());
+
+            let Some(stage_id) = stage_id else {
+                return;
+            };
+            if let Err(e) = self.recall_snapshot(&stage_id) {
+                warn!("snapshot_sequence stage {} failed: {}", stage_id, e);
+            }
+            return;
+        }
+
         if previous_phase.is_none() {
             for stage in stages {
                 let stage_position = stage.position();
@@ -1097,6 +2022,16 @@ impl<T: TimingSource> ControlHub<T> {
         self.populated_callbacks.push(Callback(Box::new(callback)));
     }
 
+    /// Like [`Self::register_populated_callback`] but skips the initial
+    /// population, firing only when the control script is reloaded. Useful
+    /// for re-running expensive setup that depends on the script.
+    pub fn register_reload_callback<F>(&mut self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.reload_callbacks.push(Callback(Box::new(callback)));
+    }
+
     pub fn float(&self, name: &str) -> f32 {
         self.get(name)
     }
@@ -1106,6 +2041,10 @@ impl<T: TimingSource> ControlHub<T> {
     pub fn bool_as_f32(&self, name: &str) -> f32 {
         self.ui_controls.bool_as_f32(name)
     }
+    /// True while a `gate` (or any control) is high at the current beat
+    pub fn get_bool_at_beat(&self, name: &str) -> bool {
+        self.get(name) > 0.0
+    }
     pub fn string(&self, name: &str) -> String {
         self.ui_controls.string(name)
     }
@@ -1126,6 +2065,118 @@ impl<T: TimingSource> ControlHub<T> {
         self.animation.beats()
     }
 
+    /// See [`Animation::set_frozen`]
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.animation.set_frozen(frozen);
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.animation.frozen()
+    }
+
+    pub fn loop_region(&self) -> Option<(f32, f32)> {
+        self.animation.loop_region()
+    }
+
+    // Code-first counterparts of the script animation types. `beats` is the
+    // length of one cycle in beats of the hub's timing source, and `phase`
+    // is a fraction of that cycle, exactly as in a control script.
+
+    /// 0 to 1 and back over `beats`. See [`Animation::tri`].
+    pub fn tri(&self, beats: f32) -> f32 {
+        self.animation.tri(beats)
+    }
+
+    /// Same as a `triangle` script control. See [`Animation::triangle`].
+    pub fn triangle(&self, beats: f32, range: (f32, f32), phase: f32) -> f32 {
+        self.animation.triangle(beats, range, phase)
+    }
+
+    /// Rises from 0 to 1 over `beats`, then jumps back. See
+    /// [`Animation::ramp`].
+    pub fn saw(&self, beats: f32) -> f32 {
+        self.animation.ramp(beats)
+    }
+
+    /// Same as a `ramp` script control. See [`Animation::ramp_plus`].
+    pub fn ramp(&self, beats: f32, range: (f32, f32), phase: f32) -> f32 {
+        self.animation.ramp_plus(beats, range, phase)
+    }
+
+    /// A new value in `range` every `beats`, like a `random` script control
+    /// without a delay. See [`Animation::random`].
+    pub fn random(&self, beats: f32, range: (f32, f32), stem: u64) -> f32 {
+        self.animation.random(beats, range, 0.0, stem)
+    }
+
+    /// Like [`Self::random`], gliding between values. See
+    /// [`Animation::random_slewed`].
+    pub fn random_slewed(
+        &self,
+        beats: f32,
+        range: (f32, f32),
+        slew: f32,
+        stem: u64,
+    ) -> f32 {
+        self.animation.random_slewed(beats, range, slew, 0.0, stem)
+    }
+
+    /// Same as a `noise` script control. See [`Animation::noise`].
+    pub fn noise(
+        &self,
+        beats: f32,
+        range: (f32, f32),
+        octaves: u32,
+        seed: u64,
+    ) -> f32 {
+        self.animation.noise(beats, range, octaves, seed)
+    }
+
+    /// Same as a `round_robin` script control. See
+    /// [`Animation::round_robin`].
+    pub fn round_robin(
+        &self,
+        beats: f32,
+        values: &[f32],
+        slew: f32,
+        stem: u64,
+    ) -> f32 {
+        self.animation.round_robin(beats, values, slew, stem)
+    }
+
+    /// Same as a `gate` script control, as a bool. See [`Animation::gate`].
+    pub fn gate(&self, beats: f32, steps: &[f32]) -> bool {
+        self.animation.gate(beats, steps)
+    }
+
+    /// Write the current UI control values into the `default:` fields of the
+    /// control script this hub was loaded from and the files it `!include`s,
+    /// keeping comments, ordering and `$` parameter references intact.
+    /// Returns the script's path.
+    pub fn export_control_script(&self) -> Result<PathBuf, String> {
+        let Some(update_state) = self.update_state.as_ref() else {
+            return Err("hub was not loaded from a control script".to_string());
+        };
+        let path = &update_state.path;
+        script_export::export_defaults(path, &self.ui_controls.values())?;
+        Ok(path.clone())
+    }
+
+    /// See [`Animation::beats_to_frames`]
+    pub fn beats_to_frames(&self, beats: f32) -> f32 {
+        self.animation.beats_to_frames(beats)
+    }
+
+    /// See [`Animation::beats_to_seconds`]
+    pub fn beats_to_seconds(&self, beats: f32) -> f32 {
+        self.animation.beats_to_seconds(beats)
+    }
+
+    /// See [`Animation::seconds_to_beats`]
+    pub fn seconds_to_beats(&self, seconds: f32) -> f32 {
+        self.animation.seconds_to_beats(seconds)
+    }
+
     pub fn var_values(&self) -> HashMap<String, f32> {
         self.vars
             .keys()
@@ -1133,30 +2184,115 @@ impl<T: TimingSource> ControlHub<T> {
             .collect()
     }
 
+    /// Re-read the control script from disk. Does nothing for hubs that
+    /// weren't loaded from a file.
     pub fn request_reload(&self) {
         if let Some(update_state) = self.update_state.as_ref() {
             info!(
                 "manual control config reload requested: {}",
                 update_state.path.display()
             );
-            if let Ok(config) = Self::parse_from_path(&update_state.path) {
-                if let Ok(mut guard) = update_state.state.lock() {
-                    *guard = Some(config);
+            match Self::parse_from_path(&update_state.path) {
+                Ok((config, includes)) => {
+                    if let Ok(mut guard) = update_state.state.lock() {
+                        *guard = Some(config);
+                    }
+                    if let Ok(mut guard) = update_state.includes.lock() {
+                        *guard = includes;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "manual control config reload failed to parse: {}",
+                        update_state.path.display()
+                    );
+                    self.set_script_error(e.to_string());
                 }
-            } else {
-                warn!(
-                    "manual control config reload failed to parse: {}",
-                    update_state.path.display()
-                );
             }
             update_state.has_changes.store(true, Ordering::Release);
         }
     }
 
+    /// The most recent failure to parse or apply the control script,
+    /// including unresolvable `!include`s, since the last call. The previous
+    /// controls stay in place when a reload fails.
+    pub fn take_script_error(&self) -> Option<String> {
+        let update_state = self.update_state.as_ref()?;
+        update_state.error.lock().ok()?.take()
+    }
+
+    fn set_script_error(&self, message: String) {
+        if let Some(update_state) = self.update_state.as_ref()
+            && let Ok(mut guard) = update_state.error.lock()
+        {
+            *guard = Some(message);
+        }
+    }
+
+    /// Layer the controls of another control script over this hub's. A
+    /// control whose name is new is added after the existing ones; one that
+    /// shares a name replaces the existing control in place and starts over
+    /// from its new default. Every other control keeps its current value.
+    /// Unlike `!include`, this happens at runtime, and merged controls
+    /// survive reloads of the hub's own script. A fragment that fails to
+    /// parse or validate changes nothing.
+    pub fn merge(&mut self, yaml: &str) -> Result<(), String> {
+        let fragment = Self::parse_from_str(yaml).map_err(|e| e.to_string())?;
+        let replaced: Vec<String> = fragment.keys().cloned().collect();
+        self.fragments.push(fragment);
+
+        let script = self.script.clone();
+        let merged = self.with_fragments(&script);
+        if let Err(e) = Self::validate_config_file(&merged) {
+            self.fragments.pop();
+            return Err(e.to_string());
+        }
+
+        // Replaced controls start over from their new defaults, but keep the
+        // current values to put back if populating fails
+        let mut ui_values = vec![];
+        let mut midi_values = vec![];
+        let mut osc_values = vec![];
+        for id in &replaced {
+            self.ui_controls.with_values_mut(|values| {
+                ui_values.extend(values.remove_entry(id));
+            });
+            self.midi_controls.with_values_mut(|values| {
+                midi_values.extend(values.remove_entry(id));
+            });
+            self.osc_controls.with_values_mut(|values| {
+                osc_values.extend(values.remove_entry(id));
+            });
+        }
+
+        if let Err(e) = self.populate_controls(&script) {
+            self.fragments.pop();
+            self.ui_controls
+                .with_values_mut(|values| values.extend(ui_values));
+            self.midi_controls
+                .with_values_mut(|values| values.extend(midi_values));
+            self.osc_controls
+                .with_values_mut(|values| values.extend(osc_values));
+            return Err(e.to_string());
+        }
+
+        self.refresh_disabled_map();
+
+        Ok(())
+    }
+
     pub fn set_preserve_values_on_reload(&mut self, preserve: bool) {
         self.preserve_values_on_reload = preserve;
     }
 
+    /// When enabled (the default), animations and effects whose config is
+    /// unchanged by a reload keep their internal state, such as slew
+    /// history, spring motion and effect buffers, instead of starting over.
+    /// Changed or removed ones always start fresh.
+    pub fn set_preserve_animation_state_on_reload(&mut self, preserve: bool) {
+        self.preserve_animation_state_on_reload = preserve;
+    }
+
     /// Abstracts around a common pattern where you have a checkbox, slider, and
     /// animation that are all connected as follows:
     ///
@@ -1207,23 +2343,107 @@ impl<T: TimingSource> ControlHub<T> {
     }
 
     fn parse_from_str(yaml_str: &str) -> Result<ConfigFile, Box<dyn Error>> {
+        Self::parse_script(yaml_str, None)
+    }
+
+    fn parse_from_path(
+        path: &PathBuf,
+    ) -> Result<(ConfigFile, Vec<PathBuf>), Box<dyn Error>> {
+        let file_content = fs::read_to_string(path)?;
+        Self::parse_script_with_includes(&file_content, Some(path))
+    }
+
+    // `script_path` is where `yaml_str` was read from, used to resolve
+    // `!include`s relative to it.
+    fn parse_script(
+        yaml_str: &str,
+        script_path: Option<&Path>,
+    ) -> Result<ConfigFile, Box<dyn Error>> {
+        Self::parse_script_with_includes(yaml_str, script_path)
+            .map(|(config, _)| config)
+    }
+
+    // Same as `parse_script`, also returning the files pulled in through
+    // `!include`
+    fn parse_script_with_includes(
+        yaml_str: &str,
+        script_path: Option<&Path>,
+    ) -> Result<(ConfigFile, Vec<PathBuf>), Box<dyn Error>> {
         let raw_config = serde_yml::from_str(yaml_str)?;
-        let merged_config = merge_keys_serde_yml(raw_config)?;
+        let (included_config, includes) =
+            script_include::resolve_includes(raw_config, script_path)?;
+        let merged_config = merge_keys_serde_yml(included_config)?;
         let config: ConfigFile = serde_yml::from_value(merged_config)?;
         Self::validate_config_file(&config)?;
-        Ok(config)
+        Ok((config, includes))
     }
 
-    fn parse_from_path(path: &PathBuf) -> Result<ConfigFile, Box<dyn Error>> {
-        let file_content = fs::read_to_string(path)?;
-        let config = Self::parse_from_str(&file_content)?;
-        Ok(config)
+    // Register the directory of every `!include`d file with the watcher so
+    // editing a fragment reloads the script that includes it
+    fn watch_includes(&mut self) {
+        let Some(update_state) = self.update_state.as_mut() else {
+            return;
+        };
+        let includes = match update_state.includes.lock() {
+            Ok(guard) => guard.clone(),
+            Err(_) => return,
+        };
+        for include in includes {
+            let Some(dir) = include.parent() else {
+                continue;
+            };
+            if update_state.watched_dirs.contains(dir) {
+                continue;
+            }
+            match update_state.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    info!(
+                        "watching control script include '{}'",
+                        include.display()
+                    );
+                    update_state.watched_dirs.insert(dir.to_path_buf());
+                }
+                Err(e) => warn!(
+                    "unable to watch control script include '{}': {}",
+                    include.display(),
+                    e
+                ),
+            }
+        }
+    }
+
+    fn with_fragments(&self, script: &ConfigFile) -> ConfigFile {
+        let mut merged = script.clone();
+        for fragment in &self.fragments {
+            for (id, config) in fragment {
+                merged.insert(id.clone(), config.clone());
+            }
+        }
+        merged
     }
 
     fn populate_controls(
         &mut self,
-        control_configs: &ConfigFile,
+        script: &ConfigFile,
     ) -> Result<(), Box<dyn Error>> {
+        let control_configs = &self.with_fragments(script);
+        let is_reload = self.populated;
+        self.populated = true;
+
+        // Resolve parameter dependencies before touching any state so a
+        // script with a cycle is rejected and the current controls stay live
+        let mut dep_graph = DepGraph::default();
+        for (id, maybe_config) in control_configs {
+            if let MaybeControlConfig::Control(config) = maybe_config {
+                let hot_params = self.find_hot_params(&config.config);
+                if !hot_params.is_empty() {
+                    dep_graph.insert_node(id, hot_params);
+                }
+            }
+        }
+        dep_graph.build_graph()?;
+        self.script = script.clone();
+
         let current_values: ControlValues = if self.preserve_values_on_reload {
             self.ui_controls.values().clone()
         } else {
@@ -1252,16 +2472,51 @@ impl<T: TimingSource> ControlHub<T> {
                 HashMap::default()
             };
 
+        let config_hashes: HashMap<String, u64> = control_configs
+            .iter()
+            .filter_map(|(id, maybe_config)| match maybe_config {
+                MaybeControlConfig::Control(config) => {
+                    Some((id.clone(), config_hash(&config.config)))
+                }
+                MaybeControlConfig::Other(_) => None,
+            })
+            .collect();
+        let previous_hashes =
+            std::mem::replace(&mut self.config_hashes, config_hashes);
+        let preserve = self.preserve_animation_state_on_reload;
+        let unchanged: HashSet<String> = self
+            .config_hashes
+            .iter()
+            .filter(|(id, hash)| {
+                preserve && previous_hashes.get(*id) == Some(*hash)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for (id, (config, _)) in self.animations.drain() {
+            if unchanged.contains(&id) {
+                continue;
+            }
+            if let Some(stem) = config.state_stem() {
+                self.animation.forget_state(stem);
+            }
+        }
+        let mut previous_effects = self.effects.take();
+
         self.ui_controls = UiControls::default();
-        self.animations.clear();
         self.snapshot_sequence = None;
         self.snapshot_sequence_runtime = SnapshotSequenceRuntime::default();
         self.modulations.clear();
         self.vars.clear();
         self.bypassed.clear();
-        self.dep_graph.clear();
+        self.previews.clear();
+        self.randomize_amounts.clear();
+        self.transition_times.clear();
+        self.select_weights.clear();
+        self.dep_graph = dep_graph;
         self.eval_cache.clear();
         self.active_transition = None;
+        self.morph.clear();
         self.midi_override_configs.clear();
         self.midi_overrides.lock().unwrap().clear();
 
@@ -1271,11 +2526,6 @@ impl<T: TimingSource> ControlHub<T> {
                 MaybeControlConfig::Other(_) => continue,
             };
 
-            let hot_params = self.find_hot_params(&config.config);
-            if !hot_params.is_empty() {
-                self.dep_graph.insert_node(id, hot_params);
-            }
-
             if let Some(v) = config.config.get("var").and_then(|v| v.as_str()) {
                 self.vars.insert(v.to_string(), id.to_string());
             }
@@ -1290,6 +2540,18 @@ impl<T: TimingSource> ControlHub<T> {
                 self.bypassed.insert(id.to_string(), bypass);
             }
 
+            // `mod: [a, b]` expands to the same chain `type: mod` declares.
+            // Any control type can carry it, so it's read here rather than
+            // from each type's config.
+            if let Some(modulators) = config.config.get("mod") {
+                let modulators: Vec<String> =
+                    serde_yml::from_value(modulators.clone())?;
+                self.modulations
+                    .entry(id.to_string())
+                    .or_default()
+                    .extend(modulators);
+            }
+
             match config.control_type {
                 ControlType::Slider => {
                     let mut conf: SliderConfig =
@@ -1301,6 +2563,8 @@ impl<T: TimingSource> ControlHub<T> {
                         .unwrap_or(conf.default);
 
                     let disabled = Self::extract_disabled_fn(&mut conf.shared);
+                    self.insert_randomize_amount(id, &conf.shared);
+                    self.insert_transition_time(id, &conf.shared);
 
                     let slider = UiControlConfig::Slider {
                         name: id.to_string(),
@@ -1343,10 +2607,27 @@ impl<T: TimingSource> ControlHub<T> {
 
                     let disabled = Self::extract_disabled_fn(&mut conf.shared);
 
+                    if conf.options.iter().any(|option| {
+                        matches!(option, SelectOption::Weighted { .. })
+                    }) {
+                        Self::validate_select_weights(id, &conf.options)?;
+                        self.select_weights.insert(
+                            id.to_string(),
+                            conf.options
+                                .iter()
+                                .map(SelectOption::weight)
+                                .collect(),
+                        );
+                    }
+
                     let select = UiControlConfig::Select {
                         name: id.to_string(),
                         value: value.to_string(),
-                        options: conf.options,
+                        options: conf
+                            .options
+                            .iter()
+                            .map(|option| option.value().to_string())
+                            .collect(),
                         disabled,
                     };
 
@@ -1364,6 +2645,9 @@ impl<T: TimingSource> ControlHub<T> {
                     let conf: OscConfig =
                         serde_yml::from_value(config.config.clone())?;
 
+                    self.insert_randomize_amount(id, &conf.shared);
+                    self.insert_transition_time(id, &conf.shared);
+
                     let existing_value = if osc_values.contains_key(id) {
                         osc_values.get(id)
                     } else {
@@ -1387,6 +2671,9 @@ impl<T: TimingSource> ControlHub<T> {
                     let conf: MidiConfig =
                         serde_yml::from_value(config.config.clone())?;
 
+                    self.insert_randomize_amount(id, &conf.shared);
+                    self.insert_transition_time(id, &conf.shared);
+
                     let existing_value = if midi_values.contains_key(id) {
                         midi_values.get(id)
                     } else {
@@ -1397,7 +2684,8 @@ impl<T: TimingSource> ControlHub<T> {
                         (conf.channel, conf.cc),
                         (conf.range[0], conf.range[1]),
                         conf.default,
-                    );
+                    )
+                    .with_encoder(conf.encoder);
 
                     self.midi_controls.add(id, midi_control);
 
@@ -1499,19 +2787,69 @@ impl<T: TimingSource> ControlHub<T> {
                         ),
                     );
                 }
-                ControlType::SnapshotSequence => {
-                    let mut conf: SnapshotSequenceConfig =
+                ControlType::Gate => {
+                    let conf: GateConfig =
                         serde_yml::from_value(config.config.clone())?;
 
-                    self.snapshot_sequence_runtime.disabled =
-                        Self::extract_snapshot_sequence_disabled_fn(
-                            &mut conf.disabled,
-                        );
-                    self.snapshot_sequence_runtime.sequence_length = conf
-                        .stages
-                        .last()
-                        .map_or(0.0, |stage| stage.position());
-                    self.snapshot_sequence = Some(conf);
+                    self.animations.insert(
+                        id.to_string(),
+                        (AnimationConfig::Gate(conf), KeyframeSequence::None),
+                    );
+                }
+                ControlType::Euclidean => {
+                    let mut conf: EuclideanConfig =
+                        serde_yml::from_value(config.config.clone())?;
+                    conf.pattern = euclidean_rhythm(
+                        conf.pulses,
+                        conf.steps,
+                        conf.rotation,
+                    );
+
+                    self.animations.insert(
+                        id.to_string(),
+                        (
+                            AnimationConfig::Euclidean(conf),
+                            KeyframeSequence::None,
+                        ),
+                    );
+                }
+                ControlType::Noise => {
+                    let mut conf: NoiseConfig =
+                        serde_yml::from_value(config.config.clone())?;
+                    conf.seed = match config.config.get("seed") {
+                        Some(seed) => serde_yml::from_value(seed.clone())?,
+                        None => hash_stem(id),
+                    };
+
+                    self.animations.insert(
+                        id.to_string(),
+                        (AnimationConfig::Noise(conf), KeyframeSequence::None),
+                    );
+                }
+                ControlType::Spring => {
+                    let mut conf: SpringConfig =
+                        serde_yml::from_value(config.config.clone())?;
+                    conf.stem =
+                        Some(conf.stem.unwrap_or_else(|| hash_stem(id)));
+
+                    self.animations.insert(
+                        id.to_string(),
+                        (AnimationConfig::Spring(conf), KeyframeSequence::None),
+                    );
+                }
+                ControlType::SnapshotSequence => {
+                    let mut conf: SnapshotSequenceConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    self.snapshot_sequence_runtime.disabled =
+                        Self::extract_snapshot_sequence_disabled_fn(
+                            &mut conf.disabled,
+                        );
+                    self.snapshot_sequence_runtime.sequence_length = conf
+                        .stages
+                        .last()
+                        .map_or(0.0, |stage| stage.position());
+                    self.snapshot_sequence = Some(conf);
                 }
                 ControlType::Modulation => {
                     let conf: ModulationConfig =
@@ -1527,6 +2865,19 @@ impl<T: TimingSource> ControlHub<T> {
                         serde_yml::from_value(config.config.clone())?;
 
                     let effect = match conf.kind {
+                        EffectKind::Comparator {
+                            ref op,
+                            epsilon,
+                            pass_through,
+                            ..
+                        } => {
+                            let mut effect =
+                                Comparator::from_cold_params(&conf);
+                            effect.comparison = Comparison::from_str(op)?;
+                            effect.epsilon = epsilon;
+                            effect.pass_through = pass_through;
+                            Effect::Comparator(effect)
+                        }
                         EffectKind::Constrain { ref mode, range } => {
                             Effect::Constrain(
                                 Constrain::try_from((
@@ -1537,6 +2888,11 @@ impl<T: TimingSource> ControlHub<T> {
                                 .unwrap_or(Constrain::None),
                             )
                         }
+                        EffectKind::Delay { max_beats, .. } => {
+                            let mut effect = Delay::from_cold_params(&conf);
+                            effect.max_beats = max_beats;
+                            Effect::Delay(effect)
+                        }
                         EffectKind::Hysteresis { pass_through, .. } => {
                             let mut effect =
                                 Hysteresis::from_cold_params(&conf);
@@ -1558,6 +2914,15 @@ impl<T: TimingSource> ControlHub<T> {
                             effect.set_range(range);
                             Effect::Quantizer(effect)
                         }
+                        EffectKind::Min { .. } => {
+                            Effect::Reducer(Reducer::new(Reduction::Min))
+                        }
+                        EffectKind::Max { .. } => {
+                            Effect::Reducer(Reducer::new(Reduction::Max))
+                        }
+                        EffectKind::Average { .. } => {
+                            Effect::Reducer(Reducer::new(Reduction::Average))
+                        }
                         EffectKind::RingModulator { range, .. } => {
                             let mut effect =
                                 RingModulator::from_cold_params(&conf);
@@ -1583,17 +2948,21 @@ impl<T: TimingSource> ControlHub<T> {
                         }
                     };
 
-                    self.effects
-                        .borrow_mut()
-                        .insert(id.to_string(), (conf.clone(), effect));
+                    let entry = match previous_effects.remove(id) {
+                        Some(previous) if unchanged.contains(id) => previous,
+                        _ => (conf.clone(), effect),
+                    };
+                    self.effects.borrow_mut().insert(id.to_string(), entry);
                 }
             }
         }
 
-        self.dep_graph.build_graph();
         trace!("node_graph: {:#?}", self.dep_graph);
-        self.midi_controls
-            .set_override_configs(self.midi_override_configs.clone());
+        self.sync_midi_override_configs();
+
+        let mut frozen_values = std::mem::take(&mut self.frozen_values);
+        frozen_values.retain(|name, _| self.is_random_animation(name));
+        self.frozen_values = frozen_values;
 
         if !self.osc_controls.is_active {
             self.osc_controls
@@ -1610,6 +2979,11 @@ impl<T: TimingSource> ControlHub<T> {
         for callback in &self.populated_callbacks {
             callback.call();
         }
+        if is_reload {
+            for callback in &self.reload_callbacks {
+                callback.call();
+            }
+        }
 
         self.ui_controls.mark_changed();
 
@@ -1636,6 +3010,33 @@ impl<T: TimingSource> ControlHub<T> {
         }
     }
 
+    fn validate_select_weights(
+        name: &str,
+        options: &[SelectOption],
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(option) = options.iter().find(|option| {
+            !option.weight().is_finite() || option.weight() < 0.0
+        }) {
+            return Err(format!(
+                "select {} option {} has invalid weight {}",
+                name,
+                option.value(),
+                option.weight()
+            )
+            .into());
+        }
+
+        if options.iter().all(|option| option.weight() == 0.0) {
+            return Err(format!(
+                "select {} needs at least one option with a positive weight",
+                name
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
     fn validate_snapshot_sequence_config(
         name: &str,
         conf: &SnapshotSequenceConfig,
@@ -1782,6 +3183,8 @@ impl<T: TimingSource> ControlHub<T> {
         path: PathBuf,
         state: Arc<Mutex<Option<ConfigFile>>>,
         has_changes: Arc<AtomicBool>,
+        error: Arc<Mutex<Option<String>>>,
+        includes: Arc<Mutex<Vec<PathBuf>>>,
         initial_content_hash: Option<u64>,
     ) -> notify::RecommendedWatcher {
         let path_to_watch = path.clone();
@@ -1818,7 +3221,15 @@ impl<T: TimingSource> ControlHub<T> {
                 event.paths
             );
 
-            if !config_file_changed(&event, &path) {
+            let current_includes = includes
+                .lock()
+                .map(|guard| guard.clone())
+                .unwrap_or_default();
+            if !config_file_changed(&event, &path)
+                && !current_includes
+                    .iter()
+                    .any(|include| config_file_changed(&event, include))
+            {
                 return;
             }
             debug!(
@@ -1839,7 +3250,7 @@ impl<T: TimingSource> ControlHub<T> {
                 }
             };
 
-            let new_hash = content_hash(&file_content);
+            let new_hash = script_hash(&file_content, &current_includes);
             if let Ok(mut guard) = last_loaded_hash.lock() {
                 if guard.is_some_and(|existing_hash| existing_hash == new_hash)
                 {
@@ -1873,8 +3284,12 @@ impl<T: TimingSource> ControlHub<T> {
                 *guard = Some(new_hash);
             }
 
-            match Self::parse_from_str(&file_content) {
-                Ok(new_config) => {
+            match Self::parse_script_with_includes(&file_content, Some(&path))
+            {
+                Ok((new_config, new_includes)) => {
+                    if let Ok(mut guard) = includes.lock() {
+                        *guard = new_includes;
+                    }
                     if let Ok(mut guard) = state.lock() {
                         *guard = Some(new_config);
                         let already_pending =
@@ -1923,6 +3338,9 @@ impl<T: TimingSource> ControlHub<T> {
                         path.display(),
                         e
                     );
+                    if let Ok(mut guard) = error.lock() {
+                        *guard = Some(e.to_string());
+                    }
                 }
             }
         })
@@ -1936,6 +3354,79 @@ impl<T: TimingSource> ControlHub<T> {
     }
 }
 
+impl ControlHub<Timing> {
+    /// Move the transport to `beats` when the hub runs on manual timing, e.g.
+    /// from a timeline slider. Scrubbing backwards cancels transitions that
+    /// haven't started yet, and snapshot-sequence stages between the old and
+    /// new position are skipped rather than all fired at once. A frozen hub
+    /// stays frozen at the new position.
+    pub fn set_beats(&mut self, beats: f32) -> Result<(), String> {
+        if !matches!(self.animation.timing, Timing::Manual(_)) {
+            return Err("beats can only be set with manual timing".to_string());
+        }
+        if !beats.is_finite() {
+            return Err(format!("invalid beat position: {}", beats));
+        }
+
+        let frozen = self.animation.frozen();
+        self.animation.set_frozen(false);
+        self.animation.timing.set_external_beats(beats.max(0.0));
+        self.animation.set_frozen(frozen);
+
+        self.cancel_transition_before(self.animation.beats());
+        self.snapshot_sequence_runtime.last_phase = None;
+        self.snapshot_sequence_runtime.last_beat = None;
+
+        Ok(())
+    }
+
+    /// Loop the `(in, out)` beat range, see [`Animation::set_loop_region`].
+    /// Only frame and manual timing can loop; external MIDI/OSC transports
+    /// own their position, so setting a region there is rejected. Clearing
+    /// with `None` always succeeds.
+    pub fn set_loop_region(
+        &mut self,
+        region: Option<(f32, f32)>,
+    ) -> Result<(), String> {
+        let loopable = matches!(
+            self.animation.timing,
+            Timing::Frame(_) | Timing::Manual(_)
+        );
+        if region.is_some() && !loopable {
+            return Err(
+                "loop regions are ignored while following an external \
+                 transport"
+                    .to_string(),
+            );
+        }
+        self.animation.set_loop_region(region)
+    }
+
+    /// Put a freshly built hub back at `beats`, e.g. to keep a sketch's place
+    /// across a reload. Frame timing moves the shared frame clock's elapsed
+    /// time and frame count. Manual timing scrubs. External MIDI/OSC
+    /// transports own their position, so restoring there is rejected.
+    pub fn restore_beats(&mut self, beats: f32) -> Result<(), String> {
+        match self.animation.timing {
+            Timing::Frame(_) => {
+                if !beats.is_finite() {
+                    return Err(format!("invalid beat position: {}", beats));
+                }
+                let seconds = self.beats_to_seconds(beats.max(0.0));
+                frame_clock::set_elapsed_seconds(seconds);
+                // Frame-driven effects and videos read the frame count
+                let frames = seconds * frame_clock::render_fps();
+                frame_clock::set_frame_count(frames.round() as u32);
+                Ok(())
+            }
+            Timing::Manual(_) => self.set_beats(beats),
+            _ => {
+                Err("an external transport owns the beat position".to_string())
+            }
+        }
+    }
+}
+
 fn config_file_changed(event: &Event, target: &Path) -> bool {
     if !matches!(
         event.kind,
@@ -1983,9 +3474,22 @@ fn hash_stem(name: &str) -> u64 {
     hasher.finish()
 }
 
-fn content_hash(content: &str) -> u64 {
+// Covers the script's `!include`s too so an edit to one of them isn't skipped
+// as an unchanged script
+fn script_hash(content: &str, includes: &[PathBuf]) -> u64 {
     let mut hasher = DefaultHasher::new();
     content.hash(&mut hasher);
+    for include in includes {
+        fs::read_to_string(include)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn config_hash(config: &serde_yml::Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.hash(&mut hasher);
     hasher.finish()
 }
 
@@ -2007,7 +3511,12 @@ fn apply_bias(value: f32, bias: f32, range: [f32; 2]) -> f32 {
 mod tests {
     use super::*;
 
+    use crate::core::testing::assert_close;
     use crate::motion::animation::animation_tests::{BPM, init};
+    use crate::runtime::serialization::{
+        SerializableSketchState, TransitorySketchState,
+    };
+    use crate::runtime::web_view::Mappings;
     use serial_test::serial;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -2016,15 +3525,168 @@ mod tests {
         ControlHub::new(Some(yaml), FrameTiming::new(Bpm::new(BPM)))
     }
 
-    fn assert_close(actual: f32, expected: f32, label: &str) {
-        let epsilon = 0.000_1;
-        assert!(
-            (actual - expected).abs() <= epsilon,
-            "{}: expected {}, got {}",
-            label,
-            expected,
-            actual
+    // Like `init` but with the transport paused at exactly `beat`, so time
+    // spent between assertions doesn't move animations or transitions
+    fn hold(beat: f32) {
+        init(beat);
+        frame_clock::set_paused(true);
+        frame_clock::set_elapsed_seconds(beat * 60.0 / BPM);
+    }
+
+    fn write_script(dir: &str, name: &str, yaml: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join("xtal-control-hub-tests")
+            .join(dir)
+            .join(name);
+        fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+        fs::write(&path, yaml).expect("write script");
+        path
+    }
+
+    #[test]
+    fn test_included_fragment_controls_are_populated() {
+        write_script(
+            "include",
+            "shared/colors.yaml",
+            r#"
+hue:
+  type: slider
+  default: 0.75
+
+lightness:
+  type: slider
+  default: 0.5
+"#,
+        );
+        let path = write_script(
+            "include",
+            "sketch.yaml",
+            r#"
+<<: !include shared/colors.yaml
+
+size:
+  type: slider
+  default: 0.25
+"#,
+        );
+
+        let hub = ControlHub::from_path(path, FrameTiming::new(Bpm::new(BPM)));
+
+        assert_close(hub.get("hue"), 0.75, "hue");
+        assert_close(hub.get("lightness"), 0.5, "lightness");
+        assert_close(hub.get("size"), 0.25, "size");
+    }
+
+    #[test]
+    fn test_editing_an_included_file_reloads_the_script() {
+        let colors = r#"
+hue:
+  type: slider
+  default: 0.75
+"#;
+        let fragment =
+            write_script("include-watch", "shared/colors.yaml", colors);
+        let path = write_script(
+            "include-watch",
+            "sketch.yaml",
+            "<<: !include shared/colors.yaml\n",
+        );
+        let mut hub =
+            ControlHub::from_path(path, FrameTiming::new(Bpm::new(BPM)));
+        assert!(hub.ui_controls.config("saturation").is_none());
+
+        fs::write(
+            &fragment,
+            format!("{colors}\nsaturation:\n  type: slider\n  default: 0.5\n"),
+        )
+        .expect("write fragment");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while hub.ui_controls.config("saturation").is_none()
+            && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(20));
+            hub.update();
+        }
+
+        assert_close(hub.get("saturation"), 0.5, "saturation");
+        assert_close(hub.get("hue"), 0.75, "hue");
+    }
+
+    #[test]
+    fn test_include_cycle_is_an_error() {
+        write_script("cycle", "b.yaml", "<<: !include a.yaml\n");
+        let path = write_script("cycle", "a.yaml", "<<: !include b.yaml\n");
+
+        let err = ControlHub::<FrameTiming>::parse_from_path(&path)
+            .expect_err("cyclic include should fail")
+            .to_string();
+
+        assert!(err.contains("include cycle"), "{}", err);
+        assert!(err.contains("a.yaml -> "), "{}", err);
+    }
+
+    #[test]
+    fn test_include_failure_is_reported() {
+        let path = write_script(
+            "missing-include",
+            "sketch.yaml",
+            "<<: !include shared/missing.yaml\n",
+        );
+        let hub = ControlHub::from_path(path, FrameTiming::new(Bpm::new(BPM)));
+
+        let err = hub.take_script_error().expect("include error reported");
+        assert!(err.contains("missing.yaml"), "{}", err);
+        assert_eq!(hub.take_script_error(), None);
+
+        hub.request_reload();
+        assert!(hub.take_script_error().is_some());
+
+        write_script(
+            "missing-include",
+            "sketch.yaml",
+            "size:\n  type: slider\n  default: 0.25\n",
+        );
+        hub.request_reload();
+        assert_eq!(hub.take_script_error(), None);
+    }
+
+    #[test]
+    fn test_include_without_script_path_is_an_error() {
+        let err = ControlHub::<FrameTiming>::parse_from_str(
+            "<<: !include shared/colors.yaml\n",
+        )
+        .expect_err("embedded include should fail")
+        .to_string();
+
+        assert!(err.contains("without a control script path"), "{}", err);
+    }
+
+    #[test]
+    fn test_embedded_yaml_resolves_controls_without_a_file() {
+        let mut hub = ControlHub::from_str(
+            r#"
+size:
+  type: slider
+  var: aw
+  default: 0.25
+
+enabled:
+  type: checkbox
+  default: true
+"#,
+            FrameTiming::new(Bpm::new(BPM)),
         );
+
+        assert!(hub.update_state.is_none());
+        assert_close(hub.get("size"), 0.25, "size");
+        assert_close(hub.get("aw"), 0.25, "aw var");
+        assert!(hub.bool("enabled"));
+
+        hub.request_reload();
+        hub.update();
+        assert_close(hub.get("size"), 0.25, "size after reload");
+        assert!(hub.export_control_script().is_err());
     }
 
     #[test]
@@ -2092,75 +3754,1075 @@ test_mod:
 
     #[test]
     #[serial]
-    fn test_parameter_modulation_breakpoint() {
+    fn test_gate_is_high_during_active_steps() {
         let controls = create_instance(
             r#"
-slider:
-  type: slider
-  default: 40
+trigger:
+  type: gate
+  beats: 4
+  steps: [1, 0, 0, 1]
 
-automate:
-  type: automate
-  breakpoints:
-    - position: 0
-      value: $slider
-      kind: step
+hats:
+  type: gate
+  beats: 1
+  steps: [0, 0, 1]
+            "#,
+        );
+
+        for (beats, expected) in
+            [(0.0, 1.0), (0.5, 1.0), (1.0, 0.0), (2.5, 0.0), (3.5, 1.0)]
+        {
+            init(beats);
+            assert_eq!(controls.get("trigger"), expected, "beats: {}", beats);
+        }
+
+        init(0.5);
+        assert!(!controls.get_bool_at_beat("hats"));
+        init(0.75);
+        assert!(controls.get_bool_at_beat("hats"));
+        init(1.25);
+        assert!(!controls.get_bool_at_beat("hats"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_lfo_shorthands_match_animation_and_scripts() {
+        let controls = create_instance(
+            r#"
+tri:
+  type: triangle
+  beats: 2
+  range: [-1, 1]
+  phase: 0.25
+
+saw:
+  type: ramp
+  beats: 4
+  range: [0, 10]
+  phase: 0.5
+
+random:
+  type: random
+  beats: 1
+  range: [0, 100]
+  stem: 99
 
+noise:
+  type: noise
+  beats: 3
+  octaves: 2
+  seed: 5
             "#,
         );
+        let animation = &controls.animation;
+
+        for beats in [0.0, 0.5, 1.25, 3.0, 7.75] {
+            hold(beats);
+            assert_eq!(controls.tri(2.0), animation.tri(2.0));
+            assert_eq!(controls.saw(4.0), animation.ramp(4.0));
+            assert_eq!(
+                controls.triangle(2.0, (-1.0, 1.0), 0.25),
+                controls.get("tri")
+            );
+            assert_eq!(
+                controls.ramp(4.0, (0.0, 10.0), 0.5),
+                controls.get("saw")
+            );
+            assert_eq!(
+                controls.random(1.0, (0.0, 100.0), 99),
+                controls.get("random")
+            );
+            assert_eq!(
+                controls.noise(3.0, (0.0, 1.0), 2, 5),
+                controls.get("noise")
+            );
+            assert_eq!(
+                controls.gate(1.0, &[1.0, 0.0]),
+                animation.gate(1.0, &[1.0, 0.0])
+            );
+        }
+    }
 
-        init(0.0);
-        assert_eq!(
-            controls.get("automate"),
-            40.0,
-            "[automate.0.value]<-[$slider@40]"
+    #[test]
+    #[serial]
+    fn test_euclidean_gate_follows_bjorklund_pattern() {
+        let controls = create_instance(
+            r#"
+tresillo:
+  type: euclidean
+  beats: 2
+  pulses: 3
+  steps: 8
+            "#,
         );
+
+        // 8 steps over 2 beats, x..x..x.
+        let expected = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        for (step, value) in expected.iter().enumerate() {
+            init(step as f32 * 0.25);
+            assert_eq!(controls.get("tresillo"), *value, "step {}", step);
+        }
     }
 
     #[test]
     #[serial]
-    fn test_snapshot() {
-        let mut controls = create_instance(
+    fn test_noise_is_deterministic_per_seed_and_within_range() {
+        let controls = create_instance(
             r#"
 a:
-  type: slider
-  default: 10
+  type: noise
+  beats: 2
+  range: [10, 20]
+  octaves: 3
+  seed: 7
+
 b:
-  type: midi
-  default: 20
-c:
-  type: osc
-  default: 30
+  type: noise
+  beats: 2
+  range: [10, 20]
+  octaves: 3
+  seed: 7
 
+c:
+  type: noise
+  beats: 2
+  range: [10, 20]
+  octaves: 3
+  seed: 8
             "#,
         );
 
-        controls.set_transition_time(0.0);
-        controls.take_snapshot("foo");
-
-        controls.ui_controls.set("a", ControlValue::Float(100.0));
-        controls.midi_controls.set("b", 200.0);
-        controls.osc_controls.set("c", 300.0);
-        controls.take_snapshot("bar");
-
-        init(0.0);
-        controls.recall_snapshot("bar").unwrap();
-        controls.update();
-        assert_eq!(controls.get("a"), 100.0);
-        assert_eq!(controls.get("b"), 200.0);
-        assert_eq!(controls.get("c"), 300.0);
-
-        init(0.25);
-        controls.update();
-        controls.recall_snapshot("foo").unwrap();
-        assert_eq!(controls.get("a"), 10.0);
-        assert_eq!(controls.get("b"), 20.0);
-        assert_eq!(controls.get("c"), 30.0);
+        let mut differs = false;
+        for step in 1..64 {
+            hold(step as f32 * 0.25);
+            let a = controls.get("a");
+            assert_eq!(a, controls.get("b"), "step {}", step);
+            assert!((10.0..=20.0).contains(&a), "step {}: {}", step, a);
+            differs |= (a - controls.get("c")).abs() > 0.1;
+        }
+        assert!(differs, "different seeds produce different curves");
     }
 
     #[test]
     #[serial]
-    fn test_snapshot_recall_interpolates_and_lands_on_saved_values() {
+    fn test_underdamped_spring_overshoots_then_settles() {
+        let mut controls = create_instance(
+            r#"
+target:
+  type: slider
+  default: 0
+
+follower:
+  type: spring
+  target: $target
+  stiffness: 100
+  damping: 5
+            "#,
+        );
+
+        frame_clock::set_fps(24.0);
+        frame_clock::set_render_fps(None);
+        frame_clock::set_frame_count(0);
+        assert_eq!(controls.get("follower"), 0.0);
+
+        controls.ui_controls.set("target", ControlValue::Float(1.0));
+        let mut peak: f32 = 0.0;
+        for frame in 1..=240 {
+            frame_clock::set_frame_count(frame);
+            peak = peak.max(controls.get("follower"));
+        }
+
+        assert!(peak > 1.1, "expected overshoot, peaked at {}", peak);
+        assert!((controls.get("follower") - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[serial]
+    fn test_delay_effect_lags_a_ramp_by_one_beat() {
+        let controls = create_instance(
+            r#"
+ramp:
+  type: ramp
+  beats: 4
+
+delayed:
+  type: ramp
+  beats: 4
+  mod: [echo]
+
+echo:
+  type: effect
+  kind: delay
+  beats: 1
+            "#,
+        );
+
+        // 4 frames per beat, so the delayed ramp trails by 4 frames
+        let mut history = vec![];
+        for frame in 0..16_usize {
+            init(frame as f32 * 0.25);
+            history.push(controls.get("ramp"));
+            let delayed = controls.get("delayed");
+            let expected = history[frame.saturating_sub(4)];
+            assert!(
+                (delayed - expected).abs() < 0.01,
+                "frame {}: {} != {}",
+                frame,
+                delayed,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_reducer_effects_combine_sources() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.2
+  mod: [loudest]
+
+b:
+  type: slider
+  default: 0.7
+
+c:
+  type: slider
+  default: 0.4
+
+quietest:
+  type: slider
+  default: 0.5
+  mod: [lowest]
+
+mean:
+  type: slider
+  default: 0.3
+  mod: [average]
+
+loudest:
+  type: effect
+  kind: max
+  sources: [b, c]
+
+lowest:
+  type: effect
+  kind: min
+  sources: [a, b, c]
+
+average:
+  type: effect
+  kind: average
+  sources: [b, c]
+            "#,
+        );
+
+        assert_eq!(controls.get("a"), 0.7);
+        assert_eq!(controls.get("quietest"), 0.2);
+        assert!((controls.get("mean") - 0.466_666).abs() < 0.001);
+
+        controls.ui_controls.set("c", ControlValue::Float(0.9));
+        assert_eq!(controls.get("a"), 0.9);
+    }
+
+    #[test]
+    #[serial]
+    fn test_comparator_effect_with_hot_threshold() {
+        let mut controls = create_instance(
+            r#"
+level:
+  type: slider
+  default: 0.6
+  mod: [loud]
+
+gate_level:
+  type: slider
+  default: 0.5
+
+loud:
+  type: effect
+  kind: comparator
+  op: gte
+  threshold: $gate_level
+            "#,
+        );
+
+        // Threshold params are cached per frame, so step frames between edits
+        init(0.0);
+        assert_eq!(controls.get("level"), 1.0);
+
+        init(0.25);
+        let gate_level = ControlValue::Float(0.6);
+        controls.ui_controls.set("gate_level", gate_level);
+        assert_eq!(controls.get("level"), 1.0, "at threshold");
+
+        init(0.5);
+        let gate_level = ControlValue::Float(0.7);
+        controls.ui_controls.set("gate_level", gate_level);
+        assert_eq!(controls.get("level"), 0.0, "below threshold");
+    }
+
+    #[test]
+    #[serial]
+    fn test_reload_preserves_unchanged_animation_state() {
+        let yaml = r#"
+a:
+  type: random_slewed
+  beats: 1
+  range: [0, 100]
+  slew: 0.9
+"#;
+        let changed_yaml = yaml.replace("slew: 0.9", "slew: 0.8");
+        let config = ControlHub::<FrameTiming>::parse_from_str(yaml).unwrap();
+        let changed_config =
+            ControlHub::<FrameTiming>::parse_from_str(&changed_yaml).unwrap();
+
+        // Without history random_slewed jumps straight to the new value
+        let fresh = create_instance(yaml);
+        init(4.5);
+        let target = fresh.get("a");
+
+        let mut hub = create_instance(yaml);
+        init(0.5);
+        hub.get("a");
+        init(4.5);
+        let before_reload = hub.get("a");
+        assert!((before_reload - target).abs() > 1.0);
+
+        hub.populate_controls(&config).unwrap();
+        init(4.5);
+        let after_reload = hub.get("a");
+        assert!(
+            (after_reload - target).abs() > 0.5,
+            "unchanged config should keep slewing from {}",
+            before_reload
+        );
+        assert!((after_reload - target).abs() < (before_reload - target).abs());
+
+        hub.populate_controls(&changed_config).unwrap();
+        init(4.5);
+        assert!(
+            (hub.get("a") - target).abs() < 0.001,
+            "changed config should start fresh"
+        );
+
+        hub.set_preserve_animation_state_on_reload(false);
+        init(5.5);
+        hub.get("a");
+        hub.populate_controls(&changed_config).unwrap();
+        init(4.5);
+        assert!((hub.get("a") - target).abs() < 0.001, "opted out");
+    }
+
+    #[test]
+    #[serial]
+    fn test_merge_adds_controls_and_replaces_shared_ones() {
+        let base = r#"
+size:
+  type: slider
+  default: 0.25
+
+speed:
+  type: slider
+  default: 0.5
+
+cutoff:
+  type: midi
+  cc: 74
+  default: 0.1
+        "#;
+        let mut controls = create_instance(base);
+        init(0.0);
+        controls.ui_controls.set("size", ControlValue::from(0.75));
+        controls.ui_controls.set("speed", ControlValue::from(0.9));
+
+        controls
+            .merge(
+                r#"
+size:
+  type: slider
+  range: [0, 10]
+  default: 5
+
+glow:
+  type: checkbox
+  default: true
+
+cutoff:
+  type: midi
+  cc: 74
+  default: 0.6
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(controls.get("size"), 5.0);
+        assert_eq!(
+            controls.ui_controls.slider_range("size"),
+            Some((0.0, 10.0))
+        );
+        assert_eq!(controls.get("speed"), 0.9);
+        assert_eq!(controls.get("cutoff"), 0.6);
+        assert!(controls.bool("glow"));
+        assert_eq!(
+            controls.ui_controls.configs().keys().collect::<Vec<_>>(),
+            ["size", "speed", "glow"]
+        );
+
+        // Reloading the base script keeps what was merged over it
+        let config = ControlHub::<FrameTiming>::parse_from_str(base).unwrap();
+        controls.populate_controls(&config).unwrap();
+        assert_eq!(
+            controls.ui_controls.slider_range("size"),
+            Some((0.0, 10.0))
+        );
+        assert!(controls.bool("glow"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_merge_rejects_invalid_fragments() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+            "#,
+        );
+
+        assert!(controls.merge("a: [unterminated").is_err());
+        assert!(
+            controls
+                .merge(
+                    r#"
+fold_a:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_b
+
+fold_b:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_a
+                    "#
+                )
+                .is_err()
+        );
+
+        // A rejected fragment that replaces `a` leaves its value alone
+        controls.ui_controls.set("a", ControlValue::from(0.75));
+        assert!(
+            controls
+                .merge(
+                    r#"
+a:
+  type: slider
+  default: 1
+
+fold_a:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_b
+
+fold_b:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_a
+                    "#
+                )
+                .is_err()
+        );
+
+        init(0.0);
+        assert_eq!(controls.get("a"), 0.75);
+        assert!(controls.merge("a:\n  type: slider\n  default: 1\n").is_ok());
+        assert_eq!(controls.get("a"), 1.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_inline_mod_matches_explicit_mod() {
+        let shared = r#"
+triangle:
+  type: triangle
+  beats: 4
+
+slider:
+  type: slider
+  default: 0.33
+
+scale:
+  type: slider
+  default: 0.5
+
+effect:
+  type: effect
+  kind: hysteresis
+  upper_threshold: 0.55
+  lower_threshold: 0.1
+  output_low: 0
+  output_high: $slider
+"#;
+        let explicit = create_instance(&format!(
+            "{}{}",
+            shared,
+            r#"
+test_mod:
+  type: mod
+  source: triangle
+  modulators:
+    - effect
+    - scale
+"#
+        ));
+        let inline_yaml = shared
+            .replace("  beats: 4\n", "  beats: 4\n  mod: [effect, scale]\n");
+        let inline = create_instance(&inline_yaml);
+
+        for beats in [0.0, 1.0, 1.5, 2.5, 3.0] {
+            init(beats);
+            assert_eq!(
+                inline.get("triangle"),
+                explicit.get("triangle"),
+                "beats: {}",
+                beats
+            );
+        }
+        init(1.5);
+        assert_eq!(inline.get("triangle"), 0.33 * 0.5);
+    }
+
+    #[test]
+    #[serial]
+    fn test_parameter_modulation_breakpoint() {
+        let controls = create_instance(
+            r#"
+slider:
+  type: slider
+  default: 40
+
+automate:
+  type: automate
+  breakpoints:
+    - position: 0
+      value: $slider
+      kind: step
+
+            "#,
+        );
+
+        init(0.0);
+        assert_eq!(
+            controls.get("automate"),
+            40.0,
+            "[automate.0.value]<-[$slider@40]"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 10
+b:
+  type: midi
+  default: 20
+c:
+  type: osc
+  default: 30
+
+            "#,
+        );
+
+        controls.set_transition_time(0.0);
+        controls.take_snapshot("foo");
+
+        controls.ui_controls.set("a", ControlValue::Float(100.0));
+        controls.midi_controls.set("b", 200.0);
+        controls.osc_controls.set("c", 300.0);
+        controls.take_snapshot("bar");
+
+        init(0.0);
+        controls.recall_snapshot("bar").unwrap();
+        controls.update();
+        assert_eq!(controls.get("a"), 100.0);
+        assert_eq!(controls.get("b"), 200.0);
+        assert_eq!(controls.get("c"), 300.0);
+
+        init(0.25);
+        controls.update();
+        controls.recall_snapshot("foo").unwrap();
+        assert_eq!(controls.get("a"), 10.0);
+        assert_eq!(controls.get("b"), 20.0);
+        assert_eq!(controls.get("c"), 30.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_preview_is_not_committed() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+  var: alias
+
+scale:
+  type: slider
+  default: 0.5
+
+a_mod:
+  type: mod
+  source: a
+  modulators:
+    - scale
+            "#,
+        );
+        controls.set_transition_time(0.0);
+        init(0.0);
+        assert_eq!(controls.get("a"), 0.25);
+
+        controls.set_preview("alias", Some(0.8));
+        controls.set_preview("scale", Some(1.0));
+        assert_eq!(controls.preview("a"), Some(0.8));
+        assert_eq!(controls.get("alias"), 0.8);
+        assert_eq!(controls.get("scale"), 1.0);
+
+        controls.take_snapshot("previewed");
+        let saved =
+            SerializableSketchState::from(&TransitorySketchState::from_hub(
+                &controls,
+                Mappings::default(),
+                vec![],
+            ));
+        assert_eq!(controls.ui_controls.float("a"), 0.5);
+        assert_eq!(
+            controls.snapshots["previewed"]["a"],
+            ControlValue::from(0.5)
+        );
+        let saved_a = saved.ui_controls.iter().find(|c| c.name == "a");
+        assert_eq!(saved_a.unwrap().value, ControlValue::from(0.5));
+
+        controls.set_preview("alias", None);
+        assert_eq!(controls.preview("a"), None);
+        assert_eq!(controls.get("a"), 0.5);
+
+        controls.ui_controls.set("a", ControlValue::from(0.4));
+        controls.clear_previews();
+        init(1.0);
+        assert_eq!(controls.get("a"), 0.2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_toggle_ab_restores_stashed_values_instantly() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 10
+b:
+  type: midi
+  default: 20
+c:
+  type: osc
+  default: 30
+d:
+  type: checkbox
+  default: false
+e:
+  type: select
+  options: [x, y]
+  default: x
+            "#,
+        );
+        init(0.0);
+        assert!(controls.toggle_ab().is_err());
+
+        controls.take_snapshot("1");
+        controls.stash(AbSlot::A);
+        let a_values = controls.ui_controls.values();
+
+        controls.ui_controls.set("a", ControlValue::from(100.0));
+        controls.midi_controls.set("b", 200.0);
+        controls.osc_controls.set("c", 300.0);
+        controls.ui_controls.set("d", ControlValue::from(true));
+        controls
+            .ui_controls
+            .set("e", ControlValue::from("y".to_string()));
+        controls.stash(AbSlot::B);
+        let b_values = controls.ui_controls.values();
+
+        assert_eq!(controls.toggle_ab(), Ok(AbSlot::A));
+        assert!(controls.active_transition.is_none());
+        assert_eq!(controls.ui_controls.values(), a_values);
+        assert_eq!(controls.get("a"), 10.0);
+        assert_eq!(controls.get("b"), 20.0);
+        assert_eq!(controls.get("c"), 30.0);
+
+        assert_eq!(controls.toggle_ab(), Ok(AbSlot::B));
+        assert_eq!(controls.ui_controls.values(), b_values);
+        assert_eq!(controls.get("a"), 100.0);
+        assert_eq!(controls.get("b"), 200.0);
+        assert_eq!(controls.get("c"), 300.0);
+        assert_eq!(controls.ab_slot(), Some(AbSlot::B));
+
+        assert_eq!(controls.snapshot_keys_sorted(), ["1"]);
+        assert_eq!(controls.snapshots["1"]["a"], ControlValue::from(10.0));
+    }
+
+    #[test]
+    #[serial]
+    fn test_morph_snapshots_passes_through_each_slot() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0, 100]
+  default: 0
+b:
+  type: osc
+  range: [0, 100]
+  default: 0
+            "#,
+        );
+        let ended = Arc::new(AtomicUsize::new(0));
+        let ended_clone = ended.clone();
+        controls.register_snapshot_ended_callback(move || {
+            ended_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        for (id, value) in [("1", 10.0), ("2", 20.0), ("3", 40.0)] {
+            controls.ui_controls.set("a", ControlValue::from(value));
+            controls.osc_controls.set("b", value * 2.0);
+            controls.take_snapshot(id);
+        }
+        controls.ui_controls.set("a", ControlValue::from(0.0));
+        controls.osc_controls.set("b", 0.0);
+
+        hold(0.0);
+        assert!(controls.morph_snapshots(&[], 12.0).is_err());
+        assert!(
+            controls
+                .morph_snapshots(
+                    &[("1", Easing::Linear), ("9", Easing::Linear)],
+                    12.0
+                )
+                .is_err()
+        );
+        assert!(controls.active_transition.is_none());
+
+        controls
+            .morph_snapshots(
+                &[
+                    ("1", Easing::Linear),
+                    ("2", Easing::EaseIn),
+                    ("3", Easing::Linear),
+                ],
+                12.0,
+            )
+            .unwrap();
+
+        let expected = [
+            (2.0, 5.0),
+            (4.0, 10.0),
+            // EaseIn at the halfway point covers a quarter of the leg
+            (6.0, 12.5),
+            (8.0, 20.0),
+            (10.0, 30.0),
+            (12.0, 40.0),
+        ];
+        for (beat, a) in expected {
+            hold(beat);
+            controls.update();
+            assert_close(controls.get("a"), a, &format!("a at beat {}", beat));
+            assert_close(
+                controls.get("b"),
+                a * 2.0,
+                &format!("b at beat {}", beat),
+            );
+        }
+        assert!(controls.active_transition.is_none());
+        assert_eq!(controls.ui_controls.float("a"), 40.0);
+        assert_eq!(ended.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_morph_snapshots_catches_up_after_skipped_segments() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0, 100]
+  default: 0
+            "#,
+        );
+        for (id, value) in [("1", 10.0), ("2", 20.0), ("3", 40.0)] {
+            controls.ui_controls.set("a", ControlValue::from(value));
+            controls.take_snapshot(id);
+        }
+
+        hold(0.0);
+        controls
+            .morph_snapshots(
+                &[
+                    ("1", Easing::Linear),
+                    ("2", Easing::Linear),
+                    ("3", Easing::Linear),
+                ],
+                12.0,
+            )
+            .unwrap();
+
+        hold(9.0);
+        controls.update();
+        assert_close(controls.get("a"), 25.0, "a after skipped segments");
+
+        controls.recall_snapshot("1").unwrap();
+        assert!(controls.morph.is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_transition_time_overrides() {
+        let mut controls = create_instance(
+            r#"
+color:
+  type: slider
+  range: [0, 100]
+  transition_time: 8
+geometry:
+  type: slider
+  range: [0, 100]
+  transition_time: 0
+speed:
+  type: slider
+  range: [0, 100]
+            "#,
+        );
+        controls.set_transition_time(4.0);
+        for name in ["color", "geometry", "speed"] {
+            controls.ui_controls.set(name, ControlValue::from(80.0));
+        }
+        controls.take_snapshot("a");
+        for name in ["color", "geometry", "speed"] {
+            controls.ui_controls.set(name, ControlValue::from(0.0));
+        }
+
+        hold(0.0);
+        controls.recall_snapshot("a").unwrap();
+        assert_eq!(controls.get("geometry"), 80.0);
+        assert_eq!(controls.get("speed"), 0.0);
+
+        hold(2.0);
+        controls.update();
+        assert_close(controls.get("color"), 20.0, "color at beat 2");
+        assert_close(controls.get("speed"), 40.0, "speed at beat 2");
+
+        hold(4.0);
+        controls.update();
+        assert_close(controls.get("color"), 40.0, "color at beat 4");
+        assert_eq!(controls.get("speed"), 80.0);
+        assert!(controls.active_transition.is_some());
+
+        hold(8.0);
+        controls.update();
+        assert!(controls.active_transition.is_none());
+        for name in ["color", "geometry", "speed"] {
+            assert_eq!(controls.get(name), 80.0, "{name}");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_freeze_random_recalls_captured_values() {
+        let yaml = r#"
+r:
+  type: random
+  beats: 1
+  range: [0, 100]
+  stem: 99
+
+n:
+  type: noise
+  beats: 1
+  range: [0, 100]
+
+s:
+  type: slider
+  default: 0.5
+        "#;
+        let mut controls = create_instance(yaml);
+        let live = create_instance(yaml);
+        controls.set_transition_time(0.0);
+
+        hold(0.5);
+        controls.take_snapshot("unfrozen");
+        assert!(!controls.snapshots["unfrozen"].contains_key("r"));
+
+        controls.set_freeze_random(true);
+        let (r, n) = (controls.get("r"), controls.get("n"));
+        controls.take_snapshot("a");
+        assert_eq!(controls.snapshots["a"]["r"], ControlValue::from(r));
+
+        hold(3.5);
+        assert_ne!(controls.get("r"), r);
+        controls.recall_snapshot("a").unwrap();
+        controls.update();
+
+        for beat in [3.5, 7.25, 12.75] {
+            hold(beat);
+            assert_eq!(controls.get("r"), r);
+            assert_eq!(controls.get("n"), n);
+        }
+
+        controls.set_freeze_random(false);
+        assert_eq!(controls.get("r"), live.get("r"));
+        assert_eq!(controls.get("n"), live.get("n"));
+
+        // Unfreezing mid-transition doesn't pin values once it lands
+        controls.set_freeze_random(true);
+        controls.set_transition_time(2.0);
+        controls.recall_snapshot("a").unwrap();
+        controls.set_freeze_random(false);
+        hold(16.5);
+        controls.update();
+        assert!(controls.active_transition.is_none());
+        assert_eq!(controls.get("r"), live.get("r"));
+    }
+
+    #[test]
+    fn test_set_beats_scrubs_manual_timing_and_cancels_stale_transition() {
+        let mut controls = ControlHub::new(
+            Some(
+                r#"
+x:
+  type: slider
+  default: 0
+"#,
+            ),
+            Timing::manual(Bpm::new(BPM)),
+        );
+        controls.set_transition_time(4.0);
+        controls.take_snapshot("a");
+
+        controls.set_beats(8.0).unwrap();
+        assert_eq!(controls.animation.beats(), 8.0);
+
+        controls.recall_snapshot("a").unwrap();
+        assert!(controls.active_transition.is_some());
+
+        controls.set_beats(6.0).unwrap();
+        assert_eq!(controls.animation.beats(), 6.0);
+        assert!(controls.active_transition.is_none());
+
+        controls.set_beats(100_000.0).unwrap();
+        assert_eq!(controls.beats(), 100_000.0);
+
+        let mut frame_controls =
+            ControlHub::new(None, Timing::frame(Bpm::new(BPM)));
+        assert!(frame_controls.set_beats(1.0).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_beats_after_reload() {
+        let mut manual = ControlHub::new(None, Timing::manual(Bpm::new(BPM)));
+        manual.set_beats(12.5).unwrap();
+        let mut reloaded = ControlHub::new(None, Timing::manual(Bpm::new(BPM)));
+        assert_eq!(reloaded.beats(), 0.0);
+        reloaded.restore_beats(manual.beats()).unwrap();
+        assert_eq!(reloaded.beats(), 12.5);
+
+        init(4.0);
+        frame_clock::set_paused(true);
+        let frame = ControlHub::new(None, Timing::frame(Bpm::new(BPM)));
+        let beats = frame.beats();
+        frame_clock::reset();
+        let mut reloaded = ControlHub::new(None, Timing::frame(Bpm::new(BPM)));
+        assert_eq!(reloaded.beats(), 0.0);
+        reloaded.restore_beats(beats).unwrap();
+        assert!(
+            (reloaded.beats() - 4.0).abs() < 0.001,
+            "{}",
+            reloaded.beats()
+        );
+        let expected_frames = 4.0 * 60.0 / BPM * frame_clock::render_fps();
+        assert_eq!(frame_clock::frame_count(), expected_frames.round() as u32);
+        frame_clock::set_paused(false);
+
+        let mut osc = ControlHub::new(None, Timing::osc(Bpm::new(BPM)));
+        osc.animation.timing.set_external_beats(3.0);
+        assert!(osc.restore_beats(12.5).is_err());
+        assert_eq!(osc.beats(), 3.0);
+    }
+
+    #[test]
+    fn test_loop_region_wrap_restores_stage_at_loop_in() {
+        let mut hub = ControlHub::new(
+            Some(
+                r#"
+a:
+  type: slider
+  default: 0
+
+sequence:
+  type: snapshot_sequence
+  stages:
+    - kind: stage
+      snapshot: 1
+      position: 0.0
+    - kind: stage
+      snapshot: 2
+      position: 1.0
+    - kind: stage
+      snapshot: 1
+      position: 3.0
+    - kind: end
+      position: 4.0
+"#,
+            ),
+            Timing::manual(Bpm::new(BPM)),
+        );
+        hub.set_transition_time(0.0);
+        hub.take_snapshot("1");
+        hub.ui_controls.set("a", ControlValue::Float(1.0));
+        hub.take_snapshot("2");
+        hub.ui_controls.set("a", ControlValue::Float(-10.0));
+        hub.set_loop_region(Some((1.5, 3.5))).unwrap();
+
+        hub.set_beats(2.0).unwrap();
+        hub.update();
+        assert_eq!(hub.get("a"), -10.0, "no stage starts at beat 2");
+
+        hub.animation.timing.set_external_beats(3.25);
+        hub.update();
+        assert_eq!(hub.get("a"), 0.0, "stage at beat 3");
+
+        // 3.75 wraps to 1.75, where the stage at beat 1 is in effect
+        hub.animation.timing.set_external_beats(3.75);
+        hub.update();
+        assert_eq!(hub.beats(), 1.75);
+        assert_eq!(hub.get("a"), 1.0, "wrap restores the stage at loop in");
+
+        hub.animation.timing.set_external_beats(5.25);
+        hub.update();
+        assert_eq!(hub.get("a"), 0.0, "stage at beat 3 fires again");
+
+        let mut midi_hub = ControlHub::new(None, Timing::midi(Bpm::new(BPM)));
+        assert!(midi_hub.set_loop_region(Some((0.0, 4.0))).is_err());
+        assert!(midi_hub.set_loop_region(None).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_recall_interpolates_and_lands_on_saved_values() {
         let mut controls = create_instance(
             r#"
 x:
@@ -2172,42 +4834,199 @@ y:
 "#,
         );
 
-        controls.set_transition_time(4.0);
-
-        controls.take_snapshot("a");
-        controls.ui_controls.set("x", ControlValue::Float(100.0));
-        controls.ui_controls.set("y", ControlValue::Float(90.0));
-        controls.take_snapshot("b");
+        controls.set_transition_time(4.0);
+
+        controls.take_snapshot("a");
+        controls.ui_controls.set("x", ControlValue::Float(100.0));
+        controls.ui_controls.set("y", ControlValue::Float(90.0));
+        controls.take_snapshot("b");
+
+        controls.ui_controls.set("x", ControlValue::Float(0.0));
+        controls.ui_controls.set("y", ControlValue::Float(10.0));
+
+        init(0.0);
+        controls.recall_snapshot("b").unwrap();
+
+        let transition = controls.active_transition.as_ref().unwrap();
+        let (x_from, x_to) = transition.values["x"];
+        let (y_from, y_to) = transition.values["y"];
+
+        assert_close(controls.get("x"), x_from, "x at transition start");
+        assert_close(controls.get("y"), y_from, "y at transition start");
+
+        init(2.0);
+        assert_close(
+            controls.get("x"),
+            lerp(x_from, x_to, 0.5),
+            "x at transition midpoint",
+        );
+        assert_close(
+            controls.get("y"),
+            lerp(y_from, y_to, 0.5),
+            "y at transition midpoint",
+        );
+
+        init(4.1);
+        controls.update();
+        assert_close(controls.get("x"), x_to, "x at transition end");
+        assert_close(controls.get("y"), y_to, "y at transition end");
+    }
+
+    #[test]
+    #[serial]
+    fn test_randomize_amount_limits_randomize_to_neighborhood() {
+        let mut controls = create_instance(
+            r#"
+x:
+  type: slider
+  range: [0, 100]
+  step: 0.5
+  default: 50
+  randomize_amount: 0.1
+o:
+  type: osc
+  range: [-1, 1]
+  default: 0.95
+  randomize_amount: 0.1
+"#,
+        );
+        init(0.0);
+
+        for _ in 0..200 {
+            controls.randomize(vec![]);
+            let transition = controls.active_transition.take().unwrap();
+
+            let (x_from, x_to) = transition.values["x"];
+            assert!((x_to - x_from).abs() <= 10.0, "{} -> {}", x_from, x_to);
+            assert_eq!(x_to % 0.5, 0.0, "{} is off step", x_to);
+            assert!((0.0..=100.0).contains(&x_to));
+
+            let (o_from, o_to) = transition.values["o"];
+            assert!((o_to - o_from).abs() <= 0.2 + f32::EPSILON);
+            assert!((-1.0..=1.0).contains(&o_to));
+
+            controls.ui_controls.set("x", ControlValue::Float(x_to));
+            controls.osc_controls.set("o", o_to);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_randomize_select_favors_weighted_options() {
+        let mut controls = create_instance(
+            r#"
+weighted:
+  type: select
+  options:
+    - value: a
+      weight: 3
+    - value: b
+      weight: 1
+    - value: c
+      weight: 0
+  default: a
+plain:
+  type: select
+  options: [a, b]
+  default: a
+"#,
+        );
+        init(0.0);
+
+        let draws = 4000;
+        let mut weighted_a = 0;
+        let mut plain_a = 0;
+        for _ in 0..draws {
+            controls.randomize(vec![]);
+            match controls.string("weighted").as_str() {
+                "a" => weighted_a += 1,
+                "b" => {}
+                other => panic!("unexpected option {}", other),
+            }
+            if controls.string("plain") == "a" {
+                plain_a += 1;
+            }
+        }
 
-        controls.ui_controls.set("x", ControlValue::Float(0.0));
-        controls.ui_controls.set("y", ControlValue::Float(10.0));
+        let weighted_ratio = weighted_a as f32 / draws as f32;
+        let plain_ratio = plain_a as f32 / draws as f32;
+        assert!((weighted_ratio - 0.75).abs() < 0.05, "{}", weighted_ratio);
+        assert!((plain_ratio - 0.5).abs() < 0.05, "{}", plain_ratio);
+    }
 
+    #[test]
+    #[serial]
+    fn test_randomize_seed_reproduces_transitions() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0, 100]
+  default: 10
+b:
+  type: slider
+  range: [-1, 1]
+  default: 0
+c:
+  type: checkbox
+  default: false
+d:
+  type: select
+  options: [w, x, y, z]
+  default: w
+"#,
+        );
         init(0.0);
-        controls.recall_snapshot("b").unwrap();
 
-        let transition = controls.active_transition.as_ref().unwrap();
-        let (x_from, x_to) = transition.values["x"];
-        let (y_from, y_to) = transition.values["y"];
+        let draw = |controls: &mut ControlHub<FrameTiming>| {
+            controls.ui_controls.set("c", ControlValue::Bool(false));
+            controls
+                .ui_controls
+                .set("d", ControlValue::String("w".to_string()));
+            controls.randomize(vec![]);
+            let transition = controls.active_transition.take().unwrap();
+            let mut values: Vec<_> = transition.values.into_iter().collect();
+            values.sort_by(|a, b| a.0.cmp(&b.0));
+            (values, controls.bool("c"), controls.string("d"))
+        };
 
-        assert_close(controls.get("x"), x_from, "x at transition start");
-        assert_close(controls.get("y"), y_from, "y at transition start");
+        controls.set_randomize_seed(42);
+        let first = draw(&mut controls);
+        let second = draw(&mut controls);
+        assert_eq!(first, second);
 
-        init(2.0);
-        assert_close(
-            controls.get("x"),
-            lerp(x_from, x_to, 0.5),
-            "x at transition midpoint",
-        );
-        assert_close(
-            controls.get("y"),
-            lerp(y_from, y_to, 0.5),
-            "y at transition midpoint",
-        );
+        controls.set_randomize_seed(43);
+        assert_ne!(draw(&mut controls), first);
 
-        init(4.1);
-        controls.update();
-        assert_close(controls.get("x"), x_to, "x at transition end");
-        assert_close(controls.get("y"), y_to, "y at transition end");
+        controls.clear_randomize_seed();
+        assert_eq!(controls.randomize_seed(), None);
+    }
+
+    #[test]
+    fn test_select_weights_are_validated() {
+        let mut controls = create_instance("");
+
+        for (yaml, expected) in [
+            (
+                "weight: -1",
+                "select shape option circle has invalid weight -1",
+            ),
+            (
+                "weight: 0",
+                "select shape needs at least one option with a positive weight",
+            ),
+        ] {
+            let config = ControlHub::<FrameTiming>::parse_from_str(&format!(
+                "shape:\n  type: select\n  default: circle\n  options:\n    \
+                 - value: circle\n      {}\n",
+                yaml
+            ))
+            .unwrap();
+
+            let err =
+                controls.populate_controls(&config).unwrap_err().to_string();
+            assert_eq!(err, expected);
+        }
     }
 
     #[test]
@@ -2352,6 +5171,118 @@ x:
         assert_eq!(populated_count.load(Ordering::SeqCst), 1);
     }
 
+    #[test]
+    #[serial]
+    fn test_reload_callback_skips_initial_population() {
+        let yaml = r#"
+x:
+  type: slider
+  default: 0.5
+"#;
+
+        let mut controls =
+            ControlHub::new(None, FrameTiming::new(Bpm::new(BPM)));
+        let populated_count = Arc::new(AtomicUsize::new(0));
+        let reload_count = Arc::new(AtomicUsize::new(0));
+        let populated_count_clone = populated_count.clone();
+        let reload_count_clone = reload_count.clone();
+        controls.register_populated_callback(move || {
+            populated_count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+        controls.register_reload_callback(move || {
+            reload_count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let config = ControlHub::<FrameTiming>::parse_from_str(yaml).unwrap();
+        controls.populate_controls(&config).unwrap();
+        assert_eq!(populated_count.load(Ordering::SeqCst), 1);
+        assert_eq!(reload_count.load(Ordering::SeqCst), 0);
+
+        controls.populate_controls(&config).unwrap();
+        assert_eq!(populated_count.load(Ordering::SeqCst), 2);
+        assert_eq!(reload_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_circular_dependency_is_rejected_and_names_both_nodes() {
+        let mut controls = create_instance(
+            r#"
+x:
+  type: slider
+  default: 0.5
+"#,
+        );
+
+        let config = ControlHub::<FrameTiming>::parse_from_str(
+            r#"
+fold_a:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_b
+
+fold_b:
+  type: effect
+  kind: wave_folder
+  symmetry: $fold_a
+"#,
+        )
+        .unwrap();
+
+        let err = controls.populate_controls(&config).unwrap_err().to_string();
+        assert!(err.contains("circular dependency"), "{}", err);
+        assert!(err.contains("fold_a") && err.contains("fold_b"), "{}", err);
+
+        // The rejected script leaves the previous controls in place
+        assert_eq!(controls.get("x"), 0.5);
+    }
+
+    #[test]
+    #[serial]
+    fn test_bypass_all_returns_raw_values_and_restores_bypasses() {
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+
+b:
+  type: slider
+  default: 0.25
+  bypass: 0.9
+
+scale:
+  type: slider
+  default: 0.5
+
+a_mod:
+  type: mod
+  source: a
+  modulators:
+    - scale
+"#,
+        );
+
+        assert_eq!(controls.get("a"), 0.25);
+        assert_eq!(controls.get("b"), 0.9);
+
+        controls.set_bypass_all(true);
+        assert_eq!(controls.get("a"), 0.5);
+        assert_eq!(controls.get("b"), 0.25);
+        assert_eq!(controls.bypassed().get("b"), Some(&0.9));
+
+        controls.set_bypass_all(false);
+        assert_eq!(controls.get("a"), 0.25);
+        assert_eq!(controls.get("b"), 0.9);
+
+        controls.solo(Some("a"));
+        assert_eq!(controls.get("a"), 0.25);
+        assert_eq!(controls.get("b"), 0.25);
+
+        controls.solo(None);
+        assert_eq!(controls.get("b"), 0.9);
+    }
+
     #[test]
     #[serial]
     fn test_disabled() {
@@ -2375,6 +5306,43 @@ baz:
         assert!(hub.ui_controls.disabled("foo"));
     }
 
+    #[test]
+    #[serial]
+    fn test_randomize_skips_disabled_controls() {
+        let mut hub = create_instance(
+            r#"
+frozen:
+  type: slider
+  default: 0.5
+  disabled: locked
+
+free:
+  type: slider
+  default: 0.5
+
+locked:
+  type: checkbox
+  default: true
+            "#,
+        );
+
+        init(0.0);
+        hub.update();
+        assert!(hub.disabled("frozen"));
+        assert!(!hub.disabled("free"));
+        assert_eq!(hub.disabled_map().get("locked"), Some(&false));
+
+        hub.randomize(vec!["locked".into()]);
+        let transition = hub.active_transition.as_ref().unwrap();
+        assert!(transition.values.contains_key("free"));
+        assert!(!transition.values.contains_key("frozen"));
+
+        hub.ui_controls.set("locked", ControlValue::from(false));
+        assert!(hub.disabled("frozen"), "map refreshes on update");
+        hub.update();
+        assert!(!hub.disabled("frozen"));
+    }
+
     #[test]
     #[serial]
     fn test_midi_override_pmod_bug() {
@@ -2400,6 +5368,7 @@ foo_animation:
                 min: 0.0,
                 max: 100.0,
                 value: 99.0,
+                encoder: EncoderMode::Absolute,
             },
         );
         hub.midi_overrides
@@ -2411,6 +5380,150 @@ foo_animation:
         assert_eq!(hub.get("foo_animation"), 99.0);
     }
 
+    #[test]
+    #[serial]
+    fn test_midi_feedback_sends_one_cc_when_mapped_control_changes() {
+        let mut hub = create_instance(
+            r#"
+level:
+  type: slider
+  range: [0, 2]
+
+other:
+  type: slider
+            "#,
+        );
+        init(0.0);
+        hub.midi_override_configs.insert(
+            "level".to_string(),
+            MidiControlConfig::new((0, 3), (0.0, 2.0), 0.0),
+        );
+
+        let mut feedback = MidiFeedback::default();
+        let start = Instant::now();
+        let mut poll = |hub: &ControlHub<FrameTiming>, elapsed: u32| {
+            feedback.messages(
+                &hub.midi_mapped_values(),
+                false,
+                start + MIDI_FEEDBACK_INTERVAL * elapsed,
+            )
+        };
+
+        assert_eq!(poll(&hub, 0), [[176, 3, 0]]);
+        assert!(poll(&hub, 1).is_empty());
+
+        hub.ui_controls.set("level", ControlValue::Float(1.0));
+        hub.ui_controls.set("other", ControlValue::Float(0.7));
+
+        assert_eq!(poll(&hub, 2), [[176, 3, 64]]);
+        assert!(poll(&hub, 3).is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_midi_mapped_effect_param_moves_effect() {
+        let mut hub = create_instance(
+            r#"
+foo:
+  type: slider
+  default: 0.5
+
+amount:
+  type: slider
+  range: [0, 4]
+
+scale:
+  type: effect
+  kind: math
+  operator: mult
+  operand: 1.0
+
+folder:
+  type: effect
+  kind: wave_folder
+  symmetry: $amount
+
+foo_mod:
+  type: mod
+  source: foo
+  modulators:
+    - scale
+            "#,
+        );
+
+        assert_eq!(hub.mapping_range("scale.operand"), Some((0.0, 1.0)));
+        assert_eq!(hub.mapping_range("folder.symmetry"), Some((0.0, 4.0)));
+        assert_eq!(hub.mapping_range("folder.gain"), Some((1.0, 10.0)));
+        assert_eq!(hub.mapping_range("missing.gain"), None);
+
+        init(0.0);
+        assert_eq!(hub.get("foo"), 0.5);
+
+        hub.midi_override_configs.insert(
+            "scale.operand".to_string(),
+            MidiControlConfig {
+                channel: 0,
+                cc: 1,
+                min: 0.0,
+                max: 1.0,
+                value: 0.5,
+                encoder: EncoderMode::Absolute,
+            },
+        );
+        hub.sync_midi_override_configs();
+        hub.midi_overrides
+            .lock()
+            .unwrap()
+            .insert("scale.operand".to_string(), 0.5);
+
+        assert_eq!(hub.get("foo"), 0.25);
+
+        hub.midi_overrides
+            .lock()
+            .unwrap()
+            .insert("scale.operand".to_string(), 0.0);
+        assert_eq!(hub.get("foo"), 0.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_restored_param_mapping_moves_effect() {
+        let mut hub = create_instance(
+            r#"
+foo:
+  type: slider
+  default: 0.5
+
+scale:
+  type: effect
+  kind: math
+  operator: mult
+  operand: 1.0
+
+foo_mod:
+  type: mod
+  source: foo
+  modulators:
+    - scale
+            "#,
+        );
+
+        hub.midi_overrides
+            .lock()
+            .unwrap()
+            .insert("scale.operand".to_string(), 0.5);
+        let mut mappings = map_mode::Mappings::default();
+        mappings.insert("scale.operand".to_string(), (0, 1));
+        hub.restore_param_mappings(&mappings);
+
+        let config = &hub.midi_override_configs["scale.operand"];
+        assert_eq!((config.cc, config.min, config.max), (1, 0.0, 1.0));
+        assert_eq!(config.value, 0.5);
+
+        init(0.0);
+        assert_eq!(hub.get("foo"), 0.25);
+    }
+
     #[test]
     #[serial]
     fn test_midi_override_source_uses_original_modulation_chain() {
@@ -2442,6 +5555,7 @@ foo_mod:
                 min: 0.0,
                 max: 1.0,
                 value: 0.25,
+                encoder: EncoderMode::Absolute,
             },
         );
         hub.midi_overrides
@@ -2582,6 +5696,8 @@ a:
             values,
             start_beat: 10.0,
             end_beat: 12.0,
+            end_beats: HashMap::default(),
+            easing: Easing::Linear,
         });
 
         init(0.0);
@@ -2717,4 +5833,27 @@ sequence:
             initial_stages
         );
     }
+
+    #[test]
+    #[serial]
+    fn test_setting_bpm_updates_beats_to_frames() {
+        init(0.0);
+        let bpm = Bpm::new(BPM);
+        let hub = ControlHub::new(None, FrameTiming::new(bpm.clone()));
+
+        // 360 bpm at 24 fps
+        assert_close(hub.beats_to_frames(1.0), 4.0, "frames per beat");
+
+        bpm.set(120.0);
+        assert_close(
+            hub.beats_to_frames(1.0),
+            12.0,
+            "frames per beat after bpm change",
+        );
+        assert_close(
+            hub.beats_to_frames(4.0),
+            48.0,
+            "frames per bar after bpm change",
+        );
+    }
 }
@@ -1,7 +1,5 @@
 use std::collections::VecDeque;
 
-use log::warn;
-
 use super::param_mod::ParamValue;
 use crate::core::prelude::*;
 use crate::ternary;
@@ -22,7 +20,7 @@ pub type EvalOrder = Option<Vec<String>>;
 /// 2. Add nodes with [`DepGraph::insert_node`], where each node represents a
 ///    control with prerequisites
 /// 3. Call [`DepGraph::build_graph`] to analyze all prerequisites and compute
-///    the evaluation order
+///    the evaluation order, or get an error naming the nodes of a cycle
 /// 4. Use [`DepGraph::order`] to get the proper evaluation sequence and
 ///    [`DepGraph::is_prerequisite`] to check if a node is required for other
 ///    calculations
@@ -61,14 +59,11 @@ impl DepGraph {
         self.node_defs.insert(name.to_string(), node);
     }
 
-    pub fn clear(&mut self) {
-        self.node_defs.clear();
-        self.eval_order = None;
-    }
-
     /// Builds the prerequisite evaluation order using a modified Kahn's
-    /// Algorithm for topological sorting
-    pub fn build_graph(&mut self) {
+    /// Algorithm for topological sorting. Circular dependencies can't be
+    /// ordered, so they leave no evaluation order and return an error
+    /// describing one of the cycles.
+    pub fn build_graph(&mut self) -> Result<(), String> {
         let (graph, mut in_degree) = self.extract_relationships();
 
         let mut actual_deps: HashSet<String> = HashSet::default();
@@ -114,12 +109,54 @@ impl DepGraph {
             }
             self.eval_order =
                 ternary!(sorted_order.is_empty(), None, Some(sorted_order));
+            Ok(())
         } else {
             self.eval_order = None;
-            warn!(
-                "cycle detected. sorted_order: {:?}, in_degree: {:?}",
-                sorted_order, in_degree
-            );
+            let unresolved: HashSet<String> = in_degree
+                .into_iter()
+                .filter(|(_, degree)| *degree > 0)
+                .map(|(node, _)| node)
+                .collect();
+            let cycle = Self::find_cycle(&graph, &unresolved);
+            Err(format!("circular dependency: {}", cycle.join(" -> ")))
+        }
+    }
+
+    /// Nodes left with unmet prerequisites after sorting either sit on a
+    /// cycle or downstream of one. Walk consumer edges between them until a
+    /// node repeats to recover just the cycle, e.g. `["a", "b", "a"]`.
+    fn find_cycle(
+        graph: &HashMap<String, Vec<String>>,
+        unresolved: &HashSet<String>,
+    ) -> Vec<String> {
+        let Some(start) = unresolved.iter().min() else {
+            return vec![];
+        };
+
+        let mut path = vec![start.clone()];
+        loop {
+            let current = &path[path.len() - 1];
+            // Every unresolved node has an unresolved prerequisite, so walking
+            // backwards along prerequisites always finds a repeat
+            let next = graph
+                .iter()
+                .filter(|(_, consumers)| consumers.contains(current))
+                .map(|(prerequisite, _)| prerequisite)
+                .filter(|prerequisite| unresolved.contains(*prerequisite))
+                .min();
+
+            let Some(next) = next else {
+                return path;
+            };
+
+            if let Some(index) = path.iter().position(|node| node == next) {
+                let mut cycle = path.split_off(index);
+                cycle.push(next.clone());
+                cycle.reverse();
+                return cycle;
+            }
+
+            path.push(next.clone());
         }
     }
 
@@ -0,0 +1,305 @@
+//! Declare a [`ControlHub`]'s controls in Rust instead of a Control Script.
+//! Each method adds the control the matching script type would, and the hub
+//! is populated exactly as if it had been loaded from that script, so
+//! values, snapshots and saved state behave the same. Hubs built in code
+//! don't hot reload; recompiling is the reload.
+//!
+//! ```rust
+//! use xtal::prelude::*;
+//!
+//! let hub = ControlHubBuilder::new()
+//!     .slider("size", (0.0, 2.0), 0.5)
+//!     .checkbox("invert", false)
+//!     .select("mode", &["lines", "dots"], "dots")
+//!     .midi("cutoff", (0, 74), (0.0, 1.0), 0.25)
+//!     .osc("brightness", (0.0, 1.0), 1.0)
+//!     .build(FrameTiming::new(Bpm::new(120.0)));
+//!
+//! assert_eq!(hub.get("size"), 0.5);
+//! assert_eq!(hub.string("mode"), "dots");
+//! ```
+
+use serde_yml::{Mapping, Value};
+
+use super::config::{
+    ConfigFile, ControlType, MaybeControlConfig, ScriptedControlConfig,
+};
+use super::control_hub::ControlHub;
+use crate::motion::TimingSource;
+
+#[derive(Debug, Default)]
+pub struct ControlHubBuilder {
+    controls: ConfigFile,
+    hrcc: bool,
+}
+
+impl ControlHubBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as a `slider` script control
+    pub fn slider(self, name: &str, range: (f32, f32), default: f32) -> Self {
+        self.control(
+            name,
+            ControlType::Slider,
+            [("range", range_value(range)), ("default", default.into())],
+        )
+    }
+
+    /// A 0 to 1 slider
+    pub fn slider_n(self, name: &str, default: f32) -> Self {
+        self.slider(name, (0.0, 1.0), default)
+    }
+
+    /// Same as a `checkbox` script control
+    pub fn checkbox(self, name: &str, default: bool) -> Self {
+        self.control(name, ControlType::Checkbox, [("default", default.into())])
+    }
+
+    /// Same as a `select` script control
+    pub fn select<S>(self, name: &str, options: &[S], default: &str) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let options: Vec<&str> =
+            options.iter().map(|option| option.as_ref()).collect();
+        self.control(
+            name,
+            ControlType::Select,
+            [("options", options.into()), ("default", default.into())],
+        )
+    }
+
+    /// Same as a `midi` script control listening on `(channel, cc)`
+    pub fn midi(
+        self,
+        name: &str,
+        (channel, cc): (u8, u8),
+        range: (f32, f32),
+        default: f32,
+    ) -> Self {
+        self.control(
+            name,
+            ControlType::Midi,
+            [
+                ("channel", channel.into()),
+                ("cc", cc.into()),
+                ("range", range_value(range)),
+                ("default", default.into()),
+            ],
+        )
+    }
+
+    /// A 0 to 1 MIDI control starting at 0
+    pub fn midi_n(self, name: &str, midi: (u8, u8)) -> Self {
+        self.midi(name, midi, (0.0, 1.0), 0.0)
+    }
+
+    /// Same as an `osc` script control, received at `/{name}`
+    pub fn osc(self, name: &str, range: (f32, f32), default: f32) -> Self {
+        self.control(
+            name,
+            ControlType::Osc,
+            [("range", range_value(range)), ("default", default.into())],
+        )
+    }
+
+    /// See [`ControlHub::hrcc`]
+    pub fn hrcc(mut self, hrcc: bool) -> Self {
+        self.hrcc = hrcc;
+        self
+    }
+
+    pub fn build<T: TimingSource>(self, timing: T) -> ControlHub<T> {
+        let mut hub = ControlHub::from_config(&self.controls, timing);
+        hub.hrcc(self.hrcc);
+        hub
+    }
+
+    // Redeclaring a name replaces the earlier control in place
+    fn control<const N: usize>(
+        mut self,
+        name: &str,
+        control_type: ControlType,
+        fields: [(&str, Value); N],
+    ) -> Self {
+        let config: Mapping = fields
+            .into_iter()
+            .map(|(key, value)| (Value::from(key), value))
+            .collect();
+        self.controls.insert(
+            name.to_string(),
+            MaybeControlConfig::Control(ScriptedControlConfig {
+                control_type,
+                config: Value::Mapping(config),
+            }),
+        );
+        self
+    }
+}
+
+fn range_value((min, max): (f32, f32)) -> Value {
+    Value::from(vec![min, max])
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::control::*;
+    use crate::motion::test_timing::TestTiming;
+    use crate::runtime::serialization::{
+        SerializableSketchState, TransitorySketchState,
+    };
+    use crate::runtime::web_view::Mappings;
+
+    const YAML: &str = r#"
+size:
+  type: slider
+  range: [0, 2]
+  default: 0.5
+
+invert:
+  type: checkbox
+  default: true
+
+mode:
+  type: select
+  options: [lines, dots]
+  default: dots
+
+cutoff:
+  type: midi
+  channel: 1
+  cc: 74
+  range: [20, 200]
+  default: 110
+
+brightness:
+  type: osc
+  range: [0, 10]
+  default: 4
+"#;
+
+    const NAMES: [&str; 5] = ["size", "invert", "mode", "cutoff", "brightness"];
+
+    fn timing() -> TestTiming {
+        let timing = TestTiming::new(120.0, 60.0);
+        timing.advance_to_beat(0.0);
+        timing
+    }
+
+    fn yaml_hub() -> ControlHub<TestTiming> {
+        ControlHub::new(Some(YAML), timing())
+    }
+
+    fn builder_hub() -> ControlHub<TestTiming> {
+        ControlHubBuilder::new()
+            .slider("size", (0.0, 2.0), 0.5)
+            .checkbox("invert", true)
+            .select("mode", &["lines", "dots"], "dots")
+            .midi("cutoff", (1, 74), (20.0, 200.0), 110.0)
+            .osc("brightness", (0.0, 10.0), 4.0)
+            .build(timing())
+    }
+
+    fn saved(hub: &ControlHub<TestTiming>) -> SerializableSketchState {
+        SerializableSketchState::from(&TransitorySketchState::from_hub(
+            hub,
+            Mappings::default(),
+            vec![],
+        ))
+    }
+
+    fn assert_same_values(
+        a: &ControlHub<TestTiming>,
+        b: &ControlHub<TestTiming>,
+    ) {
+        for name in NAMES {
+            assert_eq!(a.get(name), b.get(name), "{name}");
+        }
+        assert_eq!(a.string("mode"), b.string("mode"));
+        assert_eq!(a.ui_controls.values(), b.ui_controls.values());
+        assert_eq!(a.midi_controls.values(), b.midi_controls.values());
+        assert_eq!(a.osc_controls.values(), b.osc_controls.values());
+    }
+
+    #[test]
+    #[serial]
+    fn builder_matches_an_equivalent_script() {
+        let (yaml, built) = (yaml_hub(), builder_hub());
+
+        assert_same_values(&yaml, &built);
+        assert_eq!(built.get("cutoff"), 110.0);
+        assert!(built.bool("invert"));
+        assert_eq!(
+            built.ui_controls.configs().keys().collect::<Vec<_>>(),
+            yaml.ui_controls.configs().keys().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn builder_snapshots_match_an_equivalent_script() {
+        let mut hubs = [yaml_hub(), builder_hub()];
+
+        for hub in &mut hubs {
+            hub.set_transition_time(0.0);
+            hub.ui_controls.set("size", ControlValue::from(1.5));
+            hub.take_snapshot("a");
+            hub.ui_controls.set("size", ControlValue::from(0.25));
+            hub.ui_controls
+                .set("mode", ControlValue::from("lines".to_string()));
+            hub.recall_snapshot("a").unwrap();
+            hub.update();
+        }
+
+        let [yaml, built] = hubs;
+        assert_eq!(built.get("size"), 1.5);
+        assert_same_values(&yaml, &built);
+    }
+
+    #[test]
+    #[serial]
+    fn saved_state_restores_across_builder_and_script() {
+        let mut built = builder_hub();
+        built.ui_controls.set("size", ControlValue::from(1.25));
+        built.ui_controls.set("invert", ControlValue::from(false));
+
+        let mut restored = TransitorySketchState::from_hub(
+            &yaml_hub(),
+            Mappings::default(),
+            vec![],
+        );
+        restored.merge(saved(&built));
+
+        assert_eq!(restored.ui_controls.values(), built.ui_controls.values());
+
+        let mut restored = TransitorySketchState::from_hub(
+            &builder_hub(),
+            Mappings::default(),
+            vec![],
+        );
+        restored.merge(saved(&built));
+
+        assert_eq!(restored.ui_controls.values(), built.ui_controls.values());
+    }
+
+    #[test]
+    #[serial]
+    fn redeclaring_a_control_replaces_it() {
+        let hub = ControlHubBuilder::new()
+            .slider_n("a", 0.1)
+            .checkbox("b", false)
+            .slider("a", (0.0, 10.0), 7.0)
+            .build(timing());
+
+        assert_eq!(hub.get("a"), 7.0);
+        assert_eq!(
+            hub.ui_controls.configs().keys().collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 //! Provides runtime mapping of MIDI CCs to UI sliders, AKA "MIDI learn".
+//! Targets can also be `$`-modulatable effect or animation params written as
+//! `control.param`, e.g. `folder.symmetry`.
 use std::error::Error;
 use std::fmt;
 use std::sync::{Arc, Mutex};
@@ -9,6 +11,13 @@ use crate::io::midi;
 pub type ChannelAndController = (usize, usize);
 pub type Mappings = HashMap<String, ChannelAndController>;
 
+/// Split a `control.param` mapping target into the effect or animation name
+/// and param name. Plain slider names return `None`.
+pub fn param_target(name: &str) -> Option<(&str, &str)> {
+    name.split_once('.')
+        .filter(|(control, param)| !control.is_empty() && !param.is_empty())
+}
+
 #[derive(Debug)]
 pub struct MapModeState {
     mappings: Mappings,
@@ -227,6 +236,16 @@ mod tests {
         assert!(mappings.contains_key("c"));
     }
 
+    #[test]
+    fn param_targets_are_dotted_names() {
+        assert_eq!(
+            param_target("folder.symmetry"),
+            Some(("folder", "symmetry"))
+        );
+        assert_eq!(param_target("size"), None);
+        assert_eq!(param_target(".gain"), None);
+    }
+
     #[test]
     fn start_without_port_is_noop() {
         let mode = MapMode::default();
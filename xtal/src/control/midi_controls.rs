@@ -1,9 +1,45 @@
 use std::error::Error;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
 
 use super::control_traits::{ControlCollection, ControlConfig};
 use crate::core::prelude::*;
 use crate::io::midi::{self, is_control_change};
+use crate::ternary;
+
+/// How a controller encodes a CC's value. Endless encoders send relative
+/// deltas that are accumulated into the stored value rather than replacing
+/// it, which keeps controls from jumping after a bank switch.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub enum EncoderMode {
+    #[default]
+    #[serde(rename = "absolute")]
+    Absolute,
+    /// `1..=63` increment and `127` down to `64` decrement (`127` is -1)
+    #[serde(rename = "relative_2s_comp")]
+    Relative2sComp,
+    /// `65..=127` increment and `63` down to `0` decrement (`64` is 0)
+    #[serde(rename = "relative_binary_offset")]
+    RelativeBinaryOffset,
+}
+
+impl EncoderMode {
+    /// Signed number of steps encoded in a relative CC value, or `None` in
+    /// absolute mode. Accelerated encoders send larger magnitudes when
+    /// turned quickly, which move the value proportionally further.
+    pub fn delta(self, value: u8) -> Option<i32> {
+        let value = (value & 0x7F) as i32;
+        match self {
+            Self::Absolute => None,
+            Self::Relative2sComp => {
+                Some(ternary!(value < 64, value, value - 128))
+            }
+            Self::RelativeBinaryOffset => Some(value - 64),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct MidiControlConfig {
@@ -12,6 +48,7 @@ pub struct MidiControlConfig {
     pub min: f32,
     pub max: f32,
     pub value: f32,
+    pub encoder: EncoderMode,
 }
 
 impl MidiControlConfig {
@@ -22,7 +59,95 @@ impl MidiControlConfig {
             min: range.0,
             max: range.1,
             value,
+            encoder: EncoderMode::Absolute,
+        }
+    }
+
+    pub fn with_encoder(mut self, encoder: EncoderMode) -> Self {
+        self.encoder = encoder;
+        self
+    }
+
+    /// Move `current` by `delta` relative encoder steps of 1/127th of the
+    /// range each, clamped to the range
+    pub fn apply_delta(&self, current: f32, delta: i32) -> f32 {
+        let step = (self.max - self.min) / 127.0;
+        let (low, high) = (self.min.min(self.max), self.min.max(self.max));
+        (current + step * delta as f32).clamp(low, high)
+    }
+
+    /// 7-bit CC message carrying `value` scaled from this config's range
+    pub fn message(&self, value: f32) -> [u8; 3] {
+        let value = map_range(value, self.min, self.max, 0.0, 127.0);
+        let value = constrain::clamp(value, 0.0, 127.0);
+        [176 + self.channel, self.cc, value.round() as u8]
+    }
+
+    /// MSB/LSB message pair for CCs 0-31, otherwise a single 7-bit message
+    pub fn hrcc_messages(&self, value: f32) -> Vec<[u8; 3]> {
+        let status = 0xB0 | self.channel;
+
+        if self.cc < 32 {
+            let value_14bit =
+                map_range(value, self.min, self.max, 0.0, 16_383.0);
+            let value_14bit =
+                constrain::clamp(value_14bit, 0.0, 16_383.0) as u16;
+
+            let msb = ((value_14bit >> 7) & 0x7F) as u8;
+            let lsb = (value_14bit & 0x7F) as u8;
+
+            vec![[status, self.cc, msb], [status, self.cc + 32, lsb]]
+        } else {
+            let value = map_range(value, self.min, self.max, 0.0, 127.0);
+            let value = constrain::clamp(value, 0.0, 127.0) as u8;
+            vec![[status, self.cc, value]]
+        }
+    }
+}
+
+/// Minimum time between feedback messages for a single control
+pub const MIDI_FEEDBACK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Tracks the messages last echoed back to the MIDI output for each mapped
+/// control so motorized faders and LED rings only receive changes, and no
+/// more often than [`MIDI_FEEDBACK_INTERVAL`] per control. A change made
+/// within the interval is sent by the first call after it elapses.
+#[derive(Debug, Default)]
+pub struct MidiFeedback {
+    sent: HashMap<String, (Vec<[u8; 3]>, Instant)>,
+}
+
+impl MidiFeedback {
+    /// Forget what was sent so the next call resyncs every control
+    pub fn clear(&mut self) {
+        self.sent.clear();
+    }
+
+    pub fn messages(
+        &mut self,
+        values: &[(String, MidiControlConfig, f32)],
+        hrcc: bool,
+        now: Instant,
+    ) -> Vec<[u8; 3]> {
+        let mut messages = vec![];
+
+        for (name, config, value) in values {
+            let next = ternary!(
+                hrcc,
+                config.hrcc_messages(*value),
+                vec![config.message(*value)]
+            );
+            let is_due = self.sent.get(name).is_none_or(|(sent, sent_at)| {
+                *sent != next
+                    && now.duration_since(*sent_at) >= MIDI_FEEDBACK_INTERVAL
+            });
+            if is_due {
+                messages.extend_from_slice(&next);
+                self.sent.insert(name.clone(), (next, now));
+            }
         }
+
+        messages
     }
 }
 
@@ -100,6 +225,17 @@ impl MidiControls {
                     channel, cc, value, hrcc
                 );
 
+                if apply_relative_cc(
+                    &state,
+                    override_state.as_deref(),
+                    &config_lookup,
+                    &override_lookup,
+                    ch_cc,
+                    value,
+                ) {
+                    return;
+                }
+
                 if !hrcc || cc > 63 {
                     if let Some((name, config)) = config_lookup.get(&ch_cc) {
                         let value = value as f32 / 127.0;
@@ -247,14 +383,8 @@ impl MidiControls {
         let values = self.values();
         let mut messages: Vec<[u8; 3]> = vec![];
         for (name, value) in values.iter() {
-            let mut message: [u8; 3] = [0; 3];
             let config = self.configs.get(name).unwrap();
-            message[0] = 176 + config.channel;
-            message[1] = config.cc;
-            let value = map_range(*value, config.min, config.max, 0.0, 127.0);
-            let value = constrain::clamp(value, 0.0, 127.0);
-            message[2] = value.round() as u8;
-            messages.push(message);
+            messages.push(config.message(*value));
         }
         messages
     }
@@ -265,32 +395,12 @@ impl MidiControls {
         debug!("values: {:?}, configs: {:?}", values, self.configs());
         for (name, value) in values.iter() {
             let config = self.configs.get(name).unwrap();
-            let status = 0xB0 | config.channel;
-
-            if config.cc < 32 {
-                let value_14bit =
-                    map_range(*value, config.min, config.max, 0.0, 16_383.0);
-                let value_14bit =
-                    constrain::clamp(value_14bit, 0.0, 16_383.0) as u16;
-
-                let msb = ((value_14bit >> 7) & 0x7F) as u8;
-                let lsb = (value_14bit & 0x7F) as u8;
-
-                messages.push([status, config.cc, msb]);
-                messages.push([status, config.cc + 32, lsb]);
-            } else {
-                let value =
-                    map_range(*value, config.min, config.max, 0.0, 127.0);
-                let value = constrain::clamp(value, 0.0, 127.0) as u8;
-                messages.push([status, config.cc, value]);
-            }
+            messages.extend(config.hrcc_messages(*value));
         }
         messages
     }
 
-    fn configs_by_channel_and_cc(
-        &self,
-    ) -> HashMap<ChannelAndController, (String, MidiControlConfig)> {
+    fn configs_by_channel_and_cc(&self) -> ConfigLookup {
         self.configs
             .iter()
             .map(|(name, config)| {
@@ -299,9 +409,7 @@ impl MidiControls {
             .collect()
     }
 
-    fn override_configs_by_channel_and_cc(
-        &self,
-    ) -> HashMap<ChannelAndController, (String, MidiControlConfig)> {
+    fn override_configs_by_channel_and_cc(&self) -> ConfigLookup {
         self.override_configs
             .iter()
             .map(|(name, config)| {
@@ -366,6 +474,40 @@ impl
     }
 }
 
+// Accumulates a relative encoder's delta into the stored value. Returns false
+// when neither lookup has a relative control on `ch_cc` so the caller can
+// handle absolute 7-bit and 14-bit values.
+fn apply_relative_cc(
+    state: &Mutex<State>,
+    override_state: Option<&Mutex<HashMap<String, f32>>>,
+    config_lookup: &ConfigLookup,
+    override_lookup: &ConfigLookup,
+    ch_cc: ChannelAndController,
+    value: u8,
+) -> bool {
+    let relative = |lookup: &ConfigLookup| {
+        let (name, config) = lookup.get(&ch_cc)?;
+        Some((name.clone(), config.clone(), config.encoder.delta(value)?))
+    };
+
+    let control = relative(config_lookup);
+    if let Some((name, config, delta)) = &control {
+        let mut state = state.lock().unwrap();
+        let current = state.get_optional(name).copied().unwrap_or(config.value);
+        state.set(name, config.apply_delta(current, *delta));
+    }
+
+    let mapping = override_state.zip(relative(override_lookup));
+    if let Some((override_state, (name, config, delta))) = &mapping {
+        let mut override_state = override_state.lock().unwrap();
+        let current = override_state.get(name).copied().unwrap_or(config.value);
+        override_state
+            .insert(name.clone(), config.apply_delta(current, *delta));
+    }
+
+    control.is_some() || mapping.is_some()
+}
+
 #[derive(Default)]
 pub struct MidiControlBuilder {
     controls: MidiControls,
@@ -421,6 +563,120 @@ mod tests {
         assert_eq!(messages[2], [177, 42, 127]);
     }
 
+    #[test]
+    fn relative_encoder_deltas_decode_with_wraparound() {
+        let twos = EncoderMode::Relative2sComp;
+        assert_eq!(twos.delta(1), Some(1));
+        assert_eq!(twos.delta(5), Some(5));
+        assert_eq!(twos.delta(127), Some(-1));
+        assert_eq!(twos.delta(65), Some(-63));
+
+        let offset = EncoderMode::RelativeBinaryOffset;
+        assert_eq!(offset.delta(65), Some(1));
+        assert_eq!(offset.delta(63), Some(-1));
+        assert_eq!(offset.delta(64), Some(0));
+
+        assert_eq!(EncoderMode::Absolute.delta(127), None);
+    }
+
+    #[test]
+    fn relative_encoder_increments_ramp_and_decrements_reverse() {
+        let config = MidiControlConfig::new((0, 20), (0.0, 127.0), 0.0)
+            .with_encoder(EncoderMode::Relative2sComp);
+        let lookup: ConfigLookup = [((0, 20), ("knob".to_string(), config))]
+            .into_iter()
+            .collect();
+        let state = Mutex::new(State::default());
+        let send = |value: u8| {
+            let handled = apply_relative_cc(
+                &state,
+                None,
+                &lookup,
+                &ConfigLookup::default(),
+                (0, 20),
+                value,
+            );
+            assert!(handled);
+            state.lock().unwrap().get("knob")
+        };
+
+        let ramp: Vec<f32> = (0..4).map(|_| send(1)).collect();
+        assert_eq!(ramp, vec![1.0, 2.0, 3.0, 4.0]);
+
+        // An accelerated turn moves several steps at once
+        assert_eq!(send(6), 10.0);
+
+        let reversed: Vec<f32> = (0..3).map(|_| send(127)).collect();
+        assert_eq!(reversed, vec![9.0, 8.0, 7.0]);
+
+        // Clamped at both ends of the range
+        assert_eq!(send(65), 0.0);
+        for _ in 0..4 {
+            send(63);
+        }
+        assert_eq!(state.lock().unwrap().get("knob"), 127.0);
+    }
+
+    #[test]
+    fn absolute_controls_are_left_to_the_regular_path() {
+        let lookup: ConfigLookup = [(
+            (0, 20),
+            (
+                "knob".to_string(),
+                MidiControlConfig::new((0, 20), (0.0, 1.0), 0.0),
+            ),
+        )]
+        .into_iter()
+        .collect();
+        let state = Mutex::new(State::default());
+
+        let handled = apply_relative_cc(
+            &state,
+            None,
+            &lookup,
+            &ConfigLookup::default(),
+            (0, 20),
+            1,
+        );
+
+        assert!(!handled);
+        assert!(!state.lock().unwrap().has("knob"));
+    }
+
+    #[test]
+    fn midi_feedback_throttles_changes_per_control() {
+        let values = |value: f32| {
+            vec![(
+                "fader".to_string(),
+                MidiControlConfig::new((1, 7), (0.0, 1.0), 0.0),
+                value,
+            )]
+        };
+        let mut feedback = MidiFeedback::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            feedback.messages(&values(0.0), false, start),
+            [[177, 7, 0]]
+        );
+        assert!(feedback.messages(&values(0.0), false, start).is_empty());
+
+        // Changes within the interval wait for it to elapse, then send the
+        // latest value only
+        assert!(feedback.messages(&values(0.5), false, start).is_empty());
+        let due = start + MIDI_FEEDBACK_INTERVAL;
+        assert_eq!(
+            feedback.messages(&values(1.0), false, due),
+            [[177, 7, 127]]
+        );
+
+        feedback.clear();
+        assert_eq!(
+            feedback.messages(&values(1.0), true, due),
+            [[177, 7, 127], [177, 39, 127]]
+        );
+    }
+
     #[test]
     fn start_without_port_is_noop() {
         let mut controls = MidiControls::default();
@@ -432,6 +688,7 @@ mod tests {
 
 pub type ChannelAndController = (u8, u8);
 type Msb = u8;
+type ConfigLookup = HashMap<ChannelAndController, (String, MidiControlConfig)>;
 
 #[derive(Debug, Default)]
 struct State {
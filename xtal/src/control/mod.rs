@@ -4,15 +4,20 @@ pub mod control_hub;
 pub mod control_traits;
 mod dep_graph;
 mod eval_cache;
+pub mod hub_builder;
 pub mod map_mode;
 pub mod midi_controls;
 pub mod osc_controls;
 mod param_mod;
+mod script_export;
+mod script_include;
+pub(crate) mod test_hub;
 pub mod ui_controls;
 
 pub use audio_controls::*;
 pub use control_hub::*;
 pub use control_traits::*;
+pub use hub_builder::*;
 pub use midi_controls::*;
 pub use osc_controls::*;
 pub use ui_controls::*;
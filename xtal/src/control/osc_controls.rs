@@ -2,8 +2,9 @@ use std::sync::{Arc, Mutex};
 
 use super::control_traits::{ControlCollection, ControlConfig};
 use crate::core::prelude::*;
+pub use crate::io::osc::OscArg;
 use crate::io::osc::SHARED_OSC_RECEIVER;
-use crate::warn_once;
+use crate::{ternary, warn_once};
 use nannou_osc as osc;
 
 #[derive(Clone, Debug)]
@@ -39,6 +40,12 @@ impl OscControls {
         let state = self.state.clone();
         let configs = self.configs.clone();
 
+        let sync_state = self.state.clone();
+        let sync_configs = self.configs.clone();
+        SHARED_OSC_RECEIVER.set_sync_handler(move || {
+            sync_messages(&sync_configs, &sync_state.lock().unwrap())
+        });
+
         SHARED_OSC_RECEIVER.register_callback("*", move |msg| {
             let key = msg.addr.trim_start_matches('/');
 
@@ -62,6 +69,35 @@ impl OscControls {
         self.is_active = true;
         Ok(())
     }
+
+    /// One message per control, addressed by its name, carrying its current
+    /// value normalized back to the `[0, 1]` range controllers send. These are
+    /// the replies to a [`SYNC_ADDRESS`](crate::io::osc::SYNC_ADDRESS)
+    /// request.
+    pub fn sync_messages(&self) -> Vec<osc::Message> {
+        sync_messages(&self.configs, &self.state.lock().unwrap())
+    }
+}
+
+fn sync_messages(
+    configs: &HashMap<String, OscControlConfig>,
+    state: &State,
+) -> Vec<osc::Message> {
+    let mut names: Vec<&String> = configs.keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let config = &configs[name];
+            let value =
+                state.get_optional(name).copied().unwrap_or(config.value);
+            let range = config.max - config.min;
+            let normalized =
+                ternary!(range == 0.0, 0.0, (value - config.min) / range);
+            osc::msg(format!("/{}", name), vec![osc::Type::Float(normalized)])
+        })
+        .collect()
 }
 
 impl
@@ -183,3 +219,28 @@ fn check_address(address: &str) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_messages_reply_with_normalized_values() {
+        let mut controls = OscControlBuilder::new()
+            .control(
+                "radius",
+                OscControlConfig::new("radius", (0.0, 10.0), 2.5),
+            )
+            .control("hue", OscControlConfig::new("hue", (0.0, 1.0), 0.0))
+            .build();
+        controls.set("hue", 0.75);
+
+        assert_eq!(
+            controls.sync_messages(),
+            vec![
+                osc::msg("/hue", vec![osc::Type::Float(0.75)]),
+                osc::msg("/radius", vec![osc::Type::Float(0.25)]),
+            ]
+        );
+    }
+}
@@ -101,6 +101,23 @@ pub trait SetFromParam {
     fn set_from_param(&mut self, name: &str, value: f32);
 }
 
+/// Range to scale a MIDI mapping to when it targets a param directly and
+/// there's no slider range to borrow. Follows the suggested ranges in the
+/// control script reference; `None` for names that aren't modulatable params.
+pub fn suggested_param_range(name: &str) -> Option<(f32, f32)> {
+    match name {
+        "gain" | "drive" => Some((1.0, 10.0)),
+        "symmetry" => Some((0.5, 2.0)),
+        "bias" => Some((-1.0, 1.0)),
+        "shape" => Some((-2.0, 2.0)),
+        "beats" => Some((0.25, 16.0)),
+        "mix" | "step" | "rise" | "fall" | "lower_threshold"
+        | "upper_threshold" | "output_low" | "output_high" | "operand"
+        | "phase" | "delay" | "slew" => Some((0.0, 1.0)),
+        _ => None,
+    }
+}
+
 fn warn_for(thing: &str, field: &str) {
     warn_once!("{} does not support field: {}", thing, field);
 }
@@ -155,6 +172,8 @@ macro_rules! impl_effect_params {
     };
 }
 
+impl_effect_params!(Comparator, EffectKind::Comparator, threshold);
+impl_effect_params!(Delay, EffectKind::Delay, beats);
 impl_effect_params!(
     Hysteresis,
     EffectKind::Hysteresis,
@@ -234,6 +253,44 @@ impl SetFromParam for TriangleConfig {
     }
 }
 
+impl SetFromParam for GateConfig {
+    fn set_from_param(&mut self, name: &str, value: f32) {
+        match name {
+            "beats" => self.beats = ParamValue::Cold(value),
+            _ => warn_for("Gate", name),
+        }
+    }
+}
+
+impl SetFromParam for EuclideanConfig {
+    fn set_from_param(&mut self, name: &str, value: f32) {
+        match name {
+            "beats" => self.beats = ParamValue::Cold(value),
+            _ => warn_for("Euclidean", name),
+        }
+    }
+}
+
+impl SetFromParam for NoiseConfig {
+    fn set_from_param(&mut self, name: &str, value: f32) {
+        match name {
+            "beats" => self.beats = ParamValue::Cold(value),
+            _ => warn_for("Noise", name),
+        }
+    }
+}
+
+impl SetFromParam for SpringConfig {
+    fn set_from_param(&mut self, name: &str, value: f32) {
+        match name {
+            "target" => self.target = ParamValue::Cold(value),
+            "stiffness" => self.stiffness = ParamValue::Cold(value),
+            "damping" => self.damping = ParamValue::Cold(value),
+            _ => warn_for("Spring", name),
+        }
+    }
+}
+
 fn cold_or_default(param: &ParamValue, default: f32) -> f32 {
     match param {
         ParamValue::Cold(v) => *v,
@@ -0,0 +1,592 @@
+//! Write live UI control values back into a control script's `default`
+//! fields. The script is read with a YAML event parser and only the source
+//! text of the affected values is replaced, so comments, ordering, anchors,
+//! flow style and everything other than the given controls' defaults survive
+//! untouched.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use yaml_rust2::Event;
+use yaml_rust2::parser::Parser;
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+use crate::core::prelude::*;
+use crate::ternary;
+
+const INCLUDE_TAG: &str = "include";
+
+/// Rewrite the `default:` field of every top-level mapping in `yaml` named in
+/// `values`. Mappings without a `default:` get one added after their `type:`
+/// (on its own line for block mappings, as another entry for flow mappings).
+/// Defaults that are `$` parameter references or multi-line values are left
+/// alone.
+pub fn update_defaults(
+    yaml: &str,
+    values: &HashMap<String, ControlValue>,
+) -> Result<String, String> {
+    let source = Source::new(yaml);
+    let mut events = Events::new(yaml);
+    let mut edits = vec![];
+
+    // Find the document's root mapping
+    loop {
+        match events.next()? {
+            (Event::MappingStart(..), _) => break,
+            (Event::StreamEnd, _) => return Ok(yaml.to_string()),
+            _ => {}
+        }
+    }
+
+    loop {
+        let name = match events.next()? {
+            (Event::MappingEnd, _) => break,
+            (Event::Scalar(name, ..), _) => name,
+            (event, _) => {
+                events.skip_node(event)?;
+                let (event, _) = events.next()?;
+                events.skip_node(event)?;
+                continue;
+            }
+        };
+
+        let (event, marker) = events.next()?;
+        match (values.get(&name), event) {
+            (Some(value), Event::MappingStart(..)) => {
+                let control = read_control(&mut events, &source, marker)?;
+                edits.extend(control.edit(&source, &format_value(value)));
+            }
+            (_, event) => events.skip_node(event)?,
+        }
+    }
+
+    let mut output = yaml.to_string();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+    for edit in edits {
+        output.replace_range(edit.start..edit.end, &edit.text);
+    }
+    Ok(output)
+}
+
+/// Update the defaults in the script at `path` and, recursively, in the files
+/// it `!include`s, which is where controls pulled in from fragments are
+/// declared. Only files whose text changes are written.
+pub fn export_defaults(
+    path: &Path,
+    values: &HashMap<String, ControlValue>,
+) -> Result<(), String> {
+    let mut visited = HashSet::new();
+    export_file(path, values, &mut visited)
+}
+
+fn export_file(
+    path: &Path,
+    values: &HashMap<String, ControlValue>,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<(), String> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let yaml = fs::read_to_string(path).map_err(|err| {
+        format!("failed to read '{}': {}", path.display(), err)
+    })?;
+    let updated = update_defaults(&yaml, values)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    if updated != yaml {
+        fs::write(path, updated).map_err(|err| {
+            format!("failed to write '{}': {}", path.display(), err)
+        })?;
+    }
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    for include in include_paths(&yaml)? {
+        export_file(&base_dir.join(include), values, visited)?;
+    }
+
+    Ok(())
+}
+
+fn include_paths(yaml: &str) -> Result<Vec<String>, String> {
+    let mut events = Events::new(yaml);
+    let mut paths = vec![];
+    loop {
+        match events.next()? {
+            (Event::StreamEnd, _) => return Ok(paths),
+            (Event::Scalar(path, _, _, Some(tag)), _)
+                if tag.suffix == INCLUDE_TAG =>
+            {
+                paths.push(path);
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+// Where a control's `default` is, or where one can be added
+struct ControlSpans {
+    flow_end: Option<Marker>,
+    type_key: Option<Marker>,
+    default: Option<DefaultSpan>,
+}
+
+enum DefaultSpan {
+    Replace(usize, usize),
+    Keep,
+}
+
+impl ControlSpans {
+    fn edit(&self, source: &Source, value: &str) -> Option<Edit> {
+        match (&self.default, self.flow_end, self.type_key) {
+            (Some(DefaultSpan::Replace(start, end)), _, _) => Some(Edit {
+                start: *start,
+                end: *end,
+                text: value.to_string(),
+            }),
+            (Some(DefaultSpan::Keep), _, _) | (None, _, None) => None,
+            (None, Some(flow_end), Some(_)) => {
+                let end = source.byte(flow_end);
+                let start = source.text[..end].trim_end().len();
+                Some(Edit {
+                    start,
+                    end: start,
+                    text: format!(", default: {}", value),
+                })
+            }
+            (None, None, Some(type_key)) => {
+                let line_start = source.byte(type_key) - type_key.col();
+                let start = source.text[line_start..]
+                    .find('\n')
+                    .map_or(source.text.len(), |offset| line_start + offset);
+                Some(Edit {
+                    start,
+                    end: start,
+                    text: format!(
+                        "\n{}default: {}",
+                        " ".repeat(type_key.col()),
+                        value
+                    ),
+                })
+            }
+        }
+    }
+}
+
+// Reads the entries of a control's mapping, whose `MappingStart` was just
+// consumed at `start`
+fn read_control(
+    events: &mut Events,
+    source: &Source,
+    start: Marker,
+) -> Result<ControlSpans, String> {
+    let mut is_flow = None;
+    let mut spans = ControlSpans {
+        flow_end: None,
+        type_key: None,
+        default: None,
+    };
+
+    loop {
+        let (key, key_marker) = match events.next()? {
+            (Event::MappingEnd, marker) => {
+                if is_flow == Some(true) {
+                    spans.flow_end = Some(marker);
+                }
+                return Ok(spans);
+            }
+            (Event::Scalar(key, ..), marker) => (key, marker),
+            (event, _) => {
+                events.skip_node(event)?;
+                let (event, _) = events.next()?;
+                events.skip_node(event)?;
+                continue;
+            }
+        };
+
+        // A flow mapping's start marks its `{`, a block mapping's doesn't
+        // precede its first key
+        is_flow.get_or_insert_with(|| {
+            start.index() < key_marker.index()
+                && source.char_at(start) == Some('{')
+        });
+
+        let (event, marker) = events.next()?;
+        match key.as_str() {
+            "type" => spans.type_key = Some(key_marker),
+            "default" => {
+                spans.default =
+                    Some(default_span(events, source, &event, marker)?);
+            }
+            _ => {}
+        }
+        if key != "default" {
+            events.skip_node(event)?;
+        }
+    }
+}
+
+fn default_span(
+    events: &mut Events,
+    source: &Source,
+    event: &Event,
+    marker: Marker,
+) -> Result<DefaultSpan, String> {
+    let start = source.byte(marker);
+    match event {
+        Event::Scalar(value, TScalarStyle::Plain, ..) => {
+            if value.starts_with('$') || value.contains('\n') {
+                return Ok(DefaultSpan::Keep);
+            }
+            let end = source.text[start..]
+                .char_indices()
+                .nth(value.chars().count())
+                .map_or(source.text.len(), |(offset, _)| start + offset);
+            Ok(DefaultSpan::Replace(start, end))
+        }
+        Event::Scalar(_, TScalarStyle::SingleQuoted, ..) => {
+            Ok(quoted_end(&source.text[start..], '\'')
+                .map_or(DefaultSpan::Keep, |len| {
+                    DefaultSpan::Replace(start, start + len)
+                }))
+        }
+        Event::Scalar(_, TScalarStyle::DoubleQuoted, ..) => {
+            Ok(quoted_end(&source.text[start..], '"')
+                .map_or(DefaultSpan::Keep, |len| {
+                    DefaultSpan::Replace(start, start + len)
+                }))
+        }
+        Event::SequenceStart(..) if source.char_at(marker) == Some('[') => {
+            let end = events.skip_to_end(1)?;
+            Ok(DefaultSpan::Replace(start, source.byte(end) + 1))
+        }
+        event => {
+            events.skip_node(event.clone())?;
+            Ok(DefaultSpan::Keep)
+        }
+    }
+}
+
+// Length of the quoted scalar at the start of `text`, including its quotes.
+// Single quotes escape themselves by doubling, double quotes use `\`.
+fn quoted_end(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            c if c == quote => {
+                if quote == '\''
+                    && chars.peek().is_some_and(|(_, c)| *c == '\'')
+                {
+                    chars.next();
+                    continue;
+                }
+                return Some(i + c.len_utf8());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// The parser reports positions in chars; edits need byte offsets
+struct Source<'a> {
+    text: &'a str,
+    offsets: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    fn new(text: &'a str) -> Self {
+        let offsets = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([text.len()])
+            .collect();
+        Self { text, offsets }
+    }
+
+    fn byte(&self, marker: Marker) -> usize {
+        self.offsets
+            .get(marker.index())
+            .copied()
+            .unwrap_or(self.text.len())
+    }
+
+    fn char_at(&self, marker: Marker) -> Option<char> {
+        self.text[self.byte(marker)..].chars().next()
+    }
+}
+
+struct Events<'a> {
+    parser: Parser<std::str::Chars<'a>>,
+}
+
+impl<'a> Events<'a> {
+    fn new(yaml: &'a str) -> Self {
+        Self {
+            parser: Parser::new_from_str(yaml),
+        }
+    }
+
+    fn next(&mut self) -> Result<(Event, Marker), String> {
+        self.parser.next_token().map_err(|err| err.to_string())
+    }
+
+    // Consume the rest of the node that `event` started
+    fn skip_node(&mut self, event: Event) -> Result<(), String> {
+        if matches!(event, Event::SequenceStart(..) | Event::MappingStart(..)) {
+            self.skip_to_end(1)?;
+        }
+        Ok(())
+    }
+
+    // Consume events until `depth` open collections have closed, returning
+    // the marker of the last one's end
+    fn skip_to_end(&mut self, mut depth: usize) -> Result<Marker, String> {
+        loop {
+            match self.next()? {
+                (Event::SequenceStart(..) | Event::MappingStart(..), _) => {
+                    depth += 1;
+                }
+                (Event::SequenceEnd | Event::MappingEnd, marker) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(marker);
+                    }
+                }
+                (Event::StreamEnd, _) => {
+                    return Err("unexpected end of YAML".to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn format_value(value: &ControlValue) -> String {
+    match value {
+        ControlValue::Float(v) => v.to_string(),
+        ControlValue::Bool(v) => v.to_string(),
+        ControlValue::String(v) => {
+            let is_plain = !v.is_empty()
+                && v.chars().all(|c| {
+                    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+                })
+                && !matches!(v.as_str(), "true" | "false" | "null" | "~")
+                && v.parse::<f64>().is_err();
+            ternary!(is_plain, v.clone(), format!("{:?}", v))
+        }
+        ControlValue::Vec2(v) => format_seq(v),
+        ControlValue::Color(v) => format_seq(v),
+    }
+}
+
+fn format_seq(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(f32::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_script(dir: &str, name: &str, yaml: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join("xtal-script-export-tests")
+            .join(dir)
+            .join(name);
+        fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+        fs::write(&path, yaml).expect("write script");
+        path
+    }
+
+    fn values(
+        entries: &[(&str, ControlValue)],
+    ) -> HashMap<String, ControlValue> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn updates_only_default_fields() {
+        let yaml = r#"# sizes
+size:
+  type: slider
+  range: [0, 100]
+  default: 10 # starting size
+
+invert:
+  type: checkbox
+
+shape:
+  type: select
+  options: [circle, square]
+  default: circle
+
+wobble:
+  type: triangle
+  beats: 4
+  range: [0, $size]
+
+folder:
+  type: effect
+  kind: wave_folder
+  symmetry: $size
+"#;
+
+        let updated = update_defaults(
+            yaml,
+            &values(&[
+                ("size", ControlValue::Float(42.5)),
+                ("invert", ControlValue::Bool(true)),
+                ("shape", ControlValue::String("square".to_string())),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            r#"# sizes
+size:
+  type: slider
+  range: [0, 100]
+  default: 42.5 # starting size
+
+invert:
+  type: checkbox
+  default: true
+
+shape:
+  type: select
+  options: [circle, square]
+  default: square
+
+wobble:
+  type: triangle
+  beats: 4
+  range: [0, $size]
+
+folder:
+  type: effect
+  kind: wave_folder
+  symmetry: $size
+"#
+        );
+    }
+
+    #[test]
+    fn leaves_parameter_references_intact() {
+        let yaml = r#"amount:
+  type: slider
+  default: 0
+linked:
+  type: slider
+  default: $amount
+ramp:
+  type: automate
+  breakpoints:
+    - kind: ramp
+      position: 0
+      value: $amount
+    - kind: end
+      position: 4
+      value: 0
+"#;
+
+        let updated = update_defaults(
+            yaml,
+            &values(&[
+                ("amount", ControlValue::Float(0.3)),
+                ("linked", ControlValue::Float(0.9)),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(updated, yaml.replace("default: 0\n", "default: 0.3\n"));
+    }
+
+    #[test]
+    fn updates_flow_style_mappings() {
+        let yaml = r#"size: { type: slider, range: [0, 100], default: 10 }
+invert: {type: checkbox}
+color: { type: color, default: [1, 0, 0, 1] } # red
+shape: { type: select, options: [a, b], default: 'a' }
+"#;
+
+        let updated = update_defaults(
+            yaml,
+            &values(&[
+                ("size", ControlValue::Float(42.5)),
+                ("invert", ControlValue::Bool(true)),
+                ("color", ControlValue::Color([0.0, 0.5, 1.0, 1.0])),
+                ("shape", ControlValue::String("b".to_string())),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            r#"size: { type: slider, range: [0, 100], default: 42.5 }
+invert: {type: checkbox, default: true}
+color: { type: color, default: [0, 0.5, 1, 1] } # red
+shape: { type: select, options: [a, b], default: b }
+"#
+        );
+    }
+
+    #[test]
+    fn updates_controls_in_included_files() {
+        let fragment = r#"# shared
+hue:
+  type: slider
+  default: 0.1
+"#;
+        let fragment_path =
+            write_script("include", "shared/colors.yaml", fragment);
+        let script = r#"<<: !include shared/colors.yaml
+
+size:
+  type: slider
+  default: 1
+"#;
+        let script_path = write_script("include", "script.yaml", script);
+
+        export_defaults(
+            &script_path,
+            &values(&[
+                ("hue", ControlValue::Float(0.75)),
+                ("size", ControlValue::Float(2.0)),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&script_path).unwrap(),
+            script.replace("default: 1", "default: 2")
+        );
+        assert_eq!(
+            fs::read_to_string(&fragment_path).unwrap(),
+            fragment.replace("default: 0.1", "default: 0.75")
+        );
+    }
+
+    #[test]
+    fn invalid_yaml_is_an_error() {
+        let result = update_defaults(
+            "size: { type: slider\n",
+            &values(&[("size", ControlValue::Float(1.0))]),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! `!include` support for control scripts. A `!include path.yaml` value is
+//! replaced by the parsed contents of that file, resolved relative to the file
+//! containing it, before merge keys are applied. Combined with a merge key
+//! (`<<: !include shared/colors.yaml`) this pulls a fragment's controls into
+//! the including script.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yml::{Mapping, Value};
+
+const INCLUDE_TAG: &str = "include";
+
+/// Replace every `!include` in `value`, returning the result along with the
+/// canonical path of every file pulled in. Relative paths resolve against the
+/// directory of `script_path`; without one (e.g. an embedded script) any
+/// include is an error.
+pub fn resolve_includes(
+    value: Value,
+    script_path: Option<&Path>,
+) -> Result<(Value, Vec<PathBuf>), String> {
+    let mut stack = Vec::new();
+    if let Some(path) = script_path {
+        stack.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+    let base_dir = script_path.and_then(Path::parent);
+    let mut included = Vec::new();
+    let value = resolve(value, base_dir, &mut stack, &mut included)?;
+    Ok((value, included))
+}
+
+// `stack` holds the canonical paths of the files currently being included so
+// a file including one of its includers is reported instead of recursing.
+fn resolve(
+    value: Value,
+    base_dir: Option<&Path>,
+    stack: &mut Vec<PathBuf>,
+    included: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    match value {
+        Value::Tagged(tagged) if tagged.tag == INCLUDE_TAG => {
+            let Value::String(relative) = tagged.value else {
+                return Err("!include expects a file path".to_string());
+            };
+            let Some(base_dir) = base_dir else {
+                return Err(format!(
+                    "cannot resolve `!include {}` without a control script path",
+                    relative
+                ));
+            };
+
+            let path = base_dir.join(&relative);
+            let canonical = path.canonicalize().map_err(|err| {
+                format!("failed to include '{}': {}", path.display(), err)
+            })?;
+            if let Some(start) = stack.iter().position(|p| *p == canonical) {
+                let cycle: Vec<String> = stack[start..]
+                    .iter()
+                    .chain([&canonical])
+                    .map(|p| p.display().to_string())
+                    .collect();
+                return Err(format!("include cycle: {}", cycle.join(" -> ")));
+            }
+
+            let yaml = fs::read_to_string(&canonical).map_err(|err| {
+                format!("failed to include '{}': {}", path.display(), err)
+            })?;
+            let fragment: Value =
+                serde_yml::from_str(&yaml).map_err(|err| {
+                    format!("failed to parse '{}': {}", path.display(), err)
+                })?;
+
+            if !included.contains(&canonical) {
+                included.push(canonical.clone());
+            }
+            stack.push(canonical.clone());
+            let resolved =
+                resolve(fragment, canonical.parent(), stack, included);
+            stack.pop();
+            resolved
+        }
+        Value::Tagged(mut tagged) => {
+            tagged.value = resolve(tagged.value, base_dir, stack, included)?;
+            Ok(Value::Tagged(tagged))
+        }
+        Value::Mapping(mapping) => mapping
+            .into_iter()
+            .map(|(key, value)| {
+                Ok((key, resolve(value, base_dir, stack, included)?))
+            })
+            .collect::<Result<Mapping, String>>()
+            .map(Value::Mapping),
+        Value::Sequence(sequence) => sequence
+            .into_iter()
+            .map(|value| resolve(value, base_dir, stack, included))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::Sequence),
+        other => Ok(other),
+    }
+}
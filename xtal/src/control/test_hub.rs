@@ -0,0 +1,90 @@
+//! Drive a [`ControlHub`] from tests without a window or GPU. The hub runs on
+//! [`TestTiming`], so a test moves the transport to an exact beat or frame and
+//! asserts what the script produces there.
+//!
+//! ```rust
+//! use xtal::prelude::*;
+//! use xtal::prelude::test::*;
+//!
+//! let mut hub = TestHubBuilder::new()
+//!     .bpm(120.0)
+//!     .script(
+//!         r#"
+//! wave:
+//!   type: triangle
+//!   beats: 2
+//! "#,
+//!     )
+//!     .build();
+//!
+//! hub.advance_to_beat(1.0);
+//! assert_eq!(hub.get("wave"), 1.0);
+//! ```
+
+use super::control_hub::ControlHub;
+use crate::motion::test_timing::TestTiming;
+
+const DEFAULT_BPM: f32 = 120.0;
+const DEFAULT_FPS: f32 = 60.0;
+
+/// Builds a [`ControlHub`] on [`TestTiming`], starting at beat 0
+#[derive(Clone, Debug)]
+pub struct TestHubBuilder {
+    script: Option<String>,
+    bpm: f32,
+    fps: f32,
+}
+
+impl Default for TestHubBuilder {
+    fn default() -> Self {
+        Self {
+            script: None,
+            bpm: DEFAULT_BPM,
+            fps: DEFAULT_FPS,
+        }
+    }
+}
+
+impl TestHubBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The Control Script YAML to load
+    pub fn script(mut self, yaml: &str) -> Self {
+        self.script = Some(yaml.to_string());
+        self
+    }
+
+    pub fn bpm(mut self, bpm: f32) -> Self {
+        self.bpm = bpm;
+        self
+    }
+
+    pub fn fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn build(self) -> ControlHub<TestTiming> {
+        let timing = TestTiming::new(self.bpm, self.fps);
+        timing.advance_to_beat(0.0);
+        ControlHub::new(self.script.as_deref(), timing)
+    }
+}
+
+impl ControlHub<TestTiming> {
+    /// Move the transport to `beat` and run the per-frame update, so snapshot
+    /// transitions and sequences see the new position like they would at
+    /// runtime
+    pub fn advance_to_beat(&mut self, beat: f32) {
+        self.animation.advance_to_beat(beat);
+        self.update();
+    }
+
+    /// Same as [`Self::advance_to_beat`] for a frame number
+    pub fn advance_to_frame(&mut self, frame: u32) {
+        self.animation.advance_to_frame(frame);
+        self.update();
+    }
+}
@@ -17,6 +17,9 @@ pub enum ControlValue {
     Float(f32),
     Bool(bool),
     String(String),
+    Vec2([f32; 2]),
+    /// RGBA with each channel in `[0, 1]`
+    Color([f32; 4]),
 }
 
 impl ControlValue {
@@ -43,6 +46,33 @@ impl ControlValue {
             None
         }
     }
+
+    pub fn as_vec2(&self) -> Option<[f32; 2]> {
+        if let ControlValue::Vec2(v) = self {
+            Some(*v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_color(&self) -> Option<[f32; 4]> {
+        if let ControlValue::Color(v) = self {
+            Some(*v)
+        } else {
+            None
+        }
+    }
+
+    /// The variant name, for error messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ControlValue::Float(_) => "Float",
+            ControlValue::Bool(_) => "Bool",
+            ControlValue::String(_) => "String",
+            ControlValue::Vec2(_) => "Vec2",
+            ControlValue::Color(_) => "Color",
+        }
+    }
 }
 
 impl Default for ControlValue {
@@ -69,6 +99,61 @@ impl From<String> for ControlValue {
     }
 }
 
+impl From<[f32; 2]> for ControlValue {
+    fn from(value: [f32; 2]) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<[f32; 4]> for ControlValue {
+    fn from(value: [f32; 4]) -> Self {
+        Self::Color(value)
+    }
+}
+
+/// Returned by the `TryFrom<&ControlValue>` conversions when the value holds
+/// a different variant than the one asked for
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlValueTypeError {
+    pub expected: &'static str,
+    pub actual: &'static str,
+}
+
+impl fmt::Display for ControlValueTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected a {} control value, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ControlValueTypeError {}
+
+macro_rules! impl_try_from_control_value {
+    ($target:ty, $accessor:ident, $expected:literal) => {
+        impl TryFrom<&ControlValue> for $target {
+            type Error = ControlValueTypeError;
+
+            fn try_from(value: &ControlValue) -> Result<Self, Self::Error> {
+                value.$accessor().map(Into::into).ok_or_else(|| {
+                    ControlValueTypeError {
+                        expected: $expected,
+                        actual: value.kind(),
+                    }
+                })
+            }
+        }
+    };
+}
+
+impl_try_from_control_value!(f32, as_float, "Float");
+impl_try_from_control_value!(bool, as_bool, "Bool");
+impl_try_from_control_value!(String, as_string, "String");
+impl_try_from_control_value!([f32; 2], as_vec2, "Vec2");
+impl_try_from_control_value!([f32; 4], as_color, "Color");
+
 /// Used by [`UiControls`] to compute if a [`UiControlConfig`] should be
 /// disabled or not based on the value of other controls
 ///
@@ -351,6 +436,11 @@ impl UiControls {
         }
     }
 
+    /// The current value of `name` whatever its type
+    pub fn value(&self, name: &str) -> Option<&ControlValue> {
+        self.values.get(name)
+    }
+
     pub fn float(&self, name: &str) -> f32 {
         self.values
             .get(name)
@@ -718,4 +808,38 @@ mod tests {
         controls.mark_unchanged();
         assert!(!controls.changed());
     }
+
+    #[test]
+    fn test_typed_accessors() {
+        let vec2 = ControlValue::from([0.25, 0.75]);
+        assert_eq!(vec2.as_vec2(), Some([0.25, 0.75]));
+        assert_eq!(vec2.as_color(), None);
+        assert_eq!(vec2.as_float(), None);
+
+        let color = ControlValue::from([1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(color.as_color(), Some([1.0, 0.5, 0.0, 1.0]));
+        assert_eq!(color.as_vec2(), None);
+    }
+
+    #[test]
+    fn test_try_from_reports_mismatched_variants() {
+        assert_eq!(f32::try_from(&ControlValue::Float(0.5)), Ok(0.5));
+        assert_eq!(bool::try_from(&ControlValue::Bool(true)), Ok(true));
+        assert_eq!(
+            String::try_from(&ControlValue::String("a".into())),
+            Ok("a".to_string())
+        );
+        assert_eq!(
+            <[f32; 2]>::try_from(&ControlValue::Vec2([1.0, 2.0])),
+            Ok([1.0, 2.0])
+        );
+
+        let err = <[f32; 4]>::try_from(&ControlValue::Float(0.5)).unwrap_err();
+        assert_eq!(err.expected, "Color");
+        assert_eq!(err.actual, "Float");
+        assert_eq!(
+            err.to_string(),
+            "Expected a Color control value, got Float"
+        );
+    }
 }
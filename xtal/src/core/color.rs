@@ -0,0 +1,155 @@
+//! Conversions between sRGB, linear RGB and [OKLab], plus blending in OKLab
+//! so color transitions stay perceptually even instead of dipping through
+//! muddy, dark midpoints. Colors are `[f32; 3]`, nominally 0 to 1 per channel
+//! for RGB; out of gamut values pass through unclamped.
+//!
+//! ```rust
+//! use xtal::core::color::{linear_to_srgb, oklab_mix, srgb_to_linear};
+//!
+//! let red = srgb_to_linear([1.0, 0.0, 0.0]);
+//! let blue = srgb_to_linear([0.0, 0.0, 1.0]);
+//! let purple = linear_to_srgb(oklab_mix(red, blue, 0.5));
+//! ```
+//!
+//! [OKLab]: https://bottosson.github.io/posts/oklab/
+
+/// Decode sRGB gamma to linear light
+pub fn srgb_to_linear(rgb: [f32; 3]) -> [f32; 3] {
+    rgb.map(|value| {
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+/// Encode linear light with the sRGB gamma curve
+pub fn linear_to_srgb(rgb: [f32; 3]) -> [f32; 3] {
+    rgb.map(|value| {
+        if value <= 0.003_130_8 {
+            value * 12.92
+        } else {
+            1.055 * value.powf(1.0 / 2.4) - 0.055
+        }
+    })
+}
+
+/// Linear RGB to OKLab `[lightness, a, b]`. Lightness runs 0 to 1 from black
+/// to white; `a` and `b` are 0 for neutral grays.
+pub fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_99 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+pub fn oklab_to_linear([lightness, a, b]: [f32; 3]) -> [f32; 3] {
+    let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m = (lightness - 0.105_561_35 * a - 0.063_854_17 * b).powi(3);
+    let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_4 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+pub fn srgb_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    linear_to_oklab(srgb_to_linear(rgb))
+}
+
+pub fn oklab_to_srgb(lab: [f32; 3]) -> [f32; 3] {
+    linear_to_srgb(oklab_to_linear(lab))
+}
+
+/// Blend linear RGB colors `a` and `b` by `t` (0 is `a`, 1 is `b`) through
+/// OKLab, so lightness and hue change evenly along the way
+pub fn oklab_mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    let (a, b) = (linear_to_oklab(a), linear_to_oklab(b));
+    oklab_to_linear(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    fn assert_color(actual: [f32; 3], expected: [f32; 3]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert_close(actual, expected, "channel");
+        }
+    }
+
+    #[test]
+    fn srgb_round_trips_through_oklab() {
+        for rgb in [
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.2, 0.6, 0.9],
+            [0.93, 0.41, 0.05],
+            [0.5, 0.5, 0.5],
+        ] {
+            assert_color(oklab_to_srgb(srgb_to_oklab(rgb)), rgb);
+            assert_color(linear_to_srgb(srgb_to_linear(rgb)), rgb);
+        }
+    }
+
+    #[test]
+    fn oklab_reference_values() {
+        // From the OKLab reference implementation
+        assert_color(linear_to_oklab([1.0, 1.0, 1.0]), [1.0, 0.0, 0.0]);
+        assert_color(
+            srgb_to_oklab([1.0, 0.0, 0.0]),
+            [0.62796, 0.22486, 0.12585],
+        );
+        assert_color(
+            srgb_to_oklab([0.0, 0.0, 1.0]),
+            [0.45201, -0.03246, -0.31153],
+        );
+    }
+
+    #[test]
+    fn black_to_white_midpoint_is_perceptual_mid_gray() {
+        let gray = oklab_mix([0.0; 3], [1.0; 3], 0.5);
+
+        assert_close(gray[0], gray[1], "red = green");
+        assert_close(gray[1], gray[2], "green = blue");
+        assert_close(linear_to_oklab(gray)[0], 0.5, "lightness");
+
+        // Halfway in linear light reads far brighter than halfway
+        assert!(gray[0] < 0.5);
+        assert!(linear_to_oklab([0.5; 3])[0] > 0.75);
+    }
+
+    #[test]
+    fn mixing_hues_avoids_a_muddy_midpoint() {
+        let red = srgb_to_linear([1.0, 0.0, 0.0]);
+        let green = srgb_to_linear([0.0, 1.0, 0.0]);
+        let naive = srgb_to_linear([0.5, 0.5, 0.0]);
+
+        let mixed = oklab_mix(red, green, 0.5);
+        let lightness = linear_to_oklab(mixed)[0];
+
+        assert_close(
+            lightness,
+            (linear_to_oklab(red)[0] + linear_to_oklab(green)[0]) / 2.0,
+            "mixed lightness",
+        );
+        assert!(lightness > linear_to_oklab(naive)[0] + 0.1);
+    }
+
+    #[test]
+    fn mix_endpoints_are_the_inputs() {
+        let (a, b) = ([0.1, 0.7, 0.3], [0.9, 0.2, 0.4]);
+        assert_color(oklab_mix(a, b, 0.0), a);
+        assert_color(oklab_mix(a, b, 1.0), b);
+    }
+}
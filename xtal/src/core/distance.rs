@@ -0,0 +1,186 @@
+//! Signed distance functions for 2D shapes, mirroring the WGSL helpers many
+//! sketches carry so CPU-side geometry (placement, hit testing, contour
+//! points) can agree with what the shader draws. Distances are negative
+//! inside a shape, zero on its edge and positive outside.
+
+/// Circle of `radius` centered on the origin
+pub fn circle(p: [f32; 2], radius: f32) -> f32 {
+    length(p) - radius
+}
+
+/// Axis-aligned rectangle centered on the origin, extending `half_size` in
+/// each direction
+pub fn rect(p: [f32; 2], half_size: [f32; 2]) -> f32 {
+    let q = [p[0].abs() - half_size[0], p[1].abs() - half_size[1]];
+    let outside = length([q[0].max(0.0), q[1].max(0.0)]);
+    let inside = q[0].max(q[1]).min(0.0);
+    outside + inside
+}
+
+/// Unsigned distance to the segment from `a` to `b`. Subtract a radius for a
+/// capsule.
+pub fn segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    length(sub(p, closest_on_segment(p, a, b)))
+}
+
+/// Closed polygon through `vertices` in either winding order.
+/// Self-intersecting polygons use the even-odd rule; no vertices is
+/// infinitely far away.
+pub fn polygon(p: [f32; 2], vertices: &[[f32; 2]]) -> f32 {
+    let Some(&last) = vertices.last() else {
+        return f32::INFINITY;
+    };
+
+    let mut nearest = f32::INFINITY;
+    let mut sign = 1.0;
+    let mut previous = last;
+    for &vertex in vertices {
+        let offset = sub(p, closest_on_segment(p, vertex, previous));
+        nearest = nearest.min(dot(offset, offset));
+
+        // Crossing test against a ray cast in +x
+        let edge = sub(previous, vertex);
+        let w = sub(p, vertex);
+        let above = p[1] >= vertex[1];
+        let below = p[1] < previous[1];
+        let left = edge[0] * w[1] > edge[1] * w[0];
+        if (above && below && left) || (!above && !below && !left) {
+            sign = -sign;
+        }
+        previous = vertex;
+    }
+
+    sign * nearest.sqrt()
+}
+
+/// Area covered by either shape
+pub fn union(a: f32, b: f32) -> f32 {
+    a.min(b)
+}
+
+/// Area of `a` with `b` cut out of it
+pub fn subtract(a: f32, b: f32) -> f32 {
+    a.max(-b)
+}
+
+/// Area covered by both shapes
+pub fn intersect(a: f32, b: f32) -> f32 {
+    a.max(b)
+}
+
+/// Polynomial smooth minimum: a union whose seam is rounded over roughly `k`
+/// units. Matches the `smooth_min` in the sketch shaders; `k <= 0` is a
+/// plain [`union`].
+pub fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if k <= 0.0 {
+        return union(a, b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+fn closest_on_segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    let ab = sub(b, a);
+    let length_squared = dot(ab, ab);
+    if length_squared == 0.0 {
+        return a;
+    }
+    let t = (dot(sub(p, a), ab) / length_squared).clamp(0.0, 1.0);
+    [a[0] + ab[0] * t, a[1] + ab[1] * t]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn length(p: [f32; 2]) -> f32 {
+    p[0].hypot(p[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::SQRT_2;
+
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    const SQUARE: [[f32; 2]; 4] =
+        [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+
+    #[test]
+    fn circle_distances() {
+        assert_close(circle([2.0, 0.0], 1.0), 1.0, "outside");
+        assert_close(circle([0.0, 0.5], 1.0), -0.5, "inside");
+        assert_close(circle([0.0, 0.0], 1.0), -1.0, "center");
+        assert_close(circle([SQRT_2 / 2.0, SQRT_2 / 2.0], 1.0), 0.0, "edge");
+    }
+
+    #[test]
+    fn rect_distances() {
+        let half_size = [2.0, 1.0];
+        assert_close(rect([3.0, 0.0], half_size), 1.0, "outside");
+        assert_close(rect([0.0, 0.0], half_size), -1.0, "center");
+        assert_close(rect([1.5, 0.0], half_size), -0.5, "inside");
+        // Past a corner the nearest point is the corner itself
+        assert_close(rect([5.0, 5.0], half_size), 5.0, "past a corner");
+    }
+
+    #[test]
+    fn segment_distances() {
+        let (a, b) = ([0.0, 0.0], [2.0, 0.0]);
+        assert_close(segment([1.0, 1.0], a, b), 1.0, "above");
+        assert_close(segment([1.0, -0.5], a, b), 0.5, "below");
+        assert_close(segment([5.0, 4.0], a, b), 5.0, "past an end");
+        assert_close(segment([3.0, 4.0], a, a), 5.0, "degenerate segment");
+    }
+
+    #[test]
+    fn polygon_distances_are_signed() {
+        assert_close(polygon([0.0, 0.0], &SQUARE), -1.0, "center");
+        assert_close(polygon([0.5, 0.0], &SQUARE), -0.5, "inside");
+        assert_close(polygon([3.0, 0.0], &SQUARE), 2.0, "outside");
+        assert_close(polygon([4.0, 5.0], &SQUARE), 5.0, "past a corner");
+
+        let mut reversed = SQUARE;
+        reversed.reverse();
+        assert_close(polygon([0.5, 0.0], &reversed), -0.5, "reversed winding");
+        assert_eq!(polygon([0.0, 0.0], &[]), f32::INFINITY);
+    }
+
+    #[test]
+    fn polygon_matches_rect() {
+        for p in [[0.3, -0.7], [1.5, 0.2], [-2.0, 3.0], [0.9, 0.9]] {
+            assert_close(polygon(p, &SQUARE), rect(p, [1.0, 1.0]), "square");
+        }
+    }
+
+    #[test]
+    fn boolean_ops() {
+        let (a, b) = (0.5, -0.25);
+        assert_eq!(union(a, b), -0.25);
+        assert_eq!(intersect(a, b), 0.5);
+        assert_eq!(subtract(a, b), 0.5);
+        assert_eq!(subtract(b, a), -0.25);
+    }
+
+    #[test]
+    fn smooth_min_matches_the_standard_formula() {
+        let reference = |a: f32, b: f32, k: f32| {
+            let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+            // mix(b, a, h) - k * h * (1 - h)
+            b * (1.0 - h) + a * h - k * h * (1.0 - h)
+        };
+        for (a, b, k) in [(0.2, 0.3, 0.5), (1.0, -1.0, 0.25), (0.0, 0.0, 1.0)] {
+            assert_close(smooth_min(a, b, k), reference(a, b, k), "reference");
+        }
+
+        // Equal inputs dip by k / 4; distant ones are a plain min
+        assert_close(smooth_min(1.0, 1.0, 0.4), 0.9, "equal inputs");
+        assert_close(smooth_min(0.0, 5.0, 0.5), 0.0, "distant inputs");
+        assert_eq!(smooth_min(0.3, 0.2, 0.0), 0.2);
+    }
+}
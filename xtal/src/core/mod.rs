@@ -1,3 +1,8 @@
+pub mod color;
+pub mod distance;
 pub mod logging;
+pub mod noise;
+pub mod palette;
 pub mod prelude;
+pub mod testing;
 pub mod util;
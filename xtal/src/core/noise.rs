@@ -0,0 +1,712 @@
+//! Seedable Perlin, simplex and value noise. The permutation table is
+//! shuffled from the seed with a fixed PRNG, so a seed produces the same
+//! field on every run and platform, and [`Noise::wgsl`] bakes the same table
+//! into shader code so the GPU can sample it too.
+//!
+//! ```rust
+//! # use xtal::core::noise::{Noise, NoiseKind};
+//! let a = Noise::new(7).kind(NoiseKind::Simplex);
+//! let b = Noise::new(7).kind(NoiseKind::Simplex);
+//! assert_eq!(a.noise_2d([0.25, 1.5]), b.noise_2d([0.25, 1.5]));
+//! ```
+
+use std::fmt;
+
+const TABLE_SIZE: usize = 256;
+
+// Gustavson's scale factors mapping each variant to roughly [-1, 1]
+const PERLIN_1D_SCALE: f32 = 0.188;
+const PERLIN_2D_SCALE: f32 = 0.507;
+const PERLIN_3D_SCALE: f32 = 0.936;
+const SIMPLEX_1D_SCALE: f32 = 0.395;
+const SIMPLEX_2D_SCALE: f32 = 40.0;
+const SIMPLEX_3D_SCALE: f32 = 32.0;
+
+// Skew and unskew factors between simplex and square grids
+const F2: f32 = 0.366_025_42;
+const G2: f32 = 0.211_324_87;
+const F3: f32 = 1.0 / 3.0;
+const G3: f32 = 1.0 / 6.0;
+
+// Shift each octave and warp axis onto unrelated parts of the field, so
+// octaves don't all vanish together at the origin and warp offsets don't
+// echo the base noise
+const OCTAVE_SHIFT: f32 = 19.19;
+const WARP_SHIFTS: [f32; 3] = [5.2, 1.3, 8.7];
+
+// Step for the central differences in `curl_noise_2d`
+const CURL_EPSILON: f32 = 1e-3;
+
+const NOISE_WGSL: &str = include_str!("noise.wgsl");
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NoiseKind {
+    /// Gradient noise on a square grid
+    #[default]
+    Perlin,
+    /// Gradient noise on a simplex grid: fewer axis-aligned artifacts
+    Simplex,
+    /// Interpolated random values: blockier, cheapest
+    Value,
+}
+
+/// Smooth noise in roughly `[-1, 1]`, reproducible from its seed
+#[derive(Clone)]
+pub struct Noise {
+    seed: u64,
+    kind: NoiseKind,
+    table: [u8; TABLE_SIZE],
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            kind: NoiseKind::default(),
+            table: permutation_table(seed),
+        }
+    }
+
+    pub fn kind(mut self, kind: NoiseKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn noise_1d(&self, x: f32) -> f32 {
+        match self.kind {
+            NoiseKind::Perlin => self.perlin_1d(x),
+            NoiseKind::Simplex => self.simplex_1d(x),
+            NoiseKind::Value => self.value_1d(x),
+        }
+    }
+
+    pub fn noise_2d(&self, point: [f32; 2]) -> f32 {
+        match self.kind {
+            NoiseKind::Perlin => self.perlin_2d(point),
+            NoiseKind::Simplex => self.simplex_2d(point),
+            NoiseKind::Value => self.value_2d(point),
+        }
+    }
+
+    pub fn noise_3d(&self, point: [f32; 3]) -> f32 {
+        match self.kind {
+            NoiseKind::Perlin => self.perlin_3d(point),
+            NoiseKind::Simplex => self.simplex_3d(point),
+            NoiseKind::Value => self.value_3d(point),
+        }
+    }
+
+    /// [`Self::noise_1d`], [`Self::noise_2d`] or [`Self::noise_3d`] for
+    /// whichever point type is given
+    pub fn sample<P: NoisePoint>(&self, point: P) -> f32 {
+        point.sample(self)
+    }
+
+    /// Fractal Brownian motion: `octaves` layers of noise, each `lacunarity`
+    /// times the frequency and `gain` times the amplitude of the one before
+    /// (2 and 0.5 are the usual choices). The sum is divided by the total
+    /// amplitude, so it stays within `[-1, 1]` whatever the parameters.
+    pub fn fbm<P: NoisePoint>(
+        &self,
+        point: P,
+        octaves: u32,
+        lacunarity: f32,
+        gain: f32,
+    ) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude_sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+
+        for octave in 0..octaves.max(1) {
+            let shifted =
+                point.scale(frequency).shift(octave as f32 * OCTAVE_SHIFT);
+            sum += self.sample(shifted) * amplitude;
+            amplitude_sum += amplitude;
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+
+        sum / amplitude_sum
+    }
+
+    /// `point` displaced by up to `amount` along each axis, by noise sampled
+    /// at shifted copies of it
+    pub fn warp<P: NoisePoint>(&self, point: P, amount: f32) -> P {
+        point.warp(self, amount)
+    }
+
+    /// Noise sampled at [`Self::warp`]ed coordinates, for swirling, organic
+    /// distortion. An `amount` of 0 is the plain noise. For more detail,
+    /// [`Self::fbm`] the warped point instead.
+    pub fn domain_warp<P: NoisePoint>(&self, point: P, amount: f32) -> f32 {
+        self.sample(self.warp(point, amount))
+    }
+
+    /// Divergence-free flow at `point`: the curl of the 2D noise treated as
+    /// a stream function, `(dn/dy, -dn/dx)`. Particles advected through it
+    /// swirl around the noise's peaks and valleys without bunching up or
+    /// thinning out. Speeds peak around 2 for Perlin and 6 for simplex
+    /// noise; normalize or scale to taste. The WGSL counterpart is
+    /// `noise_curl_2d`.
+    pub fn curl_noise_2d(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let e = CURL_EPSILON;
+        let dx = self.noise_2d([x + e, y]) - self.noise_2d([x - e, y]);
+        let dy = self.noise_2d([x, y + e]) - self.noise_2d([x, y - e]);
+        [dy / (2.0 * e), -dx / (2.0 * e)]
+    }
+
+    /// WGSL defining `noise_1d(f32)`, `noise_2d(vec2f)` and
+    /// `noise_3d(vec3f)` for this seed and kind, plus `noise_fbm_*`,
+    /// `noise_warp_*`, `noise_domain_warp_*` and `noise_curl_2d`
+    /// counterparts of the methods here, to paste into or prepend to a
+    /// shader. It declares `NOISE_TABLE` and `noise_`-prefixed helpers, so
+    /// avoid those names.
+    pub fn wgsl(&self) -> String {
+        let table = self
+            .table
+            .iter()
+            .map(|entry| entry.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let prefix = match self.kind {
+            NoiseKind::Perlin => "noise_perlin",
+            NoiseKind::Simplex => "noise_simplex",
+            NoiseKind::Value => "noise_value",
+        };
+
+        format!(
+            "// Noise for seed {seed}, generated by xtal\n\
+             var<private> NOISE_TABLE: array<i32, {TABLE_SIZE}> = \
+             array<i32, {TABLE_SIZE}>({table});\n\n\
+             {NOISE_WGSL}\n\
+             fn noise_1d(x: f32) -> f32 {{ return {prefix}_1d(x); }}\n\
+             fn noise_2d(p: vec2f) -> f32 {{ return {prefix}_2d(p); }}\n\
+             fn noise_3d(p: vec3f) -> f32 {{ return {prefix}_3d(p); }}\n",
+            seed = self.seed,
+        )
+    }
+
+    fn hash(&self, i: i32) -> i32 {
+        self.table[(i & 255) as usize] as i32
+    }
+
+    fn perlin_1d(&self, x: f32) -> f32 {
+        let ix0 = x.floor() as i32;
+        let fx0 = x - ix0 as f32;
+        let fx1 = fx0 - 1.0;
+        let s = fade(fx0);
+        let n0 = grad_1d(self.hash(ix0), fx0);
+        let n1 = grad_1d(self.hash(ix0 + 1), fx1);
+        (PERLIN_1D_SCALE * lerp(n0, n1, s)).clamp(-1.0, 1.0)
+    }
+
+    fn perlin_2d(&self, [x, y]: [f32; 2]) -> f32 {
+        let (ix0, iy0) = (x.floor() as i32, y.floor() as i32);
+        let (fx0, fy0) = (x - ix0 as f32, y - iy0 as f32);
+        let (fx1, fy1) = (fx0 - 1.0, fy0 - 1.0);
+        let (s, t) = (fade(fx0), fade(fy0));
+        let hash = |ix: i32, iy: i32| self.hash(ix + self.hash(iy));
+
+        let nx0 = lerp(
+            grad_2d(hash(ix0, iy0), fx0, fy0),
+            grad_2d(hash(ix0, iy0 + 1), fx0, fy1),
+            t,
+        );
+        let nx1 = lerp(
+            grad_2d(hash(ix0 + 1, iy0), fx1, fy0),
+            grad_2d(hash(ix0 + 1, iy0 + 1), fx1, fy1),
+            t,
+        );
+        (PERLIN_2D_SCALE * lerp(nx0, nx1, s)).clamp(-1.0, 1.0)
+    }
+
+    fn perlin_3d(&self, [x, y, z]: [f32; 3]) -> f32 {
+        let (ix0, iy0, iz0) =
+            (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let (fx0, fy0, fz0) = (x - ix0 as f32, y - iy0 as f32, z - iz0 as f32);
+        let (fx1, fy1, fz1) = (fx0 - 1.0, fy0 - 1.0, fz0 - 1.0);
+        let (s, t, r) = (fade(fx0), fade(fy0), fade(fz0));
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            let h =
+                self.hash(ix0 + dx + self.hash(iy0 + dy + self.hash(iz0 + dz)));
+            let fx = if dx == 0 { fx0 } else { fx1 };
+            let fy = if dy == 0 { fy0 } else { fy1 };
+            let fz = if dz == 0 { fz0 } else { fz1 };
+            grad_3d(h, fx, fy, fz)
+        };
+
+        let nx0 = lerp(
+            lerp(corner(0, 0, 0), corner(0, 0, 1), r),
+            lerp(corner(0, 1, 0), corner(0, 1, 1), r),
+            t,
+        );
+        let nx1 = lerp(
+            lerp(corner(1, 0, 0), corner(1, 0, 1), r),
+            lerp(corner(1, 1, 0), corner(1, 1, 1), r),
+            t,
+        );
+        (PERLIN_3D_SCALE * lerp(nx0, nx1, s)).clamp(-1.0, 1.0)
+    }
+
+    fn simplex_1d(&self, x: f32) -> f32 {
+        let i0 = x.floor() as i32;
+        let x0 = x - i0 as f32;
+        let x1 = x0 - 1.0;
+        let kernel = |d: f32, h: i32| {
+            let t = 1.0 - d * d;
+            let t = t * t;
+            t * t * grad_1d(h, d)
+        };
+        let n = kernel(x0, self.hash(i0)) + kernel(x1, self.hash(i0 + 1));
+        (SIMPLEX_1D_SCALE * n).clamp(-1.0, 1.0)
+    }
+
+    fn simplex_2d(&self, [x, y]: [f32; 2]) -> f32 {
+        let s = (x + y) * F2;
+        let i = (x + s).floor() as i32;
+        let j = (y + s).floor() as i32;
+        let t = (i + j) as f32 * G2;
+        let x0 = x - (i as f32 - t);
+        let y0 = y - (j as f32 - t);
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let x1 = x0 - i1 as f32 + G2;
+        let y1 = y0 - j1 as f32 + G2;
+        let x2 = x0 - 1.0 + 2.0 * G2;
+        let y2 = y0 - 1.0 + 2.0 * G2;
+        let hash = |di: i32, dj: i32| self.hash(i + di + self.hash(j + dj));
+        let kernel = |dx: f32, dy: f32, h: i32| {
+            let t = 0.5 - dx * dx - dy * dy;
+            if t < 0.0 {
+                0.0
+            } else {
+                let t = t * t;
+                t * t * grad_2d(h, dx, dy)
+            }
+        };
+
+        let n = kernel(x0, y0, hash(0, 0))
+            + kernel(x1, y1, hash(i1, j1))
+            + kernel(x2, y2, hash(1, 1));
+        (SIMPLEX_2D_SCALE * n).clamp(-1.0, 1.0)
+    }
+
+    fn simplex_3d(&self, [x, y, z]: [f32; 3]) -> f32 {
+        let s = (x + y + z) * F3;
+        let i = (x + s).floor() as i32;
+        let j = (y + s).floor() as i32;
+        let k = (z + s).floor() as i32;
+        let t = (i + j + k) as f32 * G3;
+        let x0 = x - (i as f32 - t);
+        let y0 = y - (j as f32 - t);
+        let z0 = z - (k as f32 - t);
+
+        // Which of the six tetrahedra in the skewed cube holds the point
+        let (first, second) = if x0 >= y0 {
+            if y0 >= z0 {
+                ([1, 0, 0], [1, 1, 0])
+            } else if x0 >= z0 {
+                ([1, 0, 0], [1, 0, 1])
+            } else {
+                ([0, 0, 1], [1, 0, 1])
+            }
+        } else if y0 < z0 {
+            ([0, 0, 1], [0, 1, 1])
+        } else if x0 < z0 {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        let corner = |offset: [i32; 3], unskew: f32| {
+            let dx = x0 - offset[0] as f32 + unskew;
+            let dy = y0 - offset[1] as f32 + unskew;
+            let dz = z0 - offset[2] as f32 + unskew;
+            let t = 0.6 - dx * dx - dy * dy - dz * dz;
+            if t < 0.0 {
+                return 0.0;
+            }
+            let h = self.hash(
+                i + offset[0]
+                    + self.hash(j + offset[1] + self.hash(k + offset[2])),
+            );
+            let t = t * t;
+            t * t * grad_3d(h, dx, dy, dz)
+        };
+
+        let n = corner([0, 0, 0], 0.0)
+            + corner(first, G3)
+            + corner(second, 2.0 * G3)
+            + corner([1, 1, 1], 3.0 * G3);
+        (SIMPLEX_3D_SCALE * n).clamp(-1.0, 1.0)
+    }
+
+    fn value_1d(&self, x: f32) -> f32 {
+        let ix0 = x.floor() as i32;
+        let s = fade(x - ix0 as f32);
+        lerp(
+            lattice_value(self.hash(ix0)),
+            lattice_value(self.hash(ix0 + 1)),
+            s,
+        )
+    }
+
+    fn value_2d(&self, [x, y]: [f32; 2]) -> f32 {
+        let (ix0, iy0) = (x.floor() as i32, y.floor() as i32);
+        let (s, t) = (fade(x - ix0 as f32), fade(y - iy0 as f32));
+        let value =
+            |ix: i32, iy: i32| lattice_value(self.hash(ix + self.hash(iy)));
+        lerp(
+            lerp(value(ix0, iy0), value(ix0, iy0 + 1), t),
+            lerp(value(ix0 + 1, iy0), value(ix0 + 1, iy0 + 1), t),
+            s,
+        )
+    }
+
+    fn value_3d(&self, [x, y, z]: [f32; 3]) -> f32 {
+        let (ix0, iy0, iz0) =
+            (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let (s, t, r) = (
+            fade(x - ix0 as f32),
+            fade(y - iy0 as f32),
+            fade(z - iz0 as f32),
+        );
+        let value = |ix: i32, iy: i32, iz: i32| {
+            lattice_value(self.hash(ix + self.hash(iy + self.hash(iz))))
+        };
+        let face = |ix: i32| {
+            lerp(
+                lerp(value(ix, iy0, iz0), value(ix, iy0, iz0 + 1), r),
+                lerp(value(ix, iy0 + 1, iz0), value(ix, iy0 + 1, iz0 + 1), r),
+                t,
+            )
+        };
+        lerp(face(ix0), face(ix0 + 1), s)
+    }
+}
+
+/// Points noise can be sampled at: `f32`, `[f32; 2]` and `[f32; 3]`
+pub trait NoisePoint: Copy {
+    fn sample(self, noise: &Noise) -> f32;
+
+    /// Multiply every coordinate by `factor`
+    fn scale(self, factor: f32) -> Self;
+
+    /// Add `offset` to every coordinate
+    fn shift(self, offset: f32) -> Self;
+
+    fn warp(self, noise: &Noise, amount: f32) -> Self;
+}
+
+impl NoisePoint for f32 {
+    fn sample(self, noise: &Noise) -> f32 {
+        noise.noise_1d(self)
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        self * factor
+    }
+
+    fn shift(self, offset: f32) -> Self {
+        self + offset
+    }
+
+    fn warp(self, noise: &Noise, amount: f32) -> Self {
+        self + amount * noise.noise_1d(self + WARP_SHIFTS[0])
+    }
+}
+
+macro_rules! impl_noise_point {
+    ($len:literal, $sample:ident) => {
+        impl NoisePoint for [f32; $len] {
+            fn sample(self, noise: &Noise) -> f32 {
+                noise.$sample(self)
+            }
+
+            fn scale(self, factor: f32) -> Self {
+                self.map(|coordinate| coordinate * factor)
+            }
+
+            fn shift(self, offset: f32) -> Self {
+                self.map(|coordinate| coordinate + offset)
+            }
+
+            fn warp(self, noise: &Noise, amount: f32) -> Self {
+                let mut warped = self;
+                for (axis, coordinate) in warped.iter_mut().enumerate() {
+                    let offset = noise.$sample(self.shift(WARP_SHIFTS[axis]));
+                    *coordinate += amount * offset;
+                }
+                warped
+            }
+        }
+    };
+}
+
+impl_noise_point!(2, noise_2d);
+impl_noise_point!(3, noise_3d);
+
+impl fmt::Debug for Noise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Noise")
+            .field("seed", &self.seed)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+impl PartialEq for Noise {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed && self.kind == other.kind
+    }
+}
+
+/// Fisher-Yates shuffle of 0..=255 driven by SplitMix64. The PRNG is spelled
+/// out rather than taken from `rand`, whose generators may change between
+/// versions and would silently change every seeded texture.
+fn permutation_table(seed: u64) -> [u8; TABLE_SIZE] {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+
+    let mut table: [u8; TABLE_SIZE] = std::array::from_fn(|i| i as u8);
+    for i in (1..TABLE_SIZE).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        table.swap(i, j);
+    }
+    table
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lattice_value(hash: i32) -> f32 {
+    hash as f32 / 127.5 - 1.0
+}
+
+fn grad_1d(hash: i32, x: f32) -> f32 {
+    let h = hash & 15;
+    let gradient = (1 + (h & 7)) as f32;
+    if h & 8 != 0 {
+        -gradient * x
+    } else {
+        gradient * x
+    }
+}
+
+fn grad_2d(hash: i32, x: f32, y: f32) -> f32 {
+    let h = hash & 7;
+    let (u, v) = if h < 4 { (x, y) } else { (y, x) };
+    let u = if h & 1 != 0 { -u } else { u };
+    let v = if h & 2 != 0 { -2.0 * v } else { 2.0 * v };
+    u + v
+}
+
+fn grad_3d(hash: i32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    let u = if h & 1 != 0 { -u } else { u };
+    let v = if h & 2 != 0 { -v } else { v };
+    u + v
+}
+
+#[cfg(test)]
+mod tests {
+    use naga::front::wgsl;
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    const KINDS: [NoiseKind; 3] =
+        [NoiseKind::Perlin, NoiseKind::Simplex, NoiseKind::Value];
+
+    fn samples(noise: &Noise) -> Vec<f32> {
+        (0..64)
+            .flat_map(|i| {
+                let x = i as f32 * 0.37 - 11.0;
+                [
+                    noise.noise_1d(x),
+                    noise.noise_2d([x, x * 0.5 + 3.1]),
+                    noise.noise_3d([x, -x * 0.25, x * 0.75 + 0.4]),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_yields_identical_samples() {
+        for kind in KINDS {
+            let a = samples(&Noise::new(42).kind(kind));
+            let b = samples(&Noise::new(42).kind(kind));
+            assert_eq!(a, b, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        for kind in KINDS {
+            let a = samples(&Noise::new(1).kind(kind));
+            let b = samples(&Noise::new(2).kind(kind));
+            let differing = a.iter().zip(&b).filter(|(a, b)| a != b).count();
+            assert!(differing > a.len() / 2, "{:?}: {}", kind, differing);
+        }
+    }
+
+    #[test]
+    fn permutation_table_is_a_stable_permutation() {
+        let table = permutation_table(0);
+        let mut sorted = table;
+        sorted.sort_unstable();
+        assert!(sorted.iter().enumerate().all(|(i, &v)| i == v as usize));
+
+        // Pinned so a change to the shuffle can't slip by unnoticed
+        assert_eq!(&table[..8], &[99, 179, 124, 78, 196, 203, 221, 113]);
+    }
+
+    #[test]
+    fn samples_stay_in_range_and_vary_smoothly() {
+        for kind in KINDS {
+            let noise = Noise::new(9).kind(kind);
+            let mut previous = noise.noise_2d([0.0, 0.5]);
+            for step in 1..2_000 {
+                let x = step as f32 * 0.005;
+                let value = noise.noise_2d([x, 0.5]);
+                assert!((-1.0..=1.0).contains(&value), "{:?}", kind);
+                assert!((value - previous).abs() < 0.1, "{:?} at {}", kind, x);
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_noise_is_zero_on_the_lattice() {
+        let noise = Noise::new(3);
+        assert_eq!(noise.noise_1d(4.0), 0.0);
+        assert_eq!(noise.noise_2d([-2.0, 7.0]), 0.0);
+        assert_eq!(noise.noise_3d([1.0, -5.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn fbm_weights_octaves_by_gain() {
+        let noise = Noise::new(11).kind(NoiseKind::Simplex);
+        let p = [0.3, -1.7];
+
+        assert_eq!(noise.fbm(p, 1, 2.0, 0.5), noise.noise_2d(p));
+        assert_eq!(noise.fbm(p, 6, 2.0, 0.0), noise.noise_2d(p));
+
+        let second = noise.noise_2d([p[0] * 2.0 + 19.19, p[1] * 2.0 + 19.19]);
+        let expected = (noise.noise_2d(p) + 0.5 * second) / 1.5;
+        assert_close(noise.fbm(p, 2, 2.0, 0.5), expected, "two octaves");
+    }
+
+    #[test]
+    fn fbm_stays_bounded() {
+        for kind in KINDS {
+            let noise = Noise::new(4).kind(kind);
+            for i in 0..500 {
+                let x = i as f32 * 0.113;
+                for value in [
+                    noise.fbm(x, 8, 2.0, 0.5),
+                    noise.fbm([x, x * 0.7], 8, 2.0, 0.5),
+                    noise.fbm([x, 1.0, -x], 8, 1.9, 0.9),
+                ] {
+                    assert!((-1.0..=1.0).contains(&value), "{:?}", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn domain_warp_with_zero_amount_is_the_base_noise() {
+        let noise = Noise::new(8);
+        assert_eq!(noise.domain_warp(2.3, 0.0), noise.noise_1d(2.3));
+        assert_eq!(
+            noise.domain_warp([2.3, -0.4], 0.0),
+            noise.noise_2d([2.3, -0.4])
+        );
+        assert_eq!(
+            noise.domain_warp([2.3, -0.4, 9.1], 0.0),
+            noise.noise_3d([2.3, -0.4, 9.1])
+        );
+
+        let p = [0.37, 0.91];
+        assert_ne!(noise.warp(p, 1.0), p);
+        assert_ne!(noise.domain_warp(p, 1.0), noise.noise_2d(p));
+    }
+
+    #[test]
+    fn curl_is_divergence_free() {
+        // A different step than the curl's own, so the check isn't just the
+        // same samples cancelling out
+        let h = 1e-2;
+        for kind in KINDS {
+            let noise = Noise::new(21).kind(kind);
+            let mut divergence = 0.0;
+            let mut stretch = 0.0;
+            for i in 0..200 {
+                let [x, y] = [i as f32 * 0.173 - 9.0, i as f32 * 0.091 + 0.3];
+                let right = noise.curl_noise_2d([x + h, y]);
+                let left = noise.curl_noise_2d([x - h, y]);
+                let up = noise.curl_noise_2d([x, y + h]);
+                let down = noise.curl_noise_2d([x, y - h]);
+                let dvx_dx = (right[0] - left[0]) / (2.0 * h);
+                let dvy_dy = (up[1] - down[1]) / (2.0 * h);
+                divergence += (dvx_dx + dvy_dy).abs();
+                stretch += dvx_dx.abs() + dvy_dy.abs();
+            }
+            // Each axis stretches and squeezes the flow, but the two cancel
+            assert!(stretch > 100.0, "{:?}: {}", kind, stretch);
+            assert!(divergence < stretch * 0.01, "{:?}: {}", kind, divergence);
+        }
+    }
+
+    #[test]
+    fn generated_wgsl_validates() {
+        const FRAGMENT: &str = "
+            @fragment
+            fn fs_main(@builtin(position) p: vec4f) -> @location(0) vec4f {
+                let n = noise_1d(p.x) + noise_2d(p.xy) + noise_3d(p.xyz);
+                let f = noise_fbm_1d(p.x, 3u, 2.0, 0.5)
+                    + noise_fbm_2d(p.xy, 4u, 2.0, 0.5)
+                    + noise_fbm_3d(p.xyz, 5u, 2.0, 0.5);
+                let w = noise_domain_warp_1d(p.x, 0.5)
+                    + noise_domain_warp_2d(p.xy, 0.5)
+                    + noise_domain_warp_3d(p.xyz, 0.5);
+                return vec4f(n, f, w, length(noise_curl_2d(p.xy)));
+            }
+        ";
+
+        for kind in KINDS {
+            let source =
+                format!("{}{}", Noise::new(5).kind(kind).wgsl(), FRAGMENT);
+            let module = wgsl::parse_str(&source).unwrap_or_else(|err| {
+                panic!("{:?}: {}", kind, err.emit_to_string(&source))
+            });
+            Validator::new(ValidationFlags::all(), Capabilities::all())
+                .validate(&module)
+                .unwrap_or_else(|err| panic!("{:?}: {:?}", kind, err));
+        }
+    }
+}
@@ -0,0 +1,347 @@
+// Shader side of xtal::core::noise, mirroring the Rust implementation line
+// for line. `Noise::wgsl` prepends the seeded NOISE_TABLE.
+
+fn noise_hash(i: i32) -> i32 {
+    return NOISE_TABLE[i & 255];
+}
+
+fn noise_fade(t: f32) -> f32 {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+fn noise_lattice_value(hash: i32) -> f32 {
+    return f32(hash) / 127.5 - 1.0;
+}
+
+fn noise_grad_1d(hash: i32, x: f32) -> f32 {
+    let h = hash & 15;
+    let gradient = f32(1 + (h & 7));
+    return select(gradient * x, -gradient * x, (h & 8) != 0);
+}
+
+fn noise_grad_2d(hash: i32, x: f32, y: f32) -> f32 {
+    let h = hash & 7;
+    let u = select(y, x, h < 4);
+    let v = select(x, y, h < 4);
+    return select(u, -u, (h & 1) != 0)
+        + select(2.0 * v, -2.0 * v, (h & 2) != 0);
+}
+
+fn noise_grad_3d(hash: i32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = select(y, x, h < 8);
+    let v = select(select(z, x, h == 12 || h == 14), y, h < 4);
+    return select(u, -u, (h & 1) != 0) + select(v, -v, (h & 2) != 0);
+}
+
+fn noise_perlin_1d(x: f32) -> f32 {
+    let ix0 = i32(floor(x));
+    let fx0 = x - f32(ix0);
+    let fx1 = fx0 - 1.0;
+    let n0 = noise_grad_1d(noise_hash(ix0), fx0);
+    let n1 = noise_grad_1d(noise_hash(ix0 + 1), fx1);
+    return clamp(0.188 * mix(n0, n1, noise_fade(fx0)), -1.0, 1.0);
+}
+
+fn noise_perlin_2d(p: vec2f) -> f32 {
+    let i0 = vec2i(floor(p));
+    let f0 = p - vec2f(i0);
+    let f1 = f0 - 1.0;
+    let s = noise_fade(f0.x);
+    let t = noise_fade(f0.y);
+    let h00 = noise_hash(i0.x + noise_hash(i0.y));
+    let h01 = noise_hash(i0.x + noise_hash(i0.y + 1));
+    let h10 = noise_hash(i0.x + 1 + noise_hash(i0.y));
+    let h11 = noise_hash(i0.x + 1 + noise_hash(i0.y + 1));
+    let nx0 = mix(
+        noise_grad_2d(h00, f0.x, f0.y),
+        noise_grad_2d(h01, f0.x, f1.y),
+        t,
+    );
+    let nx1 = mix(
+        noise_grad_2d(h10, f1.x, f0.y),
+        noise_grad_2d(h11, f1.x, f1.y),
+        t,
+    );
+    return clamp(0.507 * mix(nx0, nx1, s), -1.0, 1.0);
+}
+
+fn noise_perlin_3d_corner(i0: vec3i, f0: vec3f, d: vec3i) -> f32 {
+    let h = noise_hash(
+        i0.x + d.x + noise_hash(i0.y + d.y + noise_hash(i0.z + d.z)),
+    );
+    let f = f0 - vec3f(d);
+    return noise_grad_3d(h, f.x, f.y, f.z);
+}
+
+fn noise_perlin_3d(p: vec3f) -> f32 {
+    let i0 = vec3i(floor(p));
+    let f0 = p - vec3f(i0);
+    let s = noise_fade(f0.x);
+    let t = noise_fade(f0.y);
+    let r = noise_fade(f0.z);
+    let nx0 = mix(
+        mix(
+            noise_perlin_3d_corner(i0, f0, vec3i(0, 0, 0)),
+            noise_perlin_3d_corner(i0, f0, vec3i(0, 0, 1)),
+            r,
+        ),
+        mix(
+            noise_perlin_3d_corner(i0, f0, vec3i(0, 1, 0)),
+            noise_perlin_3d_corner(i0, f0, vec3i(0, 1, 1)),
+            r,
+        ),
+        t,
+    );
+    let nx1 = mix(
+        mix(
+            noise_perlin_3d_corner(i0, f0, vec3i(1, 0, 0)),
+            noise_perlin_3d_corner(i0, f0, vec3i(1, 0, 1)),
+            r,
+        ),
+        mix(
+            noise_perlin_3d_corner(i0, f0, vec3i(1, 1, 0)),
+            noise_perlin_3d_corner(i0, f0, vec3i(1, 1, 1)),
+            r,
+        ),
+        t,
+    );
+    return clamp(0.936 * mix(nx0, nx1, s), -1.0, 1.0);
+}
+
+fn noise_simplex_1d_kernel(d: f32, h: i32) -> f32 {
+    var t = 1.0 - d * d;
+    t = t * t;
+    return t * t * noise_grad_1d(h, d);
+}
+
+fn noise_simplex_1d(x: f32) -> f32 {
+    let i0 = i32(floor(x));
+    let x0 = x - f32(i0);
+    let n = noise_simplex_1d_kernel(x0, noise_hash(i0))
+        + noise_simplex_1d_kernel(x0 - 1.0, noise_hash(i0 + 1));
+    return clamp(0.395 * n, -1.0, 1.0);
+}
+
+fn noise_simplex_2d_kernel(d: vec2f, h: i32) -> f32 {
+    var t = 0.5 - dot(d, d);
+    if (t < 0.0) {
+        return 0.0;
+    }
+    t = t * t;
+    return t * t * noise_grad_2d(h, d.x, d.y);
+}
+
+fn noise_simplex_2d(p: vec2f) -> f32 {
+    let f2 = 0.36602542;
+    let g2 = 0.21132487;
+    let s = (p.x + p.y) * f2;
+    let i = vec2i(floor(p + s));
+    let t = f32(i.x + i.y) * g2;
+    let d0 = p - (vec2f(i) - t);
+    let o1 = select(vec2i(0, 1), vec2i(1, 0), d0.x > d0.y);
+    let d1 = d0 - vec2f(o1) + g2;
+    let d2 = d0 - 1.0 + 2.0 * g2;
+    let n = noise_simplex_2d_kernel(d0, noise_hash(i.x + noise_hash(i.y)))
+        + noise_simplex_2d_kernel(
+            d1,
+            noise_hash(i.x + o1.x + noise_hash(i.y + o1.y)),
+        )
+        + noise_simplex_2d_kernel(
+            d2,
+            noise_hash(i.x + 1 + noise_hash(i.y + 1)),
+        );
+    return clamp(40.0 * n, -1.0, 1.0);
+}
+
+fn noise_simplex_3d_corner(
+    i: vec3i,
+    d0: vec3f,
+    offset: vec3i,
+    unskew: f32,
+) -> f32 {
+    let d = d0 - vec3f(offset) + unskew;
+    var t = 0.6 - dot(d, d);
+    if (t < 0.0) {
+        return 0.0;
+    }
+    let h = noise_hash(
+        i.x + offset.x
+            + noise_hash(i.y + offset.y + noise_hash(i.z + offset.z)),
+    );
+    t = t * t;
+    return t * t * noise_grad_3d(h, d.x, d.y, d.z);
+}
+
+fn noise_simplex_3d(p: vec3f) -> f32 {
+    let f3 = 1.0 / 3.0;
+    let g3 = 1.0 / 6.0;
+    let s = (p.x + p.y + p.z) * f3;
+    let i = vec3i(floor(p + s));
+    let t = f32(i.x + i.y + i.z) * g3;
+    let d0 = p - (vec3f(i) - t);
+
+    var first: vec3i;
+    var second: vec3i;
+    if (d0.x >= d0.y) {
+        if (d0.y >= d0.z) {
+            first = vec3i(1, 0, 0);
+            second = vec3i(1, 1, 0);
+        } else if (d0.x >= d0.z) {
+            first = vec3i(1, 0, 0);
+            second = vec3i(1, 0, 1);
+        } else {
+            first = vec3i(0, 0, 1);
+            second = vec3i(1, 0, 1);
+        }
+    } else if (d0.y < d0.z) {
+        first = vec3i(0, 0, 1);
+        second = vec3i(0, 1, 1);
+    } else if (d0.x < d0.z) {
+        first = vec3i(0, 1, 0);
+        second = vec3i(0, 1, 1);
+    } else {
+        first = vec3i(0, 1, 0);
+        second = vec3i(1, 1, 0);
+    }
+
+    let n = noise_simplex_3d_corner(i, d0, vec3i(0, 0, 0), 0.0)
+        + noise_simplex_3d_corner(i, d0, first, g3)
+        + noise_simplex_3d_corner(i, d0, second, 2.0 * g3)
+        + noise_simplex_3d_corner(i, d0, vec3i(1, 1, 1), 3.0 * g3);
+    return clamp(32.0 * n, -1.0, 1.0);
+}
+
+fn noise_value_1d(x: f32) -> f32 {
+    let ix0 = i32(floor(x));
+    return mix(
+        noise_lattice_value(noise_hash(ix0)),
+        noise_lattice_value(noise_hash(ix0 + 1)),
+        noise_fade(x - f32(ix0)),
+    );
+}
+
+fn noise_value_2d_at(i: vec2i) -> f32 {
+    return noise_lattice_value(noise_hash(i.x + noise_hash(i.y)));
+}
+
+fn noise_value_2d(p: vec2f) -> f32 {
+    let i0 = vec2i(floor(p));
+    let s = noise_fade(p.x - f32(i0.x));
+    let t = noise_fade(p.y - f32(i0.y));
+    return mix(
+        mix(noise_value_2d_at(i0), noise_value_2d_at(i0 + vec2i(0, 1)), t),
+        mix(
+            noise_value_2d_at(i0 + vec2i(1, 0)),
+            noise_value_2d_at(i0 + vec2i(1, 1)),
+            t,
+        ),
+        s,
+    );
+}
+
+fn noise_value_3d_at(i: vec3i) -> f32 {
+    return noise_lattice_value(
+        noise_hash(i.x + noise_hash(i.y + noise_hash(i.z))),
+    );
+}
+
+fn noise_value_3d_face(i: vec3i, t: f32, r: f32) -> f32 {
+    return mix(
+        mix(noise_value_3d_at(i), noise_value_3d_at(i + vec3i(0, 0, 1)), r),
+        mix(
+            noise_value_3d_at(i + vec3i(0, 1, 0)),
+            noise_value_3d_at(i + vec3i(0, 1, 1)),
+            r,
+        ),
+        t,
+    );
+}
+
+fn noise_value_3d(p: vec3f) -> f32 {
+    let i0 = vec3i(floor(p));
+    let f = p - vec3f(i0);
+    let t = noise_fade(f.y);
+    let r = noise_fade(f.z);
+    return mix(
+        noise_value_3d_face(i0, t, r),
+        noise_value_3d_face(i0 + vec3i(1, 0, 0), t, r),
+        noise_fade(f.x),
+    );
+}
+
+// fBm and domain warping over whichever kind `noise_1d`..`noise_3d` select
+
+fn noise_fbm_1d(x: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    var sum = 0.0;
+    var amplitude_sum = 0.0;
+    var amplitude = 1.0;
+    var frequency = 1.0;
+    for (var octave = 0u; octave < max(octaves, 1u); octave++) {
+        sum += noise_1d(x * frequency + f32(octave) * 19.19) * amplitude;
+        amplitude_sum += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return sum / amplitude_sum;
+}
+
+fn noise_fbm_2d(p: vec2f, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    var sum = 0.0;
+    var amplitude_sum = 0.0;
+    var amplitude = 1.0;
+    var frequency = 1.0;
+    for (var octave = 0u; octave < max(octaves, 1u); octave++) {
+        sum += noise_2d(p * frequency + f32(octave) * 19.19) * amplitude;
+        amplitude_sum += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return sum / amplitude_sum;
+}
+
+fn noise_fbm_3d(p: vec3f, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    var sum = 0.0;
+    var amplitude_sum = 0.0;
+    var amplitude = 1.0;
+    var frequency = 1.0;
+    for (var octave = 0u; octave < max(octaves, 1u); octave++) {
+        sum += noise_3d(p * frequency + f32(octave) * 19.19) * amplitude;
+        amplitude_sum += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return sum / amplitude_sum;
+}
+
+fn noise_warp_1d(x: f32, amount: f32) -> f32 {
+    return x + amount * noise_1d(x + 5.2);
+}
+
+fn noise_warp_2d(p: vec2f, amount: f32) -> vec2f {
+    return p + amount * vec2f(noise_2d(p + 5.2), noise_2d(p + 1.3));
+}
+
+fn noise_warp_3d(p: vec3f, amount: f32) -> vec3f {
+    let offset = vec3f(noise_3d(p + 5.2), noise_3d(p + 1.3), noise_3d(p + 8.7));
+    return p + amount * offset;
+}
+
+fn noise_domain_warp_1d(x: f32, amount: f32) -> f32 {
+    return noise_1d(noise_warp_1d(x, amount));
+}
+
+fn noise_domain_warp_2d(p: vec2f, amount: f32) -> f32 {
+    return noise_2d(noise_warp_2d(p, amount));
+}
+
+fn noise_domain_warp_3d(p: vec3f, amount: f32) -> f32 {
+    return noise_3d(noise_warp_3d(p, amount));
+}
+
+fn noise_curl_2d(p: vec2f) -> vec2f {
+    let e = 1e-3;
+    let dx = noise_2d(p + vec2f(e, 0.0)) - noise_2d(p - vec2f(e, 0.0));
+    let dy = noise_2d(p + vec2f(0.0, e)) - noise_2d(p - vec2f(0.0, e));
+    return vec2f(dy, -dx) / (2.0 * e);
+}
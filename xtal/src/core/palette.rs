@@ -0,0 +1,287 @@
+//! Color palettes sampled by a position `t` from 0 to 1. Palettes are either
+//! gradients through evenly spaced color stops or Inigo Quilez style cosine
+//! gradients, `a + b * cos(2π * (c * t + d))` per channel. Samples are linear
+//! RGB, ready for a shader uniform or an [`Instance`] color:
+//!
+//! ```rust
+//! # use xtal::prelude::*;
+//! let palette = Palette::named("viridis").unwrap();
+//! let [r, g, b] = palette.sample(0.5);
+//!
+//! let custom = Palette::from_hex(&["#1b1b3a", "#693668", "#ff8552"])?;
+//! let color = custom.sample_rgba(0.25, 1.0);
+//! # Ok::<(), xtal::core::palette::HexColorError>(())
+//! ```
+//!
+//! [`Instance`]: crate::instancing::Instance
+
+use std::f32::consts::TAU;
+use std::fmt;
+
+use super::color::{oklab_mix, srgb_to_linear};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Palette {
+    /// Linear RGB colors spread evenly from `t = 0` to `t = 1`
+    Stops(Vec<[f32; 3]>),
+    /// `a + b * cos(2π * (c * t + d))`, evaluated per channel
+    Cosine {
+        a: [f32; 3],
+        b: [f32; 3],
+        c: [f32; 3],
+        d: [f32; 3],
+    },
+}
+
+const VIRIDIS: [&str; 10] = [
+    "#440154", "#482878", "#3e4989", "#31688e", "#26828e", "#1f9e89",
+    "#35b779", "#6ece58", "#b5de2b", "#fde725",
+];
+
+const MAGMA: [&str; 10] = [
+    "#000004", "#180f3d", "#440f76", "#721f81", "#9e2f7f", "#cd4071",
+    "#f1605d", "#fd9668", "#feca8d", "#fcfdbf",
+];
+
+// c and d for cosine palettes centered on gray with full swing
+const COSINE_PRESETS: [(&str, [f32; 3], [f32; 3]); 4] = [
+    ("rainbow", [1.0, 1.0, 1.0], [0.0, 0.33, 0.67]),
+    ("dusk", [1.0, 1.0, 1.0], [0.0, 0.1, 0.2]),
+    ("desert", [1.0, 0.7, 0.4], [0.0, 0.15, 0.2]),
+    ("neon", [2.0, 1.0, 0.0], [0.5, 0.2, 0.25]),
+];
+
+impl Palette {
+    /// Every name accepted by [`Palette::named`]
+    pub const NAMES: [&str; 7] = [
+        "viridis", "magma", "rainbow", "dusk", "desert", "neon", "coral",
+    ];
+
+    /// Look up a built-in palette: the perceptually uniform `viridis` and
+    /// `magma`, or one of the cosine gradients `rainbow`, `dusk`, `desert`,
+    /// `neon` and `coral`
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "viridis" => Some(Self::hex_stops(&VIRIDIS)),
+            "magma" => Some(Self::hex_stops(&MAGMA)),
+            "coral" => Some(Self::cosine(
+                [0.8, 0.5, 0.4],
+                [0.2, 0.4, 0.2],
+                [2.0, 1.0, 1.0],
+                [0.0, 0.25, 0.25],
+            )),
+            _ => COSINE_PRESETS
+                .iter()
+                .find(|(preset, ..)| *preset == name)
+                .map(|&(_, c, d)| Self::cosine([0.5; 3], [0.5; 3], c, d)),
+        }
+    }
+
+    pub fn cosine(a: [f32; 3], b: [f32; 3], c: [f32; 3], d: [f32; 3]) -> Self {
+        Self::Cosine { a, b, c, d }
+    }
+
+    /// Gradient through sRGB hex colors such as `#ff8800`, `ff8800` or
+    /// `#f80`
+    pub fn from_hex<S: AsRef<str>>(stops: &[S]) -> Result<Self, HexColorError> {
+        stops
+            .iter()
+            .map(|stop| hex_to_linear(stop.as_ref()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::Stops)
+    }
+
+    /// Linear RGB color at `t`. Stop gradients clamp `t` to `[0, 1]` and
+    /// blend neighboring stops linearly; cosine gradients repeat wherever
+    /// `c` is a whole number. An empty gradient is black.
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        match self {
+            Self::Stops(stops) => sample_stops(stops, t, lerp),
+            Self::Cosine { a, b, c, d } => std::array::from_fn(|i| {
+                a[i] + b[i] * (TAU * (c[i] * t + d[i])).cos()
+            }),
+        }
+    }
+
+    /// Like [`Palette::sample`], but stop gradients blend through OKLab so
+    /// the steps between stops look even. Cosine gradients are unchanged.
+    pub fn sample_oklab(&self, t: f32) -> [f32; 3] {
+        match self {
+            Self::Stops(stops) => sample_stops(stops, t, oklab_mix),
+            Self::Cosine { .. } => self.sample(t),
+        }
+    }
+
+    pub fn sample_rgba(&self, t: f32, alpha: f32) -> [f32; 4] {
+        let [r, g, b] = self.sample(t);
+        [r, g, b, alpha]
+    }
+
+    fn hex_stops(stops: &[&str]) -> Self {
+        Self::from_hex(stops).expect("built-in palettes are valid hex")
+    }
+}
+
+fn sample_stops(
+    stops: &[[f32; 3]],
+    t: f32,
+    mix: fn([f32; 3], [f32; 3], f32) -> [f32; 3],
+) -> [f32; 3] {
+    match stops {
+        [] => [0.0; 3],
+        [only] => *only,
+        _ => {
+            let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+            let index = (position as usize).min(stops.len() - 2);
+            mix(stops[index], stops[index + 1], position - index as f32)
+        }
+    }
+}
+
+fn lerp(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Parse an sRGB hex color (`#rrggbb`, `#rgb`, with or without the `#`)
+/// into linear RGB
+pub fn hex_to_linear(hex: &str) -> Result<[f32; 3], HexColorError> {
+    let error = || HexColorError {
+        value: hex.to_string(),
+    };
+    let digits = hex.trim().trim_start_matches('#');
+    if !digits.is_ascii() {
+        return Err(error());
+    }
+
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    let rgb = match digits.len() {
+        6 => [&digits[0..2], &digits[2..4], &digits[4..6]].map(channel),
+        3 => [&digits[0..1], &digits[1..2], &digits[2..3]]
+            .map(|digit| channel(digit).map(|value| value * 17)),
+        _ => return Err(error()),
+    };
+
+    let mut srgb = [0.0; 3];
+    for (out, value) in srgb.iter_mut().zip(rgb) {
+        *out = value.ok_or_else(error)? as f32 / 255.0;
+    }
+    Ok(srgb_to_linear(srgb))
+}
+
+/// Returned when a palette stop isn't a valid hex color
+#[derive(Clone, Debug, PartialEq)]
+pub struct HexColorError {
+    pub value: String,
+}
+
+impl fmt::Display for HexColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid hex color {:?}, expected #rrggbb or #rgb",
+            self.value
+        )
+    }
+}
+
+impl std::error::Error for HexColorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    fn assert_color(actual: [f32; 3], expected: [f32; 3]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert_close(actual, expected, "channel");
+        }
+    }
+
+    #[test]
+    fn cosine_palette_follows_the_formula() {
+        let palette = Palette::cosine(
+            [0.5, 0.4, 0.3],
+            [0.5, 0.2, 0.1],
+            [1.0, 2.0, 0.5],
+            [0.0, 0.25, 0.5],
+        );
+
+        // t = 0: cos(0), cos(π/2), cos(π)
+        assert_color(palette.sample(0.0), [1.0, 0.4, 0.2]);
+        // t = 0.5: cos(π), cos(5π/2), cos(3π/2)
+        assert_color(palette.sample(0.5), [0.0, 0.4, 0.3]);
+
+        let t = 0.3_f32;
+        assert_close(
+            palette.sample(t)[1],
+            0.4 + 0.2 * (TAU * (2.0 * t + 0.25)).cos(),
+            "green at t = 0.3",
+        );
+    }
+
+    #[test]
+    fn named_cosine_palettes_repeat() {
+        let rainbow = Palette::named("rainbow").unwrap();
+        assert_color(rainbow.sample(0.2), rainbow.sample(1.2));
+        assert_color(rainbow.sample(0.0), [1.0, 0.2591, 0.2591]);
+    }
+
+    #[test]
+    fn parses_hex_colors_to_linear() {
+        assert_color(hex_to_linear("#ffffff").unwrap(), [1.0, 1.0, 1.0]);
+        assert_color(hex_to_linear("000000").unwrap(), [0.0, 0.0, 0.0]);
+        assert_color(hex_to_linear("#f80").unwrap(), [1.0, 0.2462, 0.0]);
+        assert_color(hex_to_linear(" #FF8800 ").unwrap(), [1.0, 0.2462, 0.0]);
+        // sRGB mid gray is about a fifth of the way in linear light
+        assert_close(hex_to_linear("#808080").unwrap()[0], 0.2159, "mid gray");
+
+        for invalid in ["", "#12345", "#gg0000", "#ff00000", "#ééé"] {
+            assert_eq!(
+                hex_to_linear(invalid),
+                Err(HexColorError {
+                    value: invalid.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn stops_interpolate_and_clamp() {
+        let palette =
+            Palette::from_hex(&["#000000", "#ffffff", "#ff0000"]).unwrap();
+
+        assert_color(palette.sample(0.0), [0.0, 0.0, 0.0]);
+        assert_color(palette.sample(0.25), [0.5, 0.5, 0.5]);
+        assert_color(palette.sample(0.5), [1.0, 1.0, 1.0]);
+        assert_color(palette.sample(0.75), [1.0, 0.5, 0.5]);
+        assert_color(palette.sample(1.0), [1.0, 0.0, 0.0]);
+        assert_color(palette.sample(-1.0), [0.0, 0.0, 0.0]);
+        assert_color(palette.sample(2.0), [1.0, 0.0, 0.0]);
+        assert_eq!(palette.sample_rgba(1.0, 0.5), [1.0, 0.0, 0.0, 0.5]);
+
+        assert_eq!(Palette::Stops(vec![]).sample(0.5), [0.0; 3]);
+        assert!(Palette::from_hex(&["#000", "nope"]).is_err());
+    }
+
+    #[test]
+    fn oklab_sampling_keeps_stops_and_evens_out_lightness() {
+        let palette = Palette::from_hex(&["#000000", "#ffffff"]).unwrap();
+
+        assert_color(palette.sample_oklab(0.0), [0.0, 0.0, 0.0]);
+        assert_color(palette.sample_oklab(1.0), [1.0, 1.0, 1.0]);
+        let gray = palette.sample_oklab(0.5);
+        assert!(gray[0] < palette.sample(0.5)[0]);
+    }
+
+    #[test]
+    fn every_name_resolves() {
+        for name in Palette::NAMES {
+            assert!(Palette::named(name).is_some(), "{name}");
+        }
+        assert_eq!(Palette::named("plaid"), None);
+
+        let viridis = Palette::named("viridis").unwrap();
+        assert_color(viridis.sample(0.0), hex_to_linear("#440154").unwrap());
+        assert_color(viridis.sample(1.0), hex_to_linear("#fde725").unwrap());
+    }
+}
@@ -0,0 +1,36 @@
+//! Assertion helpers for sketch and control script tests, re-exported from
+//! [`crate::prelude::test`]
+
+const EPSILON: f32 = 1e-4;
+
+/// Assert that two floats are within `1e-4` of each other, which absorbs the
+/// rounding of animation and control math that makes `assert_eq!` brittle.
+/// `label` prefixes the failure message so a test with several checks says
+/// which one broke.
+#[track_caller]
+pub fn assert_close(actual: f32, expected: f32, label: &str) {
+    assert!(
+        (actual - expected).abs() <= EPSILON,
+        "{}: expected {}, got {}",
+        label,
+        expected,
+        actual
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_values_within_epsilon() {
+        assert_close(0.1 + 0.2, 0.3, "sum");
+        assert_close(1.0, 1.000_05, "half epsilon");
+    }
+
+    #[test]
+    #[should_panic(expected = "value: expected 1, got 1.001")]
+    fn rejects_values_outside_epsilon() {
+        assert_close(1.001, 1.0, "value");
+    }
+}
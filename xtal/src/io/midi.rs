@@ -9,6 +9,9 @@ use midir::{
 
 use crate::core::prelude::*;
 
+const ALL_SOUND_OFF: u8 = 120;
+const ALL_NOTES_OFF: u8 = 123;
+
 static THREADS: LazyLock<
     Mutex<HashMap<ConnectionType, thread::JoinHandle<()>>>,
 > = LazyLock::new(|| Mutex::new(HashMap::default()));
@@ -101,7 +104,6 @@ where
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn disconnect(connection_type: ConnectionType) {
     let mut threads = THREADS.lock().unwrap();
     if let Some(handle) = threads.remove(&connection_type) {
@@ -113,6 +115,8 @@ pub fn disconnect(connection_type: ConnectionType) {
 pub struct MidiOut {
     port: String,
     connection: Option<MidiOutputConnection>,
+    #[cfg(test)]
+    sent: Option<Vec<[u8; 3]>>,
 }
 
 impl MidiOut {
@@ -120,9 +124,25 @@ impl MidiOut {
         Self {
             port: port.to_string(),
             connection: None,
+            #[cfg(test)]
+            sent: None,
         }
     }
 
+    /// A connected port that keeps what it's sent instead of sending it
+    #[cfg(test)]
+    pub(crate) fn recording(port: &str) -> Self {
+        Self {
+            sent: Some(vec![]),
+            ..Self::new(port)
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn sent(&self) -> &[[u8; 3]] {
+        self.sent.as_deref().unwrap_or_default()
+    }
+
     pub fn connect(&mut self) -> Result<(), Box<dyn Error>> {
         let midi_out = MidiOutput::new("ControlOut")?;
         let out_ports = midi_out.ports();
@@ -138,6 +158,11 @@ impl MidiOut {
     }
 
     pub fn send(&mut self, message: &[u8; 3]) -> Result<(), Box<dyn Error>> {
+        #[cfg(test)]
+        if let Some(sent) = &mut self.sent {
+            sent.push(*message);
+            return Ok(());
+        }
         if let Some(connection) = &mut self.connection {
             connection.send(message)?;
         } else {
@@ -145,6 +170,14 @@ impl MidiOut {
         }
         Ok(())
     }
+
+    pub fn is_connected(&self) -> bool {
+        #[cfg(test)]
+        if self.sent.is_some() {
+            return true;
+        }
+        self.connection.is_some()
+    }
 }
 
 pub type PortIndexAndName = (usize, String);
@@ -188,3 +221,30 @@ pub fn print_ports() -> Result<(), Box<dyn Error>> {
 pub fn is_control_change(status: u8) -> bool {
     status & 0xF0 == 0xB0
 }
+
+/// All Sound Off followed by All Notes Off on every channel, for silencing
+/// notes a sketch left hanging on external gear
+pub fn panic_messages() -> Vec<[u8; 3]> {
+    (0..16)
+        .flat_map(|channel| {
+            let status = 0xB0 | channel;
+            [[status, ALL_SOUND_OFF, 0], [status, ALL_NOTES_OFF, 0]]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_messages_cover_every_channel() {
+        let messages = panic_messages();
+        assert_eq!(messages.len(), 32);
+        assert_eq!(messages[0], [0xB0, 120, 0]);
+        assert_eq!(messages[1], [0xB0, 123, 0]);
+        assert_eq!(messages[30], [0xBF, 120, 0]);
+        assert_eq!(messages[31], [0xBF, 123, 0]);
+        assert!(messages.iter().all(|message| is_control_change(message[0])));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod audio;
 pub mod midi;
+#[cfg(feature = "ndi")]
+pub mod ndi;
 pub mod osc;
@@ -0,0 +1,411 @@
+//! Minimal bindings to the NDI runtime for sending and receiving video. The
+//! library is loaded dynamically, so building with the `ndi` feature doesn't
+//! require the NDI SDK; a missing runtime is reported when a sender or
+//! receiver is created. Set `XTAL_NDI_LIBRARY` to load it from a custom path.
+
+use std::ffi::{CString, c_char, c_void};
+use std::time::Duration;
+
+use libloading::Library;
+
+// NDIlib_frame_format_type_progressive
+const FRAME_FORMAT_PROGRESSIVE: i32 = 1;
+// NDIlib_send_timecode_synthesize
+const TIMECODE_SYNTHESIZE: i64 = i64::MAX;
+// NDIlib_frame_type_video
+const FRAME_TYPE_VIDEO: i32 = 1;
+// NDIlib_recv_color_format_RGBX_RGBA
+const RECV_COLOR_FORMAT_RGBX_RGBA: i32 = 2;
+// NDIlib_recv_bandwidth_highest
+const RECV_BANDWIDTH_HIGHEST: i32 = 100;
+
+#[repr(C)]
+struct Source {
+    p_ndi_name: *const c_char,
+    p_url_address: *const c_char,
+}
+
+#[repr(C)]
+struct SendCreate {
+    p_ndi_name: *const c_char,
+    p_groups: *const c_char,
+    clock_video: bool,
+    clock_audio: bool,
+}
+
+#[repr(C)]
+struct RecvCreateV3 {
+    source_to_connect_to: Source,
+    color_format: i32,
+    bandwidth: i32,
+    allow_video_fields: bool,
+    p_ndi_recv_name: *const c_char,
+}
+
+#[repr(C)]
+struct VideoFrameV2 {
+    xres: i32,
+    yres: i32,
+    four_cc: u32,
+    frame_rate_n: i32,
+    frame_rate_d: i32,
+    picture_aspect_ratio: f32,
+    frame_format_type: i32,
+    timecode: i64,
+    p_data: *const u8,
+    line_stride_in_bytes: i32,
+    p_metadata: *const c_char,
+    timestamp: i64,
+}
+
+impl VideoFrameV2 {
+    fn empty() -> Self {
+        Self {
+            xres: 0,
+            yres: 0,
+            four_cc: 0,
+            frame_rate_n: 0,
+            frame_rate_d: 0,
+            picture_aspect_ratio: 0.0,
+            frame_format_type: 0,
+            timecode: 0,
+            p_data: std::ptr::null(),
+            line_stride_in_bytes: 0,
+            p_metadata: std::ptr::null(),
+            timestamp: 0,
+        }
+    }
+}
+
+type Initialize = unsafe extern "C" fn() -> bool;
+type Destroy = unsafe extern "C" fn();
+type SendCreateFn = unsafe extern "C" fn(*const SendCreate) -> *mut c_void;
+type SendDestroy = unsafe extern "C" fn(*mut c_void);
+type SendVideo = unsafe extern "C" fn(*mut c_void, *const VideoFrameV2);
+type RecvCreate = unsafe extern "C" fn(*const RecvCreateV3) -> *mut c_void;
+type RecvDestroy = unsafe extern "C" fn(*mut c_void);
+type RecvCapture = unsafe extern "C" fn(
+    *mut c_void,
+    *mut VideoFrameV2,
+    *mut c_void,
+    *mut c_void,
+    u32,
+) -> i32;
+type RecvFreeVideo = unsafe extern "C" fn(*mut c_void, *const VideoFrameV2);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FourCc {
+    Bgra,
+    Rgba,
+    /// RGBA with an undefined alpha channel, sent by opaque sources
+    Rgbx,
+}
+
+impl FourCc {
+    fn code(&self) -> u32 {
+        let code = match self {
+            Self::Bgra => b"BGRA",
+            Self::Rgba => b"RGBA",
+            Self::Rgbx => b"RGBX",
+        };
+        u32::from_le_bytes(*code)
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        [Self::Bgra, Self::Rgba, Self::Rgbx]
+            .into_iter()
+            .find(|four_cc| four_cc.code() == code)
+    }
+}
+
+fn library_candidates() -> Vec<String> {
+    if let Ok(path) = std::env::var("XTAL_NDI_LIBRARY") {
+        return vec![path];
+    }
+    if cfg!(target_os = "windows") {
+        ["NDI_RUNTIME_DIR_V6", "NDI_RUNTIME_DIR_V5"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .map(|dir| format!("{}\\Processing.NDI.Lib.x64.dll", dir))
+            .chain(["Processing.NDI.Lib.x64.dll".to_string()])
+            .collect()
+    } else if cfg!(target_os = "macos") {
+        vec![
+            "libndi.dylib".to_string(),
+            "/usr/local/lib/libndi.dylib".to_string(),
+        ]
+    } else {
+        ["libndi.so.6", "libndi.so.5", "libndi.so"]
+            .map(String::from)
+            .to_vec()
+    }
+}
+
+/// The loaded, initialized runtime. Senders and receivers each hold one so
+/// the library outlives the function pointers taken from it.
+struct Runtime {
+    library: Library,
+    destroy: Destroy,
+}
+
+impl Runtime {
+    fn load() -> Result<Self, String> {
+        let candidates = library_candidates();
+        let library = candidates
+            .iter()
+            .find_map(|path| unsafe { Library::new(path) }.ok())
+            .ok_or_else(|| {
+                format!(
+                    "NDI runtime not found (tried {}); install it or set \
+                     XTAL_NDI_LIBRARY",
+                    candidates.join(", ")
+                )
+            })?;
+
+        unsafe {
+            let initialize =
+                symbol::<Initialize>(&library, "NDIlib_initialize")?;
+            let destroy = symbol::<Destroy>(&library, "NDIlib_destroy")?;
+            if !initialize() {
+                return Err("NDI is not supported on this CPU".into());
+            }
+            Ok(Self { library, destroy })
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)() };
+    }
+}
+
+/// # Safety
+///
+/// `T` must be the function pointer type of the symbol named `name`.
+unsafe fn symbol<T: Copy>(library: &Library, name: &str) -> Result<T, String> {
+    let name = format!("{}\0", name);
+    unsafe { library.get::<T>(name.as_bytes()) }
+        .map(|found| *found)
+        .map_err(|err| err.to_string())
+}
+
+pub struct NdiSender {
+    instance: *mut c_void,
+    send_video: SendVideo,
+    send_destroy: SendDestroy,
+    _runtime: Runtime,
+}
+
+// An NDI sender may be used from any thread as long as calls aren't
+// concurrent, which `&mut self` guarantees.
+unsafe impl Send for NdiSender {}
+
+impl NdiSender {
+    /// Publish a source named `name` on the local network
+    pub fn new(name: &str) -> Result<Self, String> {
+        let name = CString::new(name)
+            .map_err(|_| "NDI sender name contains a NUL byte")?;
+        let runtime = Runtime::load()?;
+        let library = &runtime.library;
+        let (send_create, send_destroy, send_video) = unsafe {
+            (
+                symbol::<SendCreateFn>(library, "NDIlib_send_create")?,
+                symbol::<SendDestroy>(library, "NDIlib_send_destroy")?,
+                symbol::<SendVideo>(library, "NDIlib_send_send_video_v2")?,
+            )
+        };
+
+        // The runtime paces frames, so NDI's own clocking stays off
+        let settings = SendCreate {
+            p_ndi_name: name.as_ptr(),
+            p_groups: std::ptr::null(),
+            clock_video: false,
+            clock_audio: false,
+        };
+        let instance = unsafe { send_create(&settings) };
+        if instance.is_null() {
+            return Err("failed to create NDI sender".into());
+        }
+
+        Ok(Self {
+            instance,
+            send_video,
+            send_destroy,
+            _runtime: runtime,
+        })
+    }
+
+    /// Send one progressive frame whose rows are `line_stride` bytes apart.
+    /// Without video clocking the call is synchronous, so `data` only needs
+    /// to outlive it.
+    pub fn send_video(
+        &mut self,
+        width: u32,
+        height: u32,
+        line_stride: u32,
+        four_cc: FourCc,
+        fps: f32,
+        data: &[u8],
+    ) {
+        let video_frame = VideoFrameV2 {
+            xres: width as i32,
+            yres: height as i32,
+            four_cc: four_cc.code(),
+            frame_rate_n: (fps * 1000.0).round() as i32,
+            frame_rate_d: 1000,
+            picture_aspect_ratio: width as f32 / height.max(1) as f32,
+            frame_format_type: FRAME_FORMAT_PROGRESSIVE,
+            timecode: TIMECODE_SYNTHESIZE,
+            p_data: data.as_ptr(),
+            line_stride_in_bytes: line_stride as i32,
+            p_metadata: std::ptr::null(),
+            timestamp: 0,
+        };
+        unsafe { (self.send_video)(self.instance, &video_frame) };
+    }
+}
+
+impl Drop for NdiSender {
+    fn drop(&mut self) {
+        unsafe { (self.send_destroy)(self.instance) };
+    }
+}
+
+/// A received frame with tightly packed RGBA rows
+pub struct ReceivedVideo {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub struct NdiReceiver {
+    instance: *mut c_void,
+    capture: RecvCapture,
+    free_video: RecvFreeVideo,
+    recv_destroy: RecvDestroy,
+    _runtime: Runtime,
+}
+
+// Same contract as `NdiSender`
+unsafe impl Send for NdiReceiver {}
+
+impl NdiReceiver {
+    /// Connect to the source named `source`, e.g. `"HOST (OBS)"`. The source
+    /// doesn't need to exist yet; NDI connects whenever it appears.
+    pub fn new(source: &str) -> Result<Self, String> {
+        let source = CString::new(source)
+            .map_err(|_| "NDI source name contains a NUL byte")?;
+        let runtime = Runtime::load()?;
+        let library = &runtime.library;
+        let (recv_create, capture, free_video, recv_destroy) = unsafe {
+            (
+                symbol::<RecvCreate>(library, "NDIlib_recv_create_v3")?,
+                symbol::<RecvCapture>(library, "NDIlib_recv_capture_v2")?,
+                symbol::<RecvFreeVideo>(library, "NDIlib_recv_free_video_v2")?,
+                symbol::<RecvDestroy>(library, "NDIlib_recv_destroy")?,
+            )
+        };
+
+        let settings = RecvCreateV3 {
+            source_to_connect_to: Source {
+                p_ndi_name: source.as_ptr(),
+                p_url_address: std::ptr::null(),
+            },
+            color_format: RECV_COLOR_FORMAT_RGBX_RGBA,
+            bandwidth: RECV_BANDWIDTH_HIGHEST,
+            allow_video_fields: false,
+            p_ndi_recv_name: std::ptr::null(),
+        };
+        let instance = unsafe { recv_create(&settings) };
+        if instance.is_null() {
+            return Err("failed to create NDI receiver".into());
+        }
+
+        Ok(Self {
+            instance,
+            capture,
+            free_video,
+            recv_destroy,
+            _runtime: runtime,
+        })
+    }
+
+    /// Wait up to `timeout` for the next video frame. Audio and metadata
+    /// are ignored.
+    pub fn capture_video(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<ReceivedVideo> {
+        let mut frame = VideoFrameV2::empty();
+        let frame_type = unsafe {
+            (self.capture)(
+                self.instance,
+                &mut frame,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                timeout.as_millis() as u32,
+            )
+        };
+        if frame_type != FRAME_TYPE_VIDEO {
+            return None;
+        }
+
+        let received = FourCc::from_code(frame.four_cc).and_then(|four_cc| {
+            let width = frame.xres.max(0) as u32;
+            let height = frame.yres.max(0) as u32;
+            let stride = frame.line_stride_in_bytes.max(0) as usize;
+            if frame.p_data.is_null() || stride < width as usize * 4 {
+                return None;
+            }
+            let data = unsafe {
+                std::slice::from_raw_parts(
+                    frame.p_data,
+                    stride * height as usize,
+                )
+            };
+            Some(ReceivedVideo {
+                width,
+                height,
+                rgba: packed_rgba(data, width, height, stride, four_cc),
+            })
+        });
+        unsafe { (self.free_video)(self.instance, &frame) };
+
+        received
+    }
+}
+
+impl Drop for NdiReceiver {
+    fn drop(&mut self) {
+        unsafe { (self.recv_destroy)(self.instance) };
+    }
+}
+
+fn packed_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    four_cc: FourCc,
+) -> Vec<u8> {
+    let row_bytes = width as usize * 4;
+    let mut rgba = Vec::with_capacity(row_bytes * height as usize);
+    for row in data.chunks(stride).take(height as usize) {
+        rgba.extend_from_slice(&row[..row_bytes]);
+    }
+    match four_cc {
+        FourCc::Rgba => {}
+        FourCc::Rgbx => {
+            for pixel in rgba.chunks_exact_mut(4) {
+                pixel[3] = 255;
+            }
+        }
+        FourCc::Bgra => {
+            for pixel in rgba.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+    }
+    rgba
+}
@@ -1,33 +1,68 @@
 use std::error::Error;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, LazyLock, Mutex};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use nannou_osc as osc;
 
 use crate::core::prelude::*;
+use crate::ternary;
 
 pub static SHARED_OSC_RECEIVER: LazyLock<Arc<Receiver>> =
     LazyLock::new(Receiver::new);
 
+pub static SHARED_OSC_SENDER: LazyLock<Sender> = LazyLock::new(Sender::new);
+
+/// Where [`SHARED_OSC_SENDER`] sends until told otherwise
+pub const DEFAULT_OSC_SEND_TARGET: &str = "127.0.0.1:9000";
+
+/// An argument of an outbound OSC message, e.g. `OscArg::Float(0.5)`
+pub type OscArg = osc::Type;
+
+/// Address external controllers send to to request the current value of every
+/// OSC control, e.g. when a tablet reconnects mid-session
+pub const SYNC_ADDRESS: &str = "/sync";
+
+/// Longest a bundle is held waiting for its timetag. Bundles scheduled further
+/// ahead are dispatched once this elapses so a bad sender clock can't make the
+/// receiver buffer without bound.
+pub const MAX_BUNDLE_DEFER: Duration = Duration::from_secs(1);
+
+/// Seconds between the OSC (NTP) epoch of 1900 and the Unix epoch
+const OSC_UNIX_OFFSET: u64 = 2_208_988_800;
+
 type OscCallback = Box<dyn Fn(&osc::Message) + Send + Sync>;
+type SyncHandler = Box<dyn Fn() -> Vec<osc::Message> + Send + Sync>;
 
 pub struct Receiver {
-    callbacks: Arc<Mutex<HashMap<String, Vec<OscCallback>>>>,
+    routes: Arc<Routes>,
     thread_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     thread_running: Arc<AtomicBool>,
+    port: Mutex<Option<u16>>,
 }
 
 impl Default for Receiver {
     fn default() -> Self {
         Self {
-            callbacks: Arc::new(Mutex::new(HashMap::default())),
+            routes: Arc::new(Routes::default()),
             thread_handle: Arc::new(Mutex::new(None)),
             thread_running: Arc::new(AtomicBool::new(false)),
+            port: Mutex::new(None),
         }
     }
 }
 
+#[derive(Default)]
+struct Routes {
+    callbacks: Mutex<HashMap<String, Vec<OscCallback>>>,
+    unprefixed_callbacks: Mutex<HashMap<String, Vec<OscCallback>>>,
+    sync_handler: Mutex<Option<SyncHandler>>,
+    prefix: Mutex<String>,
+}
+
 impl Receiver {
     pub fn new() -> Arc<Self> {
         Arc::new(Self::default())
@@ -37,35 +72,95 @@ impl Receiver {
     where
         F: Fn(&osc::Message) + Send + Sync + 'static,
     {
-        let mut callbacks = self.callbacks.lock().unwrap();
+        let mut callbacks = self.routes.callbacks.lock().unwrap();
         let handlers = callbacks.entry(address.to_string()).or_default();
         handlers.push(Box::new(callback));
     }
 
+    /// Like [`Self::register_callback`], but `address` is matched exactly as
+    /// sent, outside of any prefix. For senders at fixed paths, like the DAW
+    /// plug-in's `/transport`.
+    pub fn register_unprefixed<F>(&self, address: &str, callback: F)
+    where
+        F: Fn(&osc::Message) + Send + Sync + 'static,
+    {
+        let mut callbacks = self.routes.unprefixed_callbacks.lock().unwrap();
+        let handlers = callbacks.entry(address.to_string()).or_default();
+        handlers.push(Box::new(callback));
+    }
+
+    /// Set the function that answers [`SYNC_ADDRESS`] requests. The messages
+    /// it returns are sent back to the requesting address. Replaces any
+    /// previously set handler.
+    pub fn set_sync_handler<F>(&self, handler: F)
+    where
+        F: Fn() -> Vec<osc::Message> + Send + Sync + 'static,
+    {
+        *self.routes.sync_handler.lock().unwrap() = Some(Box::new(handler));
+    }
+
+    /// Namespace this instance's addresses so several instances can share a
+    /// network: with a prefix of `/xtal1`, `/xtal1/radius` is dispatched as
+    /// `/radius` and unprefixed messages are ignored, except those for
+    /// [`Self::register_unprefixed`] addresses. Replies are prefixed too. An
+    /// empty prefix disables namespacing.
+    pub fn set_prefix(&self, prefix: &str) {
+        let prefix = prefix.trim().trim_matches('/');
+        *self.routes.prefix.lock().unwrap() =
+            ternary!(prefix.is_empty(), String::new(), format!("/{}", prefix));
+    }
+
+    pub fn prefix(&self) -> String {
+        self.routes.prefix.lock().unwrap().clone()
+    }
+
+    /// Run the callbacks registered for `msg`, returning any replies owed to
+    /// its sender
+    pub fn dispatch(&self, msg: &osc::Message) -> Vec<osc::Message> {
+        self.routes.dispatch(msg)
+    }
+
+    /// The port currently listened on, if started
+    pub fn port(&self) -> Option<u16> {
+        *self.port.lock().unwrap()
+    }
+
     pub fn start(&self, port: u16) -> Result<(), Box<dyn Error>> {
         let receiver = osc::Receiver::bind(port)?;
-        let callbacks = self.callbacks.clone();
+        self.listen(receiver, port);
+        Ok(())
+    }
+
+    fn listen(&self, receiver: osc::Receiver, port: u16) {
+        let routes = self.routes.clone();
         let running = self.thread_running.clone();
 
         running.store(true, Ordering::SeqCst);
 
         let handle = thread::spawn(move || {
+            let mut reply_sender = None;
+            let mut bundles = BundleQueue::default();
             while running.load(Ordering::SeqCst) {
                 let mut processed = false;
-                for (packet, _) in receiver.try_iter() {
+                let mut received = vec![];
+                for (packet, from) in receiver.try_iter() {
                     processed = true;
-                    if let osc::Packet::Message(msg) = packet {
-                        let callbacks = callbacks.lock().unwrap();
-                        if let Some(handlers) = callbacks.get(&msg.addr) {
-                            for handler in handlers {
-                                handler(&msg);
-                            }
-                        } else if let Some(handlers) = callbacks.get("*") {
-                            for handler in handlers {
-                                handler(&msg);
-                            }
-                        }
-                    }
+                    let messages = match packet {
+                        osc::Packet::Message(msg) => vec![msg],
+                        osc::Packet::Bundle(bundle) => bundles.schedule(
+                            bundle,
+                            from,
+                            Instant::now(),
+                            SystemTime::now(),
+                        ),
+                    };
+                    received.extend(messages.into_iter().map(|m| (m, from)));
+                }
+                received.extend(bundles.take_due(Instant::now()));
+
+                for (msg, from) in received {
+                    let replies = routes.dispatch(&msg);
+                    send_replies(&mut reply_sender, replies, from);
                 }
 
                 if !processed {
@@ -78,10 +173,9 @@ impl Receiver {
 
         let mut thread_handle = self.thread_handle.lock().unwrap();
         *thread_handle = Some(handle);
+        *self.port.lock().unwrap() = Some(port);
 
         info!("OSC receiver listening on port {}", port);
-
-        Ok(())
     }
 
     pub fn stop(&self) -> Result<(), Box<dyn Error>> {
@@ -90,12 +184,583 @@ impl Receiver {
         if let Some(handle) = thread_handle.take() {
             handle.join().unwrap();
         }
+        *self.port.lock().unwrap() = None;
         Ok(())
     }
 
+    /// Rebind to `port`, e.g. when it's changed in the UI. Registered
+    /// callbacks, the sync handler and the prefix belong to the receiver
+    /// rather than the socket, so they all carry over. When moving to a new
+    /// port the new socket is bound before the old one is closed: if the bind
+    /// fails the receiver keeps listening where it was.
     pub fn restart(&self, port: u16) -> Result<(), Box<dyn Error>> {
-        self.stop()?;
         info!("Restarting OSC receiver on {}", port);
-        self.start(port)
+        if self.port() == Some(port) {
+            self.stop()?;
+            return self.start(port);
+        }
+
+        let receiver = osc::Receiver::bind(port)?;
+        self.stop()?;
+        self.listen(receiver, port);
+        Ok(())
+    }
+}
+
+/// Holds the messages of bundles whose timetag is in the future until it
+/// arrives (capped at [`MAX_BUNDLE_DEFER`]). A bundle's messages, including
+/// those of nested bundles, are always dispatched together and in order.
+#[derive(Default)]
+struct BundleQueue {
+    pending: Vec<(Instant, SocketAddr, Vec<osc::Message>)>,
+}
+
+impl BundleQueue {
+    /// Returns the bundle's messages if they are due now, otherwise holds
+    /// them for [`Self::take_due`]
+    fn schedule(
+        &mut self,
+        bundle: osc::Bundle,
+        from: SocketAddr,
+        now: Instant,
+        wall_clock: SystemTime,
+    ) -> Vec<osc::Message> {
+        let delay = bundle_delay(bundle.timetag, wall_clock);
+        let messages = osc::Packet::Bundle(bundle).into_msgs();
+
+        if delay.is_zero() {
+            return messages;
+        }
+
+        trace!("Deferring OSC bundle by {:?}", delay);
+        self.pending.push((now + delay, from, messages));
+        vec![]
+    }
+
+    /// Messages of every bundle due by `now`, earliest scheduled first
+    fn take_due(&mut self, now: Instant) -> Vec<(osc::Message, SocketAddr)> {
+        if self.pending.is_empty() {
+            return vec![];
+        }
+
+        let (mut due, pending): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|(due_at, _, _)| *due_at <= now);
+        self.pending = pending;
+        due.sort_by_key(|(due_at, _, _)| *due_at);
+
+        due.into_iter()
+            .flat_map(|(_, from, messages)| {
+                messages.into_iter().map(move |msg| (msg, from))
+            })
+            .collect()
+    }
+}
+
+// How long to wait before dispatching a bundle tagged `timetag`. The special
+// "immediately" tag (0, 1) and times in the past are due now.
+fn bundle_delay(timetag: osc::Time, wall_clock: SystemTime) -> Duration {
+    let seconds = timetag.seconds as u64;
+    if seconds < OSC_UNIX_OFFSET {
+        return Duration::ZERO;
+    }
+
+    let nanos = (timetag.fractional as u64 * 1_000_000_000) >> 32;
+    let scheduled =
+        UNIX_EPOCH + Duration::new(seconds - OSC_UNIX_OFFSET, nanos as u32);
+
+    scheduled
+        .duration_since(wall_clock)
+        .unwrap_or(Duration::ZERO)
+        .min(MAX_BUNDLE_DEFER)
+}
+
+impl Routes {
+    fn dispatch(&self, msg: &osc::Message) -> Vec<osc::Message> {
+        if let Some(handlers) =
+            self.unprefixed_callbacks.lock().unwrap().get(&msg.addr)
+        {
+            for handler in handlers {
+                handler(msg);
+            }
+            return vec![];
+        }
+
+        let prefix = self.prefix.lock().unwrap().clone();
+        let Some(address) = strip_prefix(&msg.addr, &prefix) else {
+            trace!("Ignoring OSC message outside of {}: {}", prefix, msg.addr);
+            return vec![];
+        };
+
+        if address == SYNC_ADDRESS {
+            let replies = self
+                .sync_handler
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|handler| handler())
+                .unwrap_or_default();
+            return replies
+                .into_iter()
+                .map(|reply| osc::Message {
+                    addr: format!("{}{}", prefix, reply.addr),
+                    args: reply.args,
+                })
+                .collect();
+        }
+
+        let msg = osc::Message {
+            addr: address.to_string(),
+            args: msg.args.clone(),
+        };
+        let callbacks = self.callbacks.lock().unwrap();
+        if let Some(handlers) = callbacks.get(&msg.addr) {
+            for handler in handlers {
+                handler(&msg);
+            }
+        } else if let Some(handlers) = callbacks.get("*") {
+            for handler in handlers {
+                handler(&msg);
+            }
+        }
+
+        vec![]
+    }
+}
+
+/// `address` with `prefix` removed, or `None` if it's outside the prefix.
+/// An empty prefix matches every address.
+pub fn strip_prefix<'a>(address: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() {
+        return Some(address);
+    }
+    address
+        .strip_prefix(prefix)
+        .filter(|rest| rest.starts_with('/'))
+}
+
+// The reply socket is only bound once the first reply is owed
+fn send_replies(
+    reply_sender: &mut Option<osc::Sender>,
+    replies: Vec<osc::Message>,
+    to: SocketAddr,
+) {
+    if replies.is_empty() {
+        return;
+    }
+
+    if reply_sender.is_none() {
+        match osc::Sender::bind() {
+            Ok(sender) => *reply_sender = Some(sender),
+            Err(err) => {
+                error!("Unable to bind OSC reply socket: {}", err);
+                return;
+            }
+        }
+    }
+    let Some(sender) = reply_sender.as_ref() else {
+        return;
+    };
+
+    debug!("Replying to {} with {} OSC messages", to, replies.len());
+    for reply in replies {
+        if let Err(err) = sender.send(reply, to) {
+            warn!("Error sending OSC reply to {}: {}", to, err);
+            return;
+        }
+    }
+}
+
+/// Outbound OSC for sketches, e.g. to trigger a sampler from a collision.
+/// The runtime binds the UDP socket at startup with [`Self::bind`]; sending
+/// never binds one.
+pub struct Sender {
+    target: Mutex<String>,
+    transport: Mutex<Option<Box<dyn Transport>>>,
+    connect: Box<dyn Fn() -> io::Result<Box<dyn Transport>> + Send + Sync>,
+}
+
+trait Transport: Send {
+    fn send_to(&self, bytes: &[u8], target: &str) -> io::Result<usize>;
+}
+
+impl Transport for UdpSocket {
+    fn send_to(&self, bytes: &[u8], target: &str) -> io::Result<usize> {
+        UdpSocket::send_to(self, bytes, target)
+    }
+}
+
+impl Sender {
+    pub fn new() -> Self {
+        Self::with_transport(|| {
+            let socket = UdpSocket::bind("0.0.0.0:0")?;
+            Ok(Box::new(socket) as Box<dyn Transport>)
+        })
+    }
+
+    fn with_transport<F>(connect: F) -> Self
+    where
+        F: Fn() -> io::Result<Box<dyn Transport>> + Send + Sync + 'static,
+    {
+        Self {
+            target: Mutex::new(DEFAULT_OSC_SEND_TARGET.to_string()),
+            transport: Mutex::new(None),
+            connect: Box::new(connect),
+        }
+    }
+
+    /// Set the `host:port` messages are sent to
+    pub fn set_target(&self, host: &str, port: u16) {
+        *self.target.lock().unwrap() = format!("{}:{}", host, port);
+    }
+
+    pub fn target(&self) -> String {
+        self.target.lock().unwrap().clone()
+    }
+
+    /// Bind the send socket. Does nothing once bound.
+    pub fn bind(&self) -> Result<(), String> {
+        let mut transport = self.transport.lock().unwrap();
+        if transport.is_none() {
+            let socket = (self.connect)().map_err(|err| {
+                format!("Unable to bind OSC send socket: {}", err)
+            })?;
+            *transport = Some(socket);
+        }
+        Ok(())
+    }
+
+    /// Send one message to the target. A missing leading `/` is added to
+    /// `address` so it can be written like an OSC control's name.
+    pub fn send(&self, address: &str, args: &[OscArg]) -> Result<(), String> {
+        let addr = ternary!(
+            address.starts_with('/'),
+            address.to_string(),
+            format!("/{}", address)
+        );
+        let bytes = osc::encode(osc::msg(addr, args.to_vec()).into())
+            .map_err(|err| format!("Unable to encode OSC message: {}", err))?;
+
+        let transport = self.transport.lock().unwrap();
+        let Some(transport) = transport.as_ref() else {
+            return Err("OSC send socket isn't bound".to_string());
+        };
+
+        let target = self.target();
+        transport.send_to(&bytes, &target).map_err(|err| {
+            format!("Unable to send OSC to {}: {}", target, err)
+        })?;
+        Ok(())
+    }
+}
+
+impl Default for Sender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn sync_request_replies_without_running_callbacks() {
+        let receiver = Receiver::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        receiver.register_callback("*", move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let sync = osc::msg(SYNC_ADDRESS, vec![]);
+        assert!(receiver.dispatch(&sync).is_empty());
+
+        receiver.set_sync_handler(|| {
+            vec![osc::msg("/radius", vec![osc::Type::Float(0.5)])]
+        });
+        assert_eq!(
+            receiver.dispatch(&sync),
+            vec![osc::msg("/radius", vec![osc::Type::Float(0.5)])]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let update = osc::msg("/radius", vec![osc::Type::Float(0.2)]);
+        assert!(receiver.dispatch(&update).is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn prefix_routes_only_namespaced_addresses() {
+        let receiver = Receiver::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        let addresses = received.clone();
+        receiver.register_callback("*", move |msg| {
+            addresses.lock().unwrap().push(msg.addr.clone());
+        });
+        receiver.set_sync_handler(|| vec![osc::msg("/radius", vec![])]);
+        let send = |addr: &str| receiver.dispatch(&osc::msg(addr, vec![]));
+
+        send("/radius");
+        send("/xtal1/radius");
+        assert_eq!(*received.lock().unwrap(), ["/radius", "/xtal1/radius"]);
+        received.lock().unwrap().clear();
+
+        receiver.set_prefix("xtal1/");
+        assert_eq!(receiver.prefix(), "/xtal1");
+
+        send("/radius");
+        send("/xtal1/radius");
+        send("/xtal10/radius");
+        send("/xtal2/radius");
+        assert_eq!(*received.lock().unwrap(), ["/radius"]);
+
+        assert!(send("/sync").is_empty());
+        assert_eq!(send("/xtal1/sync"), [osc::msg("/xtal1/radius", vec![])]);
+    }
+
+    #[test]
+    fn unprefixed_callbacks_ignore_the_prefix() {
+        let receiver = Receiver::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        let transport = received.clone();
+        receiver.register_unprefixed("/transport", move |msg| {
+            transport.lock().unwrap().push(msg.addr.clone());
+        });
+        let controls = received.clone();
+        receiver.register_callback("*", move |msg| {
+            controls.lock().unwrap().push(format!("* {}", msg.addr));
+        });
+        let send = |addr: &str| receiver.dispatch(&osc::msg(addr, vec![]));
+
+        send("/transport");
+        receiver.set_prefix("/xtal1");
+        send("/transport");
+        send("/xtal1/transport");
+        assert_eq!(
+            *received.lock().unwrap(),
+            ["/transport", "/transport", "* /transport"]
+        );
+    }
+
+    // A port nothing is listening on, found by briefly binding port 0
+    fn free_port() -> u16 {
+        let socket = UdpSocket::bind("0.0.0.0:0").expect("bind socket");
+        socket.local_addr().expect("local addr").port()
+    }
+
+    // Send `/transport` to `port` until `count` reaches `expected`, giving
+    // up after a second
+    fn send_until(port: u16, count: &AtomicUsize, expected: usize) -> bool {
+        let sender = osc::Sender::bind().expect("bind sender");
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if count.load(Ordering::SeqCst) >= expected {
+                return true;
+            }
+            let _ = sender
+                .send(osc::msg("/transport", vec![]), ("127.0.0.1", port));
+            thread::sleep(Duration::from_millis(10));
+        }
+        count.load(Ordering::SeqCst) >= expected
+    }
+
+    #[test]
+    fn restart_keeps_callbacks_across_port_changes() {
+        let receiver = Receiver::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        receiver.register_callback("/transport", move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let first = free_port();
+        receiver.start(first).expect("start");
+        assert!(send_until(first, &calls, 1));
+
+        let second = free_port();
+        receiver.restart(second).expect("restart");
+        assert_eq!(receiver.port(), Some(second));
+        let before = calls.load(Ordering::SeqCst);
+        assert!(send_until(second, &calls, before + 1));
+
+        receiver.stop().expect("stop");
+        assert_eq!(receiver.port(), None);
+    }
+
+    #[test]
+    fn failed_restart_keeps_listening_on_the_old_port() {
+        let receiver = Receiver::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        receiver.register_callback("/transport", move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let port = free_port();
+        receiver.start(port).expect("start");
+        let taken = UdpSocket::bind("0.0.0.0:0").expect("bind socket");
+        let taken_port = taken.local_addr().expect("local addr").port();
+
+        assert!(receiver.restart(taken_port).is_err());
+        assert_eq!(receiver.port(), Some(port));
+        assert!(send_until(port, &calls, 1));
+
+        receiver.stop().expect("stop");
+    }
+
+    type Sent = Arc<Mutex<Vec<(Vec<u8>, String)>>>;
+
+    struct MockTransport(Sent);
+
+    impl Transport for MockTransport {
+        fn send_to(&self, bytes: &[u8], target: &str) -> io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((bytes.to_vec(), target.to_string()));
+            Ok(bytes.len())
+        }
+    }
+
+    #[test]
+    fn sender_encodes_address_and_args_over_one_bound_socket() {
+        let sent: Sent = Arc::new(Mutex::new(vec![]));
+        let connects = Arc::new(AtomicUsize::new(0));
+        let sender = {
+            let sent = sent.clone();
+            let connects = connects.clone();
+            Sender::with_transport(move || {
+                connects.fetch_add(1, Ordering::SeqCst);
+                Ok(Box::new(MockTransport(sent.clone())) as Box<dyn Transport>)
+            })
+        };
+        assert!(sender.send("/sampler/hit", &[]).is_err());
+        assert_eq!(connects.load(Ordering::SeqCst), 0);
+
+        sender.bind().unwrap();
+        sender.bind().unwrap();
+        sender
+            .send("/sampler/hit", &[OscArg::Int(3), OscArg::Float(0.5)])
+            .unwrap();
+        sender.set_target("192.168.1.20", 57120);
+        sender.send("edge", &[]).unwrap();
+
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+        let sent = sent.lock().unwrap();
+        let decoded: Vec<(osc::Packet, &str)> = sent
+            .iter()
+            .map(|(bytes, target)| {
+                (osc::decode(bytes).unwrap(), target.as_str())
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            vec![
+                (
+                    osc::msg(
+                        "/sampler/hit",
+                        vec![OscArg::Int(3), OscArg::Float(0.5)]
+                    )
+                    .into(),
+                    DEFAULT_OSC_SEND_TARGET
+                ),
+                (osc::msg("/edge", vec![]).into(), "192.168.1.20:57120"),
+            ]
+        );
+    }
+
+    fn bundle(timetag: (u32, u32)) -> osc::Bundle {
+        let content = ["/a", "/b", "/c"]
+            .iter()
+            .enumerate()
+            .map(|(i, addr)| {
+                osc::Packet::Message(osc::msg(
+                    *addr,
+                    vec![osc::Type::Float(i as f32)],
+                ))
+                .into()
+            })
+            .collect();
+        osc::Bundle {
+            timetag: timetag.into(),
+            content,
+        }
+    }
+
+    // OSC timetag `offset` after `wall_clock`
+    fn timetag_after(wall_clock: SystemTime, offset: Duration) -> (u32, u32) {
+        let since_unix =
+            wall_clock.duration_since(UNIX_EPOCH).unwrap() + offset;
+        let seconds = since_unix.as_secs() + OSC_UNIX_OFFSET;
+        let fractional =
+            ((since_unix.subsec_nanos() as u64) << 32) / 1_000_000_000;
+        (seconds as u32, fractional as u32)
+    }
+
+    #[test]
+    fn bundle_messages_dispatch_in_order_to_their_addresses() {
+        let receiver = Receiver::default();
+        let received = Arc::new(Mutex::new(vec![]));
+        for addr in ["/a", "/b", "/c"] {
+            let received = received.clone();
+            receiver.register_callback(addr, move |msg| {
+                received.lock().unwrap().push(msg.clone());
+            });
+        }
+
+        let from: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let mut bundles = BundleQueue::default();
+        let messages = bundles.schedule(
+            bundle((0, 1)),
+            from,
+            Instant::now(),
+            SystemTime::now(),
+        );
+        for msg in &messages {
+            receiver.dispatch(msg);
+        }
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                osc::msg("/a", vec![osc::Type::Float(0.0)]),
+                osc::msg("/b", vec![osc::Type::Float(1.0)]),
+                osc::msg("/c", vec![osc::Type::Float(2.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn future_bundles_wait_for_their_timetag_up_to_the_max_defer() {
+        let from: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let wall_clock = SystemTime::now();
+        let now = Instant::now();
+        let mut bundles = BundleQueue::default();
+
+        let soon = timetag_after(wall_clock, Duration::from_millis(100));
+        assert!(
+            bundles
+                .schedule(bundle(soon), from, now, wall_clock)
+                .is_empty()
+        );
+        let later = timetag_after(wall_clock, Duration::from_secs(60));
+        assert!(
+            bundles
+                .schedule(bundle(later), from, now, wall_clock)
+                .is_empty()
+        );
+
+        assert!(bundles.take_due(now + Duration::from_millis(50)).is_empty());
+
+        let due = bundles.take_due(now + Duration::from_millis(150));
+        let addresses: Vec<&str> =
+            due.iter().map(|(msg, _)| msg.addr.as_str()).collect();
+        assert_eq!(addresses, ["/a", "/b", "/c"]);
+
+        assert_eq!(bundles.take_due(now + MAX_BUNDLE_DEFER).len(), 3);
+        assert!(bundles.pending.is_empty());
     }
 }
@@ -9,12 +9,22 @@ pub mod runtime;
 pub mod sketches;
 pub mod time;
 
+pub use render::camera;
 pub use render::frame;
 pub use render::gpu;
 pub use render::graph;
+pub use render::image;
+pub use render::instancing;
 pub use render::mesh;
+#[cfg(feature = "ndi")]
+pub use render::ndi_input;
+pub use render::pass_timing;
+pub use render::polyline;
 pub use render::shader_watch;
+pub use render::text;
 pub use render::uniforms;
+#[cfg(feature = "video")]
+pub use render::video;
 pub use runtime::app::run_registry;
 pub use sketches::sketch;
 pub use sketches::sketch_assets;
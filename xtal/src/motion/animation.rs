@@ -5,10 +5,14 @@ use rand::{Rng, SeedableRng};
 use std::cell::RefCell;
 use std::str::FromStr;
 
+use crate::core::noise::Noise;
 use crate::core::prelude::*;
+use crate::motion::test_timing::TestTiming;
 use crate::time::frame_clock;
 use crate::{ternary, warn_once};
 
+const MAX_SPRING_STEPS: u32 = 240;
+
 struct PerlinNoise {
     seed: u32,
 }
@@ -312,21 +316,78 @@ impl FromStr for Mode {
 #[derive(Clone, Debug)]
 pub struct Animation<T: TimingSource> {
     pub timing: T,
-    random_smooth_previous_values: RefCell<HashMap<u64, f32>>,
+    frozen_beats: Option<f32>,
+    loop_region: Option<(f32, f32)>,
+    random_smooth_previous_values: RefCell<HashMap<(u64, u32), f32>>,
+    springs: RefCell<HashMap<u64, (Spring, u32)>>,
 }
 
 impl<T: TimingSource> Animation<T> {
     pub fn new(timing: T) -> Self {
         Self {
             timing,
+            frozen_beats: None,
+            loop_region: None,
             random_smooth_previous_values: RefCell::new(HashMap::default()),
+            springs: RefCell::new(HashMap::default()),
         }
     }
 
     /// Return the number of beats that have elapsed
-    /// since (re)start of this Animation's Timing source
+    /// since (re)start of this Animation's Timing source, wrapped into the
+    /// loop region if one is set
     pub fn beats(&self) -> f32 {
-        self.timing.beats()
+        let beats = self.frozen_beats.unwrap_or_else(|| self.timing.beats());
+        match self.loop_region {
+            Some((start, end)) if beats >= end => {
+                start + (beats - start) % (end - start)
+            }
+            _ => beats,
+        }
+    }
+
+    /// Play the `(in, out)` beat range on repeat: once the timing source
+    /// reaches `out`, [`Self::beats`] wraps back to `in`. Beats before `in`
+    /// play through normally. `None` clears the region.
+    pub fn set_loop_region(
+        &mut self,
+        region: Option<(f32, f32)>,
+    ) -> Result<(), String> {
+        if let Some((start, end)) = region {
+            if !start.is_finite() || !end.is_finite() || start < 0.0 {
+                return Err(format!(
+                    "invalid loop region: {} to {}",
+                    start, end
+                ));
+            }
+            if end <= start {
+                return Err(format!(
+                    "loop out ({}) must come after loop in ({})",
+                    end, start
+                ));
+            }
+        }
+        self.loop_region = region;
+        Ok(())
+    }
+
+    pub fn loop_region(&self) -> Option<(f32, f32)> {
+        self.loop_region
+    }
+
+    /// Latch [`Self::beats`] at its current value so every beat-based
+    /// animation holds still while frames keep rendering. The timing source
+    /// keeps running underneath (including external MIDI/OSC beats), so
+    /// unfreezing jumps back to the live transport position.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if frozen == self.frozen() {
+            return;
+        }
+        self.frozen_beats = ternary!(frozen, Some(self.timing.beats()), None);
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.frozen_beats.is_some()
     }
 
     /// Convert `beats` to frame count
@@ -336,6 +397,16 @@ impl<T: TimingSource> Animation<T> {
         total_seconds * frame_clock::fps()
     }
 
+    /// Convert `beats` to seconds at the timing source's current tempo
+    pub fn beats_to_seconds(&self, beats: f32) -> f32 {
+        beats * 60.0 / self.timing.bpm()
+    }
+
+    /// Convert `seconds` to beats at the timing source's current tempo
+    pub fn seconds_to_beats(&self, seconds: f32) -> f32 {
+        seconds * self.timing.bpm() / 60.0
+    }
+
     /// Return a relative phase position from [0, 1] within
     /// the passed in duration (specified in beats)
     pub fn ramp(&self, duration: f32) -> f32 {
@@ -370,7 +441,42 @@ impl<T: TimingSource> Animation<T> {
         (min, max): (f32, f32),
         phase_offset: f32,
     ) -> f32 {
-        let mut x = (self.beats() / duration + phase_offset.abs() * 0.5) % 1.0;
+        self.triangle_cycle(duration, (min, max), phase_offset.abs() * 0.5)
+    }
+
+    /// Like [`Self::ramp_plus`] for instance `index` of `count` copies of the
+    /// same animation, each shifted by `index / count` of the cycle so the
+    /// copies ripple evenly (e.g. rings or particles drawn with instancing)
+    pub fn ramp_phased(
+        &self,
+        duration: f32,
+        range: (f32, f32),
+        count: usize,
+        index: usize,
+    ) -> f32 {
+        self.ramp_plus(duration, range, instance_phase(count, index))
+    }
+
+    /// Like [`Self::triangle`] for instance `index` of `count` copies, each
+    /// shifted by `index / count` of the cycle. See [`Self::ramp_phased`].
+    pub fn triangle_phased(
+        &self,
+        duration: f32,
+        range: (f32, f32),
+        count: usize,
+        index: usize,
+    ) -> f32 {
+        self.triangle_cycle(duration, range, instance_phase(count, index))
+    }
+
+    // `phase` is in whole cycles, unlike the half-cycle units of `triangle`
+    fn triangle_cycle(
+        &self,
+        duration: f32,
+        (min, max): (f32, f32),
+        phase: f32,
+    ) -> f32 {
+        let mut x = (self.beats() / duration + phase) % 1.0;
         x = ternary!(x < 0.5, x, 1.0 - x) * 2.0;
         map_range(x, 0.0, 1.0, min, max)
     }
@@ -416,7 +522,7 @@ impl<T: TimingSource> Animation<T> {
 
         // Ensures two different calls that share the same seed but differ in
         // delay have the same overall pattern
-        let key = stem + (delay.to_bits() as u64 * 10_000_000);
+        let key = (stem, delay.to_bits());
 
         let mut prev_values = self.random_smooth_previous_values.borrow_mut();
         let value = prev_values.get(&key).map_or(value, |prev| {
@@ -428,6 +534,61 @@ impl<T: TimingSource> Animation<T> {
         value
     }
 
+    /// Smooth, organic random motion within `(min, max)` from 1D Perlin noise
+    /// sampled at `beats / duration`, so `duration` is roughly the number of
+    /// beats between peaks. Each extra octave layers noise at twice the speed
+    /// and half the strength for finer detail. The same `seed` always produces
+    /// the same curve.
+    pub fn noise(
+        &self,
+        duration: f32,
+        (min, max): (f32, f32),
+        octaves: u32,
+        seed: u64,
+    ) -> f32 {
+        let x = self.beats() / duration;
+        let value = Noise::new(seed).fbm(x, octaves, 2.0, 0.5);
+        map_range(value.clamp(-1.0, 1.0), -1.0, 1.0, min, max)
+    }
+
+    /// Follow `target` with a [`Spring`], integrated once per rendered frame
+    /// since the previous call so calling more than once a frame is free. The
+    /// spring starts at rest on its first target and snaps back to the target
+    /// if the frame count goes backwards. A unique `stem` is required for
+    /// internal state tracking.
+    pub fn spring(
+        &self,
+        target: f32,
+        stiffness: f32,
+        damping: f32,
+        stem: u64,
+    ) -> f32 {
+        let frame = frame_clock::frame_count();
+        let mut springs = self.springs.borrow_mut();
+        let (spring, last_frame) = springs.entry(stem).or_insert_with(|| {
+            let mut spring = Spring::new(stiffness, damping);
+            spring.reset(target);
+            (spring, frame)
+        });
+
+        spring.stiffness = stiffness;
+        spring.damping = damping;
+
+        if frame < *last_frame {
+            spring.reset(target);
+        } else {
+            // Cap catch-up after a long stall so the spring doesn't fling
+            let steps = (frame - *last_frame).min(MAX_SPRING_STEPS);
+            let dt = 1.0 / frame_clock::render_fps();
+            for _ in 0..steps {
+                spring.step(target, dt);
+            }
+        }
+        *last_frame = frame;
+
+        spring.value()
+    }
+
     /// Cycle through an arbitrary list of values, advancing to the next value
     /// every `every` beats. The output is optionally smoothed by a slew
     /// limiter. `slew` controls smoothing when the value changes, with 0.0
@@ -452,7 +613,7 @@ impl<T: TimingSource> Animation<T> {
             return value;
         }
 
-        let key = stem;
+        let key = (stem, 0);
         let mut prev_values = self.random_smooth_previous_values.borrow_mut();
         let value = prev_values.get(&key).map_or(value, |prev| {
             SlewLimiter::slew_pure(*prev, value, slew, slew)
@@ -463,6 +624,43 @@ impl<T: TimingSource> Animation<T> {
         value
     }
 
+    /// Drop the slew and spring state kept for `stem` so the next
+    /// [`Self::random_slewed`], [`Self::round_robin`] or [`Self::spring`]
+    /// call using it starts fresh
+    pub fn forget_state(&self, stem: u64) {
+        self.random_smooth_previous_values
+            .borrow_mut()
+            .retain(|(key_stem, _), _| *key_stem != stem);
+        self.springs.borrow_mut().remove(&stem);
+    }
+
+    /// Step sequencer row: `steps` divide each `duration` beat cycle evenly
+    /// and the gate is high for the whole of any step that is non-zero, so
+    /// `[1, 0, 0, 1]` over 4 beats is high during beats 0 and 3.
+    pub fn gate(&self, duration: f32, steps: &[f32]) -> bool {
+        self.step_index(duration, steps.len())
+            .is_some_and(|index| steps[index] != 0.0)
+    }
+
+    /// Like [`Self::gate`] for a pattern of on/off steps such as one from
+    /// [`euclidean_rhythm`](crate::motion::euclidean_rhythm)
+    pub fn pattern_gate(&self, duration: f32, pattern: &[bool]) -> bool {
+        self.step_index(duration, pattern.len())
+            .is_some_and(|index| pattern[index])
+    }
+
+    // Index of the current step when `duration` beats are split into `count`
+    // equal steps
+    fn step_index(&self, duration: f32, count: usize) -> Option<usize> {
+        if count == 0 || duration <= 0.0 {
+            return None;
+        }
+
+        let step_beats = duration / count as f32;
+        let position = self.beats() % duration;
+        Some(((position / step_beats).floor() as usize) % count)
+    }
+
     /// Creates a new [`Trigger`] with specified interval and delay;
     /// Use with [`Self::should_trigger`].
     pub fn create_trigger(&self, every: f32, delay: f32) -> Trigger {
@@ -691,11 +889,32 @@ impl<T: TimingSource> Animation<T> {
     }
 }
 
+impl Animation<TestTiming> {
+    /// Move the (global) frame clock to `beat`, see [`TestTiming`]
+    pub fn advance_to_beat(&self, beat: f32) {
+        self.timing.advance_to_beat(beat);
+    }
+
+    /// Move the (global) frame clock to `frame`, see [`TestTiming`]
+    pub fn advance_to_frame(&self, frame: u32) {
+        self.timing.advance_to_frame(frame);
+    }
+}
+
+/// Fraction of a cycle that instance `index` of `count` is shifted by
+fn instance_phase(count: usize, index: usize) -> f32 {
+    if count == 0 {
+        return 0.0;
+    }
+    (index % count) as f32 / count as f32
+}
+
 #[cfg(test)]
 pub mod animation_tests {
     use super::*;
     use serial_test::serial;
     use std::sync::Once;
+    use std::time::Instant;
 
     // With BPM=360 and FPS=24, each beat = 4 frames, each 1/16 = 1 frame.
     // This keeps frame counts small and avoids precision issues.
@@ -715,6 +934,7 @@ pub mod animation_tests {
         // Re-apply global frame state every test step because other serial
         // tests mutate frame_clock FPS.
         frame_clock::set_fps(FPS);
+        frame_clock::set_render_fps(None);
         frame_clock::set_paused(false);
         frame_clock::set_elapsed_seconds(beat * (60.0 / BPM));
         frame_clock::set_frame_count((beat * FRAMES_PER_BEAT) as u32);
@@ -742,6 +962,137 @@ pub mod animation_tests {
         assert_eq!(val, 0.75, "3/16");
     }
 
+    #[test]
+    fn test_beats_seconds_conversion_follows_tempo() {
+        let bpm = Bpm::new(120.0);
+        let a = Animation::new(FrameTiming::new(bpm.clone()));
+
+        assert_eq!(a.beats_to_seconds(1.0), 0.5);
+        assert_eq!(a.seconds_to_beats(0.5), 1.0);
+        assert_eq!(a.seconds_to_beats(a.beats_to_seconds(3.0)), 3.0);
+
+        bpm.set(60.0);
+        assert_eq!(a.beats_to_seconds(1.0), 1.0);
+        assert_eq!(a.seconds_to_beats(2.0), 2.0);
+    }
+
+    #[test]
+    fn test_phased_instances_are_evenly_offset() {
+        let timing = ManualTiming::new(Bpm::new(BPM));
+        let a = Animation::new(timing.clone());
+        let count = 8;
+
+        for beats in [0.0, 0.5, 1.25, 3.0, 5.75] {
+            timing.set_beats(beats);
+            let first = a.triangle_phased(4.0, (0.0, 1.0), count, 0);
+            let opposite = a.triangle_phased(4.0, (0.0, 1.0), count, count / 2);
+
+            // Half a cycle ahead, instance N/2 sits where instance 0 will be
+            // in 2 beats, which on a triangle mirrors its current value
+            assert_eq!(first + opposite, 1.0, "beats {}", beats);
+            timing.set_beats(beats + 2.0);
+            assert_eq!(
+                a.triangle_phased(4.0, (0.0, 1.0), count, 0),
+                opposite,
+                "beats {}",
+                beats
+            );
+
+            timing.set_beats(beats);
+            let ramp_first = a.ramp_phased(4.0, (0.0, 1.0), count, 0);
+            let ramp_opposite = a.ramp_phased(4.0, (0.0, 1.0), count, 4);
+            assert_eq!((ramp_first + 0.5) % 1.0, ramp_opposite);
+        }
+
+        assert_eq!(
+            a.triangle_phased(4.0, (0.0, 1.0), count, count),
+            a.triangle_phased(4.0, (0.0, 1.0), count, 0),
+            "index wraps at count"
+        );
+    }
+
+    #[test]
+    fn test_frozen_beats_hold_until_released() {
+        let timing = ManualTiming::new(Bpm::new(BPM));
+        let mut a = Animation::new(timing.clone());
+
+        timing.set_beats(1.5);
+        a.set_frozen(true);
+        timing.set_beats(3.0);
+        assert_eq!(a.beats(), 1.5);
+        assert_eq!(a.ramp(1.0), 0.5);
+
+        // Re-freezing while frozen keeps the original latch
+        a.set_frozen(true);
+        assert_eq!(a.beats(), 1.5);
+
+        a.set_frozen(false);
+        assert_eq!(a.beats(), 3.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_render_fps_override_keeps_beats_per_frame() {
+        init(2.0);
+        frame_clock::set_paused(true);
+        let a = create_instance();
+        let beats = a.beats();
+        let frames_per_beat = a.beats_to_frames(1.0);
+
+        frame_clock::set_render_fps(Some(FPS * 2.0));
+        assert_eq!(a.beats_to_frames(1.0), frames_per_beat);
+        assert_eq!(a.beats(), beats);
+
+        frame_clock::set_render_fps(Some(FPS / 2.0));
+        assert_eq!(a.beats_to_frames(1.0), frames_per_beat);
+
+        frame_clock::set_render_fps(None);
+        frame_clock::set_paused(false);
+    }
+
+    #[test]
+    #[serial]
+    fn test_render_fps_override_advances_beats_at_sketch_fps() {
+        init(0.0);
+        frame_clock::set_paused(true);
+        frame_clock::set_render_fps(Some(FPS * 2.0));
+        let a = create_instance();
+        let start = a.beats();
+
+        for _ in 0..FRAMES_PER_BEAT as u32 {
+            frame_clock::advance_single_frame();
+            frame_clock::tick(Instant::now());
+        }
+
+        assert!((a.beats() - start - 1.0).abs() < 0.000_1, "one beat");
+        assert!((a.ramp(4.0) - 0.25).abs() < 0.000_1, "1/4 ramp");
+
+        frame_clock::set_render_fps(None);
+        frame_clock::set_paused(false);
+    }
+
+    #[test]
+    fn test_loop_region_wraps_beats_from_out_to_in() {
+        let timing = ManualTiming::new(Bpm::new(BPM));
+        let mut a = Animation::new(timing.clone());
+        a.set_loop_region(Some((4.0, 8.0))).unwrap();
+
+        timing.set_beats(2.0);
+        assert_eq!(a.beats(), 2.0, "plays into the region");
+        timing.set_beats(7.5);
+        assert_eq!(a.beats(), 7.5);
+        timing.set_beats(8.0);
+        assert_eq!(a.beats(), 4.0, "out wraps to in");
+        timing.set_beats(13.5);
+        assert_eq!(a.beats(), 5.5);
+
+        assert!(a.set_loop_region(Some((8.0, 4.0))).is_err());
+        assert_eq!(a.loop_region(), Some((4.0, 8.0)));
+
+        a.set_loop_region(None).unwrap();
+        assert_eq!(a.beats(), 13.5);
+    }
+
     #[test]
     #[serial]
     fn test_ramp_plus() {
@@ -1273,6 +1624,82 @@ pub mod animation_tests {
         assert_eq!(r(), 0.0, "beat 6: wraps to index 0");
     }
 
+    #[test]
+    #[serial]
+    fn test_gate_follows_step_pattern() {
+        let a = create_instance();
+        let steps = [1.0, 0.0, 0.0, 1.0];
+        let g = || a.gate(4.0, &steps);
+
+        init(0.0);
+        assert!(g(), "beat 0: step 0");
+        init(0.75);
+        assert!(g(), "beat 0.75: still step 0");
+        init(1.0);
+        assert!(!g(), "beat 1: step 1");
+        init(2.5);
+        assert!(!g(), "beat 2.5: step 2");
+        init(3.0);
+        assert!(g(), "beat 3: step 3");
+        init(4.0);
+        assert!(g(), "beat 4: wraps to step 0");
+        init(5.0);
+        assert!(!g(), "beat 5: wraps to step 1");
+    }
+
+    #[test]
+    #[serial]
+    fn test_gate_subdivides_by_step_count() {
+        let a = create_instance();
+        // 8 steps over 2 beats: each step is a 16th note
+        let steps = [1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let expected = [true, false, true, true, false, false, false, true];
+
+        for (index, high) in expected.iter().enumerate() {
+            init(index as f32 * 0.25);
+            assert_eq!(a.gate(2.0, &steps), *high, "step {}", index);
+        }
+
+        init(0.0);
+        assert!(!a.gate(2.0, &[]), "no steps is always low");
+    }
+
+    #[test]
+    #[serial]
+    fn test_noise_is_smooth_and_in_range() {
+        let a = create_instance();
+        let mut previous = None;
+
+        for step in 0..400 {
+            let beats = step as f32 * 0.25;
+            init(beats);
+            let value = a.noise(4.0, (-5.0, 5.0), 3, 42);
+            assert!((-5.0..=5.0).contains(&value), "beats: {}", beats);
+            if let Some(previous) = previous {
+                let delta: f32 = value - previous;
+                assert!(delta.abs() < 1.5, "beats {}: jumped", beats);
+            }
+            previous = Some(value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_noise_seeds_do_not_collide() {
+        let a = create_instance();
+        init(1.5);
+
+        for (x, y) in [(7, 4_103), (7, 8), (100, 101)] {
+            assert_ne!(
+                a.noise(4.0, (0.0, 1.0), 3, x),
+                a.noise(4.0, (0.0, 1.0), 3, y),
+                "seeds {} and {}",
+                x,
+                y
+            );
+        }
+    }
+
     #[test]
     #[serial]
     fn test_round_robin_empty_values() {
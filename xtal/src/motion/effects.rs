@@ -4,6 +4,7 @@
 //! [animation]: crate::motion::animation
 
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::f32::consts::{FRAC_PI_2, PI};
 use std::str::FromStr;
 
@@ -12,17 +13,96 @@ use crate::ternary;
 
 #[derive(Debug)]
 pub enum Effect {
+    Comparator(Comparator),
     Constrain(Constrain),
+    Delay(Delay),
     Hysteresis(Hysteresis),
     Map(Map),
     Math(Math),
     Quantizer(Quantizer),
+    Reducer(Reducer),
     RingModulator(RingModulator),
     Saturator(Saturator),
     SlewLimiter(SlewLimiter),
     WaveFolder(WaveFolder),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+    Eq,
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gt" => Ok(Self::Gt),
+            "lt" => Ok(Self::Lt),
+            "gte" => Ok(Self::Gte),
+            "lte" => Ok(Self::Lte),
+            "eq" => Ok(Self::Eq),
+            _ => Err(format!("No comparison named {}", s)),
+        }
+    }
+}
+
+/// Compares its input to `threshold`, turning a continuous signal into a gate.
+/// Outputs 1.0 when the comparison holds and 0.0 otherwise, or with
+/// `pass_through` the input itself instead of 1.0.
+#[derive(Debug, Clone)]
+pub struct Comparator {
+    pub comparison: Comparison,
+    pub threshold: f32,
+
+    /// How close the input must be to `threshold` to count as equal. Also
+    /// applied to `gte` and `lte`.
+    pub epsilon: f32,
+
+    pub pass_through: bool,
+}
+
+impl Comparator {
+    pub fn new(comparison: Comparison, threshold: f32) -> Self {
+        Self {
+            comparison,
+            threshold,
+            ..Self::default()
+        }
+    }
+
+    pub fn apply(&self, input: f32) -> f32 {
+        let equal = (input - self.threshold).abs() <= self.epsilon;
+        let holds = match self.comparison {
+            Comparison::Gt => input > self.threshold && !equal,
+            Comparison::Lt => input < self.threshold && !equal,
+            Comparison::Gte => input > self.threshold || equal,
+            Comparison::Lte => input < self.threshold || equal,
+            Comparison::Eq => equal,
+        };
+        match (holds, self.pass_through) {
+            (true, true) => input,
+            (true, false) => 1.0,
+            (false, _) => 0.0,
+        }
+    }
+}
+
+impl Default for Comparator {
+    fn default() -> Self {
+        Self {
+            comparison: Comparison::Gt,
+            threshold: 0.5,
+            epsilon: 0.000_1,
+            pass_through: false,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Constrain {
     None,
@@ -58,6 +138,88 @@ impl TryFrom<(&str, f32, f32)> for Constrain {
     }
 }
 
+/// Outputs its input from `beats` ago, like a tap delay for control signals.
+/// The input is recorded alongside its frame number, so frames where the
+/// control isn't read or the clock jumps ahead leave a gap that is bridged by
+/// interpolating between its neighbours. History is kept for `max_beats` at
+/// the current tempo. Until enough history is recorded the oldest available
+/// value is output.
+#[derive(Debug, Clone)]
+pub struct Delay {
+    /// How far the output lags the input, in beats
+    pub beats: f32,
+
+    /// Longest delay the buffer keeps history for. Always at least `beats`
+    pub max_beats: f32,
+
+    samples: VecDeque<(u32, f32)>,
+}
+
+impl Delay {
+    pub fn new(beats: f32, max_beats: f32) -> Self {
+        Self {
+            beats,
+            max_beats,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record `value` as the input for `frame` (later calls within the same
+    /// frame only read) and return the input from `beats` ago. The read
+    /// position is derived from `frames_per_beat` on every call so delays stay
+    /// beat-synced through tempo changes.
+    pub fn apply(
+        &mut self,
+        value: f32,
+        frame: u32,
+        frames_per_beat: f32,
+    ) -> f32 {
+        match self.samples.back() {
+            Some(&(last, _)) if frame == last => {}
+            Some(&(last, _)) if frame < last => {
+                self.samples.clear();
+                self.samples.push_back((frame, value));
+            }
+            _ => self.samples.push_back((frame, value)),
+        }
+
+        // Keep one sample at or before the oldest frame still reachable so
+        // there is always something to interpolate from
+        let max_beats = self.max_beats.max(self.beats);
+        let oldest = frame as f32 - max_beats * frames_per_beat;
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|&(f, _)| f as f32 <= oldest)
+        {
+            self.samples.pop_front();
+        }
+
+        let target = frame as f32 - self.beats * frames_per_beat;
+        let next = self
+            .samples
+            .iter()
+            .position(|&(f, _)| f as f32 > target)
+            .unwrap_or(self.samples.len());
+
+        match (next.checked_sub(1), self.samples.get(next)) {
+            (Some(previous), Some(&(f1, b))) => {
+                let (f0, a) = self.samples[previous];
+                let t = (target - f0 as f32) / (f1 - f0) as f32;
+                a + (b - a) * t
+            }
+            (Some(previous), None) => self.samples[previous].1,
+            (None, _) => self.samples[0].1,
+        }
+    }
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self::new(1.0, 4.0)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 enum HysteresisState {
     High,
@@ -269,6 +431,37 @@ impl Default for Quantizer {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reduction {
+    Min,
+    Max,
+    Average,
+}
+
+/// Reduces a signal together with any number of other signals to a single
+/// value, e.g. the loudest of several audio bands.
+#[derive(Debug, Clone)]
+pub struct Reducer {
+    pub reduction: Reduction,
+}
+
+impl Reducer {
+    pub fn new(reduction: Reduction) -> Self {
+        Self { reduction }
+    }
+
+    pub fn apply(&self, value: f32, others: &[f32]) -> f32 {
+        let values = others.iter().copied();
+        match self.reduction {
+            Reduction::Min => values.fold(value, f32::min),
+            Reduction::Max => values.fold(value, f32::max),
+            Reduction::Average => {
+                (value + values.sum::<f32>()) / (others.len() + 1) as f32
+            }
+        }
+    }
+}
+
 /// Implements ring modulation by combining a carrier and modulator signal.
 #[derive(Debug, Clone)]
 pub struct RingModulator {
@@ -458,6 +651,60 @@ impl Default for SlewLimiter {
     }
 }
 
+/// Mass-spring-damper follower. Unlike [`SlewLimiter`] it carries momentum
+/// toward its target, so underdamped settings overshoot and wobble before
+/// settling.
+#[derive(Debug, Clone)]
+pub struct Spring {
+    /// Pull toward the target. Higher values are snappier.
+    /// Suggested range: 10.0 to 500.0
+    pub stiffness: f32,
+
+    /// Resistance to motion. `2 * sqrt(stiffness)` settles as fast as possible
+    /// without overshooting; anything lower bounces.
+    pub damping: f32,
+
+    position: f32,
+    velocity: f32,
+}
+
+impl Spring {
+    pub fn new(stiffness: f32, damping: f32) -> Self {
+        Self {
+            stiffness,
+            damping,
+            position: 0.0,
+            velocity: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.position
+    }
+
+    /// Jump to `value` at rest
+    pub fn reset(&mut self, value: f32) {
+        self.position = value;
+        self.velocity = 0.0;
+    }
+
+    /// Advance the simulation by `dt` seconds (semi-implicit Euler, unit
+    /// mass) and return the new position
+    pub fn step(&mut self, target: f32, dt: f32) -> f32 {
+        let force = self.stiffness * (target - self.position)
+            - self.damping * self.velocity;
+        self.velocity += force * dt;
+        self.position += self.velocity * dt;
+        self.position
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self::new(100.0, 10.0)
+    }
+}
+
 /// ⚠️ Experimental
 #[derive(Debug, Clone)]
 pub struct WaveFolder {
@@ -653,11 +900,125 @@ pub fn equal_power_crossfade(a: f32, b: f32, mix: f32) -> f32 {
 
 #[cfg(test)]
 mod tests {
+    use super::Comparator;
+    use super::Comparison;
+    use super::Delay;
     use super::Quantizer;
+    use super::Reducer;
+    use super::Reduction;
     use super::Saturator;
+    use super::Spring;
     use super::WaveFolder;
     use crate::assert_approx_eq;
 
+    #[test]
+    fn test_comparator_operators() {
+        // (comparison, [below, at, above])
+        let cases = [
+            (Comparison::Gt, [0.0, 0.0, 1.0]),
+            (Comparison::Lt, [1.0, 0.0, 0.0]),
+            (Comparison::Gte, [0.0, 1.0, 1.0]),
+            (Comparison::Lte, [1.0, 1.0, 0.0]),
+            (Comparison::Eq, [0.0, 1.0, 0.0]),
+        ];
+
+        for (comparison, expected) in cases {
+            let comparator = Comparator::new(comparison, 0.5);
+            for (input, expected) in [0.25, 0.5, 0.75].iter().zip(expected) {
+                assert_eq!(
+                    comparator.apply(*input),
+                    expected,
+                    "{:?} {}",
+                    comparison,
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparator_epsilon_and_pass_through() {
+        let mut comparator = Comparator::new(Comparison::Eq, 0.5);
+        assert_eq!(comparator.apply(0.500_01), 1.0);
+        comparator.epsilon = 0.1;
+        assert_eq!(comparator.apply(0.55), 1.0);
+
+        let mut comparator = Comparator::new(Comparison::Gt, 0.5);
+        comparator.pass_through = true;
+        assert_eq!(comparator.apply(0.8), 0.8);
+        assert_eq!(comparator.apply(0.2), 0.0);
+    }
+
+    #[test]
+    fn test_delay_warms_up_then_lags() {
+        let mut delay = Delay::new(1.0, 4.0);
+
+        // 4 frames per beat: the oldest sample is held until 4 frames exist
+        for frame in 0..4 {
+            assert_eq!(delay.apply(frame as f32, frame, 4.0), 0.0);
+        }
+        for frame in 4..12 {
+            assert_eq!(
+                delay.apply(frame as f32, frame, 4.0),
+                frame as f32 - 4.0
+            );
+        }
+
+        // Repeated reads within a frame don't record
+        assert_eq!(delay.apply(100.0, 11, 4.0), 7.0);
+    }
+
+    #[test]
+    fn test_delay_rescales_read_position_on_tempo_change() {
+        let mut delay = Delay::new(1.0, 4.0);
+        for frame in 0..16 {
+            delay.apply(frame as f32, frame, 4.0);
+        }
+
+        // Halving the tempo doubles the frames in a beat
+        assert_eq!(delay.apply(16.0, 16, 8.0), 8.0);
+        // A fractional frame delay interpolates
+        assert_eq!(delay.apply(17.0, 17, 2.5), 14.5);
+    }
+
+    #[test]
+    fn test_delay_interpolates_across_skipped_frames() {
+        let mut delay = Delay::new(1.0, 4.0);
+
+        // The input equals its frame, so the output should equal the frame
+        // from one beat (4 frames) ago wherever history exists
+        for frame in [0_u32, 1, 5, 6, 7, 15, 16, 20] {
+            let expected = frame.saturating_sub(4) as f32;
+            assert_eq!(
+                delay.apply(frame as f32, frame, 4.0),
+                expected,
+                "frame {}",
+                frame
+            );
+        }
+    }
+
+    #[test]
+    fn test_reducer() {
+        let others = [0.9, 0.1];
+        assert_eq!(Reducer::new(Reduction::Min).apply(0.5, &others), 0.1);
+        assert_eq!(Reducer::new(Reduction::Max).apply(0.5, &others), 0.9);
+        assert_approx_eq!(
+            Reducer::new(Reduction::Average).apply(0.5, &others),
+            0.5
+        );
+        assert_eq!(Reducer::new(Reduction::Max).apply(0.5, &[]), 0.5);
+    }
+
+    #[test]
+    fn test_spring_critically_damped_does_not_overshoot() {
+        let mut spring = Spring::new(100.0, 20.0);
+        for _ in 0..240 {
+            assert!(spring.step(1.0, 1.0 / 60.0) <= 1.0);
+        }
+        assert_approx_eq!(spring.value(), 1.0);
+    }
+
     #[test]
     fn test_wave_folder() {
         let wf = WaveFolder::default();
@@ -1,9 +1,14 @@
 pub mod animation;
 pub mod easing;
 pub mod effects;
+pub mod particles;
+pub mod rhythm;
+pub(crate) mod test_timing;
 pub mod timing;
 
 pub use animation::*;
 pub use easing::*;
 pub use effects::*;
+pub use particles::*;
+pub use rhythm::*;
 pub use timing::*;
@@ -0,0 +1,368 @@
+//! CPU particle system with a fixed capacity. Configure an emitter, an
+//! optional force and drag, call [`ParticleSystem::update`] once per frame
+//! from `Sketch::update` and draw the result through an [`Instances`] node:
+//!
+//! ```rust
+//! # use xtal::prelude::*;
+//! let noise = Noise::new(3).kind(NoiseKind::Simplex);
+//! let mut particles = ParticleSystem::new(2_000)
+//!     .rate(200.0)
+//!     .emitter(|index| {
+//!         let x = (index % 100) as f32 / 50.0 - 1.0;
+//!         Particle::new([x, -1.0], [0.0, 0.2]).lifetime(4.0)
+//!     })
+//!     .force(move |particle| noise.curl_noise_2d(particle.position))
+//!     .drag(0.5);
+//!
+//! particles.step(1.0 / 60.0);
+//! assert!(!particles.is_empty());
+//! ```
+
+use crate::instancing::{Instance, Instances};
+use crate::time::frame_clock;
+
+// Cap catch-up after a long stall so a burst of steps doesn't freeze a frame
+const MAX_CATCH_UP_FRAMES: u32 = 240;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub color: [f32; 4],
+    pub size: f32,
+    /// Seconds since the particle was emitted
+    pub age: f32,
+    /// Seconds the particle lives for
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// A white particle 0.01 across that lives for one second
+    pub fn new(position: [f32; 2], velocity: [f32; 2]) -> Self {
+        Self {
+            position,
+            velocity,
+            color: [1.0; 4],
+            size: 0.01,
+            age: 0.0,
+            lifetime: 1.0,
+        }
+    }
+
+    pub fn color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn lifetime(mut self, lifetime: f32) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// How far through its life the particle is, from 0 to 1. Handy for
+    /// fading color or size out.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime <= 0.0 {
+            return 1.0;
+        }
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+}
+
+type Emitter = Box<dyn FnMut(u64) -> Particle>;
+type Force = Box<dyn FnMut(&Particle) -> [f32; 2]>;
+
+pub struct ParticleSystem {
+    // Allocated once at `capacity` and never grown
+    particles: Vec<Particle>,
+    // Kept separately since `Vec::with_capacity` may allocate more
+    capacity: usize,
+    emitter: Emitter,
+    force: Option<Force>,
+    rate: f32,
+    drag: f32,
+    // Fractional particles owed by the emission rate
+    pending: f32,
+    emitted: u64,
+    last_frame: Option<u32>,
+}
+
+impl ParticleSystem {
+    /// An empty system holding at most `capacity` particles. Until an
+    /// [`emitter`](Self::emitter) is set, particles spawn at rest at the
+    /// origin.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+            emitter: Box::new(|_| Particle::new([0.0, 0.0], [0.0, 0.0])),
+            force: None,
+            rate: 0.0,
+            drag: 0.0,
+            pending: 0.0,
+            emitted: 0,
+            last_frame: None,
+        }
+    }
+
+    /// Particles emitted per second while stepping
+    pub fn rate(mut self, rate: f32) -> Self {
+        self.rate = rate.max(0.0);
+        self
+    }
+
+    /// Build each new particle from its emission index, which counts up
+    /// from 0 so seeded variation is reproducible
+    pub fn emitter(
+        mut self,
+        emitter: impl FnMut(u64) -> Particle + 'static,
+    ) -> Self {
+        self.emitter = Box::new(emitter);
+        self
+    }
+
+    /// Acceleration applied to every particle each step, e.g. gravity or
+    /// [`Noise::curl_noise_2d`](crate::core::noise::Noise::curl_noise_2d) advection
+    pub fn force(
+        mut self,
+        force: impl FnMut(&Particle) -> [f32; 2] + 'static,
+    ) -> Self {
+        self.force = Some(Box::new(force));
+        self
+    }
+
+    /// Fraction of velocity lost per second
+    pub fn drag(mut self, drag: f32) -> Self {
+        self.drag = drag.max(0.0);
+        self
+    }
+
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate.max(0.0);
+    }
+
+    /// Add a particle if there is room, returning whether it was added
+    pub fn emit(&mut self, particle: Particle) -> bool {
+        if self.particles.len() == self.capacity {
+            return false;
+        }
+        self.particles.push(particle);
+        true
+    }
+
+    /// Emit up to `count` particles from the emitter at once, returning how
+    /// many fit
+    pub fn burst(&mut self, count: usize) -> usize {
+        let room = self.capacity - self.particles.len();
+        let count = count.min(room);
+        for _ in 0..count {
+            let particle = (self.emitter)(self.emitted);
+            self.emitted += 1;
+            self.particles.push(particle);
+        }
+        count
+    }
+
+    /// Advance by however many frames the frame clock moved since the last
+    /// call, one [`Self::step`] per frame. Pausing freezes the system, and
+    /// a clock that jumps backwards just resynchronizes.
+    pub fn update(&mut self) {
+        let frame = frame_clock::frame_count();
+        let last_frame = self.last_frame.replace(frame);
+        let Some(last_frame) = last_frame else {
+            return;
+        };
+        if frame <= last_frame {
+            return;
+        }
+
+        let dt = 1.0 / frame_clock::render_fps();
+        for _ in 0..(frame - last_frame).min(MAX_CATCH_UP_FRAMES) {
+            self.step(dt);
+        }
+    }
+
+    /// Advance by `dt` seconds: age and move every particle, remove those
+    /// past their lifetime, then emit at [`Self::rate`]
+    pub fn step(&mut self, dt: f32) {
+        let damping = (1.0 - self.drag * dt).max(0.0);
+        let force = &mut self.force;
+        self.particles.retain_mut(|particle| {
+            particle.age += dt;
+            if !particle.is_alive() {
+                return false;
+            }
+            if let Some(force) = force.as_mut() {
+                let [ax, ay] = force(particle);
+                particle.velocity[0] += ax * dt;
+                particle.velocity[1] += ay * dt;
+            }
+            particle.velocity[0] *= damping;
+            particle.velocity[1] *= damping;
+            particle.position[0] += particle.velocity[0] * dt;
+            particle.position[1] += particle.velocity[1] * dt;
+            true
+        });
+
+        self.pending += self.rate * dt;
+        let due = self.pending.floor();
+        self.pending -= due;
+        self.burst(due as usize);
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn particles_mut(&mut self) -> &mut [Particle] {
+        &mut self.particles
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Remove every particle, keeping the allocation
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    /// Replace `instances` with one [`Instance::point`] per particle, for a
+    /// node added with `GraphBuilder::add_instanced`
+    pub fn write_instances(&self, instances: &Instances) {
+        instances.set(self.particles.iter().map(|particle| {
+            Instance::point(particle.position, particle.size, particle.color)
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    fn at_rest(index: u64) -> Particle {
+        Particle::new([index as f32, 0.0], [0.0, 0.0]).lifetime(1.0)
+    }
+
+    #[test]
+    fn emits_at_the_configured_rate() {
+        let mut system = ParticleSystem::new(100).rate(10.0).emitter(at_rest);
+
+        system.step(0.25);
+        assert_eq!(system.len(), 2);
+
+        // The leftover half particle carries over
+        system.step(0.25);
+        assert_eq!(system.len(), 5);
+
+        let positions: Vec<f32> =
+            system.particles().iter().map(|p| p.position[0]).collect();
+        assert_eq!(positions, [0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn particles_age_and_die_after_their_lifetime() {
+        let mut system = ParticleSystem::new(10).emitter(at_rest);
+        system.burst(3);
+
+        for _ in 0..3 {
+            system.step(0.3);
+        }
+        assert_eq!(system.len(), 3);
+        assert_close(system.particles()[0].age, 0.9, "age");
+        assert_close(system.particles()[0].progress(), 0.9, "progress");
+
+        system.step(0.3);
+        assert!(system.is_empty());
+    }
+
+    #[test]
+    fn capacity_is_fixed() {
+        let mut system = ParticleSystem::new(4).rate(100.0).emitter(at_rest);
+
+        system.step(1.0);
+        assert_eq!(system.len(), 4);
+        assert_eq!(system.capacity(), 4);
+        assert!(!system.emit(at_rest(99)));
+        assert_eq!(system.burst(10), 0);
+
+        // Dead particles free their slots for new ones
+        system.step(1.0);
+        assert_eq!(system.len(), 4);
+        assert!(system.particles().iter().all(|p| p.age == 0.0));
+    }
+
+    #[test]
+    fn force_and_drag_move_particles() {
+        let mut system = ParticleSystem::new(1)
+            .emitter(|_| Particle::new([0.0, 0.0], [1.0, 0.0]).lifetime(10.0))
+            .force(|_| [0.0, -2.0]);
+        system.burst(1);
+
+        system.step(0.5);
+        let particle = system.particles()[0];
+        assert_eq!(particle.velocity, [1.0, -1.0]);
+        assert_eq!(particle.position, [0.5, -0.5]);
+
+        let mut system = ParticleSystem::new(1).drag(1.0);
+        system.emit(Particle::new([0.0, 0.0], [2.0, 0.0]));
+        system.step(0.5);
+        assert_eq!(system.particles()[0].velocity, [1.0, 0.0]);
+    }
+
+    #[test]
+    #[serial]
+    fn update_steps_once_per_elapsed_frame() {
+        frame_clock::set_fps(10.0);
+        frame_clock::set_render_fps(None);
+        frame_clock::set_frame_count(100);
+
+        let mut system = ParticleSystem::new(100).rate(10.0).emitter(at_rest);
+        system.update();
+        assert!(system.is_empty());
+
+        frame_clock::set_frame_count(103);
+        system.update();
+        assert_eq!(system.len(), 3);
+        assert_close(system.particles()[0].age, 0.2, "age");
+
+        // Paused or rewound clocks don't step
+        system.update();
+        frame_clock::set_frame_count(50);
+        system.update();
+        assert_eq!(system.len(), 3);
+    }
+
+    #[test]
+    fn writes_one_instance_per_particle() {
+        let mut system = ParticleSystem::new(8).emitter(|index| {
+            at_rest(index).size(0.5).color([1.0, 0.0, 0.0, 1.0])
+        });
+        system.burst(3);
+
+        let instances = Instances::new();
+        system.write_instances(&instances);
+        assert_eq!(instances.len(), 3);
+    }
+}
@@ -0,0 +1,75 @@
+//! Rhythm pattern generators for use with [`Animation::pattern_gate`]
+//!
+//! [`Animation::pattern_gate`]: crate::motion::Animation::pattern_gate
+
+/// Spread `pulses` as evenly as possible over `steps` using the Bjorklund
+/// algorithm, then rotate the pattern `rotation` steps later. `true` marks a
+/// pulse, so `euclidean_rhythm(3, 8, 0)` is `x..x..x.`. Pulses beyond `steps`
+/// are clamped so every step fires.
+pub fn euclidean_rhythm(
+    pulses: usize,
+    steps: usize,
+    rotation: usize,
+) -> Vec<bool> {
+    if steps == 0 {
+        return vec![];
+    }
+    let pulses = pulses.min(steps);
+
+    // Repeatedly pair the remainder groups onto the leading groups until at
+    // most one remainder group is left; concatenating them is the pattern
+    let mut groups: Vec<Vec<bool>> = vec![vec![true]; pulses];
+    let mut remainders: Vec<Vec<bool>> = vec![vec![false]; steps - pulses];
+
+    while remainders.len() > 1 && !groups.is_empty() {
+        let paired = groups.len().min(remainders.len());
+        let leftover = if groups.len() > paired {
+            groups.split_off(paired)
+        } else {
+            remainders.split_off(paired)
+        };
+        for (group, remainder) in groups.iter_mut().zip(remainders) {
+            group.extend(remainder);
+        }
+        remainders = leftover;
+    }
+
+    let mut pattern: Vec<bool> =
+        groups.into_iter().chain(remainders).flatten().collect();
+    pattern.rotate_right(rotation % steps);
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notation(pattern: &[bool]) -> String {
+        pattern
+            .iter()
+            .map(|&on| if on { 'x' } else { '.' })
+            .collect()
+    }
+
+    #[test]
+    fn test_euclidean_canonical_patterns() {
+        assert_eq!(notation(&euclidean_rhythm(3, 8, 0)), "x..x..x.");
+        assert_eq!(notation(&euclidean_rhythm(5, 8, 0)), "x.xx.xx.");
+        assert_eq!(notation(&euclidean_rhythm(2, 5, 0)), "x.x..");
+        assert_eq!(notation(&euclidean_rhythm(4, 16, 0)), "x...x...x...x...");
+    }
+
+    #[test]
+    fn test_euclidean_rotation() {
+        assert_eq!(notation(&euclidean_rhythm(3, 8, 1)), ".x..x..x");
+        assert_eq!(notation(&euclidean_rhythm(3, 8, 9)), ".x..x..x");
+    }
+
+    #[test]
+    fn test_euclidean_edge_cases() {
+        assert_eq!(notation(&euclidean_rhythm(0, 4, 0)), "....");
+        assert_eq!(notation(&euclidean_rhythm(4, 4, 0)), "xxxx");
+        assert_eq!(notation(&euclidean_rhythm(6, 4, 0)), "xxxx");
+        assert!(euclidean_rhythm(3, 0, 0).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use super::timing::{Bpm, TimingSource};
+use crate::core::util::AtomicF32;
+use crate::time::frame_clock;
+
+/// Timing for tests: instead of waiting on real frames it jumps straight to a
+/// beat or frame, so animation and hub values can be asserted
+/// deterministically. The position is exact rather than read back from the
+/// wall clock, and the global frame clock is paused at the matching time and
+/// frame count for code that reads it directly. Because the frame clock is
+/// process-wide, tests using this should not run in parallel (e.g. mark them
+/// `#[serial_test::serial]`).
+///
+/// # Example
+/// ```rust
+/// use xtal::prelude::*;
+/// use xtal::prelude::test::*;
+///
+/// let animation = Animation::new(TestTiming::new(120.0, 60.0));
+/// animation.advance_to_beat(0.5);
+/// assert_eq!(animation.tri(1.0), 1.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TestTiming {
+    bpm: Bpm,
+    fps: f32,
+    beats: Arc<AtomicF32>,
+}
+
+impl TestTiming {
+    pub fn new(bpm: f32, fps: f32) -> Self {
+        Self {
+            bpm: Bpm::new(bpm),
+            fps: fps.max(1.0),
+            beats: Arc::new(AtomicF32::new(0.0)),
+        }
+    }
+
+    pub fn frames_per_beat(&self) -> f32 {
+        60.0 / self.bpm.get() * self.fps
+    }
+
+    /// Move to `beat`. The frame count becomes the frame that beat falls in,
+    /// as it would be at runtime.
+    pub fn advance_to_beat(&self, beat: f32) {
+        let beat = beat.max(0.0);
+        self.set_position(beat, (beat * self.frames_per_beat()) as u32);
+    }
+
+    /// Move to the start of `frame`
+    pub fn advance_to_frame(&self, frame: u32) {
+        self.set_position(frame as f32 / self.frames_per_beat(), frame);
+    }
+
+    fn set_position(&self, beat: f32, frame: u32) {
+        self.beats.store(beat, Ordering::Release);
+        frame_clock::set_fps(self.fps);
+        frame_clock::set_render_fps(None);
+        frame_clock::set_paused(true);
+        frame_clock::set_elapsed_seconds(beat * 60.0 / self.bpm.get());
+        frame_clock::set_frame_count(frame);
+    }
+}
+
+impl TimingSource for TestTiming {
+    fn beats(&self) -> f32 {
+        self.beats.load(Ordering::Acquire)
+    }
+
+    fn bpm(&self) -> f32 {
+        self.bpm.get()
+    }
+}
@@ -1,23 +1,50 @@
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 use crate::core::util::AtomicF32;
 use crate::time::frame_clock;
 
 #[derive(Clone, Debug)]
-pub struct Bpm(Arc<AtomicF32>);
+pub struct Bpm {
+    bpm: Arc<AtomicF32>,
+    // Tempo of an external clock being followed, 0.0 when there is none
+    external: Arc<AtomicF32>,
+}
 
 impl Bpm {
     pub fn new(bpm: f32) -> Self {
-        Self(Arc::new(AtomicF32::new(bpm.max(1.0))))
+        Self {
+            bpm: Arc::new(AtomicF32::new(bpm.max(1.0))),
+            external: Arc::new(AtomicF32::new(0.0)),
+        }
     }
 
+    /// The tempo in effect: an external clock's while one is followed,
+    /// otherwise the one last [`set`](Self::set)
     pub fn get(&self) -> f32 {
-        self.0.load(Ordering::Relaxed)
+        let external = self.external.load(Ordering::Relaxed);
+        if external > 0.0 {
+            external
+        } else {
+            self.base()
+        }
+    }
+
+    /// The tempo last [`set`](Self::set), ignoring any external clock
+    pub fn base(&self) -> f32 {
+        self.bpm.load(Ordering::Relaxed)
     }
 
     pub fn set(&self, bpm: f32) {
-        self.0.store(bpm.max(1.0), Ordering::Release);
+        self.bpm.store(bpm.max(1.0), Ordering::Release);
+    }
+
+    /// Follow an external clock's tempo without losing the one last
+    /// [`set`](Self::set), which `None` goes back to
+    pub fn set_external(&self, bpm: Option<f32>) {
+        let bpm = bpm.map_or(0.0, |bpm| bpm.max(1.0));
+        self.external.store(bpm, Ordering::Release);
     }
 }
 
@@ -139,6 +166,97 @@ impl TimingSource for OscTransportTiming {
     }
 }
 
+// Forward jumps bigger than this are transport relocations (loop, seek)
+// rather than jitter and are followed immediately
+const RELOCATION_BEATS: f32 = 1.0;
+
+// Steps backward bigger than this are rewinds rather than jitter and are
+// followed immediately
+const REWIND_BEATS: f32 = 0.25;
+
+/// Smoothing for externally reported beat positions, such as OSC transport
+/// from a jittery sender. Rather than filtering the position itself, which
+/// lags further behind the faster the transport runs, it tracks the rate the
+/// transport advances at and eases out the error between where that rate
+/// predicts the transport to be and where it reports being. Jitter never runs
+/// the output backward, while relocations and rewinds are followed
+/// immediately.
+#[derive(Clone, Debug, Default)]
+pub struct BeatSmoother {
+    /// 0.0 passes beats through untouched (for tick-accurate use); values
+    /// toward [`Self::MAX_SMOOTHING`] smooth more at the cost of settling
+    /// more slowly after a tempo change
+    pub smoothing: f32,
+    state: Option<SmootherState>,
+}
+
+#[derive(Clone, Debug)]
+struct SmootherState {
+    position: f32,
+    reported: f32,
+    beats_per_second: Option<f32>,
+    at: Instant,
+}
+
+impl BeatSmoother {
+    pub const MAX_SMOOTHING: f32 = 0.95;
+
+    pub fn new(smoothing: f32) -> Self {
+        Self {
+            smoothing,
+            state: None,
+        }
+    }
+
+    /// Forget the tracked position and tempo, e.g. when the transport stops
+    /// or the timing source changes, so the next report is taken as is
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+
+    pub fn smooth(&mut self, beats: f32, now: Instant) -> f32 {
+        let smoothing = self.smoothing.clamp(0.0, Self::MAX_SMOOTHING);
+        let Some(state) = self.state.as_mut().filter(|_| smoothing > 0.0)
+        else {
+            return self.relocate(beats, now);
+        };
+
+        let elapsed = now.saturating_duration_since(state.at).as_secs_f32();
+        let step = beats - state.reported;
+        if elapsed <= 0.0
+            || !(-REWIND_BEATS..=RELOCATION_BEATS).contains(&step)
+            || (beats - state.position).abs() > RELOCATION_BEATS
+        {
+            return self.relocate(beats, now);
+        }
+
+        // An alpha-beta filter: the position gain follows the smoothing and
+        // the rate gain is derived from it for a well damped response
+        let alpha = 1.0 - smoothing;
+        let beta = alpha * alpha / (2.0 - alpha);
+
+        let rate = state.beats_per_second.unwrap_or(step / elapsed);
+        let predicted = state.position + rate * elapsed;
+        let error = beats - predicted;
+
+        state.position = (predicted + alpha * error).max(state.position);
+        state.beats_per_second = Some((rate + beta * error / elapsed).max(0.0));
+        state.reported = beats;
+        state.at = now;
+        state.position
+    }
+
+    fn relocate(&mut self, beats: f32, now: Instant) -> f32 {
+        self.state = Some(SmootherState {
+            position: beats,
+            reported: beats,
+            beats_per_second: None,
+            at: now,
+        });
+        beats
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct MidiSongTiming {
     bpm: Bpm,
@@ -225,3 +343,103 @@ impl TimingSource for ManualTiming {
         self.bpm.get()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // Beats advancing 0.1 per frame with up to ±0.06 of jitter, enough to
+    // step backward now and then
+    fn noisy_beats() -> Vec<f32> {
+        let jitter = [0.0, 0.06, -0.05, 0.03, -0.06, 0.05, -0.02, 0.04];
+        (0..64)
+            .map(|i| i as f32 * 0.1 + jitter[i % jitter.len()])
+            .collect()
+    }
+
+    fn max_step_deviation(beats: &[f32]) -> f32 {
+        beats
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0] - 0.1).abs())
+            .fold(0.0, f32::max)
+    }
+
+    const FRAME: Duration = Duration::from_micros(16_667);
+
+    fn smooth_all(smoother: &mut BeatSmoother, beats: &[f32]) -> Vec<f32> {
+        let start = Instant::now();
+        beats
+            .iter()
+            .enumerate()
+            .map(|(i, beats)| smoother.smooth(*beats, start + FRAME * i as u32))
+            .collect()
+    }
+
+    #[test]
+    fn test_external_bpm_keeps_the_set_bpm() {
+        let bpm = Bpm::new(120.0);
+        let timing = Timing::frame(bpm.clone());
+
+        bpm.set_external(Some(97.5));
+        assert_eq!(timing.bpm(), 97.5);
+        assert_eq!(bpm.base(), 120.0);
+
+        bpm.set(90.0);
+        assert_eq!(timing.bpm(), 97.5);
+
+        bpm.set_external(None);
+        assert_eq!(timing.bpm(), 90.0);
+    }
+
+    #[test]
+    fn test_beat_smoother_is_smooth_and_monotonic() {
+        let raw = noisy_beats();
+        let mut smoother = BeatSmoother::new(0.6);
+        let smoothed = smooth_all(&mut smoother, &raw);
+
+        assert!(raw.windows(2).any(|pair| pair[1] < pair[0]));
+        assert!(smoothed.windows(2).all(|pair| pair[1] >= pair[0]));
+
+        // Skip the warm-up while the filter catches up to the tempo
+        let settled = &smoothed[16..];
+        assert!(max_step_deviation(settled) < max_step_deviation(&raw) / 2.0);
+        assert!((smoothed[63] - raw[63]).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_beat_smoother_does_not_lag_a_steady_transport() {
+        let raw: Vec<f32> = (0..240).map(|i| i as f32 * 0.1).collect();
+        let mut smoother = BeatSmoother::new(0.9);
+        let smoothed = smooth_all(&mut smoother, &raw);
+
+        assert!((smoothed[239] - raw[239]).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_beat_smoother_disabled_passes_through() {
+        let mut smoother = BeatSmoother::default();
+        let raw = noisy_beats();
+        assert_eq!(smooth_all(&mut smoother, &raw), raw);
+    }
+
+    #[test]
+    fn test_beat_smoother_follows_relocations() {
+        let mut smoother = BeatSmoother::new(0.9);
+        let start = Instant::now();
+        smoother.smooth(32.0, start);
+        assert_eq!(smoother.smooth(0.0, start + FRAME), 0.0, "stop");
+        assert_eq!(smoother.smooth(16.0, start + FRAME * 2), 16.0, "seek");
+        assert_eq!(smoother.smooth(15.5, start + FRAME * 3), 15.5, "rewind");
+    }
+
+    #[test]
+    fn test_beat_smoother_reset_takes_next_report_as_is() {
+        let mut smoother = BeatSmoother::new(0.9);
+        smooth_all(&mut smoother, &noisy_beats());
+
+        smoother.reset();
+        assert_eq!(smoother.smooth(6.35, Instant::now()), 6.35);
+    }
+}
@@ -1,13 +1,45 @@
+//! Everything a sketch needs in one import: `use xtal::prelude::*;`
+//!
+//! # Testing control scripts
+//!
+//! Control scripts can be unit-tested without a window or GPU. Build a hub
+//! with [`test::TestHubBuilder`], then move the transport with
+//! [`ControlHub::advance_to_beat`] or [`ControlHub::advance_to_frame`] and
+//! assert on [`ControlHub::get`]. Standalone animations work the same way
+//! with [`Animation::new`]`(`[`test::TestTiming`]`)`. The frame clock is
+//! global, so mark these tests `#[serial_test::serial]` (or otherwise run them
+//! one at a time). [`test`] gathers these along with assertion helpers such as
+//! [`test::assert_close`].
+//!
+//! ```rust
+//! use xtal::prelude::*;
+//! use xtal::prelude::test::*;
+//!
+//! let mut hub = TestHubBuilder::new()
+//!     .script("lfo:\n  type: triangle\n  beats: 4\n")
+//!     .build();
+//!
+//! for (beat, expected) in [(0.0, 0.0), (1.0, 0.5), (2.0, 1.0)] {
+//!     hub.advance_to_beat(beat);
+//!     assert_close(hub.get("lfo"), expected, "lfo");
+//! }
+//! ```
+
+pub use crate::camera::Camera2D;
 pub use crate::context::Context;
 pub use crate::control::*;
 pub use crate::core::logging::init_logger;
 pub use crate::core::logging::{debug, error, info, trace, warn};
+pub use crate::core::noise::{Noise, NoiseKind};
+pub use crate::core::palette::Palette;
 pub use crate::debug_once;
 pub use crate::debug_throttled;
 pub use crate::frame::Frame;
 pub use crate::graph::*;
+pub use crate::instancing::{Instance, Instances};
 pub use crate::mesh::*;
 pub use crate::motion::*;
+pub use crate::polyline::{LineJoin, StrokeOptions};
 pub use crate::register_sketches;
 pub use crate::run_registry;
 pub use crate::runtime::events::{
@@ -18,5 +50,15 @@ pub use crate::runtime::registry::{RuntimeRegistry, SketchCategory};
 pub use crate::runtime::web_view;
 pub use crate::sketch::*;
 pub use crate::sketch_assets::SketchAssets;
+pub use crate::text::{Text, TextAlign};
 pub use crate::uniforms::UniformBanks;
 pub use crate::warn_once;
+pub use winit::keyboard::{KeyCode, ModifiersState};
+
+/// Testing utilities, kept out of the main prelude:
+/// `use xtal::prelude::test::*;`
+pub mod test {
+    pub use crate::control::test_hub::TestHubBuilder;
+    pub use crate::core::testing::assert_close;
+    pub use crate::motion::test_timing::TestTiming;
+}
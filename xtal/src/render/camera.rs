@@ -0,0 +1,206 @@
+//! Pan, zoom and rotation for 2D sketches. The runtime publishes the
+//! [`Context::camera`] to shaders as a world-to-clip `mat4x4f` at
+//! `@group(0) @binding(2)`:
+//!
+//! ```wgsl
+//! @group(0) @binding(2)
+//! var<uniform> camera: mat4x4f;
+//!
+//! out.position = camera * vec4f(world_position, 0.0, 1.0);
+//! ```
+//!
+//! World space is y-up. At zoom 1 the window spans -1 to 1 vertically and
+//! horizontally as far as its aspect ratio reaches, so world units stay
+//! square. With `SketchConfig::camera_controls`, dragging with the left
+//! button pans and the wheel zooms around the cursor.
+//!
+//! [`Context::camera`]: crate::context::Context::camera
+
+/// Wheel zoom per line scrolled
+pub const ZOOM_STEP: f32 = 1.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera2D {
+    /// World point at the center of the window
+    pub pan: [f32; 2],
+    /// Scale of the world on screen, > 0
+    pub zoom: f32,
+    /// Counterclockwise rotation of the camera in radians, turning the world
+    /// clockwise on screen
+    pub rotation: f32,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self {
+            pan: [0.0, 0.0],
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl Camera2D {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pan(mut self, pan: [f32; 2]) -> Self {
+        self.pan = pan;
+        self
+    }
+
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Column-major world-to-clip matrix for a target `aspect` (width over
+    /// height), as uploaded to the camera uniform
+    pub fn view_projection(&self, aspect: f32) -> [[f32; 4]; 4] {
+        let [[xx, xy], [yx, yy]] = self.linear(aspect);
+        let [pan_x, pan_y] = self.pan;
+        [
+            [xx, yx, 0.0, 0.0],
+            [xy, yy, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [
+                -(xx * pan_x + xy * pan_y),
+                -(yx * pan_x + yy * pan_y),
+                0.0,
+                1.0,
+            ],
+        ]
+    }
+
+    pub fn world_to_clip(&self, point: [f32; 2], aspect: f32) -> [f32; 2] {
+        let [[xx, xy], [yx, yy]] = self.linear(aspect);
+        let [x, y] = [point[0] - self.pan[0], point[1] - self.pan[1]];
+        [xx * x + xy * y, yx * x + yy * y]
+    }
+
+    pub fn clip_to_world(&self, point: [f32; 2], aspect: f32) -> [f32; 2] {
+        let [x, y] = self.clip_offset_to_world(point, aspect);
+        [self.pan[0] + x, self.pan[1] + y]
+    }
+
+    /// Move the view so the world follows a cursor dragged by `delta` in
+    /// clip space
+    pub fn drag(&mut self, delta: [f32; 2], aspect: f32) {
+        let [x, y] = self.clip_offset_to_world(delta, aspect);
+        self.pan = [self.pan[0] - x, self.pan[1] - y];
+    }
+
+    /// Multiply the zoom by `factor`, keeping the world point under the
+    /// clip-space `anchor` in place
+    pub fn zoom_at(&mut self, anchor: [f32; 2], factor: f32, aspect: f32) {
+        let before = self.clip_to_world(anchor, aspect);
+        self.zoom *= factor;
+        let after = self.clip_to_world(anchor, aspect);
+        self.pan = [
+            self.pan[0] + before[0] - after[0],
+            self.pan[1] + before[1] - after[1],
+        ];
+    }
+
+    // Row-major rotation, zoom and aspect correction, without the pan
+    fn linear(&self, aspect: f32) -> [[f32; 2]; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x_scale = self.zoom / aspect.max(f32::EPSILON);
+        [
+            [x_scale * cos, x_scale * sin],
+            [-self.zoom * sin, self.zoom * cos],
+        ]
+    }
+
+    fn clip_offset_to_world(&self, offset: [f32; 2], aspect: f32) -> [f32; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let zoom = self.zoom.max(f32::EPSILON);
+        let x = offset[0] * aspect / zoom;
+        let y = offset[1] / zoom;
+        [cos * x - sin * y, sin * x + cos * y]
+    }
+}
+
+/// Map a normalized `[0, 1]` bottom-left position, e.g.
+/// [`Context::mouse_position`], to clip space
+///
+/// [`Context::mouse_position`]: crate::context::Context::mouse_position
+pub fn uv_to_clip(uv: [f32; 2]) -> [f32; 2] {
+    [uv[0] * 2.0 - 1.0, uv[1] * 2.0 - 1.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    fn transform(matrix: [[f32; 4]; 4], point: [f32; 2]) -> [f32; 2] {
+        [
+            matrix[0][0] * point[0] + matrix[1][0] * point[1] + matrix[3][0],
+            matrix[0][1] * point[0] + matrix[1][1] * point[1] + matrix[3][1],
+        ]
+    }
+
+    fn assert_point(actual: [f32; 2], expected: [f32; 2]) {
+        assert_close(actual[0], expected[0], "x");
+        assert_close(actual[1], expected[1], "y");
+    }
+
+    #[test]
+    fn pan_and_zoom_map_world_to_clip() {
+        let camera = Camera2D::new().pan([1.0, 2.0]).zoom(2.0);
+        let matrix = camera.view_projection(2.0);
+
+        // 0.5 right and up of the pan, doubled, with x halved for a 2:1
+        // window
+        assert_point(transform(matrix, [1.5, 2.5]), [0.5, 1.0]);
+        assert_point(transform(matrix, [1.0, 2.0]), [0.0, 0.0]);
+        assert_point(camera.world_to_clip([1.5, 2.5], 2.0), [0.5, 1.0]);
+    }
+
+    #[test]
+    fn rotation_turns_the_world_the_other_way() {
+        let camera = Camera2D::new().rotation(FRAC_PI_2);
+        let matrix = camera.view_projection(1.0);
+        assert_point(transform(matrix, [0.0, 0.5]), [0.5, 0.0]);
+    }
+
+    #[test]
+    fn clip_to_world_inverts_world_to_clip() {
+        let camera = Camera2D::new().pan([-3.0, 0.5]).zoom(0.25).rotation(1.0);
+        let world = [2.0, -1.0];
+        let clip = camera.world_to_clip(world, 1.5);
+        assert_point(camera.clip_to_world(clip, 1.5), world);
+    }
+
+    #[test]
+    fn zoom_at_keeps_the_anchor_fixed() {
+        let mut camera = Camera2D::new().pan([1.0, 1.0]);
+        let anchor = [0.5, -0.25];
+        let before = camera.clip_to_world(anchor, 1.0);
+
+        camera.zoom_at(anchor, 4.0, 1.0);
+
+        assert_close(camera.zoom, 4.0, "zoom");
+        assert_point(camera.clip_to_world(anchor, 1.0), before);
+    }
+
+    #[test]
+    fn drag_moves_the_world_with_the_cursor() {
+        let mut camera = Camera2D::new().zoom(2.0);
+        let grabbed = camera.clip_to_world([0.0, 0.0], 1.0);
+
+        camera.drag([0.5, 0.0], 1.0);
+
+        assert_point(camera.world_to_clip(grabbed, 1.0), [0.5, 0.0]);
+        assert_point(camera.pan, [-0.25, 0.0]);
+    }
+}
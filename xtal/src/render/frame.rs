@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use crate::text::Text;
+
 pub struct Frame {
     pub surface_view: wgpu::TextureView,
     encoder: Option<wgpu::CommandEncoder>,
     output: Option<wgpu::SurfaceTexture>,
     queue: Arc<wgpu::Queue>,
+    text: Vec<Text>,
 }
 
 impl Frame {
@@ -27,6 +30,28 @@ impl Frame {
             encoder: Some(encoder),
             output: Some(output),
             queue,
+            text: Vec::new(),
+        }
+    }
+
+    /// A frame that renders into `view` instead of a surface texture and
+    /// presents nothing on submit. Useful for headless rendering and tests.
+    pub fn offscreen(
+        device: &wgpu::Device,
+        queue: Arc<wgpu::Queue>,
+        view: wgpu::TextureView,
+    ) -> Self {
+        let encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("xtal-frame-encoder"),
+            });
+
+        Self {
+            surface_view: view,
+            encoder: Some(encoder),
+            output: None,
+            queue,
+            text: Vec::new(),
         }
     }
 
@@ -36,6 +61,21 @@ impl Frame {
             .expect("frame command encoder already submitted")
     }
 
+    /// Queue `text` to be drawn over the frame after the graph has rendered,
+    /// e.g. from `Sketch::view`. See [`crate::text`].
+    pub fn draw_text(&mut self, text: Text) {
+        self.text.push(text);
+    }
+
+    pub fn has_text(&self) -> bool {
+        !self.text.is_empty()
+    }
+
+    /// Text queued with [`Self::draw_text`], leaving none queued
+    pub fn take_text(&mut self) -> Vec<Text> {
+        std::mem::take(&mut self.text)
+    }
+
     pub fn output_texture(&self) -> &wgpu::Texture {
         &self
             .output
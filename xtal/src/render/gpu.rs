@@ -1,23 +1,36 @@
 use std::collections::{HashMap, HashSet};
+use std::error::Error as _;
+use std::fmt;
 use std::fs;
-use std::io::Cursor;
 use std::path::{Path, PathBuf};
 
 use log::{error, info, warn};
 use naga::front::wgsl;
 use naga::valid::{Capabilities, ValidationFlags, Validator};
+use serde::{Deserialize, Serialize};
 use wgpu::util::DeviceExt;
 
 use crate::frame::Frame;
 use crate::graph::{
-    ComputeNodeSpec, GraphSpec, NodeSpec, RenderNodeSpec, RenderRead,
-    RenderTarget, ResourceDecl, ResourceHandle, ResourceKind, TextureHandle,
+    BufferHandle, ComputeBinding, ComputeNodeSpec, DepthOptions, GraphSpec,
+    InstanceCount, NodeSpec, RenderNodeSpec, RenderRead, RenderTarget,
+    ResourceDecl, ResourceHandle, ResourceKind, TextureHandle, Workgroups,
 };
+use crate::image::{self, DecodedImage};
+use crate::instancing::{Instance, instance_buffer_layout};
 use crate::mesh::{Mesh, MeshVertexKind};
+#[cfg(feature = "ndi")]
+use crate::ndi_input::NdiInput;
+#[cfg(feature = "timing")]
+use crate::pass_timing::{PassTimer, PassTimings};
 use crate::shader_watch::ShaderWatch;
 use crate::uniforms::UniformBanks;
+#[cfg(feature = "video")]
+use crate::video::VideoTexture;
 
 const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const HDR_OFFSCREEN_FORMAT: wgpu::TextureFormat =
+    wgpu::TextureFormat::Rgba16Float;
 const IMAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
 
 pub fn compute_row_padding(unpadded_bytes_per_row: u32) -> u32 {
@@ -26,14 +39,178 @@ pub fn compute_row_padding(unpadded_bytes_per_row: u32) -> u32 {
     if rem == 0 { 0 } else { align - rem }
 }
 
+/// Scale a surface size by a resolution factor, rounding to the nearest
+/// pixel and never going below 1x1
+pub fn scaled_size(size: [u32; 2], scale: f32) -> [u32; 2] {
+    let scale_dimension =
+        |dimension: u32| ((dimension as f32 * scale).round() as u32).max(1);
+    [scale_dimension(size[0]), scale_dimension(size[1])]
+}
+
+/// The sample count to multisample `format` with on `adapter` for a
+/// requested `samples` of 1, 2, 4 or 8. Unsupported or invalid counts fall
+/// back to the highest supported count below them, with a warning.
+pub fn supported_msaa_samples(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+    samples: u32,
+) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    let resolvable =
+        flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE);
+    let supported = [8, 4, 2]
+        .into_iter()
+        .filter(|&count| count <= samples)
+        .find(|&count| resolvable && flags.sample_count_supported(count))
+        .unwrap_or(1);
+
+    if !matches!(samples, 1 | 2 | 4 | 8) {
+        warn!(
+            "invalid MSAA sample count {}; expected 1, 2, 4 or 8, using {}",
+            samples, supported
+        );
+    } else if supported != samples {
+        warn!(
+            "{}x MSAA is not supported for {:?}; using {}x",
+            samples, format, supported
+        );
+    }
+
+    supported
+}
+
+/// The largest `(x, y, width, height)` viewport with the source's aspect
+/// ratio that fits centered in the target
+pub fn fit_viewport(
+    source_width: u32,
+    source_height: u32,
+    target_width: u32,
+    target_height: u32,
+) -> (f32, f32, f32, f32) {
+    let source_width = source_width.max(1) as f32;
+    let source_height = source_height.max(1) as f32;
+    let target_width = target_width.max(1) as f32;
+    let target_height = target_height.max(1) as f32;
+
+    let scale =
+        (target_width / source_width).min(target_height / source_height);
+    let viewport_width = (source_width * scale).max(1.0);
+    let viewport_height = (source_height * scale).max(1.0);
+    let viewport_x = (target_width - viewport_width) * 0.5;
+    let viewport_y = (target_height - viewport_height) * 0.5;
+
+    (viewport_x, viewport_y, viewport_width, viewport_height)
+}
+
+/// A shader that failed to load, parse, validate, or build into a pipeline.
+/// `line` and `column` are 1-based and present when naga can attribute the
+/// error to a span of the source.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderError {
+    pub path: PathBuf,
+    pub message: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+impl ShaderError {
+    fn new(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message: message.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    fn at(mut self, location: Option<naga::SourceLocation>) -> Self {
+        if let Some(location) = location {
+            self.line = Some(location.line_number);
+            self.column = Some(location.line_position);
+        }
+        self
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    Shader(ShaderError),
+    Graph(String),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shader(err) => write!(f, "{}", err),
+            Self::Graph(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+impl From<ShaderError> for GraphError {
+    fn from(err: ShaderError) -> Self {
+        Self::Shader(err)
+    }
+}
+
+impl From<String> for GraphError {
+    fn from(message: String) -> Self {
+        Self::Graph(message)
+    }
+}
+
 pub struct CompiledGraph {
     surface_format: wgpu::TextureFormat,
     present_source: PresentSource,
     nodes: Vec<CompiledNode>,
     offscreen_resource_ids: Vec<TextureHandle>,
+    offscreen_formats: HashMap<TextureHandle, wgpu::TextureFormat>,
+    texture_scales: HashMap<TextureHandle, f32>,
+    /// Runtime multiplier on every offscreen texture's scale, see
+    /// [`Self::set_render_scale`]
+    render_scale: f32,
     offscreen_textures: HashMap<TextureHandle, GpuTexture>,
     image_textures: HashMap<TextureHandle, GpuTexture>,
+    drop_targets: Vec<TextureHandle>,
+    #[cfg(feature = "video")]
+    videos: Vec<(TextureHandle, VideoTexture)>,
+    #[cfg(feature = "ndi")]
+    ndi_inputs: Vec<(TextureHandle, NdiInput)>,
     texture_labels: HashMap<TextureHandle, String>,
+    buffers: HashMap<BufferHandle, wgpu::Buffer>,
+    shader_errors: Vec<ShaderError>,
+    export_size: Option<[u32; 2]>,
+    /// Stands in for the surface while an export size is set
+    export_target: Option<GpuTexture>,
+    /// Sample count of render nodes drawing to the surface, see
+    /// [`Self::set_msaa_samples`]
+    msaa_samples: u32,
+    /// Multisampled attachment those nodes draw into before resolving to the
+    /// surface (or export target)
+    msaa_target: Option<GpuTexture>,
+    /// Depth buffers of render nodes with [`DepthOptions`], one per distinct
+    /// target size, format and sample count
+    depth_targets: HashMap<DepthKey, GpuTexture>,
+    #[cfg(feature = "timing")]
+    timer: Option<PassTimer>,
 }
 
 struct GpuTexture {
@@ -48,19 +225,49 @@ enum CompiledNode {
     Compute(ComputeNode),
 }
 
+#[cfg(feature = "timing")]
+impl CompiledNode {
+    fn name(&self) -> &str {
+        match self {
+            Self::Render(node) => &node.name,
+            Self::Compute(node) => &node.name,
+        }
+    }
+}
+
 struct RenderNode {
     name: String,
     target: RenderTarget,
-    sampled_reads: Vec<TextureHandle>,
+    resource_reads: Vec<RenderRead>,
+    instances: InstanceCount,
+    instance_count: u32,
+    instance_buffer: Option<InstanceBuffer>,
     pass: RenderPass,
 }
 
+// GPU copy of an `InstanceCount::Instanced` node's data. Grows to the next
+// power of two when the data outgrows it and is only rewritten when the data
+// changes.
+struct InstanceBuffer {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    generation: u64,
+}
+
 struct ComputeNode {
     name: String,
-    target: TextureHandle,
+    bindings: Vec<ComputeBinding>,
+    workgroups: Workgroups,
+    scale: f32,
     pass: ComputePass,
 }
 
+#[derive(Clone, Copy)]
+enum ReadBindingKind {
+    Texture,
+    Buffer,
+}
+
 #[derive(Clone, Copy)]
 enum PresentSource {
     Surface,
@@ -69,11 +276,13 @@ enum PresentSource {
 
 struct RenderPass {
     shader_path: PathBuf,
-    target_format: wgpu::TextureFormat,
+    targets: PipelineTargets,
     mesh_kind: MeshVertexKind,
+    /// Whether instance attributes are bound as a second vertex buffer
+    instanced: bool,
     render_pipeline: wgpu::RenderPipeline,
     meshes: Vec<MeshDraw>,
-    texture_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    read_bind_group_layout: Option<wgpu::BindGroupLayout>,
     sampler: Option<wgpu::Sampler>,
     watcher: Option<ShaderWatch>,
 }
@@ -83,6 +292,21 @@ struct MeshDraw {
     vertex_count: u32,
 }
 
+/// The attachments a render pipeline is built against
+#[derive(Clone, Copy)]
+struct PipelineTargets {
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    depth: Option<DepthOptions>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct DepthKey {
+    size: [u32; 2],
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+}
+
 struct ComputePass {
     shader_path: PathBuf,
     compute_pipeline: wgpu::ComputePipeline,
@@ -97,7 +321,7 @@ impl CompiledGraph {
         surface_format: wgpu::TextureFormat,
         graph: GraphSpec,
         uniform_layout: &wgpu::BindGroupLayout,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, GraphError> {
         let present_source_handle = find_present_source(&graph)?;
         let (offscreen_resource_ids, image_resources, texture_labels) =
             collect_texture_resources(&graph.resources);
@@ -109,47 +333,70 @@ impl CompiledGraph {
             present_source_handle,
         )?;
 
+        let texture_scales =
+            collect_texture_scales(&graph, &offscreen_resource_ids)?;
+        let drop_targets = collect_drop_targets(&graph.resources);
+        let offscreen_formats = collect_offscreen_formats(&graph.resources);
+        let buffers = create_buffers(device, &graph.resources);
+
         let mut nodes = Vec::new();
 
         for node in graph.nodes {
             match node {
                 NodeSpec::Render(render) => {
-                    let sampled_reads = render
+                    let resource_reads = render
                         .reads
                         .iter()
-                        .filter_map(|resource| match resource {
-                            RenderRead::Texture(texture) => Some(*texture),
-                            RenderRead::Uniform(_) => None,
+                        .copied()
+                        .filter(|resource| {
+                            !matches!(resource, RenderRead::Uniform(_))
                         })
                         .collect::<Vec<_>>();
 
                     let target_format = match render.write {
                         RenderTarget::Surface => surface_format,
-                        RenderTarget::Texture(_) => OFFSCREEN_FORMAT,
+                        RenderTarget::Texture(target) => {
+                            offscreen_format(&offscreen_formats, target)
+                        }
                     };
 
                     let pass = RenderPass::new(
                         device,
                         target_format,
                         &render,
-                        &sampled_reads,
+                        &resource_reads,
                         uniform_layout,
                     )?;
 
+                    let instance_count = match render.instances {
+                        InstanceCount::Fixed(count) => count,
+                        InstanceCount::Control(_)
+                        | InstanceCount::Instanced(_) => 0,
+                    };
+
                     nodes.push(CompiledNode::Render(RenderNode {
                         name: render.name,
                         target: render.write,
-                        sampled_reads,
+                        resource_reads,
+                        instances: render.instances,
+                        instance_count,
+                        instance_buffer: None,
                         pass,
                     }));
                 }
                 NodeSpec::Compute(compute) => {
-                    let pass =
-                        ComputePass::new(device, &compute, uniform_layout)?;
+                    let pass = ComputePass::new(
+                        device,
+                        &compute,
+                        &offscreen_formats,
+                        uniform_layout,
+                    )?;
 
                     nodes.push(CompiledNode::Compute(ComputeNode {
                         name: compute.name,
-                        target: compute.read_write,
+                        bindings: compute.bindings,
+                        workgroups: compute.workgroups,
+                        scale: graph.scale * compute.scale,
                         pass,
                     }));
                 }
@@ -158,20 +405,72 @@ impl CompiledGraph {
         }
 
         if nodes.is_empty() {
-            return Err("graph has no executable nodes".to_string());
+            return Err(GraphError::Graph(
+                "graph has no executable nodes".to_string(),
+            ));
         }
 
         let mut image_textures = HashMap::new();
+        let video_handles = collect_video_handles(&graph.resources);
+        #[cfg(feature = "video")]
+        let mut videos = Vec::new();
 
         for (handle, path) in image_resources {
             let label = texture_labels
                 .get(&handle)
                 .map(|name| name.as_str())
                 .unwrap_or("xtal-image-texture");
+
+            if video_handles.contains(&handle) {
+                #[cfg(feature = "video")]
+                {
+                    let (texture, video) =
+                        open_video_texture(device, queue, label, &path)?;
+                    image_textures.insert(handle, texture);
+                    videos.push((handle, video));
+                    continue;
+                }
+                #[cfg(not(feature = "video"))]
+                return Err(GraphError::Graph(format!(
+                    "video texture '{}' requires the `video` feature",
+                    label
+                )));
+            }
+
             let texture = load_image_texture(device, queue, label, &path)?;
             image_textures.insert(handle, texture);
         }
 
+        let ndi_sources = collect_ndi_sources(&graph.resources);
+        #[cfg(not(feature = "ndi"))]
+        if let Some((handle, _)) = ndi_sources.first() {
+            return Err(GraphError::Graph(format!(
+                "NDI input '{}' requires the `ndi` feature",
+                texture_label(*handle, &texture_labels)
+            )));
+        }
+        #[cfg(feature = "ndi")]
+        let mut ndi_inputs = Vec::new();
+        #[cfg(feature = "ndi")]
+        for (handle, source) in ndi_sources {
+            let label = texture_label(handle, &texture_labels);
+            let input = NdiInput::open(source).map_err(|err| {
+                format!("failed to open NDI input '{}': {}", label, err)
+            })?;
+            image_textures.insert(
+                handle,
+                create_image_texture(device, queue, label, &ndi_placeholder()),
+            );
+            ndi_inputs.push((handle, input));
+        }
+
+        #[cfg(feature = "timing")]
+        let timer = PassTimer::new(
+            device,
+            queue,
+            nodes.iter().map(|node| node.name().to_string()).collect(),
+        );
+
         Ok(Self {
             surface_format,
             present_source: if let Some(source) = present_source_handle {
@@ -181,9 +480,26 @@ impl CompiledGraph {
             },
             nodes,
             offscreen_resource_ids,
+            offscreen_formats,
+            texture_scales,
+            render_scale: 1.0,
             offscreen_textures: HashMap::new(),
             image_textures,
+            drop_targets,
+            #[cfg(feature = "video")]
+            videos,
+            #[cfg(feature = "ndi")]
+            ndi_inputs,
             texture_labels,
+            buffers,
+            shader_errors: Vec::new(),
+            export_size: None,
+            export_target: None,
+            msaa_samples: 1,
+            msaa_target: None,
+            depth_targets: HashMap::new(),
+            #[cfg(feature = "timing")]
+            timer,
         })
     }
 
@@ -194,30 +510,53 @@ impl CompiledGraph {
         uniforms: &UniformBanks,
         surface_size: [u32; 2],
     ) -> Result<(), String> {
-        self.ensure_offscreen_textures(device, surface_size);
+        self.resize(device, surface_size);
+
+        #[cfg(feature = "timing")]
+        if let Some(timer) = self.timer.as_mut() {
+            timer.collect(device);
+        }
+
+        let depth_surface_size = self.export_size.unwrap_or(surface_size);
+        // Depth buffers cleared or drawn into so far this frame
+        let mut depth_written = HashSet::new();
+
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            #[cfg(feature = "timing")]
+            let timer = self.timer.as_ref();
+            // Only used to index timestamp queries
+            #[cfg(not(feature = "timing"))]
+            let _ = index;
 
-        for node in &mut self.nodes {
             match node {
                 CompiledNode::Render(node) => {
-                    node.pass.update_if_changed(
+                    if let Err(err) = node.pass.update_if_changed(
                         device,
-                        &node.sampled_reads,
+                        &node.resource_reads,
                         uniforms.bind_group_layout(),
-                    );
+                    ) {
+                        error!("{}", err);
+                        self.shader_errors.push(err);
+                    }
 
-                    let texture_bind_group = if !node.sampled_reads.is_empty() {
-                        Some(node.pass.create_texture_bind_group(
+                    let read_bind_group = if !node.resource_reads.is_empty() {
+                        Some(node.pass.create_read_bind_group(
                             device,
                             &self.offscreen_textures,
                             &self.image_textures,
-                            &node.sampled_reads,
+                            &self.buffers,
+                            &node.resource_reads,
                         )?)
                     } else {
                         None
                     };
 
                     let target_view = match node.target {
-                        RenderTarget::Surface => frame.surface_view.clone(),
+                        RenderTarget::Surface => self
+                            .export_target
+                            .as_ref()
+                            .map(|target| target.view.clone())
+                            .unwrap_or_else(|| frame.surface_view.clone()),
                         RenderTarget::Texture(texture) => self
                             .offscreen_textures
                             .get(&texture)
@@ -231,23 +570,95 @@ impl CompiledGraph {
                             .clone(),
                     };
 
+                    // Multisampled nodes draw into the MSAA target and
+                    // resolve into the real one
+                    let msaa_view = self
+                        .msaa_target
+                        .as_ref()
+                        .filter(|_| node.pass.targets.sample_count > 1)
+                        .map(|target| &target.view);
+                    let (attachment_view, resolve_target, store) =
+                        match msaa_view {
+                            Some(view) => (
+                                view,
+                                Some(&target_view),
+                                wgpu::StoreOp::Discard,
+                            ),
+                            None => (&target_view, None, wgpu::StoreOp::Store),
+                        };
+
+                    let depth_attachment = match depth_key(
+                        node,
+                        depth_surface_size,
+                        &self.offscreen_textures,
+                    ) {
+                        Some(key) => {
+                            let view = &self
+                                .depth_targets
+                                .get(&key)
+                                .ok_or_else(|| {
+                                    format!(
+                                        "depth buffer for render node '{}' \
+                                         was not allocated",
+                                        node.name
+                                    )
+                                })?
+                                .view;
+                            // Loading is only meaningful once another node
+                            // has written the buffer this frame
+                            let first_use = depth_written.insert(key);
+                            let clear = node
+                                .pass
+                                .targets
+                                .depth
+                                .and_then(|depth| depth.clear)
+                                .or(first_use.then_some(1.0));
+                            Some(wgpu::RenderPassDepthStencilAttachment {
+                                view,
+                                depth_ops: Some(wgpu::Operations {
+                                    load: clear
+                                        .map_or(wgpu::LoadOp::Load, |value| {
+                                            wgpu::LoadOp::Clear(value)
+                                        }),
+                                    store: wgpu::StoreOp::Store,
+                                }),
+                                stencil_ops: key
+                                    .format
+                                    .has_stencil_aspect()
+                                    .then_some(wgpu::Operations {
+                                        load: if clear.is_some() {
+                                            wgpu::LoadOp::Clear(0)
+                                        } else {
+                                            wgpu::LoadOp::Load
+                                        },
+                                        store: wgpu::StoreOp::Store,
+                                    }),
+                            })
+                        }
+                        None => None,
+                    };
+
                     let mut render_pass = frame.encoder().begin_render_pass(
                         &wgpu::RenderPassDescriptor {
                             label: Some(&node.name),
                             color_attachments: &[Some(
                                 wgpu::RenderPassColorAttachment {
-                                    view: &target_view,
-                                    resolve_target: None,
+                                    view: attachment_view,
+                                    resolve_target,
                                     depth_slice: None,
                                     ops: wgpu::Operations {
                                         load: wgpu::LoadOp::Clear(
                                             wgpu::Color::BLACK,
                                         ),
-                                        store: wgpu::StoreOp::Store,
+                                        store,
                                     },
                                 },
                             )],
-                            depth_stencil_attachment: None,
+                            depth_stencil_attachment: depth_attachment,
+                            #[cfg(feature = "timing")]
+                            timestamp_writes: timer
+                                .map(|timer| timer.render_writes(index)),
+                            #[cfg(not(feature = "timing"))]
                             timestamp_writes: None,
                             occlusion_query_set: None,
                         },
@@ -256,37 +667,77 @@ impl CompiledGraph {
                     render_pass.set_pipeline(&node.pass.render_pipeline);
                     render_pass.set_bind_group(0, uniforms.bind_group(), &[]);
 
-                    if let Some(bind_group) = texture_bind_group.as_ref() {
+                    if let Some(bind_group) = read_bind_group.as_ref() {
                         render_pass.set_bind_group(1, bind_group, &[]);
                     }
 
+                    if node.instance_count == 0 {
+                        continue;
+                    }
+
+                    if let Some(instances) = node.instance_buffer.as_ref() {
+                        render_pass
+                            .set_vertex_buffer(1, instances.buffer.slice(..));
+                    }
+
                     for mesh in &node.pass.meshes {
                         render_pass
                             .set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
-                        render_pass.draw(0..mesh.vertex_count, 0..1);
+                        render_pass
+                            .draw(0..mesh.vertex_count, 0..node.instance_count);
                     }
                 }
                 CompiledNode::Compute(node) => {
-                    node.pass.update_if_changed(
-                        device,
-                        uniforms.bind_group_layout(),
-                    );
+                    if let Err(err) = node
+                        .pass
+                        .update_if_changed(device, uniforms.bind_group_layout())
+                    {
+                        error!("{}", err);
+                        self.shader_errors.push(err);
+                    }
 
                     let storage_bind_group =
                         node.pass.create_storage_bind_group(
                             device,
                             &self.offscreen_textures,
-                            &node.target,
+                            &self.buffers,
+                            &node.bindings,
                         )?;
 
-                    let width = surface_size[0].max(1);
-                    let height = surface_size[1].max(1);
-                    let workgroup_x = width.div_ceil(8);
-                    let workgroup_y = height.div_ceil(8);
+                    let [workgroup_x, workgroup_y, workgroup_z] = match node
+                        .workgroups
+                    {
+                        Workgroups::Fixed(counts) => counts,
+                        Workgroups::Resolution => {
+                            let [width, height] = node
+                                .bindings
+                                .iter()
+                                .find_map(|binding| match binding {
+                                    ComputeBinding::StorageTexture(texture) => {
+                                        self.offscreen_textures
+                                            .get(texture)
+                                            .map(|texture| texture.size)
+                                    }
+                                    ComputeBinding::Buffer(_) => None,
+                                })
+                                .unwrap_or_else(|| {
+                                    scaled_size(
+                                        self.export_size
+                                            .unwrap_or(surface_size),
+                                        node.scale * self.render_scale,
+                                    )
+                                });
+                            [width.div_ceil(8), height.div_ceil(8), 1]
+                        }
+                    };
 
                     let mut compute_pass = frame.encoder().begin_compute_pass(
                         &wgpu::ComputePassDescriptor {
                             label: Some(&node.name),
+                            #[cfg(feature = "timing")]
+                            timestamp_writes: timer
+                                .map(|timer| timer.compute_writes(index)),
+                            #[cfg(not(feature = "timing"))]
                             timestamp_writes: None,
                         },
                     );
@@ -297,46 +748,134 @@ impl CompiledGraph {
                     compute_pass.dispatch_workgroups(
                         workgroup_x,
                         workgroup_y,
-                        1,
+                        workgroup_z,
                     );
                 }
             }
         }
 
-        if let PresentSource::Texture(source) = self.present_source {
-            let source_view = if let Some(texture) =
-                self.offscreen_textures.get(&source)
-            {
-                texture.view.clone()
-            } else if let Some(texture) = self.image_textures.get(&source) {
-                texture.view.clone()
-            } else {
-                return Err(format!(
-                    "present source '{}' is not a known texture resource",
-                    texture_label(source, &self.texture_labels)
-                ));
-            };
+        #[cfg(feature = "timing")]
+        if let Some(timer) = self.timer.as_ref() {
+            timer.resolve(frame.encoder());
+        }
+
+        let source_view = match self.present_source {
+            PresentSource::Texture(source) => Some(
+                if let Some(texture) = self.offscreen_textures.get(&source) {
+                    texture.view.clone()
+                } else if let Some(texture) = self.image_textures.get(&source) {
+                    texture.view.clone()
+                } else {
+                    return Err(format!(
+                        "present source '{}' is not a known texture resource",
+                        texture_label(source, &self.texture_labels)
+                    ));
+                },
+            ),
+            PresentSource::Surface => self
+                .export_target
+                .as_ref()
+                .map(|target| target.view.clone()),
+        };
 
+        if let Some(source_view) = source_view {
+            let viewport = self.export_size.map(|[width, height]| {
+                fit_viewport(width, height, surface_size[0], surface_size[1])
+            });
             blit_texture_to_surface(
                 device,
                 frame,
                 &source_view,
                 self.surface_format,
+                viewport,
             );
         }
 
         Ok(())
     }
 
-    fn ensure_offscreen_textures(
+    /// Render at a fixed size instead of the surface's: offscreen textures
+    /// are sized from it, surface nodes draw into an offscreen target of
+    /// exactly this size, and the result is letterboxed onto the surface.
+    /// The capture source then has this size whatever the window's.
+    pub fn set_export_size(&mut self, size: Option<[u32; 2]>) {
+        self.export_size =
+            size.map(|[width, height]| [width.max(1), height.max(1)]);
+        if self.export_size.is_none() {
+            self.export_target = None;
+        }
+    }
+
+    pub fn export_size(&self) -> Option<[u32; 2]> {
+        self.export_size
+    }
+
+    /// Multisample render nodes that draw to the surface with `samples` per
+    /// pixel, resolving into the surface (or export target). Offscreen nodes
+    /// and presenting are unaffected. `samples` must be supported by the
+    /// adapter for the surface format; see [`supported_msaa_samples`].
+    pub fn set_msaa_samples(
         &mut self,
         device: &wgpu::Device,
-        size: [u32; 2],
-    ) {
-        let width = size[0].max(1);
-        let height = size[1].max(1);
+        uniform_layout: &wgpu::BindGroupLayout,
+        samples: u32,
+    ) -> Result<(), ShaderError> {
+        let samples = samples.max(1);
+        for node in &mut self.nodes {
+            let CompiledNode::Render(node) = node else {
+                continue;
+            };
+            if node.target == RenderTarget::Surface
+                && node.pass.targets.sample_count != samples
+            {
+                node.pass
+                    .set_sample_count(device, uniform_layout, samples)?;
+            }
+        }
+        self.msaa_samples = samples;
+        self.msaa_target = None;
+        Ok(())
+    }
+
+    pub fn msaa_samples(&self) -> u32 {
+        self.msaa_samples
+    }
+
+    /// Scale every offscreen texture by `scale` on top of its graph and node
+    /// scale, e.g. to trade resolution for frame rate. Nodes that render
+    /// straight to the surface stay native, as does presenting, which
+    /// upscales the present source to the surface. Takes effect on the next
+    /// [`Self::resize`].
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale.clamp(0.01, 1.0);
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// GPU time per pass from a recent frame, when new timings have been read
+    /// back since the last call. Always `None` if the device lacks timestamp
+    /// query support.
+    #[cfg(feature = "timing")]
+    pub fn take_pass_timings(&mut self) -> Option<PassTimings> {
+        self.timer.as_mut().and_then(PassTimer::take_latest)
+    }
+
+    /// (Re)allocate offscreen textures for the given surface size (or the
+    /// export size, when set). Called at the start of every
+    /// [`Self::execute`] so explicit calls are only needed to allocate ahead
+    /// of the first frame.
+    pub fn resize(&mut self, device: &wgpu::Device, surface_size: [u32; 2]) {
+        let surface_size = self.export_size.unwrap_or(surface_size);
+        self.resize_export_target(device);
+        self.resize_msaa_target(device, surface_size);
 
         for handle in &self.offscreen_resource_ids {
+            let scale = self.texture_scales.get(handle).copied().unwrap_or(1.0)
+                * self.render_scale;
+            let [width, height] = scaled_size(surface_size, scale);
+
             let needs_new = self
                 .offscreen_textures
                 .get(handle)
@@ -346,6 +885,7 @@ impl CompiledGraph {
                 continue;
             }
 
+            let format = offscreen_format(&self.offscreen_formats, *handle);
             let texture = device.create_texture(&wgpu::TextureDescriptor {
                 label: Some(texture_label(*handle, &self.texture_labels)),
                 size: wgpu::Extent3d {
@@ -356,7 +896,7 @@ impl CompiledGraph {
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: OFFSCREEN_FORMAT,
+                format,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT
                     | wgpu::TextureUsages::TEXTURE_BINDING
                     | wgpu::TextureUsages::STORAGE_BINDING
@@ -373,15 +913,385 @@ impl CompiledGraph {
                     texture,
                     view,
                     size: [width, height],
-                    format: OFFSCREEN_FORMAT,
+                    format,
                 },
             );
         }
+
+        self.resize_depth_targets(device, surface_size);
+    }
+
+    fn resize_export_target(&mut self, device: &wgpu::Device) {
+        let Some(size) = self.export_size else {
+            return;
+        };
+        if self
+            .export_target
+            .as_ref()
+            .is_some_and(|target| target.size == size)
+        {
+            return;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("xtal-export-target"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.export_target = Some(GpuTexture {
+            texture,
+            view,
+            size,
+            format: self.surface_format,
+        });
+    }
+
+    fn resize_msaa_target(
+        &mut self,
+        device: &wgpu::Device,
+        surface_size: [u32; 2],
+    ) {
+        if self.msaa_samples == 1 {
+            self.msaa_target = None;
+            return;
+        }
+        if self
+            .msaa_target
+            .as_ref()
+            .is_some_and(|target| target.size == surface_size)
+        {
+            return;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("xtal-msaa-target"),
+            size: wgpu::Extent3d {
+                width: surface_size[0],
+                height: surface_size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: self.msaa_samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.surface_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.msaa_target = Some(GpuTexture {
+            texture,
+            view,
+            size: surface_size,
+            format: self.surface_format,
+        });
+    }
+
+    // Allocate the depth buffers current nodes need at their targets' sizes
+    // and release the rest. Runs after offscreen textures are sized.
+    fn resize_depth_targets(
+        &mut self,
+        device: &wgpu::Device,
+        surface_size: [u32; 2],
+    ) {
+        let keys = self
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                CompiledNode::Render(node) => {
+                    depth_key(node, surface_size, &self.offscreen_textures)
+                }
+                CompiledNode::Compute(_) => None,
+            })
+            .collect::<HashSet<_>>();
+        self.depth_targets.retain(|key, _| keys.contains(key));
+
+        for key in keys {
+            if self.depth_targets.contains_key(&key) {
+                continue;
+            }
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("xtal-depth-target"),
+                size: wgpu::Extent3d {
+                    width: key.size[0],
+                    height: key.size[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: key.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: key.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            let view =
+                texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.depth_targets.insert(
+                key,
+                GpuTexture {
+                    texture,
+                    view,
+                    size: key.size,
+                    format: key.format,
+                },
+            );
+        }
+    }
+
+    /// Recompile every pipeline whose shader is `path`, returning how many
+    /// were reloaded. Pipelines that fail to recompile keep running their
+    /// last good version. Saved shaders are picked up automatically by
+    /// [`Self::execute`]; this is for forcing a reload.
+    pub fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        uniform_layout: &wgpu::BindGroupLayout,
+        path: impl AsRef<Path>,
+    ) -> Result<usize, ShaderError> {
+        let path = normalize_shader_path(path.as_ref())
+            .map_err(|err| ShaderError::new(path.as_ref(), err))?;
+        let mut reloaded = 0;
+        let mut first_error = None;
+
+        for node in &mut self.nodes {
+            let result = match node {
+                CompiledNode::Render(node) if node.pass.shader_path == path => {
+                    node.pass.reload(
+                        device,
+                        &node.resource_reads,
+                        uniform_layout,
+                    )
+                }
+                CompiledNode::Compute(node)
+                    if node.pass.shader_path == path =>
+                {
+                    node.pass.reload(device, uniform_layout)
+                }
+                _ => continue,
+            };
+
+            match result {
+                Ok(()) => reloaded += 1,
+                Err(err) => {
+                    error!("{}", err);
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(reloaded),
+        }
+    }
+
+    /// Swap the contents of every texture declared with
+    /// [`GraphBuilder::dropped_image`] for `image`, returning how many were
+    /// replaced. The previous textures are released once in-flight frames
+    /// stop referencing them.
+    ///
+    /// [`GraphBuilder::dropped_image`]:
+    ///     crate::graph::GraphBuilder::dropped_image
+    pub fn replace_dropped_images(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &DecodedImage,
+    ) -> usize {
+        for handle in &self.drop_targets {
+            let label = texture_label(*handle, &self.texture_labels);
+            let texture = create_image_texture(device, queue, label, image);
+            self.image_textures.insert(*handle, texture);
+        }
+        self.drop_targets.len()
+    }
+
+    /// Advance textures declared with [`GraphBuilder::video`] to the frame
+    /// matching `frame` of a clock running at `fps`. Videos whose decoder is
+    /// behind keep their last frame rather than stalling the render thread.
+    ///
+    /// [`GraphBuilder::video`]: crate::graph::GraphBuilder::video
+    #[cfg(feature = "video")]
+    pub fn update_videos(&mut self, queue: &wgpu::Queue, frame: u32, fps: f32) {
+        for (handle, video) in &mut self.videos {
+            let Some(rgba) = video.advance(frame, fps) else {
+                continue;
+            };
+            if let Some(texture) = self.image_textures.get(handle) {
+                write_image_texture(queue, texture, &rgba);
+            }
+        }
+    }
+
+    /// Upload the newest frame of each texture declared with
+    /// [`GraphBuilder::ndi_input`]. Inputs without a new frame keep showing
+    /// their last one, and a change in the source's resolution replaces the
+    /// texture.
+    ///
+    /// [`GraphBuilder::ndi_input`]: crate::graph::GraphBuilder::ndi_input
+    #[cfg(feature = "ndi")]
+    pub fn update_ndi_inputs(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for (handle, input) in &self.ndi_inputs {
+            let Some(frame) = input.take_frame() else {
+                continue;
+            };
+            match self.image_textures.get(handle) {
+                Some(texture)
+                    if texture.size == [frame.width, frame.height] =>
+                {
+                    write_image_texture(queue, texture, &frame.rgba);
+                }
+                _ => {
+                    let label = texture_label(*handle, &self.texture_labels);
+                    let image = DecodedImage {
+                        width: frame.width,
+                        height: frame.height,
+                        rgba: frame.rgba,
+                    };
+                    let texture =
+                        create_image_texture(device, queue, label, &image);
+                    self.image_textures.insert(*handle, texture);
+                }
+            }
+        }
+    }
+
+    /// Errors from shader hot-reloads since the last call. The affected
+    /// pipelines keep running their last good version.
+    pub fn take_shader_errors(&mut self) -> Vec<ShaderError> {
+        std::mem::take(&mut self.shader_errors)
+    }
+
+    /// Re-read instance counts for render nodes built with
+    /// [`RenderNodeBuilder::instances_from`]. `resolve` maps a control name
+    /// to its current value (typically [`ControlHub::get`]).
+    ///
+    /// [`RenderNodeBuilder::instances_from`]:
+    ///     crate::graph::RenderNodeBuilder::instances_from
+    /// [`ControlHub::get`]: crate::control::ControlHub::get
+    pub fn update_instance_counts(&mut self, resolve: impl Fn(&str) -> f32) {
+        for node in &mut self.nodes {
+            let CompiledNode::Render(node) = node else {
+                continue;
+            };
+            if let InstanceCount::Control(name) = &node.instances {
+                node.instance_count = instance_count_from_value(resolve(name));
+            }
+        }
+    }
+
+    /// Upload the data of [`GraphBuilder::add_instanced`] nodes that changed
+    /// since the last call and update their instance counts. Call once per
+    /// frame after the sketch has updated its instances.
+    ///
+    /// [`GraphBuilder::add_instanced`]: crate::graph::GraphBuilder::add_instanced
+    pub fn update_instance_buffers(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for node in &mut self.nodes {
+            let CompiledNode::Render(node) = node else {
+                continue;
+            };
+            let InstanceCount::Instanced(instances) = &node.instances else {
+                continue;
+            };
+
+            instances.read(|data, generation| {
+                node.instance_count = data.len() as u32;
+                if node
+                    .instance_buffer
+                    .as_ref()
+                    .is_some_and(|buffer| buffer.generation == generation)
+                {
+                    return;
+                }
+
+                let fits = node
+                    .instance_buffer
+                    .as_ref()
+                    .is_some_and(|buffer| buffer.capacity >= data.len());
+                let mut buffer = match node.instance_buffer.take() {
+                    Some(buffer) if fits => buffer,
+                    _ => {
+                        let capacity = data.len().max(1).next_power_of_two();
+                        let size = capacity * std::mem::size_of::<Instance>();
+                        InstanceBuffer {
+                            buffer: device.create_buffer(
+                                &wgpu::BufferDescriptor {
+                                    label: Some("xtal-instances"),
+                                    size: size as u64,
+                                    usage: wgpu::BufferUsages::VERTEX
+                                        | wgpu::BufferUsages::COPY_DST,
+                                    mapped_at_creation: false,
+                                },
+                            ),
+                            capacity,
+                            generation,
+                        }
+                    }
+                };
+
+                if !data.is_empty() {
+                    queue.write_buffer(
+                        &buffer.buffer,
+                        0,
+                        bytemuck::cast_slice(data),
+                    );
+                }
+                buffer.generation = generation;
+                node.instance_buffer = Some(buffer);
+            });
+        }
+    }
+
+    /// Instance count the named render node will draw with on the next
+    /// [`Self::execute`]
+    pub fn instance_count(&self, node_name: &str) -> Option<u32> {
+        self.nodes.iter().find_map(|node| match node {
+            CompiledNode::Render(node) if node.name == node_name => {
+                Some(node.instance_count)
+            }
+            _ => None,
+        })
+    }
+
+    /// GPU buffer backing a [`GraphBuilder::buffer`] declaration
+    ///
+    /// [`GraphBuilder::buffer`]: crate::graph::GraphBuilder::buffer
+    pub fn buffer(&self, handle: BufferHandle) -> Option<&wgpu::Buffer> {
+        self.buffers.get(&handle)
+    }
+
+    /// The currently allocated size of an offscreen or image texture
+    pub fn texture_size(&self, handle: TextureHandle) -> Option<[u32; 2]> {
+        self.offscreen_textures
+            .get(&handle)
+            .or_else(|| self.image_textures.get(&handle))
+            .map(|texture| texture.size)
     }
 
     pub fn recording_source_texture(&self) -> Option<&wgpu::Texture> {
         match self.present_source {
-            PresentSource::Surface => None,
+            PresentSource::Surface => {
+                self.export_target.as_ref().map(|target| &target.texture)
+            }
             PresentSource::Texture(source) => self
                 .offscreen_textures
                 .get(&source)
@@ -396,7 +1306,9 @@ impl CompiledGraph {
 
     pub fn recording_source_format(&self) -> Option<wgpu::TextureFormat> {
         match self.present_source {
-            PresentSource::Surface => None,
+            PresentSource::Surface => {
+                self.export_target.as_ref().map(|target| target.format)
+            }
             PresentSource::Texture(source) => self
                 .offscreen_textures
                 .get(&source)
@@ -415,9 +1327,9 @@ impl RenderPass {
         device: &wgpu::Device,
         target_format: wgpu::TextureFormat,
         node: &RenderNodeSpec,
-        sampled_reads: &[TextureHandle],
+        resource_reads: &[RenderRead],
         uniform_layout: &wgpu::BindGroupLayout,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, GraphError> {
         let shader_path = normalize_shader_path(&node.shader_path)?;
 
         if !node
@@ -425,33 +1337,34 @@ impl RenderPass {
             .iter()
             .any(|resource| matches!(resource, RenderRead::Uniform(_)))
         {
-            return Err(format!(
+            return Err(GraphError::Graph(format!(
                 "render node '{}' must read 'params'",
                 node.name
-            ));
+            )));
         }
 
-        let source = fs::read_to_string(&shader_path).map_err(|err| {
-            format!(
-                "failed to read shader '{}': {}",
-                shader_path.display(),
-                err
-            )
-        })?;
+        if let Some(depth) = node.depth
+            && !depth.format.is_depth_stencil_format()
+        {
+            return Err(GraphError::Graph(format!(
+                "render node '{}' depth format {:?} is not a depth format",
+                node.name, depth.format
+            )));
+        }
 
-        validate_shader(&source).map_err(|err| {
-            format!(
-                "shader validation failed for '{}': {}",
-                shader_path.display(),
-                err
-            )
-        })?;
+        let source = load_shader(&shader_path)?;
 
-        let (texture_bind_group_layout, sampler) = if sampled_reads.is_empty() {
+        let (read_bind_group_layout, sampler) = if resource_reads.is_empty() {
             (None, None)
         } else {
-            let layout =
-                create_texture_bind_group_layout(device, sampled_reads.len());
+            let kinds = resource_reads
+                .iter()
+                .map(|resource| match resource {
+                    RenderRead::Buffer(_) => ReadBindingKind::Buffer,
+                    _ => ReadBindingKind::Texture,
+                })
+                .collect::<Vec<_>>();
+            let layout = create_read_bind_group_layout(device, &kinds);
             let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
                 label: Some("xtal-texture-sampler"),
                 address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -466,15 +1379,22 @@ impl RenderPass {
         };
 
         let mesh_kind = infer_mesh_kind_for_node(node)?;
+        let instanced = matches!(node.instances, InstanceCount::Instanced(_));
+        let targets = PipelineTargets {
+            format: target_format,
+            sample_count: 1,
+            depth: node.depth,
+        };
         let render_pipeline = create_render_pipeline(
             device,
-            target_format,
-            mesh_kind,
+            targets,
+            &vertex_buffer_layouts(mesh_kind, instanced),
             uniform_layout,
-            texture_bind_group_layout.as_ref(),
+            read_bind_group_layout.as_ref(),
             &source,
             &node.name,
-        );
+        )
+        .map_err(|err| ShaderError::new(&shader_path, err))?;
         let meshes = node
             .meshes
             .iter()
@@ -495,27 +1415,28 @@ impl RenderPass {
 
         Ok(Self {
             shader_path,
-            target_format,
+            targets,
             mesh_kind,
+            instanced,
             render_pipeline,
             meshes,
-            texture_bind_group_layout,
+            read_bind_group_layout,
             sampler,
             watcher,
         })
     }
 
-    fn create_texture_bind_group(
+    fn create_read_bind_group(
         &self,
         device: &wgpu::Device,
         offscreen_textures: &HashMap<TextureHandle, GpuTexture>,
         image_textures: &HashMap<TextureHandle, GpuTexture>,
-        sampled_reads: &[TextureHandle],
+        buffers: &HashMap<BufferHandle, wgpu::Buffer>,
+        resource_reads: &[RenderRead],
     ) -> Result<wgpu::BindGroup, String> {
-        let layout =
-            self.texture_bind_group_layout.as_ref().ok_or_else(|| {
-                "texture bind group layout missing for sampled pass".to_string()
-            })?;
+        let layout = self.read_bind_group_layout.as_ref().ok_or_else(|| {
+            "texture bind group layout missing for sampled pass".to_string()
+        })?;
 
         let sampler = self
             .sampler
@@ -527,21 +1448,38 @@ impl RenderPass {
             resource: wgpu::BindingResource::Sampler(sampler),
         }];
 
-        for (index, handle) in sampled_reads.iter().enumerate() {
-            let view = if let Some(texture) = offscreen_textures.get(handle) {
-                &texture.view
-            } else if let Some(texture) = image_textures.get(handle) {
-                &texture.view
-            } else {
-                return Err(format!(
-                    "texture resource '{}' is not available",
-                    handle.index()
-                ));
+        for (index, read) in resource_reads.iter().enumerate() {
+            let resource = match read {
+                RenderRead::Texture(handle) => {
+                    let view = if let Some(texture) =
+                        offscreen_textures.get(handle)
+                    {
+                        &texture.view
+                    } else if let Some(texture) = image_textures.get(handle) {
+                        &texture.view
+                    } else {
+                        return Err(format!(
+                            "texture resource '{}' is not available",
+                            handle.index()
+                        ));
+                    };
+                    wgpu::BindingResource::TextureView(view)
+                }
+                RenderRead::Buffer(handle) => buffers
+                    .get(handle)
+                    .ok_or_else(|| {
+                        format!(
+                            "buffer resource '{}' is not available",
+                            handle.index()
+                        )
+                    })?
+                    .as_entire_binding(),
+                RenderRead::Uniform(_) => continue,
             };
 
             entries.push(wgpu::BindGroupEntry {
                 binding: (index + 1) as u32,
-                resource: wgpu::BindingResource::TextureView(view),
+                resource,
             });
         }
 
@@ -555,55 +1493,75 @@ impl RenderPass {
     fn update_if_changed(
         &mut self,
         device: &wgpu::Device,
-        sampled_reads: &[TextureHandle],
+        resource_reads: &[RenderRead],
         uniform_layout: &wgpu::BindGroupLayout,
-    ) {
+    ) -> Result<(), ShaderError> {
         if !self.watcher.as_ref().is_some_and(ShaderWatch::take_changed) {
-            return;
+            return Ok(());
         }
 
-        info!("reloading shader: {}", self.shader_path.display());
+        self.reload(device, resource_reads, uniform_layout)
+    }
 
-        let source = match fs::read_to_string(&self.shader_path) {
-            Ok(source) => source,
-            Err(err) => {
-                error!(
-                    "failed to read shader '{}': {}",
-                    self.shader_path.display(),
-                    err
-                );
-                return;
-            }
+    // Rebuild the pipeline for a new sample count, keeping the current one on
+    // failure.
+    fn set_sample_count(
+        &mut self,
+        device: &wgpu::Device,
+        uniform_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+    ) -> Result<(), ShaderError> {
+        let source = load_shader(&self.shader_path)?;
+        let targets = PipelineTargets {
+            sample_count,
+            ..self.targets
         };
+        self.render_pipeline = create_render_pipeline(
+            device,
+            targets,
+            &vertex_buffer_layouts(self.mesh_kind, self.instanced),
+            uniform_layout,
+            self.read_bind_group_layout.as_ref(),
+            &source,
+            "xtal-multisampled",
+        )
+        .map_err(|err| ShaderError::new(&self.shader_path, err))?;
+        self.targets = targets;
+        Ok(())
+    }
+
+    // Recompile from disk; on any failure the current pipeline is kept.
+    fn reload(
+        &mut self,
+        device: &wgpu::Device,
+        resource_reads: &[RenderRead],
+        uniform_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(), ShaderError> {
+        info!("reloading shader: {}", self.shader_path.display());
 
-        if let Err(err) = validate_shader(&source) {
-            error!(
-                "shader validation failed for '{}': {}",
-                self.shader_path.display(),
-                err
-            );
-            return;
-        }
+        let source = load_shader(&self.shader_path)?;
 
         self.render_pipeline = create_render_pipeline(
             device,
-            self.target_format,
-            self.mesh_kind,
+            self.targets,
+            &vertex_buffer_layouts(self.mesh_kind, self.instanced),
             uniform_layout,
-            self.texture_bind_group_layout.as_ref(),
+            self.read_bind_group_layout.as_ref(),
             &source,
             "xtal-hot-reloaded",
-        );
+        )
+        .map_err(|err| ShaderError::new(&self.shader_path, err))?;
 
-        if !sampled_reads.is_empty() && self.texture_bind_group_layout.is_none()
-        {
+        if !resource_reads.is_empty() && self.read_bind_group_layout.is_none() {
             warn!(
-                "shader '{}' reads textures but no texture bind group layout is configured",
+                "shader '{}' reads textures or buffers but no read bind group \
+                 layout is configured",
                 self.shader_path.display()
             );
         }
 
         info!("shader reload applied: {}", self.shader_path.display());
+        Ok(())
     }
 }
 
@@ -611,28 +1569,18 @@ impl ComputePass {
     fn new(
         device: &wgpu::Device,
         node: &ComputeNodeSpec,
+        offscreen_formats: &HashMap<TextureHandle, wgpu::TextureFormat>,
         uniform_layout: &wgpu::BindGroupLayout,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, GraphError> {
         let shader_path = normalize_shader_path(&node.shader_path)?;
 
-        let source = fs::read_to_string(&shader_path).map_err(|err| {
-            format!(
-                "failed to read compute shader '{}': {}",
-                shader_path.display(),
-                err
-            )
-        })?;
-
-        validate_shader(&source).map_err(|err| {
-            format!(
-                "compute shader validation failed for '{}': {}",
-                shader_path.display(),
-                err
-            )
-        })?;
+        let source = load_shader(&shader_path)?;
 
-        let storage_bind_group_layout =
-            create_storage_bind_group_layout(device);
+        let storage_bind_group_layout = create_storage_bind_group_layout(
+            device,
+            &node.bindings,
+            offscreen_formats,
+        );
 
         let compute_pipeline = create_compute_pipeline(
             device,
@@ -640,7 +1588,8 @@ impl ComputePass {
             &storage_bind_group_layout,
             &source,
             &node.name,
-        );
+        )
+        .map_err(|err| ShaderError::new(&shader_path, err))?;
 
         let watcher = match ShaderWatch::start(shader_path.clone()) {
             Ok(watch) => Some(watch),
@@ -666,22 +1615,43 @@ impl ComputePass {
         &self,
         device: &wgpu::Device,
         textures: &HashMap<TextureHandle, GpuTexture>,
-        target: &TextureHandle,
+        buffers: &HashMap<BufferHandle, wgpu::Buffer>,
+        bindings: &[ComputeBinding],
     ) -> Result<wgpu::BindGroup, String> {
-        let texture = textures.get(target).ok_or_else(|| {
-            format!(
-                "compute target '{}' is not a declared offscreen texture",
-                target.index()
-            )
-        })?;
+        let mut entries = Vec::with_capacity(bindings.len());
+
+        for (index, binding) in bindings.iter().enumerate() {
+            let resource = match binding {
+                ComputeBinding::StorageTexture(target) => {
+                    let texture = textures.get(target).ok_or_else(|| {
+                        format!(
+                            "compute target '{}' is not a declared offscreen texture",
+                            target.index()
+                        )
+                    })?;
+                    wgpu::BindingResource::TextureView(&texture.view)
+                }
+                ComputeBinding::Buffer(target) => buffers
+                    .get(target)
+                    .ok_or_else(|| {
+                        format!(
+                            "compute target '{}' is not a declared buffer",
+                            target.index()
+                        )
+                    })?
+                    .as_entire_binding(),
+            };
+
+            entries.push(wgpu::BindGroupEntry {
+                binding: index as u32,
+                resource,
+            });
+        }
 
         Ok(device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("xtal-compute-storage-bind-group"),
             layout: &self.storage_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture.view),
-            }],
+            entries: &entries,
         }))
     }
 
@@ -689,33 +1659,23 @@ impl ComputePass {
         &mut self,
         device: &wgpu::Device,
         uniform_layout: &wgpu::BindGroupLayout,
-    ) {
+    ) -> Result<(), ShaderError> {
         if !self.watcher.as_ref().is_some_and(ShaderWatch::take_changed) {
-            return;
+            return Ok(());
         }
 
-        info!("reloading compute shader: {}", self.shader_path.display());
+        self.reload(device, uniform_layout)
+    }
 
-        let source = match fs::read_to_string(&self.shader_path) {
-            Ok(source) => source,
-            Err(err) => {
-                error!(
-                    "failed to read compute shader '{}': {}",
-                    self.shader_path.display(),
-                    err
-                );
-                return;
-            }
-        };
+    // Recompile from disk; on any failure the current pipeline is kept.
+    fn reload(
+        &mut self,
+        device: &wgpu::Device,
+        uniform_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(), ShaderError> {
+        info!("reloading compute shader: {}", self.shader_path.display());
 
-        if let Err(err) = validate_shader(&source) {
-            error!(
-                "compute shader validation failed for '{}': {}",
-                self.shader_path.display(),
-                err
-            );
-            return;
-        }
+        let source = load_shader(&self.shader_path)?;
 
         self.compute_pipeline = create_compute_pipeline(
             device,
@@ -723,19 +1683,43 @@ impl ComputePass {
             &self.storage_bind_group_layout,
             &source,
             "xtal-hot-reloaded-compute",
-        );
+        )
+        .map_err(|err| ShaderError::new(&self.shader_path, err))?;
 
         info!(
             "compute shader reload applied: {}",
             self.shader_path.display()
         );
+        Ok(())
     }
 }
 
 fn create_render_pipeline(
     device: &wgpu::Device,
-    format: wgpu::TextureFormat,
-    mesh_kind: MeshVertexKind,
+    targets: PipelineTargets,
+    vertex_buffers: &[wgpu::VertexBufferLayout],
+    uniform_layout: &wgpu::BindGroupLayout,
+    texture_layout: Option<&wgpu::BindGroupLayout>,
+    source: &str,
+    label: &str,
+) -> Result<wgpu::RenderPipeline, String> {
+    with_validation_scope(device, || {
+        build_render_pipeline(
+            device,
+            targets,
+            vertex_buffers,
+            uniform_layout,
+            texture_layout,
+            source,
+            label,
+        )
+    })
+}
+
+fn build_render_pipeline(
+    device: &wgpu::Device,
+    targets: PipelineTargets,
+    vertex_buffers: &[wgpu::VertexBufferLayout],
     uniform_layout: &wgpu::BindGroupLayout,
     texture_layout: Option<&wgpu::BindGroupLayout>,
     source: &str,
@@ -759,8 +1743,6 @@ fn create_render_pipeline(
             push_constant_ranges: &[],
         });
 
-    let vertex_buffers = [vertex_buffer_layout_for_kind(mesh_kind)];
-
     device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("xtal-render-pipeline"),
         layout: Some(&layout),
@@ -768,14 +1750,14 @@ fn create_render_pipeline(
             module: &shader,
             entry_point: Some("vs_main"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
-            buffers: &vertex_buffers,
+            buffers: vertex_buffers,
         },
         fragment: Some(wgpu::FragmentState {
             module: &shader,
             entry_point: Some("fs_main"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
             targets: &[Some(wgpu::ColorTargetState {
-                format,
+                format: targets.format,
                 blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
@@ -789,9 +1771,15 @@ fn create_render_pipeline(
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: targets.depth.map(|depth| wgpu::DepthStencilState {
+            format: depth.format,
+            depth_write_enabled: depth.write,
+            depth_compare: depth.compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: targets.sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -822,6 +1810,17 @@ fn vertex_buffer_layout_for_kind(
     }
 }
 
+fn vertex_buffer_layouts(
+    mesh_kind: MeshVertexKind,
+    instanced: bool,
+) -> Vec<wgpu::VertexBufferLayout<'static>> {
+    let mut layouts = vec![vertex_buffer_layout_for_kind(mesh_kind)];
+    if instanced {
+        layouts.push(instance_buffer_layout());
+    }
+    layouts
+}
+
 fn create_mesh_draw(
     device: &wgpu::Device,
     mesh: &Mesh,
@@ -880,34 +1879,53 @@ fn create_compute_pipeline(
     storage_layout: &wgpu::BindGroupLayout,
     source: &str,
     label: &str,
-) -> wgpu::ComputePipeline {
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some(label),
-        source: wgpu::ShaderSource::Wgsl(source.into()),
-    });
+) -> Result<wgpu::ComputePipeline, String> {
+    with_validation_scope(device, || {
+        let shader =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
 
-    let layout =
-        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("xtal-compute-pipeline-layout"),
-            bind_group_layouts: &[uniform_layout, storage_layout],
-            push_constant_ranges: &[],
-        });
+        let layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("xtal-compute-pipeline-layout"),
+                bind_group_layouts: &[uniform_layout, storage_layout],
+                push_constant_ranges: &[],
+            });
 
-    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: Some("xtal-compute-pipeline"),
-        layout: Some(&layout),
-        module: &shader,
-        entry_point: Some("cs_main"),
-        compilation_options: wgpu::PipelineCompilationOptions::default(),
-        cache: None,
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("xtal-compute-pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        })
     })
 }
 
-fn create_texture_bind_group_layout(
+// Run `create` inside a validation error scope so pipeline errors (e.g. a
+// binding that doesn't match the node's layout) come back as a `Result`
+// instead of hitting the device's uncaptured error handler and aborting.
+fn with_validation_scope<T>(
+    device: &wgpu::Device,
+    create: impl FnOnce() -> T,
+) -> Result<T, String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = create();
+
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(err) => Err(err.to_string()),
+        None => Ok(value),
+    }
+}
+
+fn create_read_bind_group_layout(
     device: &wgpu::Device,
-    texture_count: usize,
+    kinds: &[ReadBindingKind],
 ) -> wgpu::BindGroupLayout {
-    let mut entries = Vec::with_capacity(texture_count + 1);
+    let mut entries = Vec::with_capacity(kinds.len() + 1);
 
     entries.push(wgpu::BindGroupLayoutEntry {
         binding: 0,
@@ -916,17 +1934,32 @@ fn create_texture_bind_group_layout(
         count: None,
     });
 
-    for index in 0..texture_count {
+    for (index, kind) in kinds.iter().enumerate() {
+        let (visibility, ty) = match kind {
+            ReadBindingKind::Texture => (
+                wgpu::ShaderStages::FRAGMENT,
+                wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float {
+                        filterable: true,
+                    },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+            ),
+            ReadBindingKind::Buffer => (
+                wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            ),
+        };
+
         entries.push(wgpu::BindGroupLayoutEntry {
             binding: (index + 1) as u32,
-            visibility: wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Texture {
-                sample_type: wgpu::TextureSampleType::Float {
-                    filterable: true,
-                },
-                view_dimension: wgpu::TextureViewDimension::D2,
-                multisampled: false,
-            },
+            visibility,
+            ty,
             count: None,
         });
     }
@@ -939,27 +1972,69 @@ fn create_texture_bind_group_layout(
 
 fn create_storage_bind_group_layout(
     device: &wgpu::Device,
+    bindings: &[ComputeBinding],
+    offscreen_formats: &HashMap<TextureHandle, wgpu::TextureFormat>,
 ) -> wgpu::BindGroupLayout {
-    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("xtal-compute-storage-bind-group-layout"),
-        entries: &[wgpu::BindGroupLayoutEntry {
-            binding: 0,
+    let entries = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, binding)| wgpu::BindGroupLayoutEntry {
+            binding: index as u32,
             visibility: wgpu::ShaderStages::COMPUTE,
-            ty: wgpu::BindingType::StorageTexture {
-                access: wgpu::StorageTextureAccess::WriteOnly,
-                format: OFFSCREEN_FORMAT,
-                view_dimension: wgpu::TextureViewDimension::D2,
+            ty: match binding {
+                ComputeBinding::StorageTexture(target) => {
+                    wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: offscreen_format(offscreen_formats, *target),
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    }
+                }
+                ComputeBinding::Buffer(_) => wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
             },
             count: None,
-        }],
+        })
+        .collect::<Vec<_>>();
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("xtal-compute-storage-bind-group-layout"),
+        entries: &entries,
     })
 }
 
+fn create_buffers(
+    device: &wgpu::Device,
+    resources: &[ResourceDecl],
+) -> HashMap<BufferHandle, wgpu::Buffer> {
+    resources
+        .iter()
+        .filter_map(|resource| match (resource.handle, &resource.kind) {
+            (ResourceHandle::Buffer(handle), ResourceKind::Buffer { size }) => {
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&resource.name),
+                    size: (*size).max(4).next_multiple_of(4),
+                    usage: wgpu::BufferUsages::STORAGE
+                        | wgpu::BufferUsages::VERTEX
+                        | wgpu::BufferUsages::COPY_SRC
+                        | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                Some((handle, buffer))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 fn blit_texture_to_surface(
     device: &wgpu::Device,
     frame: &mut Frame,
     source_view: &wgpu::TextureView,
     surface_format: wgpu::TextureFormat,
+    viewport: Option<(f32, f32, f32, f32)>,
 ) {
     let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
         label: Some("xtal-present-sampler"),
@@ -972,7 +2047,8 @@ fn blit_texture_to_surface(
         ..Default::default()
     });
 
-    let bind_group_layout = create_texture_bind_group_layout(device, 1);
+    let bind_group_layout =
+        create_read_bind_group_layout(device, &[ReadBindingKind::Texture]);
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: Some("xtal-present-bind-group"),
         layout: &bind_group_layout,
@@ -1059,6 +2135,10 @@ fn blit_texture_to_surface(
 
     render_pass.set_pipeline(&pipeline);
     render_pass.set_bind_group(0, &bind_group, &[]);
+    // The clear above leaves the bars outside the viewport black
+    if let Some((x, y, width, height)) = viewport {
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+    }
     render_pass.draw(0..4, 0..1);
 }
 
@@ -1103,69 +2183,23 @@ fn load_image_texture(
     path: &Path,
 ) -> Result<GpuTexture, String> {
     let resolved = normalize_shader_path(path)?;
-    let bytes = fs::read(&resolved).map_err(|err| {
-        format!(
-            "failed to read image '{}' at '{}': {}",
-            name,
-            resolved.display(),
-            err
-        )
-    })?;
+    let image = image::load_image(&resolved)
+        .map_err(|err| format!("failed to load image '{}': {}", name, err))?;
 
-    let decoder = png::Decoder::new(Cursor::new(bytes));
-    let mut reader = decoder.read_info().map_err(|err| {
-        format!(
-            "failed to decode PNG '{}' at '{}': {}",
-            name,
-            resolved.display(),
-            err
-        )
-    })?;
-    let output_buffer_size = reader.output_buffer_size().ok_or_else(|| {
-        format!(
-            "failed to determine PNG output buffer size for '{}' at '{}'",
-            name,
-            resolved.display()
-        )
-    })?;
-    let mut buf = vec![0; output_buffer_size];
-    let info = reader.next_frame(&mut buf).map_err(|err| {
-        format!(
-            "failed to read PNG frame '{}' at '{}': {}",
-            name,
-            resolved.display(),
-            err
-        )
-    })?;
-    let src = &buf[..info.buffer_size()];
-    let width = info.width.max(1);
-    let height = info.height.max(1);
-
-    let rgba = match (info.color_type, info.bit_depth) {
-        (png::ColorType::Rgba, png::BitDepth::Eight) => src.to_vec(),
-        (png::ColorType::Rgb, png::BitDepth::Eight) => {
-            let mut out = Vec::with_capacity((width * height * 4) as usize);
-            for pixel in src.chunks_exact(3) {
-                out.push(pixel[0]);
-                out.push(pixel[1]);
-                out.push(pixel[2]);
-                out.push(255);
-            }
-            out
-        }
-        _ => {
-            return Err(format!(
-                "unsupported PNG format for '{}': {:?} {:?} (expected RGB/RGBA 8-bit)",
-                name, info.color_type, info.bit_depth
-            ));
-        }
-    };
+    Ok(create_image_texture(device, queue, name, &image))
+}
 
+fn create_image_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    name: &str,
+    image: &DecodedImage,
+) -> GpuTexture {
     let texture = device.create_texture(&wgpu::TextureDescriptor {
         label: Some(name),
         size: wgpu::Extent3d {
-            width,
-            height,
+            width: image.width,
+            height: image.height,
             depth_or_array_layers: 1,
         },
         mip_level_count: 1,
@@ -1176,15 +2210,29 @@ fn load_image_texture(
             | wgpu::TextureUsages::COPY_DST,
         view_formats: &[],
     });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let texture = GpuTexture {
+        texture,
+        view,
+        size: [image.width, image.height],
+        format: IMAGE_FORMAT,
+    };
+
+    write_image_texture(queue, &texture, &image.rgba);
+    texture
+}
+
+fn write_image_texture(queue: &wgpu::Queue, texture: &GpuTexture, rgba: &[u8]) {
+    let [width, height] = texture.size;
 
     queue.write_texture(
         wgpu::TexelCopyTextureInfo {
-            texture: &texture,
+            texture: &texture.texture,
             mip_level: 0,
             origin: wgpu::Origin3d::ZERO,
             aspect: wgpu::TextureAspect::All,
         },
-        &rgba,
+        rgba,
         wgpu::TexelCopyBufferLayout {
             offset: 0,
             bytes_per_row: Some(4 * width),
@@ -1196,27 +2244,61 @@ fn load_image_texture(
             depth_or_array_layers: 1,
         },
     );
+}
 
-    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+#[cfg(feature = "video")]
+fn open_video_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    name: &str,
+    path: &Path,
+) -> Result<(GpuTexture, VideoTexture), String> {
+    let resolved = normalize_shader_path(path)?;
+    let video = VideoTexture::open(&resolved)
+        .map_err(|err| format!("failed to open video '{}': {}", name, err))?;
+    let info = video.info();
+    // Black until the decoder delivers the first frame
+    let placeholder = DecodedImage {
+        width: info.width,
+        height: info.height,
+        rgba: vec![0; (info.width * info.height * 4) as usize],
+    };
 
-    Ok(GpuTexture {
-        texture,
-        view,
-        size: [width, height],
-        format: IMAGE_FORMAT,
-    })
+    let texture = create_image_texture(device, queue, name, &placeholder);
+
+    Ok((texture, video))
+}
+
+fn load_shader(path: &Path) -> Result<String, ShaderError> {
+    let source = fs::read_to_string(path).map_err(|err| {
+        ShaderError::new(path, format!("failed to read shader: {}", err))
+    })?;
+    validate_shader(path, &source)?;
+    Ok(source)
 }
 
-fn validate_shader(source: &str) -> Result<(), String> {
-    let module = wgsl::parse_str(source).map_err(|err| err.to_string())?;
+fn validate_shader(path: &Path, source: &str) -> Result<(), ShaderError> {
+    let module = wgsl::parse_str(source).map_err(|err| {
+        ShaderError::new(path, err.message()).at(err.location(source))
+    })?;
 
     let mut validator =
         Validator::new(ValidationFlags::all(), Capabilities::all());
 
-    validator
-        .validate(&module)
-        .map_err(|err| err.to_string())
-        .map(|_| ())
+    validator.validate(&module).map_err(|err| {
+        // The top-level validation error is usually just "Function [n] is
+        // invalid"; the useful detail lives in its source chain.
+        let mut message = err.as_inner().to_string();
+        let mut cause = err.as_inner().source();
+        while let Some(inner) = cause {
+            message.push_str(": ");
+            message.push_str(&inner.to_string());
+            cause = inner.source();
+        }
+        ShaderError::new(path, message).at(err.location(source))
+    })?;
+
+    Ok(())
 }
 
 fn normalize_shader_path(path: &Path) -> Result<PathBuf, String> {
@@ -1230,6 +2312,14 @@ fn normalize_shader_path(path: &Path) -> Result<PathBuf, String> {
     Ok(cwd.join(path))
 }
 
+fn instance_count_from_value(value: f32) -> u32 {
+    if value.is_finite() && value > 0.0 {
+        value.round() as u32
+    } else {
+        0
+    }
+}
+
 fn find_present_source(
     graph: &GraphSpec,
 ) -> Result<Option<TextureHandle>, String> {
@@ -1247,6 +2337,81 @@ fn find_present_source(
     Ok(source)
 }
 
+fn collect_drop_targets(resources: &[ResourceDecl]) -> Vec<TextureHandle> {
+    resources
+        .iter()
+        .filter_map(|resource| match (resource.handle, &resource.kind) {
+            (
+                ResourceHandle::Texture(handle),
+                ResourceKind::Image2d {
+                    drop_target: true, ..
+                },
+            ) => Some(handle),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_offscreen_formats(
+    resources: &[ResourceDecl],
+) -> HashMap<TextureHandle, wgpu::TextureFormat> {
+    resources
+        .iter()
+        .filter_map(|resource| match (resource.handle, &resource.kind) {
+            (ResourceHandle::Texture(handle), ResourceKind::Texture2dHdr) => {
+                Some((handle, HDR_OFFSCREEN_FORMAT))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Format of a declared offscreen texture; plain `texture2d`s are absent
+/// from the map
+fn offscreen_format(
+    formats: &HashMap<TextureHandle, wgpu::TextureFormat>,
+    handle: TextureHandle,
+) -> wgpu::TextureFormat {
+    formats.get(&handle).copied().unwrap_or(OFFSCREEN_FORMAT)
+}
+
+fn collect_video_handles(resources: &[ResourceDecl]) -> HashSet<TextureHandle> {
+    resources
+        .iter()
+        .filter_map(|resource| match (resource.handle, &resource.kind) {
+            (ResourceHandle::Texture(handle), ResourceKind::Video { .. }) => {
+                Some(handle)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_ndi_sources(
+    resources: &[ResourceDecl],
+) -> Vec<(TextureHandle, String)> {
+    resources
+        .iter()
+        .filter_map(|resource| match (resource.handle, &resource.kind) {
+            (
+                ResourceHandle::Texture(handle),
+                ResourceKind::NdiInput { source },
+            ) => Some((handle, source.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+// Black until the source delivers its first frame
+#[cfg(feature = "ndi")]
+fn ndi_placeholder() -> DecodedImage {
+    DecodedImage {
+        width: 1,
+        height: 1,
+        rgba: vec![0, 0, 0, 255],
+    }
+}
+
 fn collect_texture_resources(
     resources: &[ResourceDecl],
 ) -> (
@@ -1266,11 +2431,21 @@ fn collect_texture_resources(
         labels.insert(handle, resource.name.clone());
 
         match &resource.kind {
-            ResourceKind::Texture2d => offscreen.push(handle),
-            ResourceKind::Image2d { path } => {
+            ResourceKind::Texture2d | ResourceKind::Texture2dHdr => {
+                offscreen.push(handle)
+            }
+            // Videos are sampled like images; their pixels come from the
+            // decoder instead of a one-off load.
+            ResourceKind::Image2d { path, .. }
+            | ResourceKind::Video { path } => {
                 images.insert(handle, path.clone());
             }
-            ResourceKind::Uniforms => unreachable!(),
+            // Also sampled like images, but created from the first received
+            // frame rather than a file
+            ResourceKind::NdiInput { .. } => {}
+            ResourceKind::Uniforms | ResourceKind::Buffer { .. } => {
+                unreachable!()
+            }
         }
     }
 
@@ -1287,7 +2462,23 @@ fn validate_graph_resources(
         .iter()
         .copied()
         .collect::<HashSet<_>>();
-    let image_ids = image_resources.keys().copied().collect::<HashSet<_>>();
+    let image_ids = image_resources
+        .keys()
+        .copied()
+        .chain(
+            collect_ndi_sources(&graph.resources)
+                .into_iter()
+                .map(|(handle, _)| handle),
+        )
+        .collect::<HashSet<_>>();
+    let buffer_ids = graph
+        .resources
+        .iter()
+        .filter_map(|resource| match resource.handle {
+            ResourceHandle::Buffer(handle) => Some(handle),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
 
     if let Some(source) = present_source {
         if !offscreen_ids.contains(&source) && !image_ids.contains(&source) {
@@ -1312,9 +2503,10 @@ fn validate_graph_resources(
                 }
 
                 for read in &render.reads {
-                    if let RenderRead::Texture(texture) = read {
-                        if !offscreen_ids.contains(texture)
-                            && !image_ids.contains(texture)
+                    match read {
+                        RenderRead::Texture(texture)
+                            if !offscreen_ids.contains(texture)
+                                && !image_ids.contains(texture) =>
                         {
                             return Err(format!(
                                 "render node '{}' reads texture {} which is not a declared texture2d/image resource",
@@ -1322,16 +2514,42 @@ fn validate_graph_resources(
                                 texture.index()
                             ));
                         }
+                        RenderRead::Buffer(buffer)
+                            if !buffer_ids.contains(buffer) =>
+                        {
+                            return Err(format!(
+                                "render node '{}' reads buffer {} which is not a declared buffer resource",
+                                render.name,
+                                buffer.index()
+                            ));
+                        }
+                        _ => {}
                     }
                 }
             }
             NodeSpec::Compute(compute) => {
-                if !offscreen_ids.contains(&compute.read_write) {
-                    return Err(format!(
-                        "compute node '{}' read_write target '{}' is not a declared texture2d resource",
-                        compute.name,
-                        compute.read_write.index()
-                    ));
+                for binding in &compute.bindings {
+                    match binding {
+                        ComputeBinding::StorageTexture(texture)
+                            if !offscreen_ids.contains(texture) =>
+                        {
+                            return Err(format!(
+                                "compute node '{}' read_write target '{}' is not a declared texture2d resource",
+                                compute.name,
+                                texture.index()
+                            ));
+                        }
+                        ComputeBinding::Buffer(buffer)
+                            if !buffer_ids.contains(buffer) =>
+                        {
+                            return Err(format!(
+                                "compute node '{}' read_write target '{}' is not a declared buffer resource",
+                                compute.name,
+                                buffer.index()
+                            ));
+                        }
+                        _ => {}
+                    }
                 }
             }
             NodeSpec::Present { .. } => {}
@@ -1341,6 +2559,107 @@ fn validate_graph_resources(
     Ok(())
 }
 
+fn collect_texture_scales(
+    graph: &GraphSpec,
+    offscreen_resource_ids: &[TextureHandle],
+) -> Result<HashMap<TextureHandle, f32>, String> {
+    let validate = |name: &str, scale: f32| {
+        if scale.is_finite() && scale > 0.0 {
+            Ok(scale)
+        } else {
+            Err(format!(
+                "node '{}' has invalid resolution scale {}; expected a positive number",
+                name, scale
+            ))
+        }
+    };
+
+    let graph_scale = validate("graph", graph.scale)?;
+    let mut scales = offscreen_resource_ids
+        .iter()
+        .map(|handle| (*handle, graph_scale))
+        .collect::<HashMap<_, _>>();
+    let mut assigned_by = HashMap::new();
+
+    for node in &graph.nodes {
+        let (name, targets, scale) = match node {
+            NodeSpec::Render(render) => {
+                let scale = validate(&render.name, render.scale)?;
+                match render.write {
+                    RenderTarget::Texture(target) => {
+                        (&render.name, vec![target], scale)
+                    }
+                    RenderTarget::Surface => {
+                        if scale != 1.0 {
+                            warn!(
+                                "render node '{}' targets the surface; ignoring scale {}",
+                                render.name, scale
+                            );
+                        }
+                        continue;
+                    }
+                }
+            }
+            NodeSpec::Compute(compute) => (
+                &compute.name,
+                compute
+                    .bindings
+                    .iter()
+                    .filter_map(|binding| match binding {
+                        ComputeBinding::StorageTexture(texture) => {
+                            Some(*texture)
+                        }
+                        ComputeBinding::Buffer(_) => None,
+                    })
+                    .collect(),
+                validate(&compute.name, compute.scale)?,
+            ),
+            NodeSpec::Present { .. } => continue,
+        };
+
+        for target in targets {
+            if let Some((other_name, other_scale)) = assigned_by
+                .get(&target)
+                .filter(|(_, other_scale)| *other_scale != scale)
+            {
+                return Err(format!(
+                    "nodes '{}' and '{}' write texture {} with different scales ({} vs {})",
+                    other_name,
+                    name,
+                    target.index(),
+                    other_scale,
+                    scale
+                ));
+            }
+
+            assigned_by.insert(target, (name.clone(), scale));
+            scales.insert(target, graph_scale * scale);
+        }
+    }
+
+    Ok(scales)
+}
+
+// The shared depth buffer a render node draws with, if it has depth options
+fn depth_key(
+    node: &RenderNode,
+    surface_size: [u32; 2],
+    offscreen_textures: &HashMap<TextureHandle, GpuTexture>,
+) -> Option<DepthKey> {
+    let depth = node.pass.targets.depth?;
+    let size = match node.target {
+        RenderTarget::Surface => surface_size,
+        RenderTarget::Texture(texture) => {
+            offscreen_textures.get(&texture)?.size
+        }
+    };
+    Some(DepthKey {
+        size,
+        format: depth.format,
+        sample_count: node.pass.targets.sample_count,
+    })
+}
+
 fn texture_label(
     handle: TextureHandle,
     labels: &HashMap<TextureHandle, String>,
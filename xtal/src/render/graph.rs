@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::instancing::{self, Instances};
 use crate::mesh::Mesh;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -8,6 +9,9 @@ pub struct UniformHandle(usize);
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct TextureHandle(usize);
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BufferHandle(usize);
+
 impl UniformHandle {
     pub fn index(self) -> usize {
         self.0
@@ -20,16 +24,28 @@ impl TextureHandle {
     }
 }
 
+impl BufferHandle {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ResourceHandle {
     Uniform(UniformHandle),
     Texture(TextureHandle),
+    Buffer(BufferHandle),
 }
 
+/// Resources a render node can read. Textures are sampled and buffers are
+/// bound as read-only storage, both in group 1 following the sampler at
+/// binding 0, numbered in the order they were passed to
+/// [`RenderNodeBuilder::read`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum RenderRead {
     Uniform(UniformHandle),
     Texture(TextureHandle),
+    Buffer(BufferHandle),
 }
 
 impl From<UniformHandle> for RenderRead {
@@ -44,6 +60,12 @@ impl From<TextureHandle> for RenderRead {
     }
 }
 
+impl From<BufferHandle> for RenderRead {
+    fn from(value: BufferHandle) -> Self {
+        Self::Buffer(value)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum RenderTarget {
     Surface,
@@ -54,7 +76,21 @@ pub enum RenderTarget {
 pub enum ResourceKind {
     Uniforms,
     Texture2d,
-    Image2d { path: PathBuf },
+    /// Half-float offscreen texture for values outside 0..1
+    Texture2dHdr,
+    Image2d {
+        path: PathBuf,
+        drop_target: bool,
+    },
+    Video {
+        path: PathBuf,
+    },
+    NdiInput {
+        source: String,
+    },
+    Buffer {
+        size: u64,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -71,13 +107,85 @@ pub struct RenderNodeSpec {
     pub meshes: Vec<Mesh>,
     pub reads: Vec<RenderRead>,
     pub write: RenderTarget,
+    pub scale: f32,
+    pub instances: InstanceCount,
+    pub depth: Option<DepthOptions>,
+}
+
+/// Depth attachment for a render node, see [`RenderNodeBuilder::depth`].
+/// Nodes whose targets share a size, format and sample count share one depth
+/// buffer, so a later node can test against geometry an earlier one drew.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthOptions {
+    pub format: wgpu::TextureFormat,
+    pub compare: wgpu::CompareFunction,
+    /// Whether passing fragments write their depth
+    pub write: bool,
+    /// Value to clear depth to before drawing. `None` keeps what the previous
+    /// node sharing the buffer wrote this frame (or clears to 1.0 when no
+    /// node has).
+    pub clear: Option<f32>,
+}
+
+impl Default for DepthOptions {
+    fn default() -> Self {
+        Self {
+            format: wgpu::TextureFormat::Depth32Float,
+            compare: wgpu::CompareFunction::Less,
+            write: true,
+            clear: Some(1.0),
+        }
+    }
+}
+
+/// How many instances each mesh in a render node is drawn with
+#[derive(Clone, Debug, PartialEq)]
+pub enum InstanceCount {
+    Fixed(u32),
+    /// Read from the named hub control every frame. Values are rounded and
+    /// clamped at zero; a count of zero skips the draw.
+    Control(String),
+    /// One instance per entry, with its attributes bound as a second vertex
+    /// buffer; see [`GraphBuilder::add_instanced`]
+    Instanced(Instances),
+}
+
+/// Resources a compute node can write. Bound in group 1 in the order they
+/// were passed to [`ComputeNodeBuilder::read_write`]: textures as write-only
+/// `rgba8unorm` storage textures, buffers as read-write storage buffers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ComputeBinding {
+    StorageTexture(TextureHandle),
+    Buffer(BufferHandle),
+}
+
+impl From<TextureHandle> for ComputeBinding {
+    fn from(value: TextureHandle) -> Self {
+        Self::StorageTexture(value)
+    }
+}
+
+impl From<BufferHandle> for ComputeBinding {
+    fn from(value: BufferHandle) -> Self {
+        Self::Buffer(value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Workgroups {
+    /// One 8x8 workgroup per 8x8 pixel tile of the first storage texture
+    /// binding (or the scaled surface when the node only binds buffers)
+    Resolution,
+    Fixed([u32; 3]),
 }
 
 #[derive(Clone, Debug)]
 pub struct ComputeNodeSpec {
     pub name: String,
     pub shader_path: PathBuf,
-    pub read_write: TextureHandle,
+    pub bindings: Vec<ComputeBinding>,
+    pub workgroups: Workgroups,
+    pub scale: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -91,6 +199,9 @@ pub enum NodeSpec {
 pub struct GraphSpec {
     pub resources: Vec<ResourceDecl>,
     pub nodes: Vec<NodeSpec>,
+    /// Resolution multiplier applied to every offscreen texture on top of
+    /// the per-node scale of the node that writes it
+    pub scale: f32,
 }
 
 #[derive(Default)]
@@ -98,7 +209,9 @@ pub struct GraphBuilder {
     resources: Vec<ResourceDecl>,
     nodes: Vec<NodeSpec>,
     uniform_handle: Option<UniformHandle>,
+    scale: Option<f32>,
     next_texture_index: usize,
+    next_buffer_index: usize,
     next_render_node_index: usize,
     next_compute_node_index: usize,
 }
@@ -124,26 +237,109 @@ impl GraphBuilder {
     }
 
     pub fn texture2d(&mut self) -> TextureHandle {
+        self.declare_texture2d(ResourceKind::Texture2d)
+    }
+
+    /// Like [`texture2d`](Self::texture2d), but `rgba16float` so values are
+    /// neither clamped to 0..1 nor quantized to 8 bits. Compute nodes bind
+    /// it as `texture_storage_2d<rgba16float, write>`. Frame captures of an
+    /// HDR present source can keep the full range (see
+    /// [`StillFormat`](crate::runtime::stills::StillFormat)); video
+    /// recording needs an 8-bit source.
+    pub fn texture2d_hdr(&mut self) -> TextureHandle {
+        self.declare_texture2d(ResourceKind::Texture2dHdr)
+    }
+
+    fn declare_texture2d(&mut self, kind: ResourceKind) -> TextureHandle {
         let handle = TextureHandle(self.next_texture_index);
         self.next_texture_index += 1;
 
         self.resources.push(ResourceDecl {
             handle: ResourceHandle::Texture(handle),
             name: format!("tex{}", handle.0),
-            kind: ResourceKind::Texture2d,
+            kind,
         });
 
         handle
     }
 
     pub fn image(&mut self, path: impl Into<PathBuf>) -> TextureHandle {
+        self.declare_image(path.into(), false)
+    }
+
+    /// Like [`image`](Self::image), but replaced at runtime by any image file
+    /// dropped onto the main window. `fallback` is shown until the first drop.
+    pub fn dropped_image(
+        &mut self,
+        fallback: impl Into<PathBuf>,
+    ) -> TextureHandle {
+        self.declare_image(fallback.into(), true)
+    }
+
+    /// Declare a looping video file sampled like an image, advancing with
+    /// the frame clock. Requires the `video` feature and `ffmpeg`/`ffprobe`
+    /// on the `PATH`.
+    pub fn video(&mut self, path: impl Into<PathBuf>) -> TextureHandle {
+        let handle = TextureHandle(self.next_texture_index);
+        self.next_texture_index += 1;
+
+        self.resources.push(ResourceDecl {
+            handle: ResourceHandle::Texture(handle),
+            name: format!("vid{}", handle.0),
+            kind: ResourceKind::Video { path: path.into() },
+        });
+
+        handle
+    }
+
+    /// Declare an external video feed received over NDI from the source
+    /// named `source` (e.g. `"HOST (OBS)"`), sampled like an image so it
+    /// scales to whatever pass reads it. The last frame stays on screen
+    /// while the source is missing or stalls; until the first one arrives
+    /// the texture is black. Requires the `ndi` feature.
+    pub fn ndi_input(&mut self, source: impl Into<String>) -> TextureHandle {
+        let handle = TextureHandle(self.next_texture_index);
+        self.next_texture_index += 1;
+
+        self.resources.push(ResourceDecl {
+            handle: ResourceHandle::Texture(handle),
+            name: format!("ndi{}", handle.0),
+            kind: ResourceKind::NdiInput {
+                source: source.into(),
+            },
+        });
+
+        handle
+    }
+
+    fn declare_image(
+        &mut self,
+        path: PathBuf,
+        drop_target: bool,
+    ) -> TextureHandle {
         let handle = TextureHandle(self.next_texture_index);
         self.next_texture_index += 1;
 
         self.resources.push(ResourceDecl {
             handle: ResourceHandle::Texture(handle),
             name: format!("img{}", handle.0),
-            kind: ResourceKind::Image2d { path: path.into() },
+            kind: ResourceKind::Image2d { path, drop_target },
+        });
+
+        handle
+    }
+
+    /// Declare a zero-initialized GPU buffer of `size` bytes that persists
+    /// across frames, e.g. particle state written by a compute node and read
+    /// by a render node.
+    pub fn buffer(&mut self, size: u64) -> BufferHandle {
+        let handle = BufferHandle(self.next_buffer_index);
+        self.next_buffer_index += 1;
+
+        self.resources.push(ResourceDecl {
+            handle: ResourceHandle::Buffer(handle),
+            name: format!("buf{}", handle.0),
+            kind: ResourceKind::Buffer { size },
         });
 
         handle
@@ -163,9 +359,25 @@ impl GraphBuilder {
             shader_path: None,
             meshes: Vec::new(),
             reads: Vec::new(),
+            scale: 1.0,
+            instances: InstanceCount::Fixed(1),
+            depth: None,
         }
     }
 
+    /// A render node that draws the unit quad once per entry of
+    /// `instances`, passing each [`Instance`](instancing::Instance) to the
+    /// vertex shader (see [`instancing`] for the layout). The count follows
+    /// the data, so primitives can come and go every frame.
+    pub fn add_instanced(
+        &mut self,
+        instances: &Instances,
+    ) -> RenderNodeBuilder<'_> {
+        let mut node = self.render().mesh(instancing::corner_mesh());
+        node.instances = InstanceCount::Instanced(instances.clone());
+        node
+    }
+
     pub fn compute(&mut self) -> ComputeNodeBuilder<'_> {
         let index = self.next_compute_node_index;
         self.next_compute_node_index += 1;
@@ -174,10 +386,36 @@ impl GraphBuilder {
             builder: self,
             name: format!("compute_{}", index),
             shader_path: None,
-            read_write: None,
+            bindings: Vec::new(),
+            workgroups: Workgroups::Resolution,
+            scale: 1.0,
         }
     }
 
+    /// Shorthand for a [`Self::compute`] node that runs `shader_path` over
+    /// `bindings`, e.g. a particle simulation writing the buffer a later
+    /// render node reads. Use [`Self::compute`] for a scaled node.
+    pub fn add_compute_pass(
+        &mut self,
+        shader_path: impl Into<PathBuf>,
+        workgroups: Workgroups,
+        bindings: impl IntoIterator<Item = ComputeBinding>,
+    ) {
+        let mut node = self.compute().shader(shader_path);
+        node.bindings.extend(bindings);
+        node.workgroups = workgroups;
+        node.dispatch();
+    }
+
+    /// Render every offscreen pass at `scale` times the window resolution,
+    /// e.g. 0.5 for a cheap low-res graph or 2.0 for supersampling. Combines
+    /// multiplicatively with per-node scales. The final present to the surface
+    /// always happens at native resolution.
+    pub fn scale(&mut self, scale: f32) -> &mut Self {
+        self.scale = Some(scale);
+        self
+    }
+
     pub fn present(&mut self, source: TextureHandle) -> &mut Self {
         self.nodes.push(NodeSpec::Present { source });
         self
@@ -187,6 +425,7 @@ impl GraphBuilder {
         GraphSpec {
             resources: self.resources,
             nodes: self.nodes,
+            scale: self.scale.unwrap_or(1.0),
         }
     }
 }
@@ -197,6 +436,9 @@ pub struct RenderNodeBuilder<'a> {
     shader_path: Option<PathBuf>,
     meshes: Vec<Mesh>,
     reads: Vec<RenderRead>,
+    scale: f32,
+    instances: InstanceCount,
+    depth: Option<DepthOptions>,
 }
 
 impl RenderNodeBuilder<'_> {
@@ -215,6 +457,35 @@ impl RenderNodeBuilder<'_> {
         self
     }
 
+    /// Allocate this node's target texture at `scale` times the window
+    /// resolution. Has no effect when rendering directly to the surface.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Draw each mesh `count` times. Use `@builtin(instance_index)` in the
+    /// vertex shader to tell instances apart.
+    pub fn instances(mut self, count: u32) -> Self {
+        self.instances = InstanceCount::Fixed(count);
+        self
+    }
+
+    /// Like [`Self::instances`] but reads the count from a hub control each
+    /// frame, so it can change without rebuilding the pipeline
+    pub fn instances_from(mut self, control: impl Into<String>) -> Self {
+        self.instances = InstanceCount::Control(control.into());
+        self
+    }
+
+    /// Depth test this node's geometry, e.g.
+    /// `.depth(DepthOptions::default())` for a 3D mesh whose near faces
+    /// should hide far ones. Vertex shaders output depth in `position.z`.
+    pub fn depth(mut self, options: DepthOptions) -> Self {
+        self.depth = Some(options);
+        self
+    }
+
     pub fn to(self, target: TextureHandle) {
         self.finish(RenderTarget::Texture(target));
     }
@@ -237,6 +508,9 @@ impl RenderNodeBuilder<'_> {
             meshes: self.meshes,
             reads: self.reads,
             write,
+            scale: self.scale,
+            instances: self.instances,
+            depth: self.depth,
         }));
     }
 }
@@ -245,7 +519,9 @@ pub struct ComputeNodeBuilder<'a> {
     builder: &'a mut GraphBuilder,
     name: String,
     shader_path: Option<PathBuf>,
-    read_write: Option<TextureHandle>,
+    bindings: Vec<ComputeBinding>,
+    workgroups: Workgroups,
+    scale: f32,
 }
 
 impl ComputeNodeBuilder<'_> {
@@ -254,8 +530,22 @@ impl ComputeNodeBuilder<'_> {
         self
     }
 
-    pub fn read_write(mut self, resource: TextureHandle) -> Self {
-        self.read_write = Some(resource);
+    pub fn read_write(mut self, resource: impl Into<ComputeBinding>) -> Self {
+        self.bindings.push(resource.into());
+        self
+    }
+
+    /// Dispatch a fixed number of workgroups instead of deriving the count
+    /// from resolution
+    pub fn workgroups(mut self, x: u32, y: u32, z: u32) -> Self {
+        self.workgroups = Workgroups::Fixed([x, y, z]);
+        self
+    }
+
+    /// Allocate `read_write` textures at `scale` times the window
+    /// resolution; workgroup counts follow the scaled size.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
         self
     }
 
@@ -264,14 +554,16 @@ impl ComputeNodeBuilder<'_> {
             panic!("compute node '{}' missing shader", self.name)
         });
 
-        let read_write = self.read_write.unwrap_or_else(|| {
-            panic!("compute node '{}' missing read_write target", self.name)
-        });
+        if self.bindings.is_empty() {
+            panic!("compute node '{}' missing read_write target", self.name);
+        }
 
         self.builder.nodes.push(NodeSpec::Compute(ComputeNodeSpec {
             name: self.name,
             shader_path,
-            read_write,
+            bindings: self.bindings,
+            workgroups: self.workgroups,
+            scale: self.scale,
         }));
     }
 }
@@ -0,0 +1,188 @@
+use std::fmt;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Container formats recognized from a file's leading bytes. Only PNG can be
+/// decoded; the rest are detected so errors can name what was dropped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    WebP,
+    Tiff,
+}
+
+impl ImageFormat {
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if bytes.starts_with(b"BM") {
+            Some(Self::Bmp)
+        } else if bytes.len() >= 12
+            && &bytes[..4] == b"RIFF"
+            && &bytes[8..12] == b"WEBP"
+        {
+            Some(Self::WebP)
+        } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+            Some(Self::Tiff)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Gif => "GIF",
+            Self::Bmp => "BMP",
+            Self::WebP => "WebP",
+            Self::Tiff => "TIFF",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 8-bit RGBA pixels ready for upload to an sRGB texture
+#[derive(Clone, PartialEq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl fmt::Debug for DecodedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodedImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("bytes", &self.rgba.len())
+            .finish()
+    }
+}
+
+pub fn load_image(path: &Path) -> Result<DecodedImage, String> {
+    let bytes = fs::read(path).map_err(|err| {
+        format!("failed to read '{}': {}", path.display(), err)
+    })?;
+    decode_image(&bytes).map_err(|err| {
+        format!("failed to decode '{}': {}", path.display(), err)
+    })
+}
+
+pub fn decode_image(bytes: &[u8]) -> Result<DecodedImage, String> {
+    match ImageFormat::detect(bytes) {
+        Some(ImageFormat::Png) => decode_png(bytes),
+        Some(format) => Err(format!(
+            "{} images are not supported (expected PNG)",
+            format
+        )),
+        None => Err("unrecognized image format (expected PNG)".to_string()),
+    }
+}
+
+fn decode_png(bytes: &[u8]) -> Result<DecodedImage, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    // Expand palettes, low bit depths and tRNS chunks, and strip 16-bit
+    // channels so only the 8-bit gray/RGB(A) layouts remain.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|err| format!("invalid PNG header: {}", err))?;
+    let output_buffer_size = reader
+        .output_buffer_size()
+        .ok_or_else(|| "PNG is too large to decode".to_string())?;
+    let mut buf = vec![0; output_buffer_size];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|err| format!("failed to read PNG frame: {}", err))?;
+    let src = &buf[..info.buffer_size()];
+
+    let rgba = match (info.color_type, info.bit_depth) {
+        (png::ColorType::Rgba, png::BitDepth::Eight) => src.to_vec(),
+        (png::ColorType::Rgb, png::BitDepth::Eight) => src
+            .chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 255])
+            .collect(),
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => src
+            .chunks_exact(2)
+            .flat_map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => {
+            src.iter().flat_map(|&v| [v, v, v, 255]).collect()
+        }
+        (color_type, bit_depth) => {
+            return Err(format!(
+                "unsupported PNG layout {:?} {:?}",
+                color_type, bit_depth
+            ));
+        }
+    };
+
+    Ok(DecodedImage {
+        width: info.width,
+        height: info.height,
+        rgba,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_png(
+        width: u32,
+        height: u32,
+        color: png::ColorType,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().expect("png header");
+        writer.write_image_data(data).expect("png data");
+        writer.finish().expect("png finish");
+        bytes
+    }
+
+    #[test]
+    fn decodes_in_memory_rgb_png_to_rgba() {
+        let bytes =
+            encode_png(2, 1, png::ColorType::Rgb, &[255, 0, 0, 0, 0, 255]);
+
+        assert_eq!(ImageFormat::detect(&bytes), Some(ImageFormat::Png));
+        let image = decode_image(&bytes).expect("decode png");
+
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.rgba, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn expands_grayscale_png() {
+        let bytes = encode_png(1, 1, png::ColorType::GrayscaleAlpha, &[7, 9]);
+
+        let image = decode_image(&bytes).expect("decode png");
+
+        assert_eq!(image.rgba, vec![7, 7, 7, 9]);
+    }
+
+    #[test]
+    fn names_detected_but_unsupported_formats() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0, 0];
+
+        let err = decode_image(&jpeg).expect_err("jpeg is unsupported");
+
+        assert!(err.contains("JPEG"), "{}", err);
+        assert!(decode_image(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Draw many points, lines or quads in one draw call. A sketch keeps an
+//! [`Instances`] handle, rewrites it (typically in `Sketch::update`) and a
+//! node created with [`GraphBuilder::add_instanced`] draws one unit quad per
+//! entry, uploading the data only when it changed.
+//!
+//! The node's vertex shader receives the quad corner at location 0 and the
+//! [`Instance`] attributes at locations 1-4:
+//!
+//! ```wgsl
+//! @vertex
+//! fn vs_main(
+//!     @location(0) corner: vec2f,
+//!     @location(1) position: vec2f,
+//!     @location(2) size: vec2f,
+//!     @location(3) rotation: f32,
+//!     @location(4) color: vec4f,
+//! ) -> VsOut {
+//!     let c = cos(rotation);
+//!     let s = sin(rotation);
+//!     let p = corner * size;
+//!     let offset = vec2f(p.x * c - p.y * s, p.x * s + p.y * c);
+//!     var out: VsOut;
+//!     out.position = vec4f(position + offset, 0.0, 1.0);
+//!     out.color = color;
+//!     return out;
+//! }
+//! ```
+//!
+//! [`GraphBuilder::add_instanced`]: crate::graph::GraphBuilder::add_instanced
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::mesh::Mesh;
+
+const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+    1 => Float32x2,
+    2 => Float32x2,
+    3 => Float32,
+    4 => Float32x4,
+];
+
+/// One primitive: a quad of `size` centered on `position` and rotated by
+/// `rotation` radians counterclockwise. Units are whatever the shader makes
+/// of them, e.g. clip space.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Instance {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub rotation: f32,
+    pub color: [f32; 4],
+}
+
+impl Instance {
+    /// A `size` wide square. Shaders can round it off by discarding
+    /// fragments whose corner is more than 0.5 from the center.
+    pub fn point(position: [f32; 2], size: f32, color: [f32; 4]) -> Self {
+        Self::quad(position, [size, size], color)
+    }
+
+    /// A segment from `start` to `end`, `width` thick
+    pub fn line(
+        start: [f32; 2],
+        end: [f32; 2],
+        width: f32,
+        color: [f32; 4],
+    ) -> Self {
+        let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+        Self {
+            position: [start[0] + dx * 0.5, start[1] + dy * 0.5],
+            size: [dx.hypot(dy), width],
+            rotation: dy.atan2(dx),
+            color,
+        }
+    }
+
+    pub fn quad(position: [f32; 2], size: [f32; 2], color: [f32; 4]) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            color,
+        }
+    }
+}
+
+/// Instance data shared between a sketch and the node drawing it. Clones
+/// refer to the same data.
+#[derive(Clone, Default)]
+pub struct Instances {
+    shared: Arc<Mutex<InstanceData>>,
+}
+
+#[derive(Default)]
+struct InstanceData {
+    instances: Vec<Instance>,
+    // Bumped on every change so unchanged data isn't uploaded again
+    generation: u64,
+}
+
+impl Instances {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace every instance, reusing the existing allocation
+    pub fn set(&self, instances: impl IntoIterator<Item = Instance>) {
+        self.update(|current| {
+            current.clear();
+            current.extend(instances);
+        });
+    }
+
+    /// Edit the instances in place
+    pub fn update(&self, edit: impl FnOnce(&mut Vec<Instance>)) {
+        let mut data = self.shared.lock().unwrap();
+        edit(&mut data.instances);
+        data.generation += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.lock().unwrap().instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn read<T>(
+        &self,
+        read: impl FnOnce(&[Instance], u64) -> T,
+    ) -> T {
+        let data = self.shared.lock().unwrap();
+        read(&data.instances, data.generation)
+    }
+}
+
+impl fmt::Debug for Instances {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instances")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl PartialEq for Instances {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+}
+
+/// The unit quad each instance is drawn with, corners at ±0.5
+pub(crate) fn corner_mesh() -> Mesh {
+    Mesh::positions2d([
+        [-0.5, -0.5],
+        [0.5, -0.5],
+        [-0.5, 0.5],
+        [-0.5, 0.5],
+        [0.5, -0.5],
+        [0.5, 0.5],
+    ])
+}
+
+pub(crate) fn instance_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    #[test]
+    fn line_spans_start_to_end() {
+        let line = Instance::line([0.0, 0.0], [0.0, 2.0], 0.1, [1.0; 4]);
+        assert_eq!(line.position, [0.0, 1.0]);
+        assert_close(line.size[0], 2.0, "length");
+        assert_close(line.size[1], 0.1, "thickness");
+        assert_close(line.rotation, FRAC_PI_2, "rotation");
+    }
+
+    #[test]
+    fn changes_bump_generation_and_clones_share_data() {
+        let instances = Instances::new();
+        let shared = instances.clone();
+        let before = instances.read(|_, generation| generation);
+
+        shared.set([Instance::point([0.0, 0.0], 1.0, [1.0; 4]); 3]);
+
+        assert_eq!(instances.len(), 3);
+        assert_eq!(instances, shared);
+        assert_ne!(instances.read(|_, generation| generation), before);
+        assert_ne!(instances, Instances::new());
+    }
+}
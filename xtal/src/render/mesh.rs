@@ -1,3 +1,5 @@
+use crate::polyline::{self, StrokeOptions};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MeshVertexKind {
     Position2D,
@@ -26,6 +28,13 @@ impl Mesh {
         Self::Positions2D(vertices.into())
     }
 
+    /// Triangles covering `points` drawn as a thick line
+    pub fn stroke(points: &[[f32; 2]], options: &StrokeOptions) -> Self {
+        Self::Positions2D(polyline::triangle_list(&polyline::stroke(
+            points, options,
+        )))
+    }
+
     pub fn positions3d(vertices: impl Into<Vec<[f32; 3]>>) -> Self {
         Self::Positions3D(vertices.into())
     }
@@ -1,6 +1,16 @@
+pub mod camera;
 pub mod frame;
 pub mod gpu;
 pub mod graph;
+pub mod image;
+pub mod instancing;
 pub mod mesh;
+#[cfg(feature = "ndi")]
+pub mod ndi_input;
+pub mod pass_timing;
+pub mod polyline;
 pub mod shader_watch;
+pub mod text;
 pub mod uniforms;
+#[cfg(feature = "video")]
+pub mod video;
@@ -0,0 +1,158 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::io::ndi::{NdiReceiver, ReceivedVideo};
+
+/// How long the receive thread waits for a frame before checking whether
+/// the input was dropped
+const CAPTURE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Where an [`NdiInput`] gets its frames, called on the receive thread.
+/// Returns `None` when no frame arrived within `timeout`.
+pub trait FrameSource: Send {
+    fn receive(&mut self, timeout: Duration) -> Option<ReceivedVideo>;
+}
+
+impl FrameSource for NdiReceiver {
+    fn receive(&mut self, timeout: Duration) -> Option<ReceivedVideo> {
+        self.capture_video(timeout)
+    }
+}
+
+/// An external video feed received on a background thread. Only the newest
+/// frame is kept, so a slow render thread skips frames instead of queueing
+/// them, and a source that stops sending leaves its last frame on screen.
+pub struct NdiInput {
+    source: String,
+    latest: Arc<Mutex<Option<ReceivedVideo>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl NdiInput {
+    /// Receive from the NDI source named `source`, e.g. `"HOST (OBS)"`
+    pub fn open(source: impl Into<String>) -> Result<Self, String> {
+        let source = source.into();
+        let receiver = NdiReceiver::new(&source)?;
+        Ok(Self::with_source(source, receiver))
+    }
+
+    pub fn with_source(
+        source: impl Into<String>,
+        mut frames: impl FrameSource + 'static,
+    ) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let running = Arc::new(AtomicBool::new(true));
+        {
+            let latest = latest.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                while running.load(Ordering::Acquire) {
+                    if let Some(frame) = frames.receive(CAPTURE_TIMEOUT) {
+                        *latest.lock().unwrap() = Some(frame);
+                    }
+                }
+            });
+        }
+
+        Self {
+            source: source.into(),
+            latest,
+            running,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The newest frame received since the last call, if any
+    pub fn take_frame(&self) -> Option<ReceivedVideo> {
+        self.latest.lock().unwrap().take()
+    }
+}
+
+impl Drop for NdiInput {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    use super::*;
+
+    struct MockSource {
+        frames: mpsc::Receiver<ReceivedVideo>,
+    }
+
+    impl FrameSource for MockSource {
+        fn receive(&mut self, timeout: Duration) -> Option<ReceivedVideo> {
+            self.frames.recv_timeout(timeout).ok()
+        }
+    }
+
+    fn frame(width: u32, height: u32, value: u8) -> ReceivedVideo {
+        ReceivedVideo {
+            width,
+            height,
+            rgba: vec![value; (width * height * 4) as usize],
+        }
+    }
+
+    fn wait_for_frame(input: &NdiInput) -> ReceivedVideo {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(frame) = input.take_frame() {
+                return frame;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for frame");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn receives_frames_as_they_arrive() {
+        let (tx, frames) = mpsc::channel();
+        let input = NdiInput::with_source("mock", MockSource { frames });
+        assert!(input.take_frame().is_none());
+
+        tx.send(frame(4, 2, 7)).unwrap();
+        let received = wait_for_frame(&input);
+        assert_eq!((received.width, received.height), (4, 2));
+        assert!(received.rgba.iter().all(|&byte| byte == 7));
+
+        // Nothing new until the source sends again
+        assert!(input.take_frame().is_none());
+
+        tx.send(frame(8, 8, 9)).unwrap();
+        let received = wait_for_frame(&input);
+        assert_eq!((received.width, received.height), (8, 8));
+    }
+
+    #[test]
+    fn keeps_only_the_newest_frame() {
+        let (tx, frames) = mpsc::channel();
+        for value in 1..=3 {
+            tx.send(frame(2, 2, value)).unwrap();
+        }
+        let input = NdiInput::with_source("mock", MockSource { frames });
+
+        // Frames 1 and 2 are overwritten before anything takes them
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while input.latest.lock().unwrap().as_ref().map(|f| f.rgba[0])
+            != Some(3)
+        {
+            assert!(Instant::now() < deadline, "timed out waiting for frame");
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(input.take_frame().map(|frame| frame.rgba[0]), Some(3));
+        assert!(input.take_frame().is_none());
+        drop(tx);
+    }
+}
@@ -0,0 +1,300 @@
+//! Per-pass GPU timing for finding which graph node is expensive. With the
+//! `timing` feature, [`crate::gpu::CompiledGraph`] writes a timestamp query
+//! around every pass and reads the results back a few frames later without
+//! waiting on the GPU. Adapters without timestamp query support simply report
+//! nothing.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "timing")]
+pub use timer::PassTimer;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PassTiming {
+    pub name: String,
+    pub milliseconds: f32,
+}
+
+/// GPU time of each pass in execution order. `total_milliseconds` spans from
+/// the first pass starting to the last one ending, so it also counts any gaps
+/// between passes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PassTimings {
+    pub passes: Vec<PassTiming>,
+    pub total_milliseconds: f32,
+}
+
+impl PassTimings {
+    /// Build timings from resolved `(begin, end)` timestamp pairs, one per
+    /// name. `period` is nanoseconds per tick, see
+    /// [`wgpu::Queue::get_timestamp_period`].
+    pub fn from_timestamps(
+        names: &[String],
+        timestamps: &[u64],
+        period: f32,
+    ) -> Self {
+        let to_milliseconds =
+            |ticks: u64| (ticks as f64 * period as f64 / 1_000_000.0) as f32;
+
+        let passes = names
+            .iter()
+            .zip(timestamps.chunks_exact(2))
+            .map(|(name, pair)| PassTiming {
+                name: name.clone(),
+                milliseconds: to_milliseconds(pair[1].saturating_sub(pair[0])),
+            })
+            .collect();
+
+        let total_milliseconds = match (timestamps.first(), timestamps.last()) {
+            (Some(first), Some(last)) => {
+                to_milliseconds(last.saturating_sub(*first))
+            }
+            _ => 0.0,
+        };
+
+        Self {
+            passes,
+            total_milliseconds,
+        }
+    }
+}
+
+#[cfg(feature = "timing")]
+mod timer {
+    use std::sync::{Arc, Mutex};
+
+    use super::PassTimings;
+
+    // Readbacks in flight at once. A frame's timestamps are only read once
+    // the GPU is done with it, so when every buffer is still busy that
+    // frame's sample is dropped rather than waited for.
+    const READBACK_BUFFERS: usize = 3;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum ReadbackState {
+        Free,
+        // Copy encoded into a frame that may not have been submitted yet
+        Encoded,
+        Mapping,
+        Mapped,
+    }
+
+    struct Readback {
+        buffer: wgpu::Buffer,
+        state: Arc<Mutex<ReadbackState>>,
+    }
+
+    /// Timestamp queries for every pass of one compiled graph
+    pub struct PassTimer {
+        names: Vec<String>,
+        period: f32,
+        query_set: wgpu::QuerySet,
+        resolve_buffer: wgpu::Buffer,
+        readbacks: Vec<Readback>,
+        latest: Option<PassTimings>,
+    }
+
+    impl PassTimer {
+        /// `None` when the device was created without
+        /// [`wgpu::Features::TIMESTAMP_QUERY`] or there is nothing to time
+        pub fn new(
+            device: &wgpu::Device,
+            queue: &wgpu::Queue,
+            names: Vec<String>,
+        ) -> Option<Self> {
+            if names.is_empty()
+                || !device.features().contains(wgpu::Features::TIMESTAMP_QUERY)
+            {
+                return None;
+            }
+
+            let count = (names.len() * 2) as u32;
+            let size = count as u64 * wgpu::QUERY_SIZE as u64;
+
+            let query_set =
+                device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("xtal-pass-timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count,
+                });
+            let resolve_buffer =
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("xtal-pass-timestamps-resolve"),
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE
+                        | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                });
+            let readbacks = (0..READBACK_BUFFERS)
+                .map(|_| Readback {
+                    buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("xtal-pass-timestamps-readback"),
+                        size,
+                        usage: wgpu::BufferUsages::MAP_READ
+                            | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                    state: Arc::new(Mutex::new(ReadbackState::Free)),
+                })
+                .collect();
+
+            Some(Self {
+                names,
+                period: queue.get_timestamp_period(),
+                query_set,
+                resolve_buffer,
+                readbacks,
+                latest: None,
+            })
+        }
+
+        pub fn render_writes(
+            &self,
+            index: usize,
+        ) -> wgpu::RenderPassTimestampWrites<'_> {
+            let (begin, end) = query_indices(index);
+            wgpu::RenderPassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(begin),
+                end_of_pass_write_index: Some(end),
+            }
+        }
+
+        pub fn compute_writes(
+            &self,
+            index: usize,
+        ) -> wgpu::ComputePassTimestampWrites<'_> {
+            let (begin, end) = query_indices(index);
+            wgpu::ComputePassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(begin),
+                end_of_pass_write_index: Some(end),
+            }
+        }
+
+        /// Encode copying this frame's timestamps into a free readback
+        /// buffer. Call after every pass has been encoded.
+        pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+            let Some(readback) = self.readbacks.iter().find(|readback| {
+                *readback.state.lock().unwrap() == ReadbackState::Free
+            }) else {
+                return;
+            };
+
+            let count = (self.names.len() * 2) as u32;
+            encoder.resolve_query_set(
+                &self.query_set,
+                0..count,
+                &self.resolve_buffer,
+                0,
+            );
+            encoder.copy_buffer_to_buffer(
+                &self.resolve_buffer,
+                0,
+                &readback.buffer,
+                0,
+                self.resolve_buffer.size(),
+            );
+            *readback.state.lock().unwrap() = ReadbackState::Encoded;
+        }
+
+        /// Start mapping readbacks encoded into earlier (by now submitted)
+        /// frames and collect any that finished, without blocking. Call
+        /// before encoding the next frame.
+        pub fn collect(&mut self, device: &wgpu::Device) {
+            for readback in &self.readbacks {
+                {
+                    let mut state = readback.state.lock().unwrap();
+                    if *state != ReadbackState::Encoded {
+                        continue;
+                    }
+                    *state = ReadbackState::Mapping;
+                }
+                // Mapping errors can call back immediately, so the lock must
+                // be released first
+                let callback_state = readback.state.clone();
+                readback.buffer.slice(..).map_async(
+                    wgpu::MapMode::Read,
+                    move |result| {
+                        *callback_state.lock().unwrap() = match result {
+                            Ok(()) => ReadbackState::Mapped,
+                            Err(_) => ReadbackState::Free,
+                        };
+                    },
+                );
+            }
+
+            let _ = device.poll(wgpu::PollType::Poll);
+
+            for readback in &self.readbacks {
+                let mut state = readback.state.lock().unwrap();
+                if *state != ReadbackState::Mapped {
+                    continue;
+                }
+                let timestamps: Vec<u64> = {
+                    let data = readback.buffer.slice(..).get_mapped_range();
+                    bytemuck::cast_slice(&data).to_vec()
+                };
+                readback.buffer.unmap();
+                *state = ReadbackState::Free;
+
+                self.latest = Some(PassTimings::from_timestamps(
+                    &self.names,
+                    &timestamps,
+                    self.period,
+                ));
+            }
+        }
+
+        /// The most recently collected timings, if any arrived since the
+        /// last call
+        pub fn take_latest(&mut self) -> Option<PassTimings> {
+            self.latest.take()
+        }
+    }
+
+    fn query_indices(index: usize) -> (u32, u32) {
+        let begin = (index * 2) as u32;
+        (begin, begin + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_timestamp_pairs_to_milliseconds() {
+        let names = vec!["blur".to_string(), "composite".to_string()];
+        let timestamps = [1_000, 3_000, 4_000, 4_500];
+
+        let timings =
+            PassTimings::from_timestamps(&names, &timestamps, 1_000.0);
+
+        assert_eq!(
+            timings.passes,
+            vec![
+                PassTiming {
+                    name: "blur".to_string(),
+                    milliseconds: 2.0,
+                },
+                PassTiming {
+                    name: "composite".to_string(),
+                    milliseconds: 0.5,
+                },
+            ]
+        );
+        assert_eq!(timings.total_milliseconds, 3.5);
+    }
+
+    #[test]
+    fn out_of_order_timestamps_are_not_negative() {
+        let names = vec!["pass".to_string()];
+        let timings = PassTimings::from_timestamps(&names, &[10, 5], 1.0);
+
+        assert_eq!(timings.passes[0].milliseconds, 0.0);
+        assert_eq!(timings.total_milliseconds, 0.0);
+    }
+}
@@ -0,0 +1,278 @@
+//! Tessellates polylines into thick lines. [`stroke`] returns a triangle
+//! strip with a left/right vertex pair per point (two pairs at bevelled
+//! joins); [`triangle_list`] converts it for [`Mesh::positions2d`], which is
+//! what [`Mesh::stroke`] does in one step. Edges are hard, so pair with
+//! `SketchConfig::msaa_samples` for antialiasing.
+//!
+//! [`Mesh::positions2d`]: crate::mesh::Mesh::positions2d
+//! [`Mesh::stroke`]: crate::mesh::Mesh::stroke
+
+// Points closer than this are merged so zero-length segments don't produce
+// undefined normals
+const EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineJoin {
+    /// Extend both edges until they meet, falling back to a bevel past the
+    /// miter limit
+    #[default]
+    Miter,
+    /// Cut the corner off
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeOptions {
+    pub width: f32,
+    pub join: LineJoin,
+    /// Longest miter allowed, as a multiple of half the width. Sharp angles
+    /// produce long miters; 4 bevels anything sharper than about 29 degrees.
+    pub miter_limit: f32,
+    /// Join the last point back to the first
+    pub closed: bool,
+}
+
+impl StrokeOptions {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            closed: false,
+        }
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+}
+
+/// Triangle strip covering `points` stroked with `options`. Repeated points
+/// are ignored; fewer than two distinct points produce nothing.
+pub fn stroke(points: &[[f32; 2]], options: &StrokeOptions) -> Vec<[f32; 2]> {
+    let mut points = dedup(points);
+    if options.closed
+        && points.len() > 2
+        && distance(points[0], points[points.len() - 1]) <= EPSILON
+    {
+        points.pop();
+    }
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let half_width = options.width * 0.5;
+    let closed = options.closed && points.len() > 2;
+    let count = points.len();
+    let mut strip = Vec::with_capacity(count * 4 + 2);
+
+    for (index, &point) in points.iter().enumerate() {
+        let previous = if index > 0 {
+            Some(points[index - 1])
+        } else if closed {
+            Some(points[count - 1])
+        } else {
+            None
+        };
+        let next = if index + 1 < count {
+            Some(points[index + 1])
+        } else if closed {
+            Some(points[0])
+        } else {
+            None
+        };
+
+        let incoming = previous.map(|previous| normal(previous, point));
+        let outgoing = next.map(|next| normal(point, next));
+        match (incoming, outgoing) {
+            (Some(incoming), Some(outgoing)) => {
+                push_join(
+                    &mut strip, point, incoming, outgoing, half_width, options,
+                );
+            }
+            (Some(normal), None) | (None, Some(normal)) => {
+                push_pair(&mut strip, point, normal, half_width);
+            }
+            (None, None) => unreachable!("at least two points remain"),
+        }
+    }
+
+    if closed {
+        strip.push(strip[0]);
+        strip.push(strip[1]);
+    }
+
+    strip
+}
+
+/// Triangles of a triangle strip, for pipelines drawing triangle lists
+pub fn triangle_list(strip: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    strip
+        .windows(3)
+        .flat_map(|triangle| triangle.iter().copied())
+        .collect()
+}
+
+fn push_join(
+    strip: &mut Vec<[f32; 2]>,
+    point: [f32; 2],
+    incoming: [f32; 2],
+    outgoing: [f32; 2],
+    half_width: f32,
+    options: &StrokeOptions,
+) {
+    let sum = [incoming[0] + outgoing[0], incoming[1] + outgoing[1]];
+    let sum_length = sum[0].hypot(sum[1]);
+
+    // A path doubling back on itself has no miter
+    if options.join == LineJoin::Miter && sum_length > EPSILON {
+        let miter = [sum[0] / sum_length, sum[1] / sum_length];
+        let scale = 1.0 / (miter[0] * outgoing[0] + miter[1] * outgoing[1]);
+        if scale <= options.miter_limit {
+            push_pair(strip, point, miter, half_width * scale);
+            return;
+        }
+    }
+
+    push_pair(strip, point, incoming, half_width);
+    push_pair(strip, point, outgoing, half_width);
+}
+
+fn push_pair(
+    strip: &mut Vec<[f32; 2]>,
+    point: [f32; 2],
+    normal: [f32; 2],
+    offset: f32,
+) {
+    strip.push([point[0] + normal[0] * offset, point[1] + normal[1] * offset]);
+    strip.push([point[0] - normal[0] * offset, point[1] - normal[1] * offset]);
+}
+
+// Unit normal to the left of the direction from `from` to `to`
+fn normal(from: [f32; 2], to: [f32; 2]) -> [f32; 2] {
+    let length = distance(from, to);
+    [(from[1] - to[1]) / length, (to[0] - from[0]) / length]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (b[0] - a[0]).hypot(b[1] - a[1])
+}
+
+fn dedup(points: &[[f32; 2]]) -> Vec<[f32; 2]> {
+    let mut unique: Vec<[f32; 2]> = Vec::with_capacity(points.len());
+    for &point in points {
+        if unique
+            .last()
+            .is_none_or(|&last| distance(last, point) > EPSILON)
+        {
+            unique.push(point);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::assert_close;
+
+    // Right then up: (0, 0) -> (1, 0) -> (1, 1)
+    const ELBOW: [[f32; 2]; 3] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+
+    fn assert_points(actual: &[[f32; 2]], expected: &[[f32; 2]]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert_close(actual[0], expected[0], "x");
+            assert_close(actual[1], expected[1], "y");
+        }
+    }
+
+    #[test]
+    fn miter_join_meets_at_the_offset_corners() {
+        let strip = stroke(&ELBOW, &StrokeOptions::new(0.2));
+        assert_points(
+            &strip,
+            &[
+                [0.0, 0.1],
+                [0.0, -0.1],
+                [0.9, 0.1],
+                [1.1, -0.1],
+                [0.9, 1.0],
+                [1.1, 1.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn bevel_join_emits_a_pair_per_segment() {
+        let options = StrokeOptions::new(0.2).join(LineJoin::Bevel);
+        let strip = stroke(&ELBOW, &options);
+        assert_points(
+            &strip,
+            &[
+                [0.0, 0.1],
+                [0.0, -0.1],
+                [1.0, 0.1],
+                [1.0, -0.1],
+                [0.9, 0.0],
+                [1.1, 0.0],
+                [0.9, 1.0],
+                [1.1, 1.0],
+            ],
+        );
+    }
+
+    #[test]
+    fn sharp_angles_past_the_miter_limit_bevel() {
+        let hairpin = [[0.0, 0.0], [1.0, 0.0], [0.0, 0.1]];
+        let strip = stroke(&hairpin, &StrokeOptions::new(0.2));
+        assert_eq!(strip.len(), 8);
+        assert!(strip.iter().all(|&[x, _]| x <= 1.1 + 1e-4));
+    }
+
+    #[test]
+    fn closed_path_joins_every_corner_and_wraps() {
+        let square = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let options = StrokeOptions::new(0.2).closed(true);
+        let strip = stroke(&square, &options);
+        assert_eq!(strip.len(), 10);
+        assert_points(&strip[..2], &[[0.1, 0.1], [-0.1, -0.1]]);
+        assert_points(&strip[8..], &strip[..2]);
+    }
+
+    #[test]
+    fn repeated_points_are_ignored() {
+        let doubled =
+            [[0.0, 0.0], [0.0, 0.0], [1.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+        let options = StrokeOptions::new(0.2);
+        assert_eq!(stroke(&doubled, &options), stroke(&ELBOW, &options));
+        assert!(stroke(&[[0.5, 0.5]; 3], &options).is_empty());
+    }
+
+    #[test]
+    fn triangle_list_unrolls_the_strip() {
+        let strip = [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]];
+        assert_eq!(
+            triangle_list(&strip),
+            [
+                [0.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0],
+                [1.0, 1.0],
+            ]
+        );
+    }
+}
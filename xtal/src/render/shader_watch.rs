@@ -3,21 +3,25 @@ use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use log::{info, trace, warn};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 
+// Editors often save in several steps (truncate, write, rename); wait for the
+// file to settle before reporting a change so we compile the final contents.
+const SHADER_CHANGE_DEBOUNCE: Duration = Duration::from_millis(150);
+
 pub struct ShaderWatch {
-    changed: Arc<AtomicBool>,
+    changed_at: Arc<Mutex<Option<Instant>>>,
     _watcher: RecommendedWatcher,
 }
 
 impl ShaderWatch {
     pub fn start(path: PathBuf) -> Result<Self, notify::Error> {
-        let changed = Arc::new(AtomicBool::new(false));
-        let changed_flag = changed.clone();
+        let changed_at = Arc::new(Mutex::new(None));
+        let changed_at_writer = changed_at.clone();
         let initial_hash = file_content_hash(&path).ok();
         let last_loaded_hash = Arc::new(Mutex::new(initial_hash));
         let shader_path = path.clone();
@@ -83,7 +87,9 @@ impl ShaderWatch {
                     *guard = Some(file_hash);
                 }
 
-                changed_flag.store(true, Ordering::SeqCst);
+                if let Ok(mut guard) = changed_at_writer.lock() {
+                    *guard = Some(Instant::now());
+                }
                 info!("detected shader change: {}", shader_path.display());
             }
         })?;
@@ -91,13 +97,24 @@ impl ShaderWatch {
         watcher.watch(&watch_dir, RecursiveMode::NonRecursive)?;
 
         Ok(Self {
-            changed,
+            changed_at,
             _watcher: watcher,
         })
     }
 
+    /// True once per change, after the file has been quiet for the debounce
+    /// window
     pub fn take_changed(&self) -> bool {
-        self.changed.swap(false, Ordering::SeqCst)
+        let Ok(mut guard) = self.changed_at.lock() else {
+            return false;
+        };
+
+        if guard.is_some_and(|at| at.elapsed() >= SHADER_CHANGE_DEBOUNCE) {
+            *guard = None;
+            return true;
+        }
+
+        false
     }
 }
 
@@ -126,7 +143,7 @@ fn shader_changed(event: &Event, shader_path: &Path) -> bool {
         .any(|path| path_matches_target(path, shader_path))
 }
 
-fn path_matches_target(
+pub(crate) fn path_matches_target(
     path: &std::path::Path,
     target: &std::path::Path,
 ) -> bool {
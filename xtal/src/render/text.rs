@@ -0,0 +1,714 @@
+//! Signed distance field text for labels, readouts and titles. Sketches
+//! queue [`Text`] on the [`Frame`] from `Sketch::view` and the runtime draws
+//! it over the finished frame with a [`TextRenderer`]. Glyphs come from a
+//! bitmap [`Font`] turned into a distance field once at startup, so text
+//! stays sharp at any size.
+
+use std::collections::HashMap;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::frame::Frame;
+
+// Atlas texels per font pixel
+const SDF_SCALE: u32 = 8;
+// Font pixels of empty margin around each glyph, which is also how far the
+// distance field reaches outside the glyph
+const SDF_PADDING: u32 = 1;
+const ATLAS_COLUMNS: u32 = 16;
+
+const TEXT_WGSL: &str = r#"
+struct Viewport {
+    size: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> viewport: Viewport;
+
+@group(0) @binding(1)
+var atlas: texture_2d<f32>;
+
+@group(0) @binding(2)
+var atlas_sampler: sampler;
+
+struct VsOut {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+    @location(1) color: vec4f,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex: u32,
+    @location(0) rect: vec4f,
+    @location(1) uv_rect: vec4f,
+    @location(2) color: vec4f,
+) -> VsOut {
+    var corners = array<vec2f, 6>(
+        vec2f(0.0, 0.0),
+        vec2f(1.0, 0.0),
+        vec2f(0.0, 1.0),
+        vec2f(0.0, 1.0),
+        vec2f(1.0, 0.0),
+        vec2f(1.0, 1.0),
+    );
+    let corner = corners[vertex];
+    let pixel = rect.xy + corner * rect.zw;
+    let clip = pixel / viewport.size.xy * 2.0 - 1.0;
+
+    var out: VsOut;
+    out.position = vec4f(clip.x, -clip.y, 0.0, 1.0);
+    out.uv = mix(uv_rect.xy, uv_rect.zw, corner);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VsOut) -> @location(0) vec4f {
+    let distance = textureSample(atlas, atlas_sampler, in.uv).r - 0.5;
+    let width = max(fwidth(distance), 1e-4);
+    let coverage = smoothstep(-width, width, distance);
+    return vec4f(in.color.rgb, in.color.a * coverage);
+}
+"#;
+
+const GLYPH_ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+    0 => Float32x4,
+    1 => Float32x4,
+    2 => Float32x4,
+];
+
+/// A bitmap font. [`Font::default`] is an embedded 5x7 pixel font covering
+/// printable ASCII.
+#[derive(Clone, Debug)]
+pub struct Font {
+    width: u32,
+    height: u32,
+    glyphs: Vec<(char, Vec<u8>)>,
+}
+
+impl Font {
+    /// A font of `width` x `height` pixel glyphs, each given as one byte per
+    /// row, top to bottom, with the leftmost pixel in bit `width - 1`.
+    /// Glyphs can be at most 8 pixels wide.
+    pub fn from_rows(
+        width: u32,
+        height: u32,
+        glyphs: impl IntoIterator<Item = (char, Vec<u8>)>,
+    ) -> Self {
+        assert!(
+            (1..=8).contains(&width),
+            "font glyphs must be 1 to 8 pixels wide"
+        );
+        let glyphs = glyphs.into_iter().collect::<Vec<_>>();
+        for (glyph, rows) in &glyphs {
+            assert_eq!(
+                rows.len(),
+                height as usize,
+                "glyph '{}' needs {} rows",
+                glyph,
+                height
+            );
+        }
+        Self {
+            width,
+            height,
+            glyphs,
+        }
+    }
+
+    fn filled(&self, rows: &[u8], x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+        rows[y as usize] & (1 << (self.width as i32 - 1 - x)) != 0
+    }
+
+    /// Distance in font pixels from `point` to the glyph's outline, positive
+    /// inside. The glyph is treated as the union of its filled pixels.
+    fn signed_distance(&self, rows: &[u8], point: [f32; 2]) -> f32 {
+        let inside =
+            self.filled(rows, point[0].floor() as i32, point[1].floor() as i32);
+        let mut nearest = f32::MAX;
+
+        // Everything outside the glyph's bounds is empty too
+        if inside {
+            nearest = point[0]
+                .min(self.width as f32 - point[0])
+                .min(point[1])
+                .min(self.height as f32 - point[1]);
+        }
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.filled(rows, x, y) == inside {
+                    continue;
+                }
+                let dx = (x as f32 - point[0]).max(point[0] - (x + 1) as f32);
+                let dy = (y as f32 - point[1]).max(point[1] - (y + 1) as f32);
+                nearest = nearest.min(dx.max(0.0).hypot(dy.max(0.0)));
+            }
+        }
+
+        if inside { nearest } else { -nearest }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self::from_rows(
+            5,
+            7,
+            DEFAULT_GLYPHS
+                .iter()
+                .map(|(glyph, rows)| (*glyph, rows.to_vec())),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A string to draw. `position` is in physical pixels from the top-left of
+/// the window and marks the top of the first line; `align` decides whether
+/// lines start, are centered on, or end at its x. `size` is the height of a
+/// capital letter in pixels. Characters the font lacks are drawn as `?`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Text {
+    pub text: String,
+    pub position: [f32; 2],
+    pub size: f32,
+    pub color: [f32; 4],
+    pub align: TextAlign,
+}
+
+impl Text {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            position: [0.0, 0.0],
+            size: 16.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            align: TextAlign::Left,
+        }
+    }
+
+    pub fn position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct GlyphInstance {
+    /// x, y, width, height in pixels
+    rect: [f32; 4],
+    /// Top-left and bottom-right atlas coordinates
+    uv_rect: [f32; 4],
+    color: [f32; 4],
+}
+
+pub struct TextRenderer {
+    font_size: [u32; 2],
+    glyph_indices: HashMap<char, u32>,
+    atlas_size: [u32; 2],
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    viewport_buffer: wgpu::Buffer,
+    instance_buffer: Option<(wgpu::Buffer, usize)>,
+}
+
+impl TextRenderer {
+    /// A renderer drawing [`Font::default`] into `format` targets
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::with_font(device, queue, format, &Font::default())
+    }
+
+    pub fn with_font(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        font: &Font,
+    ) -> Self {
+        let (atlas, atlas_size) = build_atlas(font);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("xtal-text-atlas"),
+            size: wgpu::Extent3d {
+                width: atlas_size[0],
+                height: atlas_size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            &atlas,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(atlas_size[0]),
+                rows_per_image: Some(atlas_size[1]),
+            },
+            texture.size(),
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("xtal-text-sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let viewport_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("xtal-text-viewport"),
+            size: std::mem::size_of::<[f32; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("xtal-text-bind-group-layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("xtal-text-bind-group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: viewport_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let shader =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("xtal-text-shader"),
+                source: wgpu::ShaderSource::Wgsl(TEXT_WGSL.into()),
+            });
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("xtal-text-pipeline-layout"),
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("xtal-text-pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options:
+                        wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<GlyphInstance>()
+                            as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &GLYPH_ATTRIBUTES,
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options:
+                        wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let glyph_indices = font
+            .glyphs
+            .iter()
+            .enumerate()
+            .map(|(index, (glyph, _))| (*glyph, index as u32))
+            .collect();
+
+        Self {
+            font_size: [font.width, font.height],
+            glyph_indices,
+            atlas_size,
+            pipeline,
+            bind_group,
+            viewport_buffer,
+            instance_buffer: None,
+        }
+    }
+
+    /// Draw the text queued on `frame` over its surface view. `size` is the
+    /// surface size in pixels.
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        frame: &mut Frame,
+        size: [u32; 2],
+    ) {
+        let texts = frame.take_text();
+        if texts.is_empty() {
+            return;
+        }
+        let view = frame.surface_view.clone();
+        self.draw(device, queue, frame.encoder(), &view, size, &texts);
+    }
+
+    /// Draw `texts` over whatever `view` already holds
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: [u32; 2],
+        texts: &[Text],
+    ) {
+        let glyphs = texts
+            .iter()
+            .flat_map(|text| self.layout(text))
+            .collect::<Vec<_>>();
+        if glyphs.is_empty() {
+            return;
+        }
+
+        queue.write_buffer(
+            &self.viewport_buffer,
+            0,
+            bytemuck::cast_slice(&[size[0] as f32, size[1] as f32, 0.0, 0.0]),
+        );
+
+        let fits = self
+            .instance_buffer
+            .as_ref()
+            .is_some_and(|(_, capacity)| *capacity >= glyphs.len());
+        if !fits {
+            let capacity = glyphs.len().next_power_of_two();
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("xtal-text-glyphs"),
+                size: (capacity * std::mem::size_of::<GlyphInstance>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX
+                    | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            self.instance_buffer = Some((buffer, capacity));
+        }
+        let Some((buffer, _)) = self.instance_buffer.as_ref() else {
+            return;
+        };
+        queue.write_buffer(buffer, 0, bytemuck::cast_slice(&glyphs));
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("xtal-text"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, buffer.slice(..));
+        pass.draw(0..6, 0..glyphs.len() as u32);
+    }
+
+    fn layout(&self, text: &Text) -> Vec<GlyphInstance> {
+        let [font_width, font_height] = self.font_size.map(|size| size as f32);
+        let scale = text.size / font_height;
+        // One blank font pixel between glyphs and two between lines
+        let advance = (font_width + 1.0) * scale;
+        let line_height = (font_height + 2.0) * scale;
+        let padding = SDF_PADDING as f32 * scale;
+        let fallback = self.glyph_indices.get(&'?').copied();
+
+        let mut glyphs = Vec::new();
+        for (line_index, line) in text.text.lines().enumerate() {
+            let count = line.chars().count() as f32;
+            let width = (count * advance - scale).max(0.0);
+            let left = match text.align {
+                TextAlign::Left => text.position[0],
+                TextAlign::Center => text.position[0] - width * 0.5,
+                TextAlign::Right => text.position[0] - width,
+            };
+            let top = text.position[1] + line_index as f32 * line_height;
+
+            for (column, glyph) in line.chars().enumerate() {
+                if glyph == ' ' {
+                    continue;
+                }
+                let Some(index) =
+                    self.glyph_indices.get(&glyph).copied().or(fallback)
+                else {
+                    continue;
+                };
+                glyphs.push(GlyphInstance {
+                    rect: [
+                        left + column as f32 * advance - padding,
+                        top - padding,
+                        font_width * scale + padding * 2.0,
+                        font_height * scale + padding * 2.0,
+                    ],
+                    uv_rect: self.uv_rect(index),
+                    color: text.color,
+                });
+            }
+        }
+        glyphs
+    }
+
+    fn uv_rect(&self, index: u32) -> [f32; 4] {
+        let [cell_width, cell_height] = atlas_cell_size(self.font_size);
+        let x = (index % ATLAS_COLUMNS * cell_width) as f32;
+        let y = (index / ATLAS_COLUMNS * cell_height) as f32;
+        let [atlas_width, atlas_height] = self.atlas_size.map(|s| s as f32);
+        [
+            x / atlas_width,
+            y / atlas_height,
+            (x + cell_width as f32) / atlas_width,
+            (y + cell_height as f32) / atlas_height,
+        ]
+    }
+}
+
+fn atlas_cell_size(font_size: [u32; 2]) -> [u32; 2] {
+    font_size.map(|size| (size + SDF_PADDING * 2) * SDF_SCALE)
+}
+
+// Lay every glyph's distance field out in a grid of padded cells, encoding
+// the signed distance so 0.5 is the outline and 0/1 are a padding's width
+// outside/inside it
+fn build_atlas(font: &Font) -> (Vec<u8>, [u32; 2]) {
+    let [cell_width, cell_height] = atlas_cell_size([font.width, font.height]);
+    let rows = (font.glyphs.len() as u32).div_ceil(ATLAS_COLUMNS).max(1);
+    let size = [cell_width * ATLAS_COLUMNS, cell_height * rows];
+    let mut atlas = vec![0u8; (size[0] * size[1]) as usize];
+    let spread = SDF_PADDING as f32 * 2.0;
+
+    for (index, (_, glyph_rows)) in font.glyphs.iter().enumerate() {
+        let origin_x = index as u32 % ATLAS_COLUMNS * cell_width;
+        let origin_y = index as u32 / ATLAS_COLUMNS * cell_height;
+        for y in 0..cell_height {
+            for x in 0..cell_width {
+                let point = [x, y].map(|texel| {
+                    (texel as f32 + 0.5) / SDF_SCALE as f32 - SDF_PADDING as f32
+                });
+                let distance = font.signed_distance(glyph_rows, point);
+                let value = (0.5 + distance / spread).clamp(0.0, 1.0);
+                let offset = (origin_y + y) * size[0] + origin_x + x;
+                atlas[offset as usize] = (value * 255.0).round() as u8;
+            }
+        }
+    }
+
+    (atlas, size)
+}
+
+// Rows of the default 5x7 font, leftmost pixel in bit 4
+const DEFAULT_GLYPHS: [(char, [u8; 7]); 95] = [
+    (' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('!', [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04]),
+    ('"', [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00]),
+    ('#', [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a]),
+    ('$', [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04]),
+    ('%', [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03]),
+    ('&', [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d]),
+    ('\'', [0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    ('*', [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00]),
+    ('+', [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c]),
+    ('/', [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00]),
+    ('0', [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e]),
+    ('1', [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('2', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f]),
+    ('3', [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e]),
+    ('4', [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02]),
+    ('5', [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e]),
+    ('6', [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e]),
+    ('7', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e]),
+    ('9', [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c]),
+    (':', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00]),
+    (';', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08]),
+    ('<', [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02]),
+    ('=', [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00]),
+    ('>', [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08]),
+    ('?', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+    ('@', [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e]),
+    ('A', [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('B', [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e]),
+    ('C', [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e]),
+    ('D', [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c]),
+    ('E', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f]),
+    ('F', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10]),
+    ('G', [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f]),
+    ('H', [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('I', [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f]),
+    ('M', [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('P', [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10]),
+    ('Q', [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d]),
+    ('R', [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11]),
+    ('S', [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e]),
+    ('T', [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a]),
+    ('X', [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04]),
+    ('Z', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f]),
+    ('[', [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e]),
+    ('\\', [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00]),
+    (']', [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e]),
+    ('^', [0x04, 0x0a, 0x11, 0x00, 0x00, 0x00, 0x00]),
+    ('_', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f]),
+    ('`', [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00]),
+    ('a', [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f]),
+    ('b', [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e]),
+    ('c', [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e]),
+    ('d', [0x01, 0x01, 0x0d, 0x13, 0x11, 0x11, 0x0f]),
+    ('e', [0x00, 0x00, 0x0e, 0x11, 0x1f, 0x10, 0x0e]),
+    ('f', [0x06, 0x09, 0x08, 0x1c, 0x08, 0x08, 0x08]),
+    ('g', [0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x0e]),
+    ('h', [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11]),
+    ('i', [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e]),
+    ('j', [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0c]),
+    ('k', [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12]),
+    ('l', [0x0c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('m', [0x00, 0x00, 0x1a, 0x15, 0x15, 0x11, 0x11]),
+    ('n', [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11]),
+    ('o', [0x00, 0x00, 0x0e, 0x11, 0x11, 0x11, 0x0e]),
+    ('p', [0x00, 0x00, 0x1e, 0x11, 0x1e, 0x10, 0x10]),
+    ('q', [0x00, 0x00, 0x0d, 0x13, 0x0f, 0x01, 0x01]),
+    ('r', [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10]),
+    ('s', [0x00, 0x00, 0x0e, 0x10, 0x0e, 0x01, 0x1e]),
+    ('t', [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06]),
+    ('u', [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0d]),
+    ('v', [0x00, 0x00, 0x11, 0x11, 0x11, 0x0a, 0x04]),
+    ('w', [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0a]),
+    ('x', [0x00, 0x00, 0x11, 0x0a, 0x04, 0x0a, 0x11]),
+    ('y', [0x00, 0x00, 0x11, 0x11, 0x0f, 0x01, 0x0e]),
+    ('z', [0x00, 0x00, 0x1f, 0x02, 0x04, 0x08, 0x1f]),
+    ('{', [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02]),
+    ('|', [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('}', [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08]),
+    ('~', [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_font_covers_printable_ascii() {
+        let font = Font::default();
+        let glyphs = font.glyphs.iter().map(|(glyph, _)| *glyph);
+        assert!(glyphs.eq(' '..='~'));
+    }
+
+    #[test]
+    fn distance_is_positive_inside_and_grows_outward() {
+        let font = Font::default();
+        let rows = &font
+            .glyphs
+            .iter()
+            .find(|(glyph, _)| *glyph == '|')
+            .unwrap()
+            .1;
+
+        // The bar fills column 2
+        assert!(font.signed_distance(rows, [2.5, 3.5]) > 0.0);
+        let near = font.signed_distance(rows, [1.5, 3.5]);
+        let far = font.signed_distance(rows, [0.5, 3.5]);
+        assert!(near < 0.0 && far < near);
+    }
+}
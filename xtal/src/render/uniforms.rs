@@ -1,9 +1,14 @@
+use crate::camera::Camera2D;
 use crate::warn_once;
 use wgpu::util::DeviceExt;
 
 pub struct UniformBanks {
     data: Vec<[f32; 4]>,
     buffer: wgpu::Buffer,
+    mouse: [f32; 4],
+    mouse_buffer: wgpu::Buffer,
+    camera: [[f32; 4]; 4],
+    camera_buffer: wgpu::Buffer,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
 }
@@ -13,25 +18,56 @@ impl UniformBanks {
         assert!(banks > 0, "uniform bank count must be > 0");
 
         let data = vec![[0.0; 4]; banks];
+        let mouse = [0.0; 4];
+        let camera = Camera2D::default().view_projection(1.0);
+        let visibility = wgpu::ShaderStages::VERTEX
+            | wgpu::ShaderStages::FRAGMENT
+            | wgpu::ShaderStages::COMPUTE;
 
         let bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("xtal-uniform-banks-layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX
-                        | wgpu::ShaderStages::FRAGMENT
-                        | wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(
-                            (data.len() * std::mem::size_of::<[f32; 4]>())
-                                as u64,
-                        ),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                (data.len() * std::mem::size_of::<[f32; 4]>())
+                                    as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    // Reserved runtime inputs: mouse (x, y, buttons, 0)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<[f32; 4]>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                    // Reserved runtime inputs: 2D camera (world-to-clip)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+                            ),
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                ],
             });
 
         let buffer =
@@ -42,18 +78,48 @@ impl UniformBanks {
                     | wgpu::BufferUsages::COPY_DST,
             });
 
+        let mouse_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("xtal-uniform-mouse-buffer"),
+                contents: bytemuck::cast_slice(&mouse),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let camera_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("xtal-uniform-camera-buffer"),
+                contents: bytemuck::cast_slice(&camera),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("xtal-uniform-banks-bind-group"),
             layout: &bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: mouse_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+            ],
         });
 
         Self {
             data,
             buffer,
+            mouse,
+            mouse_buffer,
+            camera,
+            camera_buffer,
             bind_group_layout,
             bind_group,
         }
@@ -68,6 +134,21 @@ impl UniformBanks {
         self.data[0][2] = beats;
     }
 
+    /// Publish the cursor to shaders as `@group(0) @binding(1)`, a `vec4f`
+    /// of `(x, y, buttons, 0)` where `buttons` is the
+    /// [`Context::mouse_buttons`] bitmask
+    ///
+    /// [`Context::mouse_buttons`]: crate::context::Context::mouse_buttons
+    pub fn set_mouse(&mut self, position: [f32; 2], buttons: u32) {
+        self.mouse = [position[0], position[1], buttons as f32, 0.0];
+    }
+
+    /// Publish the camera to shaders as `@group(0) @binding(2)`, a
+    /// world-to-clip `mat4x4f` for a target of `aspect` width over height
+    pub fn set_camera(&mut self, camera: &Camera2D, aspect: f32) {
+        self.camera = camera.view_projection(aspect);
+    }
+
     pub fn set(&mut self, bank: &str, value: f32) -> Result<(), String> {
         let (bank_idx, component_idx) =
             parse_bank_component(bank).map_err(|message| {
@@ -89,6 +170,16 @@ impl UniformBanks {
 
     pub fn upload(&self, queue: &wgpu::Queue) {
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&self.data));
+        queue.write_buffer(
+            &self.mouse_buffer,
+            0,
+            bytemuck::cast_slice(&self.mouse),
+        );
+        queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&self.camera),
+        );
     }
 
     pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
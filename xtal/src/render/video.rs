@@ -0,0 +1,228 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::thread;
+
+use log::warn;
+
+/// Frames decoded ahead of the render thread. Small enough that a paused or
+/// slow sketch doesn't buffer much video in memory.
+const PREFETCH_FRAMES: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    /// Length of the clip in frames, when the container reports a duration
+    pub frames: Option<u64>,
+}
+
+/// A looping video file decoded to RGBA frames by an `ffmpeg` child process
+/// on a background thread. Playback is driven by the caller's frame clock via
+/// [`advance`](Self::advance), so pausing or resetting the sketch pauses or
+/// rewinds the video.
+pub struct VideoTexture {
+    path: PathBuf,
+    info: VideoInfo,
+    decoder: Decoder,
+    next_index: u64,
+}
+
+impl VideoTexture {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let info = probe(&path)?;
+        let decoder = Decoder::spawn(&path, info)?;
+
+        Ok(Self {
+            path,
+            info,
+            decoder,
+            next_index: 0,
+        })
+    }
+
+    pub fn info(&self) -> VideoInfo {
+        self.info
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Index within the clip of the last frame [`advance`](Self::advance)
+    /// returned, wrapping back to 0 each time the video loops. `None` until a
+    /// frame arrives or when the clip's length is unknown.
+    pub fn frame(&self) -> Option<u64> {
+        let frames = self.info.frames.filter(|&frames| frames > 0)?;
+        self.next_index.checked_sub(1).map(|index| index % frames)
+    }
+
+    /// Video frame index shown at `clock_frame` of a clock running at
+    /// `clock_fps`, before looping is applied
+    pub fn frame_index_at(&self, clock_frame: u32, clock_fps: f32) -> u64 {
+        let seconds = clock_frame as f64 / clock_fps.max(1.0) as f64;
+        (seconds * self.info.fps as f64).floor() as u64
+    }
+
+    /// Consume decoded frames up to the one matching `clock_frame`, returning
+    /// the newest RGBA pixels if any arrived. Never blocks: if the decoder
+    /// hasn't caught up, returns `None` and the previous frame stays on
+    /// screen. Moving the clock backwards restarts decoding from the start.
+    pub fn advance(
+        &mut self,
+        clock_frame: u32,
+        clock_fps: f32,
+    ) -> Option<Vec<u8>> {
+        let target = self.frame_index_at(clock_frame, clock_fps);
+
+        if target + 1 < self.next_index {
+            match Decoder::spawn(&self.path, self.info) {
+                Ok(decoder) => {
+                    self.decoder = decoder;
+                    self.next_index = 0;
+                }
+                Err(err) => {
+                    warn!("failed to restart video decoder: {}", err);
+                    return None;
+                }
+            }
+        }
+
+        let mut latest = None;
+        while self.next_index <= target {
+            match self.decoder.frames.try_recv() {
+                Ok(frame) => {
+                    latest = Some(frame);
+                    self.next_index += 1;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    warn!(
+                        "video decoder for '{}' stopped",
+                        self.path.display()
+                    );
+                    break;
+                }
+            }
+        }
+
+        latest
+    }
+}
+
+struct Decoder {
+    process: Child,
+    frames: Receiver<Vec<u8>>,
+}
+
+impl Decoder {
+    fn spawn(path: &Path, info: VideoInfo) -> Result<Self, String> {
+        let mut process = Command::new("ffmpeg")
+            .args(["-hide_banner", "-loglevel", "error", "-nostats"])
+            // Loop forever so playback wraps without restarting the process
+            .args(["-stream_loop", "-1", "-i"])
+            .arg(path)
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "pipe:1"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("failed to spawn ffmpeg: {}", err))?;
+
+        let stdout = process
+            .stdout
+            .take()
+            .ok_or_else(|| "failed to open ffmpeg stdout".to_string())?;
+
+        let (frame_tx, frames) = mpsc::sync_channel(PREFETCH_FRAMES);
+        let frame_size = (info.width * info.height * 4) as usize;
+        thread::spawn(move || read_frames(stdout, frame_size, frame_tx));
+
+        Ok(Self { process, frames })
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+fn read_frames(
+    mut stdout: ChildStdout,
+    frame_size: usize,
+    frame_tx: SyncSender<Vec<u8>>,
+) {
+    loop {
+        let mut frame = vec![0; frame_size];
+        if stdout.read_exact(&mut frame).is_err() {
+            return;
+        }
+        if frame_tx.send(frame).is_err() {
+            return;
+        }
+    }
+}
+
+/// Read the first video stream's dimensions, frame rate and length with
+/// `ffprobe`
+pub fn probe(path: &Path) -> Result<VideoInfo, String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args([
+            "-show_entries",
+            "stream=width,height,r_frame_rate:format=duration",
+        ])
+        .args(["-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .map_err(|err| format!("failed to run ffprobe: {}", err))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe failed for '{}': {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    parse_probe_output(&String::from_utf8_lossy(&output.stdout)).ok_or_else(
+        || format!("'{}' has no readable video stream", path.display()),
+    )
+}
+
+// The stream line comes first, then the container's duration in seconds
+fn parse_probe_output(output: &str) -> Option<VideoInfo> {
+    let mut lines = output.lines();
+    let mut fields = lines.next()?.trim().split(',');
+    let width = fields.next()?.parse::<u32>().ok()?;
+    let height = fields.next()?.parse::<u32>().ok()?;
+    let rate = fields.next()?;
+    let fps = match rate.split_once('/') {
+        Some((num, den)) => {
+            num.parse::<f32>().ok()? / den.parse::<f32>().ok()?
+        }
+        None => rate.parse::<f32>().ok()?,
+    };
+
+    if width == 0 || height == 0 || !fps.is_finite() || fps <= 0.0 {
+        return None;
+    }
+
+    let frames = lines
+        .next()
+        .and_then(|line| line.trim().parse::<f64>().ok())
+        .filter(|duration| duration.is_finite() && *duration > 0.0)
+        .map(|duration| (duration * fps as f64).round() as u64);
+
+    Some(VideoInfo {
+        width,
+        height,
+        fps,
+        frames,
+    })
+}
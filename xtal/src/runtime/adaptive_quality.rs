@@ -0,0 +1,222 @@
+//! Adaptive quality: lowers the internal render scale when a heavy sketch
+//! can't hold its target fps and raises it again once there's headroom.
+//! Offscreen textures shrink with the scale while the present pass stays at
+//! native resolution, upscaling the result.
+
+use serde::{Deserialize, Serialize};
+
+// Hysteresis band as fractions of the target fps: below `LOWER_BELOW` counts
+// as struggling, above `RAISE_ABOVE` as having headroom, and anything in
+// between leaves the scale alone
+const LOWER_BELOW: f32 = 0.9;
+const RAISE_ABOVE: f32 = 0.97;
+
+// Consecutive fps samples (one per second) needed before acting. Raising is
+// slower so a scale that only just holds the target isn't raised straight
+// back into a drop.
+const LOWER_AFTER: u32 = 2;
+const RAISE_AFTER: u32 = 5;
+
+const LOWER_FACTOR: f32 = 0.85;
+const RAISE_STEP: f32 = 0.05;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct AdaptiveQualitySettings {
+    pub enabled: bool,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    /// The fps to hold; `None` uses the sketch's own fps
+    pub target_fps: Option<f32>,
+}
+
+impl Default for AdaptiveQualitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            target_fps: None,
+        }
+    }
+}
+
+/// Decides the render scale from a history of average fps samples
+#[derive(Clone, Debug)]
+pub struct AdaptiveQuality {
+    settings: AdaptiveQualitySettings,
+    scale: f32,
+    low_samples: u32,
+    high_samples: u32,
+}
+
+impl AdaptiveQuality {
+    pub fn new(settings: AdaptiveQualitySettings) -> Self {
+        let mut quality = Self {
+            settings: AdaptiveQualitySettings::default(),
+            scale: 1.0,
+            low_samples: 0,
+            high_samples: 0,
+        };
+        quality.set_settings(settings);
+        quality
+    }
+
+    pub fn settings(&self) -> &AdaptiveQualitySettings {
+        &self.settings
+    }
+
+    /// Apply new settings, starting over at the maximum scale
+    pub fn set_settings(&mut self, settings: AdaptiveQualitySettings) {
+        let min_scale = settings.min_scale.clamp(0.1, 1.0);
+        let max_scale = settings.max_scale.clamp(min_scale, 1.0);
+        self.settings = AdaptiveQualitySettings {
+            min_scale,
+            max_scale,
+            target_fps: settings.target_fps.filter(|fps| *fps > 0.0),
+            ..settings
+        };
+        self.reset();
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.settings.enabled {
+            self.scale
+        } else {
+            1.0
+        }
+    }
+
+    /// Go back to the maximum scale, e.g. while recording
+    pub fn reset(&mut self) {
+        self.scale = self.settings.max_scale;
+        self.low_samples = 0;
+        self.high_samples = 0;
+    }
+
+    /// Feed one average fps sample. `sketch_fps` is the target unless the
+    /// settings override it. Returns the new scale when it changed.
+    pub fn update(&mut self, average_fps: f32, sketch_fps: f32) -> Option<f32> {
+        if !self.settings.enabled {
+            return None;
+        }
+
+        let target = self.settings.target_fps.unwrap_or(sketch_fps).max(1.0);
+        let ratio = average_fps / target;
+
+        if ratio < LOWER_BELOW {
+            self.low_samples += 1;
+            self.high_samples = 0;
+        } else if ratio > RAISE_ABOVE {
+            self.high_samples += 1;
+            self.low_samples = 0;
+        } else {
+            self.low_samples = 0;
+            self.high_samples = 0;
+        }
+
+        let scale = if self.low_samples >= LOWER_AFTER {
+            self.low_samples = 0;
+            self.scale * LOWER_FACTOR
+        } else if self.high_samples >= RAISE_AFTER {
+            self.high_samples = 0;
+            self.scale + RAISE_STEP
+        } else {
+            return None;
+        };
+
+        let scale =
+            scale.clamp(self.settings.min_scale, self.settings.max_scale);
+        if scale == self.scale {
+            return None;
+        }
+        self.scale = scale;
+        Some(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> AdaptiveQuality {
+        AdaptiveQuality::new(AdaptiveQualitySettings {
+            enabled: true,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            target_fps: Some(60.0),
+        })
+    }
+
+    // The scale decision after each sample of `history`
+    fn decisions(
+        quality: &mut AdaptiveQuality,
+        history: &[f32],
+    ) -> Vec<Option<f32>> {
+        history
+            .iter()
+            .map(|fps| quality.update(*fps, 30.0))
+            .collect()
+    }
+
+    #[test]
+    fn lowers_after_sustained_drops_only() {
+        let mut quality = enabled();
+
+        assert_eq!(
+            decisions(&mut quality, &[40.0, 60.0, 40.0, 59.0]),
+            vec![None; 4],
+            "a single bad second is not enough"
+        );
+        assert_eq!(
+            decisions(&mut quality, &[40.0, 40.0]),
+            vec![None, Some(0.85)]
+        );
+    }
+
+    #[test]
+    fn raises_after_sustained_headroom() {
+        let mut quality = enabled();
+        decisions(&mut quality, &[30.0, 30.0, 30.0, 30.0]);
+        assert!((quality.scale() - 0.7225).abs() < 1e-4);
+
+        let raised = decisions(&mut quality, &[60.0; 5]);
+        assert_eq!(raised[..4], [None; 4]);
+        assert!((raised[4].unwrap() - 0.7725).abs() < 1e-4);
+    }
+
+    #[test]
+    fn holds_inside_the_hysteresis_band() {
+        let mut quality = enabled();
+        decisions(&mut quality, &[40.0, 40.0]);
+
+        assert_eq!(decisions(&mut quality, &[56.0; 20]), vec![None; 20]);
+        assert_eq!(quality.scale(), 0.85);
+    }
+
+    #[test]
+    fn stays_within_min_and_max_scale() {
+        let mut quality = enabled();
+
+        decisions(&mut quality, &[10.0; 40]);
+        assert_eq!(quality.scale(), 0.5);
+
+        decisions(&mut quality, &[60.0; 100]);
+        assert_eq!(quality.scale(), 1.0);
+        assert_eq!(quality.update(60.0, 60.0), None);
+    }
+
+    #[test]
+    fn falls_back_to_the_sketch_fps_and_ignores_disabled() {
+        let mut quality = AdaptiveQuality::new(AdaptiveQualitySettings {
+            enabled: true,
+            ..Default::default()
+        });
+        assert_eq!(decisions(&mut quality, &[29.0; 4]), vec![None; 4]);
+        assert_eq!(decisions(&mut quality, &[20.0; 2])[1], Some(0.85));
+
+        let mut disabled = AdaptiveQuality::new(Default::default());
+        assert_eq!(decisions(&mut disabled, &[1.0; 10]), vec![None; 10]);
+        assert_eq!(disabled.scale(), 1.0);
+    }
+}
@@ -1,6 +1,5 @@
 use std::env;
 use std::fs;
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Once;
@@ -13,38 +12,65 @@ use log::{debug, error, info, trace, warn};
 use nannou_osc as osc;
 use winit::application::ApplicationHandler;
 use winit::dpi::LogicalSize;
-use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::event::{
+    ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent,
+};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 use winit::window::{Fullscreen, Window, WindowAttributes, WindowId};
 
+use super::adaptive_quality::AdaptiveQuality;
+use super::control_history::ControlHistory;
+use super::device_recovery::{
+    DeviceChange, DeviceListing, DeviceRecovery, DeviceWatch,
+};
 use super::events::{
     RuntimeCommandReceiver, RuntimeCommandSender, RuntimeEvent,
     RuntimeEventSender, command_channel, event_channel,
 };
+use super::frame_budget::{FrameBudget, FramePhase};
 use super::monitor_preview::{
     MonitorPreview, RenderResult as MonitorRenderResult, preview_size_for_main,
+    select_monitor,
 };
-use super::recording::{self, RecordingState};
-use super::registry::RuntimeRegistry;
+#[cfg(feature = "ndi")]
+use super::ndi_output::NdiOutput;
+use super::perf_cursor::PerfCursor;
+use super::recorder::{self, RecordingOptions};
+use super::recording::{
+    self, CountdownStep, PreRoll, RecordingState, RecordingTarget,
+};
+use super::registry::{RuntimeRegistry, SketchLookup};
+#[cfg(feature = "remote-control")]
+use super::remote_control::RemoteControlServer;
 use super::serialization::{GlobalSettings, TransitorySketchState};
+#[cfg(feature = "source-watch")]
+use super::source_watch::{SourceChange, SourceWatch};
+use super::stills::{
+    StillFormat, StillReadback, Timelapse, TimelapseInterval, TimelapseOptions,
+};
 use super::storage;
 use super::web_view;
 use super::web_view_bridge::WebViewBridge;
-use crate::context::Context;
+use crate::camera::{self, ZOOM_STEP};
+use crate::context::{Context, MOUSE_LEFT, MOUSE_MIDDLE, MOUSE_RIGHT};
 use crate::control::map_mode::MapMode;
-use crate::control::{ControlCollection, ControlHub, ControlValue};
+use crate::control::{
+    AbSlot, ControlCollection, ControlHub, ControlValue, MidiFeedback,
+};
 use crate::core::logging;
 use crate::core::util::{HashMap, uuid_5};
 use crate::frame::Frame;
-use crate::gpu::CompiledGraph;
-use crate::gpu::compute_row_padding;
+use crate::gpu::{CompiledGraph, GraphError, supported_msaa_samples};
 use crate::graph::GraphBuilder;
+use crate::image::{self, DecodedImage};
 use crate::io::audio::list_audio_devices;
 use crate::io::midi;
-use crate::io::osc::SHARED_OSC_RECEIVER;
-use crate::motion::{Bpm, Timing};
+use crate::io::osc::{SHARED_OSC_RECEIVER, SHARED_OSC_SENDER};
+use crate::motion::{BeatSmoother, Bpm, Timing};
 use crate::sketch::{PlayMode, Sketch, SketchConfig, TimingMode};
+use crate::text::TextRenderer;
+use crate::time::clock_tempo::ClockTempo;
 use crate::time::frame_clock;
 use crate::time::tap_tempo::TapTempo;
 use crate::uniforms::UniformBanks;
@@ -59,8 +85,12 @@ const DEFAULT_OSC_PORT: u16 = 2346;
 const PULSES_PER_QUARTER_NOTE: u32 = 24;
 const TICKS_PER_QUARTER_NOTE: u32 = 960;
 const HYBRID_SYNC_THRESHOLD_BEATS: f32 = 0.5;
+const MIN_BPM: f32 = 20.0;
+const MAX_BPM: f32 = 999.0;
 const CONTINUE_HANDLING: bool = false;
 const QUIT_REQUESTED: bool = true;
+// Trackpads report pixels; treat this many as one wheel notch
+const PIXELS_PER_SCROLL_LINE: f32 = 40.0;
 
 static OSC_TRANSPORT_CALLBACK_REGISTER: Once = Once::new();
 
@@ -71,12 +101,11 @@ struct SketchUiState {
 }
 
 struct PendingPngCapture {
-    path: PathBuf,
-    buffer: wgpu::Buffer,
-    width: u32,
-    height: u32,
-    padded_bytes_per_row: u32,
-    source_format: wgpu::TextureFormat,
+    /// Every path receives the same image in its own format
+    outputs: Vec<(PathBuf, StillFormat)>,
+    /// Alert on success; timelapse stills are saved quietly
+    announce: bool,
+    readback: StillReadback,
 }
 
 struct XtalRuntime {
@@ -99,6 +128,8 @@ struct XtalRuntime {
     window_id: Option<WindowId>,
     monitor_preview: Option<MonitorPreview>,
     monitor_preview_size_hint: Option<winit::dpi::PhysicalSize<u32>>,
+    /// Full-rate mirror of the main window on another monitor
+    output_window: Option<MonitorPreview>,
     windowed_size_before_fullscreen: Option<winit::dpi::PhysicalSize<u32>>,
     surface: Option<wgpu::Surface<'static>>,
     surface_config: Option<wgpu::SurfaceConfiguration>,
@@ -106,11 +137,14 @@ struct XtalRuntime {
     uniforms: Option<UniformBanks>,
     graph: Option<CompiledGraph>,
     control_hub: Option<ControlHub<Timing>>,
+    control_history: ControlHistory,
     bpm: Bpm,
     tap_tempo: TapTempo,
     tap_tempo_enabled: bool,
     perf_mode: bool,
+    perf_cursor: PerfCursor,
     transition_time: f32,
+    randomize_seed: Option<u64>,
     mappings_enabled: bool,
     map_mode: MapMode,
     sketch_ui_state: HashMap<String, SketchUiState>,
@@ -120,12 +154,20 @@ struct XtalRuntime {
     audio_devices: Vec<String>,
     hrcc: bool,
     midi_out: Option<midi::MidiOut>,
+    midi_feedback: MidiFeedback,
+    midi_feedback_enabled: bool,
+    offline_recording_seconds: f32,
+    record_audio: bool,
+    recording_options: RecordingOptions,
+    recording_pre_roll: PreRoll,
     midi_clock_port: String,
     midi_input_port: String,
     midi_output_port: String,
     midi_input_ports: Vec<(usize, String)>,
     midi_output_ports: Vec<(usize, String)>,
     osc_port: u16,
+    osc_prefix: String,
+    osc_transport_smoother: BeatSmoother,
     images_dir: String,
     user_data_dir: String,
     videos_dir: String,
@@ -133,7 +175,28 @@ struct XtalRuntime {
     last_average_fps_emit: Instant,
     shutdown_signaled: bool,
     pending_png_capture_path: Option<PathBuf>,
+    still_format: StillFormat,
+    timelapse: Option<Timelapse>,
+    timelapse_options: TimelapseOptions,
+    image_drop_id: u64,
+    dropped_image: Option<Arc<DecodedImage>>,
+    loop_region: Option<(f32, f32)>,
     modifiers: ModifiersState,
+    remote_control_address: String,
+    #[cfg(feature = "remote-control")]
+    remote_control: Option<RemoteControlServer>,
+    ndi_sender_name: String,
+    #[cfg(feature = "ndi")]
+    ndi_output: Option<NdiOutput>,
+    /// Created on the first frame a sketch draws text
+    text_renderer: Option<TextRenderer>,
+    #[cfg(feature = "source-watch")]
+    source_watch: Option<SourceWatch>,
+    device_recovery: DeviceRecovery,
+    frame_budget: FrameBudget,
+    adaptive_quality: AdaptiveQuality,
+    #[cfg(feature = "source-watch")]
+    relaunch: Option<(PathBuf, String)>,
     midi_clock_count: Arc<AtomicU32>,
     midi_song_position_ticks: Arc<AtomicU32>,
     osc_transport_playing: Arc<AtomicBool>,
@@ -141,6 +204,7 @@ struct XtalRuntime {
     osc_transport_beats: Arc<AtomicU32>,
     osc_transport_ticks: Arc<AtomicU32>,
     follow_song_position: Arc<AtomicBool>,
+    follow_clock_tempo: Arc<AtomicBool>,
     hybrid_mtc_sync_enabled: Arc<AtomicBool>,
     mtc_hours: Arc<AtomicU32>,
     mtc_minutes: Arc<AtomicU32>,
@@ -157,6 +221,53 @@ impl XtalRuntime {
         command_tx: RuntimeCommandSender,
         command_rx: RuntimeCommandReceiver,
         event_tx: Option<RuntimeEventSender>,
+    ) -> Result<Self, String> {
+        let mut runtime = Self::from_registry(
+            registry,
+            initial_sketch,
+            command_tx,
+            command_rx,
+            event_tx,
+        )?;
+
+        let persisted_audio_device = runtime.audio_device.clone();
+        let persisted_midi_input_port = runtime.midi_input_port.clone();
+        let persisted_midi_clock_port = runtime.midi_clock_port.clone();
+        let persisted_midi_output_port = runtime.midi_output_port.clone();
+        let audio_device_updated = runtime.normalize_audio_device_selection();
+        let midi_ports_updated = runtime.normalize_midi_port_selections();
+        let osc_port_updated = runtime.normalize_osc_port_selection();
+        runtime.init_device_recovery([
+            persisted_audio_device,
+            persisted_midi_input_port,
+            persisted_midi_clock_port,
+            persisted_midi_output_port,
+        ]);
+        runtime.update_timing_mode_flags();
+        runtime.register_osc_transport_listener();
+        runtime.start_osc_receiver();
+        runtime.start_osc_sender();
+        runtime.start_midi_clock_listener();
+        runtime.connect_midi_out();
+        runtime.log_midi_startup_state();
+        recorder::probe_encoders();
+        #[cfg(feature = "remote-control")]
+        runtime.start_remote_control();
+        if audio_device_updated || midi_ports_updated || osc_port_updated {
+            runtime.save_global_state();
+        }
+
+        Ok(runtime)
+    }
+
+    // The runtime state alone, before `new` opens devices, ports and
+    // listeners.
+    fn from_registry(
+        registry: RuntimeRegistry,
+        initial_sketch: Option<&str>,
+        command_tx: RuntimeCommandSender,
+        command_rx: RuntimeCommandReceiver,
+        event_tx: Option<RuntimeEventSender>,
     ) -> Result<Self, String> {
         let active_name =
             select_initial_sketch_name(&registry, initial_sketch)?;
@@ -202,7 +313,7 @@ impl XtalRuntime {
         let mut sketch_ui_state = HashMap::default();
         sketch_ui_state.insert(active_name.clone(), SketchUiState::default());
 
-        let mut runtime = Self {
+        Ok(Self {
             registry,
             active_sketch_name: active_name,
             config,
@@ -217,6 +328,7 @@ impl XtalRuntime {
             window_id: None,
             monitor_preview: None,
             monitor_preview_size_hint: None,
+            output_window: None,
             windowed_size_before_fullscreen: None,
             surface: None,
             surface_config: None,
@@ -224,11 +336,16 @@ impl XtalRuntime {
             uniforms: None,
             graph: None,
             control_hub: None,
+            control_history: ControlHistory::default(),
             bpm: bpm.clone(),
             tap_tempo: TapTempo::new(config.bpm),
             tap_tempo_enabled: false,
             perf_mode: false,
+            perf_cursor: PerfCursor::new(
+                global_settings.perf_mode_hides_cursor,
+            ),
             transition_time: global_settings.transition_time,
+            randomize_seed: None,
             mappings_enabled: global_settings.mappings_enabled,
             map_mode: MapMode::default(),
             sketch_ui_state,
@@ -238,12 +355,23 @@ impl XtalRuntime {
             audio_devices: list_audio_devices().unwrap_or_default(),
             hrcc: global_settings.hrcc,
             midi_out: None,
+            midi_feedback: MidiFeedback::default(),
+            midi_feedback_enabled: global_settings.midi_feedback_enabled,
+            offline_recording_seconds: global_settings
+                .offline_recording_seconds,
+            record_audio: global_settings.record_audio,
+            recording_options: global_settings.recording_options,
+            recording_pre_roll: global_settings.recording_pre_roll,
             midi_clock_port: global_settings.midi_clock_port,
             midi_input_port: global_settings.midi_control_in_port,
             midi_output_port: global_settings.midi_control_out_port,
             midi_input_ports: midi::list_input_ports().unwrap_or_default(),
             midi_output_ports: midi::list_output_ports().unwrap_or_default(),
             osc_port: global_settings.osc_port,
+            osc_prefix: global_settings.osc_prefix,
+            osc_transport_smoother: BeatSmoother::new(
+                global_settings.osc_transport_smoothing,
+            ),
             images_dir: global_settings.images_dir,
             user_data_dir: global_settings.user_data_dir,
             videos_dir: global_settings.videos_dir,
@@ -251,7 +379,29 @@ impl XtalRuntime {
             last_average_fps_emit: Instant::now(),
             shutdown_signaled: false,
             pending_png_capture_path: None,
+            still_format: global_settings.still_format,
+            timelapse: None,
+            timelapse_options: global_settings.timelapse_options,
+            image_drop_id: 0,
+            dropped_image: None,
+            loop_region: None,
             modifiers: ModifiersState::default(),
+            remote_control_address: global_settings.remote_control_address,
+            #[cfg(feature = "remote-control")]
+            remote_control: None,
+            ndi_sender_name: global_settings.ndi_sender_name,
+            #[cfg(feature = "ndi")]
+            ndi_output: None,
+            text_renderer: None,
+            #[cfg(feature = "source-watch")]
+            source_watch: None,
+            device_recovery: DeviceRecovery::default(),
+            frame_budget: FrameBudget::default(),
+            adaptive_quality: AdaptiveQuality::new(
+                global_settings.adaptive_quality,
+            ),
+            #[cfg(feature = "source-watch")]
+            relaunch: None,
             midi_clock_count: Arc::new(AtomicU32::new(0)),
             midi_song_position_ticks: Arc::new(AtomicU32::new(0)),
             osc_transport_playing: Arc::new(AtomicBool::new(false)),
@@ -259,27 +409,13 @@ impl XtalRuntime {
             osc_transport_beats: Arc::new(AtomicU32::new(0)),
             osc_transport_ticks: Arc::new(AtomicU32::new(0.0f32.to_bits())),
             follow_song_position: Arc::new(AtomicBool::new(true)),
+            follow_clock_tempo: Arc::new(AtomicBool::new(false)),
             hybrid_mtc_sync_enabled: Arc::new(AtomicBool::new(false)),
             mtc_hours: Arc::new(AtomicU32::new(0)),
             mtc_minutes: Arc::new(AtomicU32::new(0)),
             mtc_seconds: Arc::new(AtomicU32::new(0)),
             mtc_frames: Arc::new(AtomicU32::new(0)),
-        };
-
-        let audio_device_updated = runtime.normalize_audio_device_selection();
-        let midi_ports_updated = runtime.normalize_midi_port_selections();
-        let osc_port_updated = runtime.normalize_osc_port_selection();
-        runtime.update_timing_mode_flags();
-        runtime.register_osc_transport_listener();
-        runtime.start_osc_receiver();
-        runtime.start_midi_clock_listener();
-        runtime.connect_midi_out();
-        runtime.log_midi_startup_state();
-        if audio_device_updated || midi_ports_updated || osc_port_updated {
-            runtime.save_global_state();
-        }
-
-        Ok(runtime)
+        })
     }
 
     // Single command/event dispatcher for runtime behavior changes.
@@ -290,6 +426,38 @@ impl XtalRuntime {
         event: RuntimeEvent,
     ) -> bool {
         match event {
+            RuntimeEvent::Quit => {
+                self.shutdown(event_loop);
+                return QUIT_REQUESTED;
+            }
+            RuntimeEvent::SetMonitorPreview(enabled) => {
+                self.set_monitor_preview_enabled(event_loop, enabled);
+            }
+            RuntimeEvent::OpenOutputWindow(monitor_index) => {
+                match self.open_output_window(event_loop, monitor_index) {
+                    Ok(()) => self.request_render_now(),
+                    Err(err) => self.alert_and_log(
+                        format!("Failed to open output window: {}", err),
+                        log::Level::Error,
+                    ),
+                }
+            }
+            event => return self.apply_runtime_event(event),
+        }
+
+        CONTINUE_HANDLING
+    }
+
+    // Handles every event that doesn't need the event loop, which is all but
+    // quitting and opening windows.
+    fn apply_runtime_event(&mut self, event: RuntimeEvent) -> bool {
+        match event {
+            RuntimeEvent::AdvanceRandomizeSeed => {
+                let seed = self
+                    .randomize_seed
+                    .map_or_else(rand::random, |seed| seed.wrapping_add(1));
+                self.set_randomize_seed(Some(seed));
+            }
             RuntimeEvent::AdvanceSingleFrame => {
                 frame_clock::advance_single_frame();
             }
@@ -305,8 +473,12 @@ impl XtalRuntime {
                     return false;
                 }
 
-                let filename =
-                    format!("{}-{}.png", self.active_sketch_name, uuid_5());
+                let filename = format!(
+                    "{}-{}.{}",
+                    self.active_sketch_name,
+                    uuid_5(),
+                    self.still_format.extension()
+                );
                 let file_path = PathBuf::from(&self.images_dir).join(&filename);
                 self.pending_png_capture_path = Some(file_path);
                 self.render_requested = true;
@@ -330,23 +502,16 @@ impl XtalRuntime {
             }
             RuntimeEvent::ChangeAudioDevice(name) => {
                 self.audio_device = name.clone();
+                self.device_recovery.audio.select(name.clone());
                 if !self.audio_devices.contains(&name) {
                     self.audio_devices.push(name);
                 }
-                if let Some(hub) = self.control_hub.as_mut() {
-                    hub.audio_controls
-                        .set_device_name(self.audio_device.clone());
-                    hub.audio_controls
-                        .restart()
-                        .inspect_err(|err| {
-                            error!("Error in ChangeAudioDevice: {}", err)
-                        })
-                        .ok();
-                }
+                self.restart_audio_controls();
                 self.save_global_state();
             }
             RuntimeEvent::ChangeMidiClockPort(port) => {
                 info!("Changing MIDI clock port to '{}'", port);
+                self.device_recovery.midi_clock.select(port.clone());
                 self.midi_clock_port = port;
                 self.start_midi_clock_listener();
                 self.save_global_state();
@@ -354,6 +519,7 @@ impl XtalRuntime {
             RuntimeEvent::ChangeMidiControlInputPort(port) => {
                 info!("Changing MIDI control input port to '{}'", port);
                 self.midi_input_port = port.clone();
+                self.device_recovery.midi_input.select(port.clone());
                 if !self
                     .midi_input_ports
                     .iter()
@@ -362,23 +528,13 @@ impl XtalRuntime {
                     let idx = self.midi_input_ports.len();
                     self.midi_input_ports.push((idx, port));
                 }
-                if let Some(hub) = self.control_hub.as_mut() {
-                    hub.midi_controls.set_port(self.midi_input_port.clone());
-                    hub.midi_controls
-                        .restart()
-                        .inspect_err(|err| {
-                            error!(
-                                "Error in ChangeMidiControlInputPort: {}",
-                                err
-                            );
-                        })
-                        .ok();
-                }
+                self.restart_midi_controls();
                 self.save_global_state();
             }
             RuntimeEvent::ChangeMidiControlOutputPort(port) => {
                 info!("Changing MIDI control output port to '{}'", port);
                 self.midi_output_port = port.clone();
+                self.device_recovery.midi_output.select(port.clone());
                 if !self
                     .midi_output_ports
                     .iter()
@@ -396,6 +552,13 @@ impl XtalRuntime {
                 self.restart_osc_receiver();
                 self.save_global_state();
             }
+            RuntimeEvent::ChangeOscPrefix(prefix) => {
+                info!("Changing OSC prefix to '{}'", prefix);
+                SHARED_OSC_RECEIVER.set_prefix(&prefix);
+                self.osc_prefix = SHARED_OSC_RECEIVER.prefix();
+                self.restart_osc_receiver();
+                self.save_global_state();
+            }
             RuntimeEvent::ClearBuffer => {
                 self.alert(
                     "ClearBuffer is not yet implemented in xtal runtime.",
@@ -406,29 +569,37 @@ impl XtalRuntime {
                 // learn so subsequent MIDI movement does not keep remapping.
                 self.map_mode.stop();
                 let mappings = self.map_mode.mappings();
-                let mut missing_slider_ranges = Vec::new();
+                let mut missing_ranges = Vec::new();
 
                 {
                     let Some(hub) = self.control_hub.as_mut() else {
                         return false;
                     };
 
-                    hub.midi_override_configs.retain(|name, _| {
-                        mappings.contains_key(name) && hub.ui_controls.has(name)
-                    });
+                    let stale: Vec<String> = hub
+                        .midi_override_configs
+                        .keys()
+                        .filter(|name| {
+                            !mappings.contains_key(*name)
+                                || hub.mapping_range(name).is_none()
+                        })
+                        .cloned()
+                        .collect();
+                    for name in stale {
+                        hub.midi_override_configs.remove(&name);
+                    }
                     hub.midi_overrides.lock().unwrap().retain(|name, _| {
                         hub.midi_override_configs.contains_key(name)
                     });
 
                     for (name, (ch, cc)) in mappings {
-                        let slider_range =
-                            match hub.ui_controls.slider_range(&name) {
-                                Some(range) => range,
-                                None => {
-                                    missing_slider_ranges.push(name.clone());
-                                    continue;
-                                }
-                            };
+                        let range = match hub.mapping_range(&name) {
+                            Some(range) => range,
+                            None => {
+                                missing_ranges.push(name.clone());
+                                continue;
+                            }
+                        };
 
                         let existing_value = hub
                             .midi_overrides
@@ -442,15 +613,13 @@ impl XtalRuntime {
                             name,
                             crate::control::MidiControlConfig::new(
                                 (ch as u8, cc as u8),
-                                slider_range,
+                                range,
                                 existing_value,
                             ),
                         );
                     }
 
-                    hub.midi_controls.set_override_configs(
-                        hub.midi_override_configs.clone(),
-                    );
+                    hub.sync_midi_override_configs();
 
                     if let Err(err) = hub.midi_controls.restart() {
                         error!("{}", err);
@@ -459,9 +628,9 @@ impl XtalRuntime {
 
                 self.current_sketch_ui_state_mut().mappings =
                     self.map_mode.mappings();
-                for name in missing_slider_ranges {
+                for name in missing_ranges {
                     self.alert_and_log(
-                        format!("No slider range for {}", name),
+                        format!("No slider or param range for {}", name),
                         log::Level::Error,
                     );
                 }
@@ -476,9 +645,7 @@ impl XtalRuntime {
                 if let Some(hub) = self.control_hub.as_mut() {
                     hub.midi_override_configs.remove(&name);
                     hub.midi_overrides.lock().unwrap().remove(&name);
-                    hub.midi_controls.set_override_configs(
-                        hub.midi_override_configs.clone(),
-                    );
+                    hub.sync_midi_override_configs();
                 }
 
                 self.map_mode.currently_mapping = Some(name.clone());
@@ -506,6 +673,14 @@ impl XtalRuntime {
                     })
                     .ok();
             }
+            RuntimeEvent::DevicesListed(listing) => {
+                self.apply_device_listing(listing);
+            }
+            RuntimeEvent::DroppedImageDecoded(id, path, result) => {
+                if id == self.image_drop_id {
+                    self.apply_dropped_image(path, result);
+                }
+            }
             RuntimeEvent::MapModeError(message) => {
                 self.alert_and_log(message, log::Level::Error);
             }
@@ -518,6 +693,7 @@ impl XtalRuntime {
                 let bypassed = hub.bypassed();
                 let snapshot_sequence_enabled = hub.snapshot_sequence_enabled();
 
+                self.control_history.clear();
                 self.emit_web_view_event(web_view::Event::HubPopulated((
                     controls, bypassed,
                 )));
@@ -533,15 +709,12 @@ impl XtalRuntime {
                 frame_clock::reset();
 
                 if self.recording_state.is_queued {
-                    let _ = self.on_runtime_event(
-                        event_loop,
-                        RuntimeEvent::StartRecording,
-                    );
+                    let _ =
+                        self.apply_runtime_event(RuntimeEvent::StartRecording);
                 }
             }
             RuntimeEvent::MidiStop => {
-                let _ = self
-                    .on_runtime_event(event_loop, RuntimeEvent::StopRecording);
+                let _ = self.apply_runtime_event(RuntimeEvent::StopRecording);
             }
             RuntimeEvent::OpenOsDir(kind) => {
                 let path = self.os_dir_path(&kind);
@@ -580,6 +753,15 @@ impl XtalRuntime {
             }
             RuntimeEvent::Pause(paused) => {
                 frame_clock::set_paused(paused);
+                self.osc_transport_smoother.reset();
+                if paused {
+                    self.cancel_recording_countdown();
+                }
+            }
+            RuntimeEvent::PreviewControl((name, value)) => {
+                if let Some(hub) = self.control_hub.as_mut() {
+                    hub.set_preview(&name, value);
+                }
             }
             RuntimeEvent::QueueRecord => {
                 self.recording_state.is_queued =
@@ -591,12 +773,12 @@ impl XtalRuntime {
                     );
                 }
             }
-            RuntimeEvent::Quit => {
-                self.shutdown(event_loop);
-                return QUIT_REQUESTED;
-            }
             RuntimeEvent::Randomize(exclusions) => {
-                self.alert_and_log("Transition started", log::Level::Info);
+                let message = match self.randomize_seed {
+                    Some(seed) => format!("Transition started (seed {})", seed),
+                    None => "Transition started".to_string(),
+                };
+                self.alert_and_log(message, log::Level::Info);
 
                 if let Some(hub) = self.control_hub.as_mut() {
                     hub.randomize(exclusions);
@@ -647,9 +829,7 @@ impl XtalRuntime {
                 if let Some(hub) = self.control_hub.as_mut() {
                     hub.midi_override_configs.remove(&name);
                     hub.midi_overrides.lock().unwrap().remove(&name);
-                    hub.midi_controls.set_override_configs(
-                        hub.midi_override_configs.clone(),
-                    );
+                    hub.sync_midi_override_configs();
                 }
 
                 let mappings = self.map_mode.mappings();
@@ -660,6 +840,105 @@ impl XtalRuntime {
                 frame_clock::reset();
                 self.alert("Reset");
             }
+            RuntimeEvent::ScrubBeats(beats) => {
+                let result = match self.control_hub.as_mut() {
+                    Some(hub) => hub.set_beats(beats),
+                    None => Err("no hub".to_string()),
+                };
+                match result {
+                    Ok(()) => self.request_render_now(),
+                    Err(err) => self.alert_and_log(
+                        format!("Unable to scrub ({})", err),
+                        log::Level::Warn,
+                    ),
+                }
+            }
+            RuntimeEvent::ExportControlScript => {
+                let result = match self.control_hub.as_ref() {
+                    Some(hub) => hub.export_control_script(),
+                    None => Err("no control script".to_string()),
+                };
+                match result {
+                    Ok(path) => self.alert_and_log(
+                        format!(
+                            "Exported control values to {}",
+                            path.display()
+                        ),
+                        log::Level::Info,
+                    ),
+                    Err(err) => self.alert_and_log(
+                        format!("Unable to export control script ({})", err),
+                        log::Level::Error,
+                    ),
+                }
+            }
+            RuntimeEvent::ExportSketchState(path) => {
+                let mappings = self.map_mode.mappings();
+                let exclusions = self.current_sketch_ui_state().exclusions;
+                let result = match self.control_hub.as_ref() {
+                    Some(hub) => storage::export_sketch_state(
+                        &path,
+                        &self.active_sketch_name,
+                        hub,
+                        mappings,
+                        exclusions,
+                    ),
+                    None => Err("no hub".into()),
+                };
+                match result {
+                    Ok(()) => self.alert_and_log(
+                        format!("Exported sketch state to {}", path.display()),
+                        log::Level::Info,
+                    ),
+                    Err(err) => self.alert_and_log(
+                        format!("Unable to export sketch state ({})", err),
+                        log::Level::Error,
+                    ),
+                }
+            }
+            RuntimeEvent::ImportSketchState(path) => {
+                let sketch_name = self.active_sketch_name.clone();
+                let result = self.restore_sketch_state(|state| {
+                    storage::import_sketch_state(&path, &sketch_name, state)
+                        .map(|_| ())
+                });
+                match result {
+                    Ok(true) => {
+                        self.emit_web_view_load_sketch();
+                        self.alert_and_log(
+                            format!(
+                                "Imported sketch state from {}",
+                                path.display()
+                            ),
+                            log::Level::Info,
+                        );
+                    }
+                    Ok(false) => self.alert_and_log(
+                        "Unable to import sketch state (no hub)",
+                        log::Level::Error,
+                    ),
+                    Err(err) => self.alert_and_log(
+                        format!("Unable to import sketch state ({})", err),
+                        log::Level::Error,
+                    ),
+                }
+            }
+            RuntimeEvent::SetLoopRegion(region) => {
+                let result = match self.control_hub.as_mut() {
+                    Some(hub) => hub.set_loop_region(region),
+                    None => Err("no hub".to_string()),
+                };
+                match result {
+                    Ok(()) => {
+                        self.loop_region = region;
+                        self.request_render_now();
+                    }
+                    Err(err) => self.alert_and_log(
+                        format!("Unable to set loop region ({})", err),
+                        log::Level::Warn,
+                    ),
+                }
+            }
             RuntimeEvent::Save(exclusions) => {
                 let stored = self.current_sketch_ui_state().exclusions;
                 let next = if !exclusions.is_empty() || stored.is_empty() {
@@ -779,12 +1058,71 @@ impl XtalRuntime {
                 }
                 self.save_global_state();
             }
-            RuntimeEvent::SetMonitorPreview(enabled) => {
-                self.set_monitor_preview_enabled(event_loop, enabled);
+            RuntimeEvent::SetMidiFeedbackEnabled(enabled) => {
+                info!("Setting midi_feedback_enabled to {}", enabled);
+                self.midi_feedback_enabled = enabled;
+                self.midi_feedback.clear();
+                self.save_global_state();
+            }
+            RuntimeEvent::CloseOutputWindow => {
+                self.output_window = None;
+            }
+            RuntimeEvent::SetOfflineRecordingSeconds(seconds) => {
+                info!("Setting offline_recording_seconds to {}", seconds);
+                self.offline_recording_seconds = seconds.max(0.0);
+                self.save_global_state();
+            }
+            RuntimeEvent::SetOscTransportSmoothing(smoothing) => {
+                info!("Setting osc_transport_smoothing to {}", smoothing);
+                self.osc_transport_smoother.smoothing =
+                    smoothing.clamp(0.0, BeatSmoother::MAX_SMOOTHING);
+                self.save_global_state();
+            }
+            RuntimeEvent::SetRecordAudio(enabled) => {
+                info!("Setting record_audio to {}", enabled);
+                self.record_audio = enabled;
+                self.save_global_state();
+            }
+            RuntimeEvent::SetRecordingOptions(options) => {
+                info!("Setting recording_options to {:?}", options);
+                self.recording_options = options;
+                self.save_global_state();
+            }
+            RuntimeEvent::SetRecordingPreRoll(pre_roll) => {
+                info!("Setting recording_pre_roll to {:?}", pre_roll);
+                self.recording_pre_roll = pre_roll;
+                self.save_global_state();
+            }
+            RuntimeEvent::SetStillFormat(format) => {
+                info!("Setting still_format to {:?}", format);
+                self.still_format = format;
+                self.save_global_state();
+            }
+            RuntimeEvent::SetTimelapseOptions(options) => {
+                info!("Setting timelapse_options to {:?}", options);
+                self.timelapse_options = options;
+                self.save_global_state();
             }
             RuntimeEvent::SetPerfMode(perf_mode) => {
                 self.set_perf_mode(perf_mode);
             }
+            RuntimeEvent::SetPerfModeHidesCursor(hides) => {
+                info!("Setting perf_mode_hides_cursor to {}", hides);
+                self.perf_cursor.set_hide_in_perf_mode(hides);
+                self.apply_cursor_visibility();
+                self.save_global_state();
+            }
+            RuntimeEvent::SetRandomizeSeed(seed) => {
+                self.set_randomize_seed(seed);
+            }
+            RuntimeEvent::SetRenderFps(fps) => {
+                frame_clock::set_render_fps(fps.map(|fps| fps as f32));
+                frame_clock::reset_timing(Instant::now());
+                self.alert(format!(
+                    "Render fps: {}",
+                    frame_clock::render_fps()
+                ));
+            }
             RuntimeEvent::SetTransitionTime(transition_time) => {
                 self.transition_time = transition_time;
                 if let Some(hub) = self.control_hub.as_mut() {
@@ -839,76 +1177,30 @@ impl XtalRuntime {
                     );
                 }
             }
-            RuntimeEvent::StartRecording => {
-                let Some(context) = self.context.as_ref() else {
-                    self.alert_and_log(
-                        "Failed to start recording: runtime context unavailable",
-                        log::Level::Error,
-                    );
-                    return false;
-                };
-                let Some(graph) = self.graph.as_ref() else {
-                    self.alert_and_log(
-                        "Failed to start recording: render graph unavailable",
-                        log::Level::Error,
-                    );
-                    return false;
-                };
-                let source_format = graph.recording_source_format().or_else(|| {
-                    self.surface_config.as_ref().map(|config| config.format)
-                });
-                let Some(source_format) = source_format else {
-                    self.alert_and_log(
-                        "Failed to start recording: no capture source format available",
-                        log::Level::Error,
-                    );
-                    return false;
+            RuntimeEvent::StashA | RuntimeEvent::StashB => {
+                let slot = if matches!(event, RuntimeEvent::StashA) {
+                    AbSlot::A
+                } else {
+                    AbSlot::B
                 };
-
-                if let Err(err) = fs::create_dir_all(&self.videos_dir) {
+                if let Some(hub) = self.control_hub.as_mut() {
+                    hub.stash(slot);
                     self.alert_and_log(
-                        format!(
-                            "Failed to create videos directory '{}': {}",
-                            self.videos_dir, err
-                        ),
-                        log::Level::Error,
+                        format!("Stashed {:?}", slot),
+                        log::Level::Info,
                     );
-                    return false;
                 }
-
-                let [width, height] = context.resolution_u32();
-                let output_path = recording::video_output_path(
-                    &self.videos_dir,
-                    &self.session_id,
-                    self.config.name,
-                )
-                .to_string_lossy()
-                .into_owned();
-
-                match self.recording_state.start_recording(
-                    context.device.clone(),
-                    &output_path,
-                    width,
-                    height,
-                    self.config.fps,
-                    source_format,
-                ) {
-                    Ok(message) => {
-                        self.recording_state.is_queued = false;
-                        self.alert(message);
-                        self.emit_web_view_event(
-                            web_view::Event::StartRecording,
-                        );
-                    }
-                    Err(err) => {
-                        self.alert_and_log(
-                            format!("Failed to start recording: {}", err),
-                            log::Level::Error,
-                        );
-                    }
+            }
+            RuntimeEvent::StartRecording => {
+                if !self.recording_state.is_counting_down()
+                    && !self.start_recording_countdown()
+                {
+                    self.start_recording();
                 }
             }
             RuntimeEvent::StopRecording => {
+                self.send_midi_panic();
+                self.cancel_recording_countdown();
                 if self.recording_state.is_recording
                     && !self.recording_state.is_encoding
                 {
@@ -942,6 +1234,20 @@ impl XtalRuntime {
                     self.emit_web_view_event(web_view::Event::Bpm(bpm));
                 }
             }
+            RuntimeEvent::SetBpm(bpm) if !bpm.is_finite() => {
+                self.alert_and_log(
+                    format!("Unable to set BPM to {}", bpm),
+                    log::Level::Warn,
+                );
+            }
+            RuntimeEvent::SetBpm(bpm) => {
+                let bpm = bpm.clamp(MIN_BPM, MAX_BPM);
+                // Every timing source shares `self.bpm`, so beat math picks
+                // up the new tempo on the next frame
+                self.bpm.set(bpm);
+                self.tap_tempo = TapTempo::new(bpm);
+                self.emit_web_view_event(web_view::Event::Bpm(bpm));
+            }
             RuntimeEvent::TapTempoEnabled(enabled) => {
                 self.tap_tempo_enabled = enabled;
                 self.bpm.set(self.config.bpm);
@@ -955,6 +1261,51 @@ impl XtalRuntime {
                     log::Level::Info,
                 );
             }
+            RuntimeEvent::ToggleAB => {
+                let Some(hub) = self.control_hub.as_mut() else {
+                    return false;
+                };
+
+                match hub.toggle_ab() {
+                    Ok(slot) => {
+                        self.alert(format!("Comparing {:?}", slot));
+                        if let Some(hub) = self.control_hub.as_ref() {
+                            self.emit_web_view_event(
+                                web_view::Event::UpdatedControls(
+                                    web_view::controls_from_hub(hub),
+                                ),
+                            );
+                        }
+                        let _ = self.command_tx.send(RuntimeEvent::SendMidi);
+                    }
+                    Err(err) => self.alert_and_log(err, log::Level::Warn),
+                }
+            }
+            RuntimeEvent::ToggleFreezeRandom => {
+                if let Some(hub) = self.control_hub.as_mut() {
+                    let freeze = !hub.freeze_random();
+                    hub.set_freeze_random(freeze);
+                    self.alert(if freeze {
+                        "Snapshots freeze random animations"
+                    } else {
+                        "Random animations are live"
+                    });
+                }
+            }
+            RuntimeEvent::ToggleHold => {
+                if let Some(hub) = self.control_hub.as_mut() {
+                    let frozen = !hub.frozen();
+                    hub.set_frozen(frozen);
+                    self.alert(if frozen {
+                        "Animation held"
+                    } else {
+                        "Animation resumed"
+                    });
+                }
+            }
+            RuntimeEvent::ToggleTimelapse => {
+                self.toggle_timelapse();
+            }
             RuntimeEvent::ToggleFullScreen => {
                 let Some(window) = self.window.as_ref() else {
                     return false;
@@ -987,14 +1338,20 @@ impl XtalRuntime {
                 window.set_visible(true);
                 window.focus_window();
             }
+            RuntimeEvent::Undo => {
+                let change = self.control_history.undo();
+                self.apply_history_change(change);
+            }
+            RuntimeEvent::Redo => {
+                let change = self.control_history.redo();
+                self.apply_history_change(change);
+            }
             RuntimeEvent::UpdateExclusions(exclusions) => {
                 self.set_exclusions(exclusions);
             }
             RuntimeEvent::UpdateUiControl((name, value)) => {
-                let should_emit_updated_controls = matches!(
-                    value,
-                    ControlValue::Bool(_) | ControlValue::String(_)
-                );
+                let should_emit_updated_controls =
+                    !matches!(value, ControlValue::Float(_));
 
                 self.apply_control_update(name, value);
 
@@ -1012,6 +1369,10 @@ impl XtalRuntime {
             | RuntimeEvent::SketchSwitched(_)
             | RuntimeEvent::Stopped
             | RuntimeEvent::WebView(_) => {}
+            // Handled by `on_runtime_event`
+            RuntimeEvent::OpenOutputWindow(_)
+            | RuntimeEvent::Quit
+            | RuntimeEvent::SetMonitorPreview(_) => {}
         }
 
         CONTINUE_HANDLING
@@ -1027,7 +1388,8 @@ impl XtalRuntime {
         }
     }
 
-    // Throttled FPS broadcast to UI (once per second).
+    // Throttled FPS (and clock-following BPM, and pass timings when enabled)
+    // broadcast to UI, once per second.
     fn emit_average_fps_if_due(&mut self, now: Instant) {
         if now.duration_since(self.last_average_fps_emit)
             < Duration::from_secs(1)
@@ -1036,9 +1398,45 @@ impl XtalRuntime {
         }
 
         self.last_average_fps_emit = now;
-        self.emit_web_view_event(web_view::Event::AverageFps(
-            frame_clock::average_fps(),
-        ));
+        let average_fps = frame_clock::average_fps();
+        self.emit_web_view_event(web_view::Event::AverageFps(average_fps));
+        self.update_adaptive_quality(average_fps);
+        if self.follow_clock_tempo.load(Ordering::Acquire) {
+            self.emit_web_view_event(web_view::Event::Bpm(self.bpm.get()));
+        }
+        #[cfg(feature = "timing")]
+        if let Some(timings) = self
+            .graph
+            .as_mut()
+            .and_then(CompiledGraph::take_pass_timings)
+        {
+            self.emit_web_view_event(web_view::Event::PassTimings(timings));
+        }
+    }
+
+    // Feed the once-a-second fps sample to adaptive quality. Recordings and
+    // paused or offline frames aren't paced in real time, so they render at
+    // the maximum scale instead.
+    fn update_adaptive_quality(&mut self, average_fps: f32) {
+        let hold = self.recording_state.is_recording
+            || frame_clock::paused()
+            || frame_clock::offline();
+        let scale = if hold {
+            self.adaptive_quality.reset();
+            self.adaptive_quality.scale()
+        } else {
+            match self.adaptive_quality.update(average_fps, self.config.fps) {
+                Some(scale) => {
+                    debug!("Adaptive quality render scale: {:.2}", scale);
+                    scale
+                }
+                None => return,
+            }
+        };
+
+        if let Some(graph) = self.graph.as_mut() {
+            graph.set_render_scale(scale);
+        }
     }
 
     // Main render/update pipeline.
@@ -1061,7 +1459,7 @@ impl XtalRuntime {
             pending_png_capture_error,
             capture_device,
             capture_submission_index,
-            monitor_render_result,
+            mirror_render_results,
         ) = {
             // 1) Resolve runtime resources for this frame.
             let Some(context) = self.context.as_mut() else {
@@ -1078,13 +1476,23 @@ impl XtalRuntime {
             };
 
             // 2) Let sketch mutate runtime state before uniform upload.
+            let phase_started = Instant::now();
             self.sketch.update(context);
+            self.frame_budget
+                .record(FramePhase::SketchUpdate, phase_started.elapsed());
 
             // 3) Runtime-owned uniforms: resolution + beat source + hub vars.
-            let [w, h] = context.resolution();
+            let [w, h] = graph
+                .export_size()
+                .map(|[w, h]| [w as f32, h as f32])
+                .unwrap_or_else(|| context.resolution());
             uniforms.set_resolution(w, h);
+            uniforms
+                .set_mouse(context.mouse_position(), context.mouse_buttons());
+            uniforms.set_camera(&context.camera(), w / h.max(1.0));
             let current_beats;
 
+            let phase_started = Instant::now();
             if let Some(hub) = self.control_hub.as_mut() {
                 if let Some(beats) = external_beats_for_frame {
                     hub.animation.timing.set_external_beats(beats);
@@ -1100,13 +1508,33 @@ impl XtalRuntime {
                     }
                 }
 
+                graph.update_instance_counts(|name| hub.get(name));
+
                 current_beats = hub.beats();
             } else {
                 current_beats = context.elapsed_seconds();
             }
+            self.frame_budget
+                .record(FramePhase::HubUpdate, phase_started.elapsed());
 
             uniforms.set_beats(current_beats);
             uniforms.upload(context.queue.as_ref());
+            graph.update_instance_buffers(
+                context.device.as_ref(),
+                context.queue.as_ref(),
+            );
+
+            #[cfg(feature = "video")]
+            graph.update_videos(
+                context.queue.as_ref(),
+                frame_clock::frame_count(),
+                frame_clock::render_fps(),
+            );
+            #[cfg(feature = "ndi")]
+            graph.update_ndi_inputs(
+                context.device.as_ref(),
+                context.queue.as_ref(),
+            );
 
             // 4) Acquire current presentation surface texture.
             let Some(surface) = self.surface.as_mut() else {
@@ -1143,6 +1571,7 @@ impl XtalRuntime {
                 output,
             );
 
+            let phase_started = Instant::now();
             self.sketch.view(&mut frame, context);
 
             if let Err(err) = graph.execute(
@@ -1156,9 +1585,46 @@ impl XtalRuntime {
                 return;
             }
 
-            // 6) Recording readback copy is encoded pre-submit.
+            if frame.has_text() {
+                let renderer = self.text_renderer.get_or_insert_with(|| {
+                    TextRenderer::new(
+                        context.device.as_ref(),
+                        context.queue.as_ref(),
+                        surface_config.format,
+                    )
+                });
+                renderer.render(
+                    context.device.as_ref(),
+                    context.queue.as_ref(),
+                    &mut frame,
+                    context.resolution_u32(),
+                );
+            }
+            self.frame_budget
+                .record(FramePhase::GraphEncode, phase_started.elapsed());
+
+            // Warn (throttled) when this frame's CPU work went over budget.
+            // Offline rendering isn't paced in real time, so it has no budget.
+            let frame_time =
+                Duration::from_secs_f32(1.0 / frame_clock::render_fps());
+            let overrun = self
+                .frame_budget
+                .finish_frame(frame_time, Instant::now())
+                .filter(|_| !frame_clock::offline());
+            if let Some(overrun) = overrun {
+                warn!("{}", overrun);
+            }
+
+            // 6) Recording readback copy is encoded pre-submit. Timelapse
+            // stills ride along with it while recording.
+            let mut timelapse_still = self.timelapse.as_mut().and_then(|t| {
+                t.tick(frame_clock::frame_count(), current_beats)
+            });
             if self.recording_state.is_recording {
                 if let Some(recorder) = self.recording_state.recorder.as_mut() {
+                    if let Some(path) = timelapse_still.take() {
+                        recorder.request_still(path);
+                    }
                     if let Some(source_texture) = graph.recording_source_texture()
                     {
                         let encoder = frame.encoder();
@@ -1173,122 +1639,64 @@ impl XtalRuntime {
                 }
             }
 
+            // NDI output reads back the same source as the recorder.
+            #[cfg(feature = "ndi")]
+            if let Some(ndi_output) = self.ndi_output.as_mut() {
+                let fps = frame_clock::fps();
+                if let Some(source_texture) = graph.recording_source_texture() {
+                    ndi_output.capture(frame.encoder(), source_texture, fps);
+                } else {
+                    let (encoder, source_texture) =
+                        frame.encoder_and_output_texture();
+                    ndi_output.capture(encoder, source_texture, fps);
+                }
+            }
+
             // 7) Optional still-image capture readback copy is also pre-submit.
             let mut pending_png_capture_error = None;
-            let pending_png_capture = if let Some(path) =
-                self.pending_png_capture_path.take()
-            {
+            let announce_png_capture = self.pending_png_capture_path.is_some();
+            let capture_outputs: Vec<(PathBuf, StillFormat)> = self
+                .pending_png_capture_path
+                .take()
+                .map(|path| (path, self.still_format))
+                .into_iter()
+                .chain(timelapse_still.map(|path| (path, StillFormat::Png8)))
+                .collect();
+            let pending_png_capture = if !capture_outputs.is_empty() {
                 let source_texture = graph.recording_source_texture();
                 let source_format = graph.recording_source_format().or_else(|| {
                     self.surface_config.as_ref().map(|config| config.format)
                 });
-                match (source_texture, source_format) {
+                let readback = match (source_texture, source_format) {
                     (Some(source_texture), Some(source_format)) => {
-                        let width = source_texture.size().width.max(1);
-                        let height = source_texture.size().height.max(1);
-                        let bytes_per_pixel = 4u32;
-                        let unpadded_bytes_per_row = width * bytes_per_pixel;
-                        let padded_bytes_per_row = unpadded_bytes_per_row
-                            + compute_row_padding(unpadded_bytes_per_row);
-                        let buffer_size =
-                            (padded_bytes_per_row as u64) * (height as u64);
-                        let buffer = context.device.create_buffer(
-                            &wgpu::BufferDescriptor {
-                                label: Some("xtal-capture-readback"),
-                                size: buffer_size,
-                                usage: wgpu::BufferUsages::COPY_DST
-                                    | wgpu::BufferUsages::MAP_READ,
-                                mapped_at_creation: false,
-                            },
-                        );
-
-                        frame.encoder().copy_texture_to_buffer(
-                            wgpu::TexelCopyTextureInfo {
-                                texture: source_texture,
-                                mip_level: 0,
-                                origin: wgpu::Origin3d::ZERO,
-                                aspect: wgpu::TextureAspect::All,
-                            },
-                            wgpu::TexelCopyBufferInfo {
-                                buffer: &buffer,
-                                layout: wgpu::TexelCopyBufferLayout {
-                                    offset: 0,
-                                    bytes_per_row: Some(padded_bytes_per_row),
-                                    rows_per_image: Some(height),
-                                },
-                            },
-                            wgpu::Extent3d {
-                                width,
-                                height,
-                                depth_or_array_layers: 1,
-                            },
-                        );
-
-                        Some(PendingPngCapture {
-                            path,
-                            buffer,
-                            width,
-                            height,
-                            padded_bytes_per_row,
+                        StillReadback::copy(
+                            context.device.as_ref(),
+                            frame.encoder(),
+                            source_texture,
                             source_format,
-                        })
+                        )
                     }
                     (None, Some(source_format)) => {
                         let (encoder, source_texture) =
                             frame.encoder_and_output_texture();
-                        let width = source_texture.size().width.max(1);
-                        let height = source_texture.size().height.max(1);
-                        let bytes_per_pixel = 4u32;
-                        let unpadded_bytes_per_row = width * bytes_per_pixel;
-                        let padded_bytes_per_row = unpadded_bytes_per_row
-                            + compute_row_padding(unpadded_bytes_per_row);
-                        let buffer_size =
-                            (padded_bytes_per_row as u64) * (height as u64);
-                        let buffer = context.device.create_buffer(
-                            &wgpu::BufferDescriptor {
-                                label: Some("xtal-capture-readback"),
-                                size: buffer_size,
-                                usage: wgpu::BufferUsages::COPY_DST
-                                    | wgpu::BufferUsages::MAP_READ,
-                                mapped_at_creation: false,
-                            },
-                        );
-
-                        encoder.copy_texture_to_buffer(
-                            wgpu::TexelCopyTextureInfo {
-                                texture: source_texture,
-                                mip_level: 0,
-                                origin: wgpu::Origin3d::ZERO,
-                                aspect: wgpu::TextureAspect::All,
-                            },
-                            wgpu::TexelCopyBufferInfo {
-                                buffer: &buffer,
-                                layout: wgpu::TexelCopyBufferLayout {
-                                    offset: 0,
-                                    bytes_per_row: Some(padded_bytes_per_row),
-                                    rows_per_image: Some(height),
-                                },
-                            },
-                            wgpu::Extent3d {
-                                width,
-                                height,
-                                depth_or_array_layers: 1,
-                            },
-                        );
-
-                        Some(PendingPngCapture {
-                            path,
-                            buffer,
-                            width,
-                            height,
-                            padded_bytes_per_row,
+                        StillReadback::copy(
+                            context.device.as_ref(),
+                            encoder,
+                            source_texture,
                             source_format,
-                        })
+                        )
                     }
-                    _ => {
-                        pending_png_capture_error = Some(
-                            "Failed to capture frame: no capture source texture".to_string(),
-                        );
+                    _ => Err("no capture source texture".to_string()),
+                };
+                match readback {
+                    Ok(readback) => Some(PendingPngCapture {
+                        outputs: capture_outputs,
+                        announce: announce_png_capture,
+                        readback,
+                    }),
+                    Err(err) => {
+                        pending_png_capture_error =
+                            Some(format!("Failed to capture frame: {}", err));
                         None
                     }
                 }
@@ -1297,7 +1705,7 @@ impl XtalRuntime {
             };
 
             let mut monitor_fallback_texture = None;
-            if self.monitor_preview.is_some()
+            if (self.monitor_preview.is_some() || self.output_window.is_some())
                 && graph.recording_source_texture().is_none()
             {
                 let (encoder, source_texture) =
@@ -1346,24 +1754,30 @@ impl XtalRuntime {
             // 8) Submit all encoded GPU work once.
             let submission_index = frame.submit();
 
-            // 9) Mirror to optional monitor preview from the finalized graph
-            // source texture. Do not sample from surface output textures.
-            let monitor_render_result =
-                if let Some(preview) = self.monitor_preview.as_mut() {
-                    let source_texture = graph
-                        .recording_source_texture()
-                        .or(monitor_fallback_texture.as_ref());
-                    source_texture.map(|texture| {
-                        preview.render_if_due(context, texture, Instant::now())
-                    })
-                } else {
-                    None
-                };
+            // 9) Mirror to optional monitor preview and output window from
+            // the finalized graph source texture. Do not sample from surface
+            // output textures.
+            let source_texture = graph
+                .recording_source_texture()
+                .or(monitor_fallback_texture.as_ref());
+            let mirror_render_results =
+                [self.monitor_preview.as_mut(), self.output_window.as_mut()]
+                    .map(|mirror| {
+                        mirror.zip(source_texture).map(|(mirror, texture)| {
+                            mirror.render_if_due(
+                                context,
+                                texture,
+                                Instant::now(),
+                            )
+                        })
+                    });
 
             if self.recording_state.is_recording {
-                if let Some(recorder) = self.recording_state.recorder.as_mut() {
-                    recorder.on_submitted();
-                }
+                self.recording_state.on_submitted();
+            }
+            #[cfg(feature = "ndi")]
+            if let Some(ndi_output) = self.ndi_output.as_mut() {
+                ndi_output.on_submitted();
             }
 
             // 10) Advance local frame-time state after successful submits.
@@ -1374,13 +1788,14 @@ impl XtalRuntime {
                 pending_png_capture_error,
                 context.device.clone(),
                 submission_index,
-                monitor_render_result,
+                mirror_render_results,
             )
         };
 
         // 11) Post-submit host-side effects/events.
-        if matches!(monitor_render_result, Some(MonitorRenderResult::OutOfMemory))
-        {
+        if mirror_render_results.iter().any(|result| {
+            matches!(result, Some(MonitorRenderResult::OutOfMemory))
+        }) {
             error!("monitor preview surface out of memory; exiting");
             self.shutdown(event_loop);
             return;
@@ -1390,6 +1805,26 @@ impl XtalRuntime {
             self.alert_and_log(message, log::Level::Error);
         }
 
+        self.send_midi_feedback();
+
+        self.tick_recording_countdown();
+
+        if self.recording_state.is_recording
+            && self.recording_state.offline_complete()
+        {
+            let _ =
+                self.on_runtime_event(event_loop, RuntimeEvent::StopRecording);
+        }
+
+        let shader_errors = self
+            .graph
+            .as_mut()
+            .map(CompiledGraph::take_shader_errors)
+            .unwrap_or_default();
+        for err in shader_errors {
+            self.emit_web_view_event(web_view::Event::ShaderError(err));
+        }
+
         if self.recording_state.is_encoding {
             if let Some(outcome) =
                 self.recording_state.poll_finalize(&mut self.session_id)
@@ -1462,6 +1897,14 @@ impl XtalRuntime {
         }
 
         match code {
+            KeyCode::KeyA | KeyCode::KeyB if self.modifiers.alt_key() => {
+                let event = if code == KeyCode::KeyA {
+                    RuntimeEvent::StashA
+                } else {
+                    RuntimeEvent::StashB
+                };
+                return self.on_runtime_event(event_loop, event);
+            }
             KeyCode::KeyA => {
                 if frame_clock::paused() {
                     return self.on_runtime_event(
@@ -1470,6 +1913,16 @@ impl XtalRuntime {
                     );
                 }
             }
+            KeyCode::KeyB if has_no_modifiers => {
+                return self
+                    .on_runtime_event(event_loop, RuntimeEvent::ToggleAB);
+            }
+            KeyCode::KeyF if self.modifiers.alt_key() => {
+                return self.on_runtime_event(
+                    event_loop,
+                    RuntimeEvent::ToggleFreezeRandom,
+                );
+            }
             KeyCode::KeyF => {
                 return self.on_runtime_event(
                     event_loop,
@@ -1479,9 +1932,17 @@ impl XtalRuntime {
             KeyCode::KeyG => {
                 self.emit_web_view_event(web_view::Event::ToggleGuiFocus);
             }
-            KeyCode::KeyI => {
+            KeyCode::KeyH if has_no_modifiers => {
                 return self
-                    .on_runtime_event(event_loop, RuntimeEvent::CaptureFrame);
+                    .on_runtime_event(event_loop, RuntimeEvent::ToggleHold);
+            }
+            KeyCode::KeyI => {
+                let event = if shift_pressed {
+                    RuntimeEvent::ToggleTimelapse
+                } else {
+                    RuntimeEvent::CaptureFrame
+                };
+                return self.on_runtime_event(event_loop, event);
             }
             KeyCode::KeyM => {
                 if !platform_mod_pressed {
@@ -1519,6 +1980,14 @@ impl XtalRuntime {
                         RuntimeEvent::Randomize(exclusions),
                     );
                 }
+                if self.modifiers.alt_key() {
+                    let event = if shift_pressed {
+                        RuntimeEvent::SetRandomizeSeed(None)
+                    } else {
+                        RuntimeEvent::AdvanceRandomizeSeed
+                    };
+                    return self.on_runtime_event(event_loop, event);
+                }
                 if has_no_modifiers {
                     return self
                         .on_runtime_event(event_loop, RuntimeEvent::Reset);
@@ -1533,6 +2002,29 @@ impl XtalRuntime {
                     );
                 }
             }
+            KeyCode::KeyZ if platform_mod_pressed => {
+                let event = if shift_pressed {
+                    RuntimeEvent::Redo
+                } else {
+                    RuntimeEvent::Undo
+                };
+                return self.on_runtime_event(event_loop, event);
+            }
+            KeyCode::BracketLeft | KeyCode::BracketRight
+                if has_no_modifiers =>
+            {
+                let name = if code == KeyCode::BracketRight {
+                    self.registry.next_sketch_name(&self.active_sketch_name)
+                } else {
+                    self.registry.previous_sketch_name(&self.active_sketch_name)
+                };
+                if let Some(name) = name {
+                    return self.on_runtime_event(
+                        event_loop,
+                        RuntimeEvent::SwitchSketch(name),
+                    );
+                }
+            }
             KeyCode::Space => {
                 if self.tap_tempo_enabled {
                     return self
@@ -1580,7 +2072,7 @@ impl XtalRuntime {
         let (device, queue) = pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
                 label: Some("xtal-device"),
-                required_features: wgpu::Features::empty(),
+                required_features: required_features(&adapter),
                 required_limits: wgpu::Limits::default(),
                 memory_hints: wgpu::MemoryHints::Performance,
                 trace: wgpu::Trace::default(),
@@ -1628,8 +2120,12 @@ impl XtalRuntime {
         self.surface = Some(surface);
         self.surface_config = Some(surface_config);
         self.context = Some(context);
+        #[cfg(feature = "ndi")]
+        self.start_ndi_output();
 
         self.rebuild_graph_state()?;
+        #[cfg(feature = "source-watch")]
+        self.restart_source_watch();
 
         Ok(())
     }
@@ -1650,6 +2146,7 @@ impl XtalRuntime {
 
         self.control_hub = self.build_control_hub();
         self.restore_sketch_state_from_disk();
+        self.restore_loop_region();
 
         let Some(surface_config) = self.surface_config.as_ref() else {
             return Err("surface config not initialized".to_string());
@@ -1663,19 +2160,137 @@ impl XtalRuntime {
             surface_config.format,
             graph_spec,
             uniforms.bind_group_layout(),
-        )?;
+        );
+
+        let mut graph = match graph {
+            Ok(graph) => graph,
+            Err(GraphError::Shader(err)) => {
+                let message = err.to_string();
+                self.emit_web_view_event(web_view::Event::ShaderError(err));
+                return Err(message);
+            }
+            Err(GraphError::Graph(message)) => return Err(message),
+        };
+        graph.set_export_size(self.config.export_size);
+        if self.config.msaa_samples != 1 {
+            let samples = self.adapter.as_ref().map_or(1, |adapter| {
+                supported_msaa_samples(
+                    adapter,
+                    surface_config.format,
+                    self.config.msaa_samples,
+                )
+            });
+            if let Err(err) = graph.set_msaa_samples(
+                context.device.as_ref(),
+                uniforms.bind_group_layout(),
+                samples,
+            ) {
+                let message = err.to_string();
+                self.emit_web_view_event(web_view::Event::ShaderError(err));
+                return Err(message);
+            }
+        }
+        graph.set_render_scale(self.adaptive_quality.scale());
 
         self.uniforms = Some(uniforms);
         self.graph = Some(graph);
 
+        if let Some(image) = self.dropped_image.clone() {
+            self.replace_dropped_images(&image);
+        }
+
         Ok(())
     }
 
+    // Carries the loop region over to a freshly built hub. A region the new
+    // hub rejects (e.g. after switching to an external transport) is dropped.
+    fn restore_loop_region(&mut self) {
+        let Some(hub) = self.control_hub.as_mut() else {
+            return;
+        };
+        if let Err(err) = hub.set_loop_region(self.loop_region) {
+            warn!("Clearing loop region: {}", err);
+            self.loop_region = None;
+        }
+    }
+
+    // Decodes a dropped file off the main thread; the result comes back
+    // through the command channel tagged with this drop's id.
+    fn decode_dropped_image(&mut self, path: PathBuf) {
+        self.image_drop_id += 1;
+        let id = self.image_drop_id;
+        let command_tx = self.command_tx.clone();
+
+        std::thread::spawn(move || {
+            let result = image::load_image(&path).map(Arc::new);
+            let _ = command_tx
+                .send(RuntimeEvent::DroppedImageDecoded(id, path, result));
+        });
+    }
+
+    // Swaps the decoded image into the graph and keeps it so graph rebuilds
+    // (e.g. shader or control reloads) don't revert to the fallback.
+    fn apply_dropped_image(
+        &mut self,
+        path: PathBuf,
+        result: Result<Arc<DecodedImage>, String>,
+    ) {
+        let image = match result {
+            Ok(image) => image,
+            Err(err) => {
+                self.alert_and_log(
+                    format!("Failed to load dropped image: {}", err),
+                    log::Level::Error,
+                );
+                return;
+            }
+        };
+
+        let replaced = self.replace_dropped_images(&image);
+        self.dropped_image = Some(image.clone());
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        if replaced == 0 {
+            self.alert_and_log(
+                format!(
+                    "Ignoring dropped image {}: {} has no dropped_image texture",
+                    name, self.config.display_name
+                ),
+                log::Level::Warn,
+            );
+            return;
+        }
+
+        self.alert_and_log(
+            format!("Loaded {} ({}x{})", name, image.width, image.height),
+            log::Level::Info,
+        );
+        self.request_render_now();
+    }
+
+    // Uploads `image` into the graph's drop-target textures.
+    fn replace_dropped_images(&mut self, image: &DecodedImage) -> usize {
+        let (Some(context), Some(graph)) =
+            (self.context.as_ref(), self.graph.as_mut())
+        else {
+            return 0;
+        };
+        graph.replace_dropped_images(
+            context.device.as_ref(),
+            context.queue.as_ref(),
+            image,
+        )
+    }
+
     // Builds hub from sketch control script and wires callback bridges.
     fn build_control_hub(&self) -> Option<ControlHub<Timing>> {
-        let path = self.sketch.control_script()?;
+        let path = self.sketch.control_script();
+        let embedded = self.sketch.embedded_control_script();
 
-        if !path.exists() {
+        if let Some(path) = path.as_ref().filter(|path| !path.exists()) {
             warn!(
                 "control script for sketch '{}' does not exist: {}",
                 self.config.name,
@@ -1692,15 +2307,21 @@ impl XtalRuntime {
             TimingMode::Manual => Timing::manual(self.bpm.clone()),
         };
 
-        let mut hub = ControlHub::from_path(path, timing);
+        let mut hub = match (path, embedded) {
+            (Some(path), _) => ControlHub::from_path(path, timing),
+            (None, Some(yaml)) => ControlHub::from_str(yaml, timing),
+            (None, None) => return None,
+        };
         hub.set_transition_time(self.transition_time);
+        if let Some(seed) = self.randomize_seed {
+            hub.set_randomize_seed(seed);
+        }
         hub.midi_overrides_enabled = self.mappings_enabled;
         hub.midi_controls.hrcc = self.hrcc;
         hub.midi_controls.set_port(self.midi_input_port.clone());
         hub.midi_controls
             .set_override_state(hub.midi_overrides.clone());
-        hub.midi_controls
-            .set_override_configs(hub.midi_override_configs.clone());
+        hub.sync_midi_override_configs();
         hub.audio_controls
             .set_device_name(self.audio_device.clone());
         info!(
@@ -1755,6 +2376,8 @@ impl XtalRuntime {
         let clock_count = self.midi_clock_count.clone();
         let song_position_ticks = self.midi_song_position_ticks.clone();
         let follow_song_position = self.follow_song_position.clone();
+        let follow_clock_tempo = self.follow_clock_tempo.clone();
+        let clock_tempo = Mutex::new(ClockTempo::new());
         let hybrid_mtc_sync_enabled = self.hybrid_mtc_sync_enabled.clone();
         let mtc_hours = self.mtc_hours.clone();
         let mtc_minutes = self.mtc_minutes.clone();
@@ -1764,7 +2387,7 @@ impl XtalRuntime {
         let midi_handler_result = midi::on_message(
             midi::ConnectionType::Clock,
             &self.midi_clock_port,
-            move |_stamp, message| {
+            move |stamp, message| {
                 if message.is_empty() {
                     return;
                 }
@@ -1772,6 +2395,13 @@ impl XtalRuntime {
                 match message[0] {
                     MIDI_CLOCK => {
                         clock_count.fetch_add(1, Ordering::SeqCst);
+                        if !follow_clock_tempo.load(Ordering::Acquire) {
+                            return;
+                        }
+                        let estimate = clock_tempo.lock().unwrap().tick(stamp);
+                        if let Some(estimate) = estimate {
+                            bpm.set_external(Some(estimate));
+                        }
                     }
                     MIDI_SONG_POSITION => {
                         if !follow_song_position.load(Ordering::Acquire) {
@@ -1793,6 +2423,7 @@ impl XtalRuntime {
                     }
                     MIDI_START => {
                         clock_count.store(0, Ordering::SeqCst);
+                        clock_tempo.lock().unwrap().reset();
                         let _ = command_tx.send(RuntimeEvent::MidiStart);
                     }
                     MIDI_CONTINUE => {
@@ -1942,7 +2573,8 @@ impl XtalRuntime {
         let ticks = self.osc_transport_ticks.clone();
 
         OSC_TRANSPORT_CALLBACK_REGISTER.call_once(move || {
-            SHARED_OSC_RECEIVER.register_callback("/transport", move |msg| {
+            // The plug-in sends from a fixed path, whatever the OSC prefix
+            SHARED_OSC_RECEIVER.register_unprefixed("/transport", move |msg| {
                 if msg.args.len() < 4 {
                     return;
                 }
@@ -1969,6 +2601,51 @@ impl XtalRuntime {
         });
     }
 
+    // Echoes MIDI-mapped control values to the MIDI output so motorized
+    // faders and LED rings follow UI edits and snapshot transitions.
+    fn send_midi_feedback(&mut self) {
+        if !self.midi_feedback_enabled {
+            return;
+        }
+        let (Some(hub), Some(midi_out)) =
+            (self.control_hub.as_ref(), self.midi_out.as_mut())
+        else {
+            return;
+        };
+
+        let messages = self.midi_feedback.messages(
+            &hub.midi_mapped_values(),
+            self.hrcc,
+            Instant::now(),
+        );
+        for message in messages {
+            if let Err(err) = midi_out.send(&message) {
+                warn!("Error sending MIDI feedback {:?}: {}", message, err);
+                return;
+            }
+        }
+    }
+
+    // Sends All Sound Off and All Notes Off on every channel so switching
+    // sketches, stopping a recording or quitting can't leave notes hanging on
+    // external gear.
+    fn send_midi_panic(&mut self) {
+        let Some(midi_out) = self
+            .midi_out
+            .as_mut()
+            .filter(|midi_out| midi_out.is_connected())
+        else {
+            return;
+        };
+
+        for message in midi::panic_messages() {
+            if let Err(err) = midi_out.send(&message) {
+                warn!("Error sending MIDI panic {:?}: {}", message, err);
+                return;
+            }
+        }
+    }
+
     fn connect_midi_out(&mut self) {
         if self.midi_output_port.is_empty() {
             info!("Skipping MIDI output connection; no MIDI output port.");
@@ -1978,6 +2655,7 @@ impl XtalRuntime {
 
         info!("Connecting MIDI output on port '{}'", self.midi_output_port);
 
+        self.midi_feedback.clear();
         let mut midi_out = midi::MidiOut::new(&self.midi_output_port);
         self.midi_out = match midi_out.connect() {
             Ok(_) => {
@@ -2113,6 +2791,213 @@ impl XtalRuntime {
         true
     }
 
+    // Persisted selections that weren't found at startup stay preferred so
+    // they are reconnected if the device shows up later in the session.
+    fn init_device_recovery(&mut self, persisted: [String; 4]) {
+        let [audio, midi_input, midi_clock, midi_output] = persisted;
+        let or_active = |persisted: String, active: &str| {
+            if persisted.is_empty() {
+                active.to_string()
+            } else {
+                persisted
+            }
+        };
+        let input_names = port_names(&self.midi_input_ports);
+        let output_names = port_names(&self.midi_output_ports);
+
+        self.device_recovery.audio = DeviceWatch::new(
+            or_active(audio, &self.audio_device),
+            self.audio_devices.clone(),
+        );
+        self.device_recovery.midi_input = DeviceWatch::new(
+            or_active(midi_input, &self.midi_input_port),
+            input_names.clone(),
+        );
+        self.device_recovery.midi_clock = DeviceWatch::new(
+            or_active(midi_clock, &self.midi_clock_port),
+            input_names,
+        );
+        self.device_recovery.midi_output = DeviceWatch::new(
+            or_active(midi_output, &self.midi_output_port),
+            output_names,
+        );
+    }
+
+    // A control script that fails to parse, including an `!include` that
+    // can't be resolved, keeps its previous controls, so say why in the UI.
+    fn report_script_error(&self) {
+        let Some(err) = self
+            .control_hub
+            .as_ref()
+            .and_then(|hub| hub.take_script_error())
+        else {
+            return;
+        };
+        self.alert_and_log(
+            format!("Control script error: {}", err),
+            log::Level::Error,
+        );
+    }
+
+    // Re-lists audio devices and MIDI ports every few seconds (immediately
+    // after an audio stream error), falling back to another device when the
+    // active one disappears and switching back when the original returns.
+    fn recover_devices_if_due(&mut self, now: Instant) {
+        let stream_error = self
+            .control_hub
+            .as_ref()
+            .is_some_and(|hub| hub.audio_controls.take_stream_error());
+        if !self.device_recovery.poll_due(now, stream_error) {
+            return;
+        }
+
+        // Enumerating devices can block for a long time on some backends
+        let command_tx = self.command_tx.clone();
+        std::thread::spawn(move || {
+            let listing = DeviceListing::collect();
+            let _ = command_tx.send(RuntimeEvent::DevicesListed(listing));
+        });
+    }
+
+    fn apply_device_listing(&mut self, listing: DeviceListing) {
+        let stream_error = self.device_recovery.listed();
+        let mut changed = false;
+
+        if let Some(devices) = listing.audio {
+            let change = self
+                .device_recovery
+                .audio
+                .update(&self.audio_device, devices.clone());
+            if let Some(device) =
+                self.apply_device_change("Audio device", change)
+            {
+                self.audio_device = device;
+                self.audio_devices = devices;
+                self.restart_audio_controls();
+                changed = true;
+            } else if stream_error {
+                info!("Restarting audio after stream error");
+                self.restart_audio_controls();
+            }
+        }
+
+        if let Some(ports) = listing.midi_inputs {
+            let names = port_names(&ports);
+            let input_change = self
+                .device_recovery
+                .midi_input
+                .update(&self.midi_input_port, names.clone());
+            let clock_change = self
+                .device_recovery
+                .midi_clock
+                .update(&self.midi_clock_port, names);
+            self.midi_input_ports = ports;
+
+            if let Some(port) =
+                self.apply_device_change("MIDI input port", input_change)
+            {
+                self.midi_input_port = port;
+                self.restart_midi_controls();
+                changed = true;
+            }
+            if let Some(port) =
+                self.apply_device_change("MIDI clock port", clock_change)
+            {
+                self.midi_clock_port = port;
+                if self.midi_clock_port.is_empty() {
+                    midi::disconnect(midi::ConnectionType::Clock);
+                }
+                self.start_midi_clock_listener();
+                changed = true;
+            }
+        }
+
+        if let Some(ports) = listing.midi_outputs {
+            let change = self
+                .device_recovery
+                .midi_output
+                .update(&self.midi_output_port, port_names(&ports));
+            self.midi_output_ports = ports;
+            if let Some(port) =
+                self.apply_device_change("MIDI output port", change)
+            {
+                self.midi_output_port = port;
+                self.connect_midi_out();
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.emit_web_view_init();
+        }
+    }
+
+    // Alerts about a device change and returns the device to switch to
+    // (empty when nothing is left).
+    fn apply_device_change(
+        &self,
+        label: &str,
+        change: Option<DeviceChange>,
+    ) -> Option<String> {
+        match change? {
+            DeviceChange::FellBack { lost, fallback } => {
+                self.alert_and_log(
+                    format!(
+                        "{} '{}' disconnected; using '{}' until it returns",
+                        label, lost, fallback
+                    ),
+                    log::Level::Warn,
+                );
+                Some(fallback)
+            }
+            DeviceChange::Lost(lost) => {
+                self.alert_and_log(
+                    format!(
+                        "{} '{}' disconnected and no other is available",
+                        label, lost
+                    ),
+                    log::Level::Warn,
+                );
+                Some(String::new())
+            }
+            DeviceChange::Restored(device) => {
+                self.alert_and_log(
+                    format!("{} '{}' reconnected", label, device),
+                    log::Level::Info,
+                );
+                Some(device)
+            }
+        }
+    }
+
+    fn restart_audio_controls(&mut self) {
+        if let Some(hub) = self.control_hub.as_mut() {
+            hub.audio_controls
+                .set_device_name(self.audio_device.clone());
+            hub.audio_controls
+                .restart()
+                .inspect_err(|err| {
+                    error!("Error restarting audio controls: {}", err)
+                })
+                .ok();
+        }
+    }
+
+    fn restart_midi_controls(&mut self) {
+        if self.midi_input_port.is_empty() {
+            midi::disconnect(midi::ConnectionType::Control);
+        }
+        if let Some(hub) = self.control_hub.as_mut() {
+            hub.midi_controls.set_port(self.midi_input_port.clone());
+            hub.midi_controls
+                .restart()
+                .inspect_err(|err| {
+                    error!("Error restarting MIDI controls: {}", err);
+                })
+                .ok();
+        }
+    }
+
     fn normalize_osc_port_selection(&mut self) -> bool {
         if self.osc_port == 0 {
             self.osc_port = DEFAULT_OSC_PORT;
@@ -2123,14 +3008,34 @@ impl XtalRuntime {
     }
 
     fn start_osc_receiver(&self) {
+        SHARED_OSC_RECEIVER.set_prefix(&self.osc_prefix);
         if let Err(err) = SHARED_OSC_RECEIVER.restart(self.osc_port) {
             error!("Failed to restart OSC receiver: {}", err);
         }
     }
 
-    fn restart_osc_receiver(&self) {
+    // Bound once so sketches' `osc_send` calls only ever send.
+    fn start_osc_sender(&self) {
+        if let Err(err) = SHARED_OSC_SENDER.bind() {
+            error!("Failed to start OSC sender: {}", err);
+        }
+    }
+
+    // Callbacks (e.g. /transport) survive the rebind. If the new port can't
+    // be bound the receiver stays on the old one, so reflect that.
+    fn restart_osc_receiver(&mut self) {
         if let Err(err) = SHARED_OSC_RECEIVER.restart(self.osc_port) {
-            error!("Failed to restart OSC receiver: {}", err);
+            self.alert_and_log(
+                format!(
+                    "Failed to listen for OSC on port {}: {}",
+                    self.osc_port, err
+                ),
+                log::Level::Error,
+            );
+            if let Some(port) = SHARED_OSC_RECEIVER.port() {
+                self.osc_port = port;
+                self.emit_web_view_init();
+            }
         }
     }
 
@@ -2148,10 +3053,6 @@ impl XtalRuntime {
     }
 
     fn current_osc_transport_beats(&self) -> f32 {
-        if !self.osc_transport_playing.load(Ordering::Acquire) {
-            return 0.0;
-        }
-
         let bars = self.osc_transport_bars.load(Ordering::Acquire) as f32;
         let beats = self.osc_transport_beats.load(Ordering::Acquire) as f32;
         let ticks =
@@ -2159,9 +3060,16 @@ impl XtalRuntime {
         (bars * 4.0) + beats + ticks
     }
 
-    fn current_external_beats_for_mode(&self) -> Option<f32> {
+    fn current_external_beats_for_mode(&mut self) -> Option<f32> {
         match self.sketch.timing_mode() {
-            TimingMode::Osc => Some(self.current_osc_transport_beats()),
+            TimingMode::Osc => {
+                if !self.osc_transport_playing.load(Ordering::Acquire) {
+                    self.osc_transport_smoother.reset();
+                    return Some(0.0);
+                }
+                let beats = self.current_osc_transport_beats();
+                Some(self.osc_transport_smoother.smooth(beats, Instant::now()))
+            }
             TimingMode::Midi => Some(self.current_midi_transport_beats()),
             TimingMode::Hybrid => Some(self.current_hybrid_transport_beats()),
             TimingMode::Manual | TimingMode::Frame => None,
@@ -2172,6 +3080,14 @@ impl XtalRuntime {
         let mode = self.sketch.timing_mode();
         self.follow_song_position
             .store(matches!(mode, TimingMode::Midi), Ordering::Release);
+        let follow_clock_tempo =
+            matches!(mode, TimingMode::Midi | TimingMode::Hybrid);
+        self.follow_clock_tempo
+            .store(follow_clock_tempo, Ordering::Release);
+        if !follow_clock_tempo {
+            // Back to the tempo set by the sketch, the UI or tap tempo
+            self.bpm.set_external(None);
+        }
         self.hybrid_mtc_sync_enabled
             .store(matches!(mode, TimingMode::Hybrid), Ordering::Release);
     }
@@ -2196,7 +3112,11 @@ impl XtalRuntime {
         surface_config.height = new_size.height;
 
         surface.configure(context.device.as_ref(), surface_config);
-        context.set_window_size([new_size.width, new_size.height]);
+        resize_sketch_context(
+            self.sketch.as_mut(),
+            context,
+            [new_size.width, new_size.height],
+        );
         if let Some(preview) = self.monitor_preview.as_ref() {
             self.monitor_preview_size_hint = Some(preview.window().inner_size());
         }
@@ -2204,6 +3124,11 @@ impl XtalRuntime {
 
     // Internal runtime event emitter.
     fn emit_event(&self, event: RuntimeEvent) {
+        #[cfg(feature = "remote-control")]
+        if let Some(server) = self.remote_control.as_ref() {
+            server.broadcast(&event);
+        }
+
         let Some(event_tx) = self.event_tx.as_ref() else {
             return;
         };
@@ -2253,7 +3178,7 @@ impl XtalRuntime {
 
     // Sends one-time UI bootstrap payload.
     fn emit_web_view_init(&self) {
-        let event = web_view::Event::Init {
+        let init = web_view::InitPayload {
             audio_device: self.audio_device.clone(),
             audio_devices: self.audio_devices.clone(),
             hrcc: self.hrcc,
@@ -2261,25 +3186,36 @@ impl XtalRuntime {
             is_light_theme: true,
             mappings_enabled: self.mappings_enabled,
             midi_clock_port: self.midi_clock_port.clone(),
+            midi_feedback_enabled: self.midi_feedback_enabled,
             midi_input_port: self.midi_input_port.clone(),
             midi_output_port: self.midi_output_port.clone(),
             midi_input_ports: self.midi_input_ports.clone(),
             midi_output_ports: self.midi_output_ports.clone(),
             monitor_preview_enabled: self.monitor_preview.is_some(),
+            offline_recording_seconds: self.offline_recording_seconds,
             osc_port: self.osc_port,
+            osc_prefix: self.osc_prefix.clone(),
+            osc_transport_smoothing: self.osc_transport_smoother.smoothing,
+            perf_mode_hides_cursor: self.perf_cursor.hide_in_perf_mode(),
+            record_audio: self.record_audio,
+            recording_options: self.recording_options.clone(),
+            recording_pre_roll: self.recording_pre_roll,
             sketches_by_category: web_view::sketches_by_category(
                 &self.registry,
             ),
+            sketches_by_tag: web_view::sketches_by_tag(&self.registry),
             sketch_catalog: Some(web_view::sketch_catalog_from_registry(
                 &self.registry,
             )),
             sketch_name: self.active_sketch_name.clone(),
+            still_format: self.still_format,
+            timelapse_options: self.timelapse_options.clone(),
             transition_time: self.transition_time,
             user_data_dir: self.user_data_dir.clone(),
             videos_dir: self.videos_dir.clone(),
         };
 
-        self.emit_web_view_event(event);
+        self.emit_web_view_event(web_view::Event::Init(Box::new(init)));
     }
 
     // Sends active sketch payload (controls/snapshots/mappings/toggles).
@@ -2315,10 +3251,11 @@ impl XtalRuntime {
         self.map_mode.set_mappings(mappings.clone());
         let exclusions = self.current_sketch_ui_state().exclusions;
 
-        let event = web_view::Event::LoadSketch {
+        let sketch = web_view::LoadSketchPayload {
             bpm: self.bpm.get(),
             bypassed,
             controls,
+            description: self.config.description.map(str::to_string),
             display_name: self.config.display_name.to_string(),
             fps: self.config.fps,
             mappings,
@@ -2333,7 +3270,7 @@ impl XtalRuntime {
             exclusions,
         };
 
-        self.emit_web_view_event(event);
+        self.emit_web_view_event(web_view::Event::LoadSketch(Box::new(sketch)));
     }
 
     // Applies one UI control mutation into the hub and requests redraw.
@@ -2346,25 +3283,66 @@ impl XtalRuntime {
             return;
         };
 
-        hub.ui_controls.set(&name, value);
-
-        if self.config.play_mode == PlayMode::Advance && frame_clock::paused() {
-            frame_clock::advance_single_frame();
-            self.request_render_now();
+        if let Some(previous) = hub.ui_controls.value(&name) {
+            self.control_history.record(
+                &name,
+                previous.clone(),
+                value.clone(),
+                Instant::now(),
+            );
         }
+
+        hub.ui_controls.set(&name, value);
+        self.advance_paused_frame();
     }
 
-    // Swaps sketch instance/config, rebuilds runtime graph state, updates UI.
-    fn switch_sketch(&mut self, name: &str) -> Result<(), String> {
-        self.map_mode.stop();
+    // Sets an undone or redone value without recording it and echoes every
+    // control back to the UI since the change didn't come from there
+    fn apply_history_change(&mut self, change: Option<(String, ControlValue)>) {
+        let (Some((name, value)), Some(hub)) =
+            (change, self.control_hub.as_mut())
+        else {
+            return;
+        };
+
+        hub.ui_controls.set(&name, value);
+        self.advance_paused_frame();
 
-        let preserved_bpm = self.bpm.get();
+        if let Some(hub) = self.control_hub.as_ref() {
+            self.emit_web_view_event(web_view::Event::UpdatedControls(
+                web_view::controls_from_hub(hub),
+            ));
+        }
+    }
+
+    // Makes control changes visible while paused in advance mode
+    fn advance_paused_frame(&mut self) {
+        if self.config.play_mode == PlayMode::Advance && frame_clock::paused() {
+            frame_clock::advance_single_frame();
+            self.request_render_now();
+        }
+    }
+
+    // Swaps sketch instance/config, rebuilds runtime graph state, updates UI.
+    fn switch_sketch(&mut self, name: &str) -> Result<(), String> {
+        self.map_mode.stop();
+        self.send_midi_panic();
+
+        let preserved_bpm = self.bpm.base();
         let (config, sketch) = instantiate_sketch(&self.registry, name)?;
+        let preserve_beats =
+            config.preserve_beats && name == self.active_sketch_name;
+        let preserved_beats = self
+            .control_hub
+            .as_ref()
+            .filter(|_| preserve_beats)
+            .map(|hub| hub.beats());
 
         self.active_sketch_name = name.to_string();
         self.config = config;
-        self.sketch = sketch;
+        replace_sketch(&mut self.sketch, sketch);
         self.update_timing_mode_flags();
+        self.osc_transport_smoother.reset();
         let next_bpm = if self.tap_tempo_enabled {
             preserved_bpm
         } else {
@@ -2386,7 +3364,17 @@ impl XtalRuntime {
                 ));
             }
         }
+        self.dropped_image = None;
+        // Decodes still running for the previous sketch are stale now
+        self.image_drop_id += 1;
+        self.loop_region = None;
+        self.control_history.clear();
         self.rebuild_graph_state()?;
+        #[cfg(feature = "source-watch")]
+        self.restart_source_watch();
+        if let Some(beats) = preserved_beats {
+            self.restore_beats(beats);
+        }
 
         info!(
             "switched sketch to '{}' ({})",
@@ -2404,6 +3392,18 @@ impl XtalRuntime {
         Ok(())
     }
 
+    // Puts a reloaded sketch back where its transport was. External timing
+    // modes follow their own clock so the hub leaves those alone.
+    fn restore_beats(&mut self, beats: f32) {
+        let Some(hub) = self.control_hub.as_mut() else {
+            return;
+        };
+        match hub.restore_beats(beats) {
+            Ok(()) => info!("Restored transport to beat {}", beats),
+            Err(err) => debug!("Not restoring transport: {}", err),
+        }
+    }
+
     fn request_render_now(&mut self) {
         self.render_requested = true;
         if let Some(window) = self.window.as_ref() {
@@ -2419,6 +3419,230 @@ impl XtalRuntime {
         frame_clock::set_paused(paused);
     }
 
+    // Starts recording right away, bypassing any pre-roll.
+    fn start_recording(&mut self) {
+        let Some(context) = self.context.as_ref() else {
+            self.alert_and_log(
+                "Failed to start recording: runtime context unavailable",
+                log::Level::Error,
+            );
+            return;
+        };
+        let Some(graph) = self.graph.as_ref() else {
+            self.alert_and_log(
+                "Failed to start recording: render graph unavailable",
+                log::Level::Error,
+            );
+            return;
+        };
+        let source_format = graph.recording_source_format().or_else(|| {
+            self.surface_config.as_ref().map(|config| config.format)
+        });
+        let Some(source_format) = source_format else {
+            self.alert_and_log(
+                "Failed to start recording: no capture source format available",
+                log::Level::Error,
+            );
+            return;
+        };
+
+        if let Err(err) = fs::create_dir_all(&self.videos_dir) {
+            self.alert_and_log(
+                format!(
+                    "Failed to create videos directory '{}': {}",
+                    self.videos_dir, err
+                ),
+                log::Level::Error,
+            );
+            return;
+        }
+
+        // Scaled graphs present from a texture that may not match
+        // the window, so record at the source texture's size.
+        let [width, height] = graph
+            .recording_source_texture()
+            .map(|texture| [texture.width(), texture.height()])
+            .unwrap_or_else(|| context.resolution_u32());
+        let (options, codec_warning) =
+            self.recording_options.clone().supported();
+        let output_path = recording::video_output_path(
+            &self.videos_dir,
+            &self.session_id,
+            self.config.name,
+            options.codec.extension(),
+        )
+        .to_string_lossy()
+        .into_owned();
+
+        let target = RecordingTarget {
+            device: context.device.clone(),
+            output_path: &output_path,
+            width,
+            height,
+            fps: frame_clock::render_fps(),
+            source_format,
+            options: &options,
+        };
+        let result = if self.offline_recording_seconds > 0.0 {
+            self.recording_state
+                .start_offline_recording(target, self.offline_recording_seconds)
+        } else {
+            self.recording_state.start_recording(target)
+        };
+        match result {
+            Ok(message) => {
+                self.recording_state.is_queued = false;
+                let message = self.start_recording_audio(message);
+                let message = match codec_warning {
+                    Some(warning) => {
+                        format!("{}. {}", message, warning)
+                    }
+                    None => message,
+                };
+                self.alert(message);
+                self.emit_web_view_event(web_view::Event::StartRecording);
+            }
+            Err(err) => {
+                self.alert_and_log(
+                    format!("Failed to start recording: {}", err),
+                    log::Level::Error,
+                );
+            }
+        }
+    }
+
+    // Begins the configured pre-roll countdown, returning false when there is
+    // none and recording should start immediately. Pausing cancels a
+    // countdown, so none starts while paused.
+    fn start_recording_countdown(&mut self) -> bool {
+        if frame_clock::paused() {
+            return false;
+        }
+        let Some(count) = self.recording_state.start_countdown(
+            self.recording_pre_roll,
+            self.bpm.get(),
+            Instant::now(),
+        ) else {
+            return false;
+        };
+        self.alert(format!("Recording in {}...", count));
+        self.emit_web_view_event(web_view::Event::RecordingCountdown(Some(
+            count,
+        )));
+        true
+    }
+
+    // A pending pre-roll has nothing to count toward once the recording is
+    // stopped or the transport pauses.
+    fn cancel_recording_countdown(&mut self) {
+        if self.recording_state.cancel_countdown() {
+            self.alert("Recording canceled");
+            self.emit_web_view_event(web_view::Event::RecordingCountdown(None));
+            self.emit_web_view_event(web_view::Event::StopRecording);
+        }
+    }
+
+    // Starts the recording once its countdown runs out.
+    fn tick_recording_countdown(&mut self) {
+        match self.recording_state.tick_countdown(Instant::now()) {
+            Some(CountdownStep::Remaining(count)) => {
+                self.emit_web_view_event(web_view::Event::RecordingCountdown(
+                    Some(count),
+                ));
+            }
+            Some(CountdownStep::Finished) => {
+                self.emit_web_view_event(web_view::Event::RecordingCountdown(
+                    None,
+                ));
+                self.start_recording();
+            }
+            None => {}
+        }
+    }
+
+    // Starts or stops saving a numbered PNG sequence to the images dir.
+    fn toggle_timelapse(&mut self) {
+        if let Some(timelapse) = self.timelapse.take() {
+            self.alert(format!(
+                "Timelapse stopped. {} images saved to {}",
+                timelapse.count(),
+                timelapse.dir().display()
+            ));
+            self.emit_web_view_event(web_view::Event::Timelapse(false));
+            return;
+        }
+
+        if let Err(err) = fs::create_dir_all(&self.images_dir) {
+            self.alert_and_log(
+                format!(
+                    "Failed to create images directory '{}': {}",
+                    self.images_dir, err
+                ),
+                log::Level::Error,
+            );
+            return;
+        }
+
+        let options = &self.timelapse_options;
+        let every = match options.interval {
+            TimelapseInterval::Frames(frames) => format!("{} frames", frames),
+            TimelapseInterval::Beats(beats) => format!("{} beats", beats),
+        };
+        let timelapse = Timelapse::new(
+            options,
+            Path::new(&self.images_dir),
+            &self.active_sketch_name,
+            &uuid_5(),
+        );
+        self.timelapse = Some(timelapse);
+        self.alert(format!(
+            "Timelapse started. Saving every {} to {}",
+            every, self.images_dir
+        ));
+        self.emit_web_view_event(web_view::Event::Timelapse(true));
+    }
+
+    // Starts audio capture for a recording that just started when enabled.
+    // Any failure leaves a silent recording running and is folded into the
+    // start message instead.
+    fn start_recording_audio(&mut self, message: String) -> String {
+        if !self.record_audio {
+            return message;
+        }
+        if self.recording_state.is_offline() {
+            warn!("Audio is not recorded during offline recording");
+            return format!("{} (offline, no audio)", message);
+        }
+        if self.audio_device.is_empty() {
+            return format!("{} (no audio device selected)", message);
+        }
+        match self.recording_state.capture_audio(&self.audio_device) {
+            Ok(()) => format!("{} with audio", message),
+            Err(err) => {
+                warn!("Recording without audio: {}", err);
+                format!("{} (without audio: {})", message, err)
+            }
+        }
+    }
+
+    // Seeds (or unseeds) the hub's randomize and shows the seed so a happy
+    // accident can be recalled later.
+    fn set_randomize_seed(&mut self, seed: Option<u64>) {
+        self.randomize_seed = seed;
+        if let Some(hub) = self.control_hub.as_mut() {
+            match seed {
+                Some(seed) => hub.set_randomize_seed(seed),
+                None => hub.clear_randomize_seed(),
+            }
+        }
+        let message = match seed {
+            Some(seed) => format!("Randomize seed: {}", seed),
+            None => "Randomize seed cleared".to_string(),
+        };
+        self.alert_and_log(message, log::Level::Info);
+        self.emit_web_view_event(web_view::Event::RandomizeSeed(seed));
+    }
+
     // Toggles performance-mode window policy.
     fn set_perf_mode(&mut self, perf_mode: bool) {
         if self.perf_mode == perf_mode {
@@ -2427,8 +3651,13 @@ impl XtalRuntime {
 
         self.perf_mode = perf_mode;
         info!("performance mode set to {}", self.perf_mode);
+        self.perf_cursor.set_perf_mode(perf_mode);
+        self.apply_cursor_visibility();
 
         if let Some(window) = self.window.as_ref() {
+            // Without a title bar there's nothing to accidentally drag or
+            // click on a projected output
+            window.set_decorations(!self.perf_mode);
             if !self.perf_mode {
                 anchor_window_top_left(window.as_ref());
                 let _ = window.request_inner_size(LogicalSize::new(
@@ -2445,6 +3674,12 @@ impl XtalRuntime {
         }
     }
 
+    fn apply_cursor_visibility(&self) {
+        if let Some(window) = self.window.as_ref() {
+            window.set_cursor_visible(self.perf_cursor.visible());
+        }
+    }
+
     fn set_monitor_preview_enabled(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -2520,6 +3755,45 @@ impl XtalRuntime {
         Ok(())
     }
 
+    // Replaces any open output window with a borderless fullscreen one on
+    // the monitor at `monitor_index`.
+    fn open_output_window(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        monitor_index: usize,
+    ) -> Result<(), String> {
+        let Some(instance) = self.instance.as_ref() else {
+            return Err("wgpu instance is not initialized".to_string());
+        };
+        let Some(adapter) = self.adapter.as_ref() else {
+            return Err("wgpu adapter is not initialized".to_string());
+        };
+        let Some(context) = self.context.as_ref() else {
+            return Err("runtime context is not initialized".to_string());
+        };
+
+        let monitor =
+            select_monitor(event_loop.available_monitors(), monitor_index)?;
+        info!(
+            "Opening output window on monitor {} ({})",
+            monitor_index,
+            monitor.name().unwrap_or_default()
+        );
+
+        self.output_window = None;
+        let output = MonitorPreview::create_output(
+            event_loop,
+            instance,
+            adapter,
+            context.device.as_ref(),
+            monitor,
+        )?;
+        output.window().request_redraw();
+        self.output_window = Some(output);
+
+        Ok(())
+    }
+
     // Sends a UI alert message.
     fn alert(&self, message: impl Into<String>) {
         self.emit_web_view_event(web_view::Event::Alert(message.into()));
@@ -2554,16 +3828,30 @@ impl XtalRuntime {
 
     // Persists global runtime settings.
     fn save_global_state(&self) {
+        let devices = &self.device_recovery;
         let settings = GlobalSettings {
+            adaptive_quality: self.adaptive_quality.settings().clone(),
             version: super::serialization::GLOBAL_SETTINGS_VERSION.to_string(),
-            audio_device_name: self.audio_device.clone(),
+            audio_device_name: devices.audio.preferred().to_string(),
             hrcc: self.hrcc,
             images_dir: self.images_dir.clone(),
             mappings_enabled: self.mappings_enabled,
-            midi_clock_port: self.midi_clock_port.clone(),
-            midi_control_in_port: self.midi_input_port.clone(),
-            midi_control_out_port: self.midi_output_port.clone(),
+            midi_clock_port: devices.midi_clock.preferred().to_string(),
+            midi_control_in_port: devices.midi_input.preferred().to_string(),
+            midi_control_out_port: devices.midi_output.preferred().to_string(),
+            midi_feedback_enabled: self.midi_feedback_enabled,
+            ndi_sender_name: self.ndi_sender_name.clone(),
+            offline_recording_seconds: self.offline_recording_seconds,
             osc_port: self.osc_port,
+            osc_prefix: self.osc_prefix.clone(),
+            osc_transport_smoothing: self.osc_transport_smoother.smoothing,
+            perf_mode_hides_cursor: self.perf_cursor.hide_in_perf_mode(),
+            record_audio: self.record_audio,
+            recording_options: self.recording_options.clone(),
+            recording_pre_roll: self.recording_pre_roll,
+            remote_control_address: self.remote_control_address.clone(),
+            still_format: self.still_format,
+            timelapse_options: self.timelapse_options.clone(),
             transition_time: self.transition_time,
             user_data_dir: self.user_data_dir.clone(),
             videos_dir: self.videos_dir.clone(),
@@ -2586,10 +3874,45 @@ impl XtalRuntime {
 
     // Loads per-sketch controls/snapshots/mappings/exclusions into runtime + hub.
     fn restore_sketch_state_from_disk(&mut self) {
+        let user_data_dir = self.user_data_dir.clone();
+        let sketch_name = self.active_sketch_name.clone();
+        let result = self.restore_sketch_state(|state| {
+            storage::load_sketch_state(&user_data_dir, &sketch_name, state)
+                .map(|_| ())
+        });
+
+        match result {
+            Ok(true) => {
+                self.alert_and_log("Controls restored", log::Level::Info);
+            }
+            Ok(false) => {}
+            Err(err) => {
+                if err
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+                {
+                    return;
+                }
+                self.alert_and_log(
+                    format!("Failed to restore controls: {}", err),
+                    log::Level::Error,
+                );
+            }
+        }
+    }
+
+    // Merges state produced by `load` into the runtime + hub through the same
+    // path as a saved sketch state. Ok(false) means there is no hub.
+    fn restore_sketch_state(
+        &mut self,
+        load: impl FnOnce(
+            &mut TransitorySketchState,
+        ) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
         let current = self.current_sketch_ui_state();
         self.map_mode.set_mappings(current.mappings.clone());
         let Some(hub) = self.control_hub.as_mut() else {
-            return;
+            return Ok(false);
         };
 
         let mut state = TransitorySketchState::from_hub(
@@ -2598,14 +3921,10 @@ impl XtalRuntime {
             current.exclusions,
         );
 
-        let result = storage::load_sketch_state(
-            &self.user_data_dir,
-            &self.active_sketch_name,
-            &mut state,
-        );
+        let result = load(&mut state);
 
         match result {
-            Ok(state) => {
+            Ok(()) => {
                 let mappings = state.mappings.clone();
                 let exclusions = state.exclusions.clone();
                 // Preserve live UI control configs (including disabled fns),
@@ -2621,37 +3940,22 @@ impl XtalRuntime {
                 hub.midi_override_configs = state.midi_override_configs.clone();
                 hub.midi_controls
                     .set_override_state(hub.midi_overrides.clone());
-                hub.midi_controls
-                    .set_override_configs(hub.midi_override_configs.clone());
+                hub.restore_param_mappings(&mappings);
                 hub.osc_controls = state.osc_controls.clone();
                 hub.snapshots = state.snapshots.clone();
                 hub.midi_controls
                     .restart()
                     .inspect_err(|err| {
-                        error!(
-                            "Error in restore_sketch_state_from_disk: {}",
-                            err
-                        )
+                        error!("Error in restore_sketch_state: {}", err)
                     })
                     .ok();
                 self.current_sketch_ui_state_mut().mappings = mappings;
                 self.current_sketch_ui_state_mut().exclusions = exclusions;
                 self.map_mode
                     .set_mappings(self.current_sketch_ui_state().mappings);
-                self.alert_and_log("Controls restored", log::Level::Info);
-            }
-            Err(err) => {
-                if err
-                    .downcast_ref::<std::io::Error>()
-                    .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
-                {
-                    return;
-                }
-                self.alert_and_log(
-                    format!("Failed to restore controls: {}", err),
-                    log::Level::Error,
-                );
+                Ok(true)
             }
+            Err(err) => Err(err),
         }
     }
 
@@ -2662,10 +3966,89 @@ impl XtalRuntime {
         }
 
         self.shutdown_signaled = true;
+        self.sketch.on_exit();
+        self.send_midi_panic();
         self.emit_event(RuntimeEvent::WebView(Box::new(web_view::Event::Quit)));
         self.emit_event(RuntimeEvent::Stopped);
     }
 
+    // Serves the web view protocol over WebSocket when an address is set.
+    #[cfg(feature = "remote-control")]
+    fn start_remote_control(&mut self) {
+        if self.remote_control_address.is_empty() {
+            return;
+        }
+        let command_tx = self.command_tx.clone();
+        match RemoteControlServer::start(
+            &self.remote_control_address,
+            command_tx,
+        ) {
+            Ok(server) => self.remote_control = Some(server),
+            Err(err) => self.alert_and_log(err, log::Level::Error),
+        }
+    }
+
+    // Publishes the final render over NDI when a sender name is set. Called
+    // whenever the device is (re)created since readback buffers belong to it.
+    #[cfg(feature = "ndi")]
+    fn start_ndi_output(&mut self) {
+        self.ndi_output = None;
+        if self.ndi_sender_name.is_empty() {
+            return;
+        }
+        let Some(context) = self.context.as_ref() else {
+            return;
+        };
+        match NdiOutput::start(context.device.clone(), &self.ndi_sender_name) {
+            Ok(output) => self.ndi_output = Some(output),
+            Err(err) => self.alert_and_log(err, log::Level::Error),
+        }
+    }
+
+    // Watches the active sketch's Rust source, if it has a known one.
+    #[cfg(feature = "source-watch")]
+    fn restart_source_watch(&mut self) {
+        self.source_watch = self.sketch.source_file().and_then(|path| {
+            SourceWatch::for_current_exe(path)
+                .inspect_err(|err| warn!("source watch unavailable: {}", err))
+                .ok()
+        });
+    }
+
+    // Alerts on source edits and relaunches the sketch once rebuilt.
+    #[cfg(feature = "source-watch")]
+    fn poll_source_watch(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(watch) = self.source_watch.as_ref() else {
+            return;
+        };
+
+        match watch.take_change() {
+            Some(SourceChange::SourceEdited) => {
+                let file_name = watch
+                    .source_path()
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                self.alert_and_log(
+                    format!(
+                        "{} changed; run `just rebuild` to reload",
+                        file_name
+                    ),
+                    log::Level::Info,
+                );
+            }
+            Some(SourceChange::BinaryRebuilt) => {
+                info!("rebuilt binary detected; relaunching");
+                self.relaunch = Some((
+                    watch.executable().to_path_buf(),
+                    self.active_sketch_name.clone(),
+                ));
+                self.shutdown(event_loop);
+            }
+            None => {}
+        }
+    }
+
     // Requests graceful exit of the event loop.
     fn shutdown(&mut self, event_loop: &ActiveEventLoop) {
         self.signal_shutdown();
@@ -2729,6 +4112,31 @@ impl ApplicationHandler for XtalRuntime {
             return;
         }
 
+        if self
+            .output_window
+            .as_ref()
+            .is_some_and(|output| output.window_id() == window_id)
+        {
+            match event {
+                WindowEvent::CloseRequested => {
+                    self.output_window = None;
+                }
+                WindowEvent::Resized(new_size) => {
+                    let Some(context) = self.context.as_ref() else {
+                        return;
+                    };
+                    if let Some(output) = self.output_window.as_mut() {
+                        output.on_window_resized(
+                            context.device.as_ref(),
+                            new_size,
+                        );
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
         if self.window_id != Some(window_id) {
             return;
         }
@@ -2744,7 +4152,95 @@ impl ApplicationHandler for XtalRuntime {
                 self.modifiers = modifiers.state();
             }
             WindowEvent::KeyboardInput { event, .. } => {
-                self.handle_main_window_shortcut(event_loop, &event);
+                let PhysicalKey::Code(code) = event.physical_key else {
+                    return;
+                };
+                let pressed = event.state.is_pressed();
+                let modifiers = self.modifiers;
+                let route = match self.context.as_ref() {
+                    Some(context) => route_key(
+                        code,
+                        modifiers,
+                        self.config.captured_keys,
+                        || {
+                            self.sketch
+                                .on_key(code, pressed, modifiers, context)
+                        },
+                    ),
+                    None => KeyRoute::Runtime,
+                };
+                match route {
+                    KeyRoute::Consumed if pressed => return,
+                    KeyRoute::Runtime => {
+                        self.handle_main_window_shortcut(event_loop, &event);
+                    }
+                    KeyRoute::Consumed | KeyRoute::Captured => {}
+                }
+                if let Some(context) = self.context.as_mut() {
+                    context.set_key_pressed(code, pressed);
+                }
+            }
+            WindowEvent::DroppedFile(path) => self.decode_dropped_image(path),
+            WindowEvent::Focused(false) => {
+                if let Some(context) = self.context.as_mut() {
+                    context.clear_keys();
+                }
+                self.perf_cursor.set_focused(false);
+                self.apply_cursor_visibility();
+            }
+            WindowEvent::Focused(true) => {
+                self.perf_cursor.set_focused(true);
+                self.apply_cursor_visibility();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if let Some(context) = self.context.as_mut() {
+                    let before = context.mouse_position();
+                    context.set_mouse_position_px([
+                        position.x as f32,
+                        position.y as f32,
+                    ]);
+                    let dragging = context.mouse_buttons() & MOUSE_LEFT != 0;
+                    if self.config.camera_controls && dragging {
+                        let after = context.mouse_position();
+                        let delta = [
+                            (after[0] - before[0]) * 2.0,
+                            (after[1] - before[1]) * 2.0,
+                        ];
+                        let aspect = context.aspect();
+                        context.update_camera(|camera| {
+                            camera.drag(delta, aspect);
+                        });
+                    }
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                if !self.config.camera_controls {
+                    return;
+                }
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        position.y as f32 / PIXELS_PER_SCROLL_LINE
+                    }
+                };
+                if let Some(context) = self.context.as_ref() {
+                    let anchor = camera::uv_to_clip(context.mouse_position());
+                    let aspect = context.aspect();
+                    context.update_camera(|camera| {
+                        camera.zoom_at(anchor, ZOOM_STEP.powf(lines), aspect);
+                    });
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let bit = match button {
+                    MouseButton::Left => MOUSE_LEFT,
+                    MouseButton::Right => MOUSE_RIGHT,
+                    MouseButton::Middle => MOUSE_MIDDLE,
+                    _ => return,
+                };
+                if let Some(context) = self.context.as_mut() {
+                    context.set_mouse_button(bit, state.is_pressed());
+                }
             }
             WindowEvent::Resized(new_size) => self.resize(new_size),
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
@@ -2764,6 +4260,10 @@ impl ApplicationHandler for XtalRuntime {
         self.process_commands(event_loop);
         let now = Instant::now();
         self.emit_average_fps_if_due(now);
+        self.recover_devices_if_due(now);
+        self.report_script_error();
+        #[cfg(feature = "source-watch")]
+        self.poll_source_watch(event_loop);
 
         if self.render_requested {
             event_loop.set_control_flow(ControlFlow::WaitUntil(
@@ -2834,7 +4334,21 @@ fn run_registry_with_channels(
 
     event_loop
         .run_app(&mut runner)
-        .map_err(|err| err.to_string())
+        .map_err(|err| err.to_string())?;
+
+    // Relaunch only after the runtime has released its ports and devices.
+    #[cfg(feature = "source-watch")]
+    if let Some((executable, sketch_name)) = runner.relaunch.take() {
+        drop(runner);
+        std::process::Command::new(&executable)
+            .arg(&sketch_name)
+            .spawn()
+            .map_err(|err| {
+                format!("failed to relaunch {}: {}", executable.display(), err)
+            })?;
+    }
+
+    Ok(())
 }
 
 fn select_initial_sketch_name(
@@ -2842,14 +4356,24 @@ fn select_initial_sketch_name(
     initial_sketch: Option<&str>,
 ) -> Result<String, String> {
     if let Some(initial_sketch) = initial_sketch {
-        if registry.get(initial_sketch).is_some() {
-            return Ok(initial_sketch.to_string());
+        match registry.find_sketch(initial_sketch) {
+            SketchLookup::Found(name) => return Ok(name),
+            SketchLookup::Ambiguous(candidates) => {
+                return Err(format!(
+                    "requested initial sketch '{}' is ambiguous; \
+                    candidates:\n  {}",
+                    initial_sketch,
+                    candidates.join("\n  ")
+                ));
+            }
+            SketchLookup::NotFound => {
+                warn!(
+                    "requested initial sketch '{}' does not exist or is \
+                    filtered out; falling back",
+                    initial_sketch
+                );
+            }
         }
-
-        warn!(
-            "requested initial sketch '{}' does not exist; falling back",
-            initial_sketch
-        );
     }
 
     registry
@@ -2872,6 +4396,16 @@ fn instantiate_sketch(
     Ok((config, sketch))
 }
 
+// Timestamp queries are optional: adapters without them still run, just
+// without pass timings
+fn required_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    if cfg!(feature = "timing") {
+        adapter.features() & wgpu::Features::TIMESTAMP_QUERY
+    } else {
+        wgpu::Features::empty()
+    }
+}
+
 fn choose_surface_format(
     formats: &[wgpu::TextureFormat],
 ) -> Option<wgpu::TextureFormat> {
@@ -2899,72 +4433,10 @@ fn save_png_capture(
     submission_index: wgpu::SubmissionIndex,
     capture: PendingPngCapture,
 ) -> Result<(), String> {
-    let PendingPngCapture {
-        path,
-        buffer,
-        width,
-        height,
-        padded_bytes_per_row,
-        source_format,
-    } = capture;
-
-    let slice = buffer.slice(..);
-    let (tx, rx) = std::sync::mpsc::sync_channel(1);
-    slice.map_async(wgpu::MapMode::Read, move |result| {
-        let _ = tx.send(result);
-    });
-    let _ =
-        device.poll(wgpu::PollType::WaitForSubmissionIndex(submission_index));
-    let map_result = rx
-        .recv()
-        .map_err(|err| format!("map channel recv failed: {}", err))?;
-    map_result.map_err(|err| format!("map failed: {:?}", err))?;
-
-    let data = slice.get_mapped_range();
-    // Recording/capture source formats are 8-bit RGBA/BGRA, so 4 bytes/pixel.
-    let unpadded_bytes_per_row = (width * 4) as usize;
-    let padded_bytes_per_row = padded_bytes_per_row as usize;
-    let mut rgba = vec![0u8; unpadded_bytes_per_row * (height as usize)];
-
-    for row in 0..(height as usize) {
-        let src_start = row * padded_bytes_per_row;
-        let src_end = src_start + unpadded_bytes_per_row;
-        let dst_start = row * unpadded_bytes_per_row;
-        let dst_end = dst_start + unpadded_bytes_per_row;
-        rgba[dst_start..dst_end].copy_from_slice(&data[src_start..src_end]);
-    }
-
-    drop(data);
-    buffer.unmap();
-
-    if matches!(
-        source_format,
-        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
-    ) {
-        for px in rgba.chunks_exact_mut(4) {
-            px.swap(0, 2);
-        }
-    }
-
-    let file = fs::File::create(&path).map_err(|err| {
-        format!("failed to create '{}': {}", path.display(), err)
-    })?;
-    let mut writer = std::io::BufWriter::new(file);
-    let mut encoder = png::Encoder::new(&mut writer, width, height);
-    encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
-    encoder.set_compression(png::Compression::Fast);
-    encoder.set_filter(png::Filter::Sub);
-    let mut png_writer = encoder
-        .write_header()
-        .map_err(|err| format!("png header failed: {}", err))?;
-    png_writer
-        .write_image_data(&rgba)
-        .map_err(|err| format!("png write failed: {}", err))?;
-    drop(png_writer);
-    writer
-        .flush()
-        .map_err(|err| format!("png flush failed: {}", err))?;
+    let still = capture.readback.read(device, submission_index)?;
+    for (path, still_format) in capture.outputs {
+        still.write(&path, still_format)?;
+    }
 
     Ok(())
 }
@@ -2976,8 +4448,10 @@ fn queue_png_capture_save(
     event_tx: Option<RuntimeEventSender>,
 ) {
     std::thread::spawn(move || {
-        let path = capture.path.clone();
+        let path = capture.outputs[0].0.clone();
+        let announce = capture.announce;
         match save_png_capture(device.as_ref(), submission_index, capture) {
+            Ok(()) if !announce => {}
             Ok(()) => {
                 let message = format!("Image saved to {:?}", path);
                 info!("{}", message);
@@ -3000,6 +4474,10 @@ fn queue_png_capture_save(
     });
 }
 
+fn port_names(ports: &[(usize, String)]) -> Vec<String> {
+    ports.iter().map(|(_, name)| name.clone()).collect()
+}
+
 fn default_user_data_dir_for_sketch(sketch: &dyn Sketch) -> Option<String> {
     let control_script = sketch.control_script()?;
     let crate_root = find_crate_root(control_script.as_path())?;
@@ -3012,6 +4490,72 @@ fn find_crate_root(path: &Path) -> Option<PathBuf> {
         .map(Path::to_path_buf)
 }
 
+/// Tears down `current` through [`Sketch::on_exit`] before `next` takes its
+/// place.
+pub fn replace_sketch(current: &mut Box<dyn Sketch>, next: Box<dyn Sketch>) {
+    current.on_exit();
+    *current = next;
+}
+
+// Updates the context's window size and notifies the sketch through
+// `Sketch::on_resize`. Does nothing when the size hasn't changed, since
+// platforms repeat `Resized` events for the same size.
+fn resize_sketch_context(
+    sketch: &mut dyn Sketch,
+    context: &mut Context,
+    new_size: [u32; 2],
+) {
+    if context.resolution_u32() == new_size {
+        return;
+    }
+    context.set_window_size(new_size);
+    sketch.on_resize(new_size, context);
+}
+
+/// Who handles a key event on the main window
+#[derive(Debug, PartialEq)]
+enum KeyRoute {
+    /// [`Sketch::on_key`] consumed it: no shortcut runs and presses aren't
+    /// recorded in [`Context::keys_down`]
+    Consumed,
+    /// Listed in [`SketchConfig::captured_keys`]: no shortcut runs, but the
+    /// key is still tracked in [`Context::keys_down`]
+    Captured,
+    /// The runtime's shortcuts handle it
+    Runtime,
+}
+
+/// Gives the sketch first refusal of a key event through `on_key`, then skips
+/// the shortcuts for unmodified presses of `captured_keys`. Reserved
+/// shortcuts (`Mod` + `Q` to quit and `Mod` + `F` for fullscreen) bypass both
+/// so they always reach the runtime.
+fn route_key(
+    code: KeyCode,
+    modifiers: ModifiersState,
+    captured_keys: &[KeyCode],
+    on_key: impl FnOnce() -> bool,
+) -> KeyRoute {
+    if is_reserved_shortcut(code, modifiers) {
+        KeyRoute::Runtime
+    } else if on_key() {
+        KeyRoute::Consumed
+    } else if modifiers.is_empty() && captured_keys.contains(&code) {
+        KeyRoute::Captured
+    } else {
+        KeyRoute::Runtime
+    }
+}
+
+// Whether `code` with `modifiers` is a runtime shortcut sketches can't consume
+fn is_reserved_shortcut(code: KeyCode, modifiers: ModifiersState) -> bool {
+    let platform_mod_pressed = if cfg!(target_os = "macos") {
+        modifiers.super_key()
+    } else {
+        modifiers.control_key()
+    };
+    platform_mod_pressed && matches!(code, KeyCode::KeyQ | KeyCode::KeyF)
+}
+
 fn digit_from_key_code(code: KeyCode) -> Option<char> {
     match code {
         KeyCode::Digit0 => Some('0'),
@@ -3027,3 +4571,280 @@ fn digit_from_key_code(code: KeyCode) -> Option<char> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::events::RuntimeEventReceiver;
+
+    // Like the integration tests' support module, GPU tests are opt-in
+    fn headless_context() -> Option<Context> {
+        let enabled = std::env::var("XTAL_RUN_GPU_TESTS").is_ok_and(|value| {
+            matches!(
+                value.to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        if !enabled {
+            eprintln!("Skipping GPU test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+            return None;
+        }
+
+        let instance =
+            wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::LowPower,
+                force_fallback_adapter: true,
+                compatible_surface: None,
+            },
+        ))
+        .expect("expected a headless adapter for GPU tests");
+        let (device, queue) = pollster::block_on(
+            adapter.request_device(&wgpu::DeviceDescriptor::default()),
+        )
+        .expect("expected a device from the headless adapter");
+
+        Some(Context::new(Arc::new(device), Arc::new(queue), [4, 4], 1.0))
+    }
+
+    fn platform_mod() -> ModifiersState {
+        if cfg!(target_os = "macos") {
+            ModifiersState::SUPER
+        } else {
+            ModifiersState::CONTROL
+        }
+    }
+
+    #[test]
+    fn quit_and_fullscreen_shortcuts_are_reserved() {
+        assert!(is_reserved_shortcut(KeyCode::KeyQ, platform_mod()));
+        assert!(is_reserved_shortcut(KeyCode::KeyF, platform_mod()));
+        assert!(is_reserved_shortcut(
+            KeyCode::KeyF,
+            platform_mod() | ModifiersState::SHIFT
+        ));
+
+        assert!(!is_reserved_shortcut(
+            KeyCode::KeyQ,
+            ModifiersState::empty()
+        ));
+        assert!(!is_reserved_shortcut(
+            KeyCode::KeyF,
+            ModifiersState::empty()
+        ));
+        assert!(!is_reserved_shortcut(KeyCode::KeyT, platform_mod()));
+    }
+
+    #[test]
+    fn sketch_consuming_a_key_skips_the_shortcut() {
+        let route = |code, modifiers| {
+            route_key(code, modifiers, &[], || code == KeyCode::KeyT)
+        };
+
+        assert_eq!(
+            route(KeyCode::KeyT, ModifiersState::SHIFT),
+            KeyRoute::Consumed
+        );
+        assert_eq!(
+            route(KeyCode::KeyP, ModifiersState::empty()),
+            KeyRoute::Runtime
+        );
+    }
+
+    #[test]
+    fn reserved_shortcuts_never_reach_the_sketch() {
+        for code in [KeyCode::KeyQ, KeyCode::KeyF] {
+            let route = route_key(code, platform_mod(), &[code], || {
+                panic!("offered {:?} to the sketch", code)
+            });
+            assert_eq!(route, KeyRoute::Runtime);
+        }
+    }
+
+    #[test]
+    fn captured_keys_skip_shortcuts_only_when_unmodified() {
+        let captured = [KeyCode::KeyP];
+        let route = |modifiers, consumed| {
+            route_key(KeyCode::KeyP, modifiers, &captured, || consumed)
+        };
+
+        assert_eq!(route(ModifiersState::empty(), false), KeyRoute::Captured);
+        assert_eq!(route(ModifiersState::SHIFT, false), KeyRoute::Runtime);
+        // The sketch's hook still gets the first look
+        assert_eq!(route(ModifiersState::empty(), true), KeyRoute::Consumed);
+    }
+
+    #[derive(Default)]
+    struct ResizeSketch {
+        resizes: Vec<([u32; 2], [u32; 2])>,
+    }
+
+    impl Sketch for ResizeSketch {
+        fn setup(&self, _graph: &mut GraphBuilder) {}
+
+        fn on_resize(&mut self, new_size: [u32; 2], ctx: &Context) {
+            self.resizes.push((new_size, ctx.resolution_u32()));
+        }
+    }
+
+    #[test]
+    fn resize_notifies_sketch_once_with_new_size() {
+        let Some(mut context) = headless_context() else {
+            return;
+        };
+        let mut sketch = ResizeSketch::default();
+
+        resize_sketch_context(&mut sketch, &mut context, [4, 4]);
+        assert!(sketch.resizes.is_empty());
+
+        resize_sketch_context(&mut sketch, &mut context, [8, 6]);
+        resize_sketch_context(&mut sketch, &mut context, [8, 6]);
+
+        assert_eq!(sketch.resizes, vec![([8, 6], [8, 6])]);
+        assert_eq!(context.resolution_u32(), [8, 6]);
+    }
+
+    struct DescribedSketch;
+
+    impl Sketch for DescribedSketch {
+        fn setup(&self, _graph: &mut GraphBuilder) {}
+    }
+
+    static DESCRIBED_CONFIG: SketchConfig = SketchConfig {
+        name: "described",
+        display_name: "Described",
+        play_mode: PlayMode::Loop,
+        fps: 60.0,
+        bpm: 120.0,
+        w: 64,
+        h: 64,
+        banks: 4,
+        description: Some("Press **T** to toggle the trails."),
+        ..SketchConfig::DEFAULT
+    };
+
+    // A runtime without devices, windows or an event loop
+    fn described_runtime() -> (XtalRuntime, RuntimeEventReceiver) {
+        let mut registry = RuntimeRegistry::new();
+        registry
+            .register(&DESCRIBED_CONFIG, || Box::new(DescribedSketch))
+            .unwrap();
+        registry
+            .define_category("Main", true, vec!["described".to_string()])
+            .unwrap();
+        let (command_tx, command_rx) = command_channel();
+        let (event_tx, event_rx) = event_channel();
+        let runtime = XtalRuntime::from_registry(
+            registry,
+            None,
+            command_tx,
+            command_rx,
+            Some(event_tx),
+        )
+        .unwrap();
+        (runtime, event_rx)
+    }
+
+    #[test]
+    fn load_sketch_and_picker_carry_the_config_description() {
+        let (mut runtime, event_rx) = described_runtime();
+
+        runtime.emit_web_view_init();
+        runtime.emit_web_view_load_sketch();
+
+        let events: Vec<web_view::Event> = event_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                RuntimeEvent::WebView(event) => Some(*event),
+                _ => None,
+            })
+            .collect();
+        let [
+            web_view::Event::Init(init),
+            web_view::Event::LoadSketch(sketch),
+        ] = events.as_slice()
+        else {
+            panic!("expected Init then LoadSketch, got {:?}", events);
+        };
+
+        let catalog = init.sketch_catalog.as_ref().unwrap();
+        assert_eq!(
+            catalog[0].descriptions.get("described").map(String::as_str),
+            DESCRIBED_CONFIG.description
+        );
+        assert_eq!(sketch.description.as_deref(), DESCRIBED_CONFIG.description);
+    }
+
+    #[test]
+    fn set_bpm_clamps_and_ignores_non_finite_tempos() {
+        let (mut runtime, _event_rx) = described_runtime();
+
+        runtime.apply_runtime_event(RuntimeEvent::SetBpm(96.0));
+        assert_eq!(runtime.bpm.get(), 96.0);
+
+        runtime.apply_runtime_event(RuntimeEvent::SetBpm(5000.0));
+        assert_eq!(runtime.bpm.get(), MAX_BPM);
+        runtime.apply_runtime_event(RuntimeEvent::SetBpm(1.0));
+        assert_eq!(runtime.bpm.get(), MIN_BPM);
+
+        for bpm in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            runtime.apply_runtime_event(RuntimeEvent::SetBpm(bpm));
+            assert_eq!(runtime.bpm.get(), MIN_BPM, "{}", bpm);
+        }
+    }
+
+    #[test]
+    fn sketch_switch_discards_pending_image_decodes() {
+        let (mut runtime, _event_rx) = described_runtime();
+        let decoded = |id| {
+            let image = DecodedImage {
+                width: 1,
+                height: 1,
+                rgba: vec![255; 4],
+            };
+            RuntimeEvent::DroppedImageDecoded(
+                id,
+                PathBuf::from("drop.png"),
+                Ok(Arc::new(image)),
+            )
+        };
+
+        // A drop whose decode finishes after the switch
+        runtime.image_drop_id += 1;
+        let stale_id = runtime.image_drop_id;
+        runtime.apply_runtime_event(RuntimeEvent::SwitchSketch(
+            "described".to_string(),
+        ));
+        runtime.apply_runtime_event(decoded(stale_id));
+        assert!(runtime.dropped_image.is_none());
+
+        runtime.apply_runtime_event(decoded(runtime.image_drop_id));
+        assert!(runtime.dropped_image.is_some());
+    }
+
+    #[test]
+    fn sketch_switch_recording_stop_and_quit_silence_midi_out() {
+        // Swaps in a fresh recording port, returning what the last one sent
+        fn take_sent(runtime: &mut XtalRuntime) -> Option<Vec<[u8; 3]>> {
+            let midi_out = midi::MidiOut::recording("test");
+            let sent = runtime.midi_out.replace(midi_out)?.sent().to_vec();
+            Some(sent)
+        }
+
+        let (mut runtime, _event_rx) = described_runtime();
+        take_sent(&mut runtime);
+
+        runtime.apply_runtime_event(RuntimeEvent::SwitchSketch(
+            "described".to_string(),
+        ));
+        assert_eq!(take_sent(&mut runtime), Some(midi::panic_messages()));
+
+        runtime.apply_runtime_event(RuntimeEvent::StopRecording);
+        assert_eq!(take_sent(&mut runtime), Some(midi::panic_messages()));
+
+        // `Quit` shuts down through here once it has exited the event loop
+        runtime.signal_shutdown();
+        assert_eq!(take_sent(&mut runtime), Some(midi::panic_messages()));
+    }
+}
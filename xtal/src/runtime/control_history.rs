@@ -0,0 +1,255 @@
+//! Bounded undo/redo history of UI control changes. Only values committed
+//! from the UI are recorded; animations, MIDI, OSC, snapshot transitions and
+//! randomization never pass through here. A burst of changes to the same
+//! control, like the stream of values a slider drag sends, collapses into a
+//! single step so one undo returns to where the drag started.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::control::ControlValue;
+
+const DEFAULT_CAPACITY: usize = 100;
+
+/// Changes to one control closer together than this become one undo step
+const COALESCE_WINDOW: Duration = Duration::from_millis(750);
+
+#[derive(Debug)]
+struct Change {
+    name: String,
+    before: ControlValue,
+    after: ControlValue,
+    at: Instant,
+}
+
+#[derive(Debug)]
+pub struct ControlHistory {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    capacity: usize,
+    // Set by undo and redo so the next change starts its own step
+    sealed: bool,
+}
+
+impl Default for ControlHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl ControlHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: vec![],
+            capacity,
+            sealed: false,
+        }
+    }
+
+    /// Record `name` changing from `before` to `after` at `now`. Any redo
+    /// steps are discarded, and once full the oldest step is dropped.
+    pub fn record(
+        &mut self,
+        name: &str,
+        before: ControlValue,
+        after: ControlValue,
+        now: Instant,
+    ) {
+        if before == after || self.capacity == 0 {
+            return;
+        }
+
+        self.redo.clear();
+        let sealed = std::mem::take(&mut self.sealed);
+
+        if !sealed
+            && let Some(last) = self.undo.back_mut()
+            && last.name == name
+            && now.saturating_duration_since(last.at) < COALESCE_WINDOW
+        {
+            last.after = after;
+            last.at = now;
+            if last.before == last.after {
+                self.undo.pop_back();
+            }
+            return;
+        }
+
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+
+        self.undo.push_back(Change {
+            name: name.to_string(),
+            before,
+            after,
+            at: now,
+        });
+    }
+
+    /// The control and value that undo the most recent step, if any
+    pub fn undo(&mut self) -> Option<(String, ControlValue)> {
+        let change = self.undo.pop_back()?;
+        let restored = (change.name.clone(), change.before.clone());
+        self.redo.push(change);
+        self.sealed = true;
+        Some(restored)
+    }
+
+    /// The control and value that reapply the most recently undone step
+    pub fn redo(&mut self) -> Option<(String, ControlValue)> {
+        let change = self.redo.pop()?;
+        let restored = (change.name.clone(), change.after.clone());
+        self.undo.push_back(change);
+        self.sealed = true;
+        Some(restored)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::*;
+
+    fn set(
+        history: &mut ControlHistory,
+        controls: &mut UiControls,
+        name: &str,
+        value: ControlValue,
+        now: Instant,
+    ) {
+        let before = controls.value(name).unwrap().clone();
+        history.record(name, before, value.clone(), now);
+        controls.set(name, value);
+    }
+
+    fn apply(controls: &mut UiControls, step: Option<(String, ControlValue)>) {
+        let (name, value) = step.unwrap();
+        controls.set(&name, value);
+    }
+
+    #[test]
+    fn undo_restores_and_redo_reapplies() {
+        let controls = &mut UiControls::new(&[
+            UiControlConfig::slider_n("size", 0.5),
+            UiControlConfig::checkbox("invert", false),
+        ]);
+        let mut history = ControlHistory::default();
+        let start = Instant::now();
+
+        set(&mut history, controls, "size", 0.8.into(), start);
+        let later = start + COALESCE_WINDOW * 2;
+        set(&mut history, controls, "invert", true.into(), later);
+
+        apply(controls, history.undo());
+        assert!(!controls.bool("invert"));
+        assert_eq!(controls.float("size"), 0.8);
+
+        apply(controls, history.undo());
+        assert_eq!(controls.float("size"), 0.5);
+        assert!(history.undo().is_none());
+
+        apply(controls, history.redo());
+        assert_eq!(controls.float("size"), 0.8);
+        apply(controls, history.redo());
+        assert!(controls.bool("invert"));
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn coalesces_rapid_changes_to_one_control() {
+        let mut history = ControlHistory::default();
+        let start = Instant::now();
+        let step = Duration::from_millis(16);
+
+        for i in 1..=50 {
+            history.record(
+                "size",
+                ControlValue::from(i as f32 - 1.0),
+                ControlValue::from(i as f32),
+                start + step * i,
+            );
+        }
+
+        assert_eq!(history.undo(), Some(("size".into(), 0.0.into())));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(), Some(("size".into(), 50.0.into())));
+    }
+
+    #[test]
+    fn separates_pauses_and_other_controls() {
+        let mut history = ControlHistory::default();
+        let start = Instant::now();
+
+        history.record("a", 0.0.into(), 1.0.into(), start);
+        history.record("b", 0.0.into(), 1.0.into(), start);
+        history.record("a", 1.0.into(), 2.0.into(), start);
+        history.record("a", 2.0.into(), 3.0.into(), start + COALESCE_WINDOW);
+
+        assert_eq!(history.undo(), Some(("a".into(), 2.0.into())));
+        assert_eq!(history.undo(), Some(("a".into(), 1.0.into())));
+        assert_eq!(history.undo(), Some(("b".into(), 0.0.into())));
+        assert_eq!(history.undo(), Some(("a".into(), 0.0.into())));
+    }
+
+    #[test]
+    fn new_change_discards_redo() {
+        let mut history = ControlHistory::default();
+        let start = Instant::now();
+
+        history.record("a", 0.0.into(), 1.0.into(), start);
+        history.undo();
+        assert!(history.can_redo());
+
+        history.record("a", 0.0.into(), 2.0.into(), start);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn change_after_undo_starts_a_new_step() {
+        let mut history = ControlHistory::default();
+        let start = Instant::now();
+
+        history.record("a", 0.0.into(), 1.0.into(), start);
+        history.record("b", 0.0.into(), 1.0.into(), start);
+        history.undo();
+        history.record("a", 1.0.into(), 2.0.into(), start);
+
+        assert_eq!(history.undo(), Some(("a".into(), 1.0.into())));
+        assert_eq!(history.undo(), Some(("a".into(), 0.0.into())));
+    }
+
+    #[test]
+    fn is_bounded() {
+        let mut history = ControlHistory::new(3);
+        let start = Instant::now();
+
+        for i in 0..5 {
+            history.record(
+                &format!("c{i}"),
+                0.0.into(),
+                1.0.into(),
+                start + COALESCE_WINDOW * i,
+            );
+        }
+
+        let undone: Vec<String> =
+            std::iter::from_fn(|| history.undo().map(|(name, _)| name))
+                .collect();
+        assert_eq!(undone, ["c4", "c3", "c2"]);
+    }
+}
@@ -0,0 +1,290 @@
+//! Recovery for audio and MIDI devices that disappear mid-session, e.g. when
+//! a USB interface glitches. The runtime periodically re-lists devices on a
+//! background thread; when the active one is gone it falls back to the next
+//! available device and keeps watching for the user's original choice so it
+//! can switch back.
+
+use std::time::{Duration, Instant};
+
+use crate::io::audio::list_audio_devices;
+use crate::io::midi::{self, PortIndexAndName};
+
+// Listing devices isn't free (especially audio), so don't do it every frame
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DeviceChange {
+    /// The active device disappeared; switch to `fallback`
+    FellBack { lost: String, fallback: String },
+    /// The active device disappeared and nothing else is available
+    Lost(String),
+    /// The user's original device is available again
+    Restored(String),
+}
+
+/// Tracks one device selection: the one the user chose (`preferred`, which
+/// is what gets persisted) and the device list as last seen
+#[derive(Clone, Debug, Default)]
+pub struct DeviceWatch {
+    preferred: String,
+    known: Vec<String>,
+}
+
+impl DeviceWatch {
+    pub fn new(preferred: impl Into<String>, known: Vec<String>) -> Self {
+        Self {
+            preferred: preferred.into(),
+            known,
+        }
+    }
+
+    pub fn preferred(&self) -> &str {
+        &self.preferred
+    }
+
+    /// Record an explicit user selection
+    pub fn select(&mut self, name: impl Into<String>) {
+        self.preferred = name.into();
+    }
+
+    /// Compare the `active` device against a fresh device list. An empty
+    /// `active` means the device was lost earlier with nothing to replace it.
+    pub fn update(
+        &mut self,
+        active: &str,
+        available: Vec<String>,
+    ) -> Option<DeviceChange> {
+        let change = self.resolve(active, &available);
+        self.known = available;
+        change
+    }
+
+    fn resolve(
+        &self,
+        active: &str,
+        available: &[String],
+    ) -> Option<DeviceChange> {
+        if self.preferred.is_empty() {
+            return None;
+        }
+        if available.contains(&self.preferred) {
+            return (active != self.preferred)
+                .then(|| DeviceChange::Restored(self.preferred.clone()));
+        }
+        if !active.is_empty() && available.iter().any(|d| d == active) {
+            return None;
+        }
+
+        let lost = if active.is_empty() {
+            &self.preferred
+        } else {
+            active
+        };
+        match fallback_device(&self.known, available, lost) {
+            Some(fallback) => Some(DeviceChange::FellBack {
+                lost: lost.to_string(),
+                fallback,
+            }),
+            None if !active.is_empty() => {
+                Some(DeviceChange::Lost(active.to_string()))
+            }
+            None => None,
+        }
+    }
+}
+
+/// Choose a replacement for `lost`: the first device that followed it in the
+/// `previous` list (wrapping around) and is still available, otherwise the
+/// first available device.
+pub fn fallback_device(
+    previous: &[String],
+    available: &[String],
+    lost: &str,
+) -> Option<String> {
+    let is_available = |name: &String| name != lost && available.contains(name);
+
+    if let Some(index) = previous.iter().position(|d| d == lost) {
+        let mut after = previous[index + 1..].iter().chain(&previous[..index]);
+        if let Some(next) = after.find(|d| is_available(d)) {
+            return Some(next.clone());
+        }
+    }
+
+    available.iter().find(|d| is_available(d)).cloned()
+}
+
+/// Device lists gathered for one poll, `None` where listing failed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceListing {
+    pub audio: Option<Vec<String>>,
+    pub midi_inputs: Option<Vec<PortIndexAndName>>,
+    pub midi_outputs: Option<Vec<PortIndexAndName>>,
+}
+
+impl DeviceListing {
+    /// Enumerate audio devices and MIDI ports. Some backends block here for
+    /// a long time, so call this off the main thread.
+    pub fn collect() -> Self {
+        Self {
+            audio: list_audio_devices().ok(),
+            midi_inputs: midi::list_input_ports().ok(),
+            midi_outputs: midi::list_output_ports().ok(),
+        }
+    }
+}
+
+/// The runtime's device selections plus the polling throttle shared by them
+#[derive(Debug, Default)]
+pub struct DeviceRecovery {
+    pub audio: DeviceWatch,
+    pub midi_input: DeviceWatch,
+    pub midi_clock: DeviceWatch,
+    pub midi_output: DeviceWatch,
+    polled_at: Option<Instant>,
+    listing: bool,
+    stream_error: bool,
+}
+
+impl DeviceRecovery {
+    /// True at most every [`POLL_INTERVAL`], or right away when `force` is
+    /// set (e.g. the audio backend reported a stream error), but never while
+    /// the previous poll's listing is still in flight
+    pub fn poll_due(&mut self, now: Instant, force: bool) -> bool {
+        self.stream_error |= force;
+        if self.listing {
+            return false;
+        }
+        let throttled = self
+            .polled_at
+            .is_some_and(|at| now.duration_since(at) < POLL_INTERVAL);
+        if throttled && !force {
+            return false;
+        }
+        self.polled_at = Some(now);
+        self.listing = true;
+        true
+    }
+
+    /// Mark the in-flight listing as arrived, returning whether a stream
+    /// error was reported since the poll started it
+    pub fn listed(&mut self) -> bool {
+        self.listing = false;
+        std::mem::take(&mut self.stream_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_fallback_prefers_the_next_device_in_the_old_list() {
+        let previous = devices(&["Built-in", "USB", "Loopback"]);
+        let available = devices(&["Built-in", "Loopback"]);
+        assert_eq!(
+            fallback_device(&previous, &available, "USB"),
+            Some("Loopback".to_string())
+        );
+
+        let available = devices(&["Built-in"]);
+        assert_eq!(
+            fallback_device(&previous, &available, "USB"),
+            Some("Built-in".to_string()),
+            "wraps around"
+        );
+
+        assert_eq!(fallback_device(&previous, &[], "USB"), None);
+    }
+
+    #[test]
+    fn test_fallback_without_history_uses_first_available() {
+        let available = devices(&["Built-in", "Loopback"]);
+        assert_eq!(
+            fallback_device(&[], &available, "USB"),
+            Some("Built-in".to_string())
+        );
+    }
+
+    #[test]
+    fn test_watch_falls_back_then_restores_preferred() {
+        let mut watch =
+            DeviceWatch::new("USB", devices(&["Built-in", "USB", "Loopback"]));
+
+        assert_eq!(
+            watch.update("USB", devices(&["Built-in", "USB", "Loopback"])),
+            None
+        );
+        assert_eq!(
+            watch.update("USB", devices(&["Built-in", "Loopback"])),
+            Some(DeviceChange::FellBack {
+                lost: "USB".to_string(),
+                fallback: "Loopback".to_string(),
+            })
+        );
+        assert_eq!(
+            watch.update("Loopback", devices(&["Built-in", "Loopback"])),
+            None
+        );
+        assert_eq!(
+            watch.update("Loopback", devices(&["Built-in", "Loopback", "USB"])),
+            Some(DeviceChange::Restored("USB".to_string()))
+        );
+        assert_eq!(watch.preferred(), "USB");
+    }
+
+    #[test]
+    fn test_watch_reports_loss_when_nothing_is_left() {
+        let mut watch = DeviceWatch::new("USB", devices(&["USB"]));
+
+        assert_eq!(
+            watch.update("USB", vec![]),
+            Some(DeviceChange::Lost("USB".to_string()))
+        );
+        assert_eq!(watch.update("", vec![]), None);
+        assert_eq!(
+            watch.update("", devices(&["Built-in"])),
+            Some(DeviceChange::FellBack {
+                lost: "USB".to_string(),
+                fallback: "Built-in".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_watch_ignores_unselected_devices() {
+        let mut watch = DeviceWatch::new("", vec![]);
+        assert_eq!(watch.update("", devices(&["USB"])), None);
+    }
+
+    #[test]
+    fn test_poll_is_throttled_unless_forced() {
+        let mut recovery = DeviceRecovery::default();
+        let start = Instant::now();
+
+        assert!(recovery.poll_due(start, false));
+        assert!(!recovery.listed());
+        assert!(!recovery.poll_due(start + Duration::from_millis(10), false));
+        assert!(recovery.poll_due(start + Duration::from_millis(20), true));
+        assert!(recovery.listed());
+        assert!(recovery.poll_due(start + POLL_INTERVAL * 2, false));
+    }
+
+    #[test]
+    fn test_poll_waits_for_the_listing_in_flight() {
+        let mut recovery = DeviceRecovery::default();
+        let start = Instant::now();
+
+        assert!(recovery.poll_due(start, false));
+        assert!(!recovery.poll_due(start + POLL_INTERVAL * 2, false));
+        assert!(!recovery.poll_due(start + POLL_INTERVAL * 2, true));
+
+        // The stream error is kept for when the listing arrives
+        assert!(recovery.listed());
+        assert!(recovery.poll_due(start + POLL_INTERVAL * 3, false));
+        assert!(!recovery.listed());
+    }
+}
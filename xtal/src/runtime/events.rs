@@ -1,11 +1,20 @@
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 
+use super::device_recovery::DeviceListing;
+use super::recorder::RecordingOptions;
+use super::recording::PreRoll;
+use super::stills::{StillFormat, TimelapseOptions};
 use super::web_view;
 use crate::control::ControlValue;
+use crate::image::DecodedImage;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum RuntimeEvent {
+    /// Move to the next randomize seed, picking one if none is set
+    AdvanceRandomizeSeed,
     AdvanceSingleFrame,
     CaptureFrame,
     ChangeAudioDevice(String),
@@ -13,27 +22,78 @@ pub enum RuntimeEvent {
     ChangeMidiControlInputPort(String),
     ChangeMidiControlOutputPort(String),
     ChangeOscPort(u16),
+    /// Namespace inbound OSC addresses, e.g. `/xtal1`; empty to disable
+    ChangeOscPrefix(String),
     ClearBuffer,
     CommitMappings,
     CurrentlyMapping(String),
+    /// Devices listed in the background for device recovery
+    DevicesListed(DeviceListing),
+    /// Result of decoding the image dropped onto the main window; the id
+    /// lets the runtime discard decodes superseded by a later drop
+    DroppedImageDecoded(u64, PathBuf, Result<Arc<DecodedImage>, String>),
+    /// Write current UI control values back to the control script's defaults
+    ExportControlScript,
+    /// Write the sketch's controls, snapshots, mappings and exclusions to a
+    /// single shareable file
+    ExportSketchState(PathBuf),
     MapModeError(String),
     MidiContinue,
     MidiStart,
     MidiStop,
     UpdateExclusions(Vec<String>),
     OpenOsDir(web_view::OsDir),
+    /// Mirror the render in a borderless fullscreen window on the monitor at
+    /// this index, replacing any output window already open
+    OpenOutputWindow(usize),
+    CloseOutputWindow,
     Pause(bool),
+    /// Show an uncommitted value for a UI control, e.g. while hovering;
+    /// `None` restores the committed value
+    PreviewControl((String, Option<f32>)),
     QueueRecord,
     ReceiveDir(web_view::UserDir, String),
+    /// Reapply the most recently undone UI control change
+    Redo,
     ReceiveMappings(web_view::Mappings),
     RemoveMapping(String),
     Save(Vec<String>),
+    /// Move the transport of a sketch using `TimingMode::Manual`
+    ScrubBeats(f32),
     SendMappings,
     SendMidi,
+    /// Set an exact tempo, clamped to 20-999, whether or not tap tempo is
+    /// enabled. Non-finite tempos are ignored.
+    SetBpm(f32),
     SetHrcc(bool),
+    /// Loop the `(in, out)` beat range with frame or manual timing; `None`
+    /// plays straight through
+    SetLoopRegion(Option<(f32, f32)>),
     SetMappingsEnabled(bool),
+    /// Echo MIDI-mapped control changes to the MIDI output port
+    SetMidiFeedbackEnabled(bool),
     SetMonitorPreview(bool),
+    /// Record this many seconds offline, one frame clock step per rendered
+    /// frame; `0` records in real time
+    SetOfflineRecordingSeconds(f32),
+    /// Smooth jittery OSC transport beats; `0.0` passes them through as is
+    SetOscTransportSmoothing(f32),
     SetPerfMode(bool),
+    /// Hide the cursor over the main window while in perf mode
+    SetPerfModeHidesCursor(bool),
+    /// Make `Randomize` reproducible; `None` restores nondeterminism
+    SetRandomizeSeed(Option<u64>),
+    /// Mux the selected audio device into real-time recordings
+    SetRecordAudio(bool),
+    SetRecordingOptions(RecordingOptions),
+    /// Count down this long before recordings start
+    SetRecordingPreRoll(PreRoll),
+    /// Encoding of captured frames (not timelapse stills)
+    SetStillFormat(StillFormat),
+    SetTimelapseOptions(TimelapseOptions),
+    /// Pace rendering at a different rate than the sketch's declared fps
+    /// without changing its beat math; `None` restores the declared fps
+    SetRenderFps(Option<u32>),
     SetTransitionTime(f32),
     StartRecording,
     StopRecording,
@@ -44,13 +104,31 @@ pub enum RuntimeEvent {
     SnapshotDelete(String),
     SnapshotRecall(String),
     SnapshotStore(String),
+    /// Store the current values as the A side of an A/B comparison
+    StashA,
+    /// Store the current values as the B side of an A/B comparison
+    StashB,
     SwitchSketch(String),
     Tap,
     TapTempoEnabled(bool),
+    /// Instantly switch to the other stashed A/B side
+    ToggleAB,
+    /// Turn capturing random animations into snapshots on or off
+    ToggleFreezeRandom,
     ToggleFullScreen,
+    /// Hold beat-based animation at its current beat, or resume it, without
+    /// pausing the frame loop
+    ToggleHold,
     ToggleMainFocus,
+    /// Start or stop saving a PNG every timelapse interval
+    ToggleTimelapse,
+    /// Revert the most recent UI control change; rapid changes to one control
+    /// count as one
+    Undo,
     UpdateUiControl((String, ControlValue)),
     HubPopulated,
+    /// Load a file written by `ExportSketchState` into the running sketch
+    ImportSketchState(PathBuf),
     SnapshotEnded,
     FrameSkipped,
     SketchSwitched(String),
@@ -0,0 +1,205 @@
+//! Detects single frames whose CPU work overruns the frame budget. Average
+//! fps smooths over one-off hitches, so the runtime times each stage of
+//! `render` and warns (throttled) about frames that took longer than a
+//! multiple of the target frame time, naming the slowest stage.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// CPU time over this multiple of the target frame time counts as over
+/// budget
+pub const DEFAULT_BUDGET_MULTIPLE: f32 = 1.0;
+
+// A sketch that's over budget usually is for many frames in a row
+const WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FramePhase {
+    SketchUpdate,
+    HubUpdate,
+    GraphEncode,
+}
+
+impl FramePhase {
+    const ALL: [Self; 3] =
+        [Self::SketchUpdate, Self::HubUpdate, Self::GraphEncode];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for FramePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::SketchUpdate => "sketch update",
+            Self::HubUpdate => "control hub update",
+            Self::GraphEncode => "graph encode",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A frame that went over budget
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetOverrun {
+    pub total: Duration,
+    pub budget: Duration,
+    pub slowest: FramePhase,
+    pub slowest_duration: Duration,
+}
+
+impl fmt::Display for BudgetOverrun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame CPU time {:.1}ms exceeded the {:.1}ms budget; slowest \
+             phase: {} ({:.1}ms)",
+            self.total.as_secs_f32() * 1000.0,
+            self.budget.as_secs_f32() * 1000.0,
+            self.slowest,
+            self.slowest_duration.as_secs_f32() * 1000.0,
+        )
+    }
+}
+
+/// Per-phase CPU time of the frame in progress
+#[derive(Debug)]
+pub struct FrameBudget {
+    multiple: f32,
+    phases: [Duration; 3],
+    warned_at: Option<Instant>,
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUDGET_MULTIPLE)
+    }
+}
+
+impl FrameBudget {
+    pub fn new(multiple: f32) -> Self {
+        Self {
+            multiple: multiple.max(0.0),
+            phases: [Duration::ZERO; 3],
+            warned_at: None,
+        }
+    }
+
+    /// Add time spent in `phase` to the current frame. Phases can be
+    /// recorded more than once per frame.
+    pub fn record(&mut self, phase: FramePhase, duration: Duration) {
+        self.phases[phase.index()] += duration;
+    }
+
+    pub fn phase(&self, phase: FramePhase) -> Duration {
+        self.phases[phase.index()]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().sum()
+    }
+
+    /// Close out the current frame and start the next one. Returns the
+    /// overrun when the frame went over `frame_time * multiple` and no
+    /// warning was reported in the last few seconds.
+    pub fn finish_frame(
+        &mut self,
+        frame_time: Duration,
+        now: Instant,
+    ) -> Option<BudgetOverrun> {
+        let total = self.total();
+        let budget = frame_time.mul_f64(self.multiple as f64);
+        let (slowest, slowest_duration) = FramePhase::ALL
+            .into_iter()
+            .map(|phase| (phase, self.phase(phase)))
+            .max_by_key(|(_, duration)| *duration)
+            .unwrap_or((FramePhase::SketchUpdate, Duration::ZERO));
+        self.phases = [Duration::ZERO; 3];
+
+        if total <= budget {
+            return None;
+        }
+        let throttled = self
+            .warned_at
+            .is_some_and(|at| now.duration_since(at) < WARNING_INTERVAL);
+        if throttled {
+            return None;
+        }
+        self.warned_at = Some(now);
+
+        Some(BudgetOverrun {
+            total,
+            budget,
+            slowest,
+            slowest_duration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const FRAME_TIME: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn accumulates_phases_until_the_frame_finishes() {
+        let mut budget = FrameBudget::default();
+
+        budget.record(FramePhase::SketchUpdate, Duration::from_millis(2));
+        budget.record(FramePhase::GraphEncode, Duration::from_millis(3));
+        budget.record(FramePhase::GraphEncode, Duration::from_millis(4));
+
+        assert_eq!(
+            budget.phase(FramePhase::GraphEncode),
+            Duration::from_millis(7)
+        );
+        assert_eq!(budget.total(), Duration::from_millis(9));
+        assert_eq!(budget.finish_frame(FRAME_TIME, Instant::now()), None);
+        assert_eq!(budget.total(), Duration::ZERO);
+    }
+
+    #[test]
+    fn reports_the_slowest_phase_of_an_overrun() {
+        let mut budget = FrameBudget::default();
+
+        let started = Instant::now();
+        thread::sleep(Duration::from_millis(20));
+        budget.record(FramePhase::HubUpdate, started.elapsed());
+        budget.record(FramePhase::SketchUpdate, Duration::from_millis(1));
+
+        let overrun = budget.finish_frame(FRAME_TIME, Instant::now()).unwrap();
+        assert_eq!(overrun.slowest, FramePhase::HubUpdate);
+        assert!(overrun.slowest_duration >= Duration::from_millis(20));
+        assert!(overrun.total > overrun.budget);
+        assert!(overrun.to_string().contains("control hub update"));
+    }
+
+    #[test]
+    fn threshold_is_a_multiple_of_the_frame_time() {
+        let mut budget = FrameBudget::new(2.0);
+
+        budget.record(FramePhase::GraphEncode, Duration::from_millis(30));
+        assert_eq!(budget.finish_frame(FRAME_TIME, Instant::now()), None);
+
+        budget.record(FramePhase::GraphEncode, Duration::from_millis(40));
+        let overrun = budget.finish_frame(FRAME_TIME, Instant::now()).unwrap();
+        assert_eq!(overrun.budget, Duration::from_millis(32));
+    }
+
+    #[test]
+    fn warnings_are_throttled() {
+        let mut budget = FrameBudget::default();
+        let start = Instant::now();
+        let overrun_at = |budget: &mut FrameBudget, now| {
+            budget.record(FramePhase::SketchUpdate, Duration::from_millis(50));
+            budget.finish_frame(FRAME_TIME, now).is_some()
+        };
+
+        assert!(overrun_at(&mut budget, start));
+        assert!(!overrun_at(&mut budget, start + Duration::from_secs(1)));
+        assert!(overrun_at(&mut budget, start + WARNING_INTERVAL));
+    }
+}
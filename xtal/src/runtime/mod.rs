@@ -1,10 +1,22 @@
+pub mod adaptive_quality;
 pub mod app;
+pub mod control_history;
+pub mod device_recovery;
 pub mod events;
+pub mod frame_budget;
 pub mod monitor_preview;
+#[cfg(feature = "ndi")]
+pub mod ndi_output;
+pub mod perf_cursor;
 pub mod recorder;
 pub mod recording;
+pub mod recording_audio;
 pub mod registry;
+pub mod remote_control;
 pub mod serialization;
+pub mod source_watch;
+pub mod state_migrations;
+pub mod stills;
 pub mod storage;
 pub mod web_view;
 pub mod web_view_bridge;
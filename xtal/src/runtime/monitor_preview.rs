@@ -4,9 +4,11 @@ use std::time::{Duration, Instant};
 use log::warn;
 use winit::dpi::{LogicalSize, PhysicalSize};
 use winit::event_loop::ActiveEventLoop;
-use winit::window::{Window, WindowAttributes, WindowId};
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowAttributes, WindowId};
 
 use crate::context::Context;
+use crate::gpu::fit_viewport;
 
 const MONITOR_PREVIEW_MAX_LONG_EDGE_PX: u32 = 640;
 const MONITOR_PREVIEW_MAX_SHORT_EDGE_PX: u32 = 180;
@@ -20,6 +22,23 @@ struct MonitorBlitState {
     pipeline_format: Option<wgpu::TextureFormat>,
 }
 
+/// The monitor at `index` in `monitors` (as listed by
+/// `ActiveEventLoop::available_monitors`) for an output window
+pub fn select_monitor<T>(
+    monitors: impl IntoIterator<Item = T>,
+    index: usize,
+) -> Result<T, String> {
+    let mut monitors: Vec<T> = monitors.into_iter().collect();
+    let count = monitors.len();
+    if index >= count {
+        return Err(format!(
+            "No monitor at index {} ({} available)",
+            index, count
+        ));
+    }
+    Ok(monitors.swap_remove(index))
+}
+
 pub fn preview_size_for_main(
     main_width: u32,
     main_height: u32,
@@ -161,6 +180,9 @@ pub enum RenderResult {
     OutOfMemory,
 }
 
+/// A secondary window that mirrors the graph's output: either the small,
+/// throttled monitor preview or a full-rate, borderless fullscreen output on
+/// another monitor
 pub struct MonitorPreview {
     window: Arc<Window>,
     window_id: WindowId,
@@ -168,6 +190,7 @@ pub struct MonitorPreview {
     surface_config: wgpu::SurfaceConfiguration,
     blit_state: MonitorBlitState,
     last_draw: Instant,
+    min_interval: Duration,
 }
 
 impl MonitorPreview {
@@ -191,6 +214,56 @@ impl MonitorPreview {
                 .map_err(|err| err.to_string())?,
         );
 
+        Self::from_window(
+            window,
+            instance,
+            adapter,
+            device,
+            initial_size,
+            Duration::from_secs_f32(1.0 / MONITOR_PREVIEW_MAX_FPS),
+        )
+    }
+
+    /// Open a borderless fullscreen window on `monitor` that draws every
+    /// frame, e.g. to project on a second display while the control UI stays
+    /// on the main one
+    pub fn create_output(
+        event_loop: &ActiveEventLoop,
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        monitor: MonitorHandle,
+    ) -> Result<Self, String> {
+        let initial_size = monitor.size();
+        let attrs = WindowAttributes::default()
+            .with_title("Xtal Output")
+            .with_fullscreen(Some(Fullscreen::Borderless(Some(monitor))))
+            .with_visible(true);
+        let window = Arc::new(
+            event_loop
+                .create_window(attrs)
+                .map_err(|err| err.to_string())?,
+        );
+        window.set_cursor_visible(false);
+
+        Self::from_window(
+            window,
+            instance,
+            adapter,
+            device,
+            initial_size,
+            Duration::ZERO,
+        )
+    }
+
+    fn from_window(
+        window: Arc<Window>,
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        initial_size: PhysicalSize<u32>,
+        min_interval: Duration,
+    ) -> Result<Self, String> {
         let surface = instance
             .create_surface(window.clone())
             .map_err(|err| err.to_string())?;
@@ -216,6 +289,7 @@ impl MonitorPreview {
             surface_config,
             blit_state: MonitorBlitState::new(device),
             last_draw: Instant::now() - Duration::from_secs(1),
+            min_interval,
         })
     }
 
@@ -256,9 +330,7 @@ impl MonitorPreview {
         source_texture: &wgpu::Texture,
         now: Instant,
     ) -> RenderResult {
-        let min_interval =
-            Duration::from_secs_f32(1.0 / MONITOR_PREVIEW_MAX_FPS);
-        if now.duration_since(self.last_draw) < min_interval {
+        if now.duration_since(self.last_draw) < self.min_interval {
             return RenderResult::Skipped;
         }
 
@@ -379,26 +451,6 @@ fn choose_surface_format(
         .or_else(|| formats.first().copied())
 }
 
-fn fit_viewport(
-    source_width: u32,
-    source_height: u32,
-    target_width: u32,
-    target_height: u32,
-) -> (f32, f32, f32, f32) {
-    let source_width = source_width.max(1) as f32;
-    let source_height = source_height.max(1) as f32;
-    let target_width = target_width.max(1) as f32;
-    let target_height = target_height.max(1) as f32;
-
-    let scale = (target_width / source_width).min(target_height / source_height);
-    let viewport_width = (source_width * scale).max(1.0);
-    let viewport_height = (source_height * scale).max(1.0);
-    let viewport_x = (target_width - viewport_width) * 0.5;
-    let viewport_y = (target_height - viewport_height) * 0.5;
-
-    (viewport_x, viewport_y, viewport_width, viewport_height)
-}
-
 const MONITOR_PREVIEW_BLIT_WGSL: &str = r#"
 @group(0) @binding(0)
 var tex_sampler: sampler;
@@ -433,3 +485,25 @@ fn fs_main(in: VsOut) -> @location(0) vec4f {
     return textureSample(tex, tex_sampler, uv);
 }
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_the_monitor_at_index() {
+        let monitors = ["built-in", "projector", "side"];
+        assert_eq!(select_monitor(monitors, 0), Ok("built-in"));
+        assert_eq!(select_monitor(monitors, 1), Ok("projector"));
+        assert_eq!(select_monitor(monitors, 2), Ok("side"));
+    }
+
+    #[test]
+    fn rejects_missing_monitors() {
+        assert_eq!(
+            select_monitor(["built-in"], 1),
+            Err("No monitor at index 1 (1 available)".to_string())
+        );
+        assert!(select_monitor(Vec::<&str>::new(), 0).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Publishes the final render over NDI so Resolume, OBS and other software
+//! can composite it without screen capture. Each frame the graph's recording
+//! source texture is copied to a readback buffer, and a worker thread maps it
+//! and hands it to a [`FrameSender`].
+
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+
+use log::{error, info, warn};
+
+use crate::gpu::compute_row_padding;
+use crate::io::ndi::{FourCc, NdiSender};
+
+// Frames are dropped rather than stalling the render when all are in flight
+const NUM_BUFFERS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelFormat {
+    Bgra,
+    Rgba,
+}
+
+impl PixelFormat {
+    pub fn from_texture_format(format: wgpu::TextureFormat) -> Option<Self> {
+        match format {
+            wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb => Some(Self::Bgra),
+            wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb => Some(Self::Rgba),
+            _ => None,
+        }
+    }
+}
+
+/// A mapped frame. Rows are `line_stride` bytes apart, which may include
+/// padding past `width * 4`.
+pub struct VideoFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub line_stride: u32,
+    pub format: PixelFormat,
+    pub fps: f32,
+    pub data: &'a [u8],
+}
+
+/// Destination for frames read back from the GPU, called on the worker
+/// thread
+pub trait FrameSender: Send {
+    fn send(&mut self, frame: &VideoFrame);
+}
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: PixelFormat,
+    fps: f32,
+}
+
+pub struct NdiOutput {
+    device: Arc<wgpu::Device>,
+    free: Vec<Readback>,
+    pending: Vec<Readback>,
+    in_flight: usize,
+    frame_tx: Option<mpsc::SyncSender<Readback>>,
+    return_rx: mpsc::Receiver<Readback>,
+    worker: Option<thread::JoinHandle<()>>,
+    warned_format: bool,
+}
+
+impl NdiOutput {
+    /// Publish over NDI as `sender_name`, which is how the source shows up
+    /// in receiving software
+    pub fn start(
+        device: Arc<wgpu::Device>,
+        sender_name: &str,
+    ) -> Result<Self, String> {
+        let sender = NdiSender::new(sender_name)?;
+        info!("NDI output publishing as \"{}\"", sender_name);
+        Ok(Self::with_sender(device, Box::new(sender)))
+    }
+
+    pub fn with_sender(
+        device: Arc<wgpu::Device>,
+        mut sender: Box<dyn FrameSender>,
+    ) -> Self {
+        let (frame_tx, frame_rx) = mpsc::sync_channel::<Readback>(NUM_BUFFERS);
+        let (return_tx, return_rx) = mpsc::channel();
+        let worker_device = device.clone();
+
+        let worker = thread::spawn(move || {
+            for readback in frame_rx {
+                send_readback(&worker_device, &readback, sender.as_mut());
+                if return_tx.send(readback).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            device,
+            free: Vec::with_capacity(NUM_BUFFERS),
+            pending: Vec::with_capacity(NUM_BUFFERS),
+            in_flight: 0,
+            frame_tx: Some(frame_tx),
+            return_rx,
+            worker: Some(worker),
+            warned_format: false,
+        }
+    }
+
+    /// Encode a copy of `source_texture` into a readback buffer. Returns
+    /// false when the frame is skipped because every buffer is in flight or
+    /// the format isn't 8-bit RGBA/BGRA.
+    pub fn capture(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        source_texture: &wgpu::Texture,
+        fps: f32,
+    ) -> bool {
+        let Some(format) =
+            PixelFormat::from_texture_format(source_texture.format())
+        else {
+            if !self.warned_format {
+                self.warned_format = true;
+                warn!(
+                    "NDI output: unsupported source format {:?}",
+                    source_texture.format()
+                );
+            }
+            return false;
+        };
+
+        let size = source_texture.size();
+        while let Ok(readback) = self.return_rx.try_recv() {
+            self.in_flight -= 1;
+            self.free.push(readback);
+        }
+        // Buffers from before a resize are released instead of reused
+        self.free.retain(|readback| {
+            readback.width == size.width && readback.height == size.height
+        });
+
+        let allocated = self.free.len() + self.pending.len() + self.in_flight;
+        let mut readback = match self.free.pop() {
+            Some(readback) => readback,
+            None if allocated < NUM_BUFFERS => {
+                self.create_readback(size.width, size.height, format)
+            }
+            None => return false,
+        };
+        readback.format = format;
+        readback.fps = fps;
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: source_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(readback.padded_bytes_per_row),
+                    rows_per_image: Some(readback.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: readback.width,
+                height: readback.height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.pending.push(readback);
+        true
+    }
+
+    /// Hand frames captured this frame to the worker; call after the
+    /// encoder is submitted
+    pub fn on_submitted(&mut self) {
+        let Some(frame_tx) = self.frame_tx.as_ref() else {
+            return;
+        };
+        for readback in self.pending.drain(..) {
+            if frame_tx.send(readback).is_err() {
+                error!("NDI output: worker thread disconnected");
+                break;
+            }
+            self.in_flight += 1;
+        }
+    }
+
+    fn create_readback(
+        &self,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> Readback {
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            + compute_row_padding(unpadded_bytes_per_row);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ndi_readback"),
+            size: (padded_bytes_per_row as u64) * (height as u64),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Readback {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format,
+            fps: 0.0,
+        }
+    }
+}
+
+impl Drop for NdiOutput {
+    fn drop(&mut self) {
+        self.frame_tx = None;
+        if let Some(Err(err)) = self.worker.take().map(|worker| worker.join()) {
+            error!("NDI worker thread panicked: {:?}", err);
+        }
+    }
+}
+
+fn send_readback(
+    device: &wgpu::Device,
+    readback: &Readback,
+    sender: &mut dyn FrameSender,
+) {
+    let slice = readback.buffer.slice(..);
+    let (map_tx, map_rx) = mpsc::sync_channel(1);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = map_tx.send(result);
+    });
+    let _ = device.poll(wgpu::PollType::Wait);
+
+    match map_rx.recv() {
+        Ok(Ok(())) => {
+            let data = slice.get_mapped_range();
+            sender.send(&VideoFrame {
+                width: readback.width,
+                height: readback.height,
+                line_stride: readback.padded_bytes_per_row,
+                format: readback.format,
+                fps: readback.fps,
+                data: &data,
+            });
+            drop(data);
+            readback.buffer.unmap();
+        }
+        Ok(Err(err)) => error!("NDI output: buffer mapping failed: {:?}", err),
+        Err(_) => error!("NDI output: buffer map channel disconnected"),
+    }
+}
+
+impl FrameSender for NdiSender {
+    fn send(&mut self, frame: &VideoFrame) {
+        let four_cc = match frame.format {
+            PixelFormat::Bgra => FourCc::Bgra,
+            PixelFormat::Rgba => FourCc::Rgba,
+        };
+        self.send_video(
+            frame.width,
+            frame.height,
+            frame.line_stride,
+            four_cc,
+            frame.fps,
+            frame.data,
+        );
+    }
+}
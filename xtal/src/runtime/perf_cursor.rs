@@ -0,0 +1,85 @@
+//! Decides whether the OS cursor shows over the main window. Performance mode
+//! can hide it for clean projection, but it always comes back while the
+//! window is unfocused so alt-tabbing away never strands an invisible cursor
+//! over other apps.
+
+#[derive(Clone, Debug)]
+pub struct PerfCursor {
+    perf_mode: bool,
+    hide_in_perf_mode: bool,
+    focused: bool,
+}
+
+impl PerfCursor {
+    pub fn new(hide_in_perf_mode: bool) -> Self {
+        Self {
+            perf_mode: false,
+            hide_in_perf_mode,
+            focused: true,
+        }
+    }
+
+    pub fn set_perf_mode(&mut self, perf_mode: bool) {
+        self.perf_mode = perf_mode;
+    }
+
+    /// Whether performance mode hides the cursor at all; some performers
+    /// want to keep it
+    pub fn hide_in_perf_mode(&self) -> bool {
+        self.hide_in_perf_mode
+    }
+
+    pub fn set_hide_in_perf_mode(&mut self, hide: bool) {
+        self.hide_in_perf_mode = hide;
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn visible(&self) -> bool {
+        !(self.perf_mode && self.hide_in_perf_mode && self.focused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_only_in_focused_perf_mode() {
+        let mut cursor = PerfCursor::new(true);
+        assert!(cursor.visible());
+
+        cursor.set_perf_mode(true);
+        assert!(!cursor.visible());
+
+        cursor.set_perf_mode(false);
+        assert!(cursor.visible());
+    }
+
+    #[test]
+    fn restores_while_unfocused() {
+        let mut cursor = PerfCursor::new(true);
+        cursor.set_perf_mode(true);
+
+        cursor.set_focused(false);
+        assert!(cursor.visible());
+
+        cursor.set_focused(true);
+        assert!(!cursor.visible());
+    }
+
+    #[test]
+    fn hiding_is_optional() {
+        let mut cursor = PerfCursor::new(false);
+        cursor.set_perf_mode(true);
+        assert!(cursor.visible());
+
+        cursor.set_hide_in_perf_mode(true);
+        assert!(!cursor.visible());
+
+        cursor.set_hide_in_perf_mode(false);
+        assert!(cursor.visible());
+    }
+}
@@ -1,20 +1,167 @@
 use std::collections::VecDeque;
 use std::io::Write;
+use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
-use std::sync::Arc;
 use std::sync::mpsc;
+use std::sync::{Arc, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
 
 use crate::gpu::compute_row_padding;
+use crate::runtime::stills::{Still, StillFormat};
 
 const DEFAULT_NUM_BUFFERS: usize = 6;
 const USE_BLOCKING_MAP_WAIT: bool = true;
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum VideoCodec {
+    /// Small files for quick sharing
+    #[default]
+    H264,
+    /// Large, edit-friendly intermediate files
+    ProRes,
+    Vp9,
+}
+
+impl VideoCodec {
+    pub fn encoder(&self) -> &'static str {
+        match self {
+            Self::H264 => "libx264",
+            Self::ProRes => "prores_ks",
+            Self::Vp9 => "libvpx-vp9",
+        }
+    }
+
+    /// Container the codec is written to
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::H264 => "mp4",
+            Self::ProRes => "mov",
+            Self::Vp9 => "webm",
+        }
+    }
+}
+
+/// How recordings are encoded
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct RecordingOptions {
+    pub codec: VideoCodec,
+    /// 0 (smallest) to 100 (best), mapped onto each codec's own quality scale
+    pub quality: u8,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            codec: VideoCodec::default(),
+            quality: 60,
+        }
+    }
+}
+
+impl RecordingOptions {
+    /// Output arguments for ffmpeg. `preset` only applies to H.264.
+    pub fn encoder_args(&self, preset: &str) -> Vec<String> {
+        let quality = self.quality.min(100) as f32 / 100.0;
+        let args: Vec<String> = match self.codec {
+            VideoCodec::H264 => vec![
+                "-crf".into(),
+                ((1.0 - quality) * 40.0).round().to_string(),
+                "-preset".into(),
+                preset.into(),
+                "-pix_fmt".into(),
+                "yuv420p".into(),
+            ],
+            VideoCodec::ProRes => vec![
+                "-profile:v".into(),
+                // proxy, lt, standard, hq
+                ((quality * 4.0).floor() as u8).min(3).to_string(),
+                "-pix_fmt".into(),
+                "yuv422p10le".into(),
+            ],
+            VideoCodec::Vp9 => vec![
+                "-crf".into(),
+                ((1.0 - quality) * 63.0).round().to_string(),
+                "-b:v".into(),
+                "0".into(),
+                "-deadline".into(),
+                "realtime".into(),
+                "-cpu-used".into(),
+                "8".into(),
+                "-row-mt".into(),
+                "1".into(),
+                "-pix_fmt".into(),
+                "yuv420p".into(),
+            ],
+        };
+        ["-c:v".into(), self.codec.encoder().into()]
+            .into_iter()
+            .chain(args)
+            .collect()
+    }
+
+    /// Fall back to H.264 when the installed ffmpeg can't encode `codec`,
+    /// returning a warning describing the fallback. Options are kept as is
+    /// when ffmpeg can't be queried or [`probe_encoders`] hasn't finished.
+    pub fn supported(self) -> (Self, Option<String>) {
+        match FFMPEG_ENCODERS.get() {
+            Some(Some(encoders)) => self.supported_by(encoders),
+            _ => (self, None),
+        }
+    }
+
+    /// [`Self::supported`] against the output of `ffmpeg -encoders`
+    pub fn supported_by(self, encoders: &str) -> (Self, Option<String>) {
+        let encoder = self.codec.encoder();
+        let is_supported = encoders
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(encoder));
+        if is_supported || self.codec == VideoCodec::H264 {
+            return (self, None);
+        }
+        let message = format!(
+            "ffmpeg has no {} encoder; recording {:?} as H264 instead",
+            encoder, self.codec
+        );
+        warn!("{}", message);
+        (
+            Self {
+                codec: VideoCodec::H264,
+                ..self
+            },
+            Some(message),
+        )
+    }
+}
+
+static FFMPEG_ENCODERS: OnceLock<Option<String>> = OnceLock::new();
+
+/// List the installed ffmpeg's encoders on a background thread so
+/// [`RecordingOptions::supported`] never waits on a subprocess. Only the first
+/// call runs ffmpeg.
+pub fn probe_encoders() {
+    if FFMPEG_ENCODERS.get().is_some() {
+        return;
+    }
+    thread::spawn(|| {
+        FFMPEG_ENCODERS.get_or_init(|| {
+            let output = Command::new("ffmpeg")
+                .args(["-hide_banner", "-encoders"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())?;
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        });
+    });
+}
+
 enum WriterMessage {
-    Frame(usize),
+    /// A readback buffer to encode, optionally also saved as a PNG still
+    Frame(usize, Option<PathBuf>),
     Stop,
 }
 
@@ -34,6 +181,7 @@ struct WriterThreadArgs {
     unpadded_bytes_per_row: u32,
     padded_bytes_per_row: u32,
     height: u32,
+    source_format: wgpu::TextureFormat,
 }
 
 #[cfg(feature = "recording-report")]
@@ -169,7 +317,8 @@ pub struct Recorder {
     buffer_return_rx: mpsc::Receiver<usize>,
     writer_tx: mpsc::SyncSender<WriterMessage>,
     writer_thread: Option<thread::JoinHandle<()>>,
-    pending_submit_buffers: VecDeque<usize>,
+    pending_submit_buffers: VecDeque<(usize, Option<PathBuf>)>,
+    pending_still: Option<PathBuf>,
     ffmpeg_process: Option<Child>,
     frames_captured: u32,
     frames_dropped: u32,
@@ -186,6 +335,7 @@ impl Recorder {
         height: u32,
         fps: f32,
         source_format: wgpu::TextureFormat,
+        options: &RecordingOptions,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let ffmpeg_pix_fmt = ffmpeg_input_pixel_format(source_format)
             .ok_or_else(|| {
@@ -220,16 +370,9 @@ impl Recorder {
                 &fps.to_string(),
                 "-i",
                 "pipe:0",
-                "-c:v",
-                "libx264",
-                "-crf",
-                "16",
-                "-preset",
-                ffmpeg_preset.as_str(),
-                "-pix_fmt",
-                "yuv420p",
-                output_path,
             ])
+            .args(options.encoder_args(&ffmpeg_preset))
+            .arg(output_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -283,6 +426,7 @@ impl Recorder {
             unpadded_bytes_per_row: w_unpadded,
             padded_bytes_per_row: w_padded,
             height: h,
+            source_format,
         };
 
         let writer_thread = thread::spawn(move || {
@@ -299,6 +443,7 @@ impl Recorder {
             writer_tx,
             writer_thread: Some(writer_thread),
             pending_submit_buffers: VecDeque::new(),
+            pending_still: None,
             ffmpeg_process: Some(ffmpeg),
             frames_captured: 0,
             frames_dropped: 0,
@@ -382,17 +527,34 @@ impl Recorder {
             },
         );
 
-        self.pending_submit_buffers.push_back(buffer_index);
+        self.pending_submit_buffers
+            .push_back((buffer_index, self.pending_still.take()));
 
         self.frames_captured += 1;
         true
     }
 
+    pub fn frames_captured(&self) -> u32 {
+        self.frames_captured
+    }
+
+    pub fn output_path(&self) -> &str {
+        &self.output_path
+    }
+
+    /// Also save the next captured frame as a PNG at `path`, reusing the
+    /// recording's readback instead of copying the texture a second time
+    pub fn request_still(&mut self, path: PathBuf) {
+        self.pending_still = Some(path);
+    }
+
     pub fn on_submitted(&mut self) {
-        while let Some(buffer_index) = self.pending_submit_buffers.pop_front() {
+        while let Some((buffer_index, still)) =
+            self.pending_submit_buffers.pop_front()
+        {
             if self
                 .writer_tx
-                .send(WriterMessage::Frame(buffer_index))
+                .send(WriterMessage::Frame(buffer_index, still))
                 .is_err()
             {
                 self.available_buffers.push_back(buffer_index);
@@ -449,7 +611,7 @@ fn writer_thread_fn(mut args: WriterThreadArgs) {
 
     loop {
         match args.frame_rx.recv() {
-            Ok(WriterMessage::Frame(buffer_index)) => {
+            Ok(WriterMessage::Frame(buffer_index, still)) => {
                 let buffer = &args.buffers[buffer_index];
                 let slice = buffer.slice(..);
 
@@ -495,6 +657,13 @@ fn writer_thread_fn(mut args: WriterThreadArgs) {
                             args.ffmpeg_stdin.write_all(&data).is_ok()
                         };
 
+                        if let Some(path) = still {
+                            let pixels = contiguous_frame
+                                .as_ref()
+                                .map_or_else(|| data.to_vec(), Vec::clone);
+                            save_still(&args, path, pixels);
+                        }
+
                         drop(data);
                         buffer.unmap();
 
@@ -522,6 +691,20 @@ fn writer_thread_fn(mut args: WriterThreadArgs) {
     }
 }
 
+// Encodes off the writer thread so ffmpeg isn't kept waiting.
+fn save_still(args: &WriterThreadArgs, path: PathBuf, pixels: Vec<u8>) {
+    let still = Still {
+        pixels,
+        width: args.unpadded_bytes_per_row / 4,
+        height: args.height,
+        format: args.source_format,
+    };
+    thread::spawn(move || match still.write(&path, StillFormat::Png8) {
+        Ok(()) => info!("Image saved to {:?}", path),
+        Err(err) => error!("Failed to save recording still: {}", err),
+    });
+}
+
 fn copy_padded_rows_to_contiguous(
     data: &[u8],
     out: &mut [u8],
@@ -554,3 +737,63 @@ fn ffmpeg_input_pixel_format(
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(codec: VideoCodec, quality: u8) -> Vec<String> {
+        RecordingOptions { codec, quality }.encoder_args("veryfast")
+    }
+
+    #[test]
+    fn maps_options_to_encoder_args() {
+        assert_eq!(
+            args(VideoCodec::H264, 60),
+            [
+                "-c:v", "libx264", "-crf", "16", "-preset", "veryfast",
+                "-pix_fmt", "yuv420p"
+            ]
+        );
+        assert_eq!(
+            args(VideoCodec::ProRes, 80),
+            [
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "3",
+                "-pix_fmt",
+                "yuv422p10le"
+            ]
+        );
+        assert_eq!(args(VideoCodec::ProRes, 0)[3], "0");
+        assert_eq!(
+            &args(VideoCodec::Vp9, 100)[..6],
+            ["-c:v", "libvpx-vp9", "-crf", "0", "-b:v", "0"]
+        );
+        assert_eq!(args(VideoCodec::Vp9, 0)[3], "63");
+    }
+
+    #[test]
+    fn falls_back_to_h264_for_unsupported_codecs() {
+        let encoders = "\
+Encoders:
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC
+ V..... prores_ks            Apple ProRes (iCodec Pro) (codec prores)
+";
+        let prores = RecordingOptions {
+            codec: VideoCodec::ProRes,
+            quality: 90,
+        };
+        assert_eq!(prores.clone().supported_by(encoders), (prores, None));
+
+        let vp9 = RecordingOptions {
+            codec: VideoCodec::Vp9,
+            quality: 90,
+        };
+        let (options, warning) = vp9.supported_by(encoders);
+        assert_eq!(options.codec, VideoCodec::H264);
+        assert_eq!(options.quality, 90);
+        assert!(warning.unwrap().contains("libvpx-vp9"));
+    }
+}
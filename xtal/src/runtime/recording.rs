@@ -1,11 +1,16 @@
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::mpsc;
 use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 
 use crate::core::util::uuid_5;
-use crate::runtime::recorder::Recorder;
+use crate::runtime::recorder::{Recorder, RecordingOptions};
+use crate::runtime::recording_audio::{AudioCapture, mux_audio};
+use crate::time::frame_clock;
 
 #[derive(Default)]
 pub struct RecordingState {
@@ -13,6 +18,12 @@ pub struct RecordingState {
     pub is_encoding: bool,
     pub is_queued: bool,
     pub recorder: Option<Recorder>,
+    /// Total frames an offline recording captures before it stops itself
+    offline_frames: Option<u32>,
+    /// Input stream muxed into the video once the recording is finalized
+    audio: Option<AudioCapture>,
+    fps: f32,
+    countdown: Option<Countdown>,
     finalize_rx: Option<mpsc::Receiver<FinalizeMessage>>,
 }
 
@@ -20,6 +31,18 @@ struct FinalizeMessage {
     frames_captured: u32,
     frames_dropped: u32,
     output_path: String,
+    audio: Option<Result<(), String>>,
+}
+
+/// What a recording captures and where it's written
+pub struct RecordingTarget<'a> {
+    pub device: Arc<wgpu::Device>,
+    pub output_path: &'a str,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub source_format: wgpu::TextureFormat,
+    pub options: &'a RecordingOptions,
 }
 
 pub struct FinalizeOutcome {
@@ -27,36 +50,232 @@ pub struct FinalizeOutcome {
     pub message: String,
 }
 
+/// Delay between triggering a recording and its first captured frame
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum PreRoll {
+    #[default]
+    Off,
+    Beats(f32),
+    Seconds(f32),
+}
+
+impl PreRoll {
+    fn length(&self) -> f32 {
+        match self {
+            Self::Off => 0.0,
+            Self::Beats(beats) => *beats,
+            Self::Seconds(seconds) => *seconds,
+        }
+    }
+}
+
+struct Countdown {
+    pre_roll: PreRoll,
+    /// Wall-clock length of one beat or second of the pre-roll
+    unit: Duration,
+    start_time: Instant,
+    remaining: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CountdownStep {
+    /// Whole beats or seconds left, reported each time it changes
+    Remaining(u32),
+    /// The pre-roll is over; start recording now
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordingPhase {
+    Idle,
+    /// Waiting for MIDI start
+    Queued,
+    CountingDown,
+    Recording,
+    Encoding,
+}
+
 impl RecordingState {
+    pub fn phase(&self) -> RecordingPhase {
+        if self.is_recording {
+            RecordingPhase::Recording
+        } else if self.countdown.is_some() {
+            RecordingPhase::CountingDown
+        } else if self.is_encoding {
+            RecordingPhase::Encoding
+        } else if self.is_queued {
+            RecordingPhase::Queued
+        } else {
+            RecordingPhase::Idle
+        }
+    }
+
+    /// Count down `pre_roll` before recording instead of starting right away.
+    /// Returns the initial count, or `None` when there is no pre-roll to wait
+    /// for. The countdown follows wall-clock time, with beat pre-rolls timed
+    /// at `bpm`, so pausing or looping the transport can't stall or restart
+    /// it.
+    pub fn start_countdown(
+        &mut self,
+        pre_roll: PreRoll,
+        bpm: f32,
+        now: Instant,
+    ) -> Option<u32> {
+        let length = pre_roll.length();
+        if length <= 0.0 {
+            return None;
+        }
+        let unit = match pre_roll {
+            PreRoll::Beats(_) => Duration::from_secs_f32(60.0 / bpm.max(1.0)),
+            _ => Duration::from_secs(1),
+        };
+        let remaining = length.ceil() as u32;
+        self.is_queued = false;
+        self.countdown = Some(Countdown {
+            pre_roll,
+            unit,
+            start_time: now,
+            remaining,
+        });
+        Some(remaining)
+    }
+
+    /// Advance a running countdown. Returns `None` when there's nothing new to
+    /// report.
+    pub fn tick_countdown(&mut self, now: Instant) -> Option<CountdownStep> {
+        let countdown = self.countdown.as_mut()?;
+        let elapsed = now.saturating_duration_since(countdown.start_time);
+        let elapsed = elapsed.as_secs_f32() / countdown.unit.as_secs_f32();
+        let remaining = countdown.pre_roll.length() - elapsed;
+        if remaining <= 0.0 {
+            self.countdown = None;
+            return Some(CountdownStep::Finished);
+        }
+        let remaining = remaining.ceil() as u32;
+        if remaining == countdown.remaining {
+            return None;
+        }
+        countdown.remaining = remaining;
+        Some(CountdownStep::Remaining(remaining))
+    }
+
+    /// Returns true if a countdown was running
+    pub fn cancel_countdown(&mut self) -> bool {
+        self.countdown.take().is_some()
+    }
+
+    pub fn is_counting_down(&self) -> bool {
+        self.countdown.is_some()
+    }
+
     pub fn start_recording(
         &mut self,
-        device: Arc<wgpu::Device>,
-        output_path: &str,
-        width: u32,
-        height: u32,
-        fps: f32,
-        source_format: wgpu::TextureFormat,
+        target: RecordingTarget,
     ) -> Result<String, Box<dyn Error>> {
         let recorder = Recorder::new(
-            device,
-            output_path,
-            width,
-            height,
-            fps,
-            source_format,
+            target.device,
+            target.output_path,
+            target.width,
+            target.height,
+            target.fps,
+            target.source_format,
+            target.options,
         )?;
         self.recorder = Some(recorder);
+        self.fps = target.fps;
         self.is_recording = true;
-        let message = format!("Recording to {}", output_path);
+        let message = format!("Recording to {}", target.output_path);
         log::info!("{}", message);
         Ok(message)
     }
 
+    /// Record exactly `fps * seconds` frames with the frame clock stepping
+    /// one frame per render instead of following wall-clock time, so slow
+    /// frames stretch the export rather than dropping out of it. Poll
+    /// [`Self::offline_complete`] after each captured frame.
+    pub fn start_offline_recording(
+        &mut self,
+        target: RecordingTarget,
+        seconds: f32,
+    ) -> Result<String, Box<dyn Error>> {
+        let frames = offline_frame_count(target.fps, seconds);
+        if frames == 0 {
+            return Err(format!(
+                "offline recording needs a positive duration, got {}s",
+                seconds
+            )
+            .into());
+        }
+
+        let message = self.start_recording(target)?;
+        self.offline_frames = Some(frames);
+        frame_clock::set_offline(true);
+
+        Ok(format!("{} ({} frames offline)", message, frames))
+    }
+
+    /// True once an offline recording has captured all of its frames
+    pub fn offline_complete(&self) -> bool {
+        match (self.offline_frames, &self.recorder) {
+            (Some(frames), Some(recorder)) => {
+                recorder.frames_captured() >= frames
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline_frames.is_some()
+    }
+
+    /// Capture `device_name` alongside the active real-time recording and mux
+    /// it into the video when it's finalized. Audio starts with the first
+    /// captured frame so it stays in sync with the recorded beats.
+    pub fn capture_audio(
+        &mut self,
+        device_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(recorder) = self.recorder.as_ref() else {
+            return Err("No active recorder".into());
+        };
+        if self.is_offline() {
+            return Err(
+                "audio can't be captured while recording offline".into()
+            );
+        }
+        // Spooled next to the video until it's muxed in
+        let spool_path =
+            Path::new(recorder.output_path()).with_extension("f32");
+        self.audio = Some(AudioCapture::start(device_name, spool_path)?);
+        Ok(())
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.audio.is_some()
+    }
+
+    /// Call after each frame is submitted to the GPU
+    pub fn on_submitted(&mut self) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        recorder.on_submitted();
+        let audio = self.audio.as_ref().filter(|audio| !audio.is_armed());
+        if let (Some(audio), true) = (audio, recorder.frames_captured() > 0) {
+            audio.arm();
+        }
+    }
+
     pub fn stop_recording(&mut self) -> Result<(), Box<dyn Error>> {
         self.is_recording = false;
         self.is_queued = false;
+        if self.offline_frames.take().is_some() {
+            frame_clock::set_offline(false);
+        }
 
         let recorder = self.recorder.take().ok_or("No active recorder")?;
+        let audio = self.audio.take().map(AudioCapture::finish);
+        let fps = self.fps;
 
         self.is_encoding = true;
 
@@ -65,10 +284,19 @@ impl RecordingState {
 
         thread::spawn(move || {
             let stats = recorder.stop();
+            let audio = audio.map(|track| {
+                mux_audio(
+                    &stats.output_path,
+                    &track?,
+                    stats.frames_captured,
+                    fps,
+                )
+            });
             let _ = finalize_tx.send(FinalizeMessage {
                 frames_captured: stats.frames_captured,
                 frames_dropped: stats.frames_dropped,
                 output_path: stats.output_path,
+                audio,
             });
         });
 
@@ -90,6 +318,7 @@ impl RecordingState {
                 frames_captured,
                 frames_dropped,
                 output_path,
+                audio,
             }) => {
                 self.is_encoding = false;
                 self.finalize_rx = None;
@@ -100,12 +329,20 @@ impl RecordingState {
                 } else {
                     String::new()
                 };
+                let audio_info = match &audio {
+                    Some(Ok(())) => " with audio".to_string(),
+                    Some(Err(err)) => {
+                        log::error!("Failed to mux recording audio: {}", err);
+                        format!(" without audio ({})", err)
+                    }
+                    None => String::new(),
+                };
 
                 Some(FinalizeOutcome {
-                    is_error: false,
+                    is_error: matches!(audio, Some(Err(_))),
                     message: format!(
-                        "Recording complete. {} frames captured{}. Video: {}",
-                        frames_captured, drop_info, output_path
+                        "Recording complete. {} frames captured{}. Video{}: {}",
+                        frames_captured, drop_info, audio_info, output_path
                     ),
                 })
             }
@@ -114,6 +351,11 @@ impl RecordingState {
     }
 }
 
+/// Number of frames an offline recording of `seconds` captures at `fps`
+pub fn offline_frame_count(fps: f32, seconds: f32) -> u32 {
+    (fps * seconds).round().max(0.0) as u32
+}
+
 pub fn generate_session_id() -> String {
     uuid_5()
 }
@@ -122,8 +364,66 @@ pub fn video_output_path(
     videos_dir: &str,
     session_id: &str,
     sketch_name: &str,
+    extension: &str,
 ) -> PathBuf {
     PathBuf::from(videos_dir)
         .join(format!("{}-{}", sketch_name, session_id))
-        .with_extension("mp4")
+        .with_extension(extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countdown_moves_from_queued_to_counting_to_recording() {
+        let mut state = RecordingState {
+            is_queued: true,
+            ..Default::default()
+        };
+        assert_eq!(state.phase(), RecordingPhase::Queued);
+
+        // At 120 bpm a beat lasts half a second
+        let now = Instant::now();
+        let at = |millis| now + Duration::from_millis(millis);
+        let count = state.start_countdown(PreRoll::Beats(2.0), 120.0, now);
+        assert_eq!(count, Some(2));
+        assert_eq!(state.phase(), RecordingPhase::CountingDown);
+        assert!(!state.is_queued);
+
+        assert_eq!(state.tick_countdown(at(250)), None);
+        assert_eq!(
+            state.tick_countdown(at(625)),
+            Some(CountdownStep::Remaining(1))
+        );
+        assert_eq!(state.tick_countdown(at(875)), None);
+        assert_eq!(
+            state.tick_countdown(at(1000)),
+            Some(CountdownStep::Finished)
+        );
+        assert_eq!(state.tick_countdown(at(1500)), None);
+
+        // Stand-in for start_recording, which needs a GPU and ffmpeg
+        state.is_recording = true;
+        assert_eq!(state.phase(), RecordingPhase::Recording);
+    }
+
+    #[test]
+    fn second_countdowns_ignore_bpm_and_can_be_canceled() {
+        let mut state = RecordingState::default();
+        let now = Instant::now();
+        assert_eq!(state.start_countdown(PreRoll::Off, 0.0, now), None);
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+
+        state.start_countdown(PreRoll::Seconds(3.0), 120.0, now);
+        assert_eq!(
+            state.tick_countdown(now + Duration::from_millis(1500)),
+            Some(CountdownStep::Remaining(2))
+        );
+
+        assert!(state.cancel_countdown());
+        assert!(!state.cancel_countdown());
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+        assert_eq!(state.tick_countdown(now + Duration::from_secs(5)), None);
+    }
 }
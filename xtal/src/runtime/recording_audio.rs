@@ -0,0 +1,401 @@
+//! Optional audio track for video recordings. Samples from the selected input
+//! device are converted to float, spooled to disk while recording and muxed
+//! into the finished video by a second ffmpeg pass, trimmed or padded to the
+//! exact video length so the audio lines up with the recorded frames (and
+//! therefore the beat clock).
+
+use cpal::{
+    Device, FromSample, Sample, SampleFormat, SizedSample, Stream,
+    StreamConfig, traits::*,
+};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+
+use log::{info, warn};
+
+use crate::ternary;
+
+/// Interleaved 32-bit float samples spooled to `path` while recording, so a
+/// long take costs disk space rather than memory
+#[derive(Debug)]
+pub struct AudioTrack {
+    path: PathBuf,
+    sample_rate: u32,
+    channels: u16,
+    len: usize,
+}
+
+impl AudioTrack {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Number of interleaved samples captured
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of interleaved samples in exactly `frames` video frames at
+    /// `fps`, rounded to whole sample frames
+    pub fn aligned_len(&self, frames: u32, fps: f32) -> usize {
+        let sample_frames = (frames as f64 / fps.max(1.0) as f64
+            * self.sample_rate as f64)
+            .round() as usize;
+        sample_frames * self.channels as usize
+    }
+
+    /// Write the spooled samples as a 32-bit float WAV file, cut or
+    /// silence-padded to exactly `frames` video frames at `fps`
+    pub fn write_wav(
+        &self,
+        writer: &mut impl Write,
+        frames: u32,
+        fps: f32,
+    ) -> io::Result<()> {
+        let len = self.aligned_len(frames, fps);
+        let channels = self.channels as u32;
+        let data_len = (len * 4) as u32;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_len).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // IEEE float
+        writer.write_all(&3u16.to_le_bytes())?;
+        writer.write_all(&(channels as u16).to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate * channels * 4).to_le_bytes())?;
+        writer.write_all(&((channels * 4) as u16).to_le_bytes())?;
+        writer.write_all(&32u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_len.to_le_bytes())?;
+
+        let captured = len.min(self.len);
+        let spool = BufReader::new(File::open(&self.path)?);
+        let copied = io::copy(&mut spool.take(captured as u64 * 4), writer)?;
+        if copied != captured as u64 * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "audio spool is shorter than recorded",
+            ));
+        }
+        io::copy(&mut io::repeat(0).take((len - captured) as u64 * 4), writer)?;
+        Ok(())
+    }
+
+    fn remove_spool(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A live input stream spooling into an [`AudioTrack`]. Nothing is kept until
+/// the capture is armed, which happens once the first video frame is
+/// submitted. Dropping it closes the stream.
+pub struct AudioCapture {
+    armed: Arc<AtomicBool>,
+    writer: JoinHandle<io::Result<AudioTrack>>,
+    stream: Stream,
+}
+
+impl AudioCapture {
+    /// Capture `device_name`, spooling its samples to `spool_path`
+    pub fn start(
+        device_name: &str,
+        spool_path: impl Into<PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let host = cpal::default_host();
+        let device = host
+            .input_devices()?
+            .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
+            .ok_or_else(|| {
+                format!("Audio device '{}' not found", device_name)
+            })?;
+        let supported = device.default_input_config()?;
+        let sample_format = supported.sample_format();
+        let stream_config: StreamConfig = supported.into();
+
+        let spool_path = spool_path.into();
+        let file = File::create(&spool_path)?;
+        let (tx, rx) = mpsc::channel();
+        let armed = Arc::new(AtomicBool::new(false));
+        let writer = spool(
+            rx,
+            file,
+            spool_path,
+            stream_config.sample_rate.0,
+            stream_config.channels,
+        );
+
+        let stream = match sample_format {
+            SampleFormat::F32 => {
+                build_stream::<f32>(&device, &stream_config, armed.clone(), tx)
+            }
+            SampleFormat::I16 => {
+                build_stream::<i16>(&device, &stream_config, armed.clone(), tx)
+            }
+            SampleFormat::U16 => {
+                build_stream::<u16>(&device, &stream_config, armed.clone(), tx)
+            }
+            format => {
+                return Err(
+                    format!("Unsupported sample format: {}", format).into()
+                );
+            }
+        }?;
+        stream.play()?;
+
+        info!("Recording audio from {}", device_name);
+        Ok(Self {
+            armed,
+            writer,
+            stream,
+        })
+    }
+
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::Release);
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Acquire)
+    }
+
+    /// Close the input stream and hand back everything it captured
+    pub fn finish(self) -> Result<AudioTrack, String> {
+        let Self { writer, stream, .. } = self;
+        // The stream owns the sender, so closing it ends the spool
+        drop(stream);
+        writer
+            .join()
+            .map_err(|_| "audio spool thread panicked".to_string())?
+            .map_err(|err| format!("failed to spool audio: {}", err))
+    }
+}
+
+fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    armed: Arc<AtomicBool>,
+    tx: mpsc::Sender<Vec<f32>>,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &_| {
+            if armed.load(Ordering::Acquire) {
+                let _ = tx.send(to_f32(data));
+            }
+        },
+        move |err| warn!("Error in recording audio stream: {}", err),
+        None,
+    )
+}
+
+fn to_f32<T>(data: &[T]) -> Vec<f32>
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    data.iter()
+        .map(|sample| sample.to_sample::<f32>())
+        .collect()
+}
+
+// Appends every chunk received to `file` until the sender hangs up
+fn spool(
+    rx: mpsc::Receiver<Vec<f32>>,
+    file: File,
+    path: PathBuf,
+    sample_rate: u32,
+    channels: u16,
+) -> JoinHandle<io::Result<AudioTrack>> {
+    thread::spawn(move || {
+        let mut writer = BufWriter::new(file);
+        let mut len = 0;
+        for chunk in rx {
+            for sample in &chunk {
+                writer.write_all(&sample.to_le_bytes())?;
+            }
+            len += chunk.len();
+        }
+        writer.flush()?;
+        Ok(AudioTrack {
+            path,
+            sample_rate,
+            channels: channels.max(1),
+            len,
+        })
+    })
+}
+
+/// Mux `track` into the finished video at `video_path`, replacing it in place.
+/// The audio is aligned to exactly `frames` frames at `fps`.
+pub fn mux_audio(
+    video_path: &str,
+    track: &AudioTrack,
+    frames: u32,
+    fps: f32,
+) -> Result<(), String> {
+    let video = Path::new(video_path);
+    let wav_path = video.with_extension("wav");
+    let extension = video
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("mp4");
+    let muxed_path = video.with_extension(format!("av.{}", extension));
+    // WebM only carries Opus or Vorbis
+    let audio_codec = ternary!(extension == "webm", "libopus", "aac");
+
+    let written = File::create(&wav_path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        track.write_wav(&mut writer, frames, fps)?;
+        writer.flush()
+    });
+    track.remove_spool();
+    written.map_err(|err| format!("failed to write audio track: {}", err))?;
+
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-hide_banner", "-loglevel", "error", "-nostats"])
+        .arg("-i")
+        .arg(video)
+        .arg("-i")
+        .arg(&wav_path)
+        .args(["-map", "0:v:0", "-map", "1:a:0"])
+        .args(["-c:v", "copy", "-c:a", audio_codec, "-b:a", "320k"])
+        .arg(&muxed_path)
+        .status();
+    let _ = fs::remove_file(&wav_path);
+
+    match status {
+        Ok(status) if status.success() => fs::rename(&muxed_path, video)
+            .map_err(|err| format!("failed to replace video: {}", err)),
+        Ok(status) => {
+            let _ = fs::remove_file(&muxed_path);
+            Err(format!("ffmpeg exited with status: {}", status))
+        }
+        Err(err) => Err(format!("failed to run ffmpeg: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("xtal-recording-audio-tests");
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir.join(name)
+    }
+
+    fn spool_samples(
+        name: &str,
+        sample_rate: u32,
+        channels: u16,
+        chunks: &[&[f32]],
+    ) -> AudioTrack {
+        let path = temp_path(name);
+        let (tx, rx) = mpsc::channel();
+        let writer = spool(
+            rx,
+            File::create(&path).unwrap(),
+            path,
+            sample_rate,
+            channels,
+        );
+        for chunk in chunks {
+            tx.send(chunk.to_vec()).unwrap();
+        }
+        drop(tx);
+        writer.join().unwrap().unwrap()
+    }
+
+    fn wav_samples(bytes: &[u8]) -> Vec<f32> {
+        bytes[44..]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn spools_chunks_in_order() {
+        let track = spool_samples(
+            "order.f32",
+            48_000,
+            2,
+            &[&[0.25, -0.25, 0.5, -0.5], &[0.75, -0.75]],
+        );
+        assert_eq!(track.len(), 6);
+
+        // 1 frame at 16000fps is 3 sample frames of 2 channels
+        let mut bytes = vec![];
+        track.write_wav(&mut bytes, 1, 16_000.0).unwrap();
+        assert_eq!(wav_samples(&bytes), [0.25, -0.25, 0.5, -0.5, 0.75, -0.75]);
+        track.remove_spool();
+    }
+
+    #[test]
+    fn aligns_audio_to_video_length() {
+        let track = spool_samples("align.f32", 100, 2, &[&[0.5; 2 * 150]]);
+
+        // 30 frames at 30fps is 1 second: 100 sample frames of 2 channels
+        let mut trimmed = vec![];
+        track.write_wav(&mut trimmed, 30, 30.0).unwrap();
+        let trimmed = wav_samples(&trimmed);
+        assert_eq!(trimmed.len(), 200);
+        assert!(trimmed.iter().all(|s| *s == 0.5));
+
+        // 60 frames is 2 seconds, padded with silence past the capture
+        let mut padded = vec![];
+        track.write_wav(&mut padded, 60, 30.0).unwrap();
+        let padded = wav_samples(&padded);
+        assert_eq!(padded.len(), 400);
+        assert_eq!(padded[299], 0.5);
+        assert_eq!(padded[300], 0.0);
+        track.remove_spool();
+    }
+
+    #[test]
+    fn wav_header_describes_float_samples() {
+        let track =
+            spool_samples("header.f32", 44_100, 2, &[&[0.0, 1.0, -1.0, 0.5]]);
+        let mut bytes = vec![];
+        // 1 frame at 22050fps is 2 sample frames of 2 channels
+        track.write_wav(&mut bytes, 1, 22_050.0).unwrap();
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3);
+        assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2);
+        assert_eq!(
+            u32::from_le_bytes(bytes[24..28].try_into().unwrap()),
+            44_100
+        );
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 16);
+        assert_eq!(bytes.len(), 44 + 16);
+        track.remove_spool();
+    }
+
+    #[test]
+    fn converts_integer_samples_to_float() {
+        assert_eq!(to_f32(&[i16::MIN, 0_i16]), [-1.0, 0.0]);
+        assert_eq!(to_f32(&[0_u16, 32_768]), [-1.0, 0.0]);
+        assert_eq!(to_f32(&[0.5_f32]), [0.5]);
+    }
+}
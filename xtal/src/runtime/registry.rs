@@ -7,6 +7,7 @@ type SketchFactory = Box<dyn Fn() -> Box<dyn Sketch> + Send + Sync + 'static>;
 pub struct SketchEntry {
     pub config: &'static SketchConfig,
     pub factory: SketchFactory,
+    pub tags: Vec<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -16,6 +17,14 @@ pub struct SketchCategory {
     pub sketches: Vec<String>,
 }
 
+/// Result of [`RuntimeRegistry::find_sketch`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SketchLookup {
+    Found(String),
+    Ambiguous(Vec<String>),
+    NotFound,
+}
+
 #[derive(Default)]
 pub struct RuntimeRegistry {
     entries: HashMap<String, SketchEntry>,
@@ -47,6 +56,7 @@ impl RuntimeRegistry {
             SketchEntry {
                 config,
                 factory: Box::new(factory),
+                tags: Vec::new(),
             },
         );
 
@@ -79,6 +89,106 @@ impl RuntimeRegistry {
         Ok(())
     }
 
+    /// Adds `tags` to a registered sketch. Unlike categories, a sketch can
+    /// carry any number of tags.
+    pub fn tag_sketch(
+        &mut self,
+        name: &str,
+        tags: Vec<String>,
+    ) -> Result<(), String> {
+        let Some(entry) = self.entries.get_mut(name) else {
+            return Err(format!("cannot tag unknown sketch '{}'", name));
+        };
+
+        for tag in tags {
+            if !entry.tags.contains(&tag) {
+                entry.tags.push(tag);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restricts the listed sketches and categories to those tagged `tag`.
+    /// Categories left empty are dropped. Sketches stay registered, so they
+    /// can still be loaded by name.
+    pub fn filter_by_tag(&mut self, tag: &str) -> Result<(), String> {
+        let tagged = self.sketches_with_tag(tag);
+        if tagged.is_empty() {
+            return Err(format!("no sketches are tagged '{}'", tag));
+        }
+
+        self.ordered_names.retain(|name| tagged.contains(name));
+        for category in &mut self.categories {
+            category.sketches.retain(|name| tagged.contains(name));
+        }
+        self.categories
+            .retain(|category| !category.sketches.is_empty());
+
+        Ok(())
+    }
+
+    /// Listed sketches tagged `tag`, in registration order.
+    pub fn sketches_with_tag(&self, tag: &str) -> Vec<String> {
+        self.ordered_names
+            .iter()
+            .filter(|name| {
+                self.entries
+                    .get(*name)
+                    .is_some_and(|entry| entry.tags.iter().any(|t| t == tag))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Every tag used by a listed sketch, in order of first use.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for name in &self.ordered_names {
+            let Some(entry) = self.entries.get(name) else {
+                continue;
+            };
+            for tag in &entry.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        tags
+    }
+
+    /// Resolves a possibly partial sketch name against the listed sketches.
+    /// Tries an exact match, then names starting with `query`, then names
+    /// containing it, then names containing its characters in order; the
+    /// first tier with any matches decides.
+    pub fn find_sketch(&self, query: &str) -> SketchLookup {
+        if self.ordered_names.iter().any(|name| name == query) {
+            return SketchLookup::Found(query.to_string());
+        }
+
+        let tiers: [&dyn Fn(&str) -> bool; 3] = [
+            &|name| name.starts_with(query),
+            &|name| name.contains(query),
+            &|name| is_subsequence(query, name),
+        ];
+
+        for matches in tiers {
+            let mut candidates: Vec<String> = self
+                .ordered_names
+                .iter()
+                .filter(|name| matches(name))
+                .cloned()
+                .collect();
+            match candidates.len() {
+                0 => continue,
+                1 => return SketchLookup::Found(candidates.remove(0)),
+                _ => return SketchLookup::Ambiguous(candidates),
+            }
+        }
+
+        SketchLookup::NotFound
+    }
+
     pub fn get(&self, name: &str) -> Option<&SketchEntry> {
         self.entries.get(name)
     }
@@ -94,6 +204,49 @@ impl RuntimeRegistry {
     pub fn categories(&self) -> &[SketchCategory] {
         &self.categories
     }
+
+    /// The sketch after `current` in browsing order, wrapping around.
+    pub fn next_sketch_name(&self, current: &str) -> Option<String> {
+        self.step_sketch_name(current, 1)
+    }
+
+    /// The sketch before `current` in browsing order, wrapping around.
+    pub fn previous_sketch_name(&self, current: &str) -> Option<String> {
+        self.step_sketch_name(current, -1)
+    }
+
+    // Browsing order is the enabled categories in order, or every listed
+    // sketch when no categories are defined. A `current` outside that order
+    // steps onto its first or last sketch.
+    fn step_sketch_name(&self, current: &str, step: isize) -> Option<String> {
+        let mut names: Vec<&String> = Vec::new();
+        if self.categories.is_empty() {
+            names.extend(&self.ordered_names);
+        }
+        for category in self.categories.iter().filter(|c| c.enabled) {
+            for name in &category.sketches {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        if names.is_empty() {
+            return None;
+        }
+
+        let len = names.len() as isize;
+        let index = match names.iter().position(|name| *name == current) {
+            Some(index) => (index as isize + step).rem_euclid(len),
+            None if step < 0 => len - 1,
+            None => 0,
+        };
+        Some(names[index as usize].clone())
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
 }
 
 #[cfg(test)]
@@ -118,6 +271,7 @@ mod tests {
         w: 640,
         h: 480,
         banks: 4,
+        ..SketchConfig::DEFAULT
     };
 
     #[test]
@@ -165,4 +319,88 @@ mod tests {
         assert!(registry.categories()[0].enabled);
         assert_eq!(registry.categories()[0].sketches, vec!["test"]);
     }
+
+    fn registry_with(names: &[&'static str]) -> RuntimeRegistry {
+        let mut registry = RuntimeRegistry::new();
+        for &name in names {
+            let config = Box::leak(Box::new(SketchConfig { name, ..CONFIG }));
+            registry
+                .register(config, || Box::new(TestSketch))
+                .expect("register test sketch");
+        }
+        registry
+    }
+
+    #[test]
+    fn find_sketch_prefers_exact_match() {
+        let registry = registry_with(&["displ", "displacement_2a"]);
+
+        assert_eq!(
+            registry.find_sketch("displ"),
+            SketchLookup::Found("displ".to_string())
+        );
+    }
+
+    #[test]
+    fn find_sketch_resolves_unique_partial_names() {
+        let registry =
+            registry_with(&["displacement_2a", "wave_sphere", "wave_fract"]);
+
+        assert_eq!(
+            registry.find_sketch("displ"),
+            SketchLookup::Found("displacement_2a".to_string())
+        );
+        assert_eq!(
+            registry.find_sketch("sphere"),
+            SketchLookup::Found("wave_sphere".to_string())
+        );
+        assert_eq!(
+            registry.find_sketch("wfr"),
+            SketchLookup::Found("wave_fract".to_string())
+        );
+        assert_eq!(registry.find_sketch("zzz"), SketchLookup::NotFound);
+    }
+
+    #[test]
+    fn find_sketch_reports_ambiguous_candidates() {
+        let registry =
+            registry_with(&["displacement_2a", "wave_sphere", "wave_fract"]);
+
+        assert_eq!(
+            registry.find_sketch("wave"),
+            SketchLookup::Ambiguous(vec![
+                "wave_sphere".to_string(),
+                "wave_fract".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn next_and_previous_sketch_wrap_around() {
+        let registry = registry_with(&["a", "b", "c"]);
+
+        assert_eq!(registry.next_sketch_name("a").as_deref(), Some("b"));
+        assert_eq!(registry.next_sketch_name("c").as_deref(), Some("a"));
+        assert_eq!(registry.previous_sketch_name("a").as_deref(), Some("c"));
+        assert_eq!(registry.previous_sketch_name("c").as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn next_and_previous_sketch_skip_disabled_categories() {
+        let mut registry = registry_with(&["a", "b", "c", "d"]);
+        registry
+            .define_category("main", true, vec!["a".into(), "b".into()])
+            .expect("define category");
+        registry
+            .define_category("hidden", false, vec!["c".into()])
+            .expect("define category");
+        registry
+            .define_category("more", true, vec!["d".into()])
+            .expect("define category");
+
+        assert_eq!(registry.next_sketch_name("b").as_deref(), Some("d"));
+        assert_eq!(registry.next_sketch_name("d").as_deref(), Some("a"));
+        assert_eq!(registry.previous_sketch_name("a").as_deref(), Some("d"));
+        assert_eq!(registry.next_sketch_name("c").as_deref(), Some("a"));
+    }
 }
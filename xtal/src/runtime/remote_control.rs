@@ -0,0 +1,316 @@
+//! Remote control over WebSocket, e.g. from a phone or a script on another
+//! machine. Clients speak the same JSON protocol as the embedded web view:
+//! inbound [`web_view::Event`]s are translated to runtime commands exactly
+//! like the web view's, and every event the runtime sends to the web view is
+//! broadcast to connected clients. The server itself requires the
+//! `remote-control` feature and is started when the `remote_control_address`
+//! setting is set.
+
+use super::events::RuntimeEvent;
+use super::web_view;
+
+#[cfg(feature = "remote-control")]
+pub use server::RemoteControlServer;
+
+/// Translate a client message into the runtime command it stands for.
+/// `Ok(None)` means a valid event the runtime doesn't act on (e.g. `Ready`).
+pub fn command_from_message(
+    message: &str,
+) -> Result<Option<RuntimeEvent>, String> {
+    let event = web_view::parse_ui_message(message)?;
+    Ok(web_view::map_event_to_runtime_command(&event))
+}
+
+/// The message broadcast to clients for `event`, if it's bound for the web
+/// view
+pub fn message_from_event(
+    event: &RuntimeEvent,
+) -> Option<Result<String, String>> {
+    match event {
+        RuntimeEvent::WebView(event) => Some(web_view::to_ui_message(event)),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "remote-control")]
+mod server {
+    use std::io::ErrorKind;
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use log::{info, warn};
+    use tungstenite::Message;
+
+    use super::*;
+    use crate::runtime::events::RuntimeCommandSender;
+    use crate::runtime::web_view::Event;
+
+    // How often the listener checks for new connections and each client
+    // thread flushes outbound messages between reads
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    // The runtime only sends these on change, so the latest of each is
+    // replayed to clients that connect later. Events sharing a slot carry the
+    // same state.
+    const REPLAY_SLOTS: usize = 4;
+
+    fn replay_slot(event: &RuntimeEvent) -> Option<usize> {
+        let RuntimeEvent::WebView(event) = event else {
+            return None;
+        };
+        match **event {
+            Event::Init(_) => Some(0),
+            Event::LoadSketch(_) => Some(1),
+            Event::Mappings(_) => Some(2),
+            Event::HubPopulated(_) | Event::UpdatedControls(_) => Some(3),
+            _ => None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Clients {
+        outboxes: Vec<Sender<String>>,
+        replay: [Option<String>; REPLAY_SLOTS],
+    }
+
+    pub struct RemoteControlServer {
+        address: SocketAddr,
+        clients: Arc<Mutex<Clients>>,
+        running: Arc<AtomicBool>,
+    }
+
+    impl RemoteControlServer {
+        /// Listen on `address` (e.g. `0.0.0.0:2347`), forwarding client
+        /// commands to `command_tx`. There is no authentication, so only
+        /// bind to interfaces on a trusted network.
+        pub fn start(
+            address: &str,
+            command_tx: RuntimeCommandSender,
+        ) -> Result<Self, String> {
+            let listener = TcpListener::bind(address).map_err(|err| {
+                format!("failed to bind remote control to {}: {}", address, err)
+            })?;
+            let local_address =
+                listener.local_addr().map_err(|err| err.to_string())?;
+            listener
+                .set_nonblocking(true)
+                .map_err(|err| err.to_string())?;
+
+            let clients = Arc::new(Mutex::new(Clients::default()));
+            let running = Arc::new(AtomicBool::new(true));
+            {
+                let clients = clients.clone();
+                let running = running.clone();
+                thread::spawn(move || {
+                    accept_clients(listener, &clients, &running, &command_tx)
+                });
+            }
+
+            info!("Remote control listening on ws://{}", local_address);
+
+            Ok(Self {
+                address: local_address,
+                clients,
+                running,
+            })
+        }
+
+        pub fn address(&self) -> SocketAddr {
+            self.address
+        }
+
+        /// Send `event` to every client if it's bound for the web view
+        pub fn broadcast(&self, event: &RuntimeEvent) {
+            let message = match message_from_event(event) {
+                Some(Ok(message)) => message,
+                Some(Err(err)) => {
+                    warn!("{}", err);
+                    return;
+                }
+                None => return,
+            };
+
+            let mut clients = self.clients.lock().unwrap();
+            if let Some(slot) = replay_slot(event) {
+                clients.replay[slot] = Some(message.clone());
+            }
+            clients
+                .outboxes
+                .retain(|outbox| outbox.send(message.clone()).is_ok());
+        }
+    }
+
+    impl Drop for RemoteControlServer {
+        fn drop(&mut self) {
+            self.running.store(false, Ordering::Release);
+        }
+    }
+
+    fn accept_clients(
+        listener: TcpListener,
+        clients: &Mutex<Clients>,
+        running: &Arc<AtomicBool>,
+        command_tx: &RuntimeCommandSender,
+    ) {
+        while running.load(Ordering::Acquire) {
+            let (stream, peer) = match listener.accept() {
+                Ok(connection) => connection,
+                Err(err) => {
+                    if err.kind() != ErrorKind::WouldBlock {
+                        warn!("Remote control accept failed: {}", err);
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            let (outbox, inbox) = mpsc::channel();
+            {
+                let mut clients = clients.lock().unwrap();
+                for message in clients.replay.iter().flatten() {
+                    let _ = outbox.send(message.clone());
+                }
+                clients.outboxes.push(outbox);
+            }
+
+            let running = running.clone();
+            let command_tx = command_tx.clone();
+            thread::spawn(move || {
+                info!("Remote control client {} connected", peer);
+                if let Err(err) =
+                    serve_client(stream, &inbox, &running, &command_tx)
+                {
+                    warn!("Remote control client {}: {}", peer, err);
+                }
+                info!("Remote control client {} disconnected", peer);
+            });
+        }
+    }
+
+    fn serve_client(
+        stream: TcpStream,
+        inbox: &Receiver<String>,
+        running: &AtomicBool,
+        command_tx: &RuntimeCommandSender,
+    ) -> Result<(), String> {
+        // Accepted sockets inherit the listener's non-blocking mode on some
+        // platforms; reads time out instead so outbound messages get flushed
+        stream
+            .set_nonblocking(false)
+            .map_err(|err| err.to_string())?;
+        let mut socket = tungstenite::accept(stream)
+            .map_err(|err| format!("handshake failed: {}", err))?;
+        socket
+            .get_ref()
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| err.to_string())?;
+
+        while running.load(Ordering::Acquire) {
+            while let Ok(message) = inbox.try_recv() {
+                socket
+                    .send(Message::text(message))
+                    .map_err(|err| err.to_string())?;
+            }
+
+            let text = match socket.read() {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        ErrorKind::WouldBlock | ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(err) => return Err(err.to_string()),
+            };
+
+            match command_from_message(text.as_str()) {
+                Ok(Some(command)) => command_tx
+                    .send(command)
+                    .map_err(|_| "runtime stopped".to_string())?,
+                Ok(None) => {}
+                Err(err) => warn!("{}", err),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ControlValue;
+    use crate::runtime::web_view::Event;
+
+    #[test]
+    fn translates_control_updates_to_runtime_commands() {
+        let message = r#"{"UpdateControlFloat":{"name":"size","value":0.5}}"#;
+        assert_eq!(
+            command_from_message(message).unwrap(),
+            Some(RuntimeEvent::UpdateUiControl((
+                "size".to_string(),
+                ControlValue::from(0.5)
+            )))
+        );
+
+        assert_eq!(command_from_message("\"Ready\"").unwrap(), None);
+        assert!(command_from_message("{\"Nope\":1}").is_err());
+    }
+
+    #[test]
+    fn broadcasts_only_web_view_events() {
+        let event = RuntimeEvent::WebView(Box::new(Event::Bpm(128.0)));
+        let message = message_from_event(&event).unwrap().unwrap();
+        assert_eq!(
+            web_view::parse_ui_message(&message).unwrap(),
+            Event::Bpm(128.0)
+        );
+
+        assert!(message_from_event(&RuntimeEvent::Tap).is_none());
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn server_forwards_commands_and_replays_state() {
+        use std::net::TcpStream;
+        use std::time::Duration;
+
+        use tungstenite::Message;
+
+        use crate::runtime::events::command_channel;
+
+        let (command_tx, command_rx) = command_channel();
+        let server =
+            RemoteControlServer::start("127.0.0.1:0", command_tx).unwrap();
+        server.broadcast(&RuntimeEvent::WebView(Box::new(
+            Event::UpdatedControls(vec![]),
+        )));
+
+        let stream = TcpStream::connect(server.address()).unwrap();
+        let url = format!("ws://{}", server.address());
+        let (mut socket, _) = tungstenite::client(url, stream).unwrap();
+
+        let replayed = socket.read().unwrap();
+        assert_eq!(
+            web_view::parse_ui_message(replayed.to_text().unwrap()).unwrap(),
+            Event::UpdatedControls(vec![])
+        );
+
+        socket
+            .send(Message::text(r#"{"SnapshotRecall":"1"}"#))
+            .unwrap();
+        assert_eq!(
+            command_rx.recv_timeout(Duration::from_secs(2)).unwrap(),
+            RuntimeEvent::SnapshotRecall("1".to_string())
+        );
+    }
+}
@@ -1,20 +1,27 @@
 use serde::{Deserialize, Serialize};
 
+use super::adaptive_quality::AdaptiveQualitySettings;
 use super::web_view::Mappings;
 use crate::control::control_hub::Snapshots;
 use crate::control::*;
 use crate::core::util::HashMap;
 use crate::motion::TimingSource;
+use crate::runtime::recorder::RecordingOptions;
+use crate::runtime::recording::PreRoll;
+use crate::runtime::stills::{StillFormat, TimelapseOptions};
 use crate::runtime::storage;
 use log::error;
 
 pub const GLOBAL_SETTINGS_VERSION: &str = "1";
 const DEFAULT_OSC_PORT: u16 = 2346;
+const DEFAULT_NDI_SENDER_NAME: &str = "Xtal";
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct GlobalSettings {
     pub version: String,
+    /// Lower the render scale automatically when fps drops below target
+    pub adaptive_quality: AdaptiveQualitySettings,
     pub audio_device_name: String,
     pub hrcc: bool,
     pub images_dir: String,
@@ -22,7 +29,24 @@ pub struct GlobalSettings {
     pub midi_clock_port: String,
     pub midi_control_in_port: String,
     pub midi_control_out_port: String,
+    pub midi_feedback_enabled: bool,
+    /// Source name of the NDI output (requires the `ndi` feature); empty
+    /// disables it
+    pub ndi_sender_name: String,
+    pub offline_recording_seconds: f32,
     pub osc_port: u16,
+    pub osc_prefix: String,
+    /// One-pole smoothing applied to OSC transport beats; `0.0` disables it
+    pub osc_transport_smoothing: f32,
+    pub perf_mode_hides_cursor: bool,
+    pub record_audio: bool,
+    pub recording_options: RecordingOptions,
+    pub recording_pre_roll: PreRoll,
+    /// Bind address of the WebSocket remote control server (requires the
+    /// `remote-control` feature), e.g. `0.0.0.0:2347`; empty disables it
+    pub remote_control_address: String,
+    pub still_format: StillFormat,
+    pub timelapse_options: TimelapseOptions,
     pub transition_time: f32,
     pub user_data_dir: String,
     pub videos_dir: String,
@@ -32,6 +56,7 @@ impl Default for GlobalSettings {
     fn default() -> Self {
         Self {
             version: GLOBAL_SETTINGS_VERSION.to_string(),
+            adaptive_quality: AdaptiveQualitySettings::default(),
             audio_device_name: String::new(),
             hrcc: false,
             images_dir: storage::default_images_dir(),
@@ -39,7 +64,19 @@ impl Default for GlobalSettings {
             midi_clock_port: String::new(),
             midi_control_in_port: String::new(),
             midi_control_out_port: String::new(),
+            midi_feedback_enabled: false,
+            ndi_sender_name: DEFAULT_NDI_SENDER_NAME.to_string(),
+            offline_recording_seconds: 0.0,
             osc_port: DEFAULT_OSC_PORT,
+            osc_prefix: String::new(),
+            osc_transport_smoothing: 0.0,
+            perf_mode_hides_cursor: true,
+            record_audio: false,
+            recording_options: RecordingOptions::default(),
+            recording_pre_roll: PreRoll::default(),
+            remote_control_address: String::new(),
+            still_format: StillFormat::default(),
+            timelapse_options: TimelapseOptions::default(),
             transition_time: 4.0,
             user_data_dir: storage::default_user_data_dir(),
             videos_dir: storage::default_videos_dir(),
@@ -52,10 +89,7 @@ pub const PROGRAM_STATE_VERSION: &str = "2";
 #[derive(Deserialize, Serialize)]
 pub struct SerializableSketchState {
     pub version: String,
-
-    #[serde(rename = "ui_controls", alias = "controls")]
     pub ui_controls: Vec<ControlConfig>,
-
     pub midi_controls: Vec<BasicNameValueConfig>,
     pub osc_controls: Vec<BasicNameValueConfig>,
 
@@ -171,6 +205,12 @@ mod control_value_format {
         if let Some(b) = value.as_bool() {
             return serializer.serialize_bool(b);
         }
+        if let Some(v) = value.as_vec2() {
+            return v.serialize(serializer);
+        }
+        if let Some(c) = value.as_color() {
+            return c.serialize(serializer);
+        }
 
         serializer.serialize_f32(0.0)
     }
@@ -187,6 +227,8 @@ mod control_value_format {
             Float(f32),
             String(String),
             Bool(bool),
+            Vec2([f32; 2]),
+            Color([f32; 4]),
         }
 
         let value = Value::deserialize(deserializer)?;
@@ -194,13 +236,14 @@ mod control_value_format {
             Value::Float(f) => Ok(ControlValue::from(f)),
             Value::String(s) => Ok(ControlValue::from(s)),
             Value::Bool(b) => Ok(ControlValue::from(b)),
+            Value::Vec2(v) => Ok(ControlValue::from(v)),
+            Value::Color(c) => Ok(ControlValue::from(c)),
         }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SerializableSnapshot {
-    #[serde(rename = "ui_controls", alias = "controls")]
     pub ui_controls: Vec<ControlConfig>,
     pub midi_controls: Vec<BasicNameValueConfig>,
     pub osc_controls: Vec<BasicNameValueConfig>,
@@ -299,8 +342,13 @@ impl TransitorySketchState {
         self.merge_snapshots(serialized_state);
     }
 
+    // Param targets like `folder.symmetry` need the hub to infer a range, so
+    // the runtime resolves those after loading.
     fn setup_midi_mappings(&mut self) {
         self.mappings.iter().for_each(|(name, (ch, cc))| {
+            if map_mode::param_target(name).is_some() {
+                return;
+            }
             if let Some((min, max)) = self.ui_controls.slider_range(name) {
                 self.midi_override_configs.insert(
                     name.clone(),
@@ -314,6 +362,7 @@ impl TransitorySketchState {
                             .get(name)
                             .copied()
                             .unwrap_or(0.0),
+                        encoder: EncoderMode::Absolute,
                     },
                 );
             } else {
@@ -371,7 +420,13 @@ impl TransitorySketchState {
                 continue;
             }
 
-            if self.midi_override_configs.contains_key(&control.name) {
+            // Param targets don't have configs until the runtime resolves
+            // them, but their values still need restoring
+            let is_param_target = self.mappings.contains_key(&control.name)
+                && map_mode::param_target(&control.name).is_some();
+            if is_param_target
+                || self.midi_override_configs.contains_key(&control.name)
+            {
                 self.midi_overrides
                     .insert(control.name.clone(), control.value);
             }
@@ -418,3 +473,78 @@ impl TransitorySketchState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(value: ControlValue) -> ControlConfig {
+        ControlConfig {
+            kind: "slider".to_string(),
+            name: "a".to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn param_target_mappings_round_trip() {
+        let name = "folder.symmetry".to_string();
+        let mut state = TransitorySketchState::default();
+        state.mappings.insert(name.clone(), (0, 21));
+        state.midi_overrides.insert(name.clone(), 2.5);
+
+        let json =
+            serde_json::to_string(&SerializableSketchState::from(&state))
+                .unwrap();
+        let mut restored = TransitorySketchState::default();
+        restored.merge(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(restored.mappings.get(&name), Some(&(0, 21)));
+        assert_eq!(restored.midi_overrides.get(&name), Some(&2.5));
+    }
+
+    #[test]
+    fn control_values_round_trip() {
+        for value in [
+            ControlValue::Float(0.5),
+            ControlValue::Bool(true),
+            ControlValue::String("square".to_string()),
+            ControlValue::Vec2([0.25, -1.0]),
+            ControlValue::Color([1.0, 0.5, 0.0, 0.75]),
+        ] {
+            let json = serde_json::to_string(&config(value.clone())).unwrap();
+            let parsed: ControlConfig = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.value, value, "{}", json);
+        }
+    }
+
+    #[test]
+    fn control_values_are_stored_as_plain_json() {
+        let json =
+            serde_json::to_string(&config(ControlValue::Vec2([0.5, 1.0])))
+                .unwrap();
+        assert_eq!(json, r#"{"type":"slider","name":"a","value":[0.5,1.0]}"#);
+    }
+
+    #[test]
+    fn scalar_values_from_older_files_still_load() {
+        let parsed: Vec<ControlConfig> = serde_json::from_str(
+            r#"[
+                {"type":"slider","name":"a","value":1},
+                {"type":"checkbox","name":"b","value":false},
+                {"type":"select","name":"c","value":"sine"}
+            ]"#,
+        )
+        .unwrap();
+        let values: Vec<ControlValue> =
+            parsed.into_iter().map(|config| config.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                ControlValue::Float(1.0),
+                ControlValue::Bool(false),
+                ControlValue::String("sine".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Dev-only watcher for a sketch's Rust source. Rust changes can't be hot
+//! swapped, so the runtime only alerts when the source is edited and relaunches
+//! once a rebuilt binary is announced through a sentinel file the build
+//! touches (see `just rebuild`).
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{info, trace, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::shader_watch::path_matches_target;
+
+/// Name of the sentinel file, expected next to the running executable
+pub const BUILD_SENTINEL_FILE_NAME: &str = "xtal-rebuilt";
+
+// Editors save in several steps and `touch` may report both a create and a
+// metadata change; wait for the events to settle so each save reports once.
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SourceChange {
+    /// The sketch's source file was saved; a rebuild is needed to see it
+    SourceEdited,
+    /// The build touched the sentinel, so a new binary is ready
+    BinaryRebuilt,
+}
+
+type ChangedAt = Arc<Mutex<Option<Instant>>>;
+
+pub struct SourceWatch {
+    source_path: PathBuf,
+    executable: PathBuf,
+    source_changed_at: ChangedAt,
+    sentinel_changed_at: ChangedAt,
+    _watchers: Vec<RecommendedWatcher>,
+}
+
+impl SourceWatch {
+    pub fn start(
+        source: impl Into<PathBuf>,
+        sentinel: impl Into<PathBuf>,
+        executable: impl Into<PathBuf>,
+    ) -> Result<Self, notify::Error> {
+        let source_path = source.into();
+        let sentinel_path = sentinel.into();
+        let source_changed_at = ChangedAt::default();
+        let sentinel_changed_at = ChangedAt::default();
+
+        let watchers = vec![
+            watch_file(source_path.clone(), source_changed_at.clone())?,
+            watch_file(sentinel_path, sentinel_changed_at.clone())?,
+        ];
+
+        Ok(Self {
+            source_path,
+            executable: executable.into(),
+            source_changed_at,
+            sentinel_changed_at,
+            _watchers: watchers,
+        })
+    }
+
+    /// Watches `source` with the sentinel next to the running executable
+    pub fn for_current_exe(source: impl Into<PathBuf>) -> Result<Self, String> {
+        let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+        let Some(dir) = exe.parent() else {
+            return Err(format!("{} has no parent directory", exe.display()));
+        };
+        let sentinel = dir.join(BUILD_SENTINEL_FILE_NAME);
+        Self::start(source, sentinel, &exe).map_err(|err| err.to_string())
+    }
+
+    pub fn source_path(&self) -> &Path {
+        &self.source_path
+    }
+
+    /// Path of the executable to relaunch, resolved when the watch started.
+    /// Linux reports a replaced executable as deleted afterwards.
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// The change seen since the last call, once its events have settled. A
+    /// rebuilt binary takes precedence over a source edit seen at the same
+    /// time.
+    pub fn take_change(&self) -> Option<SourceChange> {
+        match take_settled(&self.sentinel_changed_at) {
+            Some(true) => {
+                take_settled(&self.source_changed_at);
+                return Some(SourceChange::BinaryRebuilt);
+            }
+            // Hold a source edit back until the rebuild settles
+            Some(false) => return None,
+            None => {}
+        }
+
+        (take_settled(&self.source_changed_at) == Some(true))
+            .then_some(SourceChange::SourceEdited)
+    }
+}
+
+// Watches `path` through its parent directory so editors that save by
+// replacing the file, and a sentinel that doesn't exist yet, are still seen.
+fn watch_file(
+    path: PathBuf,
+    changed_at: ChangedAt,
+) -> Result<RecommendedWatcher, notify::Error> {
+    let watch_dir = path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    info!(
+        "watching source file '{}' via directory '{}'",
+        path.display(),
+        watch_dir.display()
+    );
+
+    let mut watcher = notify::recommended_watcher(move |result| {
+        let event: Event = match result {
+            Ok(event) => event,
+            Err(err) => {
+                warn!(
+                    "source watcher failed for '{}': {}",
+                    path.display(),
+                    err
+                );
+                return;
+            }
+        };
+
+        if !file_changed(&event, &path) {
+            return;
+        }
+        trace!(
+            "source watcher event for '{}': {:?}",
+            path.display(),
+            event.kind
+        );
+
+        if let Ok(mut guard) = changed_at.lock() {
+            *guard = Some(Instant::now());
+        }
+    })?;
+
+    watcher.watch(&watch_dir, RecursiveMode::NonRecursive)?;
+
+    Ok(watcher)
+}
+
+// `None` without a pending change, otherwise whether it has settled. A settled
+// change is cleared.
+fn take_settled(changed_at: &ChangedAt) -> Option<bool> {
+    let mut guard = changed_at.lock().ok()?;
+    let settled = guard.as_ref()?.elapsed() >= CHANGE_DEBOUNCE;
+    if settled {
+        *guard = None;
+    }
+    Some(settled)
+}
+
+// Unlike shader and script reloads, a deleted file is not a change: there is
+// nothing to rebuild from.
+fn file_changed(event: &Event, target: &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| path_matches_target(path, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("xtal-source-watch-tests")
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn wait_for_change(watch: &SourceWatch) -> Option<SourceChange> {
+        let start = Instant::now();
+        while start.elapsed() < TIMEOUT {
+            if let Some(change) = watch.take_change() {
+                return Some(change);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn reports_each_source_edit_once() {
+        let dir = temp_dir("edit");
+        let source = dir.join("sketch.rs");
+        fs::write(&source, "// v1").expect("write source");
+        let watch =
+            SourceWatch::start(&source, dir.join("sentinel"), "xtal").unwrap();
+
+        assert_eq!(watch.take_change(), None);
+
+        fs::write(&source, "// v2").expect("write source");
+        assert_eq!(wait_for_change(&watch), Some(SourceChange::SourceEdited));
+        assert_eq!(watch.take_change(), None);
+    }
+
+    #[test]
+    fn rebuild_takes_precedence_over_edit() {
+        let dir = temp_dir("rebuild");
+        let source = dir.join("sketch.rs");
+        let sentinel = dir.join("sentinel");
+        fs::write(&source, "// v1").expect("write source");
+        let watch = SourceWatch::start(&source, &sentinel, "xtal").unwrap();
+
+        fs::write(&source, "// v2").expect("write source");
+        fs::write(&sentinel, "").expect("create sentinel");
+        assert_eq!(wait_for_change(&watch), Some(SourceChange::BinaryRebuilt));
+    }
+
+    #[test]
+    fn ignores_other_files_in_the_directory() {
+        let dir = temp_dir("other");
+        let source = dir.join("sketch.rs");
+        fs::write(&source, "// v1").expect("write source");
+        let watch =
+            SourceWatch::start(&source, dir.join("sentinel"), "xtal").unwrap();
+
+        fs::write(dir.join("other.rs"), "// other").expect("write other");
+        thread::sleep(CHANGE_DEBOUNCE * 2);
+        assert_eq!(watch.take_change(), None);
+    }
+}
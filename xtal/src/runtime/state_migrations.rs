@@ -0,0 +1,169 @@
+//! Schema migrations for per-sketch state files. A saved file carries the
+//! [`PROGRAM_STATE_VERSION`] it was written with; older files are upgraded
+//! one version at a time before being deserialized, so schema changes don't
+//! drop a sketch's saved values and snapshots.
+
+use serde_json::{Map, Value};
+
+use super::serialization::PROGRAM_STATE_VERSION;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Each entry upgrades a document from the keyed version to the next one.
+/// Bumping [`PROGRAM_STATE_VERSION`] requires registering a migration here.
+const MIGRATIONS: &[(u32, Migration)] = &[(1, rename_controls_to_ui_controls)];
+
+pub fn current_version() -> u32 {
+    PROGRAM_STATE_VERSION
+        .parse()
+        .expect("PROGRAM_STATE_VERSION must be an integer")
+}
+
+/// Upgrade a parsed sketch state document to the current schema. Files that
+/// predate the `version` field are treated as version 1.
+pub fn migrate(mut document: Value) -> Result<Value, String> {
+    let Some(object) = document.as_object_mut() else {
+        return Err("sketch state must be a JSON object".to_string());
+    };
+
+    let current = current_version();
+    let mut version = stored_version(object)?;
+    if version > current {
+        return Err(format!(
+            "sketch state version {} is newer than supported version {}",
+            version, current
+        ));
+    }
+
+    while version < current {
+        let Some((_, migration)) =
+            MIGRATIONS.iter().find(|(from, _)| *from == version)
+        else {
+            return Err(format!(
+                "no migration from sketch state version {}",
+                version
+            ));
+        };
+        migration(object).map_err(|err| {
+            format!("failed to migrate sketch state v{}: {}", version, err)
+        })?;
+        version += 1;
+    }
+
+    object.insert("version".to_string(), Value::from(current.to_string()));
+    Ok(document)
+}
+
+// Versions are written as strings, but a bare number is unambiguous enough
+// to accept as well
+fn stored_version(object: &Map<String, Value>) -> Result<u32, String> {
+    match object.get("version") {
+        None => Ok(1),
+        Some(Value::String(version)) => version
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid sketch state version '{}'", version)),
+        Some(Value::Number(version)) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("invalid sketch state version {}", version)),
+        Some(other) => Err(format!("invalid sketch state version {}", other)),
+    }
+}
+
+// v1 stored UI control values under `controls`, both at the top level and in
+// each snapshot.
+fn rename_controls_to_ui_controls(
+    object: &mut Map<String, Value>,
+) -> Result<(), String> {
+    rename_key(object, "controls", "ui_controls");
+
+    let Some(snapshots) = object.get_mut("snapshots") else {
+        return Ok(());
+    };
+    let Some(snapshots) = snapshots.as_object_mut() else {
+        return Err("`snapshots` must be an object".to_string());
+    };
+    for (id, snapshot) in snapshots.iter_mut() {
+        let Some(snapshot) = snapshot.as_object_mut() else {
+            return Err(format!("snapshot '{}' must be an object", id));
+        };
+        rename_key(snapshot, "controls", "ui_controls");
+    }
+
+    Ok(())
+}
+
+// Keeps an existing `to` entry, which can only come from a newer writer
+fn rename_key(object: &mut Map<String, Value>, from: &str, to: &str) {
+    if let Some(value) = object.remove(from) {
+        object.entry(to).or_insert(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::serialization::SerializableSketchState;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_covers_every_version() {
+        for version in 1..current_version() {
+            assert!(
+                MIGRATIONS.iter().any(|(from, _)| *from == version),
+                "missing migration from v{}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn test_migrates_v1_fixture_to_current_schema() {
+        let v1 = json!({
+            "version": "1",
+            "controls": [
+                { "type": "Slider", "name": "size", "value": 0.25 },
+                { "type": "Checkbox", "name": "invert", "value": true }
+            ],
+            "midi_controls": [{ "name": "cutoff", "value": 0.5 }],
+            "osc_controls": [],
+            "snapshots": {
+                "1": {
+                    "controls": [
+                        { "type": "Select", "name": "mode", "value": "b" }
+                    ],
+                    "midi_controls": [],
+                    "osc_controls": []
+                }
+            }
+        });
+
+        let migrated = migrate(v1).unwrap();
+        assert_eq!(migrated["version"], json!(PROGRAM_STATE_VERSION));
+        assert!(migrated.get("controls").is_none());
+
+        let state: SerializableSketchState =
+            serde_json::from_value(migrated).unwrap();
+        assert_eq!(state.ui_controls.len(), 2);
+        assert_eq!(state.ui_controls[0].name, "size");
+        assert_eq!(state.ui_controls[0].value.as_float(), Some(0.25));
+        assert_eq!(state.midi_controls[0].name, "cutoff");
+
+        let snapshot = &state.snapshots["1"];
+        assert_eq!(snapshot.ui_controls[0].value.as_string(), Some("b"));
+    }
+
+    #[test]
+    fn test_unversioned_files_are_v1() {
+        let migrated = migrate(json!({ "controls": [] })).unwrap();
+        assert_eq!(migrated["ui_controls"], json!([]));
+        assert_eq!(migrated["version"], json!(PROGRAM_STATE_VERSION));
+    }
+
+    #[test]
+    fn test_rejects_newer_versions() {
+        let newer = (current_version() + 1).to_string();
+        assert!(migrate(json!({ "version": newer })).is_err());
+    }
+}
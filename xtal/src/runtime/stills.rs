@@ -0,0 +1,623 @@
+//! Still image output: readback and encoding shared by single frame captures,
+//! the recorder and timelapses, plus the scheduler deciding which frames a
+//! timelapse keeps.
+
+use exr::prelude::{Encoding, Image, SpecificChannels, Vec2, WritableImage};
+use half::f16;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+use crate::gpu::compute_row_padding;
+use crate::ternary;
+
+/// File format and bit depth of saved stills
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum StillFormat {
+    #[default]
+    Png8,
+    Png16,
+    /// Half-float OpenEXR in linear color, losslessly RLE compressed
+    Exr,
+}
+
+impl StillFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png8 | Self::Png16 => "png",
+            Self::Exr => "exr",
+        }
+    }
+}
+
+/// Size of one pixel of the texture formats stills can be read from
+pub fn bytes_per_pixel(format: wgpu::TextureFormat) -> Option<u32> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm
+        | wgpu::TextureFormat::Rgba8UnormSrgb
+        | wgpu::TextureFormat::Bgra8Unorm
+        | wgpu::TextureFormat::Bgra8UnormSrgb => Some(4),
+        wgpu::TextureFormat::Rgba16Float => Some(8),
+        wgpu::TextureFormat::Rgba32Float => Some(16),
+        _ => None,
+    }
+}
+
+/// Tightly packed pixels in the layout of the texture they were read from
+#[derive(Clone, Debug)]
+pub struct Still {
+    pub pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub format: wgpu::TextureFormat,
+}
+
+impl Still {
+    /// Encode as `still_format` at `path`. PNGs receive the stored values
+    /// as-is (clamped to 0..1 for float sources); EXRs are linear, so
+    /// sRGB-encoded sources are decoded first.
+    pub fn write(
+        &self,
+        path: &Path,
+        still_format: StillFormat,
+    ) -> Result<(), String> {
+        bytes_per_pixel(self.format).ok_or_else(|| {
+            format!("unsupported capture source format: {:?}", self.format)
+        })?;
+
+        let file = fs::File::create(path).map_err(|err| {
+            format!("failed to create '{}': {}", path.display(), err)
+        })?;
+        let mut writer = std::io::BufWriter::new(file);
+        match still_format {
+            StillFormat::Png8 => write_png(
+                &mut writer,
+                self.width,
+                self.height,
+                png::BitDepth::Eight,
+                &self.rgba8(),
+            )?,
+            StillFormat::Png16 => write_png(
+                &mut writer,
+                self.width,
+                self.height,
+                png::BitDepth::Sixteen,
+                &self.rgba16(),
+            )?,
+            StillFormat::Exr => write_exr(
+                &mut writer,
+                self.width,
+                self.height,
+                &self.rgba_f16(),
+            )?,
+        }
+        writer
+            .flush()
+            .map_err(|err| format!("image flush failed: {}", err))?;
+
+        Ok(())
+    }
+
+    fn is_8_bit(&self) -> bool {
+        bytes_per_pixel(self.format) == Some(4)
+    }
+
+    fn is_bgra(&self) -> bool {
+        matches!(
+            self.format,
+            wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        )
+    }
+
+    fn rgba8(&self) -> Vec<u8> {
+        if !self.is_8_bit() {
+            return self
+                .rgba_f32()
+                .into_iter()
+                .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+                .collect();
+        }
+
+        let mut pixels = self.pixels.clone();
+        if self.is_bgra() {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+        pixels
+    }
+
+    /// Big-endian 16-bit samples as PNG expects them
+    fn rgba16(&self) -> Vec<u8> {
+        let samples: Vec<u16> = if self.is_8_bit() {
+            self.rgba8()
+                .into_iter()
+                .map(|value| value as u16 * 257)
+                .collect()
+        } else {
+            self.rgba_f32()
+                .into_iter()
+                .map(|value| (value.clamp(0.0, 1.0) * 65535.0).round() as u16)
+                .collect()
+        };
+        samples.into_iter().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn rgba_f16(&self) -> Vec<f16> {
+        if self.format == wgpu::TextureFormat::Rgba16Float {
+            return self
+                .pixels
+                .chunks_exact(2)
+                .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]))
+                .collect();
+        }
+
+        let srgb = self.format.is_srgb();
+        self.rgba_f32()
+            .chunks_exact(4)
+            .flat_map(|px| {
+                let [r, g, b, a] = [px[0], px[1], px[2], px[3]];
+                let decode =
+                    |value| ternary!(srgb, srgb_to_linear(value), value);
+                [decode(r), decode(g), decode(b), a]
+            })
+            .map(f16::from_f32)
+            .collect()
+    }
+
+    /// Stored values as RGBA floats, without any color decoding
+    pub fn rgba_f32(&self) -> Vec<f32> {
+        match self.format {
+            wgpu::TextureFormat::Rgba16Float => self
+                .pixels
+                .chunks_exact(2)
+                .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]).to_f32())
+                .collect(),
+            wgpu::TextureFormat::Rgba32Float => self
+                .pixels
+                .chunks_exact(4)
+                .map(|bytes| {
+                    f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                })
+                .collect(),
+            _ => self
+                .rgba8()
+                .into_iter()
+                .map(|value| value as f32 / 255.0)
+                .collect(),
+        }
+    }
+}
+
+/// A frame copied from a texture into a mappable buffer. Record the copy
+/// with [`StillReadback::copy`], submit the encoder, then
+/// [`read`](StillReadback::read) the pixels back.
+pub struct StillReadback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    format: wgpu::TextureFormat,
+}
+
+impl StillReadback {
+    pub fn copy(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, String> {
+        let bytes_per_pixel = bytes_per_pixel(format).ok_or_else(|| {
+            format!("unsupported capture source format: {:?}", format)
+        })?;
+        let width = texture.size().width.max(1);
+        let height = texture.size().height.max(1);
+        let unpadded_bytes_per_row = width * bytes_per_pixel;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            + compute_row_padding(unpadded_bytes_per_row);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("xtal-capture-readback"),
+            size: (padded_bytes_per_row as u64) * (height as u64),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Ok(Self {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            format,
+        })
+    }
+
+    /// Block until `submission_index` (which must include the copy) is done
+    /// and return the pixels without row padding
+    pub fn read(
+        self,
+        device: &wgpu::Device,
+        submission_index: wgpu::SubmissionIndex,
+    ) -> Result<Still, String> {
+        let slice = self.buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        let _ = device
+            .poll(wgpu::PollType::WaitForSubmissionIndex(submission_index));
+        let map_result = rx
+            .recv()
+            .map_err(|err| format!("map channel recv failed: {}", err))?;
+        map_result.map_err(|err| format!("map failed: {:?}", err))?;
+
+        let data = slice.get_mapped_range();
+        let unpadded_bytes_per_row =
+            (self.width * bytes_per_pixel(self.format).unwrap_or(4)) as usize;
+        let padded_bytes_per_row = self.padded_bytes_per_row as usize;
+        let mut pixels =
+            vec![0u8; unpadded_bytes_per_row * (self.height as usize)];
+
+        for row in 0..(self.height as usize) {
+            let src_start = row * padded_bytes_per_row;
+            let src_end = src_start + unpadded_bytes_per_row;
+            let dst_start = row * unpadded_bytes_per_row;
+            let dst_end = dst_start + unpadded_bytes_per_row;
+            pixels[dst_start..dst_end]
+                .copy_from_slice(&data[src_start..src_end]);
+        }
+
+        drop(data);
+        self.buffer.unmap();
+
+        Ok(Still {
+            pixels,
+            width: self.width,
+            height: self.height,
+            format: self.format,
+        })
+    }
+}
+
+fn write_png(
+    writer: &mut impl Write,
+    width: u32,
+    height: u32,
+    bit_depth: png::BitDepth,
+    samples: &[u8],
+) -> Result<(), String> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(bit_depth);
+    encoder.set_compression(png::Compression::Fast);
+    encoder.set_filter(png::Filter::Sub);
+    let mut png_writer = encoder
+        .write_header()
+        .map_err(|err| format!("png header failed: {}", err))?;
+    png_writer
+        .write_image_data(samples)
+        .map_err(|err| format!("png write failed: {}", err))
+}
+
+fn write_exr(
+    writer: &mut (impl Write + Seek),
+    width: u32,
+    height: u32,
+    pixels: &[f16],
+) -> Result<(), String> {
+    let width = width as usize;
+    let channels = SpecificChannels::rgba(|Vec2(x, y)| {
+        let i = (y * width + x) * 4;
+        (pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3])
+    });
+    Image::from_encoded_channels(
+        (width, height as usize),
+        Encoding::FAST_LOSSLESS,
+        channels,
+    )
+    .write()
+    .to_buffered(writer)
+    .map_err(|err| format!("exr write failed: {}", err))
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum TimelapseInterval {
+    Frames(u32),
+    Beats(f32),
+}
+
+impl Default for TimelapseInterval {
+    fn default() -> Self {
+        Self::Frames(60)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct TimelapseOptions {
+    pub interval: TimelapseInterval,
+    /// File name without extension. `{sketch}` and `{session}` are replaced
+    /// with the sketch name and a per-timelapse id, `{n}` with the zero-padded
+    /// image number.
+    pub pattern: String,
+}
+
+impl Default for TimelapseOptions {
+    fn default() -> Self {
+        Self {
+            interval: TimelapseInterval::default(),
+            pattern: "{sketch}-{session}-{n}".to_string(),
+        }
+    }
+}
+
+/// Decides which frames of a running timelapse are saved and where
+#[derive(Debug)]
+pub struct Timelapse {
+    interval: TimelapseInterval,
+    dir: PathBuf,
+    pattern: String,
+    next: Option<f64>,
+    last: f64,
+    count: u32,
+}
+
+impl Timelapse {
+    pub fn new(
+        options: &TimelapseOptions,
+        dir: &Path,
+        sketch_name: &str,
+        session_id: &str,
+    ) -> Self {
+        let pattern = ternary!(
+            options.pattern.contains("{n}"),
+            options.pattern.clone(),
+            format!("{}-{{n}}", options.pattern)
+        );
+        Self {
+            interval: options.interval,
+            dir: dir.to_path_buf(),
+            pattern: pattern
+                .replace("{sketch}", sketch_name)
+                .replace("{session}", session_id),
+            next: None,
+            last: 0.0,
+            count: 0,
+        }
+    }
+
+    /// Returns the path to save this frame to when a still is due. The first
+    /// call always fires; later stills land on the interval grid from there,
+    /// firing once for any number of missed slots. A transport that jumps
+    /// backwards (a reset or MIDI start) restarts the grid.
+    pub fn tick(&mut self, frame: u32, beats: f32) -> Option<PathBuf> {
+        let (position, step) = match self.interval {
+            TimelapseInterval::Frames(frames) => {
+                (frame as f64, frames.max(1) as f64)
+            }
+            TimelapseInterval::Beats(interval) => {
+                (beats as f64, (interval as f64).max(f64::EPSILON))
+            }
+        };
+        if position < self.last {
+            self.next = None;
+        }
+        self.last = position;
+
+        let next = self.next.unwrap_or(position);
+        if position < next {
+            return None;
+        }
+        let missed = ((position - next) / step).floor();
+        self.next = Some(next + (missed + 1.0) * step);
+
+        let path = self.dir.join(format!(
+            "{}.png",
+            self.pattern.replace("{n}", &format!("{:05}", self.count))
+        ));
+        self.count += 1;
+        Some(path)
+    }
+
+    /// Number of stills scheduled so far
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn half_still(values: &[f32]) -> Still {
+        Still {
+            pixels: values
+                .iter()
+                .flat_map(|value| f16::from_f32(*value).to_le_bytes())
+                .collect(),
+            width: (values.len() / 4) as u32,
+            height: 1,
+            format: wgpu::TextureFormat::Rgba16Float,
+        }
+    }
+
+    #[test]
+    fn swizzles_bgra_but_not_float_sources() {
+        let bgra = Still {
+            pixels: vec![10, 20, 30, 255],
+            width: 1,
+            height: 1,
+            format: wgpu::TextureFormat::Bgra8Unorm,
+        };
+        assert_eq!(bgra.rgba8(), [30, 20, 10, 255]);
+        assert_eq!(bgra.rgba16(), [30, 30, 20, 20, 10, 10, 255, 255]);
+
+        let float = half_still(&[1.0, 0.5, 2.0, 1.0]);
+        assert_eq!(float.rgba8(), [255, 128, 255, 255]);
+        assert_eq!(
+            float.rgba16(),
+            [0xff, 0xff, 0x80, 0x00, 0xff, 0xff, 0xff, 0xff]
+        );
+        // EXR keeps values above 1
+        assert_eq!(
+            float.rgba_f16(),
+            [1.0, 0.5, 2.0, 1.0].map(f16::from_f32).to_vec()
+        );
+    }
+
+    #[test]
+    fn linearizes_srgb_sources_for_exr() {
+        let srgb = Still {
+            pixels: vec![255, 188, 0, 128],
+            width: 1,
+            height: 1,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        };
+        let linear: Vec<f32> =
+            srgb.rgba_f16().into_iter().map(f16::to_f32).collect();
+        assert_eq!(linear[0], 1.0);
+        assert!((linear[1] - 0.5).abs() < 0.01);
+        assert_eq!(linear[2], 0.0);
+        // Alpha is never encoded
+        assert!((linear[3] - 128.0 / 255.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn writes_half_exr_that_reads_back() {
+        let (width, height) = (2, 3);
+        let values: Vec<f32> =
+            (0..width * height * 4).map(|i| i as f32 * 0.5).collect();
+        let still = Still {
+            pixels: bytemuck::cast_slice(&values).to_vec(),
+            width,
+            height,
+            format: wgpu::TextureFormat::Rgba32Float,
+        };
+        let path = std::env::temp_dir().join("xtal-stills-test.exr");
+        still.write(&path, StillFormat::Exr).unwrap();
+
+        let image = exr::prelude::read_first_rgba_layer_from_file(
+            &path,
+            |resolution, _| vec![[0.0; 4]; resolution.area()],
+            move |pixels: &mut Vec<[f32; 4]>,
+                  Vec2(x, y),
+                  (r, g, b, a): (f32, f32, f32, f32)| {
+                pixels[y * width as usize + x] = [r, g, b, a];
+            },
+        )
+        .unwrap();
+        let _ = fs::remove_file(&path);
+
+        let layer = &image.layer_data;
+        assert_eq!(layer.size, Vec2(2, 3));
+        assert_eq!(layer.channel_data.pixels.concat(), values);
+        let red = &layer.channel_data.channels.0;
+        assert_eq!(red.sample_type, exr::prelude::SampleType::F16);
+        assert_eq!(layer.encoding.compression, exr::prelude::Compression::RLE);
+    }
+
+    fn timelapse(interval: TimelapseInterval) -> Timelapse {
+        Timelapse::new(
+            &TimelapseOptions {
+                interval,
+                pattern: "{sketch}-{session}-{n}".to_string(),
+            },
+            Path::new("images"),
+            "sketch",
+            "abc",
+        )
+    }
+
+    fn fired_frames(
+        timelapse: &mut Timelapse,
+        frames: impl IntoIterator<Item = u32>,
+    ) -> Vec<u32> {
+        frames
+            .into_iter()
+            .filter(|frame| timelapse.tick(*frame, 0.0).is_some())
+            .collect()
+    }
+
+    #[test]
+    fn fires_every_n_frames() {
+        let mut timelapse = timelapse(TimelapseInterval::Frames(3));
+        assert_eq!(fired_frames(&mut timelapse, 10..20), [10, 13, 16, 19]);
+        assert_eq!(timelapse.count(), 4);
+
+        // Skipped frames fire once and stay on the grid
+        assert_eq!(fired_frames(&mut timelapse, [27, 28, 30]), [27, 28]);
+    }
+
+    #[test]
+    fn fires_every_n_beats_and_restarts_after_reset() {
+        let mut timelapse = timelapse(TimelapseInterval::Beats(1.0));
+        let fired: Vec<f32> = [0.0, 0.4, 0.9, 1.0, 1.5, 2.1, 2.5, 0.0, 0.5]
+            .into_iter()
+            .filter(|beats| timelapse.tick(0, *beats).is_some())
+            .collect();
+        assert_eq!(fired, [0.0, 1.0, 2.1, 0.0]);
+    }
+
+    #[test]
+    fn numbers_stills_with_the_pattern() {
+        let mut timelapse = timelapse(TimelapseInterval::Frames(1));
+        assert_eq!(
+            timelapse.tick(0, 0.0),
+            Some(PathBuf::from("images/sketch-abc-00000.png"))
+        );
+        assert_eq!(
+            timelapse.tick(1, 0.0),
+            Some(PathBuf::from("images/sketch-abc-00001.png"))
+        );
+
+        let mut unnumbered = Timelapse::new(
+            &TimelapseOptions {
+                interval: TimelapseInterval::Frames(1),
+                pattern: "still".to_string(),
+            },
+            Path::new("images"),
+            "sketch",
+            "abc",
+        );
+        assert_eq!(
+            unnumbered.tick(0, 0.0),
+            Some(PathBuf::from("images/still-00000.png"))
+        );
+    }
+}
@@ -1,15 +1,17 @@
 use std::error::Error;
-use std::fs;
-use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::str;
 
 use directories_next::{BaseDirs, UserDirs};
+use log::warn;
 use serde::{Deserialize, Serialize};
 
 use super::serialization::{
     GlobalSettings, SerializableSketchState, TransitorySketchState,
 };
+use super::state_migrations;
 use super::web_view::Mappings;
 use crate::control::ControlHub;
 use crate::control::Exclusions;
@@ -52,6 +54,94 @@ fn user_dir(
         .into_owned()
 }
 
+/// Path of the copy of `path` kept by [`write_atomic`] before replacing it
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling_path(path, "bak")
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    sibling_path(path, "tmp")
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `contents` without ever leaving a partially written
+/// file behind: the data goes to a temp file that is synced and then renamed
+/// over the target. The previous file is kept as a `.bak` (see
+/// [`restore_backup`]).
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    if let Some(parent_dir) = path.parent() {
+        fs::create_dir_all(parent_dir)?;
+    }
+
+    let temp = temp_path(path);
+    let result = File::create(&temp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(err);
+    }
+
+    if path.exists() {
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&temp, path)
+}
+
+/// Put the `.bak` kept by [`write_atomic`] back in place of `path`. The
+/// replaced file becomes the new backup, so restoring twice undoes it.
+pub fn restore_backup(path: &Path) -> io::Result<()> {
+    let backup = fs::read(backup_path(path))?;
+    write_atomic_with(path, |file| file.write_all(&backup))
+}
+
+// Parses `path`, falling back to its `.bak` if the file itself is unreadable
+// or corrupt, e.g. written by a version from before writes were atomic.
+fn read_json_with_backup<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, Box<dyn Error>>,
+) -> Result<T, Box<dyn Error>> {
+    let read = |path: &Path| -> Result<T, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        parse(&json)
+    };
+
+    match read(path) {
+        Ok(value) => Ok(value),
+        Err(err) if is_not_found(&*err) => Err(err),
+        Err(err) => match read(&backup_path(path)) {
+            Ok(value) => {
+                warn!(
+                    "Failed to load {} ({}); using its backup",
+                    path.display(),
+                    err
+                );
+                Ok(value)
+            }
+            Err(_) => Err(err),
+        },
+    }
+}
+
+fn is_not_found(err: &(dyn Error + 'static)) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == ErrorKind::NotFound)
+}
+
 fn global_state_storage_path(storage_dir: &str) -> PathBuf {
     PathBuf::from(storage_dir).join("global_settings.json")
 }
@@ -62,10 +152,7 @@ pub fn save_global_state(
 ) -> Result<(), Box<dyn Error>> {
     let json = serde_json::to_string_pretty(&state)?;
     let path = global_state_storage_path(storage_dir);
-    if let Some(parent_dir) = path.parent() {
-        fs::create_dir_all(parent_dir)?;
-    }
-    fs::write(&path, json)?;
+    write_atomic(&path, json.as_bytes())?;
     Ok(())
 }
 
@@ -73,10 +160,9 @@ pub fn load_global_state(
     storage_dir: &str,
 ) -> Result<GlobalSettings, Box<dyn Error>> {
     let path = global_state_storage_path(storage_dir);
-    let bytes = fs::read(path)?;
-    let json = str::from_utf8(&bytes).ok().map(|s| s.to_owned()).unwrap();
-    let settings = serde_json::from_str::<GlobalSettings>(&json)?;
-    Ok(settings)
+    read_json_with_backup(&path, |json| {
+        Ok(serde_json::from_str::<GlobalSettings>(json)?)
+    })
 }
 
 pub fn load_global_state_if_exists(
@@ -85,10 +171,7 @@ pub fn load_global_state_if_exists(
     match load_global_state(storage_dir) {
         Ok(settings) => Ok(Some(settings)),
         Err(err) => {
-            if err
-                .downcast_ref::<std::io::Error>()
-                .is_some_and(|e| e.kind() == ErrorKind::NotFound)
-            {
+            if is_not_found(&*err) {
                 Ok(None)
             } else {
                 Err(err)
@@ -118,10 +201,7 @@ pub fn save_sketch_state<T: TimingSource + std::fmt::Debug + 'static>(
 
     let json = serde_json::to_string_pretty(&serializable_controls)?;
     let path = sketch_state_storage_path(user_data_dir, sketch_name);
-    if let Some(parent_dir) = path.parent() {
-        fs::create_dir_all(parent_dir)?;
-    }
-    fs::write(&path, json)?;
+    write_atomic(&path, json.as_bytes())?;
     Ok(path)
 }
 
@@ -131,14 +211,75 @@ pub fn load_sketch_state<'a>(
     state: &'a mut TransitorySketchState,
 ) -> Result<&'a mut TransitorySketchState, Box<dyn Error>> {
     let path = sketch_state_storage_path(user_data_dir, sketch_name);
-    let bytes = fs::read(path)?;
-    let json = str::from_utf8(&bytes).ok().map(|s| s.to_owned()).unwrap();
-
-    let serialized = serde_json::from_str::<SerializableSketchState>(&json)?;
+    let serialized = read_json_with_backup(&path, |json| {
+        let document = serde_json::from_str(json)?;
+        let document = state_migrations::migrate(document)?;
+        Ok(serde_json::from_value::<SerializableSketchState>(document)?)
+    })?;
     state.merge(serialized);
     Ok(state)
 }
 
+// -----------------------------------------------------------------------------
+// Sketch State Export
+// -----------------------------------------------------------------------------
+
+const SKETCH_STATE_EXPORT_FORMAT: &str = "xtal-sketch-state";
+
+/// A sketch's saved state in a single shareable file: the same document
+/// [`save_sketch_state`] writes, tagged with the sketch it belongs to
+#[derive(Deserialize, Serialize)]
+struct SketchStateExport {
+    format: String,
+    sketch_name: String,
+    state: serde_json::Value,
+}
+
+/// Bundle the hub's control values, snapshots, mappings and exclusions into
+/// one JSON file at `path`
+pub fn export_sketch_state<T: TimingSource + std::fmt::Debug + 'static>(
+    path: &Path,
+    sketch_name: &str,
+    hub: &ControlHub<T>,
+    mappings: Mappings,
+    exclusions: Exclusions,
+) -> Result<(), Box<dyn Error>> {
+    let state = TransitorySketchState::from_hub(hub, mappings, exclusions);
+    let export = SketchStateExport {
+        format: SKETCH_STATE_EXPORT_FORMAT.to_string(),
+        sketch_name: sketch_name.to_string(),
+        state: serde_json::to_value(SerializableSketchState::from(&state))?,
+    };
+    let json = serde_json::to_string_pretty(&export)?;
+    write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Merge a file written by [`export_sketch_state`] into `state`. Fails if it
+/// was exported from a different sketch or by a newer, unsupported version.
+pub fn import_sketch_state<'a>(
+    path: &Path,
+    sketch_name: &str,
+    state: &'a mut TransitorySketchState,
+) -> Result<&'a mut TransitorySketchState, Box<dyn Error>> {
+    let json = fs::read_to_string(path)?;
+    let export = serde_json::from_str::<SketchStateExport>(&json)?;
+    if export.format != SKETCH_STATE_EXPORT_FORMAT {
+        return Err(format!("unknown export format '{}'", export.format).into());
+    }
+    if export.sketch_name != sketch_name {
+        return Err(format!(
+            "state was exported from sketch '{}', not '{}'",
+            export.sketch_name, sketch_name
+        )
+        .into());
+    }
+
+    let document = state_migrations::migrate(export.state)?;
+    state.merge(serde_json::from_value(document)?);
+    Ok(state)
+}
+
 // -----------------------------------------------------------------------------
 // Image Index
 // -----------------------------------------------------------------------------
@@ -178,6 +319,118 @@ pub fn save_image_index(
     image_index: &ImageIndex,
 ) -> Result<(), Box<dyn Error>> {
     let json = serde_json::to_string_pretty(image_index)?;
-    fs::write(image_index_path(user_data_dir), json)?;
+    write_atomic(&image_index_path(user_data_dir), json.as_bytes())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ControlCollection, ControlValue};
+    use crate::motion::{Bpm, FrameTiming};
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("xtal-storage-tests");
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join(name);
+        for file in [&path, &backup_path(&path), &temp_path(&path)] {
+            let _ = fs::remove_file(file);
+        }
+        path
+    }
+
+    #[test]
+    fn interrupted_write_leaves_previous_file_intact() {
+        let path = temp_file("interrupted.json");
+        write_atomic(&path, b"{\"a\": 1}").unwrap();
+
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"{\"a\": ")?;
+            Err(io::Error::other("simulated crash"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\": 1}");
+        assert!(!temp_path(&path).exists());
+    }
+
+    #[test]
+    fn backup_keeps_previous_good_file_and_can_be_restored() {
+        let path = temp_file("backup.json");
+        write_atomic(&path, b"first").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+
+        restore_backup(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let yaml = r#"
+size:
+  type: slider
+  default: 0.5
+invert:
+  type: checkbox
+  default: false
+mode:
+  type: select
+  default: a
+  options: [a, b]
+"#;
+        let hub =
+            || ControlHub::new(Some(yaml), FrameTiming::new(Bpm::new(120.0)));
+        let path = temp_file("export.json");
+
+        let mut source = hub();
+        source.ui_controls.set("size", ControlValue::from(0.75));
+        source
+            .ui_controls
+            .set("mode", ControlValue::from("b".to_string()));
+        source.take_snapshot("1");
+        source.ui_controls.set("invert", ControlValue::from(true));
+
+        let mut mappings = Mappings::default();
+        mappings.insert("size".to_string(), (0, 7));
+        let exclusions = vec!["mode".to_string()];
+        export_sketch_state(&path, "sketch", &source, mappings, exclusions)
+            .unwrap();
+
+        let target = hub();
+        let mut state = TransitorySketchState::from_hub(
+            &target,
+            Mappings::default(),
+            vec![],
+        );
+        import_sketch_state(&path, "sketch", &mut state).unwrap();
+
+        assert_eq!(state.ui_controls.values(), source.ui_controls.values());
+        assert_eq!(state.snapshots["1"], source.snapshots["1"]);
+        assert_eq!(state.mappings.get("size"), Some(&(0, 7)));
+        assert_eq!(state.exclusions, vec!["mode".to_string()]);
+
+        let mut state = TransitorySketchState::from_hub(
+            &target,
+            Mappings::default(),
+            vec![],
+        );
+        assert!(import_sketch_state(&path, "other", &mut state).is_err());
+    }
+
+    #[test]
+    fn corrupt_file_falls_back_to_backup() {
+        let path = temp_file("corrupt.json");
+        write_atomic(&path, b"{\"a\": 1}").unwrap();
+        write_atomic(&path, b"{\"a\": 2}").unwrap();
+        fs::write(&path, "{\"a\": ").unwrap();
+
+        let parse = |json: &str| -> Result<serde_json::Value, Box<dyn Error>> {
+            Ok(serde_json::from_str(json)?)
+        };
+        let value = read_json_with_backup(&path, parse).unwrap();
+        assert_eq!(value["a"], 1);
+    }
+}
@@ -2,10 +2,15 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use super::events::RuntimeEvent;
+use super::recorder::RecordingOptions;
+use super::recording::PreRoll;
 use super::registry::RuntimeRegistry;
+use super::stills::{StillFormat, TimelapseOptions};
 use crate::control::{ControlHub, ControlValue, UiControlConfig};
 use crate::core::util::HashMap;
+use crate::gpu;
 use crate::motion::TimingSource;
+use crate::pass_timing::PassTimings;
 
 pub type Sender = ipc_channel::ipc::IpcSender<Event>;
 pub type Receiver = ipc_channel::ipc::IpcReceiver<Event>;
@@ -29,6 +34,12 @@ pub enum OsDir {
     Config,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum SketchStateTransfer {
+    Export,
+    Import,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
 pub enum ControlKind {
     Checkbox,
@@ -111,11 +122,81 @@ pub struct SketchCatalogCategory {
     pub title: String,
     pub enabled: bool,
     pub sketches: Vec<String>,
+    /// [`SketchConfig::description`](crate::sketch::SketchConfig) of each
+    /// sketch in `sketches` that has one, keyed by sketch name.
+    #[serde(default)]
+    pub descriptions: HashMap<String, String>,
+}
+
+/// One-time UI bootstrap payload of [`Event::Init`]. Schema expected by
+/// xtal-ui.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InitPayload {
+    pub audio_device: String,
+    pub audio_devices: Vec<String>,
+    pub hrcc: bool,
+    pub images_dir: String,
+    pub is_light_theme: bool,
+    pub mappings_enabled: bool,
+    pub midi_clock_port: String,
+    pub midi_feedback_enabled: bool,
+    pub midi_input_port: String,
+    pub midi_output_port: String,
+    pub midi_input_ports: Vec<(usize, String)>,
+    pub midi_output_ports: Vec<(usize, String)>,
+    pub monitor_preview_enabled: bool,
+    pub offline_recording_seconds: f32,
+    pub osc_port: u16,
+    pub osc_prefix: String,
+    #[serde(default)]
+    pub osc_transport_smoothing: f32,
+    #[serde(default)]
+    pub perf_mode_hides_cursor: bool,
+    pub record_audio: bool,
+    pub recording_options: RecordingOptions,
+    pub recording_pre_roll: PreRoll,
+    pub sketches_by_category: SketchesByCategory,
+    #[serde(default)]
+    pub sketches_by_tag: SketchesByCategory,
+    #[serde(default)]
+    pub sketch_catalog: Option<Vec<SketchCatalogCategory>>,
+    pub sketch_name: String,
+    pub still_format: StillFormat,
+    pub timelapse_options: TimelapseOptions,
+    pub transition_time: f32,
+    pub user_data_dir: String,
+    pub videos_dir: String,
+}
+
+/// Active sketch payload of [`Event::LoadSketch`]. Schema expected by
+/// xtal-ui.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSketchPayload {
+    pub bpm: f32,
+    pub bypassed: Bypassed,
+    pub controls: Vec<Control>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub display_name: String,
+    pub fps: f32,
+    pub mappings: Mappings,
+    pub paused: bool,
+    pub perf_mode: bool,
+    pub sketch_name: String,
+    pub sketch_width: i32,
+    pub sketch_height: i32,
+    pub snapshot_slots: Vec<String>,
+    pub snapshot_sequence_enabled: bool,
+    pub tap_tempo_enabled: bool,
+    pub exclusions: Exclusions,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum Event {
     Advance,
+    AdvanceRandomizeSeed,
     Alert(String),
     AverageFps(f32),
     Bpm(f32),
@@ -126,88 +207,88 @@ pub enum Event {
     ChangeMidiControlInputPort(String),
     ChangeMidiControlOutputPort(String),
     ChangeOscPort(u16),
+    ChangeOscPrefix(String),
+    /// Asks the web-view process for a file to export to or import from
+    ChooseSketchStateFile(SketchStateTransfer),
     ClearBuffer,
+    CloseOutputWindow,
     CommitMappings,
     CurrentlyMapping(String),
     Encoding(bool),
     Error(String),
     Exclusions(Exclusions),
+    /// Write current control values back to the control script's defaults
+    ExportControlScript,
+    /// Path of the sketch state file to write
+    ExportSketchState(String),
     Hrcc(bool),
     HubPopulated((Vec<Control>, Bypassed)),
+    /// Path of a sketch state file to load
+    ImportSketchState(String),
     SnapshotSequenceEnabled(bool),
 
-    /// Schema expected by xtal-ui.
-    #[serde(rename_all = "camelCase")]
-    Init {
-        audio_device: String,
-        audio_devices: Vec<String>,
-        hrcc: bool,
-        images_dir: String,
-        is_light_theme: bool,
-        mappings_enabled: bool,
-        midi_clock_port: String,
-        midi_input_port: String,
-        midi_output_port: String,
-        midi_input_ports: Vec<(usize, String)>,
-        midi_output_ports: Vec<(usize, String)>,
-        monitor_preview_enabled: bool,
-        osc_port: u16,
-        sketches_by_category: SketchesByCategory,
-        #[serde(default)]
-        sketch_catalog: Option<Vec<SketchCatalogCategory>>,
-        sketch_name: String,
-        transition_time: f32,
-        user_data_dir: String,
-        videos_dir: String,
-    },
+    /// Boxed to keep the rest of the variants small
+    Init(Box<InitPayload>),
 
-    /// Schema expected by xtal-ui.
-    #[serde(rename_all = "camelCase")]
-    LoadSketch {
-        bpm: f32,
-        bypassed: Bypassed,
-        controls: Vec<Control>,
-        display_name: String,
-        fps: f32,
-        mappings: Mappings,
-        paused: bool,
-        perf_mode: bool,
-        sketch_name: String,
-        sketch_width: i32,
-        sketch_height: i32,
-        snapshot_slots: Vec<String>,
-        snapshot_sequence_enabled: bool,
-        tap_tempo_enabled: bool,
-        exclusions: Exclusions,
-    },
+    /// Boxed to keep the rest of the variants small
+    LoadSketch(Box<LoadSketchPayload>),
 
     Mappings(Mappings),
     MappingsEnabled(bool),
+    MidiFeedbackEnabled(bool),
     MonitorPreview(bool),
+    OfflineRecordingSeconds(f32),
     OpenOsDir(OsDir),
+    /// Index into the system's monitor list
+    OpenOutputWindow(usize),
+    OscTransportSmoothing(f32),
+    /// GPU time per graph pass, sent about once a second with the `timing`
+    /// feature
+    PassTimings(PassTimings),
     Paused(bool),
     PerfMode(bool),
+    PerfModeHidesCursor(bool),
+    /// Hovered slider value to show without committing it; `None` when the
+    /// hover ends
+    PreviewControl {
+        name: String,
+        value: Option<f32>,
+    },
     QueueRecord,
     Quit,
     Randomize(Exclusions),
+    /// Sent as a string since seeds above 2^53 don't survive a JS number
+    RandomizeSeed(#[serde(with = "seed_string")] Option<u64>),
+    RecordAudio(bool),
+    RecordingOptions(RecordingOptions),
+    /// Whole beats or seconds until recording starts; `None` once it starts
+    /// or is canceled
+    RecordingCountdown(Option<u32>),
+    RecordingPreRoll(PreRoll),
     Ready,
     ReceiveDir(UserDir, String),
     RemoveMapping(String),
     Reset,
     Save(Vec<String>),
     SendMidi,
+    ShaderError(gpu::ShaderError),
     SnapshotDelete(String),
     SnapshotEnded(Vec<Control>),
     SnapshotRecall(String),
     SnapshotStore(String),
     StartRecording,
+    StillFormat(StillFormat),
     StopRecording,
     SwitchSketch(String),
     Tap,
     TapTempoEnabled(bool),
+    /// Whether a timelapse is running
+    Timelapse(bool),
+    TimelapseOptions(TimelapseOptions),
     ToggleFullScreen,
     ToggleGuiFocus,
     ToggleMainFocus,
+    ToggleTimelapse,
     TransitionTime(f32),
     UpdateControlBool {
         name: String,
@@ -224,6 +305,28 @@ pub enum Event {
     UpdatedControls(Vec<Control>),
 }
 
+mod seed_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        seed: &Option<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match seed {
+            Some(seed) => serializer.serialize_some(&seed.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|seed| seed.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
 pub fn parse_ui_message(message: &str) -> Result<Event, String> {
     serde_json::from_str(message).map_err(|err| {
         format!("invalid web-view message '{}': {}", message, err)
@@ -238,6 +341,8 @@ pub fn to_ui_message(event: &Event) -> Result<String, String> {
 pub fn map_event_to_runtime_event(event: &Event) -> Option<RuntimeEvent> {
     match event {
         Event::Advance => Some(RuntimeEvent::AdvanceSingleFrame),
+        Event::AdvanceRandomizeSeed => Some(RuntimeEvent::AdvanceRandomizeSeed),
+        Event::Bpm(bpm) => Some(RuntimeEvent::SetBpm(*bpm)),
         Event::CaptureFrame => Some(RuntimeEvent::CaptureFrame),
         Event::ChangeAudioDevice(name) => {
             Some(RuntimeEvent::ChangeAudioDevice(name.clone()))
@@ -252,7 +357,11 @@ pub fn map_event_to_runtime_event(event: &Event) -> Option<RuntimeEvent> {
             Some(RuntimeEvent::ChangeMidiControlOutputPort(port.clone()))
         }
         Event::ChangeOscPort(port) => Some(RuntimeEvent::ChangeOscPort(*port)),
+        Event::ChangeOscPrefix(prefix) => {
+            Some(RuntimeEvent::ChangeOscPrefix(prefix.clone()))
+        }
         Event::ClearBuffer => Some(RuntimeEvent::ClearBuffer),
+        Event::CloseOutputWindow => Some(RuntimeEvent::CloseOutputWindow),
         Event::CommitMappings => Some(RuntimeEvent::CommitMappings),
         Event::CurrentlyMapping(name) => {
             Some(RuntimeEvent::CurrentlyMapping(name.clone()))
@@ -260,23 +369,60 @@ pub fn map_event_to_runtime_event(event: &Event) -> Option<RuntimeEvent> {
         Event::Exclusions(exclusions) => {
             Some(RuntimeEvent::UpdateExclusions(exclusions.clone()))
         }
+        Event::ExportControlScript => Some(RuntimeEvent::ExportControlScript),
+        Event::ExportSketchState(path) => {
+            Some(RuntimeEvent::ExportSketchState(path.into()))
+        }
         Event::Hrcc(enabled) => Some(RuntimeEvent::SetHrcc(*enabled)),
+        Event::ImportSketchState(path) => {
+            Some(RuntimeEvent::ImportSketchState(path.into()))
+        }
         Event::Mappings(mappings) => {
             Some(RuntimeEvent::ReceiveMappings(mappings.clone()))
         }
         Event::MappingsEnabled(enabled) => {
             Some(RuntimeEvent::SetMappingsEnabled(*enabled))
         }
+        Event::MidiFeedbackEnabled(enabled) => {
+            Some(RuntimeEvent::SetMidiFeedbackEnabled(*enabled))
+        }
         Event::MonitorPreview(enabled) => {
             Some(RuntimeEvent::SetMonitorPreview(*enabled))
         }
+        Event::OfflineRecordingSeconds(seconds) => {
+            Some(RuntimeEvent::SetOfflineRecordingSeconds(*seconds))
+        }
         Event::OpenOsDir(kind) => Some(RuntimeEvent::OpenOsDir(kind.clone())),
+        Event::OpenOutputWindow(index) => {
+            Some(RuntimeEvent::OpenOutputWindow(*index))
+        }
+        Event::OscTransportSmoothing(smoothing) => {
+            Some(RuntimeEvent::SetOscTransportSmoothing(*smoothing))
+        }
         Event::Paused(paused) => Some(RuntimeEvent::Pause(*paused)),
         Event::PerfMode(enabled) => Some(RuntimeEvent::SetPerfMode(*enabled)),
+        Event::PerfModeHidesCursor(hides) => {
+            Some(RuntimeEvent::SetPerfModeHidesCursor(*hides))
+        }
+        Event::PreviewControl { name, value } => {
+            Some(RuntimeEvent::PreviewControl((name.clone(), *value)))
+        }
         Event::QueueRecord => Some(RuntimeEvent::QueueRecord),
         Event::Randomize(exclusions) => {
             Some(RuntimeEvent::Randomize(exclusions.clone()))
         }
+        Event::RandomizeSeed(seed) => {
+            Some(RuntimeEvent::SetRandomizeSeed(*seed))
+        }
+        Event::RecordAudio(enabled) => {
+            Some(RuntimeEvent::SetRecordAudio(*enabled))
+        }
+        Event::RecordingOptions(options) => {
+            Some(RuntimeEvent::SetRecordingOptions(options.clone()))
+        }
+        Event::RecordingPreRoll(pre_roll) => {
+            Some(RuntimeEvent::SetRecordingPreRoll(*pre_roll))
+        }
         Event::ReceiveDir(kind, dir) => {
             Some(RuntimeEvent::ReceiveDir(kind.clone(), dir.clone()))
         }
@@ -297,6 +443,9 @@ pub fn map_event_to_runtime_event(event: &Event) -> Option<RuntimeEvent> {
             Some(RuntimeEvent::SnapshotStore(id.clone()))
         }
         Event::StartRecording => Some(RuntimeEvent::StartRecording),
+        Event::StillFormat(format) => {
+            Some(RuntimeEvent::SetStillFormat(*format))
+        }
         Event::StopRecording => Some(RuntimeEvent::StopRecording),
         Event::SwitchSketch(name) => {
             Some(RuntimeEvent::SwitchSketch(name.clone()))
@@ -309,7 +458,11 @@ pub fn map_event_to_runtime_event(event: &Event) -> Option<RuntimeEvent> {
             Some(RuntimeEvent::SetTransitionTime(*time))
         }
         Event::ToggleFullScreen => Some(RuntimeEvent::ToggleFullScreen),
+        Event::TimelapseOptions(options) => {
+            Some(RuntimeEvent::SetTimelapseOptions(options.clone()))
+        }
         Event::ToggleMainFocus => Some(RuntimeEvent::ToggleMainFocus),
+        Event::ToggleTimelapse => Some(RuntimeEvent::ToggleTimelapse),
         Event::UpdateControlBool { name, value } => {
             Some(RuntimeEvent::UpdateUiControl((
                 name.clone(),
@@ -354,6 +507,14 @@ pub fn sketch_catalog_from_registry(
             title: category.title.clone(),
             enabled: category.enabled,
             sketches: category.sketches.clone(),
+            descriptions: category
+                .sketches
+                .iter()
+                .filter_map(|name| {
+                    let description = registry.get(name)?.config.description?;
+                    Some((name.clone(), description.to_string()))
+                })
+                .collect(),
         })
         .collect()
 }
@@ -367,9 +528,21 @@ pub fn sketches_by_category(registry: &RuntimeRegistry) -> SketchesByCategory {
         .collect()
 }
 
+pub fn sketches_by_tag(registry: &RuntimeRegistry) -> SketchesByCategory {
+    registry
+        .tags()
+        .into_iter()
+        .map(|tag| {
+            let sketches = registry.sketches_with_tag(&tag);
+            (tag, sketches)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pass_timing::PassTiming;
 
     #[test]
     fn parses_unit_and_payload_messages() {
@@ -393,6 +566,13 @@ mod tests {
         let command = map_event_to_runtime_event(&event);
         assert_eq!(command, Some(RuntimeEvent::SetPerfMode(true)));
 
+        let hides_cursor =
+            map_event_to_runtime_event(&Event::PerfModeHidesCursor(false));
+        assert_eq!(
+            hides_cursor,
+            Some(RuntimeEvent::SetPerfModeHidesCursor(false))
+        );
+
         let hrcc = map_event_to_runtime_event(&Event::Hrcc(true));
         assert_eq!(hrcc, Some(RuntimeEvent::SetHrcc(true)));
 
@@ -420,6 +600,34 @@ mod tests {
         assert_eq!(main_focus, Some(RuntimeEvent::ToggleMainFocus));
     }
 
+    #[test]
+    fn maps_export_control_script_command() {
+        let export = parse_ui_message("\"ExportControlScript\"").unwrap();
+        assert_eq!(
+            map_event_to_runtime_event(&export),
+            Some(RuntimeEvent::ExportControlScript)
+        );
+    }
+
+    #[test]
+    fn maps_sketch_state_file_commands() {
+        let export = parse_ui_message("{\"ExportSketchState\":\"a.json\"}");
+        assert_eq!(
+            map_event_to_runtime_event(&export.unwrap()),
+            Some(RuntimeEvent::ExportSketchState("a.json".into()))
+        );
+
+        let import = parse_ui_message("{\"ImportSketchState\":\"b.json\"}");
+        assert_eq!(
+            map_event_to_runtime_event(&import.unwrap()),
+            Some(RuntimeEvent::ImportSketchState("b.json".into()))
+        );
+
+        // The web-view process answers these with one of the above
+        let choose = Event::ChooseSketchStateFile(SketchStateTransfer::Import);
+        assert_eq!(map_event_to_runtime_event(&choose), None);
+    }
+
     #[test]
     fn maps_snapshot_commands() {
         let store =
@@ -456,6 +664,9 @@ mod tests {
         let tap = map_event_to_runtime_event(&Event::Tap);
         assert_eq!(tap, Some(RuntimeEvent::Tap));
 
+        let bpm = map_event_to_runtime_event(&Event::Bpm(96.0));
+        assert_eq!(bpm, Some(RuntimeEvent::SetBpm(96.0)));
+
         let tap_tempo =
             map_event_to_runtime_event(&Event::TapTempoEnabled(true));
         assert_eq!(tap_tempo, Some(RuntimeEvent::TapTempoEnabled(true)));
@@ -535,6 +746,14 @@ mod tests {
             map_event_to_runtime_event(&Event::OpenOsDir(OsDir::Cache)),
             Some(RuntimeEvent::OpenOsDir(OsDir::Cache))
         );
+        assert_eq!(
+            map_event_to_runtime_event(&Event::OpenOutputWindow(1)),
+            Some(RuntimeEvent::OpenOutputWindow(1))
+        );
+        assert_eq!(
+            map_event_to_runtime_event(&Event::CloseOutputWindow),
+            Some(RuntimeEvent::CloseOutputWindow)
+        );
         assert_eq!(
             map_event_to_runtime_event(&Event::CaptureFrame),
             Some(RuntimeEvent::CaptureFrame)
@@ -581,6 +800,14 @@ mod tests {
             )))
         );
 
+        assert_eq!(
+            map_event_to_runtime_event(&Event::PreviewControl {
+                name: "amount".into(),
+                value: Some(0.25),
+            }),
+            Some(RuntimeEvent::PreviewControl(("amount".into(), Some(0.25))))
+        );
+
         assert_eq!(
             map_event_to_runtime_event(&Event::UpdateControlString {
                 name: "mode".into(),
@@ -617,6 +844,12 @@ mod tests {
         let events = vec![
             Event::Randomize(vec!["foo".into(), "bar".into()]),
             Event::Save(vec!["foo".into()]),
+            Event::ShaderError(gpu::ShaderError {
+                path: "shaders/main.wgsl".into(),
+                message: "expected ';', found '}'".into(),
+                line: Some(12),
+                column: Some(5),
+            }),
             Event::SnapshotStore("1".into()),
             Event::SnapshotRecall("2".into()),
             Event::SnapshotDelete("3".into()),
@@ -625,6 +858,13 @@ mod tests {
             Event::ChangeOscPort(9000),
             Event::TransitionTime(2.5),
             Event::Paused(true),
+            Event::PassTimings(PassTimings {
+                passes: vec![PassTiming {
+                    name: "blur".into(),
+                    milliseconds: 1.5,
+                }],
+                total_milliseconds: 1.5,
+            }),
             Event::PerfMode(true),
             Event::MonitorPreview(true),
             Event::MappingsEnabled(false),
@@ -732,10 +972,11 @@ mod tests {
         );
 
         // Outbound runtime events -> JSON shape/casing expected by xtal-ui.
-        let load = Event::LoadSketch {
+        let load = Event::LoadSketch(Box::new(LoadSketchPayload {
             bpm: 120.0,
             bypassed: HashMap::default(),
             controls: vec![],
+            description: None,
             display_name: "Smoke".into(),
             fps: 60.0,
             mappings: HashMap::default(),
@@ -748,7 +989,7 @@ mod tests {
             snapshot_sequence_enabled: false,
             tap_tempo_enabled: false,
             exclusions: vec!["foo".into()],
-        };
+        }));
         let load_json = to_ui_message(&load).expect("serialize load sketch");
         assert!(load_json.contains("\"LoadSketch\""));
         assert!(load_json.contains("\"displayName\""));
@@ -5,7 +5,9 @@ macro_rules! register_sketches {
             {
                 title: $title:expr,
                 enabled: $enabled:expr,
-                sketches: [$($module:ident),* $(,)?]
+                sketches: [
+                    $($module:ident $(: [$($tag:expr),* $(,)?])?),* $(,)?
+                ]
             }
         ),+ $(,)?
     ) => {{
@@ -20,6 +22,12 @@ macro_rules! register_sketches {
                         &$module::SKETCH_CONFIG,
                         || Box::new($module::init()),
                     )?;
+                    $(
+                        __registry.tag_sketch(
+                            $module::SKETCH_CONFIG.name,
+                            vec![$($tag.to_string()),*],
+                        )?;
+                    )?
                     __category_sketches
                         .push($module::SKETCH_CONFIG.name.to_string());
                 )*
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use winit::keyboard::{KeyCode, ModifiersState};
+
 use crate::context::Context;
 use crate::frame::Frame;
 use crate::graph::GraphBuilder;
@@ -14,6 +16,50 @@ pub struct SketchConfig {
     pub w: u32,
     pub h: u32,
     pub banks: usize,
+    /// Keys the sketch handles itself. Unmodified presses of these keys skip
+    /// the runtime shortcuts (e.g. `F` fullscreen, `P` pause) and only show
+    /// up in [`Context::keys_down`]. Checked after [`Sketch::on_key`], which
+    /// can consume any key.
+    pub captured_keys: &'static [KeyCode],
+    /// Keep the transport position when the sketch is reloaded instead of
+    /// starting over. Ignored for external MIDI/OSC timing, which keeps its
+    /// own position.
+    pub preserve_beats: bool,
+    /// Render, capture and record at exactly this `[width, height]` whatever
+    /// the window's size; the window shows a letterboxed preview. Shaders see
+    /// it as the resolution (`ax`, `ay`).
+    pub export_size: Option<[u32; 2]>,
+    /// Multisample anti-aliasing for render nodes that draw to the surface:
+    /// 1 (off), 2, 4 or 8 samples per pixel. Counts the GPU doesn't support
+    /// fall back to the highest one it does, with a warning.
+    pub msaa_samples: u32,
+    /// Pan the [`Context::camera`] by dragging with the left mouse button
+    /// and zoom it around the cursor with the wheel
+    pub camera_controls: bool,
+    /// Markdown shown in the UI's sketch help panel and sketch picker, e.g.
+    /// documenting what the controls and captured keys do.
+    pub description: Option<&'static str>,
+}
+
+impl SketchConfig {
+    /// Baseline for the optional fields so sketches only spell out what they
+    /// change, e.g. `..SketchConfig::DEFAULT` after `name` through `banks`.
+    pub const DEFAULT: Self = Self {
+        name: "",
+        display_name: "",
+        play_mode: PlayMode::Loop,
+        fps: 60.0,
+        bpm: 120.0,
+        w: 700,
+        h: 700,
+        banks: 4,
+        captured_keys: &[],
+        preserve_beats: false,
+        export_size: None,
+        msaa_samples: 1,
+        camera_controls: false,
+        description: None,
+    };
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -39,18 +85,59 @@ pub trait Sketch {
         None
     }
 
+    /// Control script YAML compiled into the binary (e.g. with
+    /// `include_str!`), used when [`Sketch::control_script`] is `None`. It
+    /// isn't watched, so edits need a rebuild.
+    fn embedded_control_script(&self) -> Option<&'static str> {
+        None
+    }
+
     fn timing_mode(&self) -> TimingMode {
         TimingMode::Frame
     }
 
+    /// The sketch's Rust source, watched with the `source-watch` feature.
+    /// Defaults to the `.rs` file next to the control script.
+    fn source_file(&self) -> Option<PathBuf> {
+        self.control_script()
+            .map(|path| path.with_extension("rs"))
+            .filter(|path| path.exists())
+    }
+
     fn update(&mut self, _ctx: &Context) {}
 
     fn view(&mut self, _frame: &mut Frame, _ctx: &Context) {}
+
+    /// Called once before the sketch is dropped: when switching to another
+    /// sketch (including reloading this one) and at runtime shutdown. Use it
+    /// to flush files or stop threads started by the sketch.
+    fn on_exit(&mut self) {}
+
+    /// Called when the main window's size changes, after
+    /// [`Context::resolution`] reports `new_size` and before the next frame
+    /// renders. Use it to reallocate resolution-dependent resources.
+    fn on_resize(&mut self, _new_size: [u32; 2], _ctx: &Context) {}
+
+    /// Called for every key press, repeat and release on the main window
+    /// before the runtime shortcuts. Return `true` to consume the event so
+    /// the runtime neither runs its shortcut nor records the press in
+    /// [`Context::keys_down`]. `Mod` + `Q` (quit) and `Mod` + `F`
+    /// (fullscreen) are reserved and never reach this hook.
+    fn on_key(
+        &mut self,
+        _key: KeyCode,
+        _pressed: bool,
+        _modifiers: ModifiersState,
+        _ctx: &Context,
+    ) -> bool {
+        false
+    }
 }
 
 pub struct FullscreenShaderSketch {
     shader_path: PathBuf,
     control_script_path: Option<PathBuf>,
+    embedded_control_script: Option<&'static str>,
     timing_mode: TimingMode,
 }
 
@@ -59,6 +146,7 @@ impl FullscreenShaderSketch {
         Self {
             shader_path: shader_path.into(),
             control_script_path: None,
+            embedded_control_script: None,
             timing_mode: TimingMode::Frame,
         }
     }
@@ -71,6 +159,11 @@ impl FullscreenShaderSketch {
         self
     }
 
+    pub fn with_embedded_control_script(mut self, yaml: &'static str) -> Self {
+        self.embedded_control_script = Some(yaml);
+        self
+    }
+
     pub fn with_timing_mode(mut self, timing_mode: TimingMode) -> Self {
         self.timing_mode = timing_mode;
         self
@@ -93,6 +186,10 @@ impl Sketch for FullscreenShaderSketch {
         self.control_script_path.clone()
     }
 
+    fn embedded_control_script(&self) -> Option<&'static str> {
+        self.embedded_control_script
+    }
+
     fn timing_mode(&self) -> TimingMode {
         self.timing_mode
     }
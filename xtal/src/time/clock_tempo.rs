@@ -0,0 +1,125 @@
+//! Tempo estimation from incoming MIDI clock pulses
+
+const PULSES_PER_QUARTER_NOTE: f64 = 24.0;
+
+// Weight of each new pulse interval in the running average. At 24 PPQN this
+// settles on a tempo change within about a beat while evening out jitter.
+const SMOOTHING: f64 = 0.1;
+
+// Intervals to average before reporting, so the first estimate isn't a
+// single jittery pulse
+const WARM_UP_PULSES: u32 = 12;
+
+// A gap longer than one pulse at 10 BPM means the clock stopped; start over
+// instead of averaging the pause in
+const TIMEOUT_MICROS: u64 = 250_000;
+
+/// Estimates BPM from 24 PPQN MIDI clock timestamps (in microseconds, as
+/// reported by the MIDI input) using an exponential moving average of the
+/// pulse interval.
+#[derive(Clone, Debug, Default)]
+pub struct ClockTempo {
+    previous_stamp: Option<u64>,
+    interval_micros: f64,
+    intervals: u32,
+}
+
+impl ClockTempo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a clock pulse and return the current estimate, if warmed up
+    pub fn tick(&mut self, stamp_micros: u64) -> Option<f32> {
+        let previous = self.previous_stamp.replace(stamp_micros);
+        let interval = stamp_micros.saturating_sub(previous?);
+
+        if interval == 0 || interval > TIMEOUT_MICROS {
+            self.intervals = 0;
+            return None;
+        }
+
+        let interval = interval as f64;
+        self.interval_micros = if self.intervals == 0 {
+            interval
+        } else {
+            self.interval_micros + (interval - self.interval_micros) * SMOOTHING
+        };
+        self.intervals = self.intervals.saturating_add(1);
+
+        self.bpm()
+    }
+
+    pub fn bpm(&self) -> Option<f32> {
+        if self.intervals < WARM_UP_PULSES {
+            return None;
+        }
+        let micros_per_beat = self.interval_micros * PULSES_PER_QUARTER_NOTE;
+        Some((60_000_000.0 / micros_per_beat) as f32)
+    }
+
+    /// Forget the pulse history, e.g. after the transport restarts
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pulse_micros(bpm: f64) -> f64 {
+        60_000_000.0 / (bpm * PULSES_PER_QUARTER_NOTE)
+    }
+
+    // Feeds `beats` worth of pulses at `bpm` starting from `start`, with a
+    // repeating ±400µs of jitter, returning the last estimate and stamp
+    fn run_clock(
+        tempo: &mut ClockTempo,
+        bpm: f64,
+        beats: u32,
+        start: f64,
+    ) -> (Option<f32>, f64) {
+        let jitter = [0.0, 400.0, -300.0, 150.0, -400.0, 250.0];
+        let interval = pulse_micros(bpm);
+        let pulses = beats * PULSES_PER_QUARTER_NOTE as u32;
+        let mut estimate = None;
+        let mut time = start;
+        for i in 0..pulses as usize {
+            time += interval;
+            let stamp = time + jitter[i % jitter.len()];
+            estimate = tempo.tick(stamp as u64);
+        }
+        (estimate, time)
+    }
+
+    #[test]
+    fn test_estimates_known_tempo() {
+        let mut tempo = ClockTempo::new();
+        let (estimate, _) = run_clock(&mut tempo, 128.0, 8, 0.0);
+        let estimate = estimate.expect("warmed up");
+        assert!((estimate - 128.0).abs() < 1.0, "{}", estimate);
+    }
+
+    #[test]
+    fn test_follows_tempo_change() {
+        let mut tempo = ClockTempo::new();
+        let (_, time) = run_clock(&mut tempo, 120.0, 4, 0.0);
+        let (estimate, _) = run_clock(&mut tempo, 90.0, 4, time);
+        let estimate = estimate.expect("warmed up");
+        assert!((estimate - 90.0).abs() < 1.0, "{}", estimate);
+    }
+
+    #[test]
+    fn test_waits_for_warm_up_and_restarts_after_gap() {
+        let mut tempo = ClockTempo::new();
+        let interval = pulse_micros(120.0) as u64;
+        for i in 0..WARM_UP_PULSES as u64 {
+            assert_eq!(tempo.tick(i * interval), None);
+        }
+        assert!(tempo.tick(WARM_UP_PULSES as u64 * interval).is_some());
+
+        assert_eq!(tempo.tick(10_000_000), None);
+        assert_eq!(tempo.bpm(), None);
+    }
+}
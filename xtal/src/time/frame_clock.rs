@@ -22,6 +22,7 @@ struct Pacer {
     last_render_at: Option<Instant>,
     max_intervals: usize,
     force_render: bool,
+    offline: bool,
 }
 
 impl Pacer {
@@ -37,6 +38,7 @@ impl Pacer {
             last_render_at: None,
             max_intervals: 90,
             force_render: false,
+            offline: false,
         }
     }
 
@@ -83,11 +85,25 @@ impl Pacer {
             return TickResult::default();
         }
 
-        let frame_duration = frame_duration();
+        if self.offline {
+            // Every tick renders exactly one frame and moves the transport
+            // exactly one frame, however long the last frame really took.
+            self.accumulator = Duration::ZERO;
+            self.transport_offset += render_interval();
+            self.publish_transport_elapsed_at(now);
+            advance_frames(1);
+            self.record_render(now);
+            return TickResult {
+                should_render: true,
+                frames_advanced: 1,
+            };
+        }
+
+        let render_interval = render_interval();
         let mut advanced = 0u32;
 
-        while self.accumulator >= frame_duration {
-            self.accumulator -= frame_duration;
+        while self.accumulator >= render_interval {
+            self.accumulator -= render_interval;
             advanced += 1;
         }
 
@@ -104,7 +120,10 @@ impl Pacer {
     }
 
     fn next_deadline(&self) -> Instant {
-        let remaining = frame_duration()
+        if self.offline {
+            return self.last_tick;
+        }
+        let remaining = render_interval()
             .checked_sub(self.accumulator)
             .unwrap_or_default();
         self.last_tick + remaining
@@ -162,7 +181,18 @@ impl Pacer {
         self.publish_transport_elapsed_at(now);
     }
 
+    fn set_offline(&mut self, offline: bool, now: Instant) {
+        let elapsed = self.transport_elapsed(now);
+        self.offline = offline;
+        self.last_tick = now;
+        self.accumulator = Duration::ZERO;
+        self.set_transport_elapsed(now, elapsed);
+    }
+
     fn transport_elapsed(&self, now: Instant) -> Duration {
+        if self.offline {
+            return self.transport_offset;
+        }
         let effective_now = self.transport_paused_at.unwrap_or(now);
         let since_origin =
             effective_now.saturating_duration_since(self.transport_origin);
@@ -178,6 +208,7 @@ impl Pacer {
 
 static FRAME_COUNT: AtomicU32 = AtomicU32::new(0);
 static FPS: AtomicF32 = AtomicF32::new(60.0);
+static RENDER_FPS: AtomicF32 = AtomicF32::new(0.0);
 static PAUSED: AtomicBool = AtomicBool::new(false);
 static TRANSPORT_ELAPSED_SECONDS: AtomicF32 = AtomicF32::new(0.0);
 static PACER: LazyLock<Mutex<Pacer>> =
@@ -220,6 +251,20 @@ pub fn set_fps(fps: f32) {
     FPS.store(fps.max(1.0), Ordering::Release);
 }
 
+/// Pace rendering at `fps` instead of the sketch's declared [`fps`], which
+/// keeps driving beat/frame conversions so visuals don't change speed. `None`
+/// goes back to pacing at [`fps`].
+pub fn set_render_fps(fps: Option<f32>) {
+    let render_fps = fps.map_or(0.0, |fps| fps.max(1.0));
+    RENDER_FPS.store(render_fps, Ordering::Release);
+}
+
+/// The rate frames are actually rendered (and [`frame_count`] advances) at
+pub fn render_fps() -> f32 {
+    let render_fps = RENDER_FPS.load(Ordering::Acquire);
+    if render_fps > 0.0 { render_fps } else { fps() }
+}
+
 fn set_paused_at(paused: bool, now: Instant) {
     PAUSED.store(paused, Ordering::Release);
     with_pacer(|pacer| pacer.set_paused(paused, now));
@@ -237,6 +282,10 @@ pub fn frame_duration() -> Duration {
     Duration::from_secs_f32(1.0 / fps())
 }
 
+fn render_interval() -> Duration {
+    Duration::from_secs_f32(1.0 / render_fps())
+}
+
 pub fn average_fps() -> f32 {
     with_pacer(|pacer| pacer.average_fps())
 }
@@ -265,6 +314,22 @@ fn elapsed_seconds_at(now: Instant) -> f32 {
     with_pacer(|pacer| pacer.transport_elapsed(now).as_secs_f32())
 }
 
+fn set_offline_at(offline: bool, now: Instant) {
+    with_pacer(|pacer| pacer.set_offline(offline, now));
+}
+
+/// Decouple the transport from wall-clock time for frame-accurate offline
+/// rendering: every [`tick`] renders one frame and advances the transport by
+/// exactly one [`render_fps`] frame, no matter how long rendering takes.
+/// Turning it off resumes real-time pacing from the current position.
+pub fn set_offline(offline: bool) {
+    set_offline_at(offline, Instant::now());
+}
+
+pub fn offline() -> bool {
+    with_pacer(|pacer| pacer.offline)
+}
+
 pub fn advance_single_frame() {
     if paused() {
         with_pacer(|pacer| {
@@ -292,7 +357,9 @@ mod tests {
 
     fn init(now: Instant, fps_value: f32) {
         set_fps(fps_value);
+        set_render_fps(None);
         set_paused_at(false, now);
+        set_offline_at(false, now);
         set_frame_count(0);
         set_elapsed_seconds(0.0);
         reset_timing(now);
@@ -370,6 +437,24 @@ mod tests {
         assert_eq!(frame_count(), 2);
     }
 
+    #[test]
+    #[serial]
+    fn render_fps_override_paces_without_changing_fps() {
+        let start = Instant::now();
+        init(start, 30.0);
+        set_render_fps(Some(60.0));
+
+        assert_eq!(fps(), 30.0);
+        assert_eq!(render_fps(), 60.0);
+
+        let t = tick(start + Duration::from_secs_f32(1.0 / 60.0));
+        assert!(t.should_render);
+        assert_eq!(t.frames_advanced, 1);
+
+        set_render_fps(None);
+        assert_eq!(render_fps(), 30.0);
+    }
+
     #[test]
     #[serial]
     fn transport_elapsed_tracks_monotonic_time_when_running() {
@@ -399,4 +484,33 @@ mod tests {
                 < 0.000_1
         );
     }
+
+    #[test]
+    #[serial]
+    fn offline_steps_one_frame_per_tick_regardless_of_wall_time() {
+        let start = Instant::now();
+        init(start, 30.0);
+        set_offline_at(true, start);
+
+        // No wall time passes between ticks, then far too much does
+        for _ in 0..29 {
+            assert_eq!(
+                tick(start),
+                TickResult {
+                    should_render: true,
+                    frames_advanced: 1,
+                }
+            );
+        }
+        let late = start + Duration::from_secs(10);
+        assert_eq!(tick(late).frames_advanced, 1);
+        assert_eq!(next_deadline(), late);
+
+        assert_eq!(frame_count(), 30);
+        assert!((elapsed_seconds_at(late) - 1.0).abs() < 0.000_1);
+
+        set_offline_at(false, late);
+        let later = late + Duration::from_millis(500);
+        assert!((elapsed_seconds_at(later) - 1.5).abs() < 0.000_1);
+    }
 }
@@ -1,2 +1,3 @@
+pub mod clock_tempo;
 pub mod frame_clock;
 pub mod tap_tempo;
@@ -0,0 +1,71 @@
+use serial_test::serial;
+use xtal::prelude::test::*;
+use xtal::prelude::*;
+
+const SCRIPT: &str = r#"
+wave:
+  type: triangle
+  beats: 4
+  range: [-1, 1]
+
+scaled_wave:
+  type: triangle
+  beats: 4
+  range: [-1, 1]
+  mod: [scale]
+
+scale:
+  type: effect
+  kind: math
+  operator: mult
+  operand: 10
+"#;
+
+#[test]
+#[serial]
+fn triangle_at_several_beats() {
+    let mut hub = TestHubBuilder::new().bpm(120.0).script(SCRIPT).build();
+
+    for (beat, expected) in
+        [(0.0, -1.0), (1.0, 0.0), (2.0, 1.0), (3.0, 0.0), (4.0, -1.0)]
+    {
+        hub.advance_to_beat(beat);
+        assert_close(
+            hub.get("wave"),
+            expected,
+            &format!("wave at beat {}", beat),
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn modulation_chain_follows_the_transport() {
+    let mut hub = TestHubBuilder::new().script(SCRIPT).build();
+
+    hub.advance_to_beat(2.0);
+    assert_close(hub.get("scaled_wave"), 10.0, "scaled_wave at beat 2");
+
+    hub.advance_to_beat(1.0);
+    assert_close(hub.get("scaled_wave"), 0.0, "scaled_wave at beat 1");
+}
+
+#[test]
+#[serial]
+fn frames_map_to_beats() {
+    let timing = TestTiming::new(120.0, 60.0);
+    assert_eq!(timing.frames_per_beat(), 30.0);
+
+    let animation = Animation::new(timing);
+    animation.advance_to_frame(30);
+    assert_close(animation.beats(), 1.0, "beats at frame 30");
+    assert_eq!(frame_count(), 30);
+
+    animation.advance_to_beat(0.5);
+    assert_close(animation.tri(1.0), 1.0, "tri at beat 0.5");
+    assert_eq!(frame_count(), 15);
+}
+
+fn frame_count() -> u32 {
+    xtal::time::frame_clock::frame_count()
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "ndi")]
+
+mod support;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use xtal::runtime::ndi_output::{
+    FrameSender, NdiOutput, PixelFormat, VideoFrame,
+};
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 8;
+const FPS: f32 = 30.0;
+
+#[derive(Debug, PartialEq)]
+struct SentFrame {
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    first_pixel: [u8; 4],
+}
+
+#[derive(Clone, Default)]
+struct MockSender {
+    frames: Arc<Mutex<Vec<SentFrame>>>,
+}
+
+impl FrameSender for MockSender {
+    fn send(&mut self, frame: &VideoFrame) {
+        assert!(frame.line_stride >= frame.width * 4);
+        assert_eq!(frame.fps, FPS);
+        self.frames.lock().unwrap().push(SentFrame {
+            width: frame.width,
+            height: frame.height,
+            format: frame.format,
+            first_pixel: frame.data[..4].try_into().unwrap(),
+        });
+    }
+}
+
+#[test]
+fn captured_frames_are_enqueued_to_the_sender() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping NDI output test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let device = Arc::new(device);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("ndi-output-source"),
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let pixels = [10u8, 20, 30, 255].repeat((WIDTH * HEIGHT) as usize);
+    queue.write_texture(
+        texture.as_image_copy(),
+        &pixels,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(WIDTH * 4),
+            rows_per_image: Some(HEIGHT),
+        },
+        texture.size(),
+    );
+
+    let sender = MockSender::default();
+    let frames = sender.frames.clone();
+    let mut output = NdiOutput::with_sender(device.clone(), Box::new(sender));
+
+    let mut captured = 0;
+    for _ in 0..2 {
+        let mut encoder = device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        if output.capture(&mut encoder, &texture, FPS) {
+            captured += 1;
+        }
+        queue.submit([encoder.finish()]);
+        output.on_submitted();
+    }
+    assert_eq!(captured, 2);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while frames.lock().unwrap().len() < captured {
+        assert!(Instant::now() < deadline, "timed out waiting for frames");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    drop(output);
+
+    let expected = SentFrame {
+        width: WIDTH,
+        height: HEIGHT,
+        format: PixelFormat::Rgba,
+        first_pixel: [10, 20, 30, 255],
+    };
+    let frames = frames.lock().unwrap();
+    assert_eq!(frames.len(), captured);
+    assert!(frames.iter().all(|frame| *frame == expected));
+}
@@ -0,0 +1,113 @@
+mod support;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use xtal::runtime::recorder::RecordingOptions;
+use xtal::runtime::recording::{self, RecordingState, RecordingTarget};
+use xtal::time::frame_clock;
+
+const FPS: f32 = 30.0;
+const SECONDS: f32 = 0.5;
+const SIZE: u32 = 16;
+
+#[test]
+fn offline_recording_captures_exactly_fps_times_duration_frames() {
+    if !support::gpu_tests_enabled() {
+        eprintln!(
+            "Skipping offline recording test. Set XTAL_RUN_GPU_TESTS=1 to run."
+        );
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let device = Arc::new(device);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offline-recording-source"),
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let dir = std::env::temp_dir().join("xtal-recording-tests");
+    std::fs::create_dir_all(&dir).expect("create recording temp dir");
+    let output_path = dir.join("offline.mp4");
+
+    frame_clock::set_fps(FPS);
+    frame_clock::set_render_fps(None);
+    frame_clock::set_paused(false);
+    frame_clock::reset();
+
+    let mut state = RecordingState::default();
+    state
+        .start_offline_recording(
+            RecordingTarget {
+                device: device.clone(),
+                output_path: &output_path.to_string_lossy(),
+                width: SIZE,
+                height: SIZE,
+                fps: FPS,
+                source_format: wgpu::TextureFormat::Rgba8Unorm,
+                options: &RecordingOptions::default(),
+            },
+            SECONDS,
+        )
+        .expect("start offline recording");
+    assert!(frame_clock::offline());
+    let start_seconds = frame_clock::elapsed_seconds();
+
+    // Wall-clock time never moves, yet every tick still renders a frame
+    let now = Instant::now();
+    let mut ticks = 0;
+    while !state.offline_complete() {
+        ticks += 1;
+        assert!(ticks <= 100, "offline recording never completed");
+
+        let tick = frame_clock::tick(now);
+        assert!(tick.should_render);
+        assert_eq!(tick.frames_advanced, 1);
+
+        let recorder = state.recorder.as_mut().expect("active recorder");
+        let mut encoder = device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        assert!(recorder.capture_surface_frame(&mut encoder, &texture));
+        queue.submit([encoder.finish()]);
+        recorder.on_submitted();
+    }
+
+    let expected = recording::offline_frame_count(FPS, SECONDS);
+    assert_eq!(expected, 15);
+    assert_eq!(state.recorder.as_ref().unwrap().frames_captured(), expected);
+    let recorded_seconds = frame_clock::elapsed_seconds() - start_seconds;
+    assert!((recorded_seconds - SECONDS).abs() < 0.000_1);
+
+    state.stop_recording().expect("stop offline recording");
+    assert!(!frame_clock::offline());
+
+    let mut session_id = String::new();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let outcome = loop {
+        if let Some(outcome) = state.poll_finalize(&mut session_id) {
+            break outcome;
+        }
+        assert!(Instant::now() < deadline, "timed out finalizing recording");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert!(!outcome.is_error);
+    assert!(
+        outcome
+            .message
+            .contains(&format!("{} frames captured", expected)),
+        "{}",
+        outcome.message
+    );
+}
@@ -0,0 +1,18 @@
+use serial_test::serial;
+use xtal::prelude::test::{TestHubBuilder, TestTiming, assert_close};
+use xtal::prelude::{Animation, ControlHub};
+
+#[test]
+#[serial]
+fn test_prelude_reexports_resolve() {
+    let mut hub: ControlHub<TestTiming> = TestHubBuilder::new()
+        .bpm(120.0)
+        .script("lfo:\n  type: triangle\n  beats: 2\n")
+        .build();
+    hub.advance_to_beat(0.5);
+    assert_close(hub.get("lfo"), 0.5, "lfo");
+
+    let animation = Animation::new(TestTiming::new(120.0, 60.0));
+    animation.advance_to_beat(0.25);
+    assert_close(animation.tri(1.0), 0.5, "tri");
+}
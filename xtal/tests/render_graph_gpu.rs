@@ -0,0 +1,1093 @@
+mod support;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use xtal::frame::Frame;
+use xtal::gpu::{
+    CompiledGraph, GraphError, fit_viewport, scaled_size,
+    supported_msaa_samples,
+};
+use xtal::graph::{BufferHandle, DepthOptions, GraphBuilder, Workgroups};
+use xtal::instancing::{Instance, Instances};
+use xtal::mesh::Mesh;
+use xtal::runtime::stills::{StillFormat, StillReadback};
+use xtal::uniforms::UniformBanks;
+
+const FULLSCREEN_WGSL: &str = r#"
+struct Params {
+    a: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@vertex
+fn vs_main(@location(0) position: vec2f) -> @builtin(position) vec4f {
+    return vec4f(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return vec4f(params.a.x, 0.0, 0.0, 1.0);
+}
+"#;
+
+const DOUBLE_INDEX_COUNT: u32 = 64;
+
+const DOUBLE_INDEX_WGSL: &str = r#"
+@group(1) @binding(0)
+var<storage, read_write> values: array<u32>;
+
+@compute @workgroup_size(8, 1, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x < arrayLength(&values) {
+        values[id.x] = id.x * 2u;
+    }
+}
+"#;
+
+const INSTANCE_COLUMNS_WGSL: &str = r#"
+struct Params {
+    a: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+// Squeeze the quad into one of four columns picked by instance index
+@vertex
+fn vs_main(
+    @location(0) position: vec2f,
+    @builtin(instance_index) instance: u32,
+) -> @builtin(position) vec4f {
+    let x = ((position.x * 0.5 + 0.5) + f32(instance)) * 0.5 - 1.0;
+    return vec4f(x, position.y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return vec4f(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+const DEPTH_COLOR_WGSL: &str = r#"
+@vertex
+fn vs_main(@location(0) position: vec3f) -> @builtin(position) vec4f {
+    return vec4f(position, 1.0);
+}
+
+// Near geometry is green and far geometry red
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    if position.z < 0.5 {
+        return vec4f(0.0, 1.0, 0.0, 1.0);
+    }
+    return vec4f(1.0, 0.0, 0.0, 1.0);
+}
+"#;
+
+const INSTANCED_QUADS_WGSL: &str = r#"
+struct VsOut {
+    @builtin(position) position: vec4f,
+    @location(0) color: vec4f,
+}
+
+@vertex
+fn vs_main(
+    @location(0) corner: vec2f,
+    @location(1) position: vec2f,
+    @location(2) size: vec2f,
+    @location(4) color: vec4f,
+) -> VsOut {
+    var out: VsOut;
+    out.position = vec4f(position + corner * size, 0.0, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VsOut) -> @location(0) vec4f {
+    return in.color;
+}
+"#;
+
+// `missing_color` is undeclared: line 3, column 12
+const BROKEN_WGSL: &str = "@fragment
+fn fs_main() -> @location(0) vec4f {
+    return missing_color;
+}
+";
+
+fn solid_color_wgsl(color: &str) -> String {
+    format!(
+        r#"
+@vertex
+fn vs_main(@location(0) position: vec2f) -> @builtin(position) vec4f {{
+    return vec4f(position, 0.0, 1.0);
+}}
+
+@fragment
+fn fs_main() -> @location(0) vec4f {{
+    return vec4f({color});
+}}
+"#
+    )
+}
+
+#[test]
+fn scaled_size_rounds_and_never_drops_below_one_pixel() {
+    assert_eq!(scaled_size([900, 600], 1.0), [900, 600]);
+    assert_eq!(scaled_size([900, 600], 0.5), [450, 300]);
+    assert_eq!(scaled_size([901, 601], 0.5), [451, 301]);
+    assert_eq!(scaled_size([900, 600], 2.0), [1800, 1200]);
+    assert_eq!(scaled_size([3, 1], 0.1), [1, 1]);
+}
+
+#[test]
+fn fit_viewport_letterboxes_and_pillarboxes() {
+    let close = |actual: (f32, f32, f32, f32), expected: [f32; 4]| {
+        let actual = [actual.0, actual.1, actual.2, actual.3];
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| (actual - expected).abs() < 0.001)
+    };
+    assert!(close(
+        fit_viewport(1920, 1080, 1000, 1000),
+        [0.0, 218.75, 1000.0, 562.5]
+    ));
+    assert!(close(
+        fit_viewport(1080, 1920, 1000, 500),
+        [359.375, 0.0, 281.25, 500.0]
+    ));
+    assert!(close(fit_viewport(16, 9, 32, 18), [0.0, 0.0, 32.0, 18.0]));
+}
+
+#[test]
+fn half_scale_node_allocates_half_size_texture() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let shader = support::write_shader("scale.wgsl", FULLSCREEN_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let half = graph.texture2d();
+    let full = graph.texture2d();
+
+    graph
+        .render()
+        .shader(shader.clone())
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .scale(0.5)
+        .to(half);
+
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .read(half)
+        .to(full);
+
+    graph.present(full);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile scaled graph");
+
+    compiled.resize(&device, [640, 480]);
+    assert_eq!(compiled.texture_size(half), Some([320, 240]));
+    assert_eq!(compiled.texture_size(full), Some([640, 480]));
+
+    compiled.resize(&device, [101, 51]);
+    assert_eq!(compiled.texture_size(half), Some([51, 26]));
+    assert_eq!(compiled.texture_size(full), Some([101, 51]));
+}
+
+#[test]
+fn compute_node_writes_storage_buffer() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    assert_compute_doubles_index(
+        "double_index.wgsl",
+        |graph, shader, values| {
+            graph
+                .compute()
+                .shader(shader)
+                .read_write(values)
+                .workgroups(DOUBLE_INDEX_COUNT / 8, 1, 1)
+                .dispatch();
+        },
+    );
+}
+
+#[test]
+fn add_compute_pass_writes_storage_buffer() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    assert_compute_doubles_index(
+        "double_index_pass.wgsl",
+        |graph, shader, values| {
+            graph.add_compute_pass(
+                shader,
+                Workgroups::Fixed([DOUBLE_INDEX_COUNT / 8, 1, 1]),
+                [values.into()],
+            );
+        },
+    );
+}
+
+// Run the compute pass `declare` adds over a storage buffer and check that it
+// wrote each element's doubled index
+fn assert_compute_doubles_index(
+    shader_name: &str,
+    declare: impl FnOnce(&mut GraphBuilder, PathBuf, BufferHandle),
+) {
+    let size = (DOUBLE_INDEX_COUNT as u64) * 4;
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader = support::write_shader(shader_name, DOUBLE_INDEX_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let values = graph.buffer(size);
+    declare(&mut graph, shader, values);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile compute graph");
+
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("compute-test-target"),
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut frame = Frame::offscreen(&device, queue.clone(), view);
+
+    compiled
+        .execute(&device, &mut frame, &uniforms, [4, 4])
+        .expect("execute compute graph");
+
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("compute-test-readback"),
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let source = compiled.buffer(values).expect("declared buffer");
+    frame
+        .encoder()
+        .copy_buffer_to_buffer(source, 0, &readback, 0, size);
+    frame.submit();
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| {
+        result.expect("map readback buffer");
+    });
+    device
+        .poll(wgpu::PollType::Wait)
+        .expect("wait for readback");
+
+    let data = slice.get_mapped_range();
+    let words = data
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    let expected = (0..DOUBLE_INDEX_COUNT)
+        .map(|index| index * 2)
+        .collect::<Vec<_>>();
+    assert_eq!(words, expected);
+}
+
+#[test]
+fn instance_count_follows_control_without_rebuild() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader =
+        support::write_shader("instance_columns.wgsl", INSTANCE_COLUMNS_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let columns = graph.texture2d();
+
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .instances_from("count")
+        .to(columns);
+
+    graph.present(columns);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile instanced graph");
+
+    let lit_columns = |compiled: &mut CompiledGraph, count: f32| -> [bool; 4] {
+        compiled.update_instance_counts(|name| {
+            assert_eq!(name, "count");
+            count
+        });
+        let pixels = render_pixels(&device, &queue, compiled, &uniforms);
+        std::array::from_fn(|column| pixels[column][0] > 127)
+    };
+
+    assert_eq!(lit_columns(&mut compiled, 2.0), [true, true, false, false]);
+    assert_eq!(compiled.instance_count("render_0"), Some(2));
+    assert_eq!(lit_columns(&mut compiled, 0.0), [false; 4]);
+    assert_eq!(lit_columns(&mut compiled, 3.4), [true, true, true, false]);
+    assert_eq!(lit_columns(&mut compiled, -1.0), [false; 4]);
+}
+
+// Full-height quad spanning `left..right` in clip space at depth `z`
+fn depth_quad(left: f32, right: f32, z: f32) -> Mesh {
+    Mesh::positions3d([
+        [left, -1.0, z],
+        [right, -1.0, z],
+        [right, 1.0, z],
+        [left, -1.0, z],
+        [right, 1.0, z],
+        [left, 1.0, z],
+    ])
+}
+
+#[test]
+fn depth_test_hides_far_primitive_behind_near_one() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader = support::write_shader("depth_color.wgsl", DEPTH_COLOR_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    // The near quad covers the left half and is drawn first, so without a
+    // depth test the far quad would paint over it
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let scene = graph.texture2d();
+    graph
+        .render()
+        .shader(shader)
+        .mesh(depth_quad(-1.0, 0.0, 0.25))
+        .mesh(depth_quad(-1.0, 1.0, 0.75))
+        .read(params)
+        .depth(DepthOptions::default())
+        .to(scene);
+    graph.present(scene);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile depth graph");
+
+    let green = [0, 255, 0, 255];
+    let red = [255, 0, 0, 255];
+    let pixels = render_pixels(&device, &queue, &mut compiled, &uniforms);
+    assert_eq!(pixels, [green, green, red, red]);
+
+    // Depth is cleared every frame, so the result is stable
+    let pixels = render_pixels(&device, &queue, &mut compiled, &uniforms);
+    assert_eq!(pixels, [green, green, red, red]);
+}
+
+#[test]
+fn later_node_tests_against_shared_depth_buffer() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader = support::write_shader("depth_shared.wgsl", DEPTH_COLOR_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let near = graph.texture2d();
+    let far = graph.texture2d();
+    graph
+        .render()
+        .shader(shader.clone())
+        .mesh(depth_quad(-1.0, 0.0, 0.25))
+        .read(params)
+        .depth(DepthOptions::default())
+        .to(near);
+    // Same size target, so this node loads the depth the first one wrote
+    graph
+        .render()
+        .shader(shader)
+        .mesh(depth_quad(-1.0, 1.0, 0.75))
+        .read(params)
+        .depth(DepthOptions {
+            clear: None,
+            ..DepthOptions::default()
+        })
+        .to(far);
+    graph.present(far);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile shared depth graph");
+
+    let black = [0, 0, 0, 255];
+    let red = [255, 0, 0, 255];
+    let pixels = render_pixels(&device, &queue, &mut compiled, &uniforms);
+    assert_eq!(pixels, [black, black, red, red]);
+}
+
+#[test]
+fn instanced_quads_follow_instance_data() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader =
+        support::write_shader("instanced_quads.wgsl", INSTANCED_QUADS_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let instances = Instances::new();
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let quads = graph.texture2d();
+    graph
+        .add_instanced(&instances)
+        .shader(shader)
+        .read(params)
+        .to(quads);
+    graph.present(quads);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile instanced quad graph");
+
+    let colors = [
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0, 1.0],
+    ];
+    // A quad filling one column of the 4x1 target
+    let column_quad = |column: usize| {
+        let x = -0.75 + 0.5 * column as f32;
+        Instance::quad([x, 0.0], [0.5, 2.0], colors[column])
+    };
+    let render = |compiled: &mut CompiledGraph| {
+        compiled.update_instance_buffers(&device, &queue);
+        render_pixels(&device, &queue, compiled, &uniforms)
+    };
+
+    let black = [0, 0, 0, 255];
+    let red = [255, 0, 0, 255];
+    let green = [0, 255, 0, 255];
+    let blue = [0, 0, 255, 255];
+    let white = [255, 255, 255, 255];
+
+    instances.set([0, 2].map(column_quad));
+    assert_eq!(render(&mut compiled), [red, black, blue, black]);
+    assert_eq!(compiled.instance_count("render_0"), Some(2));
+
+    // Outgrows the first buffer
+    instances.set((0..4).map(column_quad));
+    assert_eq!(render(&mut compiled), [red, green, blue, white]);
+    assert_eq!(compiled.instance_count("render_0"), Some(4));
+
+    instances.set([]);
+    assert_eq!(render(&mut compiled), [black; 4]);
+    assert_eq!(compiled.instance_count("render_0"), Some(0));
+}
+
+// Execute the graph into a 4x1 surface and read back the presented texture
+fn render_pixels(
+    device: &wgpu::Device,
+    queue: &Arc<wgpu::Queue>,
+    compiled: &mut CompiledGraph,
+    uniforms: &UniformBanks,
+) -> [[u8; 4]; 4] {
+    let surface = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("instance-test-surface"),
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = surface.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut frame = Frame::offscreen(device, queue.clone(), view);
+
+    compiled
+        .execute(device, &mut frame, uniforms, [4, 1])
+        .expect("execute graph");
+
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("instance-test-readback"),
+        size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let source = compiled
+        .recording_source_texture()
+        .expect("present source texture");
+    frame.encoder().copy_texture_to_buffer(
+        source.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                rows_per_image: Some(1),
+            },
+        },
+        wgpu::Extent3d {
+            width: 4,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+    frame.submit();
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| {
+        result.expect("map readback buffer");
+    });
+    device
+        .poll(wgpu::PollType::Wait)
+        .expect("wait for readback");
+
+    let data = slice.get_mapped_range();
+    std::array::from_fn(|column| {
+        std::array::from_fn(|channel| data[column * 4 + channel])
+    })
+}
+
+#[test]
+fn failed_shader_reload_keeps_previous_pipeline() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader = support::write_shader(
+        "reload.wgsl",
+        &solid_color_wgsl("1.0, 0.0, 0.0, 1.0"),
+    );
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let output = graph.texture2d();
+
+    graph
+        .render()
+        .shader(shader.clone())
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .to(output);
+
+    graph.present(output);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile reload graph");
+
+    let red = [255, 0, 0, 255];
+    let green = [0, 255, 0, 255];
+    assert_eq!(
+        render_pixels(&device, &queue, &mut compiled, &uniforms)[0],
+        red
+    );
+
+    std::fs::write(&shader, "@fragment fn fs_main( {").unwrap();
+    let err = compiled
+        .reload_shader(&device, uniforms.bind_group_layout(), &shader)
+        .expect_err("broken shader should fail to reload");
+    assert_eq!(err.path, shader);
+    assert_eq!(
+        render_pixels(&device, &queue, &mut compiled, &uniforms)[0],
+        red
+    );
+
+    std::fs::write(&shader, solid_color_wgsl("0.0, 1.0, 0.0, 1.0")).unwrap();
+    let reloaded = compiled
+        .reload_shader(&device, uniforms.bind_group_layout(), &shader)
+        .expect("fixed shader should reload");
+    assert_eq!(reloaded, 1);
+    assert_eq!(
+        render_pixels(&device, &queue, &mut compiled, &uniforms)[0],
+        green
+    );
+}
+
+#[test]
+fn broken_shader_compile_error_reports_path_and_location() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let shader = support::write_shader("broken.wgsl", BROKEN_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+
+    graph
+        .render()
+        .shader(shader.clone())
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .to_surface();
+
+    let result = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    );
+
+    let Err(GraphError::Shader(err)) = result else {
+        panic!("expected a shader error");
+    };
+    assert_eq!(err.path, shader);
+    assert!(!err.message.is_empty());
+    assert!(err.message.contains("missing_color"), "{}", err.message);
+    assert_eq!(err.line, Some(3));
+    assert_eq!(err.column, Some(12));
+}
+
+#[test]
+fn hdr_texture_capture_keeps_float_values() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader = support::write_shader(
+        "hdr_color.wgsl",
+        &solid_color_wgsl("2.0, 0.5, 0.25, 1.0"),
+    );
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let hdr = graph.texture2d_hdr();
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .to(hdr);
+    graph.present(hdr);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile hdr graph");
+
+    let surface = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("hdr-test-surface"),
+        size: wgpu::Extent3d {
+            width: 3,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = surface.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut frame = Frame::offscreen(&device, queue.clone(), view);
+    compiled
+        .execute(&device, &mut frame, &uniforms, [3, 2])
+        .expect("execute hdr graph");
+
+    let format = compiled.recording_source_format().expect("source format");
+    assert_eq!(format, wgpu::TextureFormat::Rgba16Float);
+    let readback = StillReadback::copy(
+        &device,
+        frame.encoder(),
+        compiled.recording_source_texture().expect("source texture"),
+        format,
+    )
+    .expect("copy hdr texture");
+    let submission_index = frame.submit();
+    let still = readback
+        .read(&device, submission_index)
+        .expect("read hdr texture");
+
+    assert_eq!((still.width, still.height), (3, 2));
+    assert_eq!(still.rgba_f32(), [2.0, 0.5, 0.25, 1.0].repeat(6));
+
+    let dir = std::env::temp_dir().join("xtal-gpu-tests");
+    let png_path = dir.join("hdr.png");
+    still
+        .write(&png_path, StillFormat::Png16)
+        .expect("write 16-bit png");
+    let mut reader = png::Decoder::new(std::io::BufReader::new(
+        std::fs::File::open(&png_path).expect("open png"),
+    ))
+    .read_info()
+    .expect("read png info");
+    let mut png_data = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut png_data).expect("decode png");
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    assert_eq!(
+        &png_data[..8],
+        &[0xff, 0xff, 0x80, 0x00, 0x40, 0x00, 0xff, 0xff]
+    );
+
+    let exr_path = dir.join("hdr.exr");
+    still.write(&exr_path, StillFormat::Exr).expect("write exr");
+    let exr = std::fs::read(&exr_path).expect("read exr");
+    assert_eq!(&exr[0..4], &[0x76, 0x2f, 0x31, 0x01]);
+    let image =
+        exr::prelude::read_first_flat_layer_from_file(&exr_path).expect("read");
+    let channels = &image.layer_data.channel_data.list;
+    let red = channels
+        .iter()
+        .find(|channel| channel.name.eq("R"))
+        .expect("red channel");
+    // Red stays unclamped above 1.0
+    assert_eq!(red.sample_data.value_by_flat_index(0).to_f32(), 2.0);
+}
+
+#[test]
+fn export_size_sets_capture_size_and_letterboxes_preview() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader =
+        support::write_shader("export_white.wgsl", &solid_color_wgsl("1.0"));
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .to_surface();
+
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        format,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile export graph");
+    compiled.set_export_size(Some([8, 4]));
+
+    // A square window: the 2:1 export leaves bars above and below
+    let window = [4, 4];
+    let surface = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("export-test-surface"),
+        size: wgpu::Extent3d {
+            width: window[0],
+            height: window[1],
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = surface.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut frame = Frame::offscreen(&device, queue.clone(), view);
+    compiled
+        .execute(&device, &mut frame, &uniforms, window)
+        .expect("execute export graph");
+
+    let capture = StillReadback::copy(
+        &device,
+        frame.encoder(),
+        compiled.recording_source_texture().expect("export target"),
+        compiled.recording_source_format().expect("export format"),
+    )
+    .expect("copy export target");
+    let preview =
+        StillReadback::copy(&device, frame.encoder(), &surface, format)
+            .expect("copy surface");
+    let submission_index = frame.submit();
+
+    let png_path = std::env::temp_dir()
+        .join("xtal-gpu-tests")
+        .join("export.png");
+    capture
+        .read(&device, submission_index.clone())
+        .expect("read export target")
+        .write(&png_path, StillFormat::Png8)
+        .expect("write capture");
+    let reader = png::Decoder::new(std::io::BufReader::new(
+        std::fs::File::open(&png_path).expect("open png"),
+    ))
+    .read_info()
+    .expect("read png info");
+    assert_eq!((reader.info().width, reader.info().height), (8, 4));
+
+    let preview = preview
+        .read(&device, submission_index)
+        .expect("read surface");
+    let red_column: Vec<u8> = preview
+        .pixels
+        .chunks_exact(4)
+        .step_by(window[0] as usize)
+        .map(|px| px[0])
+        .collect();
+    assert_eq!(red_column, [0, 255, 255, 0]);
+}
+
+#[test]
+fn msaa_surface_node_resolves_antialiased_edges() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let adapter = support::headless_adapter();
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let shader =
+        support::write_shader("msaa_white.wgsl", &solid_color_wgsl("1.0"));
+    let uniforms = UniformBanks::new(&device, 1);
+
+    // Lower-left half of the target, split along the diagonal
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::positions2d([[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0]]))
+        .read(params)
+        .to_surface();
+
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        format,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile msaa graph");
+
+    let samples = supported_msaa_samples(&adapter, format, 4);
+    assert_eq!(samples, 4);
+    compiled
+        .set_msaa_samples(&device, uniforms.bind_group_layout(), samples)
+        .expect("rebuild pipelines for msaa");
+
+    let size = 8;
+    let surface = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("msaa-test-surface"),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = surface.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut frame = Frame::offscreen(&device, queue.clone(), view);
+    compiled
+        .execute(&device, &mut frame, &uniforms, [size, size])
+        .expect("execute msaa graph");
+
+    let readback =
+        StillReadback::copy(&device, frame.encoder(), &surface, format)
+            .expect("copy surface");
+    let submission_index = frame.submit();
+    let still = readback
+        .read(&device, submission_index)
+        .expect("read surface");
+
+    let red: Vec<u8> = still.pixels.chunks_exact(4).map(|px| px[0]).collect();
+    let at = |x: u32, y: u32| red[(y * size + x) as usize];
+    // Fully inside and outside the triangle, then straddling its edge
+    assert_eq!(at(0, size - 1), 255);
+    assert_eq!(at(size - 1, 0), 0);
+    assert!(
+        red.iter().any(|&value| value > 0 && value < 255),
+        "expected partially covered edge pixels, got {:?}",
+        red
+    );
+}
+
+#[cfg(feature = "timing")]
+#[test]
+fn pass_timings_are_non_negative_and_sum_to_total() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping GPU graph test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let Some((device, queue)) =
+        support::headless_device_with_features(wgpu::Features::TIMESTAMP_QUERY)
+    else {
+        eprintln!("Skipping pass timing test: no timestamp query support.");
+        return;
+    };
+    let queue = Arc::new(queue);
+    let shader = support::write_shader("timing.wgsl", FULLSCREEN_WGSL);
+    let uniforms = UniformBanks::new(&device, 1);
+
+    let mut graph = GraphBuilder::new();
+    let params = graph.uniforms();
+    let first = graph.texture2d();
+    let second = graph.texture2d();
+
+    graph
+        .render()
+        .shader(shader.clone())
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .to(first);
+    graph
+        .render()
+        .shader(shader)
+        .mesh(Mesh::fullscreen_quad())
+        .read(params)
+        .read(first)
+        .to(second);
+    graph.present(second);
+
+    let mut compiled = CompiledGraph::compile(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8Unorm,
+        graph.build(),
+        uniforms.bind_group_layout(),
+    )
+    .expect("compile timed graph");
+
+    // Timings are read back a frame or more after they're recorded
+    let mut timings = None;
+    for _ in 0..4 {
+        render_pixels(&device, &queue, &mut compiled, &uniforms);
+        timings = compiled.take_pass_timings().or(timings);
+    }
+    let timings = timings.expect("pass timings after a few frames");
+
+    assert_eq!(timings.passes.len(), 2);
+    let sum = timings
+        .passes
+        .iter()
+        .map(|pass| {
+            assert!(pass.milliseconds >= 0.0, "{:?}", pass);
+            pass.milliseconds
+        })
+        .sum::<f32>();
+    assert!(timings.total_milliseconds >= 0.0);
+    // The total also spans the gap between passes
+    assert!(
+        sum <= timings.total_milliseconds * 1.01 + 0.001,
+        "passes sum to {}ms, total {}ms",
+        sum,
+        timings.total_milliseconds
+    );
+}
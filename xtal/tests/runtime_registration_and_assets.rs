@@ -13,6 +13,7 @@ mod demo {
         w: 640,
         h: 480,
         banks: 4,
+        ..SketchConfig::DEFAULT
     };
 
     pub struct DemoSketch;
@@ -38,6 +39,7 @@ mod image {
         w: 640,
         h: 480,
         banks: 4,
+        ..SketchConfig::DEFAULT
     };
 
     pub struct ImageSketch;
@@ -73,6 +75,32 @@ fn register_sketches_macro_builds_registry_with_categories() {
     assert!(!registry.categories()[1].enabled);
 }
 
+#[test]
+fn register_sketches_macro_lists_sketch_under_each_tag() {
+    let mut registry = register_sketches! {
+        {
+            title: "Main",
+            enabled: true,
+            sketches: [demo: ["audio", "feedback"], image]
+        },
+    }
+    .expect("macro should build runtime registry");
+
+    assert_eq!(registry.tags(), vec!["audio", "feedback"]);
+    assert_eq!(registry.sketches_with_tag("audio"), vec!["demo_p2"]);
+    assert_eq!(registry.sketches_with_tag("feedback"), vec!["demo_p2"]);
+
+    let by_tag = web_view::sketches_by_tag(&registry);
+    assert_eq!(by_tag.get("audio"), Some(&vec!["demo_p2".to_string()]));
+    assert_eq!(by_tag.get("feedback"), Some(&vec!["demo_p2".to_string()]));
+
+    registry.filter_by_tag("audio").expect("filter by tag");
+    assert_eq!(registry.sketch_names(), &["demo_p2"]);
+    assert_eq!(registry.categories()[0].sketches, vec!["demo_p2"]);
+    assert!(registry.get("image_p2").is_some());
+    assert!(registry.filter_by_tag("missing").is_err());
+}
+
 #[test]
 fn sketch_assets_resolves_default_and_custom_paths() {
     let assets = SketchAssets::from_file("src/sketches/demo.rs");
@@ -16,6 +16,7 @@ static TEST_CONFIG: SketchConfig = SketchConfig {
     w: 640,
     h: 480,
     banks: 4,
+    ..SketchConfig::DEFAULT
 };
 
 #[test]
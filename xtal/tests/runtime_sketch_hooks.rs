@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use xtal::graph::GraphBuilder;
+use xtal::runtime::app::replace_sketch;
+use xtal::sketch::Sketch;
+
+struct StreamingSketch {
+    torn_down: Arc<AtomicBool>,
+}
+
+impl Sketch for StreamingSketch {
+    fn setup(&self, _graph: &mut GraphBuilder) {}
+
+    fn on_exit(&mut self) {
+        self.torn_down.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn replacing_sketch_tears_down_the_old_one() {
+    let old_flag = Arc::new(AtomicBool::new(false));
+    let new_flag = Arc::new(AtomicBool::new(false));
+    let mut current: Box<dyn Sketch> = Box::new(StreamingSketch {
+        torn_down: old_flag.clone(),
+    });
+
+    replace_sketch(
+        &mut current,
+        Box::new(StreamingSketch {
+            torn_down: new_flag.clone(),
+        }),
+    );
+
+    assert!(old_flag.load(Ordering::SeqCst));
+    assert!(!new_flag.load(Ordering::SeqCst));
+}
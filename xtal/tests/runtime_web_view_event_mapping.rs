@@ -1,5 +1,8 @@
 use xtal::graph::GraphBuilder;
 use xtal::prelude::*;
+use xtal::runtime::recorder::{RecordingOptions, VideoCodec};
+use xtal::runtime::recording::PreRoll;
+use xtal::runtime::stills::{StillFormat, TimelapseInterval, TimelapseOptions};
 
 struct TestSketch;
 
@@ -16,6 +19,8 @@ static TEST_CONFIG: SketchConfig = SketchConfig {
     w: 640,
     h: 480,
     banks: 4,
+    description: Some("Press **T** to toggle the trails."),
+    ..SketchConfig::DEFAULT
 };
 
 #[test]
@@ -39,6 +44,14 @@ fn web_view_command_mapping_supports_perf_mode_and_window_events() {
         Some(RuntimeEvent::SetPerfMode(true))
     );
 
+    let hides_cursor =
+        web_view::parse_ui_message("{\"PerfModeHidesCursor\":false}")
+            .expect("parse perf mode hides cursor message");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&hides_cursor),
+        Some(RuntimeEvent::SetPerfModeHidesCursor(false))
+    );
+
     let monitor = web_view::parse_ui_message("{\"MonitorPreview\":true}")
         .expect("parse monitor preview message");
     assert_eq!(
@@ -119,6 +132,83 @@ fn web_view_command_mapping_supports_phase1_actions() {
         web_view::map_event_to_runtime_event(&mappings_enabled),
         Some(RuntimeEvent::SetMappingsEnabled(false))
     );
+
+    let midi_feedback_enabled =
+        web_view::parse_ui_message("{\"MidiFeedbackEnabled\":true}")
+            .expect("parse midi feedback enabled");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&midi_feedback_enabled),
+        Some(RuntimeEvent::SetMidiFeedbackEnabled(true))
+    );
+
+    let offline_recording =
+        web_view::parse_ui_message("{\"OfflineRecordingSeconds\":12.5}")
+            .expect("parse offline recording seconds");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&offline_recording),
+        Some(RuntimeEvent::SetOfflineRecordingSeconds(12.5))
+    );
+
+    let osc_smoothing =
+        web_view::parse_ui_message("{\"OscTransportSmoothing\":0.5}")
+            .expect("parse osc transport smoothing");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&osc_smoothing),
+        Some(RuntimeEvent::SetOscTransportSmoothing(0.5))
+    );
+
+    let record_audio = web_view::parse_ui_message("{\"RecordAudio\":true}")
+        .expect("parse record audio");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&record_audio),
+        Some(RuntimeEvent::SetRecordAudio(true))
+    );
+
+    let recording_options = web_view::parse_ui_message(
+        "{\"RecordingOptions\":{\"codec\":\"ProRes\",\"quality\":80}}",
+    )
+    .expect("parse recording options");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&recording_options),
+        Some(RuntimeEvent::SetRecordingOptions(RecordingOptions {
+            codec: VideoCodec::ProRes,
+            quality: 80,
+        }))
+    );
+
+    let pre_roll =
+        web_view::parse_ui_message("{\"RecordingPreRoll\":{\"Beats\":4.0}}")
+            .expect("parse recording pre-roll");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&pre_roll),
+        Some(RuntimeEvent::SetRecordingPreRoll(PreRoll::Beats(4.0)))
+    );
+
+    let toggle_timelapse = web_view::parse_ui_message("\"ToggleTimelapse\"")
+        .expect("parse toggle timelapse");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&toggle_timelapse),
+        Some(RuntimeEvent::ToggleTimelapse)
+    );
+
+    let timelapse_options = web_view::parse_ui_message(
+        "{\"TimelapseOptions\":{\"interval\":{\"Beats\":2.0},\"pattern\":\"{n}\"}}",
+    )
+    .expect("parse timelapse options");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&timelapse_options),
+        Some(RuntimeEvent::SetTimelapseOptions(TimelapseOptions {
+            interval: TimelapseInterval::Beats(2.0),
+            pattern: "{n}".to_string(),
+        }))
+    );
+
+    let still_format = web_view::parse_ui_message("{\"StillFormat\":\"Exr\"}")
+        .expect("parse still format");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&still_format),
+        Some(RuntimeEvent::SetStillFormat(StillFormat::Exr))
+    );
 }
 
 #[test]
@@ -198,6 +288,42 @@ fn web_view_command_mapping_supports_remaining_phase1_actions() {
         Some(RuntimeEvent::ChangeOscPort(9000))
     );
 
+    let change_osc_prefix =
+        web_view::parse_ui_message("{\"ChangeOscPrefix\":\"/xtal1\"}")
+            .expect("parse change osc prefix");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&change_osc_prefix),
+        Some(RuntimeEvent::ChangeOscPrefix("/xtal1".into()))
+    );
+
+    let randomize_seed = web_view::parse_ui_message(
+        "{\"RandomizeSeed\":\"18446744073709551615\"}",
+    )
+    .expect("parse randomize seed");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&randomize_seed),
+        Some(RuntimeEvent::SetRandomizeSeed(Some(u64::MAX)))
+    );
+    assert_eq!(
+        web_view::to_ui_message(&randomize_seed).unwrap(),
+        "{\"RandomizeSeed\":\"18446744073709551615\"}"
+    );
+    assert!(web_view::parse_ui_message("{\"RandomizeSeed\":42}").is_err());
+
+    let clear_seed = web_view::parse_ui_message("{\"RandomizeSeed\":null}")
+        .expect("parse cleared randomize seed");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&clear_seed),
+        Some(RuntimeEvent::SetRandomizeSeed(None))
+    );
+
+    let advance_seed = web_view::parse_ui_message("\"AdvanceRandomizeSeed\"")
+        .expect("parse advance randomize seed");
+    assert_eq!(
+        web_view::map_event_to_runtime_event(&advance_seed),
+        Some(RuntimeEvent::AdvanceRandomizeSeed)
+    );
+
     let open_os_dir = web_view::parse_ui_message("{\"OpenOsDir\":\"Cache\"}")
         .expect("parse open os dir");
     assert_eq!(
@@ -246,7 +372,7 @@ fn web_view_init_serializes_optional_sketch_catalog_in_camel_case() {
     let mut sketches_by_category = web_view::SketchesByCategory::new();
     sketches_by_category.insert("Main".to_string(), vec!["demo".to_string()]);
 
-    let event = web_view::Event::Init {
+    let event = web_view::Event::Init(Box::new(web_view::InitPayload {
         audio_device: String::new(),
         audio_devices: vec![],
         hrcc: false,
@@ -254,27 +380,40 @@ fn web_view_init_serializes_optional_sketch_catalog_in_camel_case() {
         is_light_theme: true,
         mappings_enabled: false,
         midi_clock_port: String::new(),
+        midi_feedback_enabled: false,
         midi_input_port: String::new(),
         midi_output_port: String::new(),
         midi_input_ports: vec![],
         midi_output_ports: vec![],
         monitor_preview_enabled: false,
+        offline_recording_seconds: 0.0,
         osc_port: 0,
+        osc_prefix: String::new(),
+        osc_transport_smoothing: 0.0,
+        perf_mode_hides_cursor: true,
+        record_audio: false,
+        recording_options: RecordingOptions::default(),
+        recording_pre_roll: PreRoll::default(),
         sketches_by_category,
+        sketches_by_tag: Default::default(),
         sketch_catalog: Some(vec![web_view::SketchCatalogCategory {
             title: "Main".to_string(),
             enabled: true,
             sketches: vec!["demo".to_string()],
+            descriptions: Default::default(),
         }]),
         sketch_name: "demo".to_string(),
+        still_format: StillFormat::Png16,
+        timelapse_options: TimelapseOptions::default(),
         transition_time: 4.0,
         user_data_dir: String::new(),
         videos_dir: String::new(),
-    };
+    }));
 
     let json = web_view::to_ui_message(&event).expect("serialize init event");
     assert!(json.contains("\"sketchesByCategory\""));
     assert!(json.contains("\"sketchCatalog\""));
+    assert!(json.contains("\"stillFormat\":\"Png16\""));
     assert!(json.contains("\"title\":\"Main\""));
 }
 
@@ -296,6 +435,13 @@ fn web_view_helpers_use_registry_categories() {
     assert_eq!(catalog[0].title, "Main");
     assert!(catalog[0].enabled);
     assert_eq!(catalog[0].sketches, vec!["phase7_test"]);
+    assert_eq!(
+        catalog[0]
+            .descriptions
+            .get("phase7_test")
+            .map(String::as_str),
+        TEST_CONFIG.description
+    );
     assert_eq!(catalog[1].title, "Hidden");
     assert!(!catalog[1].enabled);
 
@@ -307,3 +453,37 @@ fn web_view_helpers_use_registry_categories() {
     );
     assert!(!sketches_by_category.contains_key("Hidden"));
 }
+
+#[test]
+fn web_view_load_sketch_round_trips_config_description() {
+    let event =
+        web_view::Event::LoadSketch(Box::new(web_view::LoadSketchPayload {
+            bpm: TEST_CONFIG.bpm,
+            bypassed: Default::default(),
+            controls: vec![],
+            description: TEST_CONFIG.description.map(str::to_string),
+            display_name: TEST_CONFIG.display_name.to_string(),
+            fps: TEST_CONFIG.fps,
+            mappings: Default::default(),
+            paused: false,
+            perf_mode: false,
+            sketch_name: TEST_CONFIG.name.to_string(),
+            sketch_width: TEST_CONFIG.w as i32,
+            sketch_height: TEST_CONFIG.h as i32,
+            snapshot_slots: vec![],
+            snapshot_sequence_enabled: false,
+            tap_tempo_enabled: false,
+            exclusions: vec![],
+        }));
+
+    let json = web_view::to_ui_message(&event).expect("serialize load sketch");
+    assert!(
+        json.contains("\"description\":\"Press **T** to toggle the trails.\"")
+    );
+
+    let parsed = web_view::parse_ui_message(&json).expect("parse load sketch");
+    let web_view::Event::LoadSketch(sketch) = parsed else {
+        panic!("expected LoadSketch, got {:?}", parsed);
+    };
+    assert_eq!(sketch.description.as_deref(), TEST_CONFIG.description);
+}
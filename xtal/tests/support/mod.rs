@@ -9,3 +9,48 @@ pub fn gpu_tests_enabled() -> bool {
         "1" | "true" | "yes" | "on"
     )
 }
+
+#[allow(dead_code)]
+pub fn headless_device() -> (wgpu::Device, wgpu::Queue) {
+    headless_device_with_features(wgpu::Features::empty())
+        .expect("expected a device from the headless adapter")
+}
+
+/// A headless device with whichever of `features` the adapter supports,
+/// `None` if it supports none of them
+#[allow(dead_code)]
+pub fn headless_device_with_features(
+    features: wgpu::Features,
+) -> Option<(wgpu::Device, wgpu::Queue)> {
+    let adapter = headless_adapter();
+    let supported = adapter.features() & features;
+    if supported.is_empty() && !features.is_empty() {
+        return None;
+    }
+
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        required_features: supported,
+        ..Default::default()
+    }))
+    .ok()
+}
+
+#[allow(dead_code)]
+pub fn headless_adapter() -> wgpu::Adapter {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::LowPower,
+        force_fallback_adapter: true,
+        compatible_surface: None,
+    }))
+    .expect("expected a headless adapter for GPU tests")
+}
+
+#[allow(dead_code)]
+pub fn write_shader(name: &str, source: &str) -> std::path::PathBuf {
+    let dir = env::temp_dir().join("xtal-gpu-tests");
+    std::fs::create_dir_all(&dir).expect("create shader temp dir");
+    let path = dir.join(name);
+    std::fs::write(&path, source).expect("write test shader");
+    path
+}
@@ -0,0 +1,73 @@
+mod support;
+
+use std::sync::Arc;
+
+use xtal::frame::Frame;
+use xtal::runtime::stills::StillReadback;
+use xtal::text::{Text, TextRenderer};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 24;
+
+#[test]
+fn text_is_drawn_inside_its_layout_bounds() {
+    if !support::gpu_tests_enabled() {
+        eprintln!("Skipping text test. Set XTAL_RUN_GPU_TESTS=1 to run.");
+        return;
+    }
+
+    let (device, queue) = support::headless_device();
+    let queue = Arc::new(queue);
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("text-test-target"),
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut renderer = TextRenderer::new(&device, &queue, format);
+    let mut frame = Frame::offscreen(&device, queue.clone(), view);
+    // 5x7 glyphs at twice their size: 4 glyphs of 10 px plus 3 gaps of 2 px
+    frame.draw_text(Text::new("xtal").position([4.0, 6.0]).size(14.0));
+    renderer.render(&device, &queue, &mut frame, [WIDTH, HEIGHT]);
+    assert!(!frame.has_text());
+
+    let readback =
+        StillReadback::copy(&device, frame.encoder(), &target, format)
+            .expect("copy text target");
+    let submission_index = frame.submit();
+    let still = readback
+        .read(&device, submission_index)
+        .expect("read text target");
+
+    let alpha =
+        |x: u32, y: u32| still.pixels[((y * WIDTH + x) * 4 + 3) as usize];
+    let inside = |x: u32, y: u32| (4..50).contains(&x) && (6..20).contains(&y);
+
+    let lit_inside = (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .filter(|&(x, y)| inside(x, y) && alpha(x, y) > 127)
+        .count();
+    assert!(lit_inside > 40, "expected glyph pixels, got {}", lit_inside);
+
+    // Nothing bleeds more than a pixel past the text's bounds
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let near = (3..51).contains(&x) && (5..21).contains(&y);
+            if !near {
+                assert_eq!(alpha(x, y), 0, "unexpected pixel at ({x}, {y})");
+            }
+        }
+    }
+}
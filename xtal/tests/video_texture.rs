@@ -0,0 +1,79 @@
+#![cfg(feature = "video")]
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use xtal::video::VideoTexture;
+
+const CLIP_FPS: f32 = 10.0;
+
+// One second of ffmpeg's test pattern, which draws a changing frame counter
+fn write_clip() -> PathBuf {
+    let dir = std::env::temp_dir().join("xtal-video-tests");
+    std::fs::create_dir_all(&dir).expect("create video temp dir");
+    let path = dir.join("testsrc.mp4");
+
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-hide_banner", "-loglevel", "error"])
+        .args(["-f", "lavfi", "-i", "testsrc=size=32x16:rate=10"])
+        .args(["-t", "1", "-c:v", "mpeg4", "-pix_fmt", "yuv420p"])
+        .arg(&path)
+        .status()
+        .expect("run ffmpeg");
+    assert!(status.success(), "ffmpeg failed to write test clip");
+
+    path
+}
+
+fn wait_for_frame(video: &mut VideoTexture, clock_frame: u32) -> Vec<u8> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        if let Some(frame) = video.advance(clock_frame, CLIP_FPS) {
+            return frame;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for clock frame {}",
+            clock_frame
+        );
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn video_frames_advance_with_the_clock_and_loop() {
+    let mut video = VideoTexture::open(write_clip()).expect("open test clip");
+    let info = video.info();
+    assert_eq!((info.width, info.height), (32, 16));
+    assert!((info.fps - CLIP_FPS).abs() < 0.01);
+    assert_eq!(info.frames, Some(10));
+
+    // A 60fps sketch clock shows the 10fps clip's frame 1 at frame 6
+    assert_eq!(video.frame_index_at(6, 60.0), 1);
+
+    assert_eq!(video.frame(), None);
+    let first = wait_for_frame(&mut video, 0);
+    assert_eq!(first.len(), 32 * 16 * 4);
+    assert_eq!(video.frame(), Some(0));
+    assert!(video.advance(0, CLIP_FPS).is_none());
+
+    let later = wait_for_frame(&mut video, 5);
+    assert_ne!(first, later, "expected the picture to change");
+    assert_eq!(video.frame(), Some(5));
+
+    // One frame past the end of the one second clip, decoding wraps around
+    // to its first frame
+    let looped = wait_for_frame(&mut video, 10);
+    assert_eq!(video.frame(), Some(0));
+    assert_eq!(looped, first);
+
+    let looped = wait_for_frame(&mut video, 25);
+    assert_eq!(looped.len(), first.len());
+    assert_eq!(video.frame(), Some(5));
+
+    // Rewinding the clock restarts from the first frame
+    let rewound = wait_for_frame(&mut video, 0);
+    assert_eq!(rewound, first);
+    assert_eq!(video.frame(), Some(0));
+}